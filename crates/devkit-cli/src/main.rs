@@ -4,9 +4,13 @@
 //! Configure features via .dev/config.toml - no Rust code required!
 
 use anyhow::Result;
-use clap::{Parser, Subcommand};
-use devkit_core::{AppContext, ExtensionRegistry, MenuItem};
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand};
+use devkit_core::favorites::Favorites;
+use devkit_core::history::{HistoryEntry, HistorySource};
+use devkit_core::palette::{palette, PaletteEntry};
+use devkit_core::{AppContext, ExtensionRegistry};
 use std::process::ExitCode;
+use std::time::Instant;
 
 #[derive(Parser)]
 #[command(name = "dev")]
@@ -17,6 +21,10 @@ struct Cli {
     #[arg(short, long, global = true)]
     quiet: bool,
 
+    /// Start even if some external extensions fail to load or are incompatible
+    #[arg(long, global = true)]
+    skip_broken: bool,
+
     #[command(subcommand)]
     command: Option<Commands>,
 }
@@ -30,26 +38,91 @@ enum Commands {
         /// Run in parallel where possible
         #[arg(long)]
         parallel: bool,
+        /// Most commands to run at once (implies --parallel; defaults to the number of CPUs)
+        #[arg(short, long)]
+        jobs: Option<usize>,
         /// Only run for specific packages
         #[arg(short, long)]
         package: Vec<String>,
+        /// Only run for packages changed since --base, and their dependents
+        #[arg(long)]
+        affected: bool,
+        /// Git ref to compare against for --affected (defaults to [git] default_pr_base)
+        #[arg(long, requires = "affected")]
+        base: Option<String>,
         /// List all available commands
         #[arg(long)]
         list: bool,
+        /// Run commands even when their inputs are unchanged
+        #[arg(long)]
+        force: bool,
     },
 
-    /// Docker operations (if enabled)
-    #[cfg(feature = "docker")]
-    Docker {
+    /// Run a [pipeline.<name>] from .dev/config.toml, or list pipelines
+    Pipeline {
+        /// Pipeline to run
+        name: Option<String>,
+        /// Run every step even after one fails
+        #[arg(long)]
+        keep_going: bool,
+    },
+
+    /// Run and compare benchmarks (if enabled)
+    #[cfg(feature = "benchmark")]
+    Bench {
         #[command(subcommand)]
-        action: DockerAction,
+        action: Option<BenchAction>,
+
+        /// Fail if any benchmark regressed against the baseline
+        #[arg(long)]
+        check: bool,
+
+        /// Allowed slowdown in percent (defaults to [bench] threshold)
+        #[arg(long, requires = "check")]
+        threshold: Option<f64>,
+
+        /// Check against results recorded for a git ref instead of the baseline file
+        #[arg(long, requires = "check")]
+        against: Option<String>,
+
+        /// Save the results as the new baseline
+        #[arg(long, conflicts_with = "check")]
+        save_baseline: bool,
+
+        /// Only run [cmd.bench] for these packages
+        #[arg(long, short)]
+        package: Vec<String>,
+
+        /// [cmd.bench] variant to run
+        #[arg(long)]
+        variant: Option<String>,
     },
 
-    /// Database operations (if enabled)
-    #[cfg(feature = "database")]
-    Database {
+    /// Run tests and collect coverage (if enabled)
+    #[cfg(feature = "test")]
+    Test {
         #[command(subcommand)]
-        action: DbAction,
+        action: Option<TestAction>,
+
+        /// Only run for these packages
+        #[arg(long, short, global = true)]
+        package: Vec<String>,
+
+        /// Write a test report per package (junit)
+        #[arg(long)]
+        report: Option<devkit_ext_test::ReportFormat>,
+
+        /// Only run one shard of the suite (e.g. 2/4)
+        #[arg(long)]
+        shard: Option<devkit_ext_test::Shard>,
+
+        /// Only test packages changed since --base, and their dependents
+        #[arg(long)]
+        affected: bool,
+
+        /// Git ref to compare against for --affected (defaults to [git] default_pr_base)
+        #[arg(long, requires = "affected")]
+        base: Option<String>,
     },
 
     /// Dependency management (if enabled)
@@ -67,6 +140,91 @@ enum Commands {
         shell: clap_complete::Shell,
     },
 
+    /// Check .dev/config.toml and package dev.toml files (same as `config validate`)
+    Validate(ValidateArgs),
+
+    /// Check and repair .dev/config.toml
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+
+    /// Generate editor run configurations from package commands
+    Export {
+        /// Editor to export for
+        #[arg(value_enum)]
+        editor: ExportEditor,
+    },
+
+    /// Create something new in the workspace
+    New {
+        #[command(subcommand)]
+        action: NewAction,
+    },
+
+    /// List and stop background port forwards (k8s, remote and docker `--background`)
+    Forward {
+        #[command(subcommand)]
+        action: Option<ForwardAction>,
+    },
+
+    /// Run the [run] processes (or Procfile) together until one exits or Ctrl-C
+    Up {
+        /// Only run these processes
+        names: Vec<String>,
+        /// Restart every process when it exits, not only those set to restart
+        #[arg(long)]
+        restart: bool,
+    },
+
+    /// Run package dev commands in the background: start, stop, status and logs
+    Services {
+        #[command(subcommand)]
+        action: Option<ServicesAction>,
+    },
+
+    /// List the project's ports and the processes listening on them
+    Ports {
+        /// Stop the process listening on this port
+        #[arg(long, value_name = "PORT")]
+        kill: Option<u16>,
+    },
+
+    /// Wait until services, ports, URLs, containers or the database are ready
+    Wait {
+        /// [services] name, port, host:port, URL, container:<name> or db
+        #[arg(required = true)]
+        targets: Vec<String>,
+        /// Give up after this many seconds
+        #[arg(long, default_value_t = 60)]
+        timeout: u64,
+        /// Seconds between checks
+        #[arg(long, default_value_t = 1)]
+        interval: u64,
+    },
+
+    /// Project overview: git, services, migrations, dependencies and CI
+    Status,
+
+    /// Check that the tools this project needs are installed
+    Doctor {
+        /// Install missing tools without asking
+        #[arg(long)]
+        fix: bool,
+    },
+
+    /// Show or install the runtimes pinned in .mise.toml/.tool-versions
+    Toolchain {
+        #[command(subcommand)]
+        action: Option<ToolchainAction>,
+    },
+
+    /// Show or switch the active environment from [environments]
+    Env {
+        #[command(subcommand)]
+        action: Option<EnvAction>,
+    },
+
     /// Check for updates
     Update {
         /// Force update check (ignore cache)
@@ -79,37 +237,234 @@ enum Commands {
         /// Skip interactive prompts
         #[arg(long)]
         no_interactive: bool,
+        /// Migrate an existing config.toml and dev.toml files to the current schema
+        #[arg(long)]
+        upgrade: bool,
     },
 
     /// View command history
+    #[command(args_conflicts_with_subcommands = true)]
     History {
+        #[command(subcommand)]
+        action: Option<HistoryAction>,
         /// Search pattern
         search: Option<String>,
+        /// Show the most-run and slowest commands (same as `history stats`)
+        #[arg(long, conflicts_with = "search")]
+        stats: bool,
+    },
+
+    /// Pin a menu item to the ⭐ Favorites group at the top of the menu
+    ///
+    /// Without an id, lists the menu item ids.
+    Pin {
+        /// Menu item id, e.g. docker/up
+        id: Option<String>,
+    },
+
+    /// Unpin a menu item
+    Unpin {
+        /// Menu item id
+        id: String,
+    },
+
+    /// Run an action from an external extension (.dev/extensions) or script (.dev/scripts)
+    ///
+    /// Without an action, lists the available actions.
+    Run {
+        /// Action id, optionally as <extension>:<action>
+        action: Option<String>,
+        /// Extra arguments passed to the action
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+
+    /// Show the actions external extensions discover, from the cache when it's current
+    Discover {
+        /// Drop the cache (.dev/cache/commands.json) and run discovery again
+        #[arg(long)]
+        refresh: bool,
+    },
+
+    /// Install and manage external extensions, or run one's actions
+    ///
+    /// `devkit ext <name> <action> [ARGS]...` runs an action (like `devkit run
+    /// <name>:<action>`); `devkit ext <name>` lists its actions.
+    Ext {
+        #[command(subcommand)]
+        action: ExtAction,
+    },
+}
+
+#[cfg(feature = "benchmark")]
+#[derive(Subcommand)]
+enum BenchAction {
+    /// Compare the latest results for HEAD with those recorded for a git ref
+    Compare { reference: String },
+    /// Benchmark commands with hyperfine ([bench.<name>] entries or `-- <command>`)
+    Cmd {
+        /// [bench] entries to run, or the name for an ad-hoc command
+        names: Vec<String>,
+        /// Warmup runs for an ad-hoc command
+        #[arg(long, default_value_t = 3)]
+        warmup: u32,
+        /// Measured runs for an ad-hoc command
+        #[arg(long)]
+        runs: Option<u32>,
+        /// Command to benchmark
+        #[arg(last = true)]
+        command: Vec<String>,
+    },
+}
+
+#[cfg(feature = "test")]
+#[derive(Subcommand)]
+enum TestAction {
+    /// Collect coverage for all packages and merge it into one lcov report
+    Coverage {
+        /// Also render an HTML report (requires genhtml)
+        #[arg(long)]
+        html: bool,
+    },
+    /// Repeat the test suite and report tests that fail intermittently
+    Flaky {
+        /// Only track tests whose name contains this
+        filter: Option<String>,
+        /// How many times to run the suite
+        #[arg(long, default_value_t = 10)]
+        runs: u32,
+    },
+}
+
+#[derive(Subcommand)]
+enum NewAction {
+    /// Create a package from a template and register it in the workspace
+    Package {
+        /// Package name
+        name: String,
+        /// rust-lib, rust-bin, ts-lib, next-app or a directory in .dev/templates
+        #[arg(short, long, default_value = "rust-lib")]
+        template: String,
+        /// Parent directory (defaults to the first [workspaces] packages glob's)
+        #[arg(long)]
+        dir: Option<String>,
+    },
+    /// List the available templates
+    Templates,
+}
+
+#[derive(Subcommand)]
+enum ForwardAction {
+    /// List background forwards (default; in a terminal, pick ones to stop)
+    List,
+    /// Stop the forward listening on a port
+    Stop { port: u16 },
+    /// Stop every background forward
+    StopAll,
+}
+
+#[derive(Subcommand)]
+enum ServicesAction {
+    /// Show which services are running (default)
+    Status,
+    /// Start services in the background (every package with [cmd.dev] when none are named)
+    Start {
+        /// Package (runs its [cmd.dev]) or <package>:<cmd>
+        names: Vec<String>,
+    },
+    /// Stop services (every running one when none are named)
+    Stop { names: Vec<String> },
+    /// Show a service's log
+    Logs {
+        name: String,
+        /// Keep printing new output
+        #[arg(short, long)]
+        follow: bool,
+        /// Lines of earlier output to show
+        #[arg(short = 'n', long, default_value = "50")]
+        lines: usize,
     },
 }
 
-#[cfg(feature = "docker")]
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum ExportEditor {
+    /// .vscode/tasks.json
+    Vscode,
+    /// .run/*.run.xml
+    Jetbrains,
+}
+
 #[derive(Subcommand)]
-enum DockerAction {
-    Up,
-    Down,
-    Restart,
-    Logs { service: Option<String> },
-    Shell { service: Option<String> },
+enum ToolchainAction {
+    /// Show whether each pinned runtime is installed (default)
+    Status,
+    /// Install the pinned runtimes with mise or asdf
+    Install,
 }
 
-#[cfg(feature = "database")]
 #[derive(Subcommand)]
-enum DbAction {
-    Migrate,
+enum EnvAction {
+    /// Show the active environment and what it sets (default)
+    Show,
+    /// Make an environment active for this checkout
+    Use {
+        /// Environment name from [environments] available
+        name: String,
+    },
+    /// Go back to the default environment
     Reset,
-    Seed,
-    Shell,
 }
 
-fn main() -> ExitCode {
-    let _ = dotenvy::dotenv();
+#[derive(Subcommand)]
+enum ConfigAction {
+    /// Check .dev/config.toml and package dev.toml files
+    Validate(ValidateArgs),
+}
+
+#[derive(clap::Args)]
+struct ValidateArgs {
+    /// Print the report as JSON
+    #[arg(long)]
+    json: bool,
+    /// Rewrite .dev/config.toml to fix duplicate ports and unknown excludes
+    #[arg(long)]
+    fix: bool,
+}
+
+#[derive(Subcommand)]
+enum HistoryAction {
+    /// Most-run and slowest commands with their average duration and failure rate
+    Stats,
+}
 
+#[derive(Subcommand)]
+enum ExtAction {
+    /// Install an extension from a git URL or a local directory into .dev/extensions
+    Install {
+        /// Git URL or path to a directory containing config.toml
+        source: String,
+        /// Branch or tag to clone
+        #[arg(long = "ref")]
+        git_ref: Option<String>,
+        /// Replace the extension if it's already installed
+        #[arg(long)]
+        force: bool,
+    },
+    /// Re-fetch installed extensions from their source
+    Update {
+        /// Extension to update (default: all installed)
+        name: Option<String>,
+    },
+    /// Remove an extension
+    Remove { name: String },
+    /// List all extensions with their availability, and external extension sources
+    List,
+    /// `<extension> [action] [args]...`
+    #[command(external_subcommand)]
+    Run(Vec<String>),
+}
+
+fn main() -> ExitCode {
     // Initialize tracing
     init_tracing();
 
@@ -134,82 +489,363 @@ fn init_tracing() {
 }
 
 fn run() -> Result<()> {
-    let mut cli = Cli::parse();
-    let ctx = AppContext::new(cli.quiet)?;
+    let mut extensions = ExtensionRegistry::new();
+    register_extensions(&mut extensions);
+
+    let matches = cli_command(&extensions).get_matches();
+    let quiet = matches.get_flag("quiet");
+    // Validation commands report config errors themselves
+    let mut ctx = if matches!(matches.subcommand_name(), Some("validate" | "config")) {
+        AppContext::load_unvalidated(quiet)?
+    } else {
+        AppContext::load(quiet, !quiet)?
+    };
+    if matches.get_flag("skip_broken") {
+        ctx.config.global.extensions.skip_broken = true;
+    }
+    devkit_core::environment::export_env_files(&ctx.repo, ctx.config.global.environments.current());
 
-    // Resolve command aliases
-    resolve_aliases(&mut cli, &ctx);
+    let started = Instant::now();
+    let result = dispatch(&ctx, &extensions, &matches);
+    if let Some(command) = history_command(&matches) {
+        command_finished(&ctx, command, HistorySource::Cli, started, &result);
+    }
+    result
+}
+
+fn dispatch(
+    ctx: &AppContext,
+    extensions: &ExtensionRegistry,
+    matches: &clap::ArgMatches,
+) -> Result<()> {
+    let mut hooks = ExtensionRegistry::new();
+    register_prerun_extensions(&mut hooks);
+    dispatch_with_hooks(ctx, extensions, &hooks, matches)
+}
 
-    // Register and run prerun hooks from extensions
+/// Register the extensions whose prerun hooks run before commands
+///
+/// External extensions have no prerun hooks, so a broken one can't block commands.
+#[cfg_attr(not(any(feature = "deps", feature = "proto")), allow(unused_variables))]
+fn register_prerun_extensions(registry: &mut ExtensionRegistry) {
     #[cfg(feature = "deps")]
-    {
-        use devkit_core::ExtensionRegistry;
-        let mut registry = ExtensionRegistry::with_external_extensions(&ctx.repo);
-        registry.register(Box::new(devkit_ext_deps::DepsExtension));
+    registry.register(Box::new(devkit_ext_deps::DepsExtension));
+    #[cfg(feature = "proto")]
+    registry.register(Box::new(devkit_ext_proto::ProtoExtension));
+}
+
+fn dispatch_with_hooks(
+    ctx: &AppContext,
+    extensions: &ExtensionRegistry,
+    hooks: &ExtensionRegistry,
+    matches: &clap::ArgMatches,
+) -> Result<()> {
+    // `command` is None for extension subcommands as well as the menu
+    let mut cli = Cli::from_arg_matches(matches).unwrap_or_else(|e| e.exit());
 
-        // Run prerun hooks (auto-install dependencies, etc.)
-        if let Err(e) = registry.run_prerun_hooks(&ctx) {
+    // Resolve command aliases
+    resolve_aliases(&mut cli, ctx);
+
+    // Run prerun hooks (runtime checks, protobuf codegen, etc.)
+    // Skipped for `toolchain` so its warnings don't repeat what the command prints, for
+    // `env` and `wait` since they shouldn't wait on codegen, and for config validation
+    // since the config may be broken
+    if !matches!(
+        cli.command,
+        Some(
+            Commands::Toolchain { .. }
+                | Commands::Env { .. }
+                | Commands::Wait { .. }
+                | Commands::Validate(_)
+                | Commands::Config { .. }
+        )
+    ) {
+        if let Err(e) = hooks.run_prerun_hooks(ctx) {
             ctx.print_error(&format!("Prerun failed: {:#}", e));
             return Err(e.into());
         }
     }
 
-    // Features are AUTO-DETECTED based on project structure
-    // No manual configuration needed!
-    let features = &ctx.features;
+    // Subcommands contributed by extensions (`devkit k8s ...`)
+    if let Some((name, sub_matches)) = matches.subcommand() {
+        if let Some(result) = extensions.handle_cli(ctx, name, sub_matches) {
+            return result.map_err(Into::into);
+        }
+    }
 
     match cli.command {
         Some(Commands::Cmd {
             command,
             parallel,
+            jobs,
             package,
+            affected,
+            base,
             list,
-        }) => cmd_run(&ctx, command, parallel, package, list),
+            force,
+        }) => {
+            let package = if affected {
+                let mut selected = devkit_tasks::select_affected(ctx, base.as_deref(), &package)?;
+                if let Some(cmd) = &command {
+                    selected.retain(|p| ctx.config.get_cmd(p, cmd).is_some());
+                }
+                if selected.is_empty() {
+                    ctx.print_success("✓ No packages affected");
+                    return Ok(());
+                }
+                selected
+            } else {
+                package
+            };
+            cmd_run(
+                ctx,
+                command,
+                parallel || jobs.is_some(),
+                jobs,
+                package,
+                list,
+                force,
+            )
+        }
 
-        #[cfg(feature = "docker")]
-        Some(Commands::Docker { action }) if features.docker => handle_docker(&ctx, action),
+        Some(Commands::Pipeline { name, keep_going }) => cmd_pipeline(ctx, name, keep_going),
 
-        #[cfg(feature = "database")]
-        Some(Commands::Database { action }) if features.database => handle_database(&ctx, action),
+        #[cfg(feature = "benchmark")]
+        Some(Commands::Bench {
+            action,
+            check,
+            threshold,
+            against,
+            save_baseline,
+            package,
+            variant,
+        }) => {
+            let opts = devkit_ext_benchmark::BenchOptions {
+                packages: package,
+                variant,
+            };
+            handle_bench(ctx, action, opts, check, threshold, against, save_baseline)
+        }
+
+        #[cfg(feature = "test")]
+        Some(Commands::Test {
+            action,
+            package,
+            report,
+            shard,
+            affected,
+            base,
+        }) => {
+            let opts = devkit_ext_test::TestOptions {
+                packages: package,
+                report,
+                shard,
+                affected,
+                base,
+            };
+            handle_test(ctx, action, opts)
+        }
 
         #[cfg(feature = "deps")]
-        Some(Commands::Deps { list }) => handle_deps(&ctx, list),
+        Some(Commands::Deps { list }) => handle_deps(ctx, list),
 
         Some(Commands::Completions { shell }) => {
             generate_completions(shell);
             Ok(())
         }
 
-        Some(Commands::Update { force }) => cmd_update(&ctx, force),
+        Some(
+            Commands::Validate(args)
+            | Commands::Config {
+                action: ConfigAction::Validate(args),
+            },
+        ) => cmd_validate(ctx, args),
+
+        Some(Commands::Export { editor }) => cmd_export(ctx, editor),
+
+        Some(Commands::New { action }) => cmd_new(ctx, action),
+
+        Some(Commands::Forward { action }) => cmd_forward(ctx, action),
+
+        Some(Commands::Services { action }) => cmd_services(ctx, action),
+
+        Some(Commands::Up { names, restart }) => cmd_up(ctx, names, restart),
+
+        Some(Commands::Ports { kill }) => cmd_ports(ctx, kill),
+
+        Some(Commands::Wait {
+            targets,
+            timeout,
+            interval,
+        }) => {
+            let options = devkit_core::wait::WaitOptions {
+                timeout: std::time::Duration::from_secs(timeout),
+                interval: std::time::Duration::from_secs(interval),
+            };
+            devkit_core::wait::wait_for(ctx, &targets, &options).map_err(Into::into)
+        }
+
+        Some(Commands::Status) => cmd_status(ctx),
+
+        Some(Commands::Doctor { fix }) => cmd_doctor(ctx, fix),
+
+        Some(Commands::Toolchain { action }) => match action {
+            Some(ToolchainAction::Install) => cmd_toolchain_install(ctx),
+            Some(ToolchainAction::Status) | None => cmd_toolchain_status(ctx),
+        },
 
-        Some(Commands::Init { no_interactive }) => {
-            devkit_core::init::init_project(&ctx.repo, !no_interactive).map_err(Into::into)
+        Some(Commands::Env { action }) => match action {
+            Some(EnvAction::Use { name }) => cmd_env_use(ctx, Some(&name)),
+            Some(EnvAction::Reset) => cmd_env_use(ctx, None),
+            Some(EnvAction::Show) | None => cmd_env_show(ctx),
+        },
+
+        Some(Commands::Update { force }) => cmd_update(ctx, force),
+
+        Some(Commands::Init {
+            no_interactive,
+            upgrade,
+        }) => {
+            if upgrade {
+                devkit_core::init::upgrade_project(&ctx.repo)
+            } else {
+                devkit_core::init::init_project(&ctx.repo, !no_interactive && ctx.is_interactive())
+            }
         }
 
-        Some(Commands::History { search }) => cmd_history(&ctx, search.as_deref()),
+        Some(Commands::History {
+            action,
+            search,
+            stats,
+        }) => match action {
+            Some(HistoryAction::Stats) => cmd_history_stats(ctx),
+            None if stats => cmd_history_stats(ctx),
+            None => cmd_history(ctx, search.as_deref()),
+        },
+
+        Some(Commands::Pin { id }) => cmd_pin(ctx, id.as_deref()),
+
+        Some(Commands::Unpin { id }) => cmd_unpin(ctx, &id),
+
+        Some(Commands::Run { action, args }) => cmd_run_extension(ctx, action.as_deref(), &args),
+
+        Some(Commands::Discover { refresh }) => cmd_discover(ctx, refresh),
+
+        Some(Commands::Ext { action }) => cmd_ext(ctx, action),
 
         None => {
             // Check for updates in background (non-blocking)
-            check_for_updates_background(&ctx);
-            interactive_menu(&ctx)
+            check_for_updates_background(ctx);
+            interactive_menu(ctx)
         }
+    }
+}
 
-        _ => {
-            ctx.print_warning("Feature not available in this project");
-            ctx.print_info(
-                "This command requires project setup (e.g., docker-compose.yml, database config)",
-            );
-            Ok(())
-        }
+/// The full CLI: built-in commands plus subcommands contributed by extensions
+fn cli_command(extensions: &ExtensionRegistry) -> clap::Command {
+    Cli::command().subcommands(extensions.subcommands())
+}
+
+/// Register the extensions compiled into this binary
+fn register_extensions(registry: &mut ExtensionRegistry) {
+    #[cfg(feature = "docker")]
+    registry.register(Box::new(devkit_ext_docker::DockerExtension));
+
+    #[cfg(feature = "database")]
+    registry.register(Box::new(devkit_ext_database::DatabaseExtension));
+
+    #[cfg(feature = "deps")]
+    registry.register(Box::new(devkit_ext_deps::DepsExtension));
+
+    #[cfg(feature = "git")]
+    registry.register(Box::new(devkit_ext_git::GitExtension));
+
+    #[cfg(feature = "ecs")]
+    registry.register(Box::new(devkit_ext_ecs::EcsExtension));
+
+    #[cfg(feature = "aws")]
+    registry.register(Box::new(devkit_ext_aws::AwsExtension));
+
+    #[cfg(feature = "pulumi")]
+    registry.register(Box::new(devkit_ext_pulumi::PulumiExtension));
+
+    #[cfg(feature = "ci")]
+    registry.register(Box::new(devkit_ext_ci::CiExtension));
+
+    #[cfg(feature = "monitoring")]
+    registry.register(Box::new(devkit_ext_monitoring::MonitoringExtension));
+
+    #[cfg(feature = "tunnel")]
+    registry.register(Box::new(devkit_ext_tunnel::TunnelExtension));
+
+    #[cfg(feature = "benchmark")]
+    registry.register(Box::new(devkit_ext_benchmark::BenchmarkExtension));
+
+    #[cfg(feature = "profile")]
+    registry.register(Box::new(devkit_ext_profile::ProfileExtension));
+
+    #[cfg(feature = "docs")]
+    registry.register(Box::new(devkit_ext_docs::DocsExtension));
+
+    #[cfg(feature = "nix")]
+    registry.register(Box::new(devkit_ext_nix::NixExtension));
+
+    #[cfg(feature = "proto")]
+    registry.register(Box::new(devkit_ext_proto::ProtoExtension));
+
+    #[cfg(feature = "server")]
+    registry.register(Box::new(devkit_ext_server::ServerExtension));
+
+    #[cfg(feature = "test")]
+    registry.register(Box::new(devkit_ext_test::TestExtension));
+
+    #[cfg(feature = "k8s")]
+    registry.register(Box::new(devkit_ext_k8s::K8sExtension));
+
+    #[cfg(feature = "localstack")]
+    registry.register(Box::new(devkit_ext_localstack::LocalstackExtension));
+
+    #[cfg(feature = "mobile")]
+    registry.register(Box::new(devkit_ext_mobile::MobileExtension));
+
+    #[cfg(feature = "secrets")]
+    registry.register(Box::new(devkit_ext_secrets::SecretsExtension));
+
+    #[cfg(feature = "cache")]
+    registry.register(Box::new(devkit_ext_cache::CacheExtension));
+
+    #[cfg(feature = "remote")]
+    registry.register(Box::new(devkit_ext_remote::RemoteExtension));
+    #[cfg(feature = "watch")]
+    registry.register(Box::new(devkit_ext_watch::WatchExtension));
+
+    #[cfg(feature = "script")]
+    registry.register(Box::new(devkit_ext_script::ScriptExtension));
+
+    #[cfg(feature = "commands")]
+    registry.register(Box::new(devkit_ext_commands::CommandsExtension));
+}
+
+/// Built-in, external and wasm extensions
+fn full_registry(ctx: &AppContext, skip_broken: bool) -> Result<ExtensionRegistry> {
+    let mut registry = ExtensionRegistry::with_external_extensions(&ctx.repo, skip_broken)?;
+    register_extensions(&mut registry);
+
+    #[cfg(feature = "wasm")]
+    for plugin in devkit_ext_wasm::wasm_extensions(&ctx.repo) {
+        registry.register(plugin);
     }
+
+    Ok(registry)
 }
 
 fn generate_completions(shell: clap_complete::Shell) {
-    use clap::CommandFactory;
     use clap_complete::generate;
     use std::io;
 
-    let mut cmd = Cli::command();
+    let mut extensions = ExtensionRegistry::new();
+    register_extensions(&mut extensions);
+    let mut cmd = cli_command(&extensions);
     generate(shell, &mut cmd, "devkit", &mut io::stdout());
 }
 
@@ -217,8 +853,10 @@ fn cmd_run(
     ctx: &AppContext,
     command: Option<String>,
     parallel: bool,
+    jobs: Option<usize>,
     packages: Vec<String>,
     list: bool,
+    force: bool,
 ) -> Result<()> {
     use devkit_tasks::{list_commands, print_results, run_cmd, CmdOptions};
 
@@ -253,9 +891,11 @@ fn cmd_run(
 
     let opts = CmdOptions {
         parallel,
+        jobs,
         variant: None,
         packages,
         capture: false,
+        force,
     };
 
     let results = run_cmd(ctx, &cmd_name, &opts)?;
@@ -268,33 +908,139 @@ fn cmd_run(
     Ok(())
 }
 
-#[cfg(feature = "docker")]
-fn handle_docker(ctx: &AppContext, action: DockerAction) -> Result<()> {
-    use devkit_ext_docker;
+fn cmd_pipeline(ctx: &AppContext, name: Option<String>, keep_going: bool) -> Result<()> {
+    use devkit_tasks::{run_pipeline, StepStatus};
+
+    let Some(name) = name else {
+        let pipelines = &ctx.config.global.pipeline;
+        if pipelines.names().is_empty() {
+            ctx.print_info("No pipelines defined. Add one to .dev/config.toml:");
+            println!();
+            println!("  [pipeline.ci]");
+            println!("  steps = [{{ cmd = \"lint\" }}, {{ cmd = \"test\" }}]");
+            return Ok(());
+        }
+        println!("Available pipelines:");
+        println!();
+        for name in pipelines.names() {
+            let pipeline = &pipelines.entries[name];
+            match &pipeline.description {
+                Some(description) => println!("  {} - {}", name, description),
+                None => println!("  {} ({} steps)", name, pipeline.steps.len()),
+            }
+        }
+        return Ok(());
+    };
+
+    let started = Instant::now();
+    let results = run_pipeline(ctx, &name, keep_going)?;
+
+    println!();
+    ctx.print_header(&format!("Pipeline {}", name));
+    let width = results
+        .iter()
+        .map(|r| r.label.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max(4);
+    for result in &results {
+        let status = match result.status {
+            StepStatus::Passed => console::style("✓ passed").green(),
+            StepStatus::Failed => console::style("✗ failed").red(),
+            StepStatus::Skipped => console::style("- skipped").dim(),
+        };
+        let time = match result.status {
+            StepStatus::Skipped => String::new(),
+            _ => format_ms(result.duration.as_millis() as u64),
+        };
+        println!(
+            "  {:<width$}  {:<9}  {:>8}",
+            result.label,
+            status,
+            time,
+            width = width
+        );
+    }
+    println!();
+
+    let failed = results
+        .iter()
+        .filter(|r| r.status == StepStatus::Failed)
+        .count();
+    let total = format_ms(started.elapsed().as_millis() as u64);
+    if failed > 0 {
+        return Err(anyhow::anyhow!(
+            "Pipeline '{}' failed: {} of {} step(s) failed in {}",
+            name,
+            failed,
+            results.len(),
+            total
+        ));
+    }
+    ctx.print_success(&format!("✓ Pipeline '{}' passed in {}", name, total));
+    Ok(())
+}
+
+#[cfg(feature = "benchmark")]
+fn handle_bench(
+    ctx: &AppContext,
+    action: Option<BenchAction>,
+    opts: devkit_ext_benchmark::BenchOptions,
+    check: bool,
+    threshold: Option<f64>,
+    against: Option<String>,
+    save_baseline: bool,
+) -> Result<()> {
+    use devkit_ext_benchmark;
 
     match action {
-        DockerAction::Up => devkit_ext_docker::compose_up(ctx, &[], false).map_err(Into::into),
-        DockerAction::Down => devkit_ext_docker::compose_down(ctx).map_err(Into::into),
-        DockerAction::Restart => devkit_ext_docker::compose_restart(ctx, &[]).map_err(Into::into),
-        DockerAction::Logs { service } => {
-            devkit_ext_docker::logs(ctx, service.as_deref()).map_err(Into::into)
+        Some(BenchAction::Compare { reference }) => {
+            devkit_ext_benchmark::compare_benchmarks(ctx, &reference)
         }
-        DockerAction::Shell { service } => {
-            devkit_ext_docker::shell(ctx, service.as_deref()).map_err(Into::into)
+        Some(BenchAction::Cmd {
+            names,
+            warmup,
+            runs,
+            command,
+        }) => devkit_ext_benchmark::bench_commands(ctx, &names, &command, warmup, runs).map(|_| ()),
+        None => {
+            let run = devkit_ext_benchmark::run_benchmarks(ctx, &opts)?;
+            if save_baseline {
+                devkit_ext_benchmark::save_baseline(ctx, &run)?;
+            }
+            if check {
+                devkit_ext_benchmark::check_regressions(ctx, &run, against.as_deref(), threshold)?;
+            }
+            Ok(())
         }
     }
 }
 
-#[cfg(feature = "database")]
-fn handle_database(ctx: &AppContext, action: DbAction) -> Result<()> {
-    use devkit_ext_database;
+#[cfg(feature = "test")]
+fn handle_test(
+    ctx: &AppContext,
+    action: Option<TestAction>,
+    opts: devkit_ext_test::TestOptions,
+) -> Result<()> {
+    use devkit_ext_test;
 
-    // Database functions return anyhow::Result, so no conversion needed
     match action {
-        DbAction::Migrate => devkit_ext_database::migrate(ctx),
-        DbAction::Reset => devkit_ext_database::reset(ctx),
-        DbAction::Seed => devkit_ext_database::seed(ctx),
-        DbAction::Shell => devkit_ext_database::shell(ctx),
+        Some(TestAction::Coverage { html }) => {
+            let opts = devkit_ext_test::CoverageOptions {
+                packages: opts.packages,
+                html,
+            };
+            devkit_ext_test::run_coverage(ctx, &opts).map(|_| ())
+        }
+        Some(TestAction::Flaky { filter, runs }) => {
+            let opts = devkit_ext_test::FlakyOptions {
+                runs,
+                filter,
+                packages: opts.packages,
+            };
+            devkit_ext_test::detect_flaky(ctx, &opts).map(|_| ())
+        }
+        None => devkit_ext_test::run_tests(ctx, &opts),
     }
 }
 
@@ -311,163 +1057,1019 @@ fn handle_deps(ctx: &AppContext, list: bool) -> Result<()> {
 
 fn interactive_menu(ctx: &AppContext) -> Result<()> {
     use dialoguer::FuzzySelect;
-    use std::collections::HashMap;
 
-    // Create extension registry and register all extensions
-    let mut registry = ExtensionRegistry::with_external_extensions(&ctx.repo);
+    ctx.require_interactive(
+        "The menu",
+        "Run a command directly instead, e.g. `devkit cmd build` (see `devkit --help`)",
+    )?;
+    let registry = full_registry(ctx, ctx.config.global.extensions.skip_broken)?;
 
-    #[cfg(feature = "docker")]
-    registry.register(Box::new(devkit_ext_docker::DockerExtension));
+    loop {
+        // Build menu dynamically
+        let entries = palette(&registry, ctx);
+        let favorites = Favorites::load(&ctx.repo).unwrap_or_else(|e| {
+            ctx.print_warning(&format!("Ignoring favorites: {:#}", e));
+            Favorites::default()
+        });
 
-    #[cfg(feature = "database")]
-    registry.register(Box::new(devkit_ext_database::DatabaseExtension));
+        // Build display list
+        #[derive(Clone)]
+        enum DisplayItem {
+            Submenu(String),
+            Item(usize),
+            SearchAll,
+            EditFavorites,
+            Exit,
+        }
 
-    #[cfg(feature = "deps")]
-    registry.register(Box::new(devkit_ext_deps::DepsExtension));
+        let mut display: Vec<String> = vec![];
+        let mut display_mapping: Vec<DisplayItem> = vec![];
 
-    #[cfg(feature = "git")]
-    registry.register(Box::new(devkit_ext_git::GitExtension));
+        // Pinned items first, in the order they were pinned
+        for id in &favorites.pinned {
+            if let Some(idx) = entries.iter().position(|entry| &entry.id == id) {
+                display.push(format!("⭐ {}", entries[idx].display()));
+                display_mapping.push(DisplayItem::Item(idx));
+            }
+        }
 
-    #[cfg(feature = "ecs")]
-    registry.register(Box::new(devkit_ext_ecs::EcsExtension));
+        let (submenus, _) = menu_level(&entries, &[]);
+        for name in submenus {
+            display.push(format!("{} ›", name));
+            display_mapping.push(DisplayItem::Submenu(name));
+        }
 
-    #[cfg(feature = "pulumi")]
-    registry.register(Box::new(devkit_ext_pulumi::PulumiExtension));
+        display.push("🔍 Search all actions".to_string());
+        display_mapping.push(DisplayItem::SearchAll);
+        display.push("⭐ Edit favorites".to_string());
+        display_mapping.push(DisplayItem::EditFavorites);
 
-    #[cfg(feature = "ci")]
-    registry.register(Box::new(devkit_ext_ci::CiExtension));
+        // Add exit option
+        display.push("❌ Exit".to_string());
+        display_mapping.push(DisplayItem::Exit);
+        let display: Vec<String> = display.iter().map(|item| ctx.text(item).into()).collect();
 
-    #[cfg(feature = "commands")]
-    registry.register(Box::new(devkit_ext_commands::CommandsExtension));
+        println!();
+        // Esc on the top level exits
+        let Some(choice) = FuzzySelect::with_theme(&*ctx.theme())
+            .with_prompt("What would you like to do? (type to filter, Esc to exit)")
+            .items(&display)
+            .default(0)
+            .interact_opt()?
+        else {
+            break;
+        };
+
+        // Handle selection
+        let result = match &display_mapping[choice] {
+            DisplayItem::Submenu(name) => show_submenu(ctx, &entries, std::slice::from_ref(name)),
+            DisplayItem::Item(idx) => {
+                println!();
+                run_palette_entry(ctx, &entries[*idx]);
+                Ok(())
+            }
+            DisplayItem::SearchAll => search_all(ctx, &entries),
+            DisplayItem::EditFavorites => edit_favorites(ctx, &entries),
+            DisplayItem::Exit => break,
+        };
+        if let Err(e) = result {
+            println!();
+            ctx.print_error(&format!("Error: {:#}", e));
+        }
+    }
+
+    Ok(())
+}
+
+/// Submenu path of a category: "🧪 Testing / Coverage" nests "Coverage" under "🧪 Testing"
+fn category_path(category: &str) -> Vec<&str> {
+    category.split(" / ").collect()
+}
+
+/// Child submenus (sorted) and entries directly under `path`
+fn menu_level(entries: &[PaletteEntry], path: &[String]) -> (Vec<String>, Vec<usize>) {
+    let mut submenus: Vec<String> = vec![];
+    let mut items = vec![];
+
+    for (idx, entry) in entries.iter().enumerate() {
+        let category = category_path(&entry.category);
+        if category.len() < path.len() || category[..path.len()] != *path {
+            continue;
+        }
+        match category.get(path.len()) {
+            Some(child) => {
+                if !submenus.iter().any(|name| name == child) {
+                    submenus.push(child.to_string());
+                }
+            }
+            None => items.push(idx),
+        }
+    }
+    submenus.sort();
+    (submenus, items)
+}
+
+/// A submenu with its subgroups and actions; Esc or "← Back" returns to the parent
+fn show_submenu(ctx: &AppContext, entries: &[PaletteEntry], path: &[String]) -> Result<()> {
+    use dialoguer::FuzzySelect;
+
+    let (submenus, items) = menu_level(entries, path);
+    let breadcrumb = format!("devkit › {}", path.join(" › "));
 
     loop {
-        // Build menu dynamically
-        let menu_items = registry.menu_items(ctx);
+        let mut display: Vec<String> = vec!["← Back".to_string()];
+        display.extend(submenus.iter().map(|name| format!("{} ›", name)));
+        display.extend(items.iter().map(|&idx| entries[idx].item.label.clone()));
+        let display: Vec<String> = display.iter().map(|item| ctx.text(item).into()).collect();
 
-        // Group items by their group field
-        let mut groups: HashMap<String, Vec<usize>> = HashMap::new();
-        let mut ungrouped: Vec<usize> = vec![];
+        println!();
+        let choice = FuzzySelect::with_theme(&*ctx.theme())
+            .with_prompt(ctx.text(&breadcrumb))
+            .items(&display)
+            .default(0)
+            .interact_opt()?;
+
+        let choice = match choice {
+            None | Some(0) => break,
+            Some(choice) => choice - 1,
+        };
+
+        if let Some(name) = submenus.get(choice) {
+            let mut child = path.to_vec();
+            child.push(name.clone());
+            show_submenu(ctx, entries, &child)?;
+        } else {
+            // Execute the selected item and stay in this submenu
+            println!();
+            run_palette_entry(ctx, &entries[items[choice - submenus.len()]]);
+        }
+    }
 
-        for (idx, item) in menu_items.iter().enumerate() {
-            if let Some(group) = &item.group {
-                groups.entry(group.clone()).or_default().push(idx);
+    Ok(())
+}
+
+/// Every action in one flat, filterable list (Category / Item)
+fn search_all(ctx: &AppContext, entries: &[PaletteEntry]) -> Result<()> {
+    use dialoguer::FuzzySelect;
+
+    let display: Vec<String> = entries
+        .iter()
+        .map(|entry| ctx.text(&entry.display()).into())
+        .collect();
+
+    println!();
+    if let Some(choice) = FuzzySelect::with_theme(&*ctx.theme())
+        .with_prompt("Search all actions (Esc to go back)")
+        .items(&display)
+        .interact_opt()?
+    {
+        println!();
+        run_palette_entry(ctx, &entries[choice]);
+    }
+    Ok(())
+}
+
+/// Toggle pinned items with a multi-select of the whole palette
+///
+/// Pins for entries that aren't in the palette right now (e.g. from an unavailable
+/// extension) are kept.
+fn edit_favorites(ctx: &AppContext, entries: &[PaletteEntry]) -> Result<()> {
+    use dialoguer::MultiSelect;
+
+    let mut favorites = Favorites::load(&ctx.repo)?;
+    let labels: Vec<String> = entries
+        .iter()
+        .map(|entry| ctx.text(&entry.display()).into())
+        .collect();
+    let pinned: Vec<bool> = entries
+        .iter()
+        .map(|entry| favorites.contains(&entry.id))
+        .collect();
+
+    println!();
+    let selection = MultiSelect::with_theme(&*ctx.theme())
+        .with_prompt("Favorites (space to pin/unpin, enter to save)")
+        .items(&labels)
+        .defaults(&pinned)
+        .interact()?;
+
+    for (idx, entry) in entries.iter().enumerate() {
+        if selection.contains(&idx) {
+            favorites.pin(&entry.id);
+        } else {
+            favorites.unpin(&entry.id);
+        }
+    }
+    favorites.save(&ctx.repo)?;
+    ctx.print_success(&format!("{} favorite(s) saved", favorites.pinned.len()));
+    Ok(())
+}
+
+fn cmd_pin(ctx: &AppContext, id: Option<&str>) -> Result<()> {
+    let registry = full_registry(ctx, ctx.config.global.extensions.skip_broken)?;
+    let entries = palette(&registry, ctx);
+    let mut favorites = Favorites::load(&ctx.repo)?;
+
+    let Some(id) = id else {
+        let width = entries
+            .iter()
+            .map(|entry| entry.id.len())
+            .max()
+            .unwrap_or(0);
+        for entry in &entries {
+            let marker = if favorites.contains(&entry.id) {
+                "*"
             } else {
-                ungrouped.push(idx);
+                " "
+            };
+            println!(
+                "{} {:<width$}  {}",
+                marker,
+                entry.id,
+                ctx.text(&entry.display()),
+                width = width
+            );
+        }
+        return Ok(());
+    };
+
+    if !entries.iter().any(|entry| entry.id == id) {
+        return Err(anyhow::anyhow!(
+            "No menu item '{}' (run `devkit pin` to list ids)",
+            id
+        ));
+    }
+    if favorites.pin(id) {
+        favorites.save(&ctx.repo)?;
+        ctx.print_success(&format!("Pinned {}", id));
+    } else {
+        ctx.print_info(&format!("{} is already pinned", id));
+    }
+    Ok(())
+}
+
+fn cmd_unpin(ctx: &AppContext, id: &str) -> Result<()> {
+    let mut favorites = Favorites::load(&ctx.repo)?;
+    if favorites.unpin(id) {
+        favorites.save(&ctx.repo)?;
+        ctx.print_success(&format!("Unpinned {}", id));
+    } else {
+        ctx.print_info(&format!("{} is not pinned", id));
+    }
+    Ok(())
+}
+
+/// Run a palette entry, report its error and record it in the history under its id
+fn run_palette_entry(ctx: &AppContext, entry: &PaletteEntry) {
+    let started = Instant::now();
+    let result: Result<()> = (entry.item.handler)(ctx).map_err(Into::into);
+    command_finished(ctx, entry.id.clone(), HistorySource::Menu, started, &result);
+
+    if let Err(e) = result {
+        println!();
+        ctx.print_error(&format!("Error: {:#}", e));
+    }
+}
+
+fn cmd_export(ctx: &AppContext, editor: ExportEditor) -> Result<()> {
+    use devkit_core::ide;
+
+    let tasks = ide::tasks(&ctx.config);
+    if tasks.is_empty() {
+        ctx.print_warning("No commands to export. Add [cmd] entries to package dev.toml files.");
+        return Ok(());
+    }
+
+    let path = match editor {
+        ExportEditor::Vscode => ide::export_vscode(&ctx.repo, &tasks)?,
+        ExportEditor::Jetbrains => ide::export_jetbrains(&ctx.repo, &tasks)?,
+    };
+    ctx.print_success(&format!(
+        "✓ Exported {} tasks to {}",
+        tasks.len(),
+        path.strip_prefix(&ctx.repo).unwrap_or(&path).display()
+    ));
+    ctx.print_info("Run it again after changing dev.toml files");
+    Ok(())
+}
+
+fn cmd_validate(ctx: &AppContext, args: ValidateArgs) -> Result<()> {
+    use devkit_core::autofix::fix_config;
+
+    let fixes = fix_config(&ctx.config, args.fix)?;
+    // Check the rewritten config
+    let fixed_config = (args.fix && !fixes.is_empty())
+        .then(|| devkit_core::Config::load(&ctx.repo))
+        .transpose()?;
+    let report = devkit_core::validate_config(fixed_config.as_ref().unwrap_or(&ctx.config))?;
+
+    if args.json {
+        let json = serde_json::json!({
+            "valid": report.is_valid(),
+            "errors": report.errors,
+            "warnings": report.warnings,
+            "fixes": fixes,
+            "fixed": args.fix && !fixes.is_empty(),
+        });
+        println!("{}", serde_json::to_string_pretty(&json)?);
+    } else {
+        ctx.print_header("Validating configuration");
+        println!();
+
+        if args.fix {
+            for fix in &fixes {
+                ctx.print_success(&format!("✓ {}", fix));
             }
         }
+        for error in &report.errors {
+            eprintln!("{} {}", console::style("✗").red(), error);
+        }
+        for warning in &report.warnings {
+            ctx.print_warning(&format!("⚠ {}", warning));
+        }
 
-        // Build display list
-        #[derive(Clone)]
-        enum DisplayItem {
-            GroupHeader(String),
-            Item(usize),
-            Exit,
+        if report.errors.is_empty() && report.warnings.is_empty() {
+            ctx.print_success("✓ Configuration is valid");
+        } else {
+            println!();
+            ctx.print_info(&format!(
+                "{} error(s), {} warning(s)",
+                report.errors.len(),
+                report.warnings.len()
+            ));
         }
+        if !args.fix && !fixes.is_empty() {
+            ctx.print_info("💡 `devkit config validate --fix` would:");
+            for fix in &fixes {
+                ctx.print_info(&format!("  {}", fix));
+            }
+        }
+    }
 
-        let mut display: Vec<String> = vec![];
-        let mut display_mapping: Vec<DisplayItem> = vec![];
+    if !report.is_valid() {
+        anyhow::bail!("Configuration validation failed");
+    }
+    Ok(())
+}
+
+fn cmd_new(ctx: &AppContext, action: NewAction) -> Result<()> {
+    use devkit_core::scaffold;
 
-        // Add ungrouped items first
-        for &idx in &ungrouped {
-            display.push(menu_items[idx].label.clone());
-            display_mapping.push(DisplayItem::Item(idx));
+    match action {
+        NewAction::Templates => {
+            for name in scaffold::templates(&ctx.repo) {
+                let description = scaffold::BUILTIN_TEMPLATES
+                    .iter()
+                    .find(|(builtin, _)| *builtin == name)
+                    .map(|(_, description)| *description)
+                    .unwrap_or(".dev/templates");
+                println!("  {:<12} {}", name, description);
+            }
+            Ok(())
+        }
+        NewAction::Package {
+            name,
+            template,
+            dir,
+        } => {
+            let scaffolded = scaffold::new_package(
+                &ctx.repo,
+                &ctx.config.global.workspaces.packages,
+                &name,
+                &template,
+                dir.as_deref(),
+            )?;
+            ctx.print_success(&format!(
+                "✓ Created {} from {} ({} files)",
+                scaffolded.path.display(),
+                template,
+                scaffolded.files
+            ));
+            for change in &scaffolded.registered {
+                ctx.print_info(change);
+            }
+            println!();
+            println!("Try: devkit cmd build --package {}", name);
+            Ok(())
         }
+    }
+}
 
-        // Add groups (sorted for consistent ordering)
-        let mut group_names: Vec<_> = groups.keys().cloned().collect();
-        group_names.sort();
+fn cmd_forward(ctx: &AppContext, action: Option<ForwardAction>) -> Result<()> {
+    use devkit_core::forwards::{self, Forwards};
 
-        for group_name in &group_names {
-            display.push(group_name.clone());
-            display_mapping.push(DisplayItem::GroupHeader(group_name.clone()));
+    match action {
+        Some(ForwardAction::Stop { port }) => {
+            let forward = forwards::stop(&ctx.repo, port)?;
+            ctx.print_success(&format!("✓ Stopped {}", forward.label()));
+            return Ok(());
         }
-
-        // Add all grouped items in flat format for filtering (Group / Item)
-        for group_name in &group_names {
-            let indices = &groups[group_name];
-            for &idx in indices {
-                let flat_label = format!("{} / {}", group_name, menu_items[idx].label);
-                display.push(flat_label);
-                display_mapping.push(DisplayItem::Item(idx));
+        Some(ForwardAction::StopAll) => {
+            let stopped = forwards::stop_all(&ctx.repo)?;
+            if stopped.is_empty() {
+                ctx.print_info("No background forwards running");
             }
+            for forward in stopped {
+                ctx.print_success(&format!("✓ Stopped {}", forward.label()));
+            }
+            return Ok(());
         }
+        Some(ForwardAction::List) | None => {}
+    }
+    let pick = action.is_none() && ctx.is_interactive();
 
-        // Add exit option
-        display.push("❌ Exit".to_string());
-        display_mapping.push(DisplayItem::Exit);
+    loop {
+        let state = Forwards::load(&ctx.repo)?;
+        ctx.print_header("Background forwards");
+        println!();
+        if state.forwards.is_empty() {
+            ctx.print_info("No background forwards running (start one with `--background`)");
+            return Ok(());
+        }
 
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        for forward in &state.forwards {
+            println!(
+                "  {:<12} -> {:<28} {:<7} pid {:<8} up {}m",
+                forward.ports_label(),
+                forward.target,
+                forward.source,
+                forward.pid,
+                now.saturating_sub(forward.started_at) / 60
+            );
+        }
         println!();
-        let choice = FuzzySelect::with_theme(&ctx.theme())
-            .with_prompt("What would you like to do? (type to filter)")
-            .items(&display)
-            .default(0)
+        if !pick {
+            ctx.print_info("Stop one with `devkit forward stop <port>`");
+            return Ok(());
+        }
+
+        let mut items: Vec<String> = state
+            .forwards
+            .iter()
+            .map(|forward| format!("Stop {}", forward.label()))
+            .collect();
+        items.push("Stop all".to_string());
+        items.push("Done".to_string());
+        let choice = dialoguer::Select::with_theme(&*ctx.theme())
+            .with_prompt("Forwards")
+            .items(&items)
+            .default(items.len() - 1)
             .interact()?;
 
-        // Handle selection
-        match &display_mapping[choice] {
-            DisplayItem::GroupHeader(group_name) => {
-                // Navigate to submenu for this group
-                if let Err(e) = show_group_submenu(ctx, group_name, &menu_items, &groups) {
-                    println!();
-                    ctx.print_error(&format!("Error: {:#}", e));
+        if choice == items.len() - 1 {
+            return Ok(());
+        }
+        if choice == items.len() - 2 {
+            forwards::stop_all(&ctx.repo)?;
+            ctx.print_success("✓ Stopped every forward");
+            return Ok(());
+        }
+        let forward = &state.forwards[choice];
+        forwards::stop(&ctx.repo, forward.ports[0])?;
+        ctx.print_success(&format!("✓ Stopped {}", forward.label()));
+        println!();
+    }
+}
+
+fn cmd_services(ctx: &AppContext, action: Option<ServicesAction>) -> Result<()> {
+    use devkit_tasks::daemon::{self, Daemons, Service};
+
+    match action.unwrap_or(ServicesAction::Status) {
+        ServicesAction::Start { names } => {
+            let services: Vec<Service> = if names.is_empty() {
+                daemon::services(&ctx.config)
+            } else {
+                names.iter().map(|name| Service::parse(name)).collect()
+            };
+            if services.is_empty() {
+                anyhow::bail!("No package has [cmd.dev]; name one as <package>:<cmd>");
+            }
+            let running = Daemons::load(&ctx.repo)?;
+            for service in services {
+                if names.is_empty() && running.services.contains_key(&service.name()) {
+                    ctx.print_info(&format!("{} is already running", service.name()));
+                    continue;
                 }
+                let started = daemon::start(ctx, &service)?;
+                ctx.print_success(&format!(
+                    "✓ Started {} (pid {}): {}",
+                    service.name(),
+                    started.pid,
+                    started.command
+                ));
             }
-            DisplayItem::Item(idx) => {
-                println!();
-                let result: Result<()> = (menu_items[*idx].handler)(ctx).map_err(Into::into);
-                if let Err(e) = result {
-                    println!();
-                    ctx.print_error(&format!("Error: {:#}", e));
+            ctx.print_info("Logs: `devkit services logs <name> -f`");
+            Ok(())
+        }
+        ServicesAction::Stop { names } => {
+            let services: Vec<Service> = if names.is_empty() {
+                let running = Daemons::load(&ctx.repo)?;
+                running
+                    .services
+                    .keys()
+                    .map(|name| Service::parse(name))
+                    .collect()
+            } else {
+                names.iter().map(|name| Service::parse(name)).collect()
+            };
+            if services.is_empty() {
+                ctx.print_info("No services running");
+            }
+            for service in services {
+                daemon::stop(&ctx.repo, &service)?;
+                ctx.print_success(&format!("✓ Stopped {}", service.name()));
+            }
+            Ok(())
+        }
+        ServicesAction::Logs {
+            name,
+            follow,
+            lines,
+        } => {
+            let log_file = Service::parse(&name).log_file(&ctx.repo);
+            daemon::tail(&log_file, lines, follow)
+        }
+        ServicesAction::Status => {
+            let running = Daemons::load(&ctx.repo)?;
+            let mut names: Vec<String> = daemon::services(&ctx.config)
+                .iter()
+                .map(Service::name)
+                .collect();
+            for name in running.services.keys() {
+                if !names.contains(name) {
+                    names.push(name.clone());
                 }
             }
-            DisplayItem::Exit => {
-                break;
+
+            ctx.print_header("Services");
+            println!();
+            if names.is_empty() {
+                ctx.print_info("No package has [cmd.dev]");
+                return Ok(());
             }
+            let now = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs();
+            for name in &names {
+                match running.services.get(name) {
+                    Some(daemon) => println!(
+                        "  {} {:<20} pid {:<8} up {:<5} {}",
+                        console::style("●").green(),
+                        console::style(name).bold(),
+                        daemon.pid,
+                        format!("{}m", now.saturating_sub(daemon.started_at) / 60),
+                        console::style(&daemon.command).dim()
+                    ),
+                    None => println!("  {} {:<20} stopped", console::style("○").dim(), name),
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+fn cmd_up(ctx: &AppContext, names: Vec<String>, restart: bool) -> Result<()> {
+    use devkit_tasks::procfile;
+
+    let mut processes = procfile::processes(ctx)?;
+    if !names.is_empty() {
+        if let Some(unknown) = names
+            .iter()
+            .find(|name| !processes.iter().any(|p| &p.name == *name))
+        {
+            anyhow::bail!("No process named '{}'", unknown);
+        }
+        processes.retain(|p| names.contains(&p.name));
+    }
+    procfile::up(ctx, processes, restart)
+}
+
+fn cmd_ports(ctx: &AppContext, kill: Option<u16>) -> Result<()> {
+    use devkit_core::ports;
+
+    let listeners = ports::listeners()?;
+    if let Some(port) = kill {
+        let Some(listener) = listeners.iter().find(|l| l.port == port) else {
+            anyhow::bail!("Nothing is listening on port {}", port);
+        };
+        let Some(pid) = listener.pid else {
+            anyhow::bail!(
+                "Can't tell which process owns port {} (it may belong to another user)",
+                port
+            );
+        };
+        if ctx.confirm(
+            &format!("Stop {} on port {}?", listener.owner(), port),
+            true,
+        )? {
+            ports::kill(pid)?;
+            ctx.print_success(&format!("✓ Stopped {}", listener.owner()));
         }
+        return Ok(());
     }
 
+    ctx.print_header("Ports");
+    println!();
+
+    let statuses = ports::project_ports(&ctx.config, &listeners);
+    if statuses.is_empty() {
+        ctx.print_info("No [services] ports configured and no common dev ports in use");
+        return Ok(());
+    }
+
+    for status in &statuses {
+        let line = format!("{:>5}  {:<20}", status.port, status.name);
+        match &status.listener {
+            None => println!("{} free", line),
+            Some(listener) if status.conflict => ctx.print_warning(&format!(
+                "{} ⚠ {} (conflicts with the compose service)",
+                line,
+                listener.owner()
+            )),
+            Some(listener) => ctx.print_success(&format!("{} {}", line, listener.owner())),
+        }
+    }
+
+    let conflicts: Vec<_> = statuses.iter().filter(|s| s.conflict).collect();
+    if conflicts.is_empty() {
+        return Ok(());
+    }
+    println!();
+    if !ctx.is_interactive() {
+        ctx.print_info("Stop a process with `devkit ports --kill <port>`");
+        return Ok(());
+    }
+    for status in conflicts {
+        let Some(listener) = &status.listener else {
+            continue;
+        };
+        let Some(pid) = listener.pid else {
+            ctx.print_info(&format!(
+                "Can't tell which process owns port {} (it may belong to another user)",
+                status.port
+            ));
+            continue;
+        };
+        if ctx.confirm(
+            &format!(
+                "Stop {} so {} can use port {}?",
+                listener.owner(),
+                status.name,
+                status.port
+            ),
+            false,
+        )? {
+            ports::kill(pid)?;
+            ctx.print_success(&format!("✓ Stopped {}", listener.owner()));
+        }
+    }
     Ok(())
 }
 
-fn show_group_submenu(
-    ctx: &AppContext,
-    group_name: &str,
-    menu_items: &[MenuItem],
-    groups: &std::collections::HashMap<String, Vec<usize>>,
-) -> Result<()> {
-    use dialoguer::FuzzySelect;
+fn cmd_status(ctx: &AppContext) -> Result<()> {
+    use devkit_core::status;
 
-    let indices = groups.get(group_name).unwrap();
+    ctx.print_header(&format!(
+        "{} ({})",
+        ctx.config.global.project.name,
+        ctx.config.global.environments.current()
+    ));
+    println!();
 
-    loop {
-        let mut display: Vec<String> = vec!["← Back".to_string()];
+    // Some(true) is fine, Some(false) needs attention, None is neither
+    let row = |label: &str, ok: Option<bool>, value: &str| {
+        let line = format!("{:<14}{}", label, value);
+        match ok {
+            Some(true) => ctx.print_success(&line),
+            Some(false) => ctx.print_warning(&line),
+            None => ctx.print_info(&line),
+        }
+    };
+    let first_line =
+        |e: anyhow::Error| e.to_string().lines().next().unwrap_or_default().to_string();
+
+    let git = status::git_status(&ctx.repo);
+    match &git {
+        Some(git) => {
+            let mut value = git.branch.as_deref().unwrap_or("(detached)").to_string();
+            if git.ahead > 0 {
+                value.push_str(&format!(" ↑{}", git.ahead));
+            }
+            if git.behind > 0 {
+                value.push_str(&format!(" ↓{}", git.behind));
+            }
+            match git.changed {
+                0 => value.push_str(", clean"),
+                n => value.push_str(&format!(", {} changed", n)),
+            }
+            row("Git", Some(git.changed == 0 && git.behind == 0), &value);
+        }
+        None => row("Git", None, "not a git repository"),
+    }
 
-        for &idx in indices {
-            display.push(menu_items[idx].label.clone());
+    if let Some((_, services)) = devkit_core::validation::compose_services(&ctx.repo) {
+        let mut names: Vec<&String> = services.keys().collect();
+        names.sort();
+        match status::running_services(&ctx.repo, &ctx.config.global) {
+            Some(running) => {
+                let stopped: Vec<&str> = names
+                    .iter()
+                    .filter(|name| !running.contains(name))
+                    .map(|name| name.as_str())
+                    .collect();
+                let mut value = format!("{}/{} running", names.len() - stopped.len(), names.len());
+                if !stopped.is_empty() {
+                    value.push_str(&format!(" (stopped: {})", stopped.join(", ")));
+                }
+                row("Services", Some(stopped.is_empty()), &value);
+            }
+            None => row("Services", None, "docker not available"),
         }
+    }
 
-        println!();
-        let choice = FuzzySelect::with_theme(&ctx.theme())
-            .with_prompt(group_name)
-            .items(&display)
-            .default(0)
-            .interact()?;
+    #[cfg(feature = "database")]
+    if ctx.features.database {
+        match devkit_ext_database::migration_status(ctx) {
+            Ok(migrations) if migrations.pending.is_empty() => row(
+                "Migrations",
+                Some(true),
+                &format!("{} applied, up to date", migrations.applied),
+            ),
+            Ok(migrations) => row(
+                "Migrations",
+                Some(false),
+                &format!(
+                    "{} applied, {} pending ({})",
+                    migrations.applied,
+                    migrations.pending.len(),
+                    migrations.pending.join(", ")
+                ),
+            ),
+            Err(e) => row("Migrations", None, &format!("unknown: {}", first_line(e))),
+        }
+    }
 
-        if choice == 0 {
-            // Back to main menu
-            break;
+    #[cfg(feature = "deps")]
+    {
+        let packages = devkit_ext_deps::discover_packages(ctx);
+        let stale: Vec<&str> = packages
+            .iter()
+            .filter(|package| package.needs_install)
+            .map(|package| package.name.as_str())
+            .collect();
+        if stale.is_empty() && !packages.is_empty() {
+            row(
+                "Dependencies",
+                Some(true),
+                &format!("{} package(s) installed", packages.len()),
+            );
+        } else if !stale.is_empty() {
+            row(
+                "Dependencies",
+                Some(false),
+                &format!("needs install: {} (run `devkit deps`)", stale.join(", ")),
+            );
+        }
+    }
+
+    #[cfg(feature = "ci")]
+    if ctx.features.github_actions && devkit_core::cmd_exists("gh") {
+        if let Some(branch) = git.as_ref().and_then(|git| git.branch.as_deref()) {
+            match devkit_ext_ci::last_run(ctx, branch) {
+                Ok(Some(run)) if run.status == "completed" => row(
+                    "CI",
+                    Some(run.conclusion == "success"),
+                    &format!("{} {} {}", run.workflow, run.conclusion, run.url),
+                ),
+                Ok(Some(run)) => row(
+                    "CI",
+                    None,
+                    &format!(
+                        "{} {} {}",
+                        run.workflow,
+                        run.status.replace('_', " "),
+                        run.url
+                    ),
+                ),
+                Ok(None) => row("CI", None, "no runs on this branch"),
+                Err(e) => row("CI", None, &format!("unknown: {}", first_line(e))),
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn cmd_doctor(ctx: &AppContext, fix: bool) -> Result<()> {
+    use devkit_core::doctor;
+
+    ctx.print_header("System Health Check");
+    println!();
+
+    let checks = doctor::check(&ctx.config);
+    for check in &checks {
+        if check.installed {
+            ctx.print_success(&format!("✓ {}", check.tool.name));
+        } else {
+            ctx.print_warning(&format!(
+                "✗ {} (needed for {})",
+                check.tool.name, check.reason
+            ));
         }
+    }
 
-        // Execute the selected item
-        let item_idx = indices[choice - 1];
+    let missing: Vec<_> = checks.iter().filter(|check| !check.installed).collect();
+    if missing.is_empty() {
         println!();
-        let result: Result<()> = (menu_items[item_idx].handler)(ctx).map_err(Into::into);
-        if let Err(e) = result {
-            println!();
-            ctx.print_error(&format!("Error: {:#}", e));
+        ctx.print_success("All tools installed");
+        return Ok(());
+    }
+
+    let ask = !fix && ctx.is_interactive();
+    let mut still_missing = Vec::new();
+    for check in missing {
+        let tool = check.tool;
+        println!();
+        let Some(installer) = tool.installer() else {
+            ctx.print_info(&format!("Install {}: {}", tool.name, tool.hint));
+            still_missing.push(tool.name);
+            continue;
+        };
+        let command = installer.command_line();
+        if !fix
+            && (!ask
+                || !ctx.confirm(&format!("Install {} with `{}`?", tool.name, command), true)?)
+        {
+            ctx.print_info(&format!("Install {}: {}", tool.name, command));
+            still_missing.push(tool.name);
+            continue;
+        }
+
+        ctx.print_info(&format!("Running {}...", command));
+        match installer.run() {
+            Ok(()) if tool.is_installed() => {
+                ctx.print_success(&format!("✓ Installed {}", tool.name))
+            }
+            Ok(()) => {
+                ctx.print_warning(&format!(
+                    "{} finished but {} is still not on PATH",
+                    command, tool.binary
+                ));
+                still_missing.push(tool.name);
+            }
+            Err(e) => {
+                ctx.print_error(&format!("{:#}", e));
+                still_missing.push(tool.name);
+            }
+        }
+    }
+
+    println!();
+    if still_missing.is_empty() {
+        ctx.print_success("All tools installed");
+    } else if fix {
+        anyhow::bail!("Still missing: {}", still_missing.join(", "));
+    } else if !ask {
+        ctx.print_info("Run `devkit doctor --fix` to install the missing tools");
+    }
+    Ok(())
+}
+
+fn cmd_toolchain_status(ctx: &AppContext) -> Result<()> {
+    use devkit_core::toolchain::Toolchain;
+
+    let Some(toolchain) = Toolchain::detect(&ctx.repo)? else {
+        ctx.print_info("No .mise.toml or .tool-versions in this project");
+        return Ok(());
+    };
+    let manager = toolchain.manager.binary();
+
+    ctx.print_header(&format!(
+        "Toolchain ({} via {})",
+        toolchain.file_name(),
+        manager
+    ));
+    println!();
+
+    let statuses = toolchain.status();
+    for status in &statuses {
+        let label = format!("{} {}", status.tool.name, status.tool.version);
+        if status.installed {
+            ctx.print_success(&format!("✓ {}", label));
+        } else {
+            ctx.print_warning(&format!("✗ {} (not installed)", label));
+        }
+    }
+
+    println!();
+    if !toolchain.manager.is_installed() {
+        ctx.print_warning(&format!("{} isn't installed", manager));
+    } else if statuses.iter().any(|status| !status.installed) {
+        ctx.print_info("Run `devkit toolchain install` to install the missing runtimes");
+    } else if toolchain.is_activated() {
+        ctx.print_success("All runtimes installed");
+    } else {
+        ctx.print_warning(&format!(
+            "{} isn't activated in this shell; add its shell hook or shims to PATH",
+            manager
+        ));
+    }
+    Ok(())
+}
+
+fn cmd_toolchain_install(ctx: &AppContext) -> Result<()> {
+    use devkit_core::toolchain::Toolchain;
+
+    let Some(toolchain) = Toolchain::detect(&ctx.repo)? else {
+        ctx.print_info("No .mise.toml or .tool-versions in this project");
+        return Ok(());
+    };
+
+    ctx.print_info(&format!(
+        "Running {} install...",
+        toolchain.manager.binary()
+    ));
+    toolchain.install(&ctx.repo)?;
+    ctx.print_success("✓ Runtimes installed");
+    Ok(())
+}
+
+fn cmd_env_show(ctx: &AppContext) -> Result<()> {
+    use devkit_core::environment::{self, Source};
+
+    let environments = &ctx.config.global.environments;
+    let (active, source) = environment::resolve(&ctx.repo, environments);
+    let source = match source {
+        Source::Variable => "from DEVKIT_ENV",
+        Source::Saved => "set with devkit env use",
+        Source::Default => "default",
+    };
+
+    ctx.print_header(&format!("Environment: {} ({})", active, source));
+    println!();
+    for name in &environments.available {
+        let marker = if *name == active { "▸" } else { " " };
+        println!("  {} {}", marker, name);
+    }
+    println!();
+
+    let overlay = environment::overlay_path(&ctx.repo, &active);
+    if overlay.exists() {
+        println!("Config overlay: {}", overlay.display());
+    }
+    let env_files: Vec<String> = environment::env_files(&active)
+        .into_iter()
+        .filter(|file| ctx.repo.join(file).exists())
+        .collect();
+    if !env_files.is_empty() {
+        println!("Env files:      {}", env_files.join(", "));
+    }
+    let compose_files = devkit_core::validation::compose_files(&ctx.repo, &ctx.config.global);
+    if !compose_files.is_empty() {
+        println!("Compose files:  {}", compose_files.join(", "));
+    }
+    if let Some(settings) = environments.current_settings() {
+        for (label, value) in [
+            ("Pulumi stack:  ", &settings.pulumi_stack),
+            ("k8s context:   ", &settings.k8s_context),
+            ("Secrets:       ", &settings.secrets),
+        ] {
+            if let Some(value) = value {
+                println!("{} {}", label, value);
+            }
+        }
+        if !settings.helm_values.is_empty() {
+            println!("Helm values:    {}", settings.helm_values.join(", "));
         }
     }
+    Ok(())
+}
 
+fn cmd_env_use(ctx: &AppContext, name: Option<&str>) -> Result<()> {
+    let environments = &ctx.config.global.environments;
+    if let Some(name) = name {
+        if !environments.available.iter().any(|env| env == name) {
+            anyhow::bail!(
+                "Unknown environment '{}' (available: {})",
+                name,
+                environments.available.join(", ")
+            );
+        }
+    }
+
+    devkit_core::environment::save(&ctx.repo, name)?;
+    ctx.print_success(&format!(
+        "✓ Using environment {}",
+        name.unwrap_or(&environments.default)
+    ));
+    if std::env::var("DEVKIT_ENV").is_ok_and(|env| !env.is_empty()) {
+        ctx.print_warning("DEVKIT_ENV is set and overrides this in the current shell");
+    }
     Ok(())
 }
 
@@ -536,6 +2138,67 @@ fn resolve_aliases(cli: &mut Cli, ctx: &AppContext) {
     }
 }
 
+/// The invocation to record for a CLI run, without global flags
+///
+/// The menu records its items itself; `history` and `completions` aren't recorded.
+fn history_command(matches: &clap::ArgMatches) -> Option<String> {
+    match matches.subcommand_name() {
+        None | Some("history") | Some("completions") | Some("pin") | Some("unpin") => None,
+        Some(_) => {
+            let args: Vec<String> = std::env::args()
+                .skip(1)
+                .filter(|arg| !matches!(arg.as_str(), "-q" | "--quiet" | "--skip-broken"))
+                .collect();
+            Some(args.join(" "))
+        }
+    }
+}
+
+/// Record a finished command in the history, and notify if it was slow
+///
+/// The package is the one containing the working directory. Both are best-effort:
+/// failing to write history doesn't fail the command.
+fn command_finished(
+    ctx: &AppContext,
+    command: String,
+    source: HistorySource,
+    started: Instant,
+    result: &Result<()>,
+) {
+    let package = std::env::current_dir()
+        .ok()
+        .and_then(|dir| ctx.config.package_at(&dir).map(str::to_string));
+    // Matches devkit's own exit code
+    let exit_code = if result.is_ok() { 0 } else { 1 };
+    let entry = HistoryEntry::finished(command, source, package, started.elapsed(), exit_code);
+
+    notify_finished(ctx, &entry);
+    if let Err(e) = devkit_core::history::add_to_history(entry) {
+        tracing::debug!("Failed to record history: {:#}", e);
+    }
+}
+
+/// Desktop notification for a command that ran longer than `[notifications]`
+/// `min_duration_secs`, e.g. "cmd build failed in api after 3m 12s"
+fn notify_finished(ctx: &AppContext, entry: &HistoryEntry) {
+    let settings = &ctx.config.global.notifications;
+    let duration_ms = entry.duration_ms.unwrap_or_default();
+    if !settings.enabled || duration_ms < settings.min_duration_secs * 1000 {
+        return;
+    }
+
+    let outcome = if entry.success { "finished" } else { "failed" };
+    let mut body = format!("{} {}", entry.command, outcome);
+    if let Some(package) = &entry.package {
+        body.push_str(&format!(" in {}", package));
+    }
+    body.push_str(&format!(" after {}", format_ms(duration_ms)));
+
+    if let Err(e) = devkit_core::utils::desktop_notify(&ctx.config.global.project.name, &body) {
+        tracing::debug!("Failed to show notification: {:#}", e);
+    }
+}
+
 fn cmd_history(ctx: &AppContext, search: Option<&str>) -> Result<()> {
     ctx.print_header("Command History");
     println!();
@@ -556,7 +2219,26 @@ fn cmd_history(ctx: &AppContext, search: Option<&str>) -> Result<()> {
             .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
             .unwrap_or_else(|| "Unknown".to_string());
 
-        println!("{} {} - {}", status, timestamp, entry.command);
+        let details: Vec<String> = [
+            entry.duration_ms.map(format_ms),
+            entry.package.clone(),
+            entry.source.map(|source| source.to_string()),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+
+        if details.is_empty() {
+            println!("{} {} - {}", status, timestamp, entry.command);
+        } else {
+            println!(
+                "{} {} - {} ({})",
+                status,
+                timestamp,
+                entry.command,
+                details.join(", ")
+            );
+        }
     }
 
     println!();
@@ -564,3 +2246,465 @@ fn cmd_history(ctx: &AppContext, search: Option<&str>) -> Result<()> {
 
     Ok(())
 }
+
+fn cmd_history_stats(ctx: &AppContext) -> Result<()> {
+    use devkit_core::history::{command_stats, slowest_commands, CommandStats};
+
+    ctx.print_header("Command Stats");
+    println!();
+
+    let history = devkit_core::history::load_history()?;
+    if history.is_empty() {
+        ctx.print_info("No command history found");
+        return Ok(());
+    }
+
+    let stats = command_stats(&history);
+    let width = stats
+        .iter()
+        .map(|s| s.command.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max(7);
+
+    let table = |title: &str, rows: Vec<&CommandStats>| {
+        ctx.print_info(title);
+        println!(
+            "  {:<width$}  {:>5}  {:>9}  {:>8}",
+            "Command",
+            "Runs",
+            "Avg time",
+            "Failures",
+            width = width
+        );
+        for stat in rows.into_iter().take(10) {
+            let avg = stat
+                .avg_duration
+                .map(|d| format_ms(d.as_millis() as u64))
+                .unwrap_or_else(|| "-".to_string());
+            let failures = format!("{:.0}%", stat.failure_rate() * 100.0);
+            let failures = if stat.failures > 0 {
+                console::style(failures).red()
+            } else {
+                console::style(failures).dim()
+            };
+
+            println!(
+                "  {:<width$}  {:>5}  {:>9}  {:>8}",
+                stat.command,
+                stat.runs,
+                avg,
+                failures,
+                width = width
+            );
+        }
+        println!();
+    };
+    table("Most run", stats.iter().collect());
+    table("Slowest", slowest_commands(&stats));
+
+    ctx.print_info(&format!(
+        "{} runs of {} commands",
+        history.len(),
+        stats.len()
+    ));
+
+    Ok(())
+}
+
+/// Human-readable duration: 850ms, 12.3s, 4m 05s
+fn format_ms(ms: u64) -> String {
+    match ms {
+        0..=999 => format!("{}ms", ms),
+        1_000..=59_999 => format!("{:.1}s", ms as f64 / 1000.0),
+        _ => format!("{}m {:02}s", ms / 60_000, (ms % 60_000) / 1000),
+    }
+}
+
+/// Runs an action by id with extra arguments
+type RunAction = Box<dyn Fn(&AppContext, &str, &[String]) -> Result<()>>;
+
+/// An extension `devkit run` can list and run actions from
+struct RunnableExtension {
+    name: String,
+    version: String,
+    /// (id, description)
+    actions: Vec<(String, String)>,
+    run: RunAction,
+}
+
+fn runnable_extensions(ctx: &AppContext) -> Result<Vec<RunnableExtension>> {
+    use devkit_core::extension_loader::discover_external_extensions;
+
+    let mut runnable = Vec::new();
+    let skip_broken = ctx.config.global.extensions.skip_broken;
+    for ext in discover_external_extensions(&ctx.repo)?.into_loaded(skip_broken)? {
+        let config = ext.config();
+        runnable.push(RunnableExtension {
+            name: config.name.clone(),
+            version: config.version.clone().unwrap_or_default(),
+            actions: ext
+                .actions(ctx)
+                .into_iter()
+                .map(|action| {
+                    let description = action.description.unwrap_or(action.label);
+                    (action.id, description)
+                })
+                .collect(),
+            run: Box::new(move |ctx, id, args| ext.run_action(ctx, id, args)),
+        });
+    }
+
+    #[cfg(feature = "wasm")]
+    for plugin in devkit_ext_wasm::load_wasm_plugins(&ctx.repo) {
+        let items = plugin.items(ctx).unwrap_or_else(|e| {
+            ctx.print_warning(&format!("{}: {:#}", plugin.name(), e));
+            Vec::new()
+        });
+        runnable.push(RunnableExtension {
+            name: plugin.name().to_string(),
+            version: "(wasm)".to_string(),
+            actions: items
+                .into_iter()
+                .map(|item| (item.id, item.label))
+                .collect(),
+            run: Box::new(move |ctx, id, args| plugin.execute(ctx, id, args)),
+        });
+    }
+
+    #[cfg(feature = "script")]
+    if !ctx.config.global.extensions.is_disabled("script") {
+        for script in devkit_ext_script::load_scripts(&ctx.repo) {
+            runnable.push(RunnableExtension {
+                name: script.name().to_string(),
+                version: "(script)".to_string(),
+                actions: script
+                    .functions()
+                    .iter()
+                    .map(|f| (f.name.clone(), f.label.clone()))
+                    .collect(),
+                run: Box::new(move |ctx, id, args| script.call(ctx, id, args)),
+            });
+        }
+    }
+
+    runnable.retain(|ext| !ctx.config.global.extensions.is_disabled(&ext.name));
+    Ok(runnable)
+}
+
+fn cmd_run_extension(ctx: &AppContext, action: Option<&str>, args: &[String]) -> Result<()> {
+    let extensions = runnable_extensions(ctx)?;
+
+    let Some(action) = action else {
+        if extensions.is_empty() {
+            ctx.print_info(
+                "No external extensions found in .dev/extensions or ~/.config/devkit/extensions",
+            );
+            return Ok(());
+        }
+        extensions.iter().for_each(print_actions);
+        return Ok(());
+    };
+
+    let (ext_name, action_id) = match action.split_once(':') {
+        Some((ext, id)) => (Some(ext), id),
+        None => (None, action),
+    };
+    let matches: Vec<_> = extensions
+        .iter()
+        .filter(|ext| ext_name.is_none_or(|name| ext.name == name))
+        .filter(|ext| ext.actions.iter().any(|(id, _)| id == action_id))
+        .collect();
+
+    match matches.as_slice() {
+        [ext] => (ext.run)(ctx, action_id, args),
+        [] => Err(anyhow::anyhow!(
+            "No extension action '{}' (run `devkit run` to list actions)",
+            action
+        )),
+        _ => Err(anyhow::anyhow!(
+            "Action '{}' is defined by several extensions ({}); use <extension>:{}",
+            action_id,
+            matches
+                .iter()
+                .map(|ext| ext.name.as_str())
+                .collect::<Vec<_>>()
+                .join(", "),
+            action_id
+        )),
+    }
+}
+
+fn cmd_discover(ctx: &AppContext, refresh: bool) -> Result<()> {
+    use devkit_core::discovery_cache;
+    use devkit_core::extension_loader::discover_external_extensions;
+
+    if refresh && discovery_cache::clear(&ctx.repo)? {
+        ctx.print_info("Cleared .dev/cache/commands.json");
+    }
+
+    let skip_broken = ctx.config.global.extensions.skip_broken;
+    let extensions: Vec<_> = discover_external_extensions(&ctx.repo)?
+        .into_loaded(skip_broken)?
+        .into_iter()
+        .filter(|ext| ext.config().discover.is_some())
+        .filter(|ext| !ctx.config.global.extensions.is_disabled(&ext.config().name))
+        .collect();
+    if extensions.is_empty() {
+        ctx.print_info("No external extensions discover actions");
+        return Ok(());
+    }
+
+    ctx.print_header("Discovered actions");
+    for ext in &extensions {
+        let name = &ext.config().name;
+        let started = Instant::now();
+        match ext.discover_cached(ctx) {
+            Ok((actions, true)) => {
+                ctx.print_info(&format!("  {}: {} action(s) (cached)", name, actions.len()))
+            }
+            Ok((actions, false)) => ctx.print_success(&format!(
+                "✓ {}: {} action(s) in {}",
+                name,
+                actions.len(),
+                format_ms(started.elapsed().as_millis() as u64)
+            )),
+            Err(e) => ctx.print_error(&format!("✗ {}: {:#}", name, e)),
+        }
+    }
+    Ok(())
+}
+
+fn print_actions(ext: &RunnableExtension) {
+    println!("{} {}", ext.name, ext.version);
+    for (id, description) in &ext.actions {
+        println!("  {}:{}  {}", ext.name, id, description);
+    }
+}
+
+/// `devkit ext <name> [action] [args]...`
+fn cmd_ext_run(ctx: &AppContext, args: &[String]) -> Result<()> {
+    let Some((name, args)) = args.split_first() else {
+        return Ok(());
+    };
+    if let Some((action, args)) = args.split_first() {
+        return cmd_run_extension(ctx, Some(&format!("{}:{}", name, action)), args);
+    }
+
+    let extensions = runnable_extensions(ctx)?;
+    let ext = extensions
+        .iter()
+        .find(|ext| &ext.name == name)
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "No extension '{}' (run `devkit ext list` to list extensions)",
+                name
+            )
+        })?;
+    print_actions(ext);
+    Ok(())
+}
+
+fn cmd_ext(ctx: &AppContext, action: ExtAction) -> Result<()> {
+    use devkit_core::extension_install::{self, InstallOptions};
+
+    match action {
+        ExtAction::Install {
+            source,
+            git_ref,
+            force,
+        } => {
+            ctx.print_header(&format!("Installing extension from {}", source));
+            let (name, installed) = extension_install::install_extension(
+                &ctx.repo,
+                &source,
+                &InstallOptions { git_ref, force },
+            )?;
+            ctx.print_success(&format!(
+                "✓ Installed {} {}",
+                name,
+                installed.version.as_deref().unwrap_or_default()
+            ));
+            ctx.print_info(&format!(
+                "Run `devkit run {}:<action>` or use the menu",
+                name
+            ));
+        }
+        ExtAction::Update { name } => {
+            let names: Vec<String> = match name {
+                Some(name) => vec![name],
+                None => extension_install::load_installed(&ctx.repo)?
+                    .into_keys()
+                    .collect(),
+            };
+            if names.is_empty() {
+                ctx.print_info("No extensions installed with `devkit ext install`");
+                return Ok(());
+            }
+
+            let mut failed = 0;
+            for name in &names {
+                match extension_install::update_extension(&ctx.repo, name) {
+                    Ok(outcome) if outcome.changed() => ctx.print_success(&format!(
+                        "✓ Updated {} {} → {}",
+                        name,
+                        describe_install(&outcome.previous),
+                        describe_install(&outcome.current)
+                    )),
+                    Ok(outcome) => ctx.print_info(&format!(
+                        "{} is up to date ({})",
+                        name,
+                        describe_install(&outcome.current)
+                    )),
+                    Err(e) => {
+                        failed += 1;
+                        ctx.print_error(&format!("✗ {}: {:#}", name, e));
+                    }
+                }
+            }
+            if failed > 0 {
+                return Err(anyhow::anyhow!("{} extension(s) failed to update", failed));
+            }
+        }
+        ExtAction::Remove { name } => {
+            extension_install::remove_extension(&ctx.repo, &name)?;
+            ctx.print_success(&format!("✓ Removed {}", name));
+        }
+        ExtAction::Run(args) => cmd_ext_run(ctx, &args)?,
+        ExtAction::List => {
+            use devkit_core::external_extension::check_api_version;
+            use devkit_core::ExtensionStatus;
+
+            // Broken external extensions are listed separately below
+            let registry = full_registry(ctx, true)?;
+            let statuses = registry.statuses(ctx);
+            let width = statuses
+                .iter()
+                .map(|(name, _)| name.len())
+                .max()
+                .unwrap_or(0);
+
+            ctx.print_header("Extensions");
+            for (name, status) in &statuses {
+                let (mark, detail) = match status {
+                    ExtensionStatus::Available => ("✓", "available".to_string()),
+                    ExtensionStatus::Disabled => ("-", "disabled in [extensions]".to_string()),
+                    ExtensionStatus::Unavailable(reason) => (
+                        "✗",
+                        reason
+                            .clone()
+                            .unwrap_or_else(|| "not available in this project".to_string()),
+                    ),
+                };
+                println!("  {} {:width$}  {}", mark, name, detail, width = width);
+            }
+
+            let discovered =
+                devkit_core::extension_loader::discover_external_extensions(&ctx.repo)?;
+            if discovered.extensions.is_empty() && discovered.broken.is_empty() {
+                return Ok(());
+            }
+            println!();
+            ctx.print_header("External extensions");
+            let installed = extension_install::load_installed(&ctx.repo)?;
+            for ext in &discovered.extensions {
+                let config = ext.config();
+                let source = match installed.get(&config.name) {
+                    Some(entry) => entry.source.clone(),
+                    None => ext.extension_dir().display().to_string(),
+                };
+                println!(
+                    "  {} {}  {} action(s)  {}",
+                    config.name,
+                    config.version.as_deref().unwrap_or_default(),
+                    ext.actions(ctx).len(),
+                    source
+                );
+                if let Ok(Some(warning)) = check_api_version(config) {
+                    println!("    ⚠ {}", warning);
+                }
+            }
+            for (path, e) in &discovered.broken {
+                println!("  ✗ {}  {:#}", path.display(), e);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Version and short commit of an installed extension
+fn describe_install(installed: &devkit_core::extension_install::InstalledExtension) -> String {
+    let version = installed.version.as_deref().unwrap_or("unversioned");
+    match &installed.rev {
+        Some(rev) => format!("{} ({})", version, &rev[..rev.len().min(8)]),
+        None => version.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use devkit_core::{Config, Extension, MenuItem};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    /// Records its prerun hook and `devkit fake` runs
+    struct FakeExtension(Rc<RefCell<Vec<&'static str>>>);
+
+    impl Extension for FakeExtension {
+        fn name(&self) -> &str {
+            "fake"
+        }
+
+        fn is_available(&self, _ctx: &AppContext) -> bool {
+            true
+        }
+
+        fn menu_items(&self, _ctx: &AppContext) -> Vec<MenuItem> {
+            Vec::new()
+        }
+
+        fn subcommands(&self) -> Vec<clap::Command> {
+            vec![clap::Command::new("fake")]
+        }
+
+        fn handle_cli(
+            &self,
+            _ctx: &AppContext,
+            name: &str,
+            _matches: &clap::ArgMatches,
+        ) -> Option<devkit_core::Result<()>> {
+            (name == "fake").then(|| {
+                self.0.borrow_mut().push("command");
+                Ok(())
+            })
+        }
+
+        fn prerun(&self, _ctx: &AppContext) -> devkit_core::Result<()> {
+            self.0.borrow_mut().push("prerun");
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_extension_subcommand_runs_prerun_hooks() {
+        let calls = Rc::new(RefCell::new(Vec::new()));
+        let mut extensions = ExtensionRegistry::new();
+        extensions.register(Box::new(FakeExtension(calls.clone())));
+        let mut hooks = ExtensionRegistry::new();
+        hooks.register(Box::new(FakeExtension(calls.clone())));
+        let ctx = AppContext {
+            repo: std::env::temp_dir(),
+            quiet: true,
+            config: Config::default(),
+            features: Default::default(),
+        };
+
+        let matches = cli_command(&extensions)
+            .try_get_matches_from(["devkit", "fake"])
+            .unwrap();
+        dispatch_with_hooks(&ctx, &extensions, &hooks, &matches).unwrap();
+        assert_eq!(*calls.borrow(), ["prerun", "command"]);
+    }
+}
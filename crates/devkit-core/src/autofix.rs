@@ -0,0 +1,148 @@
+//! Fixes for `devkit config validate --fix`
+//!
+//! Rewrites `.dev/config.toml` for the validation problems that have an obvious fix,
+//! keeping comments and formatting:
+//!
+//! - Services sharing a port: the first one in the file keeps it, the others move to
+//!   the next port no service uses
+//! - `[workspaces] exclude` entries that match no package directory are removed
+
+use anyhow::{Context, Result};
+use std::collections::HashSet;
+use std::fs;
+use toml_edit::{DocumentMut, Item, Value};
+
+use crate::config::Config;
+use crate::validation::unknown_excludes;
+
+/// Fix `.dev/config.toml`; returns the changes made, or that would be made when
+/// `write` is false
+pub fn fix_config(config: &Config, write: bool) -> Result<Vec<String>> {
+    let path = config.repo_root.join(".dev/config.toml");
+    let Ok(contents) = fs::read_to_string(&path) else {
+        return Ok(Vec::new());
+    };
+    let mut doc: DocumentMut = contents
+        .parse()
+        .with_context(|| format!("Invalid TOML in {}", path.display()))?;
+
+    let changes = fix_document(&mut doc, &unknown_excludes(config));
+    if write && !changes.is_empty() {
+        fs::write(&path, doc.to_string())
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+    }
+    Ok(changes)
+}
+
+/// Fix a `.dev/config.toml` document; returns the changes made
+pub fn fix_document(doc: &mut DocumentMut, unknown_excludes: &[&str]) -> Vec<String> {
+    let mut changes = fix_duplicate_ports(doc);
+
+    if let Some(excludes) = doc
+        .get_mut("workspaces")
+        .and_then(|workspaces| workspaces.get_mut("exclude"))
+        .and_then(Item::as_array_mut)
+    {
+        excludes.retain(|exclude| {
+            let unknown = exclude
+                .as_str()
+                .is_some_and(|exclude| unknown_excludes.contains(&exclude));
+            if unknown {
+                changes.push(format!(
+                    "Removed '{}' from [workspaces] exclude (no package directory matches it)",
+                    exclude.as_str().unwrap_or_default()
+                ));
+            }
+            !unknown
+        });
+    }
+
+    changes
+}
+
+fn fix_duplicate_ports(doc: &mut DocumentMut) -> Vec<String> {
+    let mut changes = Vec::new();
+    let Some(services) = doc.get_mut("services").and_then(Item::as_table_like_mut) else {
+        return changes;
+    };
+
+    let ports: Vec<(String, u16)> = services
+        .iter()
+        .filter_map(|(name, item)| {
+            let port = u16::try_from(item.as_integer()?).ok()?;
+            Some((name.to_string(), port))
+        })
+        .collect();
+    let mut used: HashSet<u16> = ports.iter().map(|(_, port)| *port).collect();
+    let mut owners: Vec<(u16, &str)> = Vec::new();
+
+    for (name, port) in &ports {
+        let Some((_, owner)) = owners.iter().find(|(taken, _)| taken == port) else {
+            owners.push((*port, name));
+            continue;
+        };
+        let Some(free) = (*port..=u16::MAX).find(|candidate| !used.contains(candidate)) else {
+            continue;
+        };
+        used.insert(free);
+
+        if let Some(value) = services.get_mut(name).and_then(Item::as_value_mut) {
+            let decor = value.decor().clone();
+            *value = Value::from(i64::from(free));
+            *value.decor_mut() = decor;
+        }
+        changes.push(format!(
+            "Moved service '{}' from port {} to {} ({} is {}'s)",
+            name, port, free, port, owner
+        ));
+    }
+
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fix_document() {
+        let mut doc: DocumentMut = r#"[services]
+api = 3000
+web = 3000 # the frontend
+admin = 3001
+docs = 3000
+
+[workspaces]
+packages = ["packages/*"]
+exclude = ["legacy", "old-app"]
+"#
+        .parse()
+        .unwrap();
+
+        let changes = fix_document(&mut doc, &["old-app"]);
+        assert_eq!(
+            changes,
+            [
+                "Moved service 'web' from port 3000 to 3002 (3000 is api's)",
+                "Moved service 'docs' from port 3000 to 3003 (3000 is api's)",
+                "Removed 'old-app' from [workspaces] exclude (no package directory matches it)",
+            ]
+        );
+        assert_eq!(
+            doc.to_string(),
+            r#"[services]
+api = 3000
+web = 3002 # the frontend
+admin = 3001
+docs = 3003
+
+[workspaces]
+packages = ["packages/*"]
+exclude = ["legacy"]
+"#
+        );
+
+        // Nothing to fix
+        assert!(fix_document(&mut doc, &[]).is_empty());
+    }
+}
@@ -4,6 +4,27 @@
 //! - `.dev/config.toml` - Global configuration shared across all packages
 //! - `packages/*/dev.toml` - Package-specific configuration (optional)
 //!
+//! `.dev/config.<env>.toml` overrides `.dev/config.toml` for the active environment (see
+//! [`crate::environment`]).
+//!
+//! `[snippets]` in `.dev/config.toml` holds command strings that `[cmd.<name>]` entries
+//! reuse with `snippet = "<name>"`, filling in their `{param}` placeholders:
+//!
+//! ```toml
+//! # .dev/config.toml
+//! [snippets]
+//! eslint = "npx eslint --max-warnings 0 --cache --cache-location .cache/eslint {paths}"
+//! exec = { run = "docker compose exec -T {service} {cmd}", params = { service = "api" } }
+//!
+//! # packages/web/dev.toml
+//! [cmd.lint]
+//! snippet = "eslint"
+//! params = { paths = "src" }
+//! ```
+//!
+//! `[repos]` in `.dev/config.toml` adds the packages of sibling repositories, keyed
+//! `<repo>/<package>`, so several repos can be worked on as one.
+//!
 //! Package names are derived from existing configs:
 //! - Rust packages: Cargo.toml `[package] name = "..."`
 //! - JS packages: package.json `"name": "..."`
@@ -11,6 +32,7 @@
 //! Packages declare capabilities via TOML sections:
 //! - `[database]` - Package has migrations/seeds
 //! - `[mobile]` - Package is a mobile app
+//! - `[proto]` - Package has protobuf definitions to generate code from
 //! - `[cmd]` - Package commands
 
 #![allow(dead_code)]
@@ -38,6 +60,28 @@ pub struct GlobalConfig {
     pub defaults: DefaultsConfig,
     pub features: FeaturesConfig,
     pub aliases: AliasesConfig,
+    pub monitoring: MonitoringConfig,
+    pub tunnel: TunnelsConfig,
+    pub remote: RemotesConfig,
+    pub watch: WatchersConfig,
+    pub run: RunConfig,
+    pub pipeline: PipelinesConfig,
+    pub docker: DockerConfig,
+    pub localstack: LocalstackConfig,
+    pub aws: AwsConfig,
+    pub k8s: K8sConfig,
+    pub ecs: EcsConfig,
+    pub secrets: SecretsConfig,
+    pub bench: BenchConfig,
+    pub test: TestConfig,
+    pub hooks: HooksConfig,
+    pub extensions: ExtensionsConfig,
+    pub output: OutputConfig,
+    pub notifications: NotificationsConfig,
+    pub repos: ReposConfig,
+    pub snippets: SnippetsConfig,
+    /// Extension-specific settings (`[ext.<name>]`), passed to external extensions
+    pub ext: HashMap<String, toml::Value>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -115,7 +159,7 @@ fn default_pr_base() -> String {
     "main".to_string()
 }
 
-#[derive(Debug, Deserialize, Default)]
+#[derive(Debug, Deserialize)]
 #[serde(default)]
 pub struct EnvironmentsConfig {
     /// Available environments
@@ -124,6 +168,58 @@ pub struct EnvironmentsConfig {
     /// Default environment
     #[serde(default = "default_env")]
     pub default: String,
+    /// Per-environment settings (`[environments.<name>]`)
+    #[serde(flatten)]
+    pub settings: HashMap<String, EnvironmentSettings>,
+    /// Environment in use, resolved when the config is loaded
+    #[serde(skip)]
+    pub active: String,
+}
+
+impl Default for EnvironmentsConfig {
+    fn default() -> Self {
+        Self {
+            available: default_environments(),
+            default: default_env(),
+            settings: HashMap::new(),
+            active: String::new(),
+        }
+    }
+}
+
+impl EnvironmentsConfig {
+    /// The active environment (the default before the config is loaded)
+    pub fn current(&self) -> &str {
+        if self.active.is_empty() {
+            &self.default
+        } else {
+            &self.active
+        }
+    }
+
+    /// Settings for the active environment
+    pub fn current_settings(&self) -> Option<&EnvironmentSettings> {
+        self.settings.get(self.current())
+    }
+}
+
+/// What switching to an environment changes
+#[derive(Debug, Deserialize, Default, Clone)]
+#[serde(default)]
+pub struct EnvironmentSettings {
+    /// Pulumi stack (default: the environment name when `Pulumi.<env>.yaml` exists)
+    pub pulumi_stack: Option<String>,
+    /// kubectl context
+    pub k8s_context: Option<String>,
+    /// Helm values files, relative to each chart (default: `values-<env>.yaml` when
+    /// the chart has one)
+    pub helm_values: Vec<String>,
+    /// Secrets source: Doppler config, AWS Secrets Manager secret id, or env file
+    /// for `op inject` (`[secrets.paths]` takes precedence)
+    pub secrets: Option<String>,
+    /// Compose file layered over the others (default: `<base>.<env>.yml` next to the
+    /// first compose file, e.g. `docker-compose.staging.yml`, when it exists)
+    pub compose_override: Option<String>,
 }
 
 fn default_environments() -> Vec<String> {
@@ -134,6 +230,104 @@ fn default_env() -> String {
     "dev".to_string()
 }
 
+/// Where `devkit secrets` pulls from (`[secrets]`)
+#[derive(Debug, Deserialize, Default)]
+#[serde(default)]
+pub struct SecretsConfig {
+    /// Provider to use (default: the first CLI found of 1Password, Doppler, AWS;
+    /// Vault is only used when chosen)
+    pub provider: Option<SecretsProviderKind>,
+    /// Secret path when the environment doesn't set one
+    pub path: Option<String>,
+    /// Secret path per environment (`[secrets.paths]`)
+    pub paths: HashMap<String, String>,
+    /// HashiCorp Vault settings (`[secrets.vault]`)
+    pub vault: VaultConfig,
+}
+
+impl SecretsConfig {
+    /// Secret path for an environment: `[secrets.paths]`, then the environment's
+    /// `secrets` setting, then `path`
+    pub fn path_for<'a>(
+        &'a self,
+        env: &str,
+        environments: &'a EnvironmentsConfig,
+    ) -> Option<&'a str> {
+        self.paths
+            .get(env)
+            .map(String::as_str)
+            .or_else(|| {
+                environments
+                    .settings
+                    .get(env)
+                    .and_then(|settings| settings.secrets.as_deref())
+            })
+            .or(self.path.as_deref())
+    }
+}
+
+/// Supported secrets providers
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SecretsProviderKind {
+    Aws,
+    #[serde(rename = "1password", alias = "op")]
+    OnePassword,
+    Doppler,
+    Vault,
+}
+
+impl SecretsProviderKind {
+    /// CLI binary for this provider
+    pub fn binary(&self) -> &'static str {
+        match self {
+            SecretsProviderKind::Aws => "aws",
+            SecretsProviderKind::OnePassword => "op",
+            SecretsProviderKind::Doppler => "doppler",
+            SecretsProviderKind::Vault => "vault",
+        }
+    }
+}
+
+/// HashiCorp Vault KV v2 (`[secrets.vault]`)
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct VaultConfig {
+    /// Server address (default: `VAULT_ADDR`)
+    pub address: Option<String>,
+    /// KV v2 secrets engine mount
+    pub mount: String,
+    /// How to log in
+    pub auth: VaultAuth,
+    /// AppRole role id
+    pub role_id: Option<String>,
+    /// Env var holding the AppRole secret id
+    pub secret_id_env: String,
+}
+
+impl Default for VaultConfig {
+    fn default() -> Self {
+        Self {
+            address: None,
+            mount: "secret".to_string(),
+            auth: VaultAuth::default(),
+            role_id: None,
+            secret_id_env: "VAULT_SECRET_ID".to_string(),
+        }
+    }
+}
+
+/// Vault login methods
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum VaultAuth {
+    /// `VAULT_TOKEN` or the token `vault login` saved
+    #[default]
+    Token,
+    /// `role_id` plus the secret id from `secret_id_env`
+    AppRole,
+}
+
 /// Services configuration - maps service name to port
 #[derive(Debug, Deserialize, Default)]
 #[serde(default)]
@@ -148,6 +342,27 @@ impl ServicesConfig {
     pub fn get_port(&self, service: &str, default: u16) -> u16 {
         self.ports.get(service).copied().unwrap_or(default)
     }
+
+    /// Replace `{services.<name>}` placeholders with the service's port
+    ///
+    /// Unknown services are left as-is (validation warns about them).
+    pub fn expand(&self, text: &str) -> String {
+        let mut expanded = text.to_string();
+        for name in service_references(text) {
+            if let Some(port) = self.ports.get(name) {
+                expanded = expanded.replace(&format!("{{services.{}}}", name), &port.to_string());
+            }
+        }
+        expanded
+    }
+}
+
+/// Service names referenced as `{services.<name>}` in a command or URL
+pub fn service_references(text: &str) -> Vec<&str> {
+    text.split("{services.")
+        .skip(1)
+        .filter_map(|rest| rest.split_once('}').map(|(name, _)| name))
+        .collect()
 }
 
 /// Quick access URLs configuration
@@ -198,6 +413,9 @@ fn default_release_list_count() -> u32 {
 }
 
 /// Feature flags for kitchen sink CLI
+///
+/// No longer read; superseded by `[extensions] disabled`. `devkit init --upgrade`
+/// migrates it.
 #[derive(Debug, Deserialize)]
 #[serde(default)]
 pub struct FeaturesConfig {
@@ -232,6 +450,759 @@ impl Default for FeaturesConfig {
     }
 }
 
+/// Monitoring stack configuration
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct MonitoringConfig {
+    /// Components included in the generated monitoring stack
+    pub components: Vec<String>,
+    /// Compose file for the monitoring stack (relative to repo root)
+    pub compose_file: String,
+    /// Additional compose files layered on top via `-f` (e.g., local overrides)
+    pub override_files: Vec<String>,
+    /// Compose project name (defaults to "<repo dir>-monitoring")
+    pub project_name: Option<String>,
+}
+
+impl Default for MonitoringConfig {
+    fn default() -> Self {
+        Self {
+            components: default_monitoring_components(),
+            compose_file: "docker-compose.monitoring.yml".to_string(),
+            override_files: Vec::new(),
+            project_name: None,
+        }
+    }
+}
+
+fn default_monitoring_components() -> Vec<String> {
+    vec![
+        "prometheus".to_string(),
+        "grafana".to_string(),
+        "loki".to_string(),
+        "tempo".to_string(),
+    ]
+}
+
+/// Tunnel definitions keyed by tunnel name (`[tunnel.api]`)
+#[derive(Debug, Deserialize, Default)]
+#[serde(default)]
+pub struct TunnelsConfig {
+    /// Tunnel entries keyed by name
+    #[serde(flatten)]
+    pub entries: HashMap<String, TunnelEntry>,
+}
+
+impl TunnelsConfig {
+    /// Get a tunnel by name
+    pub fn get(&self, name: &str) -> Option<&TunnelEntry> {
+        self.entries.get(name)
+    }
+
+    /// Get tunnel names in sorted order
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.entries.keys().map(|s| s.as_str()).collect();
+        names.sort();
+        names
+    }
+
+    /// Check if any tunnels are defined
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// A tunnel exposing a local port publicly
+#[derive(Debug, Deserialize, Clone)]
+pub struct TunnelEntry {
+    /// Local port to expose
+    pub port: u16,
+    /// Tunnel provider
+    #[serde(default)]
+    pub provider: TunnelProvider,
+    /// Requested subdomain (provider permitting)
+    pub subdomain: Option<String>,
+    /// Env vars in `.env.local` set to the public URL once the tunnel is up
+    #[serde(default)]
+    pub env: Vec<String>,
+    /// Compose services recreated after the env vars change
+    #[serde(default)]
+    pub restart: Vec<String>,
+    /// cloudflared named tunnel to run instead of a quick tunnel
+    pub tunnel_name: Option<String>,
+    /// Hostname routed to the named tunnel (`{user}` expands to the current user)
+    pub hostname: Option<String>,
+    /// Credentials file for the named tunnel (defaults to cloudflared's lookup)
+    pub credentials_file: Option<String>,
+}
+
+impl TunnelEntry {
+    /// A default-provider tunnel for a local port
+    pub fn for_port(port: u16) -> Self {
+        Self {
+            port,
+            provider: TunnelProvider::default(),
+            subdomain: None,
+            env: Vec::new(),
+            restart: Vec::new(),
+            tunnel_name: None,
+            hostname: None,
+            credentials_file: None,
+        }
+    }
+}
+
+/// Supported tunnel providers
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TunnelProvider {
+    #[default]
+    Ngrok,
+    Cloudflared,
+}
+
+impl TunnelProvider {
+    /// CLI binary for this provider
+    pub fn binary(&self) -> &'static str {
+        match self {
+            TunnelProvider::Ngrok => "ngrok",
+            TunnelProvider::Cloudflared => "cloudflared",
+        }
+    }
+}
+
+impl std::str::FromStr for TunnelProvider {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "ngrok" => Ok(TunnelProvider::Ngrok),
+            "cloudflared" => Ok(TunnelProvider::Cloudflared),
+            other => Err(format!(
+                "unknown tunnel provider '{}' (expected ngrok or cloudflared)",
+                other
+            )),
+        }
+    }
+}
+
+/// Remote machines for development (`[remote.staging]`)
+#[derive(Debug, Deserialize, Default)]
+#[serde(default)]
+pub struct RemotesConfig {
+    /// Remote entries keyed by name
+    #[serde(flatten)]
+    pub entries: HashMap<String, RemoteEntry>,
+}
+
+impl RemotesConfig {
+    /// Get a remote by name
+    pub fn get(&self, name: &str) -> Option<&RemoteEntry> {
+        self.entries.get(name)
+    }
+
+    /// Get remote names in sorted order
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.entries.keys().map(|s| s.as_str()).collect();
+        names.sort();
+        names
+    }
+}
+
+/// A machine the repo is synced to and commands run on over SSH
+#[derive(Debug, Deserialize, Clone)]
+pub struct RemoteEntry {
+    pub host: String,
+    /// SSH user (default: from ~/.ssh/config or the local user)
+    pub user: Option<String>,
+    /// Directory on the remote the repo is synced to
+    pub path: String,
+    /// Paths to sync, relative to the repo root (`src/**`, `Cargo.toml`); everything
+    /// when empty
+    #[serde(default)]
+    pub sync_patterns: Vec<String>,
+    /// Paths never synced, on top of `.git/` and `.gitignore`d files
+    #[serde(default)]
+    pub excludes: Vec<String>,
+    /// Ports forwarded while connected: `"8080"` or `"<local>:<remote>"`
+    #[serde(default)]
+    pub port_forwards: Vec<String>,
+}
+
+impl RemoteEntry {
+    /// `user@host`, or `host` without a user
+    pub fn destination(&self) -> String {
+        match &self.user {
+            Some(user) => format!("{}@{}", user, self.host),
+            None => self.host.clone(),
+        }
+    }
+
+    /// `port_forwards` as (local, remote) port pairs
+    pub fn forwards(&self) -> std::result::Result<Vec<(u16, u16)>, String> {
+        self.port_forwards
+            .iter()
+            .map(|forward| {
+                let port = |port: &str| {
+                    port.trim()
+                        .parse::<u16>()
+                        .map_err(|_| format!("invalid port forward '{}'", forward))
+                };
+                match forward.split_once(':') {
+                    Some((local, remote)) => Ok((port(local)?, port(remote)?)),
+                    None => port(forward).map(|port| (port, port)),
+                }
+            })
+            .collect()
+    }
+}
+
+/// File watchers keyed by name (`[watch.backend]`)
+#[derive(Debug, Deserialize, Default)]
+#[serde(default)]
+pub struct WatchersConfig {
+    /// Watcher entries keyed by name
+    #[serde(flatten)]
+    pub entries: HashMap<String, WatcherEntry>,
+}
+
+impl WatchersConfig {
+    /// Get a watcher by name
+    pub fn get(&self, name: &str) -> Option<&WatcherEntry> {
+        self.entries.get(name)
+    }
+
+    /// Get watcher names in sorted order
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.entries.keys().map(|s| s.as_str()).collect();
+        names.sort();
+        names
+    }
+}
+
+/// A command rerun when files matching its patterns change
+#[derive(Debug, Deserialize, Clone)]
+pub struct WatcherEntry {
+    /// Globs relative to the repo root (`src/**/*.rs`)
+    pub patterns: Vec<String>,
+    /// Shell command to run
+    pub command: String,
+    /// Milliseconds without changes before the command runs
+    #[serde(default = "default_watch_debounce")]
+    pub debounce: u64,
+    /// Desktop notification when the command finishes
+    #[serde(default)]
+    pub notify: bool,
+    /// Reload connected browser tabs after the command succeeds
+    #[serde(default)]
+    pub reload_browser: bool,
+}
+
+fn default_watch_debounce() -> u64 {
+    300
+}
+
+/// Processes run together by `devkit up`, keyed by name (`[run]`)
+#[derive(Debug, Deserialize, Default)]
+#[serde(default)]
+pub struct RunConfig {
+    #[serde(flatten)]
+    pub processes: HashMap<String, RunEntry>,
+}
+
+/// A process - either a command string or full config
+#[derive(Debug, Deserialize, Clone)]
+#[serde(untagged)]
+pub enum RunEntry {
+    Simple(String),
+    Full {
+        /// Shell command to run
+        command: String,
+        /// Directory to run in, relative to the repo root
+        cwd: Option<String>,
+        /// Start the process again when it exits
+        #[serde(default)]
+        restart: bool,
+    },
+}
+
+impl RunEntry {
+    pub fn command(&self) -> &str {
+        match self {
+            RunEntry::Simple(command) | RunEntry::Full { command, .. } => command,
+        }
+    }
+
+    pub fn cwd(&self) -> Option<&str> {
+        match self {
+            RunEntry::Simple(_) => None,
+            RunEntry::Full { cwd, .. } => cwd.as_deref(),
+        }
+    }
+
+    pub fn restart(&self) -> bool {
+        matches!(self, RunEntry::Full { restart: true, .. })
+    }
+}
+
+/// Pipelines keyed by name (`[pipeline.ci]`)
+#[derive(Debug, Deserialize, Default)]
+#[serde(default)]
+pub struct PipelinesConfig {
+    /// Pipeline entries keyed by name
+    #[serde(flatten)]
+    pub entries: HashMap<String, PipelineEntry>,
+}
+
+impl PipelinesConfig {
+    /// Get a pipeline by name
+    pub fn get(&self, name: &str) -> Option<&PipelineEntry> {
+        self.entries.get(name)
+    }
+
+    /// Get pipeline names in sorted order
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.entries.keys().map(|s| s.as_str()).collect();
+        names.sort();
+        names
+    }
+}
+
+/// Steps run in order by `devkit pipeline <name>`
+#[derive(Debug, Deserialize, Clone)]
+pub struct PipelineEntry {
+    /// Shown when listing pipelines
+    pub description: Option<String>,
+    /// Stop at the first failing step (`devkit pipeline --keep-going` overrides it)
+    #[serde(default = "default_fail_fast")]
+    pub fail_fast: bool,
+    pub steps: Vec<PipelineStep>,
+}
+
+fn default_fail_fast() -> bool {
+    true
+}
+
+/// A pipeline step: `{ cmd = "test" }`, `{ docker = "up -d" }` or `{ run = "./e2e.sh" }`
+#[derive(Debug, Deserialize, Clone)]
+pub struct PipelineStep {
+    /// Label in the output and summary (defaults to the step itself, e.g. `cmd test`)
+    pub name: Option<String>,
+    #[serde(flatten)]
+    pub action: PipelineAction,
+    /// Only run a `cmd` step for these packages
+    #[serde(default)]
+    pub packages: Vec<String>,
+    /// Carry on with the next step when this one fails
+    #[serde(default)]
+    pub continue_on_error: bool,
+}
+
+impl PipelineStep {
+    /// The step's name, or what it runs
+    pub fn label(&self) -> String {
+        match &self.name {
+            Some(name) => name.clone(),
+            None => match &self.action {
+                PipelineAction::Cmd(cmd) => format!("cmd {}", cmd),
+                PipelineAction::Docker(args) => format!("docker {}", args),
+                PipelineAction::Run(command) => command.clone(),
+            },
+        }
+    }
+}
+
+/// What a pipeline step runs
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PipelineAction {
+    /// Package command across the packages defining it
+    Cmd(String),
+    /// `docker compose` arguments, run in the repo root
+    Docker(String),
+    /// Shell command, run in the repo root
+    Run(String),
+}
+
+/// LocalStack AWS emulation (`[localstack]`)
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct LocalstackConfig {
+    /// Compose service running LocalStack
+    pub service: String,
+    /// Edge port published on the host
+    pub port: u16,
+    pub region: String,
+    /// `AWS_ENDPOINT_URL` written for local services (defaults to `http://localhost:<port>`)
+    pub endpoint: Option<String>,
+    /// S3 buckets to create
+    pub s3: Vec<String>,
+    /// SQS queues to create (names ending in `.fifo` become FIFO queues)
+    pub sqs: Vec<String>,
+    /// DynamoDB tables to create, mapped to their string partition key
+    pub dynamodb: HashMap<String, String>,
+}
+
+impl Default for LocalstackConfig {
+    fn default() -> Self {
+        Self {
+            service: "localstack".to_string(),
+            port: 4566,
+            region: "us-east-1".to_string(),
+            endpoint: None,
+            s3: Vec::new(),
+            sqs: Vec::new(),
+            dynamodb: HashMap::new(),
+        }
+    }
+}
+
+impl LocalstackConfig {
+    /// Endpoint local services reach LocalStack at
+    pub fn endpoint_url(&self) -> String {
+        self.endpoint
+            .clone()
+            .unwrap_or_else(|| format!("http://localhost:{}", self.port))
+    }
+}
+
+/// AWS settings (`[aws]`)
+#[derive(Debug, Deserialize, Default)]
+#[serde(default)]
+pub struct AwsConfig {
+    /// Default profile (`devkit aws use` overrides it per checkout)
+    pub profile: Option<String>,
+    /// Default region
+    pub region: Option<String>,
+    /// CloudFormation/CDK stacks `devkit aws stacks` shows (all when empty)
+    pub stacks: Vec<String>,
+    /// Build artifacts synced to S3, keyed by target name (`[aws.sync.web]`)
+    pub sync: HashMap<String, S3SyncTarget>,
+}
+
+/// Kubernetes settings (`[k8s]`)
+#[derive(Debug, Deserialize, Default)]
+#[serde(default)]
+pub struct K8sConfig {
+    /// Manifest files, kustomize directories and helm charts for `devkit k8s diff` and
+    /// `apply`, relative to the repo root (`k8s/` and `kubernetes/` when empty)
+    pub manifests: Vec<String>,
+}
+
+/// Docker settings (`[docker]`)
+#[derive(Debug, Deserialize, Default)]
+#[serde(default)]
+pub struct DockerConfig {
+    /// Compose files passed to `docker compose` with `-f`, in order, relative to the
+    /// repo root (default: the compose file found at the root)
+    pub compose_files: Vec<String>,
+}
+
+/// ECS settings (`[ecs]`); `cluster` and `service` are used when nothing is picked yet
+/// or the saved pick is gone
+#[derive(Debug, Deserialize, Default)]
+#[serde(default)]
+pub struct EcsConfig {
+    /// Cluster name
+    pub cluster: Option<String>,
+    /// Service name in that cluster
+    pub service: Option<String>,
+    /// CloudWatch log group for containers whose task definition doesn't name one
+    pub log_group: Option<String>,
+    /// Task definition templates for `devkit ecs deploy`, keyed by service, relative to
+    /// the repo root (default: `ecs/<service>.json`)
+    pub task_definitions: HashMap<String, String>,
+}
+
+/// A directory synced to an S3 bucket
+#[derive(Debug, Deserialize, Clone)]
+pub struct S3SyncTarget {
+    /// Directory to upload (relative to repo root)
+    pub source: String,
+    pub bucket: String,
+    /// Key prefix inside the bucket
+    pub prefix: Option<String>,
+    /// Remove objects that no longer exist locally
+    #[serde(default)]
+    pub delete: bool,
+}
+
+/// Benchmark configuration
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct BenchConfig {
+    /// Allowed slowdown in percent before `devkit bench --check` fails
+    pub threshold: f64,
+    /// Baseline results file used by `--check` (relative to repo root, meant to be committed)
+    pub baseline: String,
+    /// Command benchmarks keyed by name (`[bench.startup]`)
+    #[serde(flatten)]
+    pub commands: HashMap<String, BenchCommand>,
+}
+
+impl Default for BenchConfig {
+    fn default() -> Self {
+        Self {
+            threshold: 5.0,
+            baseline: "bench-baseline.json".to_string(),
+            commands: HashMap::new(),
+        }
+    }
+}
+
+impl BenchConfig {
+    /// Command benchmark names in sorted order
+    pub fn command_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.commands.keys().map(|s| s.as_str()).collect();
+        names.sort();
+        names
+    }
+}
+
+/// A shell command benchmarked with hyperfine
+#[derive(Debug, Deserialize, Clone)]
+pub struct BenchCommand {
+    /// Command to benchmark
+    pub command: String,
+    /// Warmup runs before measuring
+    #[serde(default = "default_bench_warmup")]
+    pub warmup: u32,
+    /// Number of measured runs (hyperfine picks when unset)
+    pub runs: Option<u32>,
+    /// Command to run before each measured run
+    pub prepare: Option<String>,
+}
+
+fn default_bench_warmup() -> u32 {
+    3
+}
+
+/// Test configuration
+#[derive(Debug, Deserialize, Default)]
+#[serde(default)]
+pub struct TestConfig {
+    /// Rust test runner
+    pub runner: TestRunner,
+    /// cargo-nextest profile (`--profile`)
+    pub profile: Option<String>,
+    /// Retries for failing tests under cargo-nextest (`--retries`)
+    pub retries: Option<u32>,
+}
+
+/// Rust test runner
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TestRunner {
+    /// cargo-nextest when installed, otherwise cargo test
+    #[default]
+    Auto,
+    Nextest,
+    Cargo,
+}
+
+/// Extension management
+#[derive(Debug, Deserialize, Default)]
+#[serde(default)]
+pub struct ExtensionsConfig {
+    /// Extensions to turn off (e.g., ["monitoring", "k8s"])
+    pub disabled: Vec<String>,
+    /// Start without external extensions that fail to load instead of erroring
+    pub skip_broken: bool,
+}
+
+impl ExtensionsConfig {
+    /// Whether an extension is listed in `disabled`
+    pub fn is_disabled(&self, name: &str) -> bool {
+        self.disabled.iter().any(|n| n == name)
+    }
+}
+
+/// Terminal output: colors, emoji and the prompt theme
+///
+/// `NO_COLOR` and `DEVKIT_NO_EMOJI` override the file; see [`OutputConfig::apply_env`].
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct OutputConfig {
+    pub color: ColorMode,
+    /// Show emoji in messages and menus
+    pub emoji: bool,
+    /// Prompt theme; defaults to `plain` when colors or emoji are off
+    pub theme: Option<PromptTheme>,
+}
+
+impl Default for OutputConfig {
+    fn default() -> Self {
+        Self {
+            color: ColorMode::Auto,
+            emoji: true,
+            theme: None,
+        }
+    }
+}
+
+impl OutputConfig {
+    /// Turn off colors when `NO_COLOR` is set and emoji when `DEVKIT_NO_EMOJI` is
+    pub fn apply_env(&mut self) {
+        // Any non-empty value counts, per https://no-color.org
+        if std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty()) {
+            self.color = ColorMode::Never;
+        }
+        if std::env::var("DEVKIT_NO_EMOJI").is_ok_and(|v| !v.is_empty() && v != "0") {
+            self.emoji = false;
+        }
+    }
+
+    /// Whether to force colors on or off; None leaves it to terminal detection
+    pub fn colors(&self) -> Option<bool> {
+        match self.color {
+            ColorMode::Auto => None,
+            ColorMode::Always => Some(true),
+            ColorMode::Never => Some(false),
+        }
+    }
+
+    pub fn prompt_theme(&self) -> PromptTheme {
+        if let Some(theme) = self.theme {
+            return theme;
+        }
+        if self.emoji && self.color != ColorMode::Never {
+            PromptTheme::Colorful
+        } else {
+            PromptTheme::Plain
+        }
+    }
+}
+
+/// When to use colors
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorMode {
+    /// Colors when writing to a terminal
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+/// Theme for interactive prompts
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PromptTheme {
+    /// Colored, with symbols (dialoguer's `ColorfulTheme`)
+    Colorful,
+    /// ASCII only, no colors (dialoguer's `SimpleTheme`)
+    Plain,
+}
+
+/// Desktop notifications when a long-running command finishes
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct NotificationsConfig {
+    pub enabled: bool,
+    /// Only notify for commands that ran at least this long
+    pub min_duration_secs: u64,
+}
+
+impl Default for NotificationsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_duration_secs: 60,
+        }
+    }
+}
+
+/// Project hooks: shell commands run around devkit operations
+///
+/// Keys are `pre_<operation>` / `post_<operation>` (e.g. `post_install`,
+/// `pre_docker_up`); see [`crate::hooks`].
+#[derive(Debug, Deserialize, Default)]
+#[serde(default)]
+pub struct HooksConfig {
+    #[serde(flatten)]
+    pub hooks: HashMap<String, HookList>,
+}
+
+impl HooksConfig {
+    /// Hooks registered for an event, in the order they run
+    pub fn get(&self, event: &str) -> Vec<HookEntry> {
+        match self.hooks.get(event) {
+            Some(HookList::One(entry)) => vec![entry.clone()],
+            Some(HookList::Many(entries)) => entries.clone(),
+            None => Vec::new(),
+        }
+    }
+}
+
+/// One hook or a list of hooks for an event
+#[derive(Debug, Deserialize, Clone)]
+#[serde(untagged)]
+pub enum HookList {
+    One(HookEntry),
+    Many(Vec<HookEntry>),
+}
+
+/// Hook entry - either a command string or full config
+#[derive(Debug, Deserialize, Clone)]
+#[serde(untagged)]
+pub enum HookEntry {
+    /// Shell command, aborting the operation on failure
+    Simple(String),
+    /// Full hook config with options
+    Full(HookConfig),
+}
+
+impl HookEntry {
+    /// Shell command to run
+    pub fn run(&self) -> &str {
+        match self {
+            HookEntry::Simple(s) => s,
+            HookEntry::Full(c) => &c.run,
+        }
+    }
+
+    /// What to do when the command fails
+    pub fn on_failure(&self) -> HookFailure {
+        match self {
+            HookEntry::Simple(_) => HookFailure::default(),
+            HookEntry::Full(c) => c.on_failure,
+        }
+    }
+
+    /// Working directory (relative to repo root)
+    pub fn cwd(&self) -> Option<&str> {
+        match self {
+            HookEntry::Simple(_) => None,
+            HookEntry::Full(c) => c.cwd.as_deref(),
+        }
+    }
+}
+
+/// Full hook configuration
+#[derive(Debug, Deserialize, Clone)]
+pub struct HookConfig {
+    /// Shell command to run
+    pub run: String,
+    /// What to do when the command fails
+    #[serde(default)]
+    pub on_failure: HookFailure,
+    /// Working directory (relative to repo root)
+    pub cwd: Option<String>,
+}
+
+/// Failure policy for a hook
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum HookFailure {
+    /// Stop and fail the operation
+    #[default]
+    Abort,
+    /// Print a warning and continue
+    Warn,
+    /// Continue silently
+    Ignore,
+}
+
 /// Command aliases configuration
 #[derive(Debug, Deserialize, Default)]
 #[serde(default)]
@@ -241,6 +1212,115 @@ pub struct AliasesConfig {
     pub aliases: HashMap<String, String>,
 }
 
+/// Sibling repositories combined into this workspace
+#[derive(Debug, Deserialize, Default)]
+#[serde(default)]
+pub struct ReposConfig {
+    /// Repo name -> path relative to the repo root (e.g., "api" -> "../api")
+    #[serde(flatten)]
+    pub repos: HashMap<String, String>,
+}
+
+impl ReposConfig {
+    /// Repo names with absolute paths, sorted by name
+    pub fn resolve(&self, repo_root: &Path) -> Vec<(&str, PathBuf)> {
+        let mut repos: Vec<_> = self
+            .repos
+            .iter()
+            .map(|(name, path)| (name.as_str(), repo_root.join(path)))
+            .collect();
+        repos.sort();
+        repos
+    }
+}
+
+/// Reusable command strings, keyed by name
+#[derive(Debug, Deserialize, Default)]
+#[serde(default)]
+pub struct SnippetsConfig {
+    #[serde(flatten)]
+    pub snippets: HashMap<String, Snippet>,
+}
+
+/// A command string with `{param}` placeholders
+#[derive(Debug, Deserialize, Clone)]
+#[serde(untagged)]
+pub enum Snippet {
+    Simple(String),
+    Full {
+        run: String,
+        /// Default parameter values
+        #[serde(default)]
+        params: HashMap<String, String>,
+    },
+}
+
+impl SnippetsConfig {
+    /// The snippet's command with `params` (over its defaults) filled in
+    ///
+    /// Errors name an unknown snippet or the parameters left unset. Placeholders
+    /// with a dot, like `{services.api}`, are left for later expansion.
+    pub fn render(
+        &self,
+        name: &str,
+        params: &HashMap<String, String>,
+    ) -> std::result::Result<String, String> {
+        let (run, defaults) = match self.snippets.get(name) {
+            Some(Snippet::Simple(run)) => (run, None),
+            Some(Snippet::Full { run, params }) => (run, Some(params)),
+            None => {
+                let names = self.snippets.keys().map(String::as_str);
+                return Err(match crate::utils::closest_match(name, names) {
+                    Some(suggestion) => format!(
+                        "unknown snippet '{}' (did you mean '{}'?)",
+                        name, suggestion
+                    ),
+                    None => format!("unknown snippet '{}' (not in [snippets])", name),
+                });
+            }
+        };
+
+        let mut rendered = String::new();
+        let mut missing = Vec::new();
+        let mut rest = run.as_str();
+        while let Some(start) = rest.find('{') {
+            rendered.push_str(&rest[..start]);
+            let placeholder = &rest[start..];
+            let Some(end) = placeholder.find('}') else {
+                break;
+            };
+            let param = &placeholder[1..end];
+            let is_param = !param.is_empty()
+                && param
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+            match params
+                .get(param)
+                .or_else(|| defaults.and_then(|defaults| defaults.get(param)))
+            {
+                Some(value) if is_param => rendered.push_str(value),
+                None if is_param => {
+                    if !missing.contains(&param) {
+                        missing.push(param);
+                    }
+                }
+                _ => rendered.push_str(&placeholder[..=end]),
+            }
+            rest = &placeholder[end + 1..];
+        }
+        rendered.push_str(rest);
+
+        if !missing.is_empty() {
+            return Err(format!(
+                "snippet '{}' needs {} in params",
+                name,
+                missing.join(", ")
+            ));
+        }
+        Ok(rendered)
+    }
+}
+
 // =============================================================================
 // Package Configuration (packages/*/dev.toml)
 // =============================================================================
@@ -253,6 +1333,8 @@ pub struct PackageToml {
     pub database: Option<DatabaseConfig>,
     /// Mobile capability
     pub mobile: Option<MobileConfig>,
+    /// Protobuf capability
+    pub proto: Option<ProtoConfig>,
     /// Package commands
     #[serde(default)]
     pub cmd: HashMap<String, CmdEntry>,
@@ -265,6 +1347,42 @@ pub struct DatabaseConfig {
     pub migrations: String,
     /// Path to seed file (relative to package)
     pub seeds: Option<String>,
+    /// Database engine (detected from the connection string when unset)
+    pub engine: Option<DatabaseEngine>,
+    /// Connection string for this package, with `{services.<name>}` expanded;
+    /// `DATABASE_URL` when unset
+    pub url: Option<String>,
+}
+
+/// Database engine, which picks the client for shells and seeds
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DatabaseEngine {
+    Postgres,
+    Mysql,
+    Sqlite,
+}
+
+impl DatabaseEngine {
+    /// The engine a database URL is for, from its scheme
+    pub fn from_url(url: &str) -> Option<Self> {
+        let (scheme, _) = url.split_once(':')?;
+        match scheme {
+            "postgres" | "postgresql" => Some(Self::Postgres),
+            "mysql" | "mariadb" => Some(Self::Mysql),
+            "sqlite" | "file" => Some(Self::Sqlite),
+            _ => None,
+        }
+    }
+
+    /// Command-line client binary
+    pub fn client(&self) -> &'static str {
+        match self {
+            Self::Postgres => "psql",
+            Self::Mysql => "mysql",
+            Self::Sqlite => "sqlite3",
+        }
+    }
 }
 
 /// Mobile capability configuration
@@ -282,18 +1400,54 @@ fn default_startup_timeout() -> u32 {
     300
 }
 
+/// Protobuf capability configuration
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct ProtoConfig {
+    /// Directory with .proto files (relative to package)
+    pub dir: String,
+    /// protoc output directory (relative to package); buf uses buf.gen.yaml instead
+    pub out: String,
+    /// protoc plugins, each passed as `--<plugin>_out` (e.g. "go", "go-grpc", "python")
+    pub plugins: Vec<String>,
+}
+
+impl Default for ProtoConfig {
+    fn default() -> Self {
+        Self {
+            dir: "proto".to_string(),
+            out: "gen".to_string(),
+            plugins: Vec::new(),
+        }
+    }
+}
+
 // =============================================================================
 // Command Configuration
 // =============================================================================
 
 /// Command entry - either a simple string or full config
-#[derive(Debug, Deserialize, Clone)]
-#[serde(untagged)]
+#[derive(Debug, Clone)]
 pub enum CmdEntry {
     /// Simple command string
     Simple(String),
     /// Full command config with options
-    Full(CmdConfig),
+    Full(Box<CmdConfig>),
+}
+
+impl<'de> Deserialize<'de> for CmdEntry {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        // Not untagged, so errors in a full config keep their message
+        match toml::Value::deserialize(deserializer)? {
+            toml::Value::String(cmd) => Ok(CmdEntry::Simple(cmd)),
+            value => CmdConfig::deserialize(value)
+                .map(|c| CmdEntry::Full(Box::new(c)))
+                .map_err(serde::de::Error::custom),
+        }
+    }
 }
 
 impl CmdEntry {
@@ -324,6 +1478,41 @@ impl CmdEntry {
             CmdEntry::Full(c) => &c.deps,
         }
     }
+
+    /// Get readiness targets to wait for (see [`crate::wait`])
+    pub fn wait(&self) -> &[String] {
+        match self {
+            CmdEntry::Simple(_) => &[],
+            CmdEntry::Full(c) => &c.wait,
+        }
+    }
+
+    /// Get input globs, relative to the package, whose hash decides if a run is cached
+    pub fn inputs(&self) -> &[String] {
+        match self {
+            CmdEntry::Simple(_) => &[],
+            CmdEntry::Full(c) => &c.inputs,
+        }
+    }
+
+    /// Get variables to run the command with: its `env_file` (relative to
+    /// `package_dir`), overridden by its `env`
+    pub fn env_vars(&self, package_dir: &Path) -> Result<Vec<(String, String)>> {
+        let CmdEntry::Full(c) = self else {
+            return Ok(Vec::new());
+        };
+        let mut vars = match &c.env_file {
+            Some(file) => crate::environment::read_env_file(&package_dir.join(file))?,
+            None => Vec::new(),
+        };
+        let mut env: Vec<_> = c.env.iter().collect();
+        env.sort();
+        for (key, value) in env {
+            vars.retain(|(k, _)| k != key);
+            vars.push((key.clone(), value.clone()));
+        }
+        Ok(vars)
+    }
 }
 
 /// Full command configuration
@@ -333,6 +1522,19 @@ pub struct CmdConfig {
     pub default: String,
     /// Dependencies to run first (format: "package:cmd" or "package" for same cmd)
     pub deps: Vec<String>,
+    /// Targets that must be ready first, e.g. `["postgres"]` (see [`crate::wait`])
+    pub wait: Vec<String>,
+    /// Files the command reads, e.g. `["src/**"]`; while they're unchanged since the
+    /// last successful run, the command is skipped
+    pub inputs: Vec<String>,
+    /// Variables set for the command only (`env = { RUST_LOG = "debug" }`)
+    pub env: HashMap<String, String>,
+    /// Env file, relative to the package, loaded before `env`
+    pub env_file: Option<String>,
+    /// `[snippets]` entry the default command comes from
+    pub snippet: Option<String>,
+    /// Values for the snippet's `{param}` placeholders
+    pub params: HashMap<String, String>,
     /// Command variants (any other key becomes a variant)
     pub variants: HashMap<String, String>,
 }
@@ -344,23 +1546,44 @@ impl<'de> Deserialize<'de> for CmdConfig {
     {
         let mut map: HashMap<String, toml::Value> = HashMap::deserialize(deserializer)?;
 
-        let default = map
-            .remove("default")
-            .and_then(|v| v.as_str().map(String::from))
-            .ok_or_else(|| serde::de::Error::missing_field("default"))?;
-
-        let deps = map
-            .remove("deps")
-            .map(|v| {
-                v.as_array()
-                    .map(|arr| {
-                        arr.iter()
-                            .filter_map(|v| v.as_str().map(String::from))
-                            .collect()
-                    })
-                    .unwrap_or_default()
-            })
-            .unwrap_or_default();
+        let snippet = map
+            .remove("snippet")
+            .and_then(|v| v.as_str().map(String::from));
+        let params = match map.remove("params") {
+            Some(v) => string_table("params", v)?,
+            None => HashMap::new(),
+        };
+        let env = match map.remove("env") {
+            Some(v) => string_table("env", v)?,
+            None => HashMap::new(),
+        };
+        let env_file = map
+            .remove("env_file")
+            .and_then(|v| v.as_str().map(String::from));
+
+        // Commands from snippets are filled in once [snippets] is loaded
+        let default = match map.remove("default") {
+            Some(v) => v.as_str().map(String::from),
+            None => snippet.as_ref().map(|_| String::new()),
+        }
+        .ok_or_else(|| serde::de::Error::missing_field("default"))?;
+
+        let mut strings = |key: &str| -> Vec<String> {
+            map.remove(key)
+                .map(|v| {
+                    v.as_array()
+                        .map(|arr| {
+                            arr.iter()
+                                .filter_map(|v| v.as_str().map(String::from))
+                                .collect()
+                        })
+                        .unwrap_or_default()
+                })
+                .unwrap_or_default()
+        };
+        let deps = strings("deps");
+        let wait = strings("wait");
+        let inputs = strings("inputs");
 
         let variants: HashMap<String, String> = map
             .into_iter()
@@ -370,11 +1593,46 @@ impl<'de> Deserialize<'de> for CmdConfig {
         Ok(CmdConfig {
             default,
             deps,
+            wait,
+            inputs,
+            env,
+            env_file,
+            snippet,
+            params,
             variants,
         })
     }
 }
 
+/// A table of strings such as `env`; numbers and booleans are converted, so
+/// `{ PORT = 3000 }` sets `PORT=3000`
+fn string_table<E: serde::de::Error>(
+    key: &str,
+    value: toml::Value,
+) -> std::result::Result<HashMap<String, String>, E> {
+    let toml::Value::Table(table) = value else {
+        return Err(E::custom(format!("'{}' must be a table", key)));
+    };
+    table
+        .into_iter()
+        .map(|(name, value)| {
+            let value = match value {
+                toml::Value::String(s) => s,
+                toml::Value::Integer(i) => i.to_string(),
+                toml::Value::Float(f) => f.to_string(),
+                toml::Value::Boolean(b) => b.to_string(),
+                _ => {
+                    return Err(E::custom(format!(
+                        "'{}.{}' must be a string, number or boolean",
+                        key, name
+                    )))
+                }
+            };
+            Ok((name, value))
+        })
+        .collect()
+}
+
 /// Resolved package configuration with inferred values
 #[derive(Debug, Default)]
 pub struct PackageConfig {
@@ -388,10 +1646,28 @@ pub struct PackageConfig {
     pub database: Option<DatabaseConfig>,
     /// Mobile capability
     pub mobile: Option<MobileConfig>,
+    /// Protobuf capability
+    pub proto: Option<ProtoConfig>,
     /// Package commands
     pub cmd: HashMap<String, CmdEntry>,
 }
 
+impl PackageConfig {
+    /// The package as seen from another repo's workspace: its name and the packages
+    /// in its command deps get the `<repo>/` prefix
+    fn in_repo(mut self, repo: &str) -> Self {
+        self.name = format!("{}/{}", repo, self.name);
+        for entry in self.cmd.values_mut() {
+            if let CmdEntry::Full(cmd) = entry {
+                for dep in &mut cmd.deps {
+                    *dep = format!("{}/{}", repo, dep);
+                }
+            }
+        }
+        self
+    }
+}
+
 // =============================================================================
 // Package Name Inference
 // =============================================================================
@@ -458,8 +1734,26 @@ pub struct Config {
 impl Config {
     /// Load configuration from the repository root
     pub fn load(repo_root: &Path) -> Result<Self> {
-        let global = Self::load_global_config(repo_root)?;
-        let packages = Self::discover_packages(repo_root, &global)?;
+        let mut global = Self::load_global_config(repo_root)?;
+        let (env, _) = crate::environment::resolve(repo_root, &global.environments);
+        let overlay = crate::environment::overlay_path(repo_root, &env);
+        if overlay.exists() {
+            global = Self::load_overlaid_config(repo_root, &overlay)?;
+        }
+        global.environments.active = env;
+        let mut packages = Self::discover_packages(repo_root, &global)?;
+
+        // Missing sibling repos are skipped here and reported by `devkit config validate`
+        for (repo, root) in global.repos.resolve(repo_root) {
+            if !root.is_dir() {
+                continue;
+            }
+            let repo_global = Self::load_global_config(&root)?;
+            for (_, package) in Self::discover_packages(&root, &repo_global)? {
+                let package = package.in_repo(repo);
+                packages.insert(package.name.clone(), package);
+            }
+        }
 
         Ok(Config {
             repo_root: repo_root.to_path_buf(),
@@ -482,6 +1776,24 @@ impl Config {
         toml::from_str(&content).map_err(|e| DevkitError::config_parse(config_path, e))
     }
 
+    /// Load .dev/config.toml with an environment's overlay merged over it
+    fn load_overlaid_config(repo_root: &Path, overlay_path: &Path) -> Result<GlobalConfig> {
+        let read = |path: &Path| -> Result<toml::Table> {
+            if !path.exists() {
+                return Ok(toml::Table::new());
+            }
+            let content = fs::read_to_string(path)
+                .map_err(|e| DevkitError::config_load(path.to_path_buf(), e.into()))?;
+            toml::from_str(&content).map_err(|e| DevkitError::config_parse(path.to_path_buf(), e))
+        };
+
+        let mut table = read(&repo_root.join(".dev/config.toml"))?;
+        crate::environment::merge_toml(&mut table, read(overlay_path)?);
+        toml::Value::Table(table)
+            .try_into()
+            .map_err(|e| DevkitError::config_parse(overlay_path.to_path_buf(), e))
+    }
+
     /// Discover packages and load their configurations
     fn discover_packages(
         repo_root: &Path,
@@ -514,7 +1826,7 @@ impl Config {
                     continue;
                 }
 
-                let config = Self::load_package_config(&path, &name)?;
+                let config = Self::load_package_config(&path, &name, &global.snippets)?;
                 packages.insert(config.name.clone(), config);
             }
         }
@@ -523,28 +1835,52 @@ impl Config {
     }
 
     /// Load package configuration
-    fn load_package_config(package_path: &Path, dir_name: &str) -> Result<PackageConfig> {
+    fn load_package_config(
+        package_path: &Path,
+        dir_name: &str,
+        snippets: &SnippetsConfig,
+    ) -> Result<PackageConfig> {
         let name = infer_package_name(package_path, dir_name);
 
         let config_path = package_path.join("dev.toml");
-        let toml_config: PackageToml = if config_path.exists() {
+        let mut toml_config: PackageToml = if config_path.exists() {
             let content = fs::read_to_string(&config_path)
                 .map_err(|e| DevkitError::config_load(config_path.clone(), e.into()))?;
-            toml::from_str(&content).map_err(|e| DevkitError::config_parse(config_path, e))?
+            toml::from_str(&content)
+                .map_err(|e| DevkitError::config_parse(config_path.clone(), e))?
         } else {
             PackageToml::default()
         };
 
+        for (cmd_name, entry) in &mut toml_config.cmd {
+            if let CmdEntry::Full(cmd) = entry {
+                if let Some(snippet) = &cmd.snippet {
+                    cmd.default = snippets.render(snippet, &cmd.params).map_err(|e| {
+                        DevkitError::config_load(
+                            config_path.clone(),
+                            anyhow::anyhow!("[cmd.{}]: {}", cmd_name, e),
+                        )
+                    })?;
+                }
+            }
+        }
+
         Ok(PackageConfig {
             path: package_path.to_path_buf(),
             dir_name: dir_name.to_string(),
             name,
             database: toml_config.database,
             mobile: toml_config.mobile,
+            proto: toml_config.proto,
             cmd: toml_config.cmd,
         })
     }
 
+    /// Sibling repositories from `[repos]` with absolute paths, sorted by name
+    pub fn repos(&self) -> Vec<(&str, PathBuf)> {
+        self.global.repos.resolve(&self.repo_root)
+    }
+
     /// Find all packages with database capability
     pub fn database_packages(&self) -> Vec<(&str, &DatabaseConfig)> {
         self.packages
@@ -553,6 +1889,26 @@ impl Config {
             .collect()
     }
 
+    /// Find all packages with mobile capability
+    pub fn mobile_packages(&self) -> Vec<(&str, &PackageConfig, &MobileConfig)> {
+        self.packages
+            .iter()
+            .filter_map(|(name, pkg)| {
+                pkg.mobile
+                    .as_ref()
+                    .map(|mobile| (name.as_str(), pkg, mobile))
+            })
+            .collect()
+    }
+
+    /// Find all packages with protobuf capability
+    pub fn proto_packages(&self) -> Vec<(&str, &PackageConfig, &ProtoConfig)> {
+        self.packages
+            .iter()
+            .filter_map(|(name, pkg)| pkg.proto.as_ref().map(|proto| (name.as_str(), pkg, proto)))
+            .collect()
+    }
+
     /// Find all packages that have a specific command
     pub fn packages_with_cmd(&self, cmd_name: &str) -> Vec<(&str, &PackageConfig, &CmdEntry)> {
         self.packages
@@ -572,4 +1928,13 @@ impl Config {
     pub fn get_package(&self, name: &str) -> Option<&PackageConfig> {
         self.packages.get(name)
     }
+
+    /// Package containing `path`; in nested packages, the innermost one
+    pub fn package_at(&self, path: &Path) -> Option<&str> {
+        self.packages
+            .iter()
+            .filter(|(_, pkg)| path.starts_with(&pkg.path))
+            .max_by_key(|(_, pkg)| pkg.path.components().count())
+            .map(|(name, _)| name.as_str())
+    }
 }
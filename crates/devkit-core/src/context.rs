@@ -2,13 +2,16 @@
 
 use anyhow::Result;
 use console::style;
-use dialoguer::{theme::ColorfulTheme, Confirm};
+use dialoguer::{theme::Theme, Confirm};
+use std::borrow::Cow;
 use std::path::PathBuf;
 use tracing::{info, warn};
 
 use crate::config::Config;
 use crate::detection::Features;
-use crate::utils::repo_root;
+use crate::error::DevkitError;
+use crate::output::strip_emoji;
+use crate::utils::{is_terminal, repo_root};
 use crate::validation::validate_config;
 
 /// Application context passed to all commands
@@ -21,16 +24,18 @@ pub struct AppContext {
 
 impl AppContext {
     pub fn new(quiet: bool) -> Result<Self> {
-        let repo = repo_root()?;
-        info!("Repository root: {}", repo.display());
+        Self::load(quiet, !quiet)
+    }
 
-        let config = Config::load(&repo)?;
-        info!("Loaded config with {} packages", config.packages.len());
+    /// Load the context; `log_warnings` logs config validation warnings (`devkit
+    /// validate` prints them itself)
+    pub fn load(quiet: bool, log_warnings: bool) -> Result<Self> {
+        let ctx = Self::load_unvalidated(quiet)?;
 
         // Validate configuration
-        let validation = validate_config(&config)?;
+        let validation = validate_config(&ctx.config)?;
 
-        if !quiet {
+        if log_warnings {
             // Show warnings
             for warning in &validation.warnings {
                 warn!("{}", warning);
@@ -46,6 +51,23 @@ impl AppContext {
             return Err(anyhow::anyhow!("Configuration validation failed"));
         }
 
+        Ok(ctx)
+    }
+
+    /// Load the context without validating the config, for `devkit config validate`
+    pub fn load_unvalidated(quiet: bool) -> Result<Self> {
+        let repo = repo_root()?;
+        info!("Repository root: {}", repo.display());
+
+        let mut config = Config::load(&repo)?;
+        info!("Loaded config with {} packages", config.packages.len());
+
+        config.global.output.apply_env();
+        if let Some(enabled) = config.global.output.colors() {
+            console::set_colors_enabled(enabled);
+            console::set_colors_enabled_stderr(enabled);
+        }
+
         let features = Features::detect(&repo, &config);
         info!(
             "Detected features: docker={}, git={}, cargo={}, node={}",
@@ -60,16 +82,43 @@ impl AppContext {
         })
     }
 
-    pub fn theme(&self) -> ColorfulTheme {
-        ColorfulTheme::default()
+    /// Prompt theme from `[output]`
+    pub fn theme(&self) -> Box<dyn Theme> {
+        self.config.global.output.prompt_theme().dialoguer()
+    }
+
+    /// `text` with emoji removed when `[output] emoji` is off
+    pub fn text<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        if self.config.global.output.emoji {
+            Cow::Borrowed(text)
+        } else {
+            Cow::Owned(strip_emoji(text))
+        }
+    }
+
+    /// Whether prompts can be shown: not `--quiet`, and stdin and stdout are terminals
+    pub fn is_interactive(&self) -> bool {
+        !self.quiet && is_terminal()
+    }
+
+    /// Error for prompts that have no sensible default in non-interactive runs
+    ///
+    /// `hint` says how to get the same result without prompting.
+    pub fn require_interactive(&self, action: &str, hint: &str) -> crate::Result<()> {
+        if self.is_interactive() {
+            Ok(())
+        } else {
+            Err(DevkitError::not_interactive(action, hint))
+        }
     }
 
+    /// Ask a yes/no question; `default` is the answer in non-interactive runs
     pub fn confirm(&self, prompt: &str, default: bool) -> Result<bool> {
-        if self.quiet {
+        if !self.is_interactive() {
             return Ok(default);
         }
-        Ok(Confirm::with_theme(&self.theme())
-            .with_prompt(prompt)
+        Ok(Confirm::with_theme(&*self.theme())
+            .with_prompt(self.text(prompt))
             .default(default)
             .interact()?)
     }
@@ -77,31 +126,31 @@ impl AppContext {
     pub fn print_header(&self, msg: &str) {
         if !self.quiet {
             println!();
-            println!("{}", style(msg).bold());
+            println!("{}", style(self.text(msg)).bold());
         }
     }
 
     pub fn print_success(&self, msg: &str) {
         if !self.quiet {
-            println!("{}", style(msg).green());
+            println!("{}", style(self.text(msg)).green());
         }
     }
 
     pub fn print_warning(&self, msg: &str) {
         if !self.quiet {
-            println!("{}", style(msg).yellow());
+            println!("{}", style(self.text(msg)).yellow());
         }
     }
 
     pub fn print_info(&self, msg: &str) {
         if !self.quiet {
-            println!("{}", style(msg).cyan());
+            println!("{}", style(self.text(msg)).cyan());
         }
     }
 
     pub fn print_error(&self, msg: &str) {
         if !self.quiet {
-            eprintln!("{}", style(msg).red());
+            eprintln!("{}", style(self.text(msg)).red());
         }
     }
 }
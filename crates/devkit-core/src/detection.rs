@@ -1,6 +1,7 @@
 //! Feature detection - automatically discover what's available in the project
 
 use crate::utils::{cmd_exists, docker_available};
+use crate::validation::compose_file;
 use crate::Config;
 use std::path::Path;
 
@@ -23,7 +24,7 @@ impl Features {
     /// Detect features based on the repository and config
     pub fn detect(repo_root: &Path, config: &Config) -> Self {
         Features {
-            docker: Self::has_docker(repo_root),
+            docker: Self::has_docker(repo_root, config),
             database: Self::has_database(config),
             git: Self::has_git(repo_root),
             cargo: cmd_exists("cargo"),
@@ -36,13 +37,14 @@ impl Features {
         }
     }
 
-    fn has_docker(repo_root: &Path) -> bool {
-        // Check if docker is installed and if docker-compose.yml exists
+    fn has_docker(repo_root: &Path, config: &Config) -> bool {
+        // Check if docker is installed and if this repo or a [repos] sibling has a compose file
         docker_available()
-            && (repo_root.join("docker-compose.yml").exists()
-                || repo_root.join("docker-compose.yaml").exists()
-                || repo_root.join("compose.yml").exists()
-                || repo_root.join("compose.yaml").exists())
+            && (compose_file(repo_root).is_some()
+                || config
+                    .repos()
+                    .iter()
+                    .any(|(_, root)| compose_file(root).is_some()))
     }
 
     fn has_database(config: &Config) -> bool {
@@ -0,0 +1,171 @@
+//! On-disk cache of the actions external extensions discover
+//!
+//! Running every extension's `discover` executable on each launch gets slow in large
+//! monorepos, so the actions each one prints are kept in `.dev/cache/commands.json`.
+//! An entry is reused while its fingerprint still matches: the active environment and
+//! the modification times of the files discovery usually reads (the extension's own
+//! files, `.dev/config.toml`, and the manifests of the repo root and every package).
+//! `devkit discover --refresh` drops the cache.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use crate::external_extension::ActionConfig;
+use crate::AppContext;
+
+const CACHE_DIR: &str = ".dev/cache";
+const CACHE_FILE: &str = "commands.json";
+
+/// Package files whose changes invalidate discovered actions
+const MANIFESTS: &[&str] = &["package.json", "Cargo.toml", "Makefile", "dev.toml"];
+
+/// What discovered actions depend on
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Fingerprint {
+    env: String,
+    /// Modification time (ns since the epoch) of each file that exists
+    files: BTreeMap<PathBuf, u128>,
+}
+
+impl Fingerprint {
+    /// Fingerprint for an extension in `extension_dir`
+    pub fn new(ctx: &AppContext, extension_dir: &Path) -> Self {
+        let mut paths = vec![ctx.repo.join(".dev/config.toml")];
+        if let Ok(entries) = fs::read_dir(extension_dir) {
+            paths.extend(entries.flatten().map(|entry| entry.path()));
+        }
+        let dirs = std::iter::once(ctx.repo.as_path())
+            .chain(ctx.config.packages.values().map(|pkg| pkg.path.as_path()));
+        for dir in dirs {
+            paths.extend(MANIFESTS.iter().map(|manifest| dir.join(manifest)));
+        }
+
+        let files = paths
+            .into_iter()
+            .filter_map(|path| {
+                let modified = fs::metadata(&path).ok()?.modified().ok()?;
+                let nanos = modified.duration_since(UNIX_EPOCH).ok()?.as_nanos();
+                Some((path, nanos))
+            })
+            .collect();
+
+        Self {
+            env: ctx.config.global.environments.current().to_string(),
+            files,
+        }
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct CacheFile {
+    /// Keyed by extension name
+    extensions: BTreeMap<String, CachedActions>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CachedActions {
+    fingerprint: Fingerprint,
+    actions: Vec<ActionConfig>,
+}
+
+/// Cached actions for an extension, if its fingerprint hasn't changed
+pub fn lookup(
+    repo: &Path,
+    extension: &str,
+    fingerprint: &Fingerprint,
+) -> Option<Vec<ActionConfig>> {
+    let mut cache = load(repo);
+    let cached = cache.extensions.remove(extension)?;
+    (cached.fingerprint == *fingerprint).then_some(cached.actions)
+}
+
+/// Save an extension's discovered actions
+pub fn store(
+    repo: &Path,
+    extension: &str,
+    fingerprint: Fingerprint,
+    actions: &[ActionConfig],
+) -> Result<()> {
+    let mut cache = load(repo);
+    cache.extensions.insert(
+        extension.to_string(),
+        CachedActions {
+            fingerprint,
+            actions: actions.to_vec(),
+        },
+    );
+
+    let path = cache_dir(repo)?.join(CACHE_FILE);
+    fs::write(&path, serde_json::to_string_pretty(&cache)?)
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// `.dev/cache`, created if needed; shared with other caches devkit keeps
+pub fn cache_dir(repo: &Path) -> Result<PathBuf> {
+    let dir = repo.join(CACHE_DIR);
+    fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    // Keep the cache out of git without touching the project's .gitignore
+    let gitignore = dir.join(".gitignore");
+    if !gitignore.exists() {
+        fs::write(&gitignore, "*\n")?;
+    }
+    Ok(dir)
+}
+
+/// Remove the cache; returns whether there was one
+pub fn clear(repo: &Path) -> Result<bool> {
+    let path = repo.join(CACHE_DIR).join(CACHE_FILE);
+    if !path.exists() {
+        return Ok(false);
+    }
+    fs::remove_file(&path).with_context(|| format!("Failed to remove {}", path.display()))?;
+    Ok(true)
+}
+
+/// The cache file, empty when missing or unreadable (e.g. written by another version)
+fn load(repo: &Path) -> CacheFile {
+    fs::read_to_string(repo.join(CACHE_DIR).join(CACHE_FILE))
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_store_and_lookup() {
+        let repo = std::env::temp_dir().join(format!("devkit-discovery-{}", std::process::id()));
+        fs::create_dir_all(&repo).unwrap();
+
+        let fingerprint = |mtime: u128| Fingerprint {
+            env: "dev".to_string(),
+            files: BTreeMap::from([(repo.join("package.json"), mtime)]),
+        };
+        let actions: Vec<ActionConfig> = serde_json::from_str(
+            r#"[{"id": "lint-api", "label": "Lint api", "command": "lint.sh", "package": "api"}]"#,
+        )
+        .unwrap();
+
+        assert!(lookup(&repo, "lint", &fingerprint(1)).is_none());
+        store(&repo, "lint", fingerprint(1), &actions).unwrap();
+
+        let cached = lookup(&repo, "lint", &fingerprint(1)).unwrap();
+        assert_eq!(cached[0].id, "lint-api");
+        assert_eq!(cached[0].package.as_deref(), Some("api"));
+        // A changed file invalidates the entry
+        assert!(lookup(&repo, "lint", &fingerprint(2)).is_none());
+        assert!(repo.join(".dev/cache/.gitignore").exists());
+
+        assert!(clear(&repo).unwrap());
+        assert!(lookup(&repo, "lint", &fingerprint(1)).is_none());
+        assert!(!clear(&repo).unwrap());
+
+        fs::remove_dir_all(&repo).ok();
+    }
+}
@@ -0,0 +1,281 @@
+//! Environment checks for `devkit doctor`
+//!
+//! Works out which tools the project needs from what's in the repo (a Cargo.lock
+//! needs cargo-audit, a pnpm-lock.yaml needs pnpm, ...) and how to install the ones
+//! that are missing. Each tool lists installers in order of preference; the first
+//! whose program is on PATH is used.
+
+use crate::oplog::LoggedCommand;
+use crate::utils::cmd_exists;
+use crate::Config;
+use anyhow::{bail, Context, Result};
+use std::path::Path;
+use std::process::Command;
+
+/// A tool the project may need
+pub struct Tool {
+    pub name: &'static str,
+    /// Executable looked up on PATH
+    pub binary: &'static str,
+    installers: &'static [Installer],
+    /// Where to get it when no installer applies
+    pub hint: &'static str,
+}
+
+/// A command that installs a tool
+pub struct Installer {
+    pub program: &'static str,
+    pub args: &'static [&'static str],
+}
+
+/// Result of checking one tool
+pub struct Check {
+    pub tool: &'static Tool,
+    /// Why the project needs it
+    pub reason: &'static str,
+    pub installed: bool,
+}
+
+const fn brew(args: &'static [&'static str]) -> Installer {
+    Installer {
+        program: "brew",
+        args,
+    }
+}
+
+const fn cargo_install(args: &'static [&'static str]) -> Installer {
+    Installer {
+        program: "cargo",
+        args,
+    }
+}
+
+const TOOLS: &[Tool] = &[
+    Tool {
+        name: "git",
+        binary: "git",
+        installers: &[brew(&["install", "git"])],
+        hint: "https://git-scm.com/downloads",
+    },
+    Tool {
+        name: "docker",
+        binary: "docker",
+        installers: &[brew(&["install", "--cask", "docker"])],
+        hint: "https://docs.docker.com/get-docker/",
+    },
+    Tool {
+        name: "cargo",
+        binary: "cargo",
+        installers: &[brew(&["install", "rustup"])],
+        hint: "https://rustup.rs",
+    },
+    Tool {
+        name: "cargo-audit",
+        binary: "cargo-audit",
+        installers: &[cargo_install(&["install", "cargo-audit"])],
+        hint: "cargo install cargo-audit",
+    },
+    Tool {
+        name: "sqlx-cli",
+        binary: "sqlx",
+        installers: &[cargo_install(&["install", "sqlx-cli"])],
+        hint: "cargo install sqlx-cli",
+    },
+    Tool {
+        name: "node",
+        binary: "node",
+        installers: &[brew(&["install", "node"])],
+        hint: "https://nodejs.org",
+    },
+    Tool {
+        name: "pnpm",
+        binary: "pnpm",
+        installers: &[
+            Installer {
+                program: "corepack",
+                args: &["enable", "pnpm"],
+            },
+            Installer {
+                program: "npm",
+                args: &["install", "-g", "pnpm"],
+            },
+        ],
+        hint: "https://pnpm.io/installation",
+    },
+    Tool {
+        name: "yarn",
+        binary: "yarn",
+        installers: &[
+            Installer {
+                program: "corepack",
+                args: &["enable", "yarn"],
+            },
+            Installer {
+                program: "npm",
+                args: &["install", "-g", "yarn"],
+            },
+        ],
+        hint: "https://yarnpkg.com/getting-started/install",
+    },
+    Tool {
+        name: "gh",
+        binary: "gh",
+        installers: &[brew(&["install", "gh"])],
+        hint: "https://cli.github.com",
+    },
+    Tool {
+        name: "kubectl",
+        binary: "kubectl",
+        installers: &[brew(&["install", "kubectl"])],
+        hint: "https://kubernetes.io/docs/tasks/tools/",
+    },
+    Tool {
+        name: "pulumi",
+        binary: "pulumi",
+        installers: &[brew(&["install", "pulumi/tap/pulumi"])],
+        hint: "https://www.pulumi.com/docs/install/",
+    },
+];
+
+impl Tool {
+    /// Look up a tool by name
+    pub fn find(name: &str) -> Option<&'static Tool> {
+        TOOLS.iter().find(|tool| tool.name == name)
+    }
+
+    pub fn is_installed(&self) -> bool {
+        cmd_exists(self.binary)
+    }
+
+    /// First installer whose program is available
+    pub fn installer(&self) -> Option<&'static Installer> {
+        self.installers
+            .iter()
+            .find(|installer| cmd_exists(installer.program))
+    }
+}
+
+impl Installer {
+    /// The command line, for display
+    pub fn command_line(&self) -> String {
+        std::iter::once(self.program)
+            .chain(self.args.iter().copied())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    pub fn run(&self) -> Result<()> {
+        let status = Command::new(self.program)
+            .args(self.args)
+            .logged_status()
+            .with_context(|| format!("Failed to run {}", self.command_line()))?;
+        if !status.success() {
+            bail!("`{}` failed with {}", self.command_line(), status);
+        }
+        Ok(())
+    }
+}
+
+/// Tools the project needs, with whether each is installed
+pub fn check(config: &Config) -> Vec<Check> {
+    let repo = config.repo_root.as_path();
+    let mut needed = vec![("git", "version control")];
+
+    if has_file(
+        config,
+        &[
+            "docker-compose.yml",
+            "docker-compose.yaml",
+            "compose.yml",
+            "compose.yaml",
+        ],
+    ) {
+        needed.push(("docker", "docker-compose file"));
+    }
+    if has_file(config, &["Cargo.toml"]) {
+        needed.push(("cargo", "Rust packages"));
+    }
+    if has_file(config, &["Cargo.lock"]) {
+        needed.push(("cargo-audit", "Rust dependency audit"));
+    }
+    if !config.database_packages().is_empty() {
+        needed.push(("sqlx-cli", "database migrations"));
+    }
+    if has_file(config, &["package.json"]) {
+        needed.push(("node", "Node packages"));
+    }
+    if has_file(config, &["pnpm-lock.yaml"]) {
+        needed.push(("pnpm", "pnpm-lock.yaml"));
+    }
+    if has_file(config, &["yarn.lock"]) {
+        needed.push(("yarn", "yarn.lock"));
+    }
+    if repo.join(".github/workflows").is_dir() {
+        needed.push(("gh", "GitHub Actions workflows"));
+    }
+    if ["k8s", "kubernetes", "kustomization.yaml"]
+        .iter()
+        .any(|marker| repo.join(marker).exists())
+    {
+        needed.push(("kubectl", "Kubernetes manifests"));
+    }
+    if repo.join("Pulumi.yaml").exists() || repo.join("Pulumi.yml").exists() {
+        needed.push(("pulumi", "Pulumi project"));
+    }
+
+    needed
+        .into_iter()
+        .filter_map(|(name, reason)| {
+            let tool = Tool::find(name)?;
+            Some(Check {
+                tool,
+                reason,
+                installed: tool.is_installed(),
+            })
+        })
+        .collect()
+}
+
+/// Whether any of `files` exists at the repo root or in a package
+fn has_file(config: &Config, files: &[&str]) -> bool {
+    std::iter::once(config.repo_root.as_path())
+        .chain(config.packages.values().map(|pkg| pkg.path.as_path()))
+        .any(|dir: &Path| files.iter().any(|file| dir.join(file).exists()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_check_tools_from_repo_files() {
+        let dir = std::env::temp_dir().join(format!("devkit-doctor-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("packages/web")).unwrap();
+        fs::create_dir_all(dir.join(".dev")).unwrap();
+        fs::write(
+            dir.join(".dev/config.toml"),
+            "[workspaces]\npackages = [\"packages/*\"]\n",
+        )
+        .unwrap();
+        fs::write(dir.join("Cargo.lock"), "").unwrap();
+        fs::write(dir.join("packages/web/pnpm-lock.yaml"), "").unwrap();
+
+        let config = Config::load(&dir).unwrap();
+        let names: Vec<&str> = check(&config).iter().map(|c| c.tool.name).collect();
+        assert_eq!(names, ["git", "cargo-audit", "pnpm"]);
+
+        fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn test_installer_command_line() {
+        let tool = Tool::find("cargo-audit").unwrap();
+        assert_eq!(
+            tool.installers[0].command_line(),
+            "cargo install cargo-audit"
+        );
+        assert!(TOOLS.iter().all(|tool| !tool.installers.is_empty()));
+    }
+}
@@ -0,0 +1,184 @@
+//! Active environment
+//!
+//! `devkit env use <name>` saves the environment for this checkout in `.dev/env.json`.
+//! `DEVKIT_ENV` overrides it for a single run, and `[environments] default` applies when
+//! neither is set. The active environment picks:
+//!
+//! - the config overlay `.dev/config.<env>.toml`, merged over `.dev/config.toml`
+//! - the env files loaded at startup, layered `.env`, `.env.<env>`, `.env.local`,
+//!   `.env.<env>.local` (later files win, the shell's variables win over all)
+//! - `[environments.<env>]` settings: Pulumi stack, kubectl context and secrets source
+
+use crate::config::EnvironmentsConfig;
+use crate::error::{DevkitError, Result};
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const STATE_FILE: &str = ".dev/env.json";
+
+/// Where the active environment came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    /// `DEVKIT_ENV`
+    Variable,
+    /// `devkit env use`
+    Saved,
+    /// `[environments] default`
+    Default,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+struct State {
+    active: Option<String>,
+}
+
+/// The environment saved with `devkit env use`
+pub fn saved(repo_root: &Path) -> Option<String> {
+    let contents = fs::read_to_string(repo_root.join(STATE_FILE)).ok()?;
+    serde_json::from_str::<State>(&contents).ok()?.active
+}
+
+/// Save the active environment; `None` goes back to the default
+pub fn save(repo_root: &Path, name: Option<&str>) -> Result<()> {
+    let path = repo_root.join(STATE_FILE);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let state = State {
+        active: name.map(String::from),
+    };
+    fs::write(
+        path,
+        serde_json::to_string_pretty(&state).map_err(anyhow::Error::from)?,
+    )?;
+    Ok(())
+}
+
+/// The active environment and where it came from
+pub fn resolve(repo_root: &Path, config: &EnvironmentsConfig) -> (String, Source) {
+    if let Some(name) = std::env::var("DEVKIT_ENV").ok().filter(|v| !v.is_empty()) {
+        return (name, Source::Variable);
+    }
+    match saved(repo_root) {
+        Some(name) => (name, Source::Saved),
+        None => (config.default.clone(), Source::Default),
+    }
+}
+
+/// `.dev/config.<env>.toml`
+pub fn overlay_path(repo_root: &Path, env: &str) -> PathBuf {
+    repo_root.join(format!(".dev/config.{}.toml", env))
+}
+
+/// Env files for an environment, lowest precedence first
+pub fn env_files(env: &str) -> [String; 4] {
+    [
+        ".env".to_string(),
+        format!(".env.{}", env),
+        ".env.local".to_string(),
+        format!(".env.{}.local", env),
+    ]
+}
+
+/// Variables from the environment's env files that exist, later files overriding
+/// earlier ones
+pub fn load_env_files(repo_root: &Path, env: &str) -> Vec<(String, String)> {
+    let mut vars: Vec<(String, String)> = Vec::new();
+    for file in env_files(env) {
+        let Ok(iter) = dotenvy::from_path_iter(repo_root.join(&file)) else {
+            continue;
+        };
+        for (key, value) in iter.flatten() {
+            match vars.iter_mut().find(|(k, _)| *k == key) {
+                Some(var) => var.1 = value,
+                None => vars.push((key, value)),
+            }
+        }
+    }
+    vars
+}
+
+/// Variables in one env file, in order
+pub fn read_env_file(path: &Path) -> Result<Vec<(String, String)>> {
+    dotenvy::from_path_iter(path)
+        .and_then(|iter| iter.collect())
+        // dotenvy's errors repeat their cause, so only the message is kept
+        .map_err(|e| DevkitError::Other(anyhow!("Failed to read {}: {}", path.display(), e)))
+}
+
+/// Set the environment's env file variables and `DEVKIT_ENV` for devkit and the
+/// commands it runs. Variables already set in the shell are kept.
+pub fn export_env_files(repo_root: &Path, env: &str) {
+    for (key, value) in load_env_files(repo_root, env) {
+        if std::env::var_os(&key).is_none() {
+            std::env::set_var(key, value);
+        }
+    }
+    std::env::set_var("DEVKIT_ENV", env);
+}
+
+/// Merge `overlay` into `base`: tables merge key by key, anything else is replaced
+pub(crate) fn merge_toml(base: &mut toml::Table, overlay: toml::Table) {
+    for (key, value) in overlay {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base)), toml::Value::Table(overlay)) => {
+                merge_toml(base, overlay)
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_env_files() {
+        let repo = std::env::temp_dir().join(format!("devkit-environment-{}", std::process::id()));
+        fs::create_dir_all(&repo).unwrap();
+        fs::write(
+            repo.join(".env"),
+            "# shared\nAPI_URL=http://localhost:8080\nLOG_LEVEL=info\n",
+        )
+        .unwrap();
+        fs::write(
+            repo.join(".env.staging"),
+            "export API_URL=\"https://staging.example.com\"\n",
+        )
+        .unwrap();
+        fs::write(repo.join(".env.local"), "TOKEN='abc'\n").unwrap();
+        fs::write(repo.join(".env.prod"), "API_URL=https://example.com\n").unwrap();
+
+        assert_eq!(
+            load_env_files(&repo, "staging"),
+            [
+                (
+                    "API_URL".to_string(),
+                    "https://staging.example.com".to_string()
+                ),
+                ("LOG_LEVEL".to_string(), "info".to_string()),
+                ("TOKEN".to_string(), "abc".to_string()),
+            ]
+        );
+
+        fs::remove_dir_all(repo).unwrap();
+    }
+
+    #[test]
+    fn test_merge_toml() {
+        let mut base: toml::Table =
+            "[services]\napi = 8080\npostgres = 5432\n\n[project]\nname = \"app\"\n"
+                .parse()
+                .unwrap();
+        let overlay: toml::Table = "[services]\napi = 9090\n".parse().unwrap();
+        merge_toml(&mut base, overlay);
+        assert_eq!(base["services"]["api"].as_integer(), Some(9090));
+        assert_eq!(base["services"]["postgres"].as_integer(), Some(5432));
+        assert_eq!(base["project"]["name"].as_str(), Some("app"));
+    }
+}
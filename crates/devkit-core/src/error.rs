@@ -58,7 +58,10 @@ pub enum DevkitError {
     #[error("Feature '{feature}' is not available in this project\n{hint}")]
     FeatureNotAvailable { feature: String, hint: String },
 
-    #[error("{0}")]
+    #[error("{action} needs an interactive terminal\n{hint}")]
+    NotInteractive { action: String, hint: String },
+
+    #[error(transparent)]
     Other(#[from] anyhow::Error),
 
     #[error(transparent)]
@@ -122,4 +125,12 @@ impl DevkitError {
     pub fn feature_not_available(feature: String, hint: String) -> Self {
         Self::FeatureNotAvailable { feature, hint }
     }
+
+    /// Create a NotInteractive error; `hint` says how to run without prompting
+    pub fn not_interactive(action: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self::NotInteractive {
+            action: action.into(),
+            hint: hint.into(),
+        }
+    }
 }
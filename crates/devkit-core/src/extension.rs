@@ -2,10 +2,12 @@
 //!
 //! Extensions can:
 //! - Register interactive menu items
-//! - Provide commands
+//! - Provide commands and top-level CLI subcommands
 //! - Handle submenus
+//!
+//! Extensions listed in `[extensions] disabled` are skipped by the registry.
 
-use crate::{AppContext, Result};
+use crate::{AppContext, DevkitError, Result};
 
 /// Menu item that an extension provides
 pub struct MenuItem {
@@ -26,6 +28,12 @@ pub trait Extension {
     /// Uses AppContext.features for auto-detection
     fn is_available(&self, ctx: &AppContext) -> bool;
 
+    /// Optional: Why is_available() returned false (e.g., "kubectl not installed")
+    /// Shown by `devkit ext list`
+    fn unavailable_reason(&self, _ctx: &AppContext) -> Option<String> {
+        None
+    }
+
     /// Get menu items for the main interactive menu
     /// Only called if is_available() returns true
     /// Takes AppContext to allow dynamic menu generation based on project state
@@ -42,6 +50,25 @@ pub trait Extension {
         None
     }
 
+    /// Optional: Top-level CLI subcommands (`devkit <name> ...`)
+    /// Built without AppContext so they appear in `--help` and shell completions
+    fn subcommands(&self) -> Vec<clap::Command> {
+        Vec::new()
+    }
+
+    /// Optional: Handle one of the subcommands returned by subcommands()
+    /// `matches` holds the subcommand's own arguments
+    /// Return None if `name` isn't one of this extension's subcommands
+    /// Subcommands that need the project set up can return `unavailable_error()`
+    fn handle_cli(
+        &self,
+        _ctx: &AppContext,
+        _name: &str,
+        _matches: &clap::ArgMatches,
+    ) -> Option<Result<()>> {
+        None
+    }
+
     /// Optional: Prerun hook - runs on startup before any commands
     /// Use this to ensure the repo is in a runnable state
     /// Examples: install dependencies, pull docker images, run migrations
@@ -51,6 +78,16 @@ pub trait Extension {
     }
 }
 
+/// Availability of a registered extension
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExtensionStatus {
+    Available,
+    /// Listed in `[extensions] disabled`
+    Disabled,
+    /// is_available() returned false, with the extension's reason if it gave one
+    Unavailable(Option<String>),
+}
+
 /// Extension registry - collects all extensions
 pub struct ExtensionRegistry {
     extensions: Vec<Box<dyn Extension>>,
@@ -64,22 +101,18 @@ impl ExtensionRegistry {
     }
 
     /// Create registry with external extensions loaded
-    pub fn with_external_extensions(repo_root: &std::path::Path) -> Self {
+    /// Fails if an extension is broken or incompatible, unless `skip_broken` is set
+    pub fn with_external_extensions(
+        repo_root: &std::path::Path,
+        skip_broken: bool,
+    ) -> Result<Self> {
         let mut registry = Self::new();
 
-        // Load external extensions
-        match crate::extension_loader::load_external_extensions(repo_root) {
-            Ok(extensions) => {
-                for ext in extensions {
-                    registry.register(ext);
-                }
-            }
-            Err(e) => {
-                tracing::warn!("Failed to load external extensions: {}", e);
-            }
+        for ext in crate::extension_loader::load_external_extensions(repo_root, skip_broken)? {
+            registry.register(ext);
         }
 
-        registry
+        Ok(registry)
     }
 
     pub fn register(&mut self, extension: Box<dyn Extension>) {
@@ -87,10 +120,28 @@ impl ExtensionRegistry {
     }
 
     /// Get all available extensions for the current project
+    /// Disabled extensions are excluded
     pub fn available_extensions<'a>(&'a self, ctx: &'a AppContext) -> Vec<&'a Box<dyn Extension>> {
         self.extensions
             .iter()
-            .filter(|ext| ext.is_available(ctx))
+            .filter(|ext| !is_disabled(ctx, ext.as_ref()) && ext.is_available(ctx))
+            .collect()
+    }
+
+    /// Every registered extension with its availability
+    pub fn statuses(&self, ctx: &AppContext) -> Vec<(&str, ExtensionStatus)> {
+        self.extensions
+            .iter()
+            .map(|ext| {
+                let status = if is_disabled(ctx, ext.as_ref()) {
+                    ExtensionStatus::Disabled
+                } else if ext.is_available(ctx) {
+                    ExtensionStatus::Available
+                } else {
+                    ExtensionStatus::Unavailable(ext.unavailable_reason(ctx))
+                };
+                (ext.name(), status)
+            })
             .collect()
     }
 
@@ -102,6 +153,31 @@ impl ExtensionRegistry {
             .collect()
     }
 
+    /// CLI subcommands contributed by all registered extensions
+    pub fn subcommands(&self) -> Vec<clap::Command> {
+        self.extensions
+            .iter()
+            .flat_map(|ext| ext.subcommands())
+            .collect()
+    }
+
+    /// Dispatch an extension subcommand
+    /// Returns None if no registered extension provides `name`
+    pub fn handle_cli(
+        &self,
+        ctx: &AppContext,
+        name: &str,
+        matches: &clap::ArgMatches,
+    ) -> Option<Result<()>> {
+        self.extensions.iter().find_map(|ext| {
+            if is_disabled(ctx, ext.as_ref()) {
+                let provides = ext.subcommands().iter().any(|cmd| cmd.get_name() == name);
+                return provides.then(|| Err(disabled_error(ext.name())));
+            }
+            ext.handle_cli(ctx, name, matches)
+        })
+    }
+
     /// Run all prerun hooks from available extensions
     /// Returns the first error encountered, or Ok if all succeeded
     pub fn run_prerun_hooks(&self, ctx: &AppContext) -> Result<()> {
@@ -117,3 +193,25 @@ impl Default for ExtensionRegistry {
         Self::new()
     }
 }
+
+fn is_disabled(ctx: &AppContext, ext: &dyn Extension) -> bool {
+    ctx.config.global.extensions.is_disabled(ext.name())
+}
+
+/// Error for a subcommand of an extension that isn't available in this project, with
+/// its unavailable_reason() as the hint
+pub fn unavailable_error(ext: &dyn Extension, ctx: &AppContext) -> DevkitError {
+    DevkitError::FeatureNotAvailable {
+        feature: ext.name().to_string(),
+        hint: ext
+            .unavailable_reason(ctx)
+            .unwrap_or_else(|| "This command requires project setup".to_string()),
+    }
+}
+
+fn disabled_error(name: &str) -> DevkitError {
+    DevkitError::FeatureNotAvailable {
+        feature: name.to_string(),
+        hint: "It is disabled in .dev/config.toml ([extensions] disabled)".to_string(),
+    }
+}
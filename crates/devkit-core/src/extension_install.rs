@@ -0,0 +1,486 @@
+//! Installing external extensions
+//!
+//! `devkit ext install <git-url|path>` clones or copies an extension into
+//! `.dev/extensions/<name>/`, validates its config.toml, and records where it came
+//! from in `.dev/extensions/installed.toml` so `ext update` can fetch it again.
+//!
+//! ```toml
+//! [tools]
+//! source = "https://github.com/acme/devkit-tools.git"
+//! ref = "v1.2.0"
+//! version = "1.2.0"
+//! rev = "3f2c9a1e..."
+//! installed_at = 1760000000
+//! ```
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::external_extension::ExternalExtension;
+use crate::oplog::LoggedCommand;
+
+const EXTENSIONS_DIR: &str = ".dev/extensions";
+const INSTALLED_FILE: &str = "installed.toml";
+
+/// Where an installed extension came from
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct InstalledExtension {
+    /// Git URL, or a local path (repo-relative when inside the repo)
+    pub source: String,
+    /// Branch or tag the extension was cloned from
+    #[serde(rename = "ref", default, skip_serializing_if = "Option::is_none")]
+    pub git_ref: Option<String>,
+    /// `version` from the extension's config.toml
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// Commit the extension was installed from (git sources only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rev: Option<String>,
+    /// Unix timestamp of the install or last update
+    pub installed_at: u64,
+}
+
+/// Options for `ext install`
+#[derive(Debug, Default)]
+pub struct InstallOptions {
+    /// Branch or tag to clone
+    pub git_ref: Option<String>,
+    /// Replace an extension that's already installed
+    pub force: bool,
+}
+
+/// Result of updating one extension
+#[derive(Debug)]
+pub struct UpdateOutcome {
+    pub name: String,
+    pub previous: InstalledExtension,
+    pub current: InstalledExtension,
+}
+
+impl UpdateOutcome {
+    /// Whether the update changed the installed commit or version
+    pub fn changed(&self) -> bool {
+        self.previous.rev != self.current.rev
+            || self.previous.version != self.current.version
+            || self.previous.rev.is_none()
+    }
+}
+
+/// Extensions recorded in `.dev/extensions/installed.toml`
+pub fn load_installed(repo_root: &Path) -> Result<BTreeMap<String, InstalledExtension>> {
+    let path = installed_path(repo_root);
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+    let contents = fs::read_to_string(&path)?;
+    toml::from_str(&contents).with_context(|| format!("Invalid {}", path.display()))
+}
+
+fn save_installed(
+    repo_root: &Path,
+    installed: &BTreeMap<String, InstalledExtension>,
+) -> Result<()> {
+    let path = installed_path(repo_root);
+    if installed.is_empty() {
+        if path.exists() {
+            fs::remove_file(&path)?;
+        }
+        return Ok(());
+    }
+    fs::create_dir_all(repo_root.join(EXTENSIONS_DIR))?;
+    fs::write(&path, toml::to_string_pretty(installed)?)?;
+    Ok(())
+}
+
+fn installed_path(repo_root: &Path) -> PathBuf {
+    repo_root.join(EXTENSIONS_DIR).join(INSTALLED_FILE)
+}
+
+/// Check that a directory holds a usable extension
+///
+/// The config must parse, the name must be usable as a directory name, and every
+/// static action needs a unique id and a command that exists inside the extension
+/// (as does `discover`). Discovered actions are only known at runtime.
+pub fn validate_extension(dir: &Path) -> Result<ExternalExtension> {
+    let ext = ExternalExtension::load(dir)?;
+    let config = ext.config();
+
+    if !is_valid_name(&config.name) {
+        bail!(
+            "Invalid extension name '{}' (use letters, digits, '-' and '_')",
+            config.name
+        );
+    }
+    if config.action.is_empty() && config.discover.is_none() {
+        bail!(
+            "Extension '{}' defines no [[action]] and no discover",
+            config.name
+        );
+    }
+
+    let root = dir.canonicalize()?;
+    let check_command = |what: &str, command: &str| -> Result<()> {
+        let path = dir
+            .join(command)
+            .canonicalize()
+            .map_err(|_| anyhow!("{}: command not found: {}", what, command))?;
+        if !path.starts_with(&root) {
+            bail!(
+                "{}: command must be inside the extension directory: {}",
+                what,
+                command
+            );
+        }
+        Ok(())
+    };
+
+    if let Some(discover) = &config.discover {
+        check_command("discover", discover)?;
+    }
+    let mut ids = Vec::new();
+    for action in &config.action {
+        if ids.contains(&action.id.as_str()) {
+            bail!("Duplicate action id '{}'", action.id);
+        }
+        ids.push(&action.id);
+        check_command(&format!("Action '{}'", action.id), &action.command)?;
+    }
+
+    Ok(ext)
+}
+
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Install an extension from a git URL or a local directory
+pub fn install_extension(
+    repo_root: &Path,
+    source: &str,
+    opts: &InstallOptions,
+) -> Result<(String, InstalledExtension)> {
+    let local = Path::new(source);
+    let source = if local.is_dir() {
+        record_path(repo_root, local)?
+    } else {
+        source.to_string()
+    };
+
+    let mut installed = load_installed(repo_root)?;
+    let (name, entry) = fetch(repo_root, &source, opts.git_ref.as_deref(), |name| {
+        if !opts.force && repo_root.join(EXTENSIONS_DIR).join(name).exists() {
+            bail!(
+                "Extension '{}' is already installed (use `devkit ext update {}` or --force)",
+                name,
+                name
+            );
+        }
+        Ok(())
+    })?;
+
+    installed.insert(name.clone(), entry.clone());
+    save_installed(repo_root, &installed)?;
+    Ok((name, entry))
+}
+
+/// Re-fetch an installed extension from its recorded source
+pub fn update_extension(repo_root: &Path, name: &str) -> Result<UpdateOutcome> {
+    let mut installed = load_installed(repo_root)?;
+    let previous = installed.get(name).cloned().ok_or_else(|| {
+        anyhow!(
+            "Extension '{}' wasn't installed with `devkit ext install`",
+            name
+        )
+    })?;
+
+    let (_, current) = fetch(
+        repo_root,
+        &previous.source,
+        previous.git_ref.as_deref(),
+        |fetched| {
+            if fetched != name {
+                bail!(
+                    "Source now provides extension '{}' instead of '{}'; remove and reinstall it",
+                    fetched,
+                    name
+                );
+            }
+            Ok(())
+        },
+    )?;
+
+    installed.insert(name.to_string(), current.clone());
+    save_installed(repo_root, &installed)?;
+    Ok(UpdateOutcome {
+        name: name.to_string(),
+        previous,
+        current,
+    })
+}
+
+/// Delete an extension directory and its install record
+pub fn remove_extension(repo_root: &Path, name: &str) -> Result<()> {
+    if !is_valid_name(name) {
+        bail!("Invalid extension name '{}'", name);
+    }
+    let mut installed = load_installed(repo_root)?;
+    let dir = repo_root.join(EXTENSIONS_DIR).join(name);
+    if !dir.exists() && !installed.contains_key(name) {
+        bail!("Extension '{}' is not installed", name);
+    }
+
+    if dir.exists() {
+        fs::remove_dir_all(&dir).with_context(|| format!("Failed to remove {}", dir.display()))?;
+    }
+    installed.remove(name);
+    save_installed(repo_root, &installed)
+}
+
+/// Fetch `source` into a staging directory, validate it, then move it into place
+///
+/// `check` sees the extension name before anything in `.dev/extensions` changes.
+fn fetch(
+    repo_root: &Path,
+    source: &str,
+    git_ref: Option<&str>,
+    check: impl FnOnce(&str) -> Result<()>,
+) -> Result<(String, InstalledExtension)> {
+    let local = repo_root.join(source);
+    // Created fresh with a random name and removed when dropped
+    let staging = tempfile::Builder::new()
+        .prefix("devkit-ext-")
+        .tempdir()
+        .context("Failed to create a staging directory")?;
+
+    let (dir, rev) = if local.is_dir() {
+        if git_ref.is_some() {
+            bail!("--ref only applies to git sources");
+        }
+        (local, None)
+    } else {
+        clone(source, git_ref, staging.path())?;
+        let rev = git_output(staging.path(), &["rev-parse", "HEAD"]);
+        (staging.path().to_path_buf(), rev)
+    };
+
+    let result = reject_symlinks(&dir)
+        .and_then(|_| validate_extension(&dir))
+        .and_then(|ext| {
+            let name = ext.config().name.clone();
+            check(&name)?;
+
+            let dest = repo_root.join(EXTENSIONS_DIR).join(&name);
+            if dest.canonicalize().ok() == Some(dir.canonicalize()?) {
+                bail!("{} is already the installed copy", dir.display());
+            }
+            if dest.exists() {
+                fs::remove_dir_all(&dest)?;
+            }
+            copy_dir(&dir, &dest)?;
+
+            Ok((
+                name,
+                InstalledExtension {
+                    source: source.to_string(),
+                    git_ref: git_ref.map(String::from),
+                    version: ext.config().version.clone(),
+                    rev,
+                    installed_at: SystemTime::now()
+                        .duration_since(UNIX_EPOCH)
+                        .map(|d| d.as_secs())
+                        .unwrap_or_default(),
+                },
+            ))
+        });
+
+    let _ = staging.close();
+    result
+}
+
+fn clone(url: &str, git_ref: Option<&str>, dest: &Path) -> Result<()> {
+    let mut cmd = Command::new("git");
+    cmd.args(["clone", "--quiet", "--depth", "1"]);
+    if let Some(git_ref) = git_ref {
+        cmd.args(["--branch", git_ref]);
+    }
+    cmd.arg("--").arg(url).arg(dest);
+
+    let status = cmd.logged_status().context("Failed to run git clone")?;
+    if !status.success() {
+        bail!("git clone {} failed", url);
+    }
+    Ok(())
+}
+
+fn git_output(dir: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .logged_output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// How a local source is recorded: repo-relative inside the repo, absolute otherwise
+fn record_path(repo_root: &Path, path: &Path) -> Result<String> {
+    let absolute = path.canonicalize()?;
+    let root = repo_root.canonicalize()?;
+    match absolute.strip_prefix(&root) {
+        Ok(relative) if relative.as_os_str().is_empty() => Ok(".".to_string()),
+        Ok(relative) => Ok(relative.to_string_lossy().replace('\\', "/")),
+        Err(_) => Ok(absolute.to_string_lossy().replace('\\', "/")),
+    }
+}
+
+/// Fail on any symlink in the tree `copy_dir` copies, since copying follows them and
+/// a fetched extension could point them at files elsewhere on disk
+fn reject_symlinks(dir: &Path) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        if entry.file_name() == ".git" || entry.file_name() == ".dev" {
+            continue;
+        }
+        let file_type = entry.file_type()?;
+        if file_type.is_symlink() {
+            bail!(
+                "{} is a symlink; extensions can't contain symlinks",
+                entry.path().display()
+            );
+        }
+        if file_type.is_dir() {
+            reject_symlinks(&entry.path())?;
+        }
+    }
+    Ok(())
+}
+
+/// Copy a directory tree, skipping `.git` and `.dev`
+///
+/// Skipping `.dev` also keeps an extension that lives at a repo root from being
+/// copied into its own `.dev/extensions`.
+fn copy_dir(from: &Path, to: &Path) -> Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let path = entry.path();
+        if entry.file_name() == ".git" || entry.file_name() == ".dev" {
+            continue;
+        }
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&path, &target)?;
+        } else {
+            // fs::copy keeps permissions, so scripts stay executable
+            fs::copy(&path, &target)
+                .with_context(|| format!("Failed to copy {}", path.display()))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "devkit-install-test-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn write_extension(dir: &Path, name: &str, command: &str) {
+        fs::create_dir_all(dir).unwrap();
+        fs::write(
+            dir.join("config.toml"),
+            format!(
+                "name = \"{}\"\nversion = \"0.1.0\"\n\n[[action]]\nid = \"hi\"\nlabel = \"Hi\"\ncommand = \"{}\"\n",
+                name, command
+            ),
+        )
+        .unwrap();
+        fs::write(dir.join("hi.sh"), "#!/bin/sh\necho hi\n").unwrap();
+    }
+
+    #[test]
+    fn test_validate_extension() {
+        let dir = scratch_dir("validate");
+        write_extension(&dir.join("ok"), "ok", "hi.sh");
+        write_extension(&dir.join("missing"), "missing", "nope.sh");
+        write_extension(&dir.join("escape"), "escape", "../ok/hi.sh");
+        write_extension(&dir.join("badname"), "bad name", "hi.sh");
+
+        assert!(validate_extension(&dir.join("ok")).is_ok());
+        assert!(validate_extension(&dir.join("missing")).is_err());
+        assert!(validate_extension(&dir.join("escape")).is_err());
+        assert!(validate_extension(&dir.join("badname")).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_install_rejects_symlinks() {
+        let repo = scratch_dir("symlink");
+        let source = repo.join("vendor/tools");
+        write_extension(&source, "tools", "hi.sh");
+        fs::create_dir_all(source.join("lib")).unwrap();
+        std::os::unix::fs::symlink(repo.join("secret"), source.join("lib/secret")).unwrap();
+
+        let error = install_extension(&repo, source.to_str().unwrap(), &InstallOptions::default())
+            .unwrap_err();
+        assert!(error.to_string().contains("can't contain symlinks"));
+        assert!(!repo.join(".dev/extensions/tools").exists());
+        fs::remove_dir_all(&repo).unwrap();
+    }
+
+    #[test]
+    fn test_install_update_remove_local_extension() {
+        let repo = scratch_dir("repo");
+        let source = repo.join("vendor/tools");
+        write_extension(&source, "tools", "hi.sh");
+
+        let (name, entry) =
+            install_extension(&repo, source.to_str().unwrap(), &InstallOptions::default()).unwrap();
+        assert_eq!(name, "tools");
+        assert_eq!(entry.source, "vendor/tools");
+        assert_eq!(entry.version.as_deref(), Some("0.1.0"));
+        assert!(repo.join(".dev/extensions/tools/hi.sh").exists());
+        assert_eq!(load_installed(&repo).unwrap()["tools"], entry);
+
+        // Installing again needs --force
+        assert!(
+            install_extension(&repo, source.to_str().unwrap(), &InstallOptions::default()).is_err()
+        );
+
+        fs::write(
+            source.join("config.toml"),
+            fs::read_to_string(source.join("config.toml"))
+                .unwrap()
+                .replace("0.1.0", "0.2.0"),
+        )
+        .unwrap();
+        let outcome = update_extension(&repo, "tools").unwrap();
+        assert!(outcome.changed());
+        assert_eq!(outcome.current.version.as_deref(), Some("0.2.0"));
+
+        remove_extension(&repo, "tools").unwrap();
+        assert!(!repo.join(".dev/extensions/tools").exists());
+        assert!(load_installed(&repo).unwrap().is_empty());
+        fs::remove_dir_all(&repo).unwrap();
+    }
+}
@@ -1,41 +1,102 @@
-use std::path::Path;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use anyhow::Result;
 use crate::{Extension, external_extension::ExternalExtension};
 
-/// Discover and load all external extensions from directories
-pub fn load_external_extensions(repo_root: &Path) -> Result<Vec<Box<dyn Extension>>> {
-    let mut extensions = Vec::new();
+/// Directories scanned for external extensions, lowest precedence first:
+/// the user-global `<config dir>/devkit/extensions`, then the project's `.dev/extensions`
+pub fn extension_dirs(repo_root: &Path) -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = dirs::config_dir()
+        .map(|dir| dir.join("devkit/extensions"))
+        .into_iter()
+        .collect();
+    dirs.push(repo_root.join(".dev/extensions"));
+    dirs
+}
 
-    let ext_dir = repo_root.join(".dev/extensions");
-    if !ext_dir.exists() {
-        return Ok(extensions);
-    }
+/// External extensions found in the extension directories
+pub struct DiscoveredExtensions {
+    pub extensions: Vec<ExternalExtension>,
+    /// Extension directories that failed to load or are incompatible, with the reason
+    pub broken: Vec<(PathBuf, anyhow::Error)>,
+}
 
-    tracing::info!("Scanning for extensions in {}", ext_dir.display());
+impl DiscoveredExtensions {
+    /// The loaded extensions; broken ones are an error unless `skip_broken` is set,
+    /// in which case they're logged and left out
+    pub fn into_loaded(self, skip_broken: bool) -> Result<Vec<ExternalExtension>> {
+        if self.broken.is_empty() {
+            return Ok(self.extensions);
+        }
 
-    // Find all subdirectories in the extensions directory
-    for entry in std::fs::read_dir(&ext_dir)? {
-        let entry = entry?;
-        let path = entry.path();
+        if skip_broken {
+            for (path, e) in &self.broken {
+                tracing::warn!("✗ Skipping broken extension {}: {:#}", path.display(), e);
+            }
+            return Ok(self.extensions);
+        }
 
-        // Only process directories
-        if !path.is_dir() {
+        let details: Vec<String> = self.broken
+            .iter()
+            .map(|(path, e)| format!("  {}: {:#}", path.display(), e))
+            .collect();
+        anyhow::bail!(
+            "{} extension(s) failed to load:\n{}\nFix or remove them, or pass --skip-broken \
+             (or set [extensions] skip_broken = true) to start without them",
+            self.broken.len(),
+            details.join("\n")
+        )
+    }
+}
+
+/// Discover external extensions (`<dir>/*/config.toml`) in all extension directories
+///
+/// A project extension replaces a user-global one with the same name. Extensions that
+/// fail to load are collected in `broken` rather than failing discovery.
+pub fn discover_external_extensions(repo_root: &Path) -> Result<DiscoveredExtensions> {
+    let mut by_name: HashMap<String, ExternalExtension> = HashMap::new();
+    let mut broken = Vec::new();
+
+    for ext_dir in extension_dirs(repo_root) {
+        if !ext_dir.exists() {
             continue;
         }
 
-        // Try to load config.toml from this directory
-        match ExternalExtension::load(&path) {
-            Ok(ext) => {
-                tracing::info!("✓ Loaded extension: {} from {}",
-                    ext.name(), path.display());
-                extensions.push(Box::new(ext) as Box<dyn Extension>);
-            }
-            Err(e) => {
-                tracing::warn!("✗ Failed to load extension from {}: {}",
-                    path.display(), e);
+        tracing::info!("Scanning for extensions in {}", ext_dir.display());
+
+        let mut paths: Vec<PathBuf> = std::fs::read_dir(&ext_dir)?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            // Only process directories
+            .filter(|path| path.is_dir())
+            .collect();
+        paths.sort();
+
+        for path in paths {
+            // Try to load config.toml from this directory
+            match ExternalExtension::load(&path) {
+                Ok(ext) => {
+                    tracing::info!("✓ Loaded extension: {} from {}",
+                        ext.name(), path.display());
+                    by_name.insert(ext.name().to_string(), ext);
+                }
+                Err(e) => broken.push((path, e)),
             }
         }
     }
 
-    Ok(extensions)
+    let mut extensions: Vec<ExternalExtension> = by_name.into_values().collect();
+    extensions.sort_by(|a, b| a.name().cmp(b.name()));
+    Ok(DiscoveredExtensions { extensions, broken })
+}
+
+/// Discover and load all external extensions from directories
+///
+/// Fails if any extension is broken, unless `skip_broken` is set.
+pub fn load_external_extensions(repo_root: &Path, skip_broken: bool) -> Result<Vec<Box<dyn Extension>>> {
+    Ok(discover_external_extensions(repo_root)?
+        .into_loaded(skip_broken)?
+        .into_iter()
+        .map(|ext| Box::new(ext) as Box<dyn Extension>)
+        .collect())
 }
@@ -1,8 +1,56 @@
-use serde::Deserialize;
+//! External extensions defined by `config.toml` in an extension directory
+//!
+//! Actions are listed statically as `[[action]]` tables, or produced at runtime by a
+//! `discover` executable that prints them as JSON on stdout:
+//!
+//! ```toml
+//! name = "db-tools"
+//! discover = "discover.sh"
+//!
+//! [[action]]
+//! id = "status"
+//! label = "📊 DB status"
+//! command = "status.sh"
+//! protocol = "json"
+//! ```
+//!
+//! `discover` prints `{"actions": [...]}` (or a bare array) using the same fields as
+//! `[[action]]`, so it can emit e.g. one action per package with `package` set.
+//!
+//! Every command gets `DEVKIT_*` environment variables. With `protocol = "json"`, it
+//! also receives the context on stdin (and `discover` always does):
+//!
+//! ```json
+//! {"protocol": 1, "action": "status", "args": [], "repo_root": "/repo", "quiet": false,
+//!  "features": {"docker": true, ...}, "config": {...}, "packages": [...],
+//!  "package": {"name": "api", "path": "/repo/packages/api", "commands": ["test"]},
+//!  "env": {...}, "ext_config": {...}}
+//! ```
+//!
+//! Extensions declare the API they were written for with `devkit_api_version`. Loading
+//! fails for versions this devkit doesn't support, and warns for outdated ones.
+//!
+//! `ext_config` is the extension's `[ext.<name>]` table from `.dev/config.toml`
+//! (also passed as JSON in `DEVKIT_EXT_CONFIG`), so projects can configure an
+//! extension without editing it.
+
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::io::Write as _;
 use std::path::{Path, PathBuf};
-use std::process::Command;
+use std::process::{Command, Stdio};
 use anyhow::{Context as _, Result};
+use crate::discovery_cache::{self, Fingerprint};
+use crate::oplog::{LoggedCommand, Operation};
+
+/// Version of the JSON context passed to `discover` and `protocol = "json"` actions
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Extension API version implemented by this devkit (`devkit_api_version`)
+pub const API_VERSION: u32 = 1;
+
+/// Oldest `devkit_api_version` still supported
+pub const MIN_API_VERSION: u32 = 1;
 
 /// Extension definition from TOML file
 #[derive(Debug, Deserialize)]
@@ -10,11 +58,16 @@ pub struct ExtensionConfig {
     pub name: String,
     pub version: Option<String>,
     pub description: Option<String>,
+    /// Extension API version the extension was written for
+    pub devkit_api_version: Option<u32>,
+    /// Executable that prints actions as JSON (relative to extension directory)
+    pub discover: Option<String>,
+    #[serde(default)]
     pub action: Vec<ActionConfig>,
 }
 
-/// Action definition from TOML
-#[derive(Debug, Deserialize, Clone)]
+/// Action definition from TOML, or from `discover` output
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ActionConfig {
     pub id: String,
     pub label: String,
@@ -23,6 +76,38 @@ pub struct ActionConfig {
     pub command: String,  // Path to executable (relative to extension directory)
     pub args: Option<Vec<String>>,  // Optional arguments
     pub env: Option<HashMap<String, String>>,  // Optional env vars
+    /// Package the action applies to (passed as DEVKIT_PACKAGE and in the JSON context)
+    pub package: Option<String>,
+    /// How context is passed to the command
+    #[serde(default)]
+    pub protocol: ActionProtocol,
+}
+
+/// How an action's command receives devkit context
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ActionProtocol {
+    /// `DEVKIT_*` environment variables only
+    #[default]
+    Env,
+    /// Environment variables plus a JSON context document on stdin
+    Json,
+}
+
+/// `discover` output: `{"actions": [...]}` or a bare array
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum DiscoverOutput {
+    Wrapped { actions: Vec<ActionConfig> },
+    Bare(Vec<ActionConfig>),
+}
+
+impl DiscoverOutput {
+    fn into_actions(self) -> Vec<ActionConfig> {
+        match self {
+            DiscoverOutput::Wrapped { actions } | DiscoverOutput::Bare(actions) => actions,
+        }
+    }
 }
 
 /// Wrapper that implements Extension trait for TOML-defined extensions
@@ -47,39 +132,159 @@ impl ExternalExtension {
         let config: ExtensionConfig = toml::from_str(&toml_content)
             .context(format!("Failed to parse TOML from {}", toml_path.display()))?;
 
+        if let Some(warning) = check_api_version(&config)? {
+            tracing::warn!("Extension {}: {}", config.name, warning);
+        }
+
         Ok(Self {
             config,
             extension_dir: extension_dir.to_path_buf(),
         })
     }
 
-    fn execute_action(&self, ctx: &crate::AppContext, action: &ActionConfig) -> Result<()> {
-        // Resolve command path (relative to extension directory)
-        let command_path = self.extension_dir.join(&action.command);
+    /// Parsed config.toml
+    pub fn config(&self) -> &ExtensionConfig {
+        &self.config
+    }
+
+    /// Directory the extension was loaded from
+    pub fn extension_dir(&self) -> &Path {
+        &self.extension_dir
+    }
 
-        if !command_path.exists() {
+    /// Static actions followed by the ones printed by `discover` (cached in `.dev/cache`)
+    ///
+    /// A failing `discover` is logged and contributes no actions.
+    pub fn actions(&self, ctx: &crate::AppContext) -> Vec<ActionConfig> {
+        let mut actions = self.config.action.clone();
+        if self.config.discover.is_some() {
+            match self.discover_cached(ctx) {
+                Ok((discovered, _)) => actions.extend(discovered),
+                Err(e) => tracing::warn!(
+                    "✗ Extension {}: discover failed: {:#}",
+                    self.config.name,
+                    e
+                ),
+            }
+        }
+        actions
+    }
+
+    /// Discovered actions from the cache while it's current, otherwise from `discover`
+    /// (and then cached); the flag is whether they came from the cache
+    pub fn discover_cached(&self, ctx: &crate::AppContext) -> Result<(Vec<ActionConfig>, bool)> {
+        let fingerprint = Fingerprint::new(ctx, &self.extension_dir);
+        if let Some(actions) = discovery_cache::lookup(&ctx.repo, &self.config.name, &fingerprint) {
+            return Ok((actions, true));
+        }
+
+        let actions = self.discover(ctx)?;
+        if let Err(e) = discovery_cache::store(&ctx.repo, &self.config.name, fingerprint, &actions) {
+            tracing::debug!("Failed to cache actions of {}: {:#}", self.config.name, e);
+        }
+        Ok((actions, false))
+    }
+
+    /// Run the `discover` executable and parse the actions it prints
+    pub fn discover(&self, ctx: &crate::AppContext) -> Result<Vec<ActionConfig>> {
+        let Some(discover) = &self.config.discover else {
+            return Ok(Vec::new());
+        };
+        let command_path = self.resolve_command(discover)?;
+
+        let mut cmd = Command::new(&command_path);
+        self.apply_env(&mut cmd, ctx, None);
+        cmd.current_dir(&ctx.repo)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit());
+
+        let op = Operation::start(&cmd);
+        let mut child = cmd
+            .spawn()
+            .context(format!("Failed to execute {}", command_path.display()))?;
+        write_context(&mut child, &context_json(ctx, &self.config.name, None, &[]))?;
+        let output = child.wait_with_output();
+        op.finish_output(&output);
+        let output = output?;
+
+        if !output.status.success() {
             anyhow::bail!(
-                "Command not found: {} (resolved to {})",
-                action.command,
-                command_path.display()
+                "{} failed with exit code {}",
+                discover,
+                output.status.code().unwrap_or(-1)
             );
         }
 
-        let mut cmd = Command::new(&command_path);
+        let parsed: DiscoverOutput = serde_json::from_slice(&output.stdout)
+            .context(format!("Invalid JSON from {}", discover))?;
+        Ok(parsed.into_actions())
+    }
 
-        // Add any configured args
-        if let Some(args) = &action.args {
-            cmd.args(args);
+    /// Find an action by id, including discovered actions
+    pub fn find_action(&self, ctx: &crate::AppContext, id: &str) -> Option<ActionConfig> {
+        self.actions(ctx).into_iter().find(|action| action.id == id)
+    }
+
+    /// Run an action by id, appending `extra_args` to its configured args
+    pub fn run_action(&self, ctx: &crate::AppContext, id: &str, extra_args: &[String]) -> Result<()> {
+        let action = self.find_action(ctx, id).ok_or_else(|| {
+            anyhow::anyhow!("Extension '{}' has no action '{}'", self.config.name, id)
+        })?;
+        self.execute_action(ctx, &action, extra_args)
+    }
+
+    /// Resolve a command path relative to the extension directory
+    fn resolve_command(&self, command: &str) -> Result<PathBuf> {
+        let command_path = self.extension_dir.join(command);
+        if !command_path.exists() {
+            anyhow::bail!(
+                "Command not found: {} (resolved to {})",
+                command,
+                command_path.display()
+            );
         }
+        Ok(command_path)
+    }
 
-        // Set context via environment variables
+    /// Set context via environment variables
+    fn apply_env(&self, cmd: &mut Command, ctx: &crate::AppContext, action: Option<&ActionConfig>) {
         cmd.env("DEVKIT_REPO_ROOT", &ctx.repo);
+        cmd.env("DEVKIT_EXTENSION_DIR", &self.extension_dir);
+        cmd.env("DEVKIT_ENV", ctx.config.global.environments.current());
+        cmd.env("DEVKIT_PROTOCOL_VERSION", PROTOCOL_VERSION.to_string());
         cmd.env("DEVKIT_QUIET", if ctx.quiet { "1" } else { "0" });
         cmd.env("DEVKIT_FEATURE_DOCKER", if ctx.features.docker { "1" } else { "0" });
         cmd.env("DEVKIT_FEATURE_GIT", if ctx.features.git { "1" } else { "0" });
         cmd.env("DEVKIT_FEATURE_CARGO", if ctx.features.cargo { "1" } else { "0" });
         cmd.env("DEVKIT_FEATURE_NODE", if ctx.features.node { "1" } else { "0" });
         cmd.env("DEVKIT_FEATURE_DATABASE", if ctx.features.database { "1" } else { "0" });
+        cmd.env("DEVKIT_EXT_CONFIG", ext_config_json(ctx, &self.config.name).to_string());
+
+        if let Some(package) = action.and_then(|a| a.package.as_deref()) {
+            cmd.env("DEVKIT_PACKAGE", package);
+            if let Some(pkg) = ctx.config.packages.get(package) {
+                cmd.env("DEVKIT_PACKAGE_PATH", &pkg.path);
+            }
+        }
+    }
+
+    fn execute_action(
+        &self,
+        ctx: &crate::AppContext,
+        action: &ActionConfig,
+        extra_args: &[String],
+    ) -> Result<()> {
+        let command_path = self.resolve_command(&action.command)?;
+        let mut cmd = Command::new(&command_path);
+
+        // Add any configured args
+        if let Some(args) = &action.args {
+            cmd.args(args);
+        }
+        cmd.args(extra_args);
+
+        self.apply_env(&mut cmd, ctx, Some(action));
 
         // Add any custom env vars from config
         if let Some(env) = &action.env {
@@ -92,8 +297,21 @@ impl ExternalExtension {
         cmd.current_dir(&ctx.repo);
 
         // Execute and wait
-        let status = cmd.status()
-            .context(format!("Failed to execute {}", command_path.display()))?;
+        let status = match action.protocol {
+            ActionProtocol::Env => cmd.logged_status()
+                .context(format!("Failed to execute {}", command_path.display()))?,
+            ActionProtocol::Json => {
+                let op = Operation::start(cmd.stdin(Stdio::piped()));
+                let mut child = cmd
+                    .spawn()
+                    .context(format!("Failed to execute {}", command_path.display()))?;
+                let context = context_json(ctx, &self.config.name, Some(action), extra_args);
+                write_context(&mut child, &context)?;
+                let status = child.wait();
+                op.finish_status(&status);
+                status?
+            }
+        };
 
         if !status.success() {
             anyhow::bail!(
@@ -107,6 +325,120 @@ impl ExternalExtension {
     }
 }
 
+/// Check `devkit_api_version` against the versions this devkit supports
+///
+/// Returns a warning for extensions that are outdated or don't declare a version.
+pub fn check_api_version(config: &ExtensionConfig) -> Result<Option<String>> {
+    let Some(version) = config.devkit_api_version else {
+        return Ok(Some(format!(
+            "no devkit_api_version set (assuming {}); add `devkit_api_version = {}` to its config.toml",
+            MIN_API_VERSION, API_VERSION
+        )));
+    };
+
+    if version > API_VERSION {
+        anyhow::bail!(
+            "requires extension API v{}, but devkit {} supports up to v{}; run `devkit update`",
+            version,
+            env!("CARGO_PKG_VERSION"),
+            API_VERSION
+        );
+    }
+    if version < MIN_API_VERSION {
+        anyhow::bail!(
+            "targets extension API v{}, which devkit {} no longer supports (v{}-v{}); update the extension",
+            version,
+            env!("CARGO_PKG_VERSION"),
+            MIN_API_VERSION,
+            API_VERSION
+        );
+    }
+    if version < API_VERSION {
+        return Ok(Some(format!(
+            "targets extension API v{} (current is v{}); consider updating it",
+            version, API_VERSION
+        )));
+    }
+    Ok(None)
+}
+
+/// JSON context document for `discover` and `protocol = "json"` actions
+pub fn context_json(
+    ctx: &crate::AppContext,
+    extension: &str,
+    action: Option<&ActionConfig>,
+    extra_args: &[String],
+) -> serde_json::Value {
+    let package_json = |name: &str| {
+        ctx.config.packages.get(name).map(|pkg| {
+            let mut commands: Vec<&String> = pkg.cmd.keys().collect();
+            commands.sort();
+            serde_json::json!({
+                "name": name,
+                "path": pkg.path,
+                "dir_name": pkg.dir_name,
+                "commands": commands,
+            })
+        })
+    };
+
+    let mut names: Vec<&String> = ctx.config.packages.keys().collect();
+    names.sort();
+    let packages: Vec<serde_json::Value> =
+        names.into_iter().filter_map(|name| package_json(name)).collect();
+
+    // Raw .dev/config.toml, so extensions can read their own sections
+    let config = std::fs::read_to_string(ctx.repo.join(".dev/config.toml"))
+        .ok()
+        .and_then(|contents| toml::from_str::<toml::Value>(&contents).ok())
+        .and_then(|value| serde_json::to_value(value).ok())
+        .unwrap_or(serde_json::Value::Null);
+
+    let features = &ctx.features;
+    serde_json::json!({
+        "protocol": PROTOCOL_VERSION,
+        "action": action.map(|a| a.id.as_str()),
+        "args": extra_args,
+        "repo_root": ctx.repo,
+        "quiet": ctx.quiet,
+        "features": {
+            "docker": features.docker,
+            "database": features.database,
+            "git": features.git,
+            "cargo": features.cargo,
+            "node": features.node,
+        },
+        "config": config,
+        "packages": packages,
+        "package": action
+            .and_then(|a| a.package.as_deref())
+            .and_then(package_json),
+        "env": action.and_then(|a| a.env.as_ref()),
+        "ext_config": ext_config_json(ctx, extension),
+    })
+}
+
+/// An extension's `[ext.<name>]` table as JSON (an empty object when absent)
+pub fn ext_config_json(ctx: &crate::AppContext, extension: &str) -> serde_json::Value {
+    ctx.config
+        .global
+        .ext
+        .get(extension)
+        .and_then(|value| serde_json::to_value(value).ok())
+        .unwrap_or_else(|| serde_json::json!({}))
+}
+
+fn write_context(child: &mut std::process::Child, context: &serde_json::Value) -> Result<()> {
+    if let Some(mut stdin) = child.stdin.take() {
+        // The command may exit without reading stdin; that's not an error
+        match stdin.write_all(context.to_string().as_bytes()) {
+            Err(e) if e.kind() != std::io::ErrorKind::BrokenPipe => return Err(e.into()),
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
 impl crate::Extension for ExternalExtension {
     fn name(&self) -> &str {
         &self.config.name
@@ -117,29 +449,22 @@ impl crate::Extension for ExternalExtension {
         true
     }
 
-    fn menu_items(&self, _ctx: &crate::AppContext) -> Vec<crate::MenuItem> {
-        self.config
-            .action
-            .iter()
+    fn menu_items(&self, ctx: &crate::AppContext) -> Vec<crate::MenuItem> {
+        self.actions(ctx)
+            .into_iter()
             .map(|action| {
-                let action_clone = action.clone();
-                let ext_dir = self.extension_dir.clone();
-                let config = ExtensionConfig {
-                    name: self.config.name.clone(),
-                    version: self.config.version.clone(),
-                    description: self.config.description.clone(),
-                    action: vec![action_clone.clone()],
+                let label = action.label.clone();
+                let group = action.group.clone();
+                let ext = ExternalExtension {
+                    config: self.config.clone(),
+                    extension_dir: self.extension_dir.clone(),
                 };
 
                 crate::MenuItem {
-                    label: action.label.clone(),
-                    group: action.group.clone(),
+                    label,
+                    group,
                     handler: Box::new(move |ctx| {
-                        let ext = ExternalExtension {
-                            config: config.clone(),
-                            extension_dir: ext_dir.clone(),
-                        };
-                        ext.execute_action(ctx, &action_clone).map_err(|e| e.into())
+                        ext.execute_action(ctx, &action, &[]).map_err(|e| e.into())
                     }),
                 }
             })
@@ -154,7 +479,64 @@ impl Clone for ExtensionConfig {
             name: self.name.clone(),
             version: self.version.clone(),
             description: self.description.clone(),
+            devkit_api_version: self.devkit_api_version,
+            discover: self.discover.clone(),
             action: self.action.clone(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_dynamic_config() {
+        let config: ExtensionConfig = toml::from_str(
+            r#"
+            name = "dyn"
+            discover = "discover.sh"
+
+            [[action]]
+            id = "ctx"
+            label = "Context"
+            command = "ctx.sh"
+            protocol = "json"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.discover.as_deref(), Some("discover.sh"));
+        assert_eq!(config.action[0].protocol, ActionProtocol::Json);
+
+        let config: ExtensionConfig = toml::from_str("name = \"empty\"").unwrap();
+        assert!(config.action.is_empty());
+    }
+
+    #[test]
+    fn test_check_api_version() {
+        let config = |version: &str| -> ExtensionConfig {
+            toml::from_str(&format!("name = \"x\"\n{}", version)).unwrap()
+        };
+
+        let current = format!("devkit_api_version = {}", API_VERSION);
+        assert!(check_api_version(&config(&current)).unwrap().is_none());
+        assert!(check_api_version(&config("")).unwrap().is_some());
+
+        let newer = format!("devkit_api_version = {}", API_VERSION + 1);
+        let err = check_api_version(&config(&newer)).unwrap_err();
+        assert!(err.to_string().contains("devkit update"));
+    }
+
+    #[test]
+    fn test_parse_discover_output() {
+        let action = r#"{"id": "lint-api", "label": "Lint api", "command": "lint.sh", "package": "api"}"#;
+        for json in [format!("{{\"actions\": [{}]}}", action), format!("[{}]", action)] {
+            let actions = serde_json::from_str::<DiscoverOutput>(&json)
+                .unwrap()
+                .into_actions();
+            assert_eq!(actions.len(), 1);
+            assert_eq!(actions[0].package.as_deref(), Some("api"));
+            assert_eq!(actions[0].protocol, ActionProtocol::Env);
+        }
+    }
+}
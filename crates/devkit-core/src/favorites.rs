@@ -0,0 +1,95 @@
+//! Pinned menu items
+//!
+//! Favorites are stored per project in `.dev/favorites.json` as a list of menu item
+//! ids (see [`palette`](crate::palette)), in the order they were pinned.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const FAVORITES_FILE: &str = ".dev/favorites.json";
+
+#[derive(Debug, Serialize, Deserialize, Default, Clone, PartialEq)]
+pub struct Favorites {
+    pub pinned: Vec<String>,
+}
+
+impl Favorites {
+    pub fn load(repo: &Path) -> Result<Self> {
+        let path = favorites_path(repo);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let contents = fs::read_to_string(&path)?;
+        serde_json::from_str(&contents)
+            .with_context(|| format!("Invalid favorites file: {}", path.display()))
+    }
+
+    pub fn save(&self, repo: &Path) -> Result<()> {
+        let path = favorites_path(repo);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.pinned.iter().any(|pinned| pinned == id)
+    }
+
+    /// Pin an item; false if it was already pinned
+    pub fn pin(&mut self, id: &str) -> bool {
+        if self.contains(id) {
+            return false;
+        }
+        self.pinned.push(id.to_string());
+        true
+    }
+
+    /// Unpin an item; false if it wasn't pinned
+    pub fn unpin(&mut self, id: &str) -> bool {
+        let before = self.pinned.len();
+        self.pinned.retain(|pinned| pinned != id);
+        self.pinned.len() != before
+    }
+}
+
+fn favorites_path(repo: &Path) -> PathBuf {
+    repo.join(FAVORITES_FILE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pin_unpin() {
+        let mut favorites = Favorites::default();
+        assert!(favorites.pin("docker/start"));
+        assert!(!favorites.pin("docker/start"));
+        assert!(favorites.pin("cmd/build"));
+        assert_eq!(favorites.pinned, ["docker/start", "cmd/build"]);
+
+        assert!(favorites.unpin("docker/start"));
+        assert!(!favorites.unpin("docker/start"));
+        assert_eq!(favorites.pinned, ["cmd/build"]);
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let repo = std::env::temp_dir().join(format!("devkit-favorites-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&repo);
+
+        assert_eq!(Favorites::load(&repo).unwrap(), Favorites::default());
+
+        let mut favorites = Favorites::default();
+        favorites.pin("cmd/test");
+        favorites.save(&repo).unwrap();
+        assert_eq!(Favorites::load(&repo).unwrap(), favorites);
+
+        fs::remove_dir_all(&repo).ok();
+    }
+}
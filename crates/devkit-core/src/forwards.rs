@@ -0,0 +1,224 @@
+//! Background port forwards
+//!
+//! `--background` forwards from `devkit k8s forward`, `devkit remote forward` and
+//! `devkit docker forward` run as detached processes recorded in
+//! `.dev/run/forwards.json`, each logging to `.dev/run/forward-<port>.log`. Entries
+//! whose process has exited are pruned on every load; `devkit forward` lists and stops
+//! them.
+
+use crate::oplog::LoggedCommand;
+use crate::process::{current_timestamp, process_alive, terminate, RUN_DIR};
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::Duration;
+
+const STATE_FILE: &str = "forwards.json";
+
+/// How long a new forward must stay up to count as started
+const STARTUP_GRACE: Duration = Duration::from_millis(1500);
+
+/// A forward running in the background
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Forward {
+    /// Local ports the process listens on
+    pub ports: Vec<u16>,
+    /// Extension that started it: k8s, remote or docker
+    pub source: String,
+    /// Where the ports lead, e.g. `api-7d9f4:80`
+    pub target: String,
+    pub pid: u32,
+    pub log_file: PathBuf,
+    pub started_at: u64,
+}
+
+impl Forward {
+    /// "8080, 8081", for listings
+    pub fn ports_label(&self) -> String {
+        self.ports
+            .iter()
+            .map(u16::to_string)
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// "localhost:8080 -> api-7d9f4:80 (k8s)", for pickers and messages
+    pub fn label(&self) -> String {
+        format!(
+            "localhost:{} -> {} ({})",
+            self.ports_label(),
+            self.target,
+            self.source
+        )
+    }
+}
+
+/// Forwards running for this checkout, oldest first
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct Forwards {
+    pub forwards: Vec<Forward>,
+}
+
+impl Forwards {
+    /// Load state, dropping entries whose process is no longer alive
+    pub fn load(repo: &Path) -> Result<Self> {
+        let path = state_path(repo);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let contents = fs::read_to_string(&path)?;
+        let mut state: Forwards = serde_json::from_str(&contents)
+            .with_context(|| format!("Invalid forwards state file: {}", path.display()))?;
+
+        let before = state.forwards.len();
+        state.forwards.retain(|forward| process_alive(forward.pid));
+        if state.forwards.len() != before {
+            state.save(repo)?;
+        }
+        Ok(state)
+    }
+
+    pub fn save(&self, repo: &Path) -> Result<()> {
+        fs::create_dir_all(repo.join(RUN_DIR))?;
+        fs::write(state_path(repo), serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// The forward listening on `port`
+    pub fn find(&self, port: u16) -> Option<&Forward> {
+        self.forwards
+            .iter()
+            .find(|forward| forward.ports.contains(&port))
+    }
+}
+
+fn state_path(repo: &Path) -> PathBuf {
+    repo.join(RUN_DIR).join(STATE_FILE)
+}
+
+/// Run `cmd` in the background as a forward of `ports` to `target`, and record it
+pub fn start(
+    repo: &Path,
+    source: &str,
+    target: &str,
+    ports: &[u16],
+    mut cmd: Command,
+) -> Result<Forward> {
+    let mut state = Forwards::load(repo)?;
+    if let Some(port) = ports.iter().find(|port| state.find(**port).is_some()) {
+        bail!(
+            "Port {} is already forwarded (stop it with `devkit forward stop {}`)",
+            port,
+            port
+        );
+    }
+
+    fs::create_dir_all(repo.join(RUN_DIR))?;
+    let log_file = repo.join(RUN_DIR).join(format!(
+        "forward-{}.log",
+        ports.first().copied().unwrap_or_default()
+    ));
+    let log = File::create(&log_file)
+        .with_context(|| format!("Failed to create {}", log_file.display()))?;
+    // Its own process group, like other background processes, so stopping it stops
+    // whatever it started
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut cmd, 0);
+    cmd.stdin(Stdio::null())
+        .stdout(log.try_clone()?)
+        .stderr(log);
+
+    let mut child = cmd.logged_spawn().context("Failed to start port forward")?;
+    thread::sleep(STARTUP_GRACE);
+    if let Some(status) = child.try_wait()? {
+        let output = fs::read_to_string(&log_file).unwrap_or_default();
+        bail!(
+            "Port forward exited during startup ({}){}",
+            status,
+            output
+                .lines()
+                .last()
+                .map(|line| format!(": {}", line))
+                .unwrap_or_default()
+        );
+    }
+
+    let forward = Forward {
+        ports: ports.to_vec(),
+        source: source.to_string(),
+        target: target.to_string(),
+        pid: child.id(),
+        log_file,
+        started_at: current_timestamp(),
+    };
+    state.forwards.push(forward.clone());
+    state.save(repo)?;
+    Ok(forward)
+}
+
+/// Stop the forward listening on `port`
+pub fn stop(repo: &Path, port: u16) -> Result<Forward> {
+    let mut state = Forwards::load(repo)?;
+    let Some(index) = state
+        .forwards
+        .iter()
+        .position(|forward| forward.ports.contains(&port))
+    else {
+        bail!("No background forward on port {}", port);
+    };
+
+    let forward = state.forwards.remove(index);
+    terminate(forward.pid)?;
+    state.save(repo)?;
+    Ok(forward)
+}
+
+/// Stop every forward, returning the ones stopped
+pub fn stop_all(repo: &Path) -> Result<Vec<Forward>> {
+    let state = Forwards::load(repo)?;
+    for forward in &state.forwards {
+        terminate(forward.pid)?;
+    }
+    Forwards::default().save(repo)?;
+    Ok(state.forwards)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_prunes_exited_forwards() {
+        let repo = std::env::temp_dir().join(format!("devkit-forwards-{}", std::process::id()));
+        let forward = |pid, port| Forward {
+            ports: vec![port],
+            source: "k8s".to_string(),
+            target: "api:80".to_string(),
+            pid,
+            log_file: PathBuf::from("forward.log"),
+            started_at: 0,
+        };
+        Forwards {
+            forwards: vec![
+                forward(std::process::id(), 8080),
+                forward(999_999_999, 8081),
+            ],
+        }
+        .save(&repo)
+        .unwrap();
+
+        let state = Forwards::load(&repo).unwrap();
+        assert_eq!(state.forwards.len(), 1);
+        assert_eq!(
+            state.find(8080).map(Forward::label).as_deref(),
+            Some("localhost:8080 -> api:80 (k8s)")
+        );
+        assert!(state.find(8081).is_none());
+
+        fs::remove_dir_all(&repo).ok();
+    }
+}
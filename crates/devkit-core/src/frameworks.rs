@@ -0,0 +1,487 @@
+//! Framework detection for `devkit init`
+//!
+//! Recognises common web stacks from their manifests and generates a dev.toml command
+//! set for each (dev/build/test/lint with the variants people reach for), plus the
+//! backing services they use for a starter docker-compose.yml.
+
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Framework {
+    NextJs,
+    Vite,
+    Django,
+    Rails,
+    Spring,
+    Phoenix,
+}
+
+/// Backing service a stack depends on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Service {
+    Postgres,
+    Mysql,
+    Redis,
+}
+
+impl Framework {
+    pub fn name(self) -> &'static str {
+        match self {
+            Framework::NextJs => "Next.js",
+            Framework::Vite => "Vite",
+            Framework::Django => "Django",
+            Framework::Rails => "Rails",
+            Framework::Spring => "Spring Boot",
+            Framework::Phoenix => "Phoenix",
+        }
+    }
+
+    /// Framework of the package in `dir`, if it's one we have a template for
+    pub fn detect(dir: &Path) -> Option<Framework> {
+        let node_deps = node_dependencies(dir);
+        if node_deps.iter().any(|dep| dep == "next") {
+            return Some(Framework::NextJs);
+        }
+        if node_deps.iter().any(|dep| dep == "vite") {
+            return Some(Framework::Vite);
+        }
+
+        let python = read(dir, &["requirements.txt", "pyproject.toml", "Pipfile"]);
+        if dir.join("manage.py").exists() && python.to_lowercase().contains("django") {
+            return Some(Framework::Django);
+        }
+        if read(dir, &["Gemfile"]).contains("\"rails\"") || dir.join("bin/rails").exists() {
+            return Some(Framework::Rails);
+        }
+        if read(dir, &["pom.xml", "build.gradle", "build.gradle.kts"]).contains("spring-boot") {
+            return Some(Framework::Spring);
+        }
+        if read(dir, &["mix.exs"]).contains(":phoenix") {
+            return Some(Framework::Phoenix);
+        }
+        None
+    }
+
+    /// `[cmd]` sections for a dev.toml
+    pub fn commands(self, dir: &Path) -> String {
+        match self {
+            Framework::NextJs => {
+                let x = node_runner(dir);
+                format!(
+                    r#"[cmd.dev]
+default = "{x} next dev"
+turbo = "{x} next dev --turbo"
+
+[cmd.build]
+default = "{x} next build"
+start = "{x} next start"
+
+[cmd.lint]
+default = "{x} next lint"
+fix = "{x} next lint --fix"
+
+[cmd.typecheck]
+default = "{x} tsc --noEmit"
+
+{test}"#,
+                    x = x,
+                    test = node_test(dir, &x),
+                )
+            }
+            Framework::Vite => {
+                let x = node_runner(dir);
+                let mut commands = format!(
+                    r#"[cmd.dev]
+default = "{x} vite"
+host = "{x} vite --host"
+
+[cmd.build]
+default = "{x} vite build"
+watch = "{x} vite build --watch"
+preview = "{x} vite preview"
+
+"#,
+                    x = x
+                );
+                if node_dependencies(dir).iter().any(|dep| dep == "eslint") {
+                    commands.push_str(&format!(
+                        r#"[cmd.lint]
+default = "{x} eslint ."
+fix = "{x} eslint . --fix"
+
+"#,
+                        x = x
+                    ));
+                }
+                commands.push_str(&node_test(dir, &x));
+                commands
+            }
+            Framework::Django => {
+                let py = if dir.join("uv.lock").exists() {
+                    "uv run python"
+                } else if dir.join("poetry.lock").exists() {
+                    "poetry run python"
+                } else {
+                    "python"
+                };
+                format!(
+                    r#"[cmd.dev]
+default = "{py} manage.py runserver"
+lan = "{py} manage.py runserver 0.0.0.0:8000"
+
+[cmd.migrate]
+default = "{py} manage.py migrate"
+make = "{py} manage.py makemigrations"
+
+[cmd.test]
+default = "{py} manage.py test"
+parallel = "{py} manage.py test --parallel"
+
+[cmd.lint]
+default = "ruff check ."
+fix = "ruff check . --fix"
+
+[cmd.fmt]
+default = "ruff format --check ."
+fix = "ruff format ."
+"#,
+                    py = py
+                )
+            }
+            Framework::Rails => {
+                let dev = if dir.join("Procfile.dev").exists() {
+                    "bin/dev"
+                } else {
+                    "bin/rails server"
+                };
+                let test = if dir.join("spec").is_dir() {
+                    "[cmd]\ntest = \"bundle exec rspec\"\n"
+                } else {
+                    "[cmd.test]\ndefault = \"bin/rails test\"\nsystem = \"bin/rails test:system\"\n"
+                };
+                format!(
+                    r#"[cmd.dev]
+default = "{dev}"
+console = "bin/rails console"
+
+[cmd.migrate]
+default = "bin/rails db:migrate"
+rollback = "bin/rails db:rollback"
+
+[cmd.seed]
+default = "bin/rails db:seed"
+
+[cmd.lint]
+default = "bundle exec rubocop"
+fix = "bundle exec rubocop -a"
+
+{test}"#,
+                    dev = dev,
+                    test = test
+                )
+            }
+            Framework::Spring if dir.join("pom.xml").exists() => {
+                let mvn = if dir.join("mvnw").exists() {
+                    "./mvnw"
+                } else {
+                    "mvn"
+                };
+                format!(
+                    r#"[cmd.dev]
+default = "{mvn} spring-boot:run"
+
+[cmd.build]
+default = "{mvn} package -DskipTests"
+clean = "{mvn} clean package -DskipTests"
+
+[cmd.lint]
+default = "{mvn} verify -DskipTests"
+
+[cmd]
+test = "{mvn} test"
+"#,
+                    mvn = mvn
+                )
+            }
+            Framework::Spring => {
+                let gradle = if dir.join("gradlew").exists() {
+                    "./gradlew"
+                } else {
+                    "gradle"
+                };
+                format!(
+                    r#"[cmd.dev]
+default = "{gradle} bootRun"
+
+[cmd.build]
+default = "{gradle} build -x test"
+watch = "{gradle} build -x test --continuous"
+
+[cmd.lint]
+default = "{gradle} check -x test"
+
+[cmd]
+test = "{gradle} test"
+"#,
+                    gradle = gradle
+                )
+            }
+            Framework::Phoenix => r#"[cmd.dev]
+default = "mix phx.server"
+iex = "iex -S mix phx.server"
+
+[cmd.build]
+default = "mix compile"
+release = "MIX_ENV=prod mix release"
+
+[cmd.migrate]
+default = "mix ecto.migrate"
+rollback = "mix ecto.rollback"
+
+[cmd.fmt]
+default = "mix format --check-formatted"
+fix = "mix format"
+
+[cmd.lint]
+default = "mix compile --warnings-as-errors --force"
+
+[cmd.test]
+default = "mix test"
+stale = "mix test --stale"
+"#
+            .to_string(),
+        }
+    }
+
+    /// Databases and caches the package's dependencies point at
+    pub fn services(self, dir: &Path) -> Vec<Service> {
+        let deps = match self {
+            Framework::NextJs | Framework::Vite => node_dependencies(dir).join("\n"),
+            Framework::Django => read(dir, &["requirements.txt", "pyproject.toml", "Pipfile"]),
+            Framework::Rails => read(dir, &["Gemfile"]),
+            Framework::Spring => read(dir, &["pom.xml", "build.gradle", "build.gradle.kts"]),
+            Framework::Phoenix => read(dir, &["mix.exs"]),
+        }
+        .to_lowercase();
+
+        let mut services = Vec::new();
+        // Node dependencies are one name per line, so `pg` is matched exactly there
+        if ["psycopg", "\"pg\"", "postgrex", "postgresql"]
+            .iter()
+            .any(|marker| deps.contains(marker))
+            || deps.lines().any(|dep| dep == "pg")
+        {
+            services.push(Service::Postgres);
+        }
+        if ["mysqlclient", "mysql2", "mysql-connector", "myxql"]
+            .iter()
+            .any(|marker| deps.contains(marker))
+            || deps.lines().any(|dep| dep == "mysql" || dep == "mysql2")
+        {
+            services.push(Service::Mysql);
+        }
+        if deps.contains("redis") {
+            services.push(Service::Redis);
+        }
+        services
+    }
+}
+
+/// docker-compose.yml with the given services
+pub fn compose_file(project_name: &str, services: &[Service]) -> String {
+    let mut compose = String::from("services:\n");
+    let mut volumes = Vec::new();
+
+    for service in services {
+        match service {
+            Service::Postgres => {
+                compose.push_str(&format!(
+                    r#"  postgres:
+    image: postgres:16
+    environment:
+      POSTGRES_USER: postgres
+      POSTGRES_PASSWORD: postgres
+      POSTGRES_DB: {}_dev
+    ports:
+      - "5432:5432"
+    volumes:
+      - postgres-data:/var/lib/postgresql/data
+"#,
+                    project_name
+                ));
+                volumes.push("postgres-data");
+            }
+            Service::Mysql => {
+                compose.push_str(&format!(
+                    r#"  mysql:
+    image: mysql:8
+    environment:
+      MYSQL_ROOT_PASSWORD: mysql
+      MYSQL_DATABASE: {}_dev
+    ports:
+      - "3306:3306"
+    volumes:
+      - mysql-data:/var/lib/mysql
+"#,
+                    project_name
+                ));
+                volumes.push("mysql-data");
+            }
+            Service::Redis => compose.push_str(
+                r#"  redis:
+    image: redis:7
+    ports:
+      - "6379:6379"
+"#,
+            ),
+        }
+    }
+
+    if !volumes.is_empty() {
+        compose.push_str("\nvolumes:\n");
+        for volume in volumes {
+            compose.push_str(&format!("  {}:\n", volume));
+        }
+    }
+    compose
+}
+
+/// Names of a package.json's dependencies and devDependencies
+fn node_dependencies(dir: &Path) -> Vec<String> {
+    let Some(package_json) = fs::read_to_string(dir.join("package.json"))
+        .ok()
+        .and_then(|contents| serde_json::from_str::<serde_json::Value>(&contents).ok())
+    else {
+        return Vec::new();
+    };
+
+    ["dependencies", "devDependencies"]
+        .iter()
+        .filter_map(|key| package_json[key].as_object())
+        .flat_map(|deps| deps.keys().cloned())
+        .collect()
+}
+
+/// Package manager for a Node package, by lockfile
+pub(crate) fn node_package_manager(dir: &Path) -> &'static str {
+    if dir.join("yarn.lock").exists() {
+        "yarn"
+    } else if dir.join("pnpm-lock.yaml").exists() {
+        "pnpm"
+    } else {
+        "npm"
+    }
+}
+
+/// Command prefix for running a package's binaries (`npx`, `pnpm exec` or `yarn`)
+pub fn node_runner(dir: &Path) -> String {
+    match node_package_manager(dir) {
+        "yarn" => "yarn".to_string(),
+        "pnpm" => "pnpm exec".to_string(),
+        _ => "npx".to_string(),
+    }
+}
+
+fn node_test(dir: &Path, runner: &str) -> String {
+    let deps = node_dependencies(dir);
+    if deps.iter().any(|dep| dep == "vitest") {
+        format!(
+            "[cmd.test]\ndefault = \"{x} vitest run\"\nwatch = \"{x} vitest\"\n",
+            x = runner
+        )
+    } else if deps.iter().any(|dep| dep == "jest") {
+        format!(
+            "[cmd.test]\ndefault = \"{x} jest\"\nwatch = \"{x} jest --watch\"\n",
+            x = runner
+        )
+    } else {
+        String::new()
+    }
+}
+
+/// Concatenated contents of whichever of `files` exist
+fn read(dir: &Path, files: &[&str]) -> String {
+    files
+        .iter()
+        .filter_map(|file| fs::read_to_string(dir.join(file)).ok())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn package(name: &str, files: &[(&str, &str)]) -> std::path::PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("devkit-frameworks-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        for (file, contents) in files {
+            let path = dir.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn test_detect_frameworks() {
+        let next = package(
+            "next",
+            &[
+                (
+                    "package.json",
+                    r#"{"dependencies": {"next": "14", "pg": "8"}}"#,
+                ),
+                ("pnpm-lock.yaml", ""),
+            ],
+        );
+        assert_eq!(Framework::detect(&next), Some(Framework::NextJs));
+        assert!(Framework::NextJs
+            .commands(&next)
+            .contains("default = \"pnpm exec next dev\""));
+        assert_eq!(Framework::NextJs.services(&next), [Service::Postgres]);
+
+        let django = package(
+            "django",
+            &[
+                ("manage.py", ""),
+                ("requirements.txt", "Django==5.0\npsycopg[binary]\nredis\n"),
+            ],
+        );
+        assert_eq!(Framework::detect(&django), Some(Framework::Django));
+        assert_eq!(
+            Framework::Django.services(&django),
+            [Service::Postgres, Service::Redis]
+        );
+
+        let phoenix = package("phoenix", &[("mix.exs", "{:phoenix, \"~> 1.7\"}")]);
+        assert_eq!(Framework::detect(&phoenix), Some(Framework::Phoenix));
+
+        let plain = package("plain", &[("package.json", r#"{"dependencies": {}}"#)]);
+        assert_eq!(Framework::detect(&plain), None);
+
+        for dir in [next, django, phoenix, plain] {
+            fs::remove_dir_all(dir).ok();
+        }
+    }
+
+    #[test]
+    fn test_commands_parse_as_dev_toml() {
+        let dir = package("spring", &[("pom.xml", "spring-boot"), ("mvnw", "")]);
+        for framework in [
+            Framework::NextJs,
+            Framework::Vite,
+            Framework::Django,
+            Framework::Rails,
+            Framework::Spring,
+            Framework::Phoenix,
+        ] {
+            let config: crate::config::PackageToml =
+                toml::from_str(&framework.commands(&dir)).unwrap();
+            assert!(config.cmd.contains_key("dev"), "{}", framework.name());
+        }
+        fs::remove_dir_all(dir).ok();
+    }
+}
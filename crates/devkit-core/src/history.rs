@@ -1,29 +1,116 @@
 //! Command history tracking
+//!
+//! devkit invocations (CLI and menu) and each package command the task runner starts
+//! are recorded in `~/.cache/devkit/history.json`. When it holds `MAX_HISTORY_SIZE`
+//! entries it's moved to `history.1.json` (replacing the previous one) and a new file
+//! is started; both are read back, so between one and two thousand runs are kept.
 
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::process::current_timestamp;
 
 const HISTORY_FILE: &str = "history.json";
-const MAX_HISTORY_SIZE: usize = 100;
+const ROTATED_FILE: &str = "history.1.json";
+const MAX_HISTORY_SIZE: usize = 1000;
+
+/// Serializes read-modify-write of the history file between threads (parallel
+/// commands finish at the same time)
+static HISTORY_LOCK: Mutex<()> = Mutex::new(());
+
+/// Where a command was started from
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum HistorySource {
+    Cli,
+    Menu,
+    /// A package command started by the task runner
+    Task,
+}
+
+impl std::fmt::Display for HistorySource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HistorySource::Cli => write!(f, "cli"),
+            HistorySource::Menu => write!(f, "menu"),
+            HistorySource::Task => write!(f, "task"),
+        }
+    }
+}
 
+// The optional fields are missing from entries recorded by older versions
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct HistoryEntry {
     pub command: String,
     pub timestamp: u64,
     pub success: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+    /// Package the command was run from (by working directory)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub package: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<HistorySource>,
+}
+
+impl HistoryEntry {
+    /// Entry for a command that just finished
+    pub fn finished(
+        command: impl Into<String>,
+        source: HistorySource,
+        package: Option<String>,
+        duration: Duration,
+        exit_code: i32,
+    ) -> Self {
+        Self {
+            command: command.into(),
+            timestamp: current_timestamp(),
+            success: exit_code == 0,
+            duration_ms: Some(duration.as_millis() as u64),
+            package,
+            exit_code: Some(exit_code),
+            source: Some(source),
+        }
+    }
 }
 
-/// Load command history from cache
+/// Run counts and timings for one command
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommandStats {
+    pub command: String,
+    pub runs: usize,
+    pub failures: usize,
+    /// Mean over the runs that recorded a duration
+    pub avg_duration: Option<Duration>,
+}
+
+impl CommandStats {
+    /// Fraction of runs that failed (0.0 - 1.0)
+    pub fn failure_rate(&self) -> f64 {
+        self.failures as f64 / self.runs as f64
+    }
+}
+
+/// Load command history from cache, oldest first (including the rotated file)
 pub fn load_history() -> Result<Vec<HistoryEntry>> {
-    let path = history_path()?;
+    let mut history = read_entries(&history_path(ROTATED_FILE)?)?;
+    history.extend(read_entries(&history_path(HISTORY_FILE)?)?);
+    Ok(history)
+}
 
+fn read_entries(path: &Path) -> Result<Vec<HistoryEntry>> {
     if !path.exists() {
         return Ok(Vec::new());
     }
 
-    let contents = fs::read_to_string(&path)?;
+    let contents = fs::read_to_string(path)?;
     let history: Vec<HistoryEntry> = serde_json::from_str(&contents)?;
 
     Ok(history)
@@ -31,7 +118,7 @@ pub fn load_history() -> Result<Vec<HistoryEntry>> {
 
 /// Save command history to cache
 pub fn save_history(history: &[HistoryEntry]) -> Result<()> {
-    let path = history_path()?;
+    let path = history_path(HISTORY_FILE)?;
 
     // Keep only last MAX_HISTORY_SIZE entries
     let trimmed: Vec<_> = history
@@ -48,15 +135,18 @@ pub fn save_history(history: &[HistoryEntry]) -> Result<()> {
     Ok(())
 }
 
-/// Add a command to history
-pub fn add_to_history(command: String, success: bool) -> Result<()> {
-    let mut history = load_history()?;
+/// Add a command to history, rotating the file when it's full
+pub fn add_to_history(entry: HistoryEntry) -> Result<()> {
+    let _lock = HISTORY_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+    let path = history_path(HISTORY_FILE)?;
+    let mut history = read_entries(&path)?;
+    if history.len() >= MAX_HISTORY_SIZE {
+        fs::rename(&path, history_path(ROTATED_FILE)?)?;
+        history.clear();
+    }
 
-    history.push(HistoryEntry {
-        command,
-        timestamp: current_timestamp(),
-        success,
-    });
+    history.push(entry);
 
     save_history(&history)?;
 
@@ -79,19 +169,100 @@ pub fn search_history(pattern: &str) -> Result<Vec<HistoryEntry>> {
         .collect())
 }
 
-fn history_path() -> Result<PathBuf> {
+/// Per-command stats, most-run first
+pub fn command_stats(history: &[HistoryEntry]) -> Vec<CommandStats> {
+    let mut by_command: HashMap<&str, Vec<&HistoryEntry>> = HashMap::new();
+    for entry in history {
+        by_command.entry(&entry.command).or_default().push(entry);
+    }
+
+    let mut stats: Vec<CommandStats> = by_command
+        .into_iter()
+        .map(|(command, entries)| {
+            let durations: Vec<u64> = entries.iter().filter_map(|e| e.duration_ms).collect();
+            CommandStats {
+                command: command.to_string(),
+                runs: entries.len(),
+                failures: entries.iter().filter(|e| !e.success).count(),
+                avg_duration: (!durations.is_empty()).then(|| {
+                    Duration::from_millis(durations.iter().sum::<u64>() / durations.len() as u64)
+                }),
+            }
+        })
+        .collect();
+
+    stats.sort_by(|a, b| b.runs.cmp(&a.runs).then_with(|| a.command.cmp(&b.command)));
+    stats
+}
+
+/// Stats of commands with a recorded duration, slowest on average first
+pub fn slowest_commands(stats: &[CommandStats]) -> Vec<&CommandStats> {
+    let mut slowest: Vec<&CommandStats> = stats
+        .iter()
+        .filter(|stat| stat.avg_duration.is_some())
+        .collect();
+    slowest.sort_by_key(|stat| std::cmp::Reverse(stat.avg_duration));
+    slowest
+}
+
+fn history_path(file: &str) -> Result<PathBuf> {
     let cache_dir =
         dirs::cache_dir().ok_or_else(|| anyhow::anyhow!("Failed to get cache directory"))?;
 
     let devkit_cache = cache_dir.join("devkit");
     fs::create_dir_all(&devkit_cache)?;
 
-    Ok(devkit_cache.join(HISTORY_FILE))
+    Ok(devkit_cache.join(file))
 }
 
-fn current_timestamp() -> u64 {
-    std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .unwrap_or_default()
-        .as_secs()
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(command: &str, success: bool, duration_ms: Option<u64>) -> HistoryEntry {
+        HistoryEntry {
+            command: command.to_string(),
+            timestamp: 0,
+            success,
+            duration_ms,
+            package: None,
+            exit_code: None,
+            source: None,
+        }
+    }
+
+    #[test]
+    fn test_command_stats() {
+        let history = vec![
+            entry("cmd build", true, Some(1000)),
+            entry("test", false, Some(500)),
+            entry("cmd build", false, Some(3000)),
+            entry("cmd build", true, None),
+            entry("deps", true, None),
+        ];
+
+        let stats = command_stats(&history);
+        let commands: Vec<&str> = stats.iter().map(|s| s.command.as_str()).collect();
+        assert_eq!(commands, ["cmd build", "deps", "test"]);
+
+        assert_eq!(stats[0].runs, 3);
+        assert_eq!(stats[0].failures, 1);
+        assert_eq!(stats[0].avg_duration, Some(Duration::from_millis(2000)));
+        assert_eq!(stats[1].avg_duration, None);
+        assert_eq!(stats[2].failure_rate(), 1.0);
+
+        let slowest: Vec<&str> = slowest_commands(&stats)
+            .iter()
+            .map(|s| s.command.as_str())
+            .collect();
+        assert_eq!(slowest, ["cmd build", "test"]);
+    }
+
+    #[test]
+    fn test_old_entries_deserialize() {
+        let json = r#"[{"command": "cmd build", "timestamp": 1, "success": true}]"#;
+        let history: Vec<HistoryEntry> = serde_json::from_str(json).unwrap();
+        assert_eq!(history[0].duration_ms, None);
+        assert_eq!(history[0].source, None);
+    }
 }
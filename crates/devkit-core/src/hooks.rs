@@ -0,0 +1,135 @@
+//! Project hooks
+//!
+//! `[hooks]` in `.dev/config.toml` runs shell commands around devkit operations:
+//!
+//! ```toml
+//! [hooks]
+//! post_install = "npm run codegen"
+//! pre_docker_up = ["./scripts/check-env.sh", "docker network create dev || true"]
+//! post_release = { run = "./scripts/notify-slack.sh", on_failure = "warn" }
+//! ```
+//!
+//! Keys are `pre_<operation>` / `post_<operation>` for the operations in [`OPERATIONS`].
+//! Post hooks only run when the operation succeeded. Hooks run through the shell in
+//! the repo root (or `cwd`) with `DEVKIT_HOOK`, `DEVKIT_REPO_ROOT`, `DEVKIT_ENV` and any
+//! operation-specific variables (`DEVKIT_CMD`, `DEVKIT_VERSION`) set.
+//!
+//! A failing hook aborts the operation unless its `on_failure` is `warn` or `ignore`.
+
+use crate::config::HookFailure;
+use crate::context::AppContext;
+use crate::error::{DevkitError, Result};
+use crate::oplog::LoggedCommand;
+use std::process::Command;
+
+/// Operations that run hooks
+pub const OPERATIONS: &[&str] = &[
+    "cmd",
+    "test",
+    "install",
+    "docker_up",
+    "docker_down",
+    "release",
+];
+
+/// Whether `event` is a known `pre_`/`post_` hook name
+pub fn is_known_event(event: &str) -> bool {
+    event
+        .strip_prefix("pre_")
+        .or_else(|| event.strip_prefix("post_"))
+        .is_some_and(|op| OPERATIONS.contains(&op))
+}
+
+/// Run the hooks registered for `event` (e.g. `pre_docker_up`)
+///
+/// `env` adds operation-specific variables.
+pub fn run_hooks(ctx: &AppContext, event: &str, env: &[(&str, &str)]) -> Result<()> {
+    for hook in ctx.config.global.hooks.get(event) {
+        let command = hook.run();
+        let cwd = match hook.cwd() {
+            Some(dir) => ctx.repo.join(dir),
+            None => ctx.repo.clone(),
+        };
+
+        if !ctx.quiet {
+            println!("[{}] {}", event, command);
+        }
+
+        let mut cmd = shell_command(command);
+        cmd.current_dir(&cwd)
+            .env("DEVKIT_HOOK", event)
+            .env("DEVKIT_REPO_ROOT", &ctx.repo)
+            .env("DEVKIT_ENV", ctx.config.global.environments.current())
+            .envs(env.iter().copied());
+
+        let failure = match cmd.logged_status() {
+            Ok(status) if status.success() => continue,
+            Ok(status) => match status.code() {
+                Some(code) => format!("exited with code {}", code),
+                None => "was terminated".to_string(),
+            },
+            Err(e) => format!("failed to start: {}", e),
+        };
+
+        match hook.on_failure() {
+            HookFailure::Abort => {
+                return Err(DevkitError::CommandFailed {
+                    command: format!("{} hook: {}", event, command),
+                    output: format!("Hook {}", failure),
+                });
+            }
+            HookFailure::Warn => {
+                ctx.print_warning(&format!("{} hook {}: {}", event, failure, command));
+            }
+            HookFailure::Ignore => {}
+        }
+    }
+    Ok(())
+}
+
+/// Run `f` between the `pre_<operation>` and `post_<operation>` hooks
+///
+/// The post hooks are skipped when `f` fails.
+pub fn with_hooks<T, E>(
+    ctx: &AppContext,
+    operation: &str,
+    env: &[(&str, &str)],
+    f: impl FnOnce() -> std::result::Result<T, E>,
+) -> std::result::Result<T, E>
+where
+    E: From<DevkitError>,
+{
+    run_hooks(ctx, &format!("pre_{}", operation), env)?;
+    let value = f()?;
+    run_hooks(ctx, &format!("post_{}", operation), env)?;
+    Ok(value)
+}
+
+/// A command running `command` through the platform shell
+#[cfg(windows)]
+pub fn shell_command(command: &str) -> Command {
+    let mut cmd = Command::new("cmd");
+    cmd.args(["/C", command]);
+    cmd
+}
+
+/// A command running `command` through the platform shell
+#[cfg(not(windows))]
+pub fn shell_command(command: &str) -> Command {
+    let mut cmd = Command::new("sh");
+    cmd.args(["-c", command]);
+    cmd
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_known_events() {
+        assert!(is_known_event("pre_cmd"));
+        assert!(is_known_event("post_docker_up"));
+        assert!(!is_known_event("post_deploy"));
+        assert!(!is_known_event("install"));
+    }
+}
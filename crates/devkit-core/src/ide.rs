@@ -0,0 +1,291 @@
+//! IDE task export for `devkit export`
+//!
+//! Turns package `[cmd]` entries into editor run configurations so the run buttons
+//! match dev.toml. Every command gets a task per package (and per variant), plus one
+//! that runs it across packages through `devkit cmd`.
+//!
+//! - VS Code: `.vscode/tasks.json`. Tasks devkit generated are replaced on each
+//!   export; tasks added by hand are kept.
+//! - JetBrains: one `.run/devkit_<name>.run.xml` shell configuration per task.
+//!
+//! launch.json isn't generated: debug configurations depend on each language's
+//! debugger rather than on the command line.
+
+use crate::Config;
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// `detail` of generated VS Code tasks, used to find them on the next export
+const VSCODE_MARKER: &str = "Generated by devkit export";
+
+/// Prefix of generated JetBrains run configuration files
+const JETBRAINS_PREFIX: &str = "devkit_";
+
+/// One run configuration
+#[derive(Debug, PartialEq)]
+pub struct IdeTask {
+    pub label: String,
+    /// Shell command line
+    pub command: String,
+    /// Working directory relative to the repo root (empty for the root)
+    pub cwd: String,
+    /// Editor group (`build` or `test`), if the command is one
+    pub group: Option<&'static str>,
+}
+
+/// Tasks for every package command, sorted by label
+pub fn tasks(config: &Config) -> Vec<IdeTask> {
+    let services = &config.global.services;
+    let mut tasks = Vec::new();
+    let mut aggregated = Vec::new();
+
+    for (pkg_name, package) in &config.packages {
+        let cwd = package
+            .path
+            .strip_prefix(&config.repo_root)
+            .unwrap_or(&package.path)
+            .to_string_lossy()
+            .to_string();
+
+        for (cmd_name, entry) in &package.cmd {
+            let group = group_for(cmd_name);
+            tasks.push(IdeTask {
+                label: format!("{}: {}", pkg_name, cmd_name),
+                command: services.expand(entry.default_cmd()),
+                cwd: cwd.clone(),
+                group,
+            });
+            if let crate::config::CmdEntry::Full(cmd) = entry {
+                for (variant, command) in &cmd.variants {
+                    tasks.push(IdeTask {
+                        label: format!("{}: {} ({})", pkg_name, cmd_name, variant),
+                        command: services.expand(command),
+                        cwd: cwd.clone(),
+                        group,
+                    });
+                }
+            }
+            if !aggregated.contains(cmd_name) {
+                aggregated.push(cmd_name.clone());
+            }
+        }
+    }
+
+    for cmd_name in aggregated {
+        tasks.push(IdeTask {
+            label: format!("devkit: {}", cmd_name),
+            command: format!("devkit cmd {}", cmd_name),
+            cwd: String::new(),
+            group: group_for(&cmd_name),
+        });
+    }
+
+    tasks.sort_by(|a, b| a.label.cmp(&b.label));
+    tasks
+}
+
+fn group_for(cmd_name: &str) -> Option<&'static str> {
+    match cmd_name {
+        "build" => Some("build"),
+        "test" => Some("test"),
+        _ => None,
+    }
+}
+
+/// Write `.vscode/tasks.json`; returns its path
+pub fn export_vscode(repo: &Path, tasks: &[IdeTask]) -> Result<PathBuf> {
+    let path = repo.join(".vscode/tasks.json");
+
+    let mut doc = match fs::read_to_string(&path) {
+        Ok(contents) => serde_json::from_str(&contents).with_context(|| {
+            format!(
+                "Can't update {} (comments aren't supported); move it aside and export again",
+                path.display()
+            )
+        })?,
+        Err(_) => json!({ "version": "2.0.0" }),
+    };
+
+    // Hand-written tasks stay, ours are regenerated
+    let mut all_tasks: Vec<Value> = doc["tasks"]
+        .as_array()
+        .map(|existing| {
+            existing
+                .iter()
+                .filter(|task| task["detail"] != VSCODE_MARKER)
+                .cloned()
+                .collect()
+        })
+        .unwrap_or_default();
+
+    for task in tasks {
+        let cwd = if task.cwd.is_empty() {
+            "${workspaceFolder}".to_string()
+        } else {
+            format!("${{workspaceFolder}}/{}", task.cwd)
+        };
+        let mut entry = json!({
+            "label": task.label,
+            "type": "shell",
+            "command": task.command,
+            "options": { "cwd": cwd },
+            "problemMatcher": [],
+            "detail": VSCODE_MARKER,
+        });
+        if let Some(group) = task.group {
+            entry["group"] = json!(group);
+        }
+        all_tasks.push(entry);
+    }
+    doc["tasks"] = Value::Array(all_tasks);
+
+    fs::create_dir_all(repo.join(".vscode"))?;
+    fs::write(&path, serde_json::to_string_pretty(&doc)? + "\n")
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(path)
+}
+
+/// Write `.run/devkit_*.run.xml`, removing ones for commands that no longer exist;
+/// returns the `.run` directory
+pub fn export_jetbrains(repo: &Path, tasks: &[IdeTask]) -> Result<PathBuf> {
+    let dir = repo.join(".run");
+    fs::create_dir_all(&dir)?;
+
+    for entry in fs::read_dir(&dir)?.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.starts_with(JETBRAINS_PREFIX) && name.ends_with(".run.xml") {
+            fs::remove_file(entry.path())?;
+        }
+    }
+
+    for task in tasks {
+        let file = dir.join(format!(
+            "{}{}.run.xml",
+            JETBRAINS_PREFIX,
+            file_stem(&task.label)
+        ));
+        let cwd = if task.cwd.is_empty() {
+            "$PROJECT_DIR$".to_string()
+        } else {
+            format!("$PROJECT_DIR$/{}", task.cwd)
+        };
+        let xml = format!(
+            r#"<component name="ProjectRunConfigurationManager">
+  <configuration default="false" name="{name}" type="ShConfigurationType">
+    <option name="SCRIPT_TEXT" value="{command}" />
+    <option name="INDEPENDENT_SCRIPT_PATH" value="true" />
+    <option name="SCRIPT_PATH" value="" />
+    <option name="SCRIPT_OPTIONS" value="" />
+    <option name="INDEPENDENT_SCRIPT_WORKING_DIRECTORY" value="false" />
+    <option name="SCRIPT_WORKING_DIRECTORY" value="{cwd}" />
+    <option name="INDEPENDENT_INTERPRETER_PATH" value="true" />
+    <option name="INTERPRETER_PATH" value="/bin/sh" />
+    <option name="INTERPRETER_OPTIONS" value="" />
+    <option name="EXECUTE_IN_TERMINAL" value="true" />
+    <option name="EXECUTE_SCRIPT_FILE" value="false" />
+    <envs />
+    <method v="2" />
+  </configuration>
+</component>
+"#,
+            name = xml_escape(&task.label),
+            command = xml_escape(&task.command),
+            cwd = xml_escape(&cwd),
+        );
+        fs::write(&file, xml).with_context(|| format!("Failed to write {}", file.display()))?;
+    }
+    Ok(dir)
+}
+
+/// Label as a file name: `api: test (watch)` -> `api_test_watch`
+fn file_stem(label: &str) -> String {
+    label
+        .split(|c: char| !c.is_alphanumeric() && c != '-')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(label: &str, command: &str) -> IdeTask {
+        IdeTask {
+            label: label.to_string(),
+            command: command.to_string(),
+            cwd: "pkgs/api".to_string(),
+            group: Some("test"),
+        }
+    }
+
+    fn temp_repo(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("devkit-ide-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_vscode_keeps_hand_written_tasks() {
+        let repo = temp_repo("vscode");
+        fs::create_dir_all(repo.join(".vscode")).unwrap();
+        fs::write(
+            repo.join(".vscode/tasks.json"),
+            r#"{"version": "2.0.0", "tasks": [{"label": "mine"}, {"label": "old", "detail": "Generated by devkit export"}]}"#,
+        )
+        .unwrap();
+
+        let path = export_vscode(&repo, &[task("api: test", "cargo test")]).unwrap();
+        let doc: Value = serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap();
+        let labels: Vec<&str> = doc["tasks"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|task| task["label"].as_str().unwrap())
+            .collect();
+        assert_eq!(labels, ["mine", "api: test"]);
+        assert_eq!(
+            doc["tasks"][1]["options"]["cwd"],
+            "${workspaceFolder}/pkgs/api"
+        );
+        assert_eq!(doc["tasks"][1]["group"], "test");
+
+        fs::remove_dir_all(repo).ok();
+    }
+
+    #[test]
+    fn test_jetbrains_replaces_stale_configurations() {
+        let repo = temp_repo("jetbrains");
+        fs::create_dir_all(repo.join(".run")).unwrap();
+        fs::write(repo.join(".run/devkit_api_gone.run.xml"), "").unwrap();
+        fs::write(repo.join(".run/Mine.run.xml"), "").unwrap();
+
+        export_jetbrains(
+            &repo,
+            &[task("api: test (watch)", "cargo watch -x \"test\"")],
+        )
+        .unwrap();
+        let mut files: Vec<String> = fs::read_dir(repo.join(".run"))
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        files.sort();
+        assert_eq!(files, ["Mine.run.xml", "devkit_api_test_watch.run.xml"]);
+
+        let xml = fs::read_to_string(repo.join(".run/devkit_api_test_watch.run.xml")).unwrap();
+        assert!(xml.contains(r#"value="cargo watch -x &quot;test&quot;""#));
+        assert!(xml.contains(r#"value="$PROJECT_DIR$/pkgs/api""#));
+
+        fs::remove_dir_all(repo).ok();
+    }
+}
@@ -1,10 +1,45 @@
 //! Project initialization and setup wizard
 
+use crate::config::PackageToml;
+use crate::frameworks::{compose_file, node_package_manager, Framework, Service};
 use anyhow::{Context, Result};
 use dialoguer::{Confirm, Input, MultiSelect};
 use glob;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+/// Manifests that mark a package for framework detection (package.json is scanned separately)
+const FRAMEWORK_MANIFESTS: &[&str] = &[
+    "manage.py",
+    "Gemfile",
+    "pom.xml",
+    "build.gradle",
+    "build.gradle.kts",
+    "mix.exs",
+];
+
+/// Features offered by the setup wizard and the extensions they enable
+const FEATURES: &[(&str, &[&str])] = &[
+    ("Docker operations", &["docker"]),
+    ("Database management", &["database"]),
+    ("CI integration", &["ci"]),
+    ("Environment management", &["secrets"]),
+    ("Tunneling (ngrok/cloudflared)", &["tunnel"]),
+];
+
+/// Package commands run by the generated CI workflow, in order; the pre-commit hook
+/// runs the first two
+const GATE_COMMANDS: &[&str] = &["fmt", "lint", "test", "build"];
+
+/// Dependency and build directories that never contain packages of their own
+const SKIPPED_DIRS: &[&str] = &[
+    "node_modules",
+    "vendor",
+    "deps",
+    "_build",
+    "target",
+    ".venv",
+];
 
 /// Initialize a new devkit project
 pub fn init_project(path: &Path, interactive: bool) -> Result<()> {
@@ -49,26 +84,17 @@ pub fn init_project(path: &Path, interactive: bool) -> Result<()> {
     }
     println!();
 
-    // Ask which features to enable
+    // Ask which features to enable; the rest are disabled in [extensions]
     let features = if interactive {
-        let feature_options = vec![
-            "Docker operations",
-            "Database management",
-            "Code quality tools",
-            "CI integration",
-            "Environment management",
-            "Tunneling (ngrok/cloudflared)",
-        ];
-
-        let defaults = vec![true, true, true, false, false, false]; // Docker, Database, Quality enabled by default
+        let feature_options: Vec<&str> = FEATURES.iter().map(|(label, _)| *label).collect();
 
         MultiSelect::new()
             .with_prompt("Select features to enable")
             .items(&feature_options)
-            .defaults(&defaults)
+            .defaults(&vec![true; FEATURES.len()])
             .interact()?
     } else {
-        vec![0, 1, 2] // Default features
+        (0..FEATURES.len()).collect()
     };
 
     // Create .dev directory
@@ -111,7 +137,7 @@ pub fn init_project(path: &Path, interactive: bool) -> Result<()> {
     // Scan for packages and generate dev.toml files
     println!();
     println!("Scanning for packages...");
-    let packages_generated = scan_and_generate_package_configs(path)?;
+    let (packages_generated, services) = scan_and_generate_package_configs(path)?;
 
     if packages_generated > 0 {
         println!(
@@ -120,6 +146,18 @@ pub fn init_project(path: &Path, interactive: bool) -> Result<()> {
         );
     }
 
+    // Starter compose file for the databases and caches the frameworks use
+    let compose_path = path.join("docker-compose.yml");
+    if !services.is_empty() && !compose_path.exists() {
+        fs::write(&compose_path, compose_file(&project_name, &services))
+            .context("Failed to write docker-compose.yml")?;
+        println!("✓ Created docker-compose.yml");
+    }
+
+    if interactive {
+        offer_quality_gates(path)?;
+    }
+
     println!();
     println!("✓ devkit project initialized!");
     println!();
@@ -131,9 +169,207 @@ pub fn init_project(path: &Path, interactive: bool) -> Result<()> {
     Ok(())
 }
 
+/// Offer a pre-commit hook and a GitHub Actions workflow running the package commands
+fn offer_quality_gates(path: &Path) -> Result<()> {
+    // Read the dev.toml files directly: they needn't match the workspace globs yet
+    let packages: Vec<(PathBuf, PackageToml)> = package_dev_tomls(path)?
+        .into_iter()
+        .filter_map(|file| {
+            let config = toml::from_str(&fs::read_to_string(&file).ok()?).ok()?;
+            Some((file.parent()?.to_path_buf(), config))
+        })
+        .collect();
+    let gates: Vec<&str> = GATE_COMMANDS
+        .iter()
+        .copied()
+        .filter(|name| {
+            packages
+                .iter()
+                .any(|(_, config)| config.cmd.contains_key(*name))
+        })
+        .collect();
+    if gates.is_empty() {
+        return Ok(());
+    }
+
+    let hook_gates: Vec<&str> = gates
+        .iter()
+        .copied()
+        .filter(|name| GATE_COMMANDS[..2].contains(name))
+        .collect();
+    let hook_path = path.join(".git/hooks/pre-commit");
+    if !hook_gates.is_empty()
+        && path.join(".git").is_dir()
+        && !hook_path.exists()
+        && Confirm::new()
+            .with_prompt(format!(
+                "Add a pre-commit hook running {} on changed packages?",
+                hook_gates.join(" and ")
+            ))
+            .default(true)
+            .interact()?
+    {
+        fs::create_dir_all(path.join(".git/hooks"))?;
+        fs::write(&hook_path, pre_commit_hook(&hook_gates))
+            .context("Failed to write pre-commit hook")?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&hook_path, fs::Permissions::from_mode(0o755))?;
+        }
+        println!("✓ Installed .git/hooks/pre-commit");
+    }
+
+    let workflow_path = path.join(".github/workflows/ci.yml");
+    if !workflow_path.exists()
+        && Confirm::new()
+            .with_prompt(format!(
+                "Add a GitHub Actions workflow running {}?",
+                gates.join(", ")
+            ))
+            .default(true)
+            .interact()?
+    {
+        let has_manifest = |manifest: &str| {
+            path.join(manifest).exists()
+                || packages.iter().any(|(dir, _)| dir.join(manifest).exists())
+        };
+        fs::create_dir_all(path.join(".github/workflows"))?;
+        fs::write(
+            &workflow_path,
+            ci_workflow(
+                &gates,
+                has_manifest("Cargo.toml"),
+                has_manifest("package.json"),
+            ),
+        )
+        .context("Failed to write CI workflow")?;
+        println!("✓ Created .github/workflows/ci.yml");
+    }
+
+    Ok(())
+}
+
+/// Pre-commit hook running commands on the packages with uncommitted changes
+fn pre_commit_hook(commands: &[&str]) -> String {
+    let mut hook = String::from(
+        "#!/bin/sh\n# Added by devkit init: checks the packages changed since the last commit\nset -e\n\n",
+    );
+    for command in commands {
+        hook.push_str(&format!("devkit cmd {} --affected --base HEAD\n", command));
+    }
+    hook
+}
+
+/// GitHub Actions workflow installing devkit and running commands across packages
+fn ci_workflow(commands: &[&str], rust: bool, node: bool) -> String {
+    let mut workflow = String::from(
+        r#"name: CI
+
+on:
+  push:
+    branches: [main]
+  pull_request:
+
+jobs:
+  check:
+    runs-on: ubuntu-latest
+    steps:
+      - uses: actions/checkout@v4
+"#,
+    );
+    if rust {
+        workflow.push_str(
+            r#"      - uses: dtolnay/rust-toolchain@stable
+        with:
+          components: rustfmt, clippy
+      - uses: Swatinem/rust-cache@v2
+"#,
+        );
+    }
+    if node {
+        workflow.push_str(
+            r#"      - uses: actions/setup-node@v4
+        with:
+          node-version: lts/*
+"#,
+        );
+    }
+    workflow.push_str(
+        r#"      - name: Install devkit
+        run: |
+          curl -fsSL https://github.com/crcn/devkit/releases/latest/download/devkit-linux-x86_64 -o devkit-linux-x86_64
+          curl -fsSL https://github.com/crcn/devkit/releases/latest/download/SHA256SUMS | sha256sum --check --ignore-missing
+          sudo install -m 755 devkit-linux-x86_64 /usr/local/bin/devkit
+"#,
+    );
+    for command in commands {
+        workflow.push_str(&format!("      - run: devkit cmd {}\n", command));
+    }
+    workflow
+}
+
+/// Package dev.toml files anywhere under `path`, outside dependency directories
+fn package_dev_tomls(path: &Path) -> Result<Vec<PathBuf>> {
+    Ok(glob::glob(&format!("{}/**/dev.toml", path.display()))
+        .context("Failed to glob for dev.toml files")?
+        .flatten()
+        .filter(|entry| {
+            !entry
+                .components()
+                .any(|c| SKIPPED_DIRS.iter().any(|skipped| c.as_os_str() == *skipped))
+        })
+        .collect())
+}
+
+/// Migrate an existing project's configs to the current schema and report the changes
+pub fn upgrade_project(path: &Path) -> Result<()> {
+    let config_path = path.join(".dev/config.toml");
+    if !config_path.exists() {
+        anyhow::bail!("No .dev/config.toml found; run `devkit init` first");
+    }
+
+    println!("🔧 Upgrading devkit config");
+    println!();
+
+    let mut files = vec![(config_path, true)];
+    files.extend(
+        package_dev_tomls(path)?
+            .into_iter()
+            .map(|file| (file, false)),
+    );
+
+    let mut changed = 0;
+    for (file, global) in files {
+        let changes = crate::migrate::migrate_file(&file, global)?;
+        if changes.is_empty() {
+            continue;
+        }
+        changed += 1;
+        println!("{}", file.strip_prefix(path).unwrap_or(&file).display());
+        for change in changes {
+            println!("  ✓ {}", change);
+        }
+    }
+
+    if changed == 0 {
+        println!("✓ Config is up to date");
+    } else {
+        println!();
+        println!(
+            "✓ Upgraded {} file(s); review the changes with `git diff`",
+            changed
+        );
+    }
+    Ok(())
+}
+
 /// Scan project for packages and generate dev.toml files with detected capabilities
-fn scan_and_generate_package_configs(project_root: &Path) -> Result<usize> {
+///
+/// Returns the number of files written and the services the detected frameworks use.
+fn scan_and_generate_package_configs(project_root: &Path) -> Result<(usize, Vec<Service>)> {
     let mut count = 0;
+    let mut services = Vec::new();
 
     // Scan for Rust packages (Cargo.toml)
     for entry in glob::glob(&format!("{}/**/Cargo.toml", project_root.display()))
@@ -181,21 +417,81 @@ fn scan_and_generate_package_configs(project_root: &Path) -> Result<usize> {
                 continue;
             }
 
-            // Generate Node dev.toml
-            let dev_config = generate_node_dev_toml(&package_path)?;
-            fs::write(&dev_toml_path, dev_config)?;
-            println!("  ✓ Created {}", dev_toml_path.display());
+            // Framework template, or generic Node dev.toml from the package scripts
+            if let Some(framework) = Framework::detect(package_dir) {
+                write_framework_dev_toml(package_dir, framework, &mut services)?;
+            } else {
+                let dev_config = generate_node_dev_toml(&package_path)?;
+                fs::write(&dev_toml_path, dev_config)?;
+                println!("  ✓ Created {}", dev_toml_path.display());
+            }
             count += 1;
         }
     }
 
-    Ok(count)
+    // Scan for Python, Ruby, JVM and Elixir frameworks
+    for manifest in FRAMEWORK_MANIFESTS {
+        for entry in glob::glob(&format!("{}/**/{}", project_root.display(), manifest))
+            .context("Failed to glob for framework manifests")?
+            .flatten()
+        {
+            let package_dir = entry.parent().unwrap();
+            if package_dir
+                .components()
+                .any(|c| SKIPPED_DIRS.iter().any(|skipped| c.as_os_str() == *skipped))
+                || package_dir.join("dev.toml").exists()
+            {
+                continue;
+            }
+
+            if let Some(framework) = Framework::detect(package_dir) {
+                write_framework_dev_toml(package_dir, framework, &mut services)?;
+                count += 1;
+            }
+        }
+    }
+
+    services.sort();
+    services.dedup();
+    Ok((count, services))
+}
+
+/// Write a framework's dev.toml and collect the services it uses
+fn write_framework_dev_toml(
+    package_dir: &Path,
+    framework: Framework,
+    services: &mut Vec<Service>,
+) -> Result<()> {
+    let name = package_dir
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("package");
+    let dev_toml_path = package_dir.join("dev.toml");
+
+    let mut config = format!(
+        r#"# =============================================================================
+# {} Dev Configuration ({})
+# =============================================================================
+
+"#,
+        name,
+        framework.name()
+    );
+    config.push_str(&framework.commands(package_dir));
+    fs::write(&dev_toml_path, config)?;
+    println!(
+        "  ✓ Created {} ({})",
+        dev_toml_path.display(),
+        framework.name()
+    );
+
+    services.extend(framework.services(package_dir));
+    Ok(())
 }
 
 /// Generate dev.toml for a Rust package
 fn generate_rust_dev_toml(cargo_path: &Path) -> Result<String> {
     let content = fs::read_to_string(cargo_path)?;
-    let package_dir = cargo_path.parent().unwrap();
 
     // Parse package name
     let name = content
@@ -213,12 +509,6 @@ fn generate_rust_dev_toml(cargo_path: &Path) -> Result<String> {
         name
     );
 
-    // Detect if it's a binary or library
-    let is_bin = package_dir.join("src/main.rs").exists();
-    if is_bin {
-        config.push_str("releasable = true\n\n");
-    }
-
     // Add standard Rust commands
     config.push_str(
         r#"[cmd.build]
@@ -266,14 +556,7 @@ fn generate_node_dev_toml(package_path: &Path) -> Result<String> {
     );
 
     // Detect package manager
-    let package_dir = package_path.parent().unwrap();
-    let pm = if package_dir.join("yarn.lock").exists() {
-        "yarn"
-    } else if package_dir.join("pnpm-lock.yaml").exists() {
-        "pnpm"
-    } else {
-        "npm"
-    };
+    let pm = node_package_manager(package_path.parent().unwrap());
 
     // Check scripts in package.json
     let scripts = package_json["scripts"].as_object();
@@ -379,14 +662,36 @@ default = "dev"
         );
     }
 
-    // Add features configuration
-    config.push_str("\n[features]\n");
-    config.push_str(&format!("docker = {}\n", features.contains(&0)));
-    config.push_str(&format!("database = {}\n", features.contains(&1)));
-    config.push_str(&format!("quality = {}\n", features.contains(&2)));
-    config.push_str(&format!("ci = {}\n", features.contains(&3)));
-    config.push_str(&format!("env = {}\n", features.contains(&4)));
-    config.push_str(&format!("tunnel = {}\n", features.contains(&5)));
+    // Extensions for the features that weren't selected
+    let disabled: Vec<String> = FEATURES
+        .iter()
+        .enumerate()
+        .filter(|(idx, _)| !features.contains(idx))
+        .flat_map(|(_, (_, extensions))| extensions.iter().map(|ext| format!("\"{}\"", ext)))
+        .collect();
+    config.push_str("\n[extensions]\n");
+    config.push_str(&format!("disabled = [{}]\n", disabled.join(", ")));
 
     config
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quality_gate_files() {
+        let hook = pre_commit_hook(&["fmt", "lint"]);
+        assert!(hook.starts_with("#!/bin/sh\n"));
+        assert!(hook.ends_with(
+            "devkit cmd fmt --affected --base HEAD\ndevkit cmd lint --affected --base HEAD\n"
+        ));
+
+        let workflow = ci_workflow(&["lint", "test"], true, false);
+        let parsed: serde_yaml::Value = serde_yaml::from_str(&workflow).unwrap();
+        let steps = parsed["jobs"]["check"]["steps"].as_sequence().unwrap();
+        assert_eq!(steps.len(), 6);
+        assert_eq!(steps[5]["run"], "devkit cmd test");
+        assert!(!workflow.contains("setup-node"));
+    }
+}
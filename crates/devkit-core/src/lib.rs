@@ -1,23 +1,42 @@
 //! Core types and utilities for devkit
 
+pub mod autofix;
 pub mod config;
 pub mod context;
 pub mod detection;
+pub mod discovery_cache;
+pub mod doctor;
+pub mod environment;
 pub mod error;
 pub mod extension;
+pub mod extension_install;
 pub mod extension_loader;
 pub mod external_extension;
+pub mod favorites;
+pub mod forwards;
+pub mod frameworks;
 pub mod history;
+pub mod hooks;
+pub mod ide;
 pub mod init;
+pub mod migrate;
+pub mod oplog;
 pub mod output;
+pub mod palette;
+pub mod ports;
+pub mod process;
+pub mod scaffold;
+pub mod status;
+pub mod toolchain;
 pub mod update;
 pub mod utils;
 pub mod validation;
+pub mod wait;
 
 pub use config::{CmdEntry, Config};
 pub use context::AppContext;
 pub use detection::Features;
 pub use error::{DevkitError, Result};
-pub use extension::{Extension, ExtensionRegistry, MenuItem};
+pub use extension::{Extension, ExtensionRegistry, ExtensionStatus, MenuItem};
 pub use utils::{cmd_exists, docker_available};
 pub use validation::{validate_config, ValidationReport};
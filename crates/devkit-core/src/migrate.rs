@@ -0,0 +1,292 @@
+//! Config migrations for `devkit init --upgrade`
+//!
+//! Rewrites `.dev/config.toml` and package `dev.toml` files to the current schema,
+//! keeping comments and formatting. Each migration reports what it changed.
+//!
+//! - `[features]` is replaced by `[extensions] disabled` (features switched off
+//!   become disabled extensions)
+//! - `releasable` in dev.toml, written by older `devkit init`, is removed
+//! - Sections added since the config was written are appended as commented defaults
+
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::Path;
+use toml_edit::{Array, DocumentMut, Item, Table};
+
+/// Extensions each `[features]` flag used to switch off
+const FEATURE_EXTENSIONS: &[(&str, &[&str])] = &[
+    ("docker", &["docker"]),
+    ("database", &["database"]),
+    ("ci", &["ci"]),
+    ("env", &["secrets"]),
+    ("deploy", &["ecs", "pulumi"]),
+    ("tunnel", &["tunnel"]),
+    ("benchmark", &["benchmark"]),
+    ("git_workflows", &["git"]),
+    ("monitoring", &["monitoring"]),
+];
+
+/// Sections appended (commented out) when missing from config.toml
+const NEW_SECTIONS: &[(&str, &str)] = &[
+    (
+        "extensions",
+        r#"# [extensions]
+# # Extensions to turn off (see `devkit ext list`)
+# disabled = []
+# # Start without external extensions that fail to load
+# skip_broken = false
+"#,
+    ),
+    (
+        "hooks",
+        r#"# [hooks]
+# # Shell commands run around devkit operations (pre_<operation> / post_<operation>)
+# post_install = "cp -n .env.example .env"
+"#,
+    ),
+    (
+        "output",
+        r#"# [output]
+# color = "auto"    # auto, always or never (NO_COLOR also turns colors off)
+# emoji = true
+# theme = "colorful"
+"#,
+    ),
+    (
+        "notifications",
+        r#"# [notifications]
+# # Desktop notification when a slow command finishes
+# enabled = false
+# min_duration_secs = 60
+"#,
+    ),
+];
+
+/// Migrate a `.dev/config.toml` document; returns the changes made
+pub fn migrate_global(doc: &mut DocumentMut) -> Vec<String> {
+    let mut changes = Vec::new();
+
+    if let Some(features) = remove_table(doc, "features") {
+        let disabled: Vec<&str> = FEATURE_EXTENSIONS
+            .iter()
+            .filter(|(flag, _)| features.get(flag).and_then(Item::as_bool) == Some(false))
+            .flat_map(|(_, extensions)| extensions.iter().copied())
+            .collect();
+
+        if disabled.is_empty() {
+            changes.push("Removed [features] (no longer used)".to_string());
+        } else {
+            let added = add_disabled_extensions(doc, &disabled);
+            changes.push(format!(
+                "Replaced [features] with [extensions] disabled ({})",
+                if added.is_empty() {
+                    "already listed".to_string()
+                } else {
+                    added.join(", ")
+                }
+            ));
+        }
+    }
+
+    changes
+}
+
+/// Commented defaults for sections missing from `config.toml` (which isn't parsed as a
+/// document here, so commented-out sections count as present)
+pub fn missing_sections(contents: &str) -> Vec<(&'static str, &'static str)> {
+    NEW_SECTIONS
+        .iter()
+        .filter(|(name, _)| {
+            let header = format!("[{}]", name);
+            !contents
+                .lines()
+                .any(|line| line.trim_start_matches(['#', ' ']).starts_with(&header))
+        })
+        .copied()
+        .collect()
+}
+
+/// Migrate a package `dev.toml` document; returns the changes made
+pub fn migrate_package(doc: &mut DocumentMut) -> Vec<String> {
+    let mut changes = Vec::new();
+
+    if doc.remove("releasable").is_some() {
+        changes.push("Removed `releasable` (no longer used)".to_string());
+    }
+
+    changes
+}
+
+/// Migrate and rewrite a config file; returns the changes made
+pub fn migrate_file(path: &Path, global: bool) -> Result<Vec<String>> {
+    let contents =
+        fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let mut doc: DocumentMut = contents
+        .parse()
+        .with_context(|| format!("Invalid TOML in {}", path.display()))?;
+
+    let mut changes = if global {
+        migrate_global(&mut doc)
+    } else {
+        migrate_package(&mut doc)
+    };
+
+    let mut output = doc.to_string();
+    if global {
+        for (name, section) in missing_sections(&output) {
+            if !output.ends_with("\n\n") {
+                output.push('\n');
+            }
+            output.push_str(section);
+            changes.push(format!("Added commented [{}] section", name));
+        }
+    }
+
+    if !changes.is_empty() {
+        fs::write(path, output).with_context(|| format!("Failed to write {}", path.display()))?;
+    }
+    Ok(changes)
+}
+
+/// Remove a top-level table, keeping the comments that belong to the section above it
+///
+/// Comments between two sections are stored as the prefix of the second table. The
+/// first paragraph (up to a blank line) usually documents the previous section, so it
+/// moves to whichever table now follows (or the end of the document).
+fn remove_table(doc: &mut DocumentMut, key: &str) -> Option<Item> {
+    let removed = doc.remove(key)?;
+    let Some(table) = removed.as_table() else {
+        return Some(removed);
+    };
+
+    let prefix = table
+        .decor()
+        .prefix()
+        .and_then(|prefix| prefix.as_str())
+        .unwrap_or_default();
+    let leading = match prefix.find("\n\n") {
+        Some(end) => &prefix[..end + 1],
+        None => "",
+    };
+    if leading.trim().is_empty() {
+        return Some(removed);
+    }
+
+    let position = table.position();
+    let next = doc
+        .iter_mut()
+        .filter_map(|(_, item)| item.as_table_mut())
+        .filter(|next| next.position() > position)
+        .min_by_key(|next| next.position());
+    match next {
+        Some(next) => {
+            let existing = next
+                .decor()
+                .prefix()
+                .and_then(|prefix| prefix.as_str())
+                .unwrap_or_default()
+                .to_string();
+            next.decor_mut().set_prefix(format!(
+                "{}\n{}",
+                leading,
+                existing.trim_start_matches('\n')
+            ));
+        }
+        None => {
+            let trailing = doc.trailing().as_str().unwrap_or_default().to_string();
+            doc.set_trailing(format!("{}{}", leading, trailing));
+        }
+    }
+    Some(removed)
+}
+
+/// Add extensions to `[extensions] disabled`; returns the ones that weren't listed
+fn add_disabled_extensions<'a>(doc: &mut DocumentMut, extensions: &[&'a str]) -> Vec<&'a str> {
+    if !doc.contains_key("extensions") {
+        // Appended after everything, so comments at the end of the file go above it
+        let mut table = Table::new();
+        let trailing = doc.trailing().as_str().unwrap_or_default().to_string();
+        table
+            .decor_mut()
+            .set_prefix(format!("{}\n", trailing.trim_end()));
+        doc.set_trailing("");
+        doc.insert("extensions", Item::Table(table));
+    }
+    let table = &mut doc["extensions"];
+    let Some(table) = table.as_table_like_mut() else {
+        return Vec::new();
+    };
+    let disabled = table
+        .entry("disabled")
+        .or_insert(toml_edit::value(Array::new()));
+    let Some(list) = disabled.as_array_mut() else {
+        return Vec::new();
+    };
+
+    let mut added = Vec::new();
+    for &name in extensions {
+        if !list.iter().any(|value| value.as_str() == Some(name)) {
+            list.push(name);
+            added.push(name);
+        }
+    }
+    added
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migrate_features() {
+        let mut doc: DocumentMut = r#"[project]
+name = "demo" # keep me
+
+[services]
+# api = 8080
+
+# Feature flags
+[features]
+docker = true
+ci = false
+deploy = false
+quality = false
+"#
+        .parse()
+        .unwrap();
+
+        let changes = migrate_global(&mut doc);
+        assert_eq!(
+            changes,
+            ["Replaced [features] with [extensions] disabled (ci, ecs, pulumi)"]
+        );
+        let output = doc.to_string();
+        assert!(output.contains("name = \"demo\" # keep me"));
+        assert!(!output.contains("[features]"));
+        assert!(output.contains(r#"disabled = ["ci", "ecs", "pulumi"]"#));
+        assert!(output.contains("[services]\n# api = 8080\n"));
+        assert!(!output.contains("# Feature flags"));
+
+        // Already migrated
+        assert!(migrate_global(&mut doc).is_empty());
+    }
+
+    #[test]
+    fn test_missing_sections() {
+        let contents = "[project]\nname = \"demo\"\n\n[hooks]\n\n# [output]\n";
+        let missing: Vec<&str> = missing_sections(contents)
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        assert_eq!(missing, ["extensions", "notifications"]);
+    }
+
+    #[test]
+    fn test_migrate_package() {
+        let mut doc: DocumentMut = "releasable = true\n\n[cmd]\ntest = \"cargo test\"\n"
+            .parse()
+            .unwrap();
+        assert_eq!(migrate_package(&mut doc).len(), 1);
+        assert_eq!(doc.to_string().trim(), "[cmd]\ntest = \"cargo test\"");
+    }
+}
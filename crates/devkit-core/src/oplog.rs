@@ -0,0 +1,313 @@
+//! Operation log
+//!
+//! Every command devkit spawns is appended to `operations.jsonl` in the devkit cache
+//! directory, one JSON object per line: program, args, cwd, duration, exit code and
+//! the tail of any captured output. It answers "what exactly did devkit run?" after
+//! the fact:
+//!
+//! ```text
+//! tail -n 5 ~/.cache/devkit/operations.jsonl | jq .
+//! ```
+//!
+//! Spawn commands through [`LoggedCommand`] (or [`Operation`] when the child is
+//! waited on separately). Logging is best-effort and never fails the command.
+
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, ExitStatus, Output};
+use std::time::Instant;
+
+use crate::process::current_timestamp;
+
+const LOG_FILE: &str = "operations.jsonl";
+
+/// Size at which the log is rotated
+const MAX_LOG_BYTES: u64 = 5 * 1024 * 1024;
+
+/// Rotated files kept (`operations.jsonl.1` is the newest)
+const ROTATED_FILES: usize = 3;
+
+/// Captured output kept per entry (the end, where errors usually are)
+const MAX_OUTPUT_BYTES: usize = 4096;
+
+/// One spawned command
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpRecord {
+    pub timestamp: u64,
+    pub program: String,
+    pub args: Vec<String>,
+    pub cwd: Option<String>,
+    pub duration_ms: u64,
+    /// None when the command was started in the background or didn't start
+    pub exit_code: Option<i32>,
+    /// Tail of stdout and stderr, when devkit captured them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
+    /// Why the command couldn't be started
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// A command being timed, for children that are spawned and waited on separately
+///
+/// ```no_run
+/// # use devkit_core::oplog::Operation;
+/// # use std::process::{Command, Stdio};
+/// let mut cmd = Command::new("tool");
+/// cmd.stdin(Stdio::piped());
+/// let op = Operation::start(&cmd);
+/// let status = cmd.spawn().and_then(|mut child| child.wait());
+/// op.finish_status(&status);
+/// ```
+pub struct Operation {
+    started: Instant,
+    program: String,
+    args: Vec<String>,
+    cwd: Option<String>,
+    redact: bool,
+}
+
+impl Operation {
+    pub fn start(cmd: &Command) -> Self {
+        let cwd = cmd
+            .get_current_dir()
+            .map(Path::to_path_buf)
+            .or_else(|| std::env::current_dir().ok());
+        Self {
+            started: Instant::now(),
+            program: cmd.get_program().to_string_lossy().into_owned(),
+            args: cmd
+                .get_args()
+                .map(|arg| arg.to_string_lossy().into_owned())
+                .collect(),
+            cwd: cwd.map(|dir| dir.display().to_string()),
+            redact: false,
+        }
+    }
+
+    /// Don't record the command's output (it may contain secrets)
+    pub fn redact_output(mut self) -> Self {
+        self.redact = true;
+        self
+    }
+
+    pub fn finish_status(self, result: &io::Result<ExitStatus>) {
+        match result {
+            Ok(status) => self.finish(status.code(), None),
+            Err(e) => self.failed(e),
+        }
+    }
+
+    pub fn finish_output(self, result: &io::Result<Output>) {
+        match result {
+            Ok(output) => {
+                let mut captured = output.stdout.clone();
+                captured.extend_from_slice(&output.stderr);
+                self.finish(output.status.code(), Some(&captured));
+            }
+            Err(e) => self.failed(e),
+        }
+    }
+
+    /// Record a child left running in the background
+    pub fn finish_spawn(self, result: &io::Result<Child>) {
+        match result {
+            Ok(_) => self.finish(None, None),
+            Err(e) => self.failed(e),
+        }
+    }
+
+    fn finish(self, exit_code: Option<i32>, output: Option<&[u8]>) {
+        let output = match output {
+            Some(_) if self.redact => Some("[redacted]".to_string()),
+            Some(bytes) if !bytes.is_empty() => Some(truncate_output(bytes)),
+            _ => None,
+        };
+        let record = self.record(exit_code, output, None);
+        append(&record);
+    }
+
+    fn failed(self, error: &io::Error) {
+        let record = self.record(None, None, Some(error.to_string()));
+        append(&record);
+    }
+
+    fn record(
+        self,
+        exit_code: Option<i32>,
+        output: Option<String>,
+        error: Option<String>,
+    ) -> OpRecord {
+        OpRecord {
+            timestamp: current_timestamp(),
+            program: self.program,
+            args: self.args,
+            cwd: self.cwd,
+            duration_ms: self.started.elapsed().as_millis() as u64,
+            exit_code,
+            output,
+            error,
+        }
+    }
+}
+
+/// `Command` methods that record the run in the operation log
+pub trait LoggedCommand {
+    /// [`Command::status`], logged
+    fn logged_status(&mut self) -> io::Result<ExitStatus>;
+
+    /// [`Command::output`], logged with the tail of the output
+    fn logged_output(&mut self) -> io::Result<Output>;
+
+    /// [`Command::output`], logged without the output (for secrets)
+    fn logged_output_redacted(&mut self) -> io::Result<Output>;
+
+    /// [`Command::spawn`] for a child that isn't waited on; logged without an exit code
+    fn logged_spawn(&mut self) -> io::Result<Child>;
+}
+
+impl LoggedCommand for Command {
+    fn logged_status(&mut self) -> io::Result<ExitStatus> {
+        let op = Operation::start(self);
+        let result = self.status();
+        op.finish_status(&result);
+        result
+    }
+
+    fn logged_output(&mut self) -> io::Result<Output> {
+        let op = Operation::start(self);
+        let result = self.output();
+        op.finish_output(&result);
+        result
+    }
+
+    fn logged_output_redacted(&mut self) -> io::Result<Output> {
+        let op = Operation::start(self).redact_output();
+        let result = self.output();
+        op.finish_output(&result);
+        result
+    }
+
+    fn logged_spawn(&mut self) -> io::Result<Child> {
+        let op = Operation::start(self);
+        let result = self.spawn();
+        op.finish_spawn(&result);
+        result
+    }
+}
+
+/// Path of the operation log
+pub fn log_path() -> Option<PathBuf> {
+    dirs::cache_dir().map(|dir| dir.join("devkit").join(LOG_FILE))
+}
+
+fn append(record: &OpRecord) {
+    let Some(path) = log_path() else {
+        return;
+    };
+    if let Err(e) = append_to(&path, record) {
+        tracing::debug!("Failed to write operation log {}: {}", path.display(), e);
+    }
+}
+
+fn append_to(path: &Path, record: &OpRecord) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    if fs::metadata(path).is_ok_and(|meta| meta.len() >= MAX_LOG_BYTES) {
+        rotate(path)?;
+    }
+
+    let mut line = serde_json::to_string(record)?;
+    line.push('\n');
+    // One write per line, so parallel commands don't interleave
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?
+        .write_all(line.as_bytes())
+}
+
+/// Shift `log.1` -> `log.2` ..., dropping the oldest, and move the log to `log.1`
+fn rotate(path: &Path) -> io::Result<()> {
+    let rotated = |n: usize| PathBuf::from(format!("{}.{}", path.display(), n));
+
+    let _ = fs::remove_file(rotated(ROTATED_FILES));
+    for n in (1..ROTATED_FILES).rev() {
+        let from = rotated(n);
+        if from.exists() {
+            fs::rename(&from, rotated(n + 1))?;
+        }
+    }
+    fs::rename(path, rotated(1))
+}
+
+/// Last `MAX_OUTPUT_BYTES` of the output, cut at a character boundary
+fn truncate_output(bytes: &[u8]) -> String {
+    let text = String::from_utf8_lossy(bytes);
+    if text.len() <= MAX_OUTPUT_BYTES {
+        return text.into_owned();
+    }
+    let mut start = text.len() - MAX_OUTPUT_BYTES;
+    while !text.is_char_boundary(start) {
+        start += 1;
+    }
+    format!("…{}", &text[start..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(program: &str) -> OpRecord {
+        OpRecord {
+            timestamp: 0,
+            program: program.to_string(),
+            args: vec!["--version".to_string()],
+            cwd: None,
+            duration_ms: 1,
+            exit_code: Some(0),
+            output: None,
+            error: None,
+        }
+    }
+
+    #[test]
+    fn test_truncate_output() {
+        assert_eq!(truncate_output(b"short"), "short");
+
+        let long = "é".repeat(MAX_OUTPUT_BYTES);
+        let truncated = truncate_output(long.as_bytes());
+        assert!(truncated.starts_with('…'));
+        assert!(truncated.len() <= MAX_OUTPUT_BYTES + '…'.len_utf8());
+    }
+
+    #[test]
+    fn test_append_and_rotate() {
+        let dir = std::env::temp_dir().join(format!("devkit-oplog-{}", std::process::id()));
+        let path = dir.join(LOG_FILE);
+        let _ = fs::remove_dir_all(&dir);
+
+        append_to(&path, &record("git")).unwrap();
+        append_to(&path, &record("cargo")).unwrap();
+        let contents = fs::read_to_string(&path).unwrap();
+        let programs: Vec<String> = contents
+            .lines()
+            .map(|line| serde_json::from_str::<OpRecord>(line).unwrap().program)
+            .collect();
+        assert_eq!(programs, ["git", "cargo"]);
+
+        for _ in 0..=ROTATED_FILES {
+            rotate(&path).ok();
+            append_to(&path, &record("npm")).unwrap();
+        }
+        assert!(dir.join(format!("{}.{}", LOG_FILE, ROTATED_FILES)).exists());
+        assert!(!dir
+            .join(format!("{}.{}", LOG_FILE, ROTATED_FILES + 1))
+            .exists());
+
+        fs::remove_dir_all(&dir).ok();
+    }
+}
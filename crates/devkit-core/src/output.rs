@@ -1,5 +1,7 @@
 //! Output formatting utilities
 
+use crate::config::PromptTheme;
+use dialoguer::theme::{ColorfulTheme, SimpleTheme, Theme};
 use serde::Serialize;
 use std::fmt::Display;
 
@@ -60,3 +62,69 @@ pub fn format_list<T: Serialize + Display>(items: &[T], format: OutputFormat) ->
         }
     }
 }
+
+impl PromptTheme {
+    /// The dialoguer theme for prompts
+    pub fn dialoguer(self) -> Box<dyn Theme> {
+        match self {
+            PromptTheme::Colorful => Box::new(ColorfulTheme::default()),
+            PromptTheme::Plain => Box::new(SimpleTheme),
+        }
+    }
+}
+
+/// Remove emoji from text, for terminals and CI logs that can't render them
+///
+/// ✅ and ❌ become ✓ and ✗. Text symbols such as ✓, ✗, ⚠ and → are kept, and
+/// a space following a leading emoji is dropped with it.
+pub fn strip_emoji(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '✅' => out.push('✓'),
+            '❌' => out.push('✗'),
+            // Variation selector and zero-width joiner
+            '\u{FE0F}' | '\u{200D}' => {}
+            c if is_emoji(c) => {
+                while chars.next_if(|&next| next == '\u{FE0F}').is_some() {}
+                if out.is_empty() || out.ends_with(char::is_whitespace) {
+                    chars.next_if_eq(&' ');
+                }
+            }
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn is_emoji(c: char) -> bool {
+    match c as u32 {
+        // Pictographs, emoticons, transport, supplemental symbols
+        0x1F000..=0x1FAFF => true,
+        // ⏩ .. ⏺ (⏱, ⏹, ...)
+        0x23E9..=0x23FA => true,
+        // Miscellaneous symbols (☁, ⚙, ...), except ⚠ which renders as text
+        0x2600..=0x26FF => c != '⚠',
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_emoji() {
+        assert_eq!(strip_emoji("🐳 Docker"), "Docker");
+        assert_eq!(strip_emoji("📜 tools / Run ☁️ sync"), "tools / Run sync");
+        assert_eq!(strip_emoji("✅ Done"), "✓ Done");
+        assert_eq!(strip_emoji("❌ Exit"), "✗ Exit");
+        assert_eq!(
+            strip_emoji("✓ built → ⚠ 2 warnings"),
+            "✓ built → ⚠ 2 warnings"
+        );
+        assert_eq!(strip_emoji("plain"), "plain");
+    }
+}
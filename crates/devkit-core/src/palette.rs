@@ -0,0 +1,124 @@
+//! Command palette
+//!
+//! Merges the menu items of every available extension — built-in actions, project
+//! `[cmd]` commands and actions discovered by external extensions — into one list.
+//! Every entry gets a category and a stable id used for pinning and history:
+//!
+//! - Items an extension leaves ungrouped are categorised by the extension's name.
+//! - Ids are `<category>/<label>` slugs, e.g. `docker/up` or `commands/build`.
+//! - The same action offered twice is listed once: entries with a repeated id are
+//!   dropped, and so are ungrouped items whose label matches a grouped one (a `[cmd]
+//!   up` next to docker's "Up").
+
+use crate::{AppContext, ExtensionRegistry, MenuItem};
+use std::collections::HashSet;
+
+/// One action in the palette
+pub struct PaletteEntry {
+    /// Stable id, e.g. `docker/up`
+    pub id: String,
+    /// Group shown in the menu (with emoji)
+    pub category: String,
+    pub item: MenuItem,
+}
+
+impl PaletteEntry {
+    /// "Category / Label", as shown in the filterable menu
+    pub fn display(&self) -> String {
+        format!("{} / {}", self.category, self.item.label)
+    }
+}
+
+/// Palette entries of all available extensions, in registration order
+pub fn palette(registry: &ExtensionRegistry, ctx: &AppContext) -> Vec<PaletteEntry> {
+    let items: Vec<(String, MenuItem)> = registry
+        .available_extensions(ctx)
+        .into_iter()
+        .flat_map(|ext| {
+            let name = ext.name().to_string();
+            ext.menu_items(ctx)
+                .into_iter()
+                .map(move |item| (name.clone(), item))
+        })
+        .collect();
+    merge(items)
+}
+
+/// Build entries from `(extension name, item)` pairs, dropping duplicates
+fn merge(items: Vec<(String, MenuItem)>) -> Vec<PaletteEntry> {
+    let grouped_labels: HashSet<String> = items
+        .iter()
+        .filter(|(_, item)| item.group.is_some())
+        .map(|(_, item)| slug(&item.label))
+        .collect();
+
+    let mut seen = HashSet::new();
+    let mut entries = Vec::new();
+    for (extension, item) in items {
+        if item.group.is_none() && grouped_labels.contains(&slug(&item.label)) {
+            continue;
+        }
+        let category = item.group.clone().unwrap_or_else(|| capitalize(&extension));
+        let id = format!("{}/{}", slug(&category), slug(&item.label));
+        if seen.insert(id.clone()) {
+            entries.push(PaletteEntry { id, category, item });
+        }
+    }
+    entries
+}
+
+/// Lowercase words joined by `-`; emoji and punctuation are dropped
+fn slug(text: &str) -> String {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().collect::<String>() + chars.as_str(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(group: Option<&str>, label: &str) -> MenuItem {
+        MenuItem {
+            label: label.to_string(),
+            group: group.map(str::to_string),
+            handler: Box::new(|_| Ok(())),
+        }
+    }
+
+    #[test]
+    fn test_palette_ids_and_categories() {
+        let entries = merge(vec![
+            (
+                "docker".to_string(),
+                item(Some("🐳 Docker"), "▶️  Start all"),
+            ),
+            ("commands".to_string(), item(None, "🔨 Build (release)")),
+        ]);
+        let ids: Vec<&str> = entries.iter().map(|e| e.id.as_str()).collect();
+        assert_eq!(ids, ["docker/start-all", "commands/build-release"]);
+        assert_eq!(entries[1].display(), "Commands / 🔨 Build (release)");
+    }
+
+    #[test]
+    fn test_palette_deduplicates() {
+        let entries = merge(vec![
+            ("docker".to_string(), item(Some("🐳 Docker"), "Up")),
+            ("commands".to_string(), item(None, "▶️ Up")),
+            ("dyn".to_string(), item(Some("🐳 Docker"), "Up")),
+            ("commands".to_string(), item(None, "🧪 Test")),
+        ]);
+        let ids: Vec<&str> = entries.iter().map(|e| e.id.as_str()).collect();
+        assert_eq!(ids, ["docker/up", "commands/test"]);
+    }
+}
@@ -0,0 +1,293 @@
+//! Listening ports for `devkit ports`
+//!
+//! Lists the ports a project cares about — `[services]` entries, ports the compose
+//! file publishes and common dev server ports — with the process listening on each.
+//! Owners come from `ss` on Linux and `lsof` elsewhere; processes belonging to
+//! another user may show up without a pid.
+//!
+//! A port conflicts when the compose file publishes it but something other than
+//! Docker holds it, so `docker compose up` would fail with "address already in use".
+
+use crate::oplog::LoggedCommand;
+use crate::utils::cmd_exists;
+use crate::validation::compose_services;
+use crate::Config;
+use anyhow::{bail, Context, Result};
+use std::collections::BTreeMap;
+use std::process::Command;
+
+/// Ports dev servers and databases usually listen on
+const COMMON_PORTS: &[(u16, &str)] = &[
+    (3000, "dev server"),
+    (3306, "mysql"),
+    (4000, "phoenix"),
+    (5000, "dev server"),
+    (5173, "vite"),
+    (5432, "postgres"),
+    (6379, "redis"),
+    (8000, "django"),
+    (8080, "http"),
+    (9229, "node inspector"),
+    (27017, "mongodb"),
+];
+
+/// Process names that mean Docker is publishing the port
+const DOCKER_PROCESSES: &[&str] = &["docker-proxy", "com.docker.backend", "vpnkit", "docker"];
+
+/// A process listening on a TCP port
+#[derive(Debug, Clone, PartialEq)]
+pub struct Listener {
+    pub port: u16,
+    pub pid: Option<u32>,
+    pub process: Option<String>,
+}
+
+impl Listener {
+    fn is_docker(&self) -> bool {
+        self.process
+            .as_deref()
+            .is_some_and(|process| DOCKER_PROCESSES.contains(&process))
+    }
+
+    /// "node (pid 123)", as shown in listings
+    pub fn owner(&self) -> String {
+        match (&self.process, self.pid) {
+            (Some(process), Some(pid)) => format!("{} (pid {})", process, pid),
+            (Some(process), None) => process.clone(),
+            (None, Some(pid)) => format!("pid {}", pid),
+            (None, None) => "unknown process".to_string(),
+        }
+    }
+}
+
+/// A port the project uses
+#[derive(Debug)]
+pub struct PortStatus {
+    pub port: u16,
+    /// `[services]` or compose service names, or what the port is commonly used for
+    pub name: String,
+    pub listener: Option<Listener>,
+    /// Published by the compose file but held by something other than Docker
+    pub conflict: bool,
+}
+
+/// Every listening TCP port on this machine
+pub fn listeners() -> Result<Vec<Listener>> {
+    if cfg!(target_os = "linux") && cmd_exists("ss") {
+        let output = Command::new("ss")
+            .args(["-ltnpH"])
+            .logged_output()
+            .context("Failed to run ss")?;
+        return Ok(parse_ss(&String::from_utf8_lossy(&output.stdout)));
+    }
+    if cmd_exists("lsof") {
+        let output = Command::new("lsof")
+            .args(["-nP", "-iTCP", "-sTCP:LISTEN", "-Fpcn"])
+            .logged_output()
+            .context("Failed to run lsof")?;
+        return Ok(parse_lsof(&String::from_utf8_lossy(&output.stdout)));
+    }
+    bail!("Listing ports needs `ss` or `lsof`")
+}
+
+/// The project's ports: configured and compose-published ones always, common dev ports
+/// when something listens on them
+pub fn project_ports(config: &Config, listeners: &[Listener]) -> Vec<PortStatus> {
+    let mut names: BTreeMap<u16, Vec<String>> = BTreeMap::new();
+    for (service, port) in &config.global.services.ports {
+        names.entry(*port).or_default().push(service.clone());
+    }
+
+    let compose = compose_services(&config.repo_root)
+        .map(|(_, services)| services)
+        .unwrap_or_default();
+    let mut published = Vec::new();
+    for (service, ports) in &compose {
+        for port in ports {
+            published.push(*port);
+            let names = names.entry(*port).or_default();
+            if !names.contains(service) {
+                names.push(service.clone());
+            }
+        }
+    }
+
+    for (port, usage) in COMMON_PORTS {
+        if !names.contains_key(port) && listeners.iter().any(|l| l.port == *port) {
+            names.insert(*port, vec![usage.to_string()]);
+        }
+    }
+
+    names
+        .into_iter()
+        .map(|(port, mut names)| {
+            names.sort();
+            let listener = listeners.iter().find(|l| l.port == port).cloned();
+            let conflict =
+                published.contains(&port) && listener.as_ref().is_some_and(|l| !l.is_docker());
+            PortStatus {
+                port,
+                name: names.join(", "),
+                listener,
+                conflict,
+            }
+        })
+        .collect()
+}
+
+/// Stop a process (SIGTERM)
+pub fn kill(pid: u32) -> Result<()> {
+    let status = if cfg!(windows) {
+        Command::new("taskkill")
+            .args(["/PID", &pid.to_string()])
+            .logged_status()
+    } else {
+        Command::new("kill").arg(pid.to_string()).logged_status()
+    }
+    .context("Failed to run kill")?;
+    if !status.success() {
+        bail!("Could not stop pid {} (is it owned by another user?)", pid);
+    }
+    Ok(())
+}
+
+/// Port at the end of an address such as `0.0.0.0:8080`, `[::1]:3000` or `*:5432`
+fn address_port(address: &str) -> Option<u16> {
+    address.rsplit(':').next()?.parse().ok()
+}
+
+/// `ss -ltnpH` output
+fn parse_ss(output: &str) -> Vec<Listener> {
+    let mut listeners: Vec<Listener> = Vec::new();
+    for line in output.lines() {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let Some(port) = fields.get(3).and_then(|address| address_port(address)) else {
+            continue;
+        };
+        // users:(("node",pid=123,fd=20))
+        let users = fields.get(5).copied().unwrap_or_default();
+        let process = users.split('"').nth(1).map(str::to_string);
+        let pid = users
+            .split("pid=")
+            .nth(1)
+            .and_then(|rest| rest.split([',', ')']).next())
+            .and_then(|pid| pid.parse().ok());
+        // IPv4 and IPv6 sockets of one server show up twice
+        if !listeners.iter().any(|l| l.port == port && l.pid == pid) {
+            listeners.push(Listener { port, pid, process });
+        }
+    }
+    listeners
+}
+
+/// `lsof -Fpcn` output: `p<pid>` and `c<command>` lines followed by `n<address>` lines
+fn parse_lsof(output: &str) -> Vec<Listener> {
+    let mut listeners: Vec<Listener> = Vec::new();
+    let mut pid = None;
+    let mut process = None;
+    for line in output.lines() {
+        let Some(field) = line.chars().next() else {
+            continue;
+        };
+        let value = &line[field.len_utf8()..];
+        match field {
+            'p' => {
+                pid = value.parse().ok();
+                process = None;
+            }
+            'c' => process = Some(value.to_string()),
+            'n' => {
+                if let Some(port) = address_port(value) {
+                    if !listeners.iter().any(|l| l.port == port && l.pid == pid) {
+                        listeners.push(Listener {
+                            port,
+                            pid,
+                            process: process.clone(),
+                        });
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    listeners
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ss() {
+        let output = "\
+LISTEN 0      511          0.0.0.0:5173      0.0.0.0:*    users:((\"node\",pid=4242,fd=21))
+LISTEN 0      511             [::]:5173         [::]:*    users:((\"node\",pid=4242,fd=22))
+LISTEN 0      128          0.0.0.0:22        0.0.0.0:*
+";
+        assert_eq!(
+            parse_ss(output),
+            [
+                Listener {
+                    port: 5173,
+                    pid: Some(4242),
+                    process: Some("node".to_string()),
+                },
+                Listener {
+                    port: 22,
+                    pid: None,
+                    process: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_lsof() {
+        let output = "p812\ncpostgres\nn127.0.0.1:5432\nn[::1]:5432\np901\ncruby\nn*:3000\n";
+        let listeners = parse_lsof(output);
+        assert_eq!(listeners.len(), 2);
+        assert_eq!(listeners[0].owner(), "postgres (pid 812)");
+        assert_eq!(listeners[1].port, 3000);
+    }
+
+    #[test]
+    fn test_project_ports_conflicts() {
+        let repo = std::env::temp_dir().join(format!("devkit-ports-{}", std::process::id()));
+        std::fs::create_dir_all(&repo).unwrap();
+        std::fs::write(
+            repo.join("docker-compose.yml"),
+            "services:\n  db:\n    ports: [\"5432:5432\"]\n  cache:\n    ports: [\"6379:6379\"]\n",
+        )
+        .unwrap();
+        let mut config = Config::load(&repo).unwrap();
+        config.global.services.ports.insert("api".to_string(), 8080);
+
+        let listener = |port, process: &str| Listener {
+            port,
+            pid: Some(1),
+            process: Some(process.to_string()),
+        };
+        let listeners = [
+            listener(5432, "postgres"),
+            listener(6379, "docker-proxy"),
+            listener(5173, "node"),
+        ];
+
+        let ports = project_ports(&config, &listeners);
+        let summary: Vec<(u16, &str, bool)> = ports
+            .iter()
+            .map(|p| (p.port, p.name.as_str(), p.conflict))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (5173, "vite", false),
+                (5432, "db", true),
+                (6379, "cache", false),
+                (8080, "api", false),
+            ]
+        );
+
+        std::fs::remove_dir_all(repo).ok();
+    }
+}
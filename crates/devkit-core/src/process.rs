@@ -0,0 +1,82 @@
+//! Background process helpers
+//!
+//! Processes that outlive a devkit run — services, forwards and tunnels — are recorded
+//! by pid in state files under `.dev/run`. Their process is the leader of its own
+//! process group (see `CmdBuilder::spawn_background`), so stopping it stops everything
+//! it started.
+
+#[cfg(windows)]
+use anyhow::Context;
+use anyhow::{bail, Result};
+#[cfg(windows)]
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Per-checkout state about running processes, and their logs
+pub const RUN_DIR: &str = ".dev/run";
+
+/// Whether a process with this pid is running
+#[cfg(unix)]
+pub fn process_alive(pid: u32) -> bool {
+    // Signal 0 only checks that the process exists
+    unsafe { libc::kill(pid as libc::pid_t, 0) == 0 }
+}
+
+#[cfg(windows)]
+pub fn process_alive(pid: u32) -> bool {
+    Command::new("tasklist")
+        .args(["/FI", &format!("PID eq {}", pid), "/NH"])
+        .output()
+        .is_ok_and(|output| String::from_utf8_lossy(&output.stdout).contains(&pid.to_string()))
+}
+
+/// SIGTERM the process group `pid` leads
+#[cfg(unix)]
+pub fn terminate(pid: u32) -> Result<()> {
+    if unsafe { libc::kill(-(pid as libc::pid_t), libc::SIGTERM) } != 0 {
+        bail!(
+            "Failed to stop process {}: {}",
+            pid,
+            std::io::Error::last_os_error()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(windows)]
+pub fn terminate(pid: u32) -> Result<()> {
+    let output = Command::new("taskkill")
+        .args(["/PID", &pid.to_string(), "/T", "/F"])
+        .output()
+        .with_context(|| format!("Failed to stop process {}", pid))?;
+    if !output.status.success() {
+        bail!("Failed to stop process {}", pid);
+    }
+    Ok(())
+}
+
+/// Seconds since the Unix epoch
+pub fn current_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(unix)]
+    fn test_process_alive() {
+        assert!(process_alive(std::process::id()));
+        let mut child = std::process::Command::new("sh")
+            .args(["-c", "exit 0"])
+            .spawn()
+            .unwrap();
+        let pid = child.id();
+        child.wait().unwrap();
+        assert!(!process_alive(pid));
+    }
+}
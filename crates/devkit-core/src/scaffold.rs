@@ -0,0 +1,475 @@
+//! Package scaffolding for `devkit new package`
+//!
+//! Creates a workspace package from a template and registers it so `devkit cmd`
+//! picks it up straight away:
+//!
+//! - Built-in templates: `rust-lib`, `rust-bin`, `ts-lib` and `next-app`
+//! - Project templates: every directory in `.dev/templates/`, copied as-is with
+//!   `{{name}}` replaced by the package name (in file names too)
+//!
+//! The package goes in the directory of the first `[workspaces] packages` glob (e.g.
+//! `packages/*`) unless another parent is given. If no glob matches it, one is added;
+//! Rust packages are also added to the Cargo workspace members.
+
+use crate::frameworks::Framework;
+use anyhow::{bail, Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml_edit::{Array, DocumentMut, Item, Table};
+
+const RUST_DEV_TOML: &str = include_str!("../../../templates/dev.toml.rust");
+const NODE_DEV_TOML: &str = include_str!("../../../templates/dev.toml.node");
+
+/// Built-in templates with a one-line description
+pub const BUILTIN_TEMPLATES: &[(&str, &str)] = &[
+    ("rust-lib", "Rust library crate"),
+    ("rust-bin", "Rust binary crate"),
+    ("ts-lib", "TypeScript library"),
+    ("next-app", "Next.js app"),
+];
+
+/// Project templates directory, relative to the repo root
+const PROJECT_TEMPLATES_DIR: &str = ".dev/templates";
+
+/// What `new_package` did
+#[derive(Debug)]
+pub struct Scaffolded {
+    /// Package directory, relative to the repo root
+    pub path: PathBuf,
+    pub files: usize,
+    /// Config changes made so the package is discovered
+    pub registered: Vec<String>,
+}
+
+/// Template names: built-in ones, then the project's
+pub fn templates(repo: &Path) -> Vec<String> {
+    let mut names: Vec<String> = BUILTIN_TEMPLATES
+        .iter()
+        .map(|(name, _)| name.to_string())
+        .collect();
+    let mut project: Vec<String> = fs::read_dir(repo.join(PROJECT_TEMPLATES_DIR))
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|entry| entry.path().is_dir())
+                .map(|entry| entry.file_name().to_string_lossy().to_string())
+                .collect()
+        })
+        .unwrap_or_default();
+    project.sort();
+    names.extend(project);
+    names
+}
+
+/// Create package `name` from `template` under `parent` (or the first workspace glob's
+/// directory)
+pub fn new_package(
+    repo: &Path,
+    patterns: &[String],
+    name: &str,
+    template: &str,
+    parent: Option<&str>,
+) -> Result<Scaffolded> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+    {
+        bail!(
+            "Invalid package name '{}': use lowercase letters, digits, - and _",
+            name
+        );
+    }
+
+    let parent = match parent {
+        Some(parent) => parent.trim_end_matches('/').to_string(),
+        None => patterns
+            .iter()
+            .find_map(|pattern| pattern.strip_suffix("/*"))
+            .unwrap_or("packages")
+            .to_string(),
+    };
+    let path = Path::new(&parent).join(name);
+    let dir = repo.join(&path);
+    if dir.exists() {
+        bail!("{} already exists", path.display());
+    }
+
+    let files = match template {
+        "rust-lib" | "rust-bin" => rust_files(name, template == "rust-bin"),
+        "ts-lib" => ts_lib_files(name),
+        "next-app" => next_app_files(name),
+        _ => {
+            let template_dir = repo.join(PROJECT_TEMPLATES_DIR).join(template);
+            if !template_dir.is_dir() {
+                bail!(
+                    "Unknown template '{}'\nAvailable templates: {}",
+                    template,
+                    templates(repo).join(", ")
+                );
+            }
+            project_template_files(&template_dir, name)?
+        }
+    };
+
+    for (file, contents) in &files {
+        let target = dir.join(file);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&target, contents)
+            .with_context(|| format!("Failed to write {}", target.display()))?;
+    }
+    if template == "next-app" {
+        // Commands depend on the lockfile, so generate them once package.json exists
+        fs::write(dir.join("dev.toml"), Framework::NextJs.commands(&dir))?;
+    }
+
+    let mut registered = Vec::new();
+    if let Some(pattern) = register_workspace_pattern(repo, patterns, &path, &parent)? {
+        registered.push(format!(
+            "Added \"{}\" to [workspaces] packages in .dev/config.toml",
+            pattern
+        ));
+    }
+    if dir.join("Cargo.toml").exists() && register_cargo_member(repo, &path)? {
+        registered.push(format!(
+            "Added \"{}\" to [workspace] members in Cargo.toml",
+            path.display()
+        ));
+    }
+
+    Ok(Scaffolded {
+        path,
+        files: files.len() + usize::from(template == "next-app"),
+        registered,
+    })
+}
+
+fn rust_files(name: &str, binary: bool) -> Vec<(PathBuf, String)> {
+    let source = if binary {
+        (
+            "src/main.rs",
+            "fn main() {\n    println!(\"Hello from {{name}}!\");\n}\n",
+        )
+    } else {
+        (
+            "src/lib.rs",
+            r#"pub fn hello() -> &'static str {
+    "Hello from {{name}}!"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hello() {
+        assert!(hello().contains("{{name}}"));
+    }
+}
+"#,
+        )
+    };
+    render(
+        name,
+        &[
+            (
+                "Cargo.toml",
+                "[package]\nname = \"{{name}}\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[dependencies]\n",
+            ),
+            source,
+            ("dev.toml", RUST_DEV_TOML),
+        ],
+    )
+}
+
+fn ts_lib_files(name: &str) -> Vec<(PathBuf, String)> {
+    render(
+        name,
+        &[
+            (
+                "package.json",
+                r#"{
+  "name": "{{name}}",
+  "version": "0.1.0",
+  "private": true,
+  "main": "dist/index.js",
+  "types": "dist/index.d.ts",
+  "scripts": {
+    "build": "tsc",
+    "test": "jest"
+  },
+  "jest": {
+    "preset": "ts-jest"
+  },
+  "devDependencies": {
+    "@types/jest": "^29.5.0",
+    "jest": "^29.7.0",
+    "ts-jest": "^29.1.0",
+    "typescript": "^5.4.0"
+  }
+}
+"#,
+            ),
+            (
+                "tsconfig.json",
+                r#"{
+  "compilerOptions": {
+    "target": "ES2020",
+    "module": "commonjs",
+    "declaration": true,
+    "outDir": "dist",
+    "rootDir": "src",
+    "strict": true,
+    "esModuleInterop": true,
+    "skipLibCheck": true
+  },
+  "include": ["src"],
+  "exclude": ["src/**/*.test.ts"]
+}
+"#,
+            ),
+            (
+                "src/index.ts",
+                "export function hello(): string {\n  return \"Hello from {{name}}!\";\n}\n",
+            ),
+            (
+                "src/index.test.ts",
+                "import { hello } from \"./index\";\n\ntest(\"hello\", () => {\n  expect(hello()).toContain(\"{{name}}\");\n});\n",
+            ),
+            ("dev.toml", NODE_DEV_TOML),
+        ],
+    )
+}
+
+fn next_app_files(name: &str) -> Vec<(PathBuf, String)> {
+    render(
+        name,
+        &[
+            (
+                "package.json",
+                r#"{
+  "name": "{{name}}",
+  "version": "0.1.0",
+  "private": true,
+  "scripts": {
+    "dev": "next dev",
+    "build": "next build",
+    "start": "next start",
+    "lint": "next lint"
+  },
+  "dependencies": {
+    "next": "^14.2.0",
+    "react": "^18.3.0",
+    "react-dom": "^18.3.0"
+  },
+  "devDependencies": {
+    "@types/node": "^20.0.0",
+    "@types/react": "^18.3.0",
+    "@types/react-dom": "^18.3.0",
+    "typescript": "^5.4.0"
+  }
+}
+"#,
+            ),
+            (
+                "tsconfig.json",
+                r#"{
+  "compilerOptions": {
+    "target": "ES2017",
+    "lib": ["dom", "dom.iterable", "esnext"],
+    "allowJs": true,
+    "skipLibCheck": true,
+    "strict": true,
+    "noEmit": true,
+    "esModuleInterop": true,
+    "module": "esnext",
+    "moduleResolution": "bundler",
+    "resolveJsonModule": true,
+    "isolatedModules": true,
+    "jsx": "preserve",
+    "incremental": true,
+    "plugins": [{ "name": "next" }]
+  },
+  "include": ["next-env.d.ts", "**/*.ts", "**/*.tsx", ".next/types/**/*.ts"],
+  "exclude": ["node_modules"]
+}
+"#,
+            ),
+            ("next.config.mjs", "/** @type {import('next').NextConfig} */\nconst nextConfig = {};\n\nexport default nextConfig;\n"),
+            (
+                "app/layout.tsx",
+                r#"export const metadata = { title: "{{name}}" };
+
+export default function RootLayout({ children }: { children: React.ReactNode }) {
+  return (
+    <html lang="en">
+      <body>{children}</body>
+    </html>
+  );
+}
+"#,
+            ),
+            (
+                "app/page.tsx",
+                "export default function Home() {\n  return <h1>{{name}}</h1>;\n}\n",
+            ),
+        ],
+    )
+}
+
+/// Files of a `.dev/templates/<name>` directory
+fn project_template_files(template_dir: &Path, name: &str) -> Result<Vec<(PathBuf, String)>> {
+    let mut files = Vec::new();
+    let pattern = format!("{}/**/*", template_dir.display());
+    for entry in glob::glob(&pattern)?.flatten() {
+        if !entry.is_file() {
+            continue;
+        }
+        let relative = entry.strip_prefix(template_dir)?.to_string_lossy();
+        let contents = fs::read_to_string(&entry)
+            .with_context(|| format!("Failed to read template file {}", entry.display()))?;
+        files.push((
+            PathBuf::from(relative.replace("{{name}}", name)),
+            contents.replace("{{name}}", name),
+        ));
+    }
+    Ok(files)
+}
+
+fn render(name: &str, files: &[(&str, &str)]) -> Vec<(PathBuf, String)> {
+    files
+        .iter()
+        .map(|(file, contents)| (PathBuf::from(file), contents.replace("{{name}}", name)))
+        .collect()
+}
+
+/// Add `<parent>/*` to `[workspaces] packages` unless a glob already matches `path`
+fn register_workspace_pattern(
+    repo: &Path,
+    patterns: &[String],
+    path: &Path,
+    parent: &str,
+) -> Result<Option<String>> {
+    let matched = patterns
+        .iter()
+        .any(|pattern| glob::Pattern::new(pattern).is_ok_and(|pattern| pattern.matches_path(path)));
+    if matched {
+        return Ok(None);
+    }
+
+    let config_path = repo.join(".dev/config.toml");
+    let mut doc: DocumentMut = fs::read_to_string(&config_path)
+        .unwrap_or_default()
+        .parse()
+        .with_context(|| format!("Invalid TOML in {}", config_path.display()))?;
+    if !doc.contains_key("workspaces") {
+        doc.insert("workspaces", Item::Table(Table::new()));
+    }
+    let pattern = format!("{}/*", parent);
+    let workspaces = &mut doc["workspaces"];
+    if workspaces.get("packages").is_none() {
+        // The defaults apply while the key is missing, so keep them
+        workspaces["packages"] =
+            toml_edit::value(Array::from_iter(patterns.iter().map(String::as_str)));
+    }
+    if let Some(packages) = workspaces["packages"].as_array_mut() {
+        packages.push(pattern.as_str());
+    }
+
+    fs::create_dir_all(repo.join(".dev"))?;
+    fs::write(&config_path, doc.to_string())
+        .with_context(|| format!("Failed to write {}", config_path.display()))?;
+    Ok(Some(pattern))
+}
+
+/// Add `path` to the root Cargo.toml's `[workspace] members` unless a member glob
+/// covers it; returns whether it was added
+fn register_cargo_member(repo: &Path, path: &Path) -> Result<bool> {
+    let manifest = repo.join("Cargo.toml");
+    let Ok(contents) = fs::read_to_string(&manifest) else {
+        return Ok(false);
+    };
+    let mut doc: DocumentMut = contents
+        .parse()
+        .with_context(|| format!("Invalid TOML in {}", manifest.display()))?;
+    let Some(members) = doc
+        .get_mut("workspace")
+        .and_then(|workspace| workspace.get_mut("members"))
+        .and_then(Item::as_array_mut)
+    else {
+        return Ok(false);
+    };
+
+    let covered = members
+        .iter()
+        .filter_map(|member| member.as_str())
+        .any(|member| glob::Pattern::new(member).is_ok_and(|pattern| pattern.matches_path(path)));
+    if covered {
+        return Ok(false);
+    }
+    members.push(path.to_string_lossy().as_ref());
+    fs::write(&manifest, doc.to_string())
+        .with_context(|| format!("Failed to write {}", manifest.display()))?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_repo(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("devkit-scaffold-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_new_rust_package_is_registered() {
+        let repo = temp_repo("rust");
+        fs::write(
+            repo.join("Cargo.toml"),
+            "[workspace]\nmembers = [\"crates/*\"]\n",
+        )
+        .unwrap();
+
+        let patterns = vec!["packages/*".to_string()];
+        let scaffolded = new_package(&repo, &patterns, "core", "rust-lib", Some("libs")).unwrap();
+        assert_eq!(scaffolded.path, Path::new("libs/core"));
+        assert_eq!(scaffolded.registered.len(), 2);
+
+        let config = crate::Config::load(&repo).unwrap();
+        assert!(config.packages["core"].cmd.contains_key("test"));
+        let cargo = fs::read_to_string(repo.join("Cargo.toml")).unwrap();
+        assert!(cargo.contains(r#"members = ["crates/*", "libs/core"]"#));
+
+        assert!(new_package(&repo, &patterns, "core", "rust-lib", Some("libs")).is_err());
+        fs::remove_dir_all(repo).ok();
+    }
+
+    #[test]
+    fn test_project_template() {
+        let repo = temp_repo("project");
+        let template = repo.join(".dev/templates/worker/src");
+        fs::create_dir_all(&template).unwrap();
+        fs::write(template.join("{{name}}.py"), "print('{{name}}')\n").unwrap();
+        assert!(templates(&repo).contains(&"worker".to_string()));
+
+        let patterns = vec!["packages/*".to_string()];
+        let scaffolded = new_package(&repo, &patterns, "mailer", "worker", None).unwrap();
+        assert!(scaffolded.registered.is_empty());
+        assert_eq!(
+            fs::read_to_string(repo.join("packages/mailer/src/mailer.py")).unwrap(),
+            "print('mailer')\n"
+        );
+
+        assert!(new_package(&repo, &patterns, "x", "nope", None)
+            .unwrap_err()
+            .to_string()
+            .contains("rust-lib, rust-bin, ts-lib, next-app, worker"));
+        fs::remove_dir_all(repo).ok();
+    }
+}
@@ -0,0 +1,104 @@
+//! Git and compose state shared by `devkit status` and the local API
+
+use crate::config::GlobalConfig;
+use crate::oplog::LoggedCommand;
+use crate::utils::{docker_available, docker_compose_program};
+use crate::validation::compose_files;
+use serde::Serialize;
+use std::path::Path;
+use std::process::Command;
+
+/// Branch and working tree state
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct GitStatus {
+    /// Current branch (`None` when detached)
+    pub branch: Option<String>,
+    /// Commits not pushed to the upstream
+    pub ahead: u32,
+    /// Upstream commits not pulled
+    pub behind: u32,
+    /// Changed and untracked files
+    pub changed: usize,
+}
+
+/// Git state of the repo, `None` outside a git checkout
+pub fn git_status(repo_root: &Path) -> Option<GitStatus> {
+    let output = Command::new("git")
+        .args(["status", "--porcelain=v2", "--branch"])
+        .current_dir(repo_root)
+        .logged_output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| parse_git_status(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// From `git status --porcelain=v2 --branch`
+fn parse_git_status(output: &str) -> GitStatus {
+    let mut status = GitStatus::default();
+    for line in output.lines() {
+        if let Some(head) = line.strip_prefix("# branch.head ") {
+            status.branch = (head != "(detached)").then(|| head.to_string());
+        } else if let Some(counts) = line.strip_prefix("# branch.ab ") {
+            for count in counts.split_whitespace() {
+                if let Some(n) = count.strip_prefix('+') {
+                    status.ahead = n.parse().unwrap_or(0);
+                } else if let Some(n) = count.strip_prefix('-') {
+                    status.behind = n.parse().unwrap_or(0);
+                }
+            }
+        } else if !line.starts_with('#') && !line.is_empty() {
+            status.changed += 1;
+        }
+    }
+    status
+}
+
+/// Compose services that are running, `None` when docker isn't available
+///
+/// Uses the same compose files as `devkit docker up`.
+pub fn running_services(repo_root: &Path, config: &GlobalConfig) -> Option<Vec<String>> {
+    if !docker_available() {
+        return None;
+    }
+    let (program, mut args) = docker_compose_program().ok()?;
+    for file in compose_files(repo_root, config) {
+        args.extend(["-f".to_string(), file]);
+    }
+    args.extend(["ps", "--services", "--filter", "status=running"].map(String::from));
+    let output = Command::new(program)
+        .args(args)
+        .current_dir(repo_root)
+        .logged_output()
+        .ok()?;
+    output.status.success().then(|| {
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(str::to_string)
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_git_status() {
+        let output = "# branch.oid 1234abcd\n# branch.head feature/api\n# branch.upstream origin/feature/api\n# branch.ab +2 -1\n1 .M N... 100644 100644 100644 aaa bbb src/main.rs\n? notes.txt\n";
+        assert_eq!(
+            parse_git_status(output),
+            GitStatus {
+                branch: Some("feature/api".to_string()),
+                ahead: 2,
+                behind: 1,
+                changed: 2,
+            }
+        );
+
+        let detached = parse_git_status("# branch.oid 1234abcd\n# branch.head (detached)\n");
+        assert_eq!(detached.branch, None);
+        assert_eq!(detached.changed, 0);
+    }
+}
@@ -0,0 +1,310 @@
+//! Runtime versions pinned with mise or asdf
+//!
+//! Reads `.mise.toml`/`mise.toml` or `.tool-versions` at the repo root. Whether a
+//! version is installed is checked in the manager's installs directory rather than by
+//! running it, so the deps prerun hook can check on every command without slowing it
+//! down. Installing delegates to `mise install` or `asdf install`.
+
+use crate::oplog::LoggedCommand;
+use crate::utils::cmd_exists;
+use anyhow::{bail, Context, Result};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Toolchain files, in the order mise reads them
+const MISE_FILES: &[&str] = &[".mise.toml", "mise.toml"];
+const TOOL_VERSIONS: &str = ".tool-versions";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Manager {
+    Mise,
+    Asdf,
+}
+
+impl Manager {
+    pub fn binary(self) -> &'static str {
+        match self {
+            Manager::Mise => "mise",
+            Manager::Asdf => "asdf",
+        }
+    }
+
+    pub fn is_installed(self) -> bool {
+        cmd_exists(self.binary())
+    }
+
+    /// Where the manager keeps installed runtimes and shims
+    fn data_dir(self) -> Option<PathBuf> {
+        match self {
+            Manager::Mise => env::var_os("MISE_DATA_DIR")
+                .map(PathBuf::from)
+                .or_else(|| env::var_os("XDG_DATA_HOME").map(|dir| PathBuf::from(dir).join("mise")))
+                .or_else(|| dirs::home_dir().map(|home| home.join(".local/share/mise"))),
+            Manager::Asdf => env::var_os("ASDF_DATA_DIR")
+                .map(PathBuf::from)
+                .or_else(|| dirs::home_dir().map(|home| home.join(".asdf"))),
+        }
+    }
+}
+
+/// A runtime and the version the project pins
+#[derive(Debug, Clone, PartialEq)]
+pub struct ToolVersion {
+    pub name: String,
+    pub version: String,
+}
+
+/// Whether a pinned version is installed
+#[derive(Debug)]
+pub struct ToolStatus<'a> {
+    pub tool: &'a ToolVersion,
+    pub installed: bool,
+}
+
+/// The project's pinned runtimes
+#[derive(Debug)]
+pub struct Toolchain {
+    pub file: PathBuf,
+    pub manager: Manager,
+    pub tools: Vec<ToolVersion>,
+}
+
+impl Toolchain {
+    /// The repo's toolchain file, if it has one. `.tool-versions` is handled by mise
+    /// when it's installed, otherwise by asdf.
+    pub fn detect(repo: &Path) -> Result<Option<Self>> {
+        for name in MISE_FILES {
+            let file = repo.join(name);
+            if file.exists() {
+                let contents = fs::read_to_string(&file)?;
+                return Ok(Some(Self {
+                    tools: parse_mise_toml(&contents)
+                        .with_context(|| format!("Invalid {}", file.display()))?,
+                    file,
+                    manager: Manager::Mise,
+                }));
+            }
+        }
+
+        let file = repo.join(TOOL_VERSIONS);
+        if !file.exists() {
+            return Ok(None);
+        }
+        let contents = fs::read_to_string(&file)?;
+        let manager = if !Manager::Asdf.is_installed() || Manager::Mise.is_installed() {
+            Manager::Mise
+        } else {
+            Manager::Asdf
+        };
+        Ok(Some(Self {
+            tools: parse_tool_versions(&contents),
+            file,
+            manager,
+        }))
+    }
+
+    /// File name for messages, e.g. `.tool-versions`
+    pub fn file_name(&self) -> String {
+        self.file
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string()
+    }
+
+    /// Whether each pinned version is installed
+    pub fn status(&self) -> Vec<ToolStatus<'_>> {
+        let installs = self.manager.data_dir().map(|dir| dir.join("installs"));
+        self.tools
+            .iter()
+            .map(|tool| ToolStatus {
+                tool,
+                installed: installs
+                    .as_deref()
+                    .is_some_and(|installs| is_installed(installs, tool)),
+            })
+            .collect()
+    }
+
+    /// Whether the manager's shims or shell hook are active, so commands pick up the
+    /// pinned versions
+    pub fn is_activated(&self) -> bool {
+        if self.manager == Manager::Mise && env::var_os("MISE_SHELL").is_some() {
+            return true;
+        }
+        let Some(shims) = self.manager.data_dir().map(|dir| dir.join("shims")) else {
+            return false;
+        };
+        env::var_os("PATH").is_some_and(|path| env::split_paths(&path).any(|dir| dir == shims))
+    }
+
+    /// Install every pinned version (adding missing asdf plugins first)
+    pub fn install(&self, repo: &Path) -> Result<()> {
+        let manager = self.manager;
+        if !manager.is_installed() {
+            bail!(
+                "{} pins runtime versions but {} isn't installed (https://mise.jdx.dev)",
+                self.file_name(),
+                manager.binary()
+            );
+        }
+
+        if manager == Manager::Asdf {
+            let plugins = manager.data_dir().map(|dir| dir.join("plugins"));
+            for tool in &self.tools {
+                if plugins
+                    .as_ref()
+                    .is_some_and(|dir| dir.join(&tool.name).exists())
+                {
+                    continue;
+                }
+                let status = Command::new("asdf")
+                    .args(["plugin", "add", &tool.name])
+                    .current_dir(repo)
+                    .logged_status()
+                    .context("Failed to run asdf plugin add")?;
+                if !status.success() {
+                    bail!("asdf plugin add {} failed", tool.name);
+                }
+            }
+        }
+
+        let status = Command::new(manager.binary())
+            .arg("install")
+            .current_dir(repo)
+            .logged_status()
+            .with_context(|| format!("Failed to run {} install", manager.binary()))?;
+        if !status.success() {
+            bail!("{} install failed", manager.binary());
+        }
+        Ok(())
+    }
+}
+
+/// `.tool-versions`: `<tool> <version> [fallback versions...]` per line
+fn parse_tool_versions(contents: &str) -> Vec<ToolVersion> {
+    contents
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or_default())
+        .filter_map(|line| {
+            let mut words = line.split_whitespace();
+            Some(ToolVersion {
+                name: words.next()?.to_string(),
+                version: words.next()?.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// `[tools]` in mise.toml: `node = "20"`, `python = ["3.12", "3.11"]` or
+/// `go = { version = "1.22" }`
+fn parse_mise_toml(contents: &str) -> Result<Vec<ToolVersion>> {
+    let doc: toml::Table = contents.parse()?;
+    let Some(tools) = doc.get("tools").and_then(|tools| tools.as_table()) else {
+        return Ok(Vec::new());
+    };
+
+    let mut pinned: Vec<ToolVersion> = tools
+        .iter()
+        .filter_map(|(name, value)| {
+            let version = match value {
+                toml::Value::String(version) => version.as_str(),
+                toml::Value::Array(versions) => versions.first()?.as_str()?,
+                toml::Value::Table(options) => options.get("version")?.as_str()?,
+                _ => return None,
+            };
+            Some(ToolVersion {
+                name: name.clone(),
+                version: version.to_string(),
+            })
+        })
+        .collect();
+    pinned.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(pinned)
+}
+
+/// Whether `installs/<tool>/` holds a version satisfying the pinned one. mise accepts
+/// prefixes (`20` matches `20.11.1`); `latest`, `lts` and `system` match anything.
+fn is_installed(installs: &Path, tool: &ToolVersion) -> bool {
+    let version = tool.version.trim_start_matches("prefix:");
+    if version == "system" || version.starts_with("ref:") || version.starts_with("path:") {
+        return true;
+    }
+
+    // Backend tools such as `npm:prettier` are installed under `npm-prettier`
+    let dir = installs.join(tool.name.replace([':', '/'], "-"));
+    let Ok(entries) = fs::read_dir(dir) else {
+        return false;
+    };
+    entries.flatten().any(|entry| {
+        let installed = entry.file_name().to_string_lossy().to_string();
+        version == "latest"
+            || version == "lts"
+            || installed == version
+            || installed.starts_with(&format!("{}.", version))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tool(name: &str, version: &str) -> ToolVersion {
+        ToolVersion {
+            name: name.to_string(),
+            version: version.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parse_tool_versions() {
+        let contents = "# runtimes\nnodejs 20.11.1 19.0.0\npython 3.12.2 # latest\n\n";
+        assert_eq!(
+            parse_tool_versions(contents),
+            [tool("nodejs", "20.11.1"), tool("python", "3.12.2")]
+        );
+    }
+
+    #[test]
+    fn test_parse_mise_toml() {
+        let contents = r#"
+[env]
+NODE_ENV = "development"
+
+[tools]
+node = "20"
+python = ["3.12", "3.11"]
+go = { version = "1.22", os = ["linux"] }
+"npm:prettier" = "latest"
+"#;
+        assert_eq!(
+            parse_mise_toml(contents).unwrap(),
+            [
+                tool("go", "1.22"),
+                tool("node", "20"),
+                tool("npm:prettier", "latest"),
+                tool("python", "3.12"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_is_installed() {
+        let installs = env::temp_dir().join(format!("devkit-toolchain-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&installs);
+        fs::create_dir_all(installs.join("node/20.11.1")).unwrap();
+        fs::create_dir_all(installs.join("npm-prettier/3.2.5")).unwrap();
+
+        assert!(is_installed(&installs, &tool("node", "20")));
+        assert!(is_installed(&installs, &tool("node", "20.11.1")));
+        assert!(!is_installed(&installs, &tool("node", "2")));
+        assert!(!is_installed(&installs, &tool("node", "18")));
+        assert!(is_installed(&installs, &tool("npm:prettier", "latest")));
+        assert!(!is_installed(&installs, &tool("python", "latest")));
+        assert!(is_installed(&installs, &tool("python", "system")));
+
+        fs::remove_dir_all(installs).ok();
+    }
+}
@@ -7,7 +7,9 @@ use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::time::Duration;
+
+use crate::process::current_timestamp;
 
 const GITHUB_REPO: &str = "crcn/devkit";
 const CACHE_FILE: &str = "update_check.json";
@@ -85,13 +87,6 @@ fn current_version() -> &'static str {
     env!("CARGO_PKG_VERSION")
 }
 
-fn current_timestamp() -> u64 {
-    SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap_or(Duration::from_secs(0))
-        .as_secs()
-}
-
 fn get_cache_path() -> Result<PathBuf> {
     let cache_dir =
         dirs::cache_dir().ok_or_else(|| anyhow::anyhow!("Failed to get cache directory"))?;
@@ -1,9 +1,11 @@
 //! Shared utility functions
 
 use crate::error::{DevkitError, Result};
+use crate::oplog::{LoggedCommand, Operation};
 use anyhow::Context;
 use std::env;
-use std::path::PathBuf;
+use std::fs;
+use std::path::{Path, PathBuf};
 use which::which;
 
 /// Get the repository root path from REPO_ROOT env var or infer from CARGO_MANIFEST_DIR
@@ -34,6 +36,12 @@ pub fn repo_root() -> Result<PathBuf> {
     Err(DevkitError::RepoRootNotFound)
 }
 
+/// Whether stdin and stdout are both terminals, so prompts can be shown
+pub fn is_terminal() -> bool {
+    use std::io::IsTerminal;
+    std::io::stdin().is_terminal() && std::io::stdout().is_terminal()
+}
+
 /// Check if a command exists in PATH
 pub fn cmd_exists(name: &str) -> bool {
     which(name).is_ok()
@@ -72,26 +80,100 @@ pub fn open_in_browser(url: &str) -> Result<()> {
     {
         std::process::Command::new("open")
             .arg(url)
-            .spawn()
+            .logged_spawn()
             .with_context(|| format!("failed to open {url} in browser"))?;
     }
     #[cfg(target_os = "linux")]
     {
         std::process::Command::new("xdg-open")
             .arg(url)
-            .spawn()
+            .logged_spawn()
             .with_context(|| format!("failed to open {url} in browser"))?;
     }
     #[cfg(target_os = "windows")]
     {
         std::process::Command::new("cmd")
             .args(["/C", "start", url])
-            .spawn()
+            .logged_spawn()
             .with_context(|| format!("failed to open {url} in browser"))?;
     }
     Ok(())
 }
 
+/// Copy text to the system clipboard
+pub fn copy_to_clipboard(text: &str) -> Result<()> {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    #[cfg(target_os = "macos")]
+    let candidates: &[(&str, &[&str])] = &[("pbcopy", &[])];
+    #[cfg(target_os = "linux")]
+    let candidates: &[(&str, &[&str])] = &[
+        ("wl-copy", &[]),
+        ("xclip", &["-selection", "clipboard"]),
+        ("xsel", &["--clipboard", "--input"]),
+    ];
+    #[cfg(target_os = "windows")]
+    let candidates: &[(&str, &[&str])] = &[("clip", &[])];
+
+    let (program, args) = candidates
+        .iter()
+        .find(|(program, _)| cmd_exists(program))
+        .ok_or_else(|| {
+            DevkitError::feature_not_available(
+                "clipboard".to_string(),
+                format!(
+                    "Install one of: {}",
+                    candidates
+                        .iter()
+                        .map(|(p, _)| *p)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            )
+        })?;
+
+    let mut cmd = Command::new(program);
+    cmd.args(*args).stdin(Stdio::piped());
+    let op = Operation::start(&cmd);
+    let mut child = cmd
+        .spawn()
+        .with_context(|| format!("failed to start {program}"))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(text.as_bytes())?;
+    }
+    let status = child.wait();
+    op.finish_status(&status);
+    status?;
+    Ok(())
+}
+
+/// Show a desktop notification
+pub fn desktop_notify(title: &str, body: &str) -> Result<()> {
+    #[cfg(target_os = "macos")]
+    {
+        std::process::Command::new("osascript")
+            .args([
+                "-e",
+                &format!("display notification {:?} with title {:?}", body, title),
+            ])
+            .logged_spawn()
+            .context("failed to show desktop notification")?;
+    }
+    #[cfg(target_os = "linux")]
+    {
+        std::process::Command::new("notify-send")
+            .args([title, body])
+            .logged_spawn()
+            .context("failed to show desktop notification")?;
+    }
+    #[cfg(target_os = "windows")]
+    {
+        let _ = (title, body);
+    }
+    Ok(())
+}
+
 /// Get docker compose program and base args
 pub fn docker_compose_program() -> Result<(String, Vec<String>)> {
     if cmd_exists("docker") {
@@ -105,3 +187,128 @@ pub fn docker_compose_program() -> Result<(String, Vec<String>)> {
         "Install Docker Compose from https://docs.docker.com/compose/install/".to_string(),
     ))
 }
+
+/// Set `KEY=value` lines in an env file such as `.env.local`, replacing existing keys and
+/// keeping everything else
+pub fn upsert_env_vars(path: &Path, vars: &[(&str, &str)]) -> std::io::Result<()> {
+    let existing = fs::read_to_string(path).unwrap_or_default();
+    fs::write(path, upsert_env_contents(&existing, vars))
+}
+
+fn upsert_env_contents(existing: &str, vars: &[(&str, &str)]) -> String {
+    let mut remaining: Vec<_> = vars.to_vec();
+    let mut lines: Vec<String> = existing
+        .lines()
+        .map(|line| {
+            let key = line
+                .trim_start()
+                .trim_start_matches("export ")
+                .split_once('=')
+                .map(|(k, _)| k.trim());
+            match key.and_then(|k| remaining.iter().position(|(name, _)| *name == k)) {
+                Some(i) => {
+                    let (name, value) = remaining.remove(i);
+                    format!("{}={}", name, value)
+                }
+                None => line.to_string(),
+            }
+        })
+        .collect();
+
+    lines.extend(remaining.iter().map(|(k, v)| format!("{}={}", k, v)));
+    let mut contents = lines.join("\n");
+    contents.push('\n');
+    contents
+}
+
+/// Quote a value as a single word for a POSIX shell
+pub fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// Closest candidate to a misspelt name, for "did you mean" hints
+pub fn closest_match<'a>(
+    name: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> Option<&'a str> {
+    let max_distance = if name.chars().count() > 4 { 2 } else { 1 };
+    candidates
+        .into_iter()
+        .filter(|candidate| *candidate != name)
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// Edits (insert, delete, substitute or swap adjacent characters) between two strings
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = distance;
+        }
+    }
+    rows[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_closest_match() {
+        let commands = ["build", "test", "lint"];
+        assert_eq!(closest_match("biuld", commands), Some("build"));
+        assert_eq!(closest_match("tset", commands), Some("test"));
+        assert_eq!(closest_match("deploy", commands), None);
+        assert_eq!(closest_match("build", commands), None);
+    }
+
+    #[test]
+    fn test_shell_quote() {
+        assert_eq!(shell_quote("/srv/it's"), r"'/srv/it'\''s'");
+        assert_eq!(shell_quote("a b"), "'a b'");
+    }
+
+    #[test]
+    fn test_upsert_env_contents() {
+        let existing = "# local\nPUBLIC_URL=http://old\nDATABASE_URL=postgres://db\n";
+        let updated = upsert_env_contents(
+            existing,
+            &[
+                ("PUBLIC_URL", "https://abc.ngrok.app"),
+                ("WEBHOOK_BASE_URL", "https://abc.ngrok.app"),
+            ],
+        );
+        assert_eq!(
+            updated,
+            "# local\nPUBLIC_URL=https://abc.ngrok.app\nDATABASE_URL=postgres://db\n\
+             WEBHOOK_BASE_URL=https://abc.ngrok.app\n"
+        );
+    }
+
+    #[test]
+    fn test_upsert_env_contents_empty_file() {
+        assert_eq!(
+            upsert_env_contents("", &[("PUBLIC_URL", "https://x")]),
+            "PUBLIC_URL=https://x\n"
+        );
+    }
+}
@@ -1,10 +1,50 @@
 //! Configuration validation
 
-use crate::config::Config;
+use crate::config::{service_references, Config, GlobalConfig, VaultAuth};
 use crate::error::Result;
+use crate::hooks::{is_known_event, OPERATIONS};
+use crate::utils::closest_match;
+use serde::Serialize;
 use std::collections::{HashMap, HashSet};
+use std::io::ErrorKind;
+use std::net::TcpListener;
+use std::path::Path;
 
-#[derive(Debug)]
+/// Compose files looked for at the repo root, in order
+const COMPOSE_FILES: &[&str] = &[
+    "docker-compose.yml",
+    "docker-compose.yaml",
+    "compose.yml",
+    "compose.yaml",
+];
+
+/// Top-level keys of a package dev.toml
+const PACKAGE_KEYS: &[&str] = &["database", "mobile", "proto", "cmd"];
+const DATABASE_KEYS: &[&str] = &["migrations", "seeds", "engine", "url"];
+const MOBILE_KEYS: &[&str] = &["pre_run_scripts", "startup_timeout_secs"];
+const PROTO_KEYS: &[&str] = &["dir", "out", "plugins"];
+
+/// dev.toml keys older versions wrote that `devkit init --upgrade` removes
+const RETIRED_PACKAGE_KEYS: &[&str] = &["releasable"];
+
+/// Command names typos are matched against, besides those other packages define
+const COMMON_COMMANDS: &[&str] = &[
+    "build",
+    "test",
+    "lint",
+    "fmt",
+    "dev",
+    "start",
+    "check",
+    "typecheck",
+    "bench",
+    "clean",
+    "deploy",
+    "migrate",
+    "seed",
+];
+
+#[derive(Debug, Serialize)]
 pub struct ValidationReport {
     pub errors: Vec<String>,
     pub warnings: Vec<String>,
@@ -50,6 +90,35 @@ pub fn validate_config(config: &Config) -> Result<ValidationReport> {
     // Validate port conflicts
     validate_port_conflicts(config, &mut report);
 
+    // Check [workspaces] exclude entries name a package directory
+    validate_workspace_excludes(config, &mut report);
+
+    // Validate [services] against references and the compose file
+    validate_service_references(config, &mut report);
+    validate_compose_services(config, &mut report);
+    validate_compose_files(config, &mut report);
+
+    // Validate hook names
+    validate_hooks(config, &mut report);
+
+    // Check [repos] names and paths
+    validate_repos(config, &mut report);
+
+    // Check the active environment and [environments.<name>] tables
+    validate_environments(config, &mut report);
+
+    // Check [remote.<name>] port forwards
+    validate_remotes(config, &mut report);
+
+    // Check [watch.<name>] patterns and commands
+    validate_watchers(config, &mut report);
+
+    // Check [secrets.vault] login settings
+    validate_vault(config, &mut report);
+
+    // Check package dev.toml files for unknown keys
+    validate_package_schemas(config, &mut report);
+
     // Check for empty packages
     if config.packages.is_empty() {
         report.add_warning(
@@ -102,6 +171,13 @@ fn validate_command_dependencies(config: &Config, report: &mut ValidationReport)
                 deps.push(dep_node);
             }
 
+            // `db` depends on DATABASE_URL, which is only needed when the command runs
+            for target in cmd_entry.wait().iter().filter(|target| *target != "db") {
+                if let Err(e) = crate::wait::Target::parse(target, config) {
+                    report.add_error(format!("{} in {}:{}", e, pkg_name, cmd_name));
+                }
+            }
+
             graph.insert(node.clone(), deps);
         }
     }
@@ -194,6 +270,474 @@ fn validate_port_conflicts(config: &Config, report: &mut ValidationReport) {
     }
 }
 
+fn validate_workspace_excludes(config: &Config, report: &mut ValidationReport) {
+    for exclude in unknown_excludes(config) {
+        report.add_warning(format!(
+            "[workspaces] exclude '{}' doesn't match any package directory",
+            exclude
+        ));
+    }
+}
+
+/// `[workspaces] exclude` entries that aren't the name of a directory the package
+/// globs match
+pub fn unknown_excludes(config: &Config) -> Vec<&str> {
+    let mut dirs = HashSet::new();
+    for pattern in &config.global.workspaces.packages {
+        let full_pattern = config.repo_root.join(pattern);
+        let Ok(entries) = glob::glob(&full_pattern.to_string_lossy()) else {
+            continue;
+        };
+        for path in entries.flatten().filter(|path| path.is_dir()) {
+            if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
+                dirs.insert(name.to_string());
+            }
+        }
+    }
+
+    config
+        .global
+        .workspaces
+        .exclude
+        .iter()
+        .filter(|exclude| !dirs.contains(exclude.as_str()))
+        .map(String::as_str)
+        .collect()
+}
+
+/// Warn about `{services.<name>}` placeholders naming unknown services
+fn validate_service_references(config: &Config, report: &mut ValidationReport) {
+    let services = &config.global.services.ports;
+    let mut references: Vec<(String, &str)> = Vec::new();
+
+    for (key, entry) in config.global.urls.all() {
+        references.push((format!("[urls.{}]", key), &entry.url));
+    }
+    for (pkg_name, pkg_config) in &config.packages {
+        for (cmd_name, cmd_entry) in &pkg_config.cmd {
+            let location = format!("{}:{}", pkg_name, cmd_name);
+            references.push((location.clone(), cmd_entry.default_cmd()));
+            if let crate::config::CmdEntry::Full(cmd) = cmd_entry {
+                for command in cmd.variants.values() {
+                    references.push((location.clone(), command));
+                }
+            }
+        }
+    }
+    references.sort();
+
+    for (location, text) in references {
+        for name in service_references(text) {
+            if !services.contains_key(name) {
+                report.add_warning(format!(
+                    "Unknown service '{}' referenced in {} (not in [services])",
+                    name, location
+                ));
+            }
+        }
+    }
+}
+
+/// Warn about `[services]` entries missing from the compose file, and ports already
+/// taken by something other than the compose project
+fn validate_compose_services(config: &Config, report: &mut ValidationReport) {
+    let compose = compose_services(&config.repo_root);
+
+    let mut services: Vec<(&String, &u16)> = config.global.services.ports.iter().collect();
+    services.sort();
+
+    for (name, port) in services {
+        if let Some((file, compose_services)) = &compose {
+            if !compose_services.contains_key(name) {
+                report.add_warning(format!(
+                    "[services] entry '{}' has no matching service in {}",
+                    name, file
+                ));
+            }
+        }
+
+        let published = compose.as_ref().is_some_and(|(_, compose_services)| {
+            compose_services.values().any(|ports| ports.contains(port))
+        });
+        if !published && port_in_use(*port) {
+            report.add_warning(format!(
+                "Port {} for service '{}' is already in use by another process",
+                port, name
+            ));
+        }
+    }
+}
+
+/// `[docker] compose_files` and the environment's override must exist
+fn validate_compose_files(config: &Config, report: &mut ValidationReport) {
+    for file in compose_files(&config.repo_root, &config.global) {
+        if !config.repo_root.join(&file).exists() {
+            report.add_warning(format!("Compose file '{}' not found", file));
+        }
+    }
+}
+
+/// The repo's compose file, if it has one
+pub fn compose_file(repo_root: &Path) -> Option<&'static str> {
+    COMPOSE_FILES
+        .iter()
+        .copied()
+        .find(|file| repo_root.join(file).exists())
+}
+
+/// Compose files to pass with `-f`: `[docker] compose_files`, then the active
+/// environment's override. Empty when there's neither, leaving compose to find its files.
+pub fn compose_files(repo_root: &Path, config: &GlobalConfig) -> Vec<String> {
+    let mut files = config.docker.compose_files.clone();
+    let environments = &config.environments;
+    let base = config
+        .docker
+        .compose_files
+        .first()
+        .map(String::as_str)
+        .or_else(|| compose_file(repo_root));
+    let env_override = environments
+        .current_settings()
+        .and_then(|settings| settings.compose_override.clone())
+        .or_else(|| {
+            let file = layered_name(base?, environments.current());
+            repo_root.join(&file).exists().then_some(file)
+        });
+    let Some(env_override) = env_override.filter(|file| !files.contains(file)) else {
+        return files;
+    };
+
+    if files.is_empty() {
+        // With -f, compose no longer picks up docker-compose.override.yml by itself
+        files.extend(base.map(String::from));
+        let default_override = base.map(|base| layered_name(base, "override"));
+        files.extend(default_override.filter(|file| repo_root.join(file).exists()));
+    }
+    files.push(env_override);
+    files
+}
+
+/// `docker-compose.yml` with `staging` is `docker-compose.staging.yml`
+fn layered_name(file: &str, layer: &str) -> String {
+    match file.rsplit_once('.') {
+        Some((stem, ext)) => format!("{}.{}.{}", stem, layer, ext),
+        None => format!("{}.{}", file, layer),
+    }
+}
+
+/// Services in the repo's compose file with their published host ports
+pub fn compose_services(repo_root: &Path) -> Option<(&'static str, HashMap<String, Vec<u16>>)> {
+    let file = compose_file(repo_root)?;
+    let contents = std::fs::read_to_string(repo_root.join(file)).ok()?;
+    let compose: serde_yaml::Value = serde_yaml::from_str(&contents).ok()?;
+
+    let services = compose
+        .get("services")
+        .and_then(|services| services.as_mapping())
+        .map(|services| {
+            services
+                .iter()
+                .filter_map(|(name, service)| {
+                    let ports = service
+                        .get("ports")
+                        .and_then(|ports| ports.as_sequence())
+                        .map(|ports| ports.iter().filter_map(published_port).collect())
+                        .unwrap_or_default();
+                    Some((name.as_str()?.to_string(), ports))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    Some((file, services))
+}
+
+/// Profiles the compose files assign their services (`profiles: [tools]`), sorted;
+/// `files` as from [`compose_files`], empty for the compose file at the root
+pub fn compose_profiles(repo_root: &Path, files: &[String]) -> Vec<String> {
+    let files: Vec<&str> = match files {
+        [] => compose_file(repo_root).into_iter().collect(),
+        files => files.iter().map(String::as_str).collect(),
+    };
+
+    let mut profiles: Vec<String> = files
+        .iter()
+        .filter_map(|file| std::fs::read_to_string(repo_root.join(file)).ok())
+        .filter_map(|contents| serde_yaml::from_str::<serde_yaml::Value>(&contents).ok())
+        .flat_map(|compose| {
+            compose
+                .get("services")
+                .and_then(|services| services.as_mapping())
+                .into_iter()
+                .flat_map(|services| services.values())
+                .filter_map(|service| service.get("profiles")?.as_sequence())
+                .flatten()
+                .filter_map(|profile| profile.as_str().map(String::from))
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    profiles.sort();
+    profiles.dedup();
+    profiles
+}
+
+/// Host port of a compose `ports` entry (`"8080:80"`, `"127.0.0.1:8080:80/tcp"` or
+/// `{ published: 8080, target: 80 }`)
+fn published_port(entry: &serde_yaml::Value) -> Option<u16> {
+    if let Some(published) = entry.get("published") {
+        return match published {
+            serde_yaml::Value::Number(port) => port.as_u64()?.try_into().ok(),
+            serde_yaml::Value::String(port) => port.parse().ok(),
+            _ => None,
+        };
+    }
+
+    let mapping = entry.as_str()?;
+    let mapping = mapping.split('/').next()?;
+    let parts: Vec<&str> = mapping.rsplitn(3, ':').collect();
+    // A bare container port is published on a random host port
+    parts.get(1)?.parse().ok()
+}
+
+/// Whether something is already listening on a local port
+fn port_in_use(port: u16) -> bool {
+    matches!(
+        TcpListener::bind(("127.0.0.1", port)),
+        Err(e) if e.kind() == ErrorKind::AddrInUse
+    )
+}
+
+/// Warn about dev.toml keys that would be silently ignored
+fn validate_package_schemas(config: &Config, report: &mut ValidationReport) {
+    let mut packages: Vec<_> = config.packages.values().collect();
+    packages.sort_by(|a, b| a.path.cmp(&b.path));
+
+    for package in packages {
+        let path = package.path.join("dev.toml");
+        let Ok(contents) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        let Ok(doc) = contents.parse::<toml::Table>() else {
+            // Parse errors already fail loading
+            continue;
+        };
+
+        // Commands other packages define are known names, not typos
+        let other_commands: HashSet<&str> = config
+            .packages
+            .values()
+            .filter(|other| other.path != package.path)
+            .flat_map(|other| other.cmd.keys().map(String::as_str))
+            .chain(COMMON_COMMANDS.iter().copied())
+            .collect();
+
+        let file = path.strip_prefix(&config.repo_root).unwrap_or(&path);
+        for problem in check_package_toml(&doc, &other_commands) {
+            report.add_warning(format!("{}: {}", file.display(), problem));
+        }
+    }
+}
+
+/// Unknown keys and likely typos in a parsed dev.toml
+fn check_package_toml(doc: &toml::Table, known_commands: &HashSet<&str>) -> Vec<String> {
+    let mut problems = Vec::new();
+
+    for (key, value) in doc {
+        match key.as_str() {
+            "database" => unknown_keys(value, "[database]", DATABASE_KEYS, &mut problems),
+            "mobile" => unknown_keys(value, "[mobile]", MOBILE_KEYS, &mut problems),
+            "proto" => unknown_keys(value, "[proto]", PROTO_KEYS, &mut problems),
+            "cmd" => {
+                let Some(commands) = value.as_table() else {
+                    continue;
+                };
+                for (name, entry) in commands {
+                    if !known_commands.contains(name.as_str()) {
+                        if let Some(suggestion) =
+                            closest_match(name, known_commands.iter().copied())
+                        {
+                            problems.push(format!(
+                                "command '{}' looks like a typo (did you mean '{}'?)",
+                                name, suggestion
+                            ));
+                        }
+                    }
+                    if let Some(entry) = entry.as_table() {
+                        check_cmd_table(name, entry, &mut problems);
+                    }
+                }
+            }
+            retired if RETIRED_PACKAGE_KEYS.contains(&retired) => problems.push(format!(
+                "'{}' is no longer used (run `devkit init --upgrade` to remove it)",
+                key
+            )),
+            _ => problems.push(unknown_key(key, "", PACKAGE_KEYS)),
+        }
+    }
+    problems
+}
+
+const CMD_KEYS: [&str; 8] = [
+    "default", "deps", "wait", "inputs", "env", "env_file", "snippet", "params",
+];
+
+/// `[cmd.<name>]` takes `default` (or `snippet` and `params`), `deps`, `wait`, `inputs`,
+/// `env`, `env_file` and string variants; anything else is dropped
+fn check_cmd_table(name: &str, entry: &toml::Table, problems: &mut Vec<String>) {
+    let table = format!("[cmd.{}]", name);
+    for (key, value) in entry {
+        match key.as_str() {
+            "deps" | "wait" | "inputs" if !value.is_array() => {
+                problems.push(format!("'{}' in {} must be a list", key, table))
+            }
+            "params" | "env" if !value.is_table() => {
+                problems.push(format!("'{}' in {} must be a table", key, table))
+            }
+            "default" | "deps" | "wait" | "inputs" | "env" | "env_file" | "snippet" | "params" => {}
+            _ if value.is_str() => {}
+            _ => problems.push(match closest_match(key, CMD_KEYS) {
+                Some(suggestion) => format!(
+                    "unknown key '{}' in {} (did you mean '{}'?)",
+                    key, table, suggestion
+                ),
+                None => format!(
+                    "'{}' in {} is not a command string and is ignored",
+                    key, table
+                ),
+            }),
+        }
+    }
+}
+
+fn unknown_keys(value: &toml::Value, table: &str, known: &[&str], problems: &mut Vec<String>) {
+    if let Some(value) = value.as_table() {
+        problems.extend(
+            value
+                .keys()
+                .filter(|key| !known.contains(&key.as_str()))
+                .map(|key| unknown_key(key, table, known)),
+        );
+    }
+}
+
+fn unknown_key(key: &str, table: &str, known: &[&str]) -> String {
+    let location = if table.is_empty() {
+        String::new()
+    } else {
+        format!(" in {}", table)
+    };
+    match closest_match(key, known.iter().copied()) {
+        Some(suggestion) => format!(
+            "unknown key '{}'{} (did you mean '{}'?)",
+            key, location, suggestion
+        ),
+        None => format!("unknown key '{}'{}", key, location),
+    }
+}
+
+fn validate_hooks(config: &Config, report: &mut ValidationReport) {
+    let mut events: Vec<&String> = config.global.hooks.hooks.keys().collect();
+    events.sort();
+
+    for event in events {
+        if !is_known_event(event) {
+            report.add_warning(format!(
+                "Unknown hook '{}' in [hooks] (expected pre_/post_ followed by one of: {})",
+                event,
+                OPERATIONS.join(", ")
+            ));
+        }
+    }
+}
+
+/// Repo names end up in package ids (`<repo>/<package>`) and command deps
+/// (`<repo>/<package>:<cmd>`), so they can't contain either separator
+fn validate_repos(config: &Config, report: &mut ValidationReport) {
+    for (name, path) in config.repos() {
+        if name.is_empty() || name.contains(['/', ':']) {
+            report.add_error(format!(
+                "Invalid repo name '{}' in [repos] (can't be empty or contain '/' or ':')",
+                name
+            ));
+        }
+        if !path.is_dir() {
+            report.add_warning(format!(
+                "Repo '{}' not found at {}; its packages are skipped",
+                name,
+                path.display()
+            ));
+        }
+    }
+}
+
+fn validate_environments(config: &Config, report: &mut ValidationReport) {
+    let environments = &config.global.environments;
+    let known = |name: &str| environments.available.iter().any(|env| env == name);
+
+    if !known(environments.current()) {
+        report.add_warning(format!(
+            "Active environment '{}' isn't in [environments] available ({})",
+            environments.current(),
+            environments.available.join(", ")
+        ));
+    }
+
+    let mut names: Vec<&String> = environments.settings.keys().collect();
+    names.sort();
+    for name in names {
+        if !known(name) {
+            report.add_warning(format!(
+                "[environments.{}] isn't in [environments] available",
+                name
+            ));
+        }
+    }
+}
+
+fn validate_remotes(config: &Config, report: &mut ValidationReport) {
+    let remotes = &config.global.remote;
+    for name in remotes.names() {
+        let Some(remote) = remotes.get(name) else {
+            continue;
+        };
+        if remote.host.is_empty() || remote.path.is_empty() {
+            report.add_error(format!("[remote.{}] needs a host and a path", name));
+        }
+        if let Err(e) = remote.forwards() {
+            report.add_error(format!(
+                "[remote.{}]: {} (expected \"<port>\" or \"<local>:<remote>\")",
+                name, e
+            ));
+        }
+    }
+}
+
+fn validate_vault(config: &Config, report: &mut ValidationReport) {
+    let vault = &config.global.secrets.vault;
+    if vault.auth == VaultAuth::AppRole && vault.role_id.is_none() {
+        report.add_error("[secrets.vault] auth = \"approle\" needs a role_id".to_string());
+    }
+}
+
+fn validate_watchers(config: &Config, report: &mut ValidationReport) {
+    let watchers = &config.global.watch;
+    for name in watchers.names() {
+        let Some(watcher) = watchers.get(name) else {
+            continue;
+        };
+        if watcher.patterns.is_empty() || watcher.command.trim().is_empty() {
+            report.add_error(format!("[watch.{}] needs patterns and a command", name));
+        }
+        for pattern in &watcher.patterns {
+            if let Err(e) = glob::Pattern::new(pattern) {
+                report.add_error(format!(
+                    "[watch.{}]: invalid pattern '{}': {}",
+                    name, pattern, e
+                ));
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -207,11 +751,17 @@ mod tests {
         let mut cmd_a = HashMap::new();
         cmd_a.insert(
             "build".to_string(),
-            CmdEntry::Full(CmdConfig {
+            CmdEntry::Full(Box::new(CmdConfig {
                 default: "cargo build".to_string(),
                 deps: vec!["b:build".to_string()],
+                wait: Vec::new(),
+                inputs: Vec::new(),
+                env: HashMap::new(),
+                env_file: None,
+                snippet: None,
+                params: HashMap::new(),
                 variants: HashMap::new(),
-            }),
+            })),
         );
 
         packages.insert(
@@ -222,6 +772,7 @@ mod tests {
                 name: "a".to_string(),
                 database: None,
                 mobile: None,
+                proto: None,
                 cmd: cmd_a,
             },
         );
@@ -229,11 +780,17 @@ mod tests {
         let mut cmd_b = HashMap::new();
         cmd_b.insert(
             "build".to_string(),
-            CmdEntry::Full(CmdConfig {
+            CmdEntry::Full(Box::new(CmdConfig {
                 default: "cargo build".to_string(),
                 deps: vec!["a:build".to_string()], // Circular!
+                wait: Vec::new(),
+                inputs: Vec::new(),
+                env: HashMap::new(),
+                env_file: None,
+                snippet: None,
+                params: HashMap::new(),
                 variants: HashMap::new(),
-            }),
+            })),
         );
 
         packages.insert(
@@ -244,6 +801,7 @@ mod tests {
                 name: "b".to_string(),
                 database: None,
                 mobile: None,
+                proto: None,
                 cmd: cmd_b,
             },
         );
@@ -267,11 +825,17 @@ mod tests {
         let mut cmd = HashMap::new();
         cmd.insert(
             "build".to_string(),
-            CmdEntry::Full(CmdConfig {
+            CmdEntry::Full(Box::new(CmdConfig {
                 default: "cargo build".to_string(),
                 deps: vec!["nonexistent:build".to_string()],
+                wait: Vec::new(),
+                inputs: Vec::new(),
+                env: HashMap::new(),
+                env_file: None,
+                snippet: None,
+                params: HashMap::new(),
                 variants: HashMap::new(),
-            }),
+            })),
         );
 
         packages.insert(
@@ -282,6 +846,7 @@ mod tests {
                 name: "a".to_string(),
                 database: None,
                 mobile: None,
+                proto: None,
                 cmd,
             },
         );
@@ -315,6 +880,7 @@ mod tests {
                 name: "a".to_string(),
                 database: None,
                 mobile: None,
+                proto: None,
                 cmd,
             },
         );
@@ -328,4 +894,162 @@ mod tests {
         let report = validate_config(&config).unwrap();
         assert!(report.is_valid());
     }
+
+    #[test]
+    fn test_compose_files_and_profiles() {
+        let repo = std::env::temp_dir().join(format!("devkit-profiles-{}", std::process::id()));
+        std::fs::create_dir_all(&repo).unwrap();
+        assert!(compose_profiles(&repo, &[]).is_empty());
+
+        std::fs::write(
+            repo.join("compose.yml"),
+            "services:\n  api:\n    image: api\n  e2e:\n    profiles: [test, tools]\n  \
+             adminer:\n    profiles: [\"tools\"]\n",
+        )
+        .unwrap();
+        assert_eq!(compose_profiles(&repo, &[]), ["test", "tools"]);
+
+        // An environment's override layers over the base file
+        std::fs::write(
+            repo.join("compose.staging.yml"),
+            "services:\n  smoke:\n    profiles: [smoke]\n",
+        )
+        .unwrap();
+        let mut config = GlobalConfig::default();
+        config.environments.active = "staging".to_string();
+        let files = compose_files(&repo, &config);
+        assert_eq!(files, ["compose.yml", "compose.staging.yml"]);
+        assert_eq!(compose_profiles(&repo, &files), ["smoke", "test", "tools"]);
+        config.environments.active = "dev".to_string();
+        assert!(compose_files(&repo, &config).is_empty());
+
+        // Listing the override already doesn't repeat it
+        config.docker.compose_files = files.clone();
+        config.environments.active = "staging".to_string();
+        assert_eq!(compose_files(&repo, &config), files);
+
+        std::fs::remove_dir_all(&repo).ok();
+    }
+
+    #[test]
+    fn test_services_checked_against_references_and_compose() {
+        let repo = std::env::temp_dir().join(format!("devkit-validation-{}", std::process::id()));
+        std::fs::create_dir_all(&repo).unwrap();
+        std::fs::write(
+            repo.join("docker-compose.yml"),
+            "services:\n  postgres:\n    ports:\n      - \"127.0.0.1:5432:5432\"\n",
+        )
+        .unwrap();
+
+        // Something else is listening on the api port
+        let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let api_port = listener.local_addr().unwrap().port();
+
+        let mut global = GlobalConfig::default();
+        global.services.ports.insert("api".to_string(), api_port);
+        global.services.ports.insert("postgres".to_string(), 5432);
+
+        let mut cmd = HashMap::new();
+        cmd.insert(
+            "smoke".to_string(),
+            CmdEntry::Simple("curl localhost:{services.web}/health".to_string()),
+        );
+        let mut packages = HashMap::new();
+        packages.insert(
+            "a".to_string(),
+            PackageConfig {
+                path: repo.join("a"),
+                dir_name: "a".to_string(),
+                name: "a".to_string(),
+                database: None,
+                mobile: None,
+                proto: None,
+                cmd,
+            },
+        );
+
+        let config = Config {
+            repo_root: repo.clone(),
+            global,
+            packages,
+        };
+        let report = validate_config(&config).unwrap();
+        assert!(report.is_valid());
+        assert_eq!(
+            report.warnings,
+            [
+                "Unknown service 'web' referenced in a:smoke (not in [services])".to_string(),
+                "[services] entry 'api' has no matching service in docker-compose.yml".to_string(),
+                format!(
+                    "Port {} for service 'api' is already in use by another process",
+                    api_port
+                ),
+            ]
+        );
+
+        drop(listener);
+        std::fs::remove_dir_all(repo).ok();
+    }
+
+    #[test]
+    fn test_package_toml_unknown_keys() {
+        let doc: toml::Table = r#"
+releasable = true
+
+[databse]
+migrations = "migrations"
+
+[cmd]
+biuld = "cargo build"
+e2e = "playwright test"
+
+[cmd.lint]
+default = "cargo clippy"
+dep = ["core"]
+inputs = ["src/**"]
+env = ["RUST_LOG=debug"]
+fix = "cargo clippy --fix"
+"#
+        .parse()
+        .unwrap();
+
+        let known = COMMON_COMMANDS.iter().copied().collect();
+        let mut problems = check_package_toml(&doc, &known);
+        problems.sort();
+        assert_eq!(
+            problems,
+            [
+                "'env' in [cmd.lint] must be a table",
+                "'releasable' is no longer used (run `devkit init --upgrade` to remove it)",
+                "command 'biuld' looks like a typo (did you mean 'build'?)",
+                "unknown key 'databse' (did you mean 'database'?)",
+                "unknown key 'dep' in [cmd.lint] (did you mean 'deps'?)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_repos_checked() {
+        let mut global = GlobalConfig::default();
+        for (name, path) in [("api", "."), ("web/app", "."), ("gone", "does-not-exist")] {
+            global
+                .repos
+                .repos
+                .insert(name.to_string(), path.to_string());
+        }
+        let config = Config {
+            repo_root: std::env::temp_dir(),
+            global,
+            packages: HashMap::new(),
+        };
+
+        let mut report = ValidationReport::new();
+        validate_repos(&config, &mut report);
+        assert_eq!(
+            report.errors,
+            ["Invalid repo name 'web/app' in [repos] (can't be empty or contain '/' or ':')"]
+        );
+        assert_eq!(report.warnings.len(), 1);
+        assert!(report.warnings[0].starts_with("Repo 'gone' not found at "));
+    }
 }
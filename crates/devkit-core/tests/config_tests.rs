@@ -15,11 +15,17 @@ fn test_cmd_entry_full() {
     variants.insert("watch".to_string(), "cargo watch -x test".to_string());
     variants.insert("release".to_string(), "cargo test --release".to_string());
 
-    let cmd = CmdEntry::Full(CmdConfig {
+    let cmd = CmdEntry::Full(Box::new(CmdConfig {
         default: "cargo test".to_string(),
         deps: vec!["common:build".to_string()],
+        wait: Vec::new(),
+        inputs: Vec::new(),
+        env: HashMap::new(),
+        env_file: None,
+        snippet: None,
+        params: HashMap::new(),
         variants,
-    });
+    }));
 
     assert_eq!(cmd.default_cmd(), "cargo test");
     assert_eq!(cmd.variant("watch"), "cargo watch -x test");
@@ -29,6 +35,70 @@ fn test_cmd_entry_full() {
     assert_eq!(cmd.deps()[0], "common:build");
 }
 
+#[test]
+fn test_cmd_entry_env() {
+    let dir = std::env::temp_dir().join(format!("devkit-cmd-env-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(
+        dir.join(".env.test"),
+        "DATABASE_URL=postgres://test\nLOG=info\n",
+    )
+    .unwrap();
+
+    let cmd: CmdEntry = toml::from_str(
+        r#"
+default = "cargo test"
+env_file = ".env.test"
+env = { LOG = "debug", CI = "1" }
+"#,
+    )
+    .unwrap();
+    assert_eq!(cmd.variant("env_file"), "cargo test"); // Not a variant
+    assert_eq!(
+        cmd.env_vars(&dir).unwrap(),
+        [
+            ("DATABASE_URL".to_string(), "postgres://test".to_string()),
+            ("CI".to_string(), "1".to_string()),
+            ("LOG".to_string(), "debug".to_string()),
+        ]
+    );
+    assert!(cmd.env_vars(&dir.join("missing")).is_err());
+    assert!(CmdEntry::Simple("cargo test".to_string())
+        .env_vars(&dir)
+        .unwrap()
+        .is_empty());
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_cmd_entry_env_values() {
+    let cmd: CmdEntry = toml::from_str(
+        r#"
+default = "npm start"
+env = { PORT = 3000, DEBUG = true, HOST = "localhost" }
+params = { retries = 3 }
+"#,
+    )
+    .unwrap();
+    let CmdEntry::Full(config) = &cmd else {
+        panic!("expected a full config");
+    };
+    assert_eq!(config.env["PORT"], "3000");
+    assert_eq!(config.env["DEBUG"], "true");
+    assert_eq!(config.env["HOST"], "localhost");
+    assert_eq!(config.params["retries"], "3");
+
+    let error = toml::from_str::<CmdEntry>("default = \"npm start\"\nenv = { PORTS = [3000] }\n")
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("'env.PORTS' must be a string"), "{}", error);
+    let error = toml::from_str::<CmdEntry>("default = \"npm start\"\nparams = \"x\"\n")
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("'params' must be a table"), "{}", error);
+}
+
 #[test]
 fn test_global_config_defaults() {
     let config = GlobalConfig::default();
@@ -80,11 +150,549 @@ fn test_package_config_has_database() {
         database: Some(DatabaseConfig {
             migrations: "migrations".to_string(),
             seeds: Some("seeds/dev.sql".to_string()),
+            engine: None,
+            url: None,
         }),
         mobile: None,
+        proto: None,
         cmd: HashMap::new(),
     };
 
     assert!(pkg.database.is_some());
     assert_eq!(pkg.database.as_ref().unwrap().migrations, "migrations");
 }
+
+#[test]
+fn test_database_engine() {
+    let config: DatabaseConfig = toml::from_str("migrations = \"db\"\nengine = \"mysql\"").unwrap();
+    assert_eq!(config.engine, Some(DatabaseEngine::Mysql));
+    assert_eq!(config.url, None);
+
+    let config: DatabaseConfig = toml::from_str(
+        "migrations = \"db\"\nurl = \"postgres://localhost:{services.postgres}/api\"",
+    )
+    .unwrap();
+    assert_eq!(
+        config.url.as_deref(),
+        Some("postgres://localhost:{services.postgres}/api")
+    );
+
+    assert_eq!(
+        DatabaseEngine::from_url("postgresql://localhost/dev"),
+        Some(DatabaseEngine::Postgres)
+    );
+    assert_eq!(
+        DatabaseEngine::from_url("mysql://root@localhost:3306/dev"),
+        Some(DatabaseEngine::Mysql)
+    );
+    assert_eq!(
+        DatabaseEngine::from_url("sqlite://data/dev.db"),
+        Some(DatabaseEngine::Sqlite)
+    );
+    assert_eq!(DatabaseEngine::from_url("redis://localhost"), None);
+    assert_eq!(DatabaseEngine::Sqlite.client(), "sqlite3");
+}
+
+#[test]
+fn test_monitoring_config() {
+    let config = GlobalConfig::default();
+    assert_eq!(
+        config.monitoring.components,
+        vec!["prometheus", "grafana", "loki", "tempo"]
+    );
+
+    let config: GlobalConfig = toml::from_str(
+        r#"
+[monitoring]
+components = ["prometheus", "grafana"]
+"#,
+    )
+    .unwrap();
+    assert_eq!(config.monitoring.components, vec!["prometheus", "grafana"]);
+}
+
+#[test]
+fn test_tunnel_config() {
+    let config: GlobalConfig = toml::from_str(
+        r#"
+[tunnel.api]
+port = 8080
+provider = "ngrok"
+subdomain = "myapp"
+
+[tunnel.web]
+port = 3000
+provider = "cloudflared"
+"#,
+    )
+    .unwrap();
+
+    assert_eq!(config.tunnel.names(), vec!["api", "web"]);
+    let api = config.tunnel.get("api").unwrap();
+    assert_eq!(api.port, 8080);
+    assert_eq!(api.provider, TunnelProvider::Ngrok);
+    assert_eq!(api.subdomain.as_deref(), Some("myapp"));
+    assert_eq!(
+        config.tunnel.get("web").unwrap().provider,
+        TunnelProvider::Cloudflared
+    );
+    assert!(GlobalConfig::default().tunnel.is_empty());
+}
+
+#[test]
+fn test_k8s_config() {
+    assert!(GlobalConfig::default().k8s.manifests.is_empty());
+
+    let config: GlobalConfig =
+        toml::from_str("[k8s]\nmanifests = [\"deploy/base\", \"deploy/charts/api\"]").unwrap();
+    assert_eq!(config.k8s.manifests, ["deploy/base", "deploy/charts/api"]);
+}
+
+#[test]
+fn test_ecs_config() {
+    let config: GlobalConfig =
+        toml::from_str("[ecs]\ncluster = \"prod\"\nservice = \"api\"\nlog_group = \"/ecs/api\"")
+            .unwrap();
+    assert_eq!(config.ecs.cluster.as_deref(), Some("prod"));
+    assert_eq!(config.ecs.service.as_deref(), Some("api"));
+    assert_eq!(config.ecs.log_group.as_deref(), Some("/ecs/api"));
+    assert!(GlobalConfig::default().ecs.cluster.is_none());
+
+    let config: GlobalConfig =
+        toml::from_str("[ecs.task_definitions]\napi = \"deploy/api-task.json\"").unwrap();
+    assert_eq!(
+        config.ecs.task_definitions.get("api").map(String::as_str),
+        Some("deploy/api-task.json")
+    );
+}
+
+#[test]
+fn test_docker_config() {
+    let config: GlobalConfig = toml::from_str(
+        r#"
+[docker]
+compose_files = ["docker-compose.yml", "docker-compose.dev.yml"]
+
+[environments.staging]
+compose_override = "deploy/compose.staging.yml"
+"#,
+    )
+    .unwrap();
+    assert_eq!(
+        config.docker.compose_files,
+        ["docker-compose.yml", "docker-compose.dev.yml"]
+    );
+    assert_eq!(
+        config.environments.settings["staging"]
+            .compose_override
+            .as_deref(),
+        Some("deploy/compose.staging.yml")
+    );
+    assert!(GlobalConfig::default().docker.compose_files.is_empty());
+}
+
+#[test]
+fn test_run_config() {
+    let config: GlobalConfig = toml::from_str(
+        r#"
+[run]
+api = "cargo run -p api"
+web = { command = "npm run dev", cwd = "packages/web", restart = true }
+"#,
+    )
+    .unwrap();
+
+    let api = &config.run.processes["api"];
+    assert_eq!(api.command(), "cargo run -p api");
+    assert_eq!(api.cwd(), None);
+    assert!(!api.restart());
+    let web = &config.run.processes["web"];
+    assert_eq!(web.command(), "npm run dev");
+    assert_eq!(web.cwd(), Some("packages/web"));
+    assert!(web.restart());
+}
+
+#[test]
+fn test_pipeline_config() {
+    let config: GlobalConfig = toml::from_str(
+        r#"
+[pipeline.ci]
+description = "Full local CI"
+steps = [
+    { docker = "up -d postgres" },
+    { cmd = "lint", packages = ["api"] },
+    { name = "e2e", run = "./scripts/e2e.sh", continue_on_error = true },
+]
+"#,
+    )
+    .unwrap();
+
+    assert_eq!(config.pipeline.names(), vec!["ci"]);
+    let ci = config.pipeline.get("ci").unwrap();
+    assert!(ci.fail_fast);
+    assert_eq!(
+        ci.steps[0].action,
+        PipelineAction::Docker("up -d postgres".to_string())
+    );
+    assert_eq!(ci.steps[0].label(), "docker up -d postgres");
+    assert_eq!(ci.steps[1].action, PipelineAction::Cmd("lint".to_string()));
+    assert_eq!(ci.steps[1].packages, vec!["api"]);
+    assert_eq!(ci.steps[2].label(), "e2e");
+    assert!(ci.steps[2].continue_on_error);
+}
+
+#[test]
+fn test_secrets_config() {
+    let config: GlobalConfig = toml::from_str(
+        r#"
+[secrets]
+provider = "1password"
+path = ".env.op"
+
+[secrets.paths]
+prod = ".env.prod.op"
+
+[environments.staging]
+secrets = ".env.staging.op"
+"#,
+    )
+    .unwrap();
+
+    let secrets = &config.secrets;
+    assert_eq!(secrets.provider, Some(SecretsProviderKind::OnePassword));
+    assert_eq!(
+        secrets.path_for("prod", &config.environments),
+        Some(".env.prod.op")
+    );
+    assert_eq!(
+        secrets.path_for("staging", &config.environments),
+        Some(".env.staging.op")
+    );
+    assert_eq!(
+        secrets.path_for("dev", &config.environments),
+        Some(".env.op")
+    );
+    assert!(toml::from_str::<GlobalConfig>("[secrets]\nprovider = \"keychain\"").is_err());
+}
+
+#[test]
+fn test_vault_secrets_config() {
+    let config: GlobalConfig = toml::from_str(
+        r#"
+[secrets]
+provider = "vault"
+path = "myapp/dev"
+
+[secrets.vault]
+address = "https://vault.example.com:8200"
+auth = "approle"
+role_id = "db02de05"
+"#,
+    )
+    .unwrap();
+
+    let vault = &config.secrets.vault;
+    assert_eq!(config.secrets.provider, Some(SecretsProviderKind::Vault));
+    assert_eq!(vault.auth, VaultAuth::AppRole);
+    assert_eq!(vault.mount, "secret");
+    assert_eq!(vault.secret_id_env, "VAULT_SECRET_ID");
+    assert_eq!(VaultConfig::default().auth, VaultAuth::Token);
+}
+
+#[test]
+fn test_bench_config() {
+    let config: GlobalConfig = toml::from_str(
+        r#"
+[bench]
+threshold = 10.0
+
+[bench.startup]
+command = "./target/release/devkit --help"
+runs = 20
+"#,
+    )
+    .unwrap();
+
+    assert_eq!(config.bench.threshold, 10.0);
+    assert_eq!(config.bench.baseline, "bench-baseline.json");
+    assert_eq!(config.bench.command_names(), vec!["startup"]);
+    let startup = &config.bench.commands["startup"];
+    assert_eq!(startup.warmup, 3);
+    assert_eq!(startup.runs, Some(20));
+    assert!(startup.prepare.is_none());
+}
+
+#[test]
+fn test_test_config() {
+    let config: GlobalConfig = toml::from_str(
+        r#"
+[test]
+runner = "nextest"
+profile = "ci"
+retries = 2
+"#,
+    )
+    .unwrap();
+
+    assert_eq!(config.test.runner, TestRunner::Nextest);
+    assert_eq!(config.test.profile.as_deref(), Some("ci"));
+    assert_eq!(config.test.retries, Some(2));
+
+    let config: GlobalConfig = toml::from_str("").unwrap();
+    assert_eq!(config.test.runner, TestRunner::Auto);
+}
+
+#[test]
+fn test_hooks_config() {
+    let config: GlobalConfig = toml::from_str(
+        r#"
+[hooks]
+post_install = "npm run codegen"
+pre_docker_up = ["./check-env.sh", "docker network create dev || true"]
+post_release = { run = "./notify.sh", on_failure = "warn", cwd = "scripts" }
+"#,
+    )
+    .unwrap();
+
+    let install = config.hooks.get("post_install");
+    assert_eq!(install.len(), 1);
+    assert_eq!(install[0].run(), "npm run codegen");
+    assert_eq!(install[0].on_failure(), HookFailure::Abort);
+
+    let docker = config.hooks.get("pre_docker_up");
+    assert_eq!(docker.len(), 2);
+    assert_eq!(docker[1].run(), "docker network create dev || true");
+
+    let release = config.hooks.get("post_release");
+    assert_eq!(release[0].on_failure(), HookFailure::Warn);
+    assert_eq!(release[0].cwd(), Some("scripts"));
+
+    assert!(config.hooks.get("pre_cmd").is_empty());
+}
+
+#[test]
+fn test_extensions_config() {
+    let config: GlobalConfig = toml::from_str(
+        r#"
+[extensions]
+disabled = ["monitoring", "k8s"]
+"#,
+    )
+    .unwrap();
+
+    assert!(config.extensions.is_disabled("monitoring"));
+    assert!(!config.extensions.is_disabled("docker"));
+
+    let config: GlobalConfig = toml::from_str("").unwrap();
+    assert!(config.extensions.disabled.is_empty());
+}
+
+#[test]
+fn test_ext_config_sections() {
+    let config: GlobalConfig = toml::from_str(
+        r#"
+[ext.deploy-tools]
+region = "us-east-1"
+services = ["api", "worker"]
+"#,
+    )
+    .unwrap();
+
+    let deploy = &config.ext["deploy-tools"];
+    assert_eq!(deploy["region"].as_str(), Some("us-east-1"));
+    assert_eq!(deploy["services"].as_array().map(|a| a.len()), Some(2));
+    assert!(!config.ext.contains_key("other"));
+}
+
+#[test]
+fn test_output_config() {
+    let config: GlobalConfig = toml::from_str("").unwrap();
+    assert!(config.output.emoji);
+    assert_eq!(config.output.colors(), None);
+    assert_eq!(config.output.prompt_theme(), PromptTheme::Colorful);
+
+    let config: GlobalConfig = toml::from_str(
+        r#"
+[output]
+color = "never"
+emoji = false
+"#,
+    )
+    .unwrap();
+    assert_eq!(config.output.colors(), Some(false));
+    assert_eq!(config.output.prompt_theme(), PromptTheme::Plain);
+
+    let config: GlobalConfig = toml::from_str(
+        r#"
+[output]
+emoji = false
+theme = "colorful"
+"#,
+    )
+    .unwrap();
+    assert_eq!(config.output.prompt_theme(), PromptTheme::Colorful);
+}
+
+#[test]
+fn test_notifications_config() {
+    let config: GlobalConfig = toml::from_str("").unwrap();
+    assert!(!config.notifications.enabled);
+    assert_eq!(config.notifications.min_duration_secs, 60);
+
+    let config: GlobalConfig = toml::from_str(
+        r#"
+[notifications]
+enabled = true
+min_duration_secs = 120
+"#,
+    )
+    .unwrap();
+    assert!(config.notifications.enabled);
+    assert_eq!(config.notifications.min_duration_secs, 120);
+}
+
+#[test]
+fn test_services_config_expand() {
+    let mut ports = HashMap::new();
+    ports.insert("api".to_string(), 8080);
+    let services = ServicesConfig { ports };
+
+    assert_eq!(
+        services.expand("curl localhost:{services.api}/{services.web}"),
+        "curl localhost:8080/{services.web}"
+    );
+}
+
+#[test]
+fn test_repos_config() {
+    let root = std::env::temp_dir().join(format!("devkit-repos-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    let write = |path: &str, contents: &str| {
+        let path = root.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    };
+    write(
+        "app/.dev/config.toml",
+        "[repos]\napi = \"../api\"\nmissing = \"../missing\"\n",
+    );
+    write(
+        "app/packages/web/dev.toml",
+        "[cmd]\nbuild = \"npm run build\"\n",
+    );
+    write(
+        "api/packages/server/dev.toml",
+        "[cmd.build]\ndefault = \"cargo build\"\ndeps = [\"common:build\"]\n",
+    );
+    write(
+        "api/packages/common/dev.toml",
+        "[cmd]\nbuild = \"cargo build\"\n",
+    );
+
+    let config = Config::load(&root.join("app")).unwrap();
+    let repos = config.repos();
+    assert_eq!(repos.len(), 2);
+    assert_eq!(repos[0], ("api", root.join("app/../api")));
+
+    let mut names: Vec<_> = config.packages.keys().map(String::as_str).collect();
+    names.sort();
+    assert_eq!(names, ["api/common", "api/server", "web"]);
+    let server = config.get_package("api/server").unwrap();
+    assert_eq!(server.name, "api/server");
+    assert_eq!(server.cmd["build"].deps(), ["api/common:build"]);
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn test_environment_overlay() {
+    let root = std::env::temp_dir().join(format!("devkit-env-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    std::fs::create_dir_all(root.join(".dev")).unwrap();
+    std::fs::write(
+        root.join(".dev/config.toml"),
+        "[environments]\navailable = [\"dev\", \"staging\"]\n\n\
+         [environments.staging]\nk8s_context = \"staging-cluster\"\n\
+         helm_values = [\"values-staging.yaml\"]\n\n\
+         [services]\napi = 8080\npostgres = 5432\n",
+    )
+    .unwrap();
+    std::fs::write(
+        root.join(".dev/config.staging.toml"),
+        "[services]\napi = 9090\n",
+    )
+    .unwrap();
+
+    let config = Config::load(&root).unwrap();
+    assert_eq!(config.global.environments.current(), "dev");
+    assert_eq!(config.global.services.ports["api"], 8080);
+    assert!(config.global.environments.current_settings().is_none());
+
+    devkit_core::environment::save(&root, Some("staging")).unwrap();
+    let config = Config::load(&root).unwrap();
+    let environments = &config.global.environments;
+    assert_eq!(environments.current(), "staging");
+    assert_eq!(config.global.services.ports["api"], 9090);
+    assert_eq!(config.global.services.ports["postgres"], 5432);
+    assert_eq!(
+        environments
+            .current_settings()
+            .unwrap()
+            .k8s_context
+            .as_deref(),
+        Some("staging-cluster")
+    );
+    assert_eq!(
+        environments.current_settings().unwrap().helm_values,
+        ["values-staging.yaml"]
+    );
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn test_snippets() {
+    let root = std::env::temp_dir().join(format!("devkit-snippets-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    let write = |path: &str, contents: &str| {
+        let path = root.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    };
+    write(
+        ".dev/config.toml",
+        "[snippets]\n\
+         eslint = \"npx eslint --max-warnings 0 {paths}\"\n\
+         exec = { run = \"docker compose exec -T {service} {cmd} --port {services.api}\", params = { service = \"api\" } }\n",
+    );
+    write(
+        "packages/web/dev.toml",
+        "[cmd.lint]\nsnippet = \"eslint\"\nparams = { paths = \"src test\" }\nfix = \"npx eslint --fix src\"\n\n\
+         [cmd.migrate]\nsnippet = \"exec\"\nparams = { cmd = \"npm run migrate\" }\n",
+    );
+
+    let config = Config::load(&root).unwrap();
+    let web = config.get_package("web").unwrap();
+    assert_eq!(
+        web.cmd["lint"].default_cmd(),
+        "npx eslint --max-warnings 0 src test"
+    );
+    assert_eq!(web.cmd["lint"].variant("fix"), "npx eslint --fix src");
+    assert_eq!(
+        web.cmd["migrate"].default_cmd(),
+        "docker compose exec -T api npm run migrate --port {services.api}"
+    );
+
+    write("packages/web/dev.toml", "[cmd.lint]\nsnippet = \"eslnt\"\n");
+    let error = Config::load(&root).unwrap_err().to_string();
+    assert!(error.contains("[cmd.lint]: unknown snippet 'eslnt' (did you mean 'eslint'?)"));
+
+    write(
+        "packages/web/dev.toml",
+        "[cmd.lint]\nsnippet = \"eslint\"\n",
+    );
+    let error = Config::load(&root).unwrap_err().to_string();
+    assert!(error.contains("snippet 'eslint' needs paths in params"));
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
@@ -66,3 +66,11 @@ fn test_circular_dependency_error() {
     assert!(msg.contains("Circular dependency"));
     assert!(msg.contains("a:build -> b:build -> a:build"));
 }
+
+#[test]
+fn test_not_interactive_error() {
+    let err = DevkitError::not_interactive("Selecting a container", "Pass the service name");
+    let msg = err.to_string();
+    assert!(msg.contains("Selecting a container needs an interactive terminal"));
+    assert!(msg.contains("Pass the service name"));
+}
@@ -0,0 +1,296 @@
+//! Affected package detection
+//!
+//! Finds packages touched by changes since the merge base with a git ref (plus
+//! uncommitted and untracked files), then adds every package that depends on them.
+//! Dependencies come from Cargo.toml and package.json dependency tables that name
+//! other workspace packages, and from `[cmd]` deps in dev.toml.
+//!
+//! Changes to root manifests and lockfiles affect every package.
+
+use anyhow::{anyhow, Result};
+use devkit_core::{AppContext, Config};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::CmdBuilder;
+
+/// Root files whose changes affect the whole workspace
+const WORKSPACE_FILES: &[&str] = &[
+    "Cargo.toml",
+    "Cargo.lock",
+    "package.json",
+    "package-lock.json",
+    "pnpm-lock.yaml",
+    "yarn.lock",
+    ".dev/config.toml",
+];
+
+/// Package -> workspace packages it depends on
+pub fn dependency_graph(config: &Config) -> HashMap<String, HashSet<String>> {
+    let names: HashSet<&str> = config.packages.keys().map(String::as_str).collect();
+
+    config
+        .packages
+        .iter()
+        .map(|(name, pkg)| {
+            let deps: HashSet<String> = manifest_dependencies(&pkg.path)
+                .into_iter()
+                .chain(
+                    pkg.cmd
+                        .values()
+                        .flat_map(|cmd| cmd.deps())
+                        .filter_map(|dep| dep.split(':').next().map(String::from)),
+                )
+                .filter(|dep| dep != name && names.contains(dep.as_str()))
+                .collect();
+            (name.clone(), deps)
+        })
+        .collect()
+}
+
+/// Dependency names declared in a package's Cargo.toml and package.json
+fn manifest_dependencies(dir: &Path) -> Vec<String> {
+    let mut deps = Vec::new();
+
+    if let Some(cargo) = fs::read_to_string(dir.join("Cargo.toml"))
+        .ok()
+        .and_then(|s| toml::from_str::<toml::Value>(&s).ok())
+    {
+        let mut tables: Vec<&toml::Value> =
+            ["dependencies", "dev-dependencies", "build-dependencies"]
+                .iter()
+                .filter_map(|key| cargo.get(key))
+                .collect();
+        if let Some(targets) = cargo.get("target").and_then(|t| t.as_table()) {
+            for target in targets.values() {
+                for key in ["dependencies", "dev-dependencies", "build-dependencies"] {
+                    tables.extend(target.get(key));
+                }
+            }
+        }
+        for table in tables.into_iter().filter_map(|t| t.as_table()) {
+            for (key, spec) in table {
+                // Renamed dependencies name the real crate in `package`
+                let name = spec.get("package").and_then(|p| p.as_str()).unwrap_or(key);
+                deps.push(name.to_string());
+            }
+        }
+    }
+
+    if let Some(pkg) = fs::read_to_string(dir.join("package.json"))
+        .ok()
+        .and_then(|s| serde_json::from_str::<serde_json::Value>(&s).ok())
+    {
+        for key in ["dependencies", "devDependencies", "peerDependencies"] {
+            if let Some(table) = pkg[key].as_object() {
+                // Package names drop the org scope ("@org/app" -> "app")
+                deps.extend(table.keys().map(|name| {
+                    name.strip_prefix('@')
+                        .and_then(|scoped| scoped.split_once('/'))
+                        .map(|(_, name)| name.to_string())
+                        .unwrap_or_else(|| name.clone())
+                }));
+            }
+        }
+    }
+
+    deps
+}
+
+/// Files changed since the merge base with `base`, including uncommitted and untracked files
+pub fn changed_files(repo: &Path, base: &str) -> Result<Vec<PathBuf>> {
+    let merge_base = CmdBuilder::new("git")
+        .args(["merge-base", base, "HEAD"])
+        .cwd(repo)
+        .capture_stdout()
+        .run_capture()
+        .map_err(|_| {
+            anyhow!(
+                "Can't find a merge base with '{}' (pass --base <ref>)",
+                base
+            )
+        })?
+        .stdout_string()
+        .trim()
+        .to_string();
+
+    let diff = CmdBuilder::new("git")
+        .args(["diff", "--name-only", &merge_base])
+        .cwd(repo)
+        .capture_stdout()
+        .run_capture()?;
+    let untracked = CmdBuilder::new("git")
+        .args(["ls-files", "--others", "--exclude-standard"])
+        .cwd(repo)
+        .capture_stdout()
+        .run_capture()?;
+
+    let files: BTreeSet<PathBuf> = diff
+        .stdout_lines()
+        .into_iter()
+        .chain(untracked.stdout_lines())
+        .map(PathBuf::from)
+        .collect();
+    Ok(files.into_iter().collect())
+}
+
+/// Packages containing the given repo-relative files
+///
+/// Files in nested packages belong to the innermost one. Returns None when a
+/// workspace-wide file changed, meaning every package is affected.
+pub fn packages_for_files(config: &Config, files: &[PathBuf]) -> Option<BTreeSet<String>> {
+    let mut packages = BTreeSet::new();
+    for file in files {
+        if WORKSPACE_FILES.iter().any(|f| file == Path::new(f)) {
+            return None;
+        }
+        if let Some(name) = config.package_at(&config.repo_root.join(file)) {
+            packages.insert(name.to_string());
+        }
+    }
+    Some(packages)
+}
+
+/// `changed` plus every package that transitively depends on one of them
+pub fn with_dependents(
+    graph: &HashMap<String, HashSet<String>>,
+    changed: &BTreeSet<String>,
+) -> BTreeSet<String> {
+    let mut affected = changed.clone();
+    loop {
+        let dependents: Vec<String> = graph
+            .iter()
+            .filter(|(name, deps)| {
+                !affected.contains(*name) && deps.iter().any(|d| affected.contains(d))
+            })
+            .map(|(name, _)| name.clone())
+            .collect();
+        if dependents.is_empty() {
+            return affected;
+        }
+        affected.extend(dependents);
+    }
+}
+
+/// Packages affected by changes since `base` (defaults to `[git] default_pr_base`)
+pub fn affected_packages(ctx: &AppContext, base: Option<&str>) -> Result<Vec<String>> {
+    if ctx.config.packages.is_empty() {
+        return Err(anyhow!(
+            "No packages configured; --affected needs [workspaces] packages in .dev/config.toml"
+        ));
+    }
+    let base = base.unwrap_or(&ctx.config.global.git.default_pr_base);
+    let files = changed_files(&ctx.repo, base)?;
+
+    let Some(changed) = packages_for_files(&ctx.config, &files) else {
+        ctx.print_info(&format!(
+            "Workspace files changed since {}; all packages affected",
+            base
+        ));
+        let mut all: Vec<String> = ctx.config.packages.keys().cloned().collect();
+        all.sort();
+        return Ok(all);
+    };
+
+    let affected = with_dependents(&dependency_graph(&ctx.config), &changed);
+    let dependents: Vec<&String> = affected.difference(&changed).collect();
+    ctx.print_info(&format!(
+        "Changed since {}: {}",
+        base,
+        if changed.is_empty() {
+            "no packages".to_string()
+        } else {
+            changed.iter().cloned().collect::<Vec<_>>().join(", ")
+        }
+    ));
+    if !dependents.is_empty() {
+        ctx.print_info(&format!(
+            "Dependents: {}",
+            dependents
+                .iter()
+                .map(|s| s.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+    Ok(affected.into_iter().collect())
+}
+
+/// Affected packages, narrowed to `only` when given
+pub fn select_affected(
+    ctx: &AppContext,
+    base: Option<&str>,
+    only: &[String],
+) -> Result<Vec<String>> {
+    let affected = affected_packages(ctx, base)?;
+    Ok(affected
+        .into_iter()
+        .filter(|name| only.is_empty() || only.contains(name))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use devkit_core::config::PackageConfig;
+
+    fn set(names: &[&str]) -> BTreeSet<String> {
+        names.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_with_dependents_is_transitive() {
+        let graph: HashMap<String, HashSet<String>> = [
+            ("core", vec![]),
+            ("api", vec!["core"]),
+            ("web", vec!["api"]),
+            ("docs", vec![]),
+        ]
+        .into_iter()
+        .map(|(name, deps)| {
+            (
+                name.to_string(),
+                deps.into_iter().map(String::from).collect(),
+            )
+        })
+        .collect();
+
+        assert_eq!(
+            with_dependents(&graph, &set(&["core"])),
+            set(&["api", "core", "web"])
+        );
+        assert_eq!(with_dependents(&graph, &set(&["web"])), set(&["web"]));
+    }
+
+    #[test]
+    fn test_packages_for_files() {
+        let mut config = Config {
+            repo_root: PathBuf::from("/repo"),
+            ..Default::default()
+        };
+        for (name, path) in [
+            ("app", "/repo/packages/app"),
+            ("plugin", "/repo/packages/app/plugin"),
+        ] {
+            config.packages.insert(
+                name.to_string(),
+                PackageConfig {
+                    path: PathBuf::from(path),
+                    name: name.to_string(),
+                    ..Default::default()
+                },
+            );
+        }
+
+        let files = vec![
+            PathBuf::from("packages/app/plugin/src/lib.rs"),
+            PathBuf::from("README.md"),
+        ];
+        assert_eq!(packages_for_files(&config, &files), Some(set(&["plugin"])));
+        assert_eq!(
+            packages_for_files(&config, &[PathBuf::from("Cargo.lock")]),
+            None
+        );
+    }
+}
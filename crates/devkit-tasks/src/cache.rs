@@ -0,0 +1,152 @@
+//! Skipping package commands whose inputs haven't changed
+//!
+//! A command with `inputs` is hashed before it runs: the command line and the
+//! variables it runs with, plus the path and contents of every file the globs match
+//! in the package, and the hashes of the commands it depends on. After a successful
+//! run the hash is saved in `.dev/cache/tasks.json`, and while it still matches, later
+//! runs skip the command:
+//!
+//! ```toml
+//! [cmd.build]
+//! default = "npx tsc"
+//! inputs = ["src/**", "tsconfig.json"]
+//! ```
+
+use anyhow::{Context, Result};
+use devkit_core::discovery_cache::cache_dir;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+
+const CACHE_FILE: &str = "tasks.json";
+
+/// Hash of a command line, its variables and the files its `inputs` globs match
+/// under `dir`
+pub fn inputs_hash(
+    dir: &Path,
+    cmd_str: &str,
+    env: &[(String, String)],
+    inputs: &[String],
+) -> Result<String> {
+    let mut files = BTreeSet::new();
+    for input in inputs {
+        // A trailing `**` only matches directories
+        let input = match input.strip_suffix("**") {
+            Some(_) => format!("{}/*", input),
+            None => input.clone(),
+        };
+        let pattern = dir.join(&input);
+        let paths = glob::glob(&pattern.to_string_lossy())
+            .with_context(|| format!("Invalid inputs pattern '{}'", input))?;
+        for path in paths.flatten() {
+            // `**` matches every directory on its own, so only walk ones named directly
+            if path.is_file() || !input.contains("**") {
+                add_files(&path, &mut files);
+            }
+        }
+    }
+
+    // SipHash with fixed keys: the same between runs, and a toolchain that changes it
+    // only costs one rerun
+    let mut hasher = DefaultHasher::new();
+    cmd_str.hash(&mut hasher);
+    env.hash(&mut hasher);
+    for file in &files {
+        file.strip_prefix(dir).unwrap_or(file).hash(&mut hasher);
+        fs::read(file)
+            .with_context(|| format!("Failed to read {}", file.display()))?
+            .hash(&mut hasher);
+    }
+    Ok(format!("{:016x}", hasher.finish()))
+}
+
+/// `hash` folded with the hashes of the commands it depends on, so a dependency whose
+/// inputs changed invalidates its dependents too
+pub fn with_dependencies(hash: &str, dependencies: &[&str]) -> String {
+    let mut hasher = DefaultHasher::new();
+    hash.hash(&mut hasher);
+    dependencies.hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+/// `path`, or the files under it when it's a directory
+fn add_files(path: &Path, files: &mut BTreeSet<PathBuf>) {
+    if path.is_file() {
+        files.insert(path.to_path_buf());
+    } else if let Ok(entries) = fs::read_dir(path) {
+        for entry in entries.flatten() {
+            add_files(&entry.path(), files);
+        }
+    }
+}
+
+/// Whether `key` (`<package>:<cmd>`) last succeeded with inputs hashing to `hash`
+pub fn is_fresh(repo: &Path, key: &str, hash: &str) -> bool {
+    load(repo).get(key).is_some_and(|cached| cached == hash)
+}
+
+/// Remember that `key` succeeded with inputs hashing to `hash`
+pub fn record(repo: &Path, key: &str, hash: &str) -> Result<()> {
+    let mut hashes = load(repo);
+    hashes.insert(key.to_string(), hash.to_string());
+    let path = cache_dir(repo)?.join(CACHE_FILE);
+    fs::write(&path, serde_json::to_string_pretty(&hashes)?)
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Saved hashes by key, empty when the file is missing or unreadable
+fn load(repo: &Path) -> BTreeMap<String, String> {
+    fs::read_to_string(repo.join(".dev/cache").join(CACHE_FILE))
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inputs_hash() {
+        let repo = std::env::temp_dir().join(format!("devkit-task-cache-{}", std::process::id()));
+        let pkg = repo.join("packages/api");
+        fs::create_dir_all(pkg.join("src/nested")).unwrap();
+        fs::write(pkg.join("src/main.ts"), "export {}").unwrap();
+        fs::write(pkg.join("src/nested/util.ts"), "export const a = 1").unwrap();
+        fs::write(pkg.join("README.md"), "docs").unwrap();
+
+        let inputs = ["src/**".to_string()];
+        let hash = inputs_hash(&pkg, "npx tsc", &[], &inputs).unwrap();
+        assert!(!is_fresh(&repo, "api:build", &hash));
+        assert_ne!(
+            with_dependencies(&hash, &["1"]),
+            with_dependencies(&hash, &["2"])
+        );
+        record(&repo, "api:build", &hash).unwrap();
+        assert!(is_fresh(&repo, "api:build", &hash));
+
+        // Files outside the inputs don't matter; inputs and the command line do
+        fs::write(pkg.join("README.md"), "more docs").unwrap();
+        assert_eq!(inputs_hash(&pkg, "npx tsc", &[], &inputs).unwrap(), hash);
+        assert_ne!(
+            inputs_hash(&pkg, "npx tsc --watch", &[], &inputs).unwrap(),
+            hash
+        );
+        let env = [("NODE_ENV".to_string(), "production".to_string())];
+        assert_ne!(inputs_hash(&pkg, "npx tsc", &env, &inputs).unwrap(), hash);
+        fs::write(pkg.join("src/main.ts"), "export const b = 1").unwrap();
+        let changed = inputs_hash(&pkg, "npx tsc", &[], &inputs).unwrap();
+        assert_ne!(changed, hash);
+        fs::write(pkg.join("src/nested/util.ts"), "export const a = 2").unwrap();
+        assert_ne!(inputs_hash(&pkg, "npx tsc", &[], &inputs).unwrap(), changed);
+        // A directory input covers everything in it
+        assert_eq!(
+            inputs_hash(&pkg, "npx tsc", &[], &["src".to_string()]).unwrap(),
+            inputs_hash(&pkg, "npx tsc", &[], &inputs).unwrap()
+        );
+
+        fs::remove_dir_all(&repo).ok();
+    }
+}
@@ -1,8 +1,20 @@
 //! Command builder pattern for running external processes
+//!
+//! Besides running with inherited IO or capturing output, a command can stream its
+//! output line by line while it's captured ([`CmdBuilder::run_streaming`],
+//! [`CmdBuilder::run_tee`]), be killed after a [`CmdBuilder::timeout`], and run
+//! attached to a pseudo-terminal ([`CmdBuilder::pty`]) for tools that only color or
+//! show progress when they think a person is watching.
 
 use anyhow::{anyhow, Context, Result};
-use std::path::PathBuf;
-use std::process::{Command, Output, Stdio};
+use devkit_core::oplog::{LoggedCommand, Operation};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, ExitStatus, Output, Stdio};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
 
 /// Fluent builder for running external commands
 #[derive(Default)]
@@ -14,6 +26,22 @@ pub struct CmdBuilder {
     inherit_io: bool,
     capture_stdout: bool,
     capture_stderr: bool,
+    timeout: Option<Duration>,
+    pty: bool,
+}
+
+/// Which stream a line of output came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stream {
+    Stdout,
+    Stderr,
+}
+
+/// A line of output, without its line ending
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputLine {
+    pub stream: Stream,
+    pub text: String,
 }
 
 impl CmdBuilder {
@@ -48,6 +76,18 @@ impl CmdBuilder {
         self
     }
 
+    /// Set several variables, e.g. from a `HashMap`; later values win
+    pub fn envs<I, K, V>(mut self, vars: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.envs
+            .extend(vars.into_iter().map(|(k, v)| (k.into(), v.into())));
+        self
+    }
+
     pub fn inherit_io(mut self) -> Self {
         self.inherit_io = true;
         self
@@ -63,6 +103,21 @@ impl CmdBuilder {
         self
     }
 
+    /// Kill the command if it's still running after `timeout`
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Run attached to a pseudo-terminal when output is captured or streamed
+    ///
+    /// stdout and stderr then arrive merged as [`Stream::Stdout`], with the tool's
+    /// colors and progress output. Without PTY support (Windows) this does nothing.
+    pub fn pty(mut self) -> Self {
+        self.pty = true;
+        self
+    }
+
     fn build_command(&self) -> Command {
         let mut cmd = Command::new(&self.program);
         cmd.args(&self.args);
@@ -75,6 +130,18 @@ impl CmdBuilder {
         cmd
     }
 
+    fn describe(&self) -> String {
+        format!("{} {}", self.program, self.args.join(" "))
+    }
+
+    fn timed_out(&self) -> anyhow::Error {
+        anyhow!(
+            "{} timed out after {}s",
+            self.program,
+            self.timeout.unwrap_or_default().as_secs_f32()
+        )
+    }
+
     pub fn run(&self) -> Result<i32> {
         let mut cmd = self.build_command();
         if self.inherit_io {
@@ -82,30 +149,50 @@ impl CmdBuilder {
                 .stdout(Stdio::inherit())
                 .stderr(Stdio::inherit());
         }
-        let status = cmd.status().with_context(|| {
-            format!("failed to start: {} {}", self.program, self.args.join(" "))
-        })?;
-        Ok(status.code().unwrap_or(1))
+        let Some(timeout) = self.timeout else {
+            let status = cmd
+                .logged_status()
+                .with_context(|| format!("failed to start: {}", self.describe()))?;
+            return Ok(status.code().unwrap_or(1));
+        };
+
+        let op = Operation::start(&cmd);
+        let mut child = cmd
+            .spawn()
+            .with_context(|| format!("failed to start: {}", self.describe()))?;
+        let status = wait_until(&mut child, Instant::now() + timeout);
+        op.finish_status(&status);
+        match status {
+            Ok(status) => Ok(status.code().unwrap_or(1)),
+            Err(e) if e.kind() == io::ErrorKind::TimedOut => Err(self.timed_out()),
+            Err(e) => Err(e).with_context(|| format!("failed to wait for: {}", self.describe())),
+        }
     }
 
-    pub fn run_capture(&self) -> Result<CmdOutput> {
+    /// Start the command in the background with output redirected to `log_file`
+    ///
+    /// Returns the child's PID. The child is not waited on and keeps running after devkit exits.
+    /// On Unix it leads its own process group, so Ctrl-C in this terminal doesn't reach it
+    /// and the PID also stops whatever it starts (see [`crate::daemon`]).
+    pub fn spawn_background(&self, log_file: &Path) -> Result<u32> {
+        let log = File::create(log_file)
+            .with_context(|| format!("failed to create log file: {}", log_file.display()))?;
+
         let mut cmd = self.build_command();
-        // Explicitly set stdin to null to prevent hanging on interactive prompts
+        #[cfg(unix)]
+        std::os::unix::process::CommandExt::process_group(&mut cmd, 0);
         cmd.stdin(Stdio::null());
-        cmd.stdout(Stdio::piped());
-        cmd.stderr(Stdio::piped());
+        cmd.stdout(log.try_clone()?);
+        cmd.stderr(log);
 
-        let output: Output = cmd
-            .spawn()
-            .with_context(|| format!("failed to start: {} {}", self.program, self.args.join(" ")))?
-            .wait_with_output()
-            .with_context(|| {
-                format!(
-                    "failed to wait for: {} {}",
-                    self.program,
-                    self.args.join(" ")
-                )
-            })?;
+        let child = cmd
+            .logged_spawn()
+            .with_context(|| format!("failed to start: {}", self.describe()))?;
+        Ok(child.id())
+    }
+
+    pub fn run_capture(&self) -> Result<CmdOutput> {
+        let output = self.execute(&mut |_| {})?;
 
         if !output.status.success() {
             let stderr = String::from_utf8_lossy(&output.stderr);
@@ -122,14 +209,203 @@ impl CmdBuilder {
         }
         Ok(CmdOutput {
             stdout,
+            stderr: output.stderr,
             code: output.status.code().unwrap_or(0),
         })
     }
+
+    /// Capture the output while passing each line to `on_line` as it arrives
+    ///
+    /// Unlike [`CmdBuilder::run_capture`], a non-zero exit isn't an error; check
+    /// [`CmdOutput::code`].
+    pub fn run_streaming(&self, mut on_line: impl FnMut(&OutputLine)) -> Result<CmdOutput> {
+        let output = self.execute(&mut on_line)?;
+        Ok(CmdOutput {
+            stdout: output.stdout,
+            stderr: output.stderr,
+            code: output.status.code().unwrap_or(1),
+        })
+    }
+
+    /// Capture the output while also printing it (tee)
+    pub fn run_tee(&self) -> Result<CmdOutput> {
+        self.run_streaming(|line| match line.stream {
+            Stream::Stdout => println!("{}", line.text),
+            Stream::Stderr => eprintln!("{}", line.text),
+        })
+    }
+
+    /// Run with captured output, reading stdout and stderr on their own threads
+    fn execute(&self, on_line: &mut dyn FnMut(&OutputLine)) -> Result<Output> {
+        let mut cmd = self.build_command();
+        // Explicitly set stdin to null to prevent hanging on interactive prompts
+        cmd.stdin(Stdio::null());
+
+        let (tx, rx) = mpsc::channel();
+        let op = Operation::start(&cmd);
+        let mut child = if self.pty {
+            self.spawn_in_pty(&mut cmd, &tx)
+        } else {
+            cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
+            cmd.spawn().map(|mut child| {
+                read_lines(child.stdout.take(), Stream::Stdout, tx.clone());
+                read_lines(child.stderr.take(), Stream::Stderr, tx.clone());
+                child
+            })
+        }
+        .with_context(|| format!("failed to start: {}", self.describe()))?;
+        // Our copies of the child's stdio (the PTY's terminal side included) must be
+        // closed, or the readers never see the end of the output
+        drop(cmd);
+        drop(tx);
+
+        let deadline = self.timeout.map(|timeout| Instant::now() + timeout);
+        let (mut stdout, mut stderr) = (Vec::new(), Vec::new());
+        loop {
+            let received = match deadline {
+                Some(deadline) => {
+                    rx.recv_timeout(deadline.saturating_duration_since(Instant::now()))
+                }
+                None => rx.recv().map_err(|_| RecvTimeoutError::Disconnected),
+            };
+            match received {
+                Ok(line) => {
+                    let buffer = match line.stream {
+                        Stream::Stdout => &mut stdout,
+                        Stream::Stderr => &mut stderr,
+                    };
+                    buffer.extend_from_slice(line.text.as_bytes());
+                    buffer.push(b'\n');
+                    on_line(&line);
+                }
+                // All output read, or out of time (the wait below kills the child)
+                Err(_) => break,
+            }
+        }
+
+        let output = match deadline {
+            Some(deadline) => wait_until(&mut child, deadline),
+            None => child.wait(),
+        }
+        .map(|status| Output {
+            status,
+            stdout,
+            stderr,
+        });
+        op.finish_output(&output);
+        match output {
+            Ok(output) => Ok(output),
+            Err(e) if e.kind() == io::ErrorKind::TimedOut => Err(self.timed_out()),
+            Err(e) => Err(e).with_context(|| format!("failed to wait for: {}", self.describe())),
+        }
+    }
+
+    #[cfg(unix)]
+    fn spawn_in_pty(&self, cmd: &mut Command, tx: &Sender<OutputLine>) -> io::Result<Child> {
+        let pty = pty::open()?;
+        cmd.stdout(pty.terminal.try_clone()?).stderr(pty.terminal);
+        let child = cmd.spawn()?;
+        read_lines(Some(pty.controller), Stream::Stdout, tx.clone());
+        Ok(child)
+    }
+
+    #[cfg(not(unix))]
+    fn spawn_in_pty(&self, cmd: &mut Command, tx: &Sender<OutputLine>) -> io::Result<Child> {
+        cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
+        let mut child = cmd.spawn()?;
+        read_lines(child.stdout.take(), Stream::Stdout, tx.clone());
+        read_lines(child.stderr.take(), Stream::Stderr, tx.clone());
+        Ok(child)
+    }
+}
+
+/// Send `source`'s lines to `tx` from a background thread until it closes
+fn read_lines(source: Option<impl Read + Send + 'static>, stream: Stream, tx: Sender<OutputLine>) {
+    let Some(source) = source else {
+        return;
+    };
+    thread::spawn(move || {
+        let mut reader = BufReader::new(source);
+        let mut line = Vec::new();
+        // A PTY reports EIO once the child exits, which ends the output like EOF
+        while reader.read_until(b'\n', &mut line).unwrap_or(0) > 0 {
+            let text = String::from_utf8_lossy(&line);
+            let text = text.trim_end_matches(['\n', '\r']).to_string();
+            if tx.send(OutputLine { stream, text }).is_err() {
+                break;
+            }
+            line.clear();
+        }
+    });
+}
+
+/// Wait for the child, killing it at `deadline` (reported as `TimedOut`)
+fn wait_until(child: &mut Child, deadline: Instant) -> io::Result<ExitStatus> {
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(status);
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(io::Error::new(io::ErrorKind::TimedOut, "timed out"));
+        }
+        thread::sleep(Duration::from_millis(20));
+    }
+}
+
+#[cfg(unix)]
+mod pty {
+    use std::fs::File;
+    use std::io;
+    use std::os::fd::{FromRawFd, OwnedFd};
+
+    /// A pseudo-terminal: the child writes to `terminal`, devkit reads `controller`
+    pub struct Pty {
+        pub controller: File,
+        pub terminal: OwnedFd,
+    }
+
+    /// Open a PTY the size of devkit's terminal (80x24 when there isn't one)
+    pub fn open() -> io::Result<Pty> {
+        let (rows, cols) = console::Term::stdout().size_checked().unwrap_or((24, 80));
+        let mut size = libc::winsize {
+            ws_row: rows,
+            ws_col: cols,
+            ws_xpixel: 0,
+            ws_ypixel: 0,
+        };
+        let (mut controller, mut terminal) = (0, 0);
+        // SAFETY: openpty writes the two descriptors it opens; nothing else is
+        // retained from the pointers passed in
+        let result = unsafe {
+            libc::openpty(
+                &mut controller,
+                &mut terminal,
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                // `*const` on Linux, `*mut` on macOS
+                std::ptr::addr_of_mut!(size),
+            )
+        };
+        if result != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // SAFETY: both descriptors were just opened and are owned by nothing else
+        unsafe {
+            Ok(Pty {
+                controller: File::from_raw_fd(controller),
+                terminal: OwnedFd::from_raw_fd(terminal),
+            })
+        }
+    }
 }
 
 /// Output from a captured command execution
+#[derive(Debug)]
 pub struct CmdOutput {
     pub stdout: Vec<u8>,
+    pub stderr: Vec<u8>,
     pub code: i32,
 }
 
@@ -146,3 +422,47 @@ impl CmdOutput {
             .collect()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_streaming() {
+        let mut lines = Vec::new();
+        let output = CmdBuilder::new("sh")
+            .args(["-c", "echo one; echo two >&2; echo $GREETING; exit 3"])
+            .envs([("GREETING", "hi")])
+            .run_streaming(|line| lines.push(line.clone()))
+            .unwrap();
+
+        assert_eq!(output.code, 3);
+        assert_eq!(output.stdout_lines(), ["one", "hi"]);
+        assert_eq!(String::from_utf8_lossy(&output.stderr), "two\n");
+        assert!(lines.contains(&OutputLine {
+            stream: Stream::Stderr,
+            text: "two".to_string()
+        }));
+        assert_eq!(lines.len(), 3);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_timeout_and_pty() {
+        let started = Instant::now();
+        let result = CmdBuilder::new("sleep")
+            .arg("5")
+            .timeout(Duration::from_millis(200))
+            .run_capture();
+        assert!(result.unwrap_err().to_string().contains("timed out"));
+        assert!(started.elapsed() < Duration::from_secs(2));
+
+        let output = CmdBuilder::new("sh")
+            .args(["-c", "[ -t 1 ] && echo tty || echo pipe"])
+            .pty()
+            .run_capture()
+            .unwrap();
+        assert_eq!(output.stdout_lines(), ["tty"]);
+    }
+}
@@ -0,0 +1,264 @@
+//! Background dev processes
+//!
+//! `devkit services start` runs package commands — `[cmd.dev]`, or another one named
+//! as `<package>:<cmd>` — detached from the terminal, so a multi-service stack runs
+//! without tmux. Each is recorded in `.dev/run/services.json` and logs to
+//! `.dev/run/<package>-<cmd>.log`. Entries whose process has exited are pruned on every
+//! load; stopping a service stops everything it started, such as the server behind
+//! `npm run dev`.
+
+use crate::cmd_builder::CmdBuilder;
+use anyhow::{anyhow, bail, Context, Result};
+use devkit_core::process::{current_timestamp, process_alive, terminate, RUN_DIR};
+use devkit_core::wait::{wait_for, WaitOptions};
+use devkit_core::{AppContext, Config};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+/// Command a service runs when none is named
+pub const DEFAULT_CMD: &str = "dev";
+
+const STATE_FILE: &str = "services.json";
+
+/// A package command run as a service
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Service {
+    pub package: String,
+    pub cmd: String,
+}
+
+impl Service {
+    /// `api` for `[cmd.dev]` in api, `api:worker` for `[cmd.worker]`
+    pub fn parse(name: &str) -> Self {
+        let (package, cmd) = name.split_once(':').unwrap_or((name, DEFAULT_CMD));
+        Self {
+            package: package.to_string(),
+            cmd: cmd.to_string(),
+        }
+    }
+
+    /// The inverse of [`Service::parse`]
+    pub fn name(&self) -> String {
+        if self.cmd == DEFAULT_CMD {
+            self.package.clone()
+        } else {
+            format!("{}:{}", self.package, self.cmd)
+        }
+    }
+
+    /// Where the service's output goes
+    pub fn log_file(&self, repo: &Path) -> PathBuf {
+        repo.join(RUN_DIR)
+            .join(format!("{}-{}.log", self.package, self.cmd))
+    }
+}
+
+/// Every package with `[cmd.dev]`, sorted
+pub fn services(config: &Config) -> Vec<Service> {
+    let mut services: Vec<Service> = config
+        .packages_with_cmd(DEFAULT_CMD)
+        .into_iter()
+        .map(|(package, _, _)| Service {
+            package: package.to_string(),
+            cmd: DEFAULT_CMD.to_string(),
+        })
+        .collect();
+    services.sort();
+    services
+}
+
+/// A service running in the background
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Daemon {
+    pub pid: u32,
+    /// The command as run, after `{services.<name>}` expansion
+    pub command: String,
+    pub log_file: PathBuf,
+    pub started_at: u64,
+}
+
+/// Running services keyed by [`Service::name`]
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct Daemons {
+    pub services: BTreeMap<String, Daemon>,
+}
+
+impl Daemons {
+    /// Load state, dropping entries whose process is no longer alive
+    pub fn load(repo: &Path) -> Result<Self> {
+        let path = state_path(repo);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let contents = fs::read_to_string(&path)?;
+        let mut state: Daemons = serde_json::from_str(&contents)
+            .with_context(|| format!("Invalid services state file: {}", path.display()))?;
+
+        let before = state.services.len();
+        state.services.retain(|_, daemon| process_alive(daemon.pid));
+        if state.services.len() != before {
+            state.save(repo)?;
+        }
+        Ok(state)
+    }
+
+    pub fn save(&self, repo: &Path) -> Result<()> {
+        fs::create_dir_all(repo.join(RUN_DIR))?;
+        fs::write(state_path(repo), serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+fn state_path(repo: &Path) -> PathBuf {
+    repo.join(RUN_DIR).join(STATE_FILE)
+}
+
+/// Start a service in the background once its `wait` targets are ready
+pub fn start(ctx: &AppContext, service: &Service) -> Result<Daemon> {
+    let mut state = Daemons::load(&ctx.repo)?;
+    if let Some(running) = state.services.get(&service.name()) {
+        bail!(
+            "{} is already running (pid {})",
+            service.name(),
+            running.pid
+        );
+    }
+
+    let package = ctx
+        .config
+        .packages
+        .get(&service.package)
+        .ok_or_else(|| anyhow!("No package named '{}'", service.package))?;
+    let entry = package
+        .cmd
+        .get(&service.cmd)
+        .ok_or_else(|| anyhow!("{} has no [cmd.{}]", service.package, service.cmd))?;
+
+    let services = &ctx.config.global.services;
+    let command = services.expand(entry.default_cmd());
+    let env: Vec<(String, String)> = entry
+        .env_vars(&package.path)?
+        .into_iter()
+        .map(|(key, value)| (key, services.expand(&value)))
+        .collect();
+    wait_for(ctx, entry.wait(), &WaitOptions::default())?;
+
+    let parts: Vec<&str> = command.split_whitespace().collect();
+    let Some((program, args)) = parts.split_first() else {
+        bail!("Empty command for {}", service.name());
+    };
+    fs::create_dir_all(ctx.repo.join(RUN_DIR))?;
+    let log_file = service.log_file(&ctx.repo);
+    let pid = CmdBuilder::new(*program)
+        .args(args.iter().copied())
+        .cwd(&package.path)
+        .envs(env)
+        .spawn_background(&log_file)?;
+
+    let daemon = Daemon {
+        pid,
+        command,
+        log_file,
+        started_at: current_timestamp(),
+    };
+    state.services.insert(service.name(), daemon.clone());
+    state.save(&ctx.repo)?;
+    Ok(daemon)
+}
+
+/// Stop a running service and everything it started
+pub fn stop(repo: &Path, service: &Service) -> Result<Daemon> {
+    let mut state = Daemons::load(repo)?;
+    let daemon = state
+        .services
+        .remove(&service.name())
+        .ok_or_else(|| anyhow!("{} is not running", service.name()))?;
+
+    terminate(daemon.pid)?;
+    state.save(repo)?;
+    Ok(daemon)
+}
+
+/// Print the last `lines` lines of a log, then with `follow`, what's appended until
+/// interrupted
+pub fn tail(path: &Path, lines: usize, follow: bool) -> Result<()> {
+    let mut file = File::open(path).with_context(|| format!("No log at {}", path.display()))?;
+    let mut contents = Vec::new();
+    file.read_to_end(&mut contents)?;
+    let text = String::from_utf8_lossy(&contents);
+    let all: Vec<&str> = text.lines().collect();
+    for line in &all[all.len().saturating_sub(lines)..] {
+        println!("{}", line);
+    }
+    if !follow {
+        return Ok(());
+    }
+
+    let mut stdout = std::io::stdout();
+    loop {
+        // Restarting the service truncates its log
+        if fs::metadata(path).map(|meta| meta.len()).unwrap_or(0) < file.stream_position()? {
+            file.seek(SeekFrom::Start(0))?;
+        }
+        contents.clear();
+        if file.read_to_end(&mut contents)? == 0 {
+            thread::sleep(Duration::from_millis(500));
+            continue;
+        }
+        stdout.write_all(&contents)?;
+        stdout.flush()?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_service_names() {
+        let service = Service::parse("api");
+        assert_eq!(service.cmd, "dev");
+        assert_eq!(service.name(), "api");
+
+        let service = Service::parse("api:worker");
+        assert_eq!(
+            (service.package.as_str(), service.cmd.as_str()),
+            ("api", "worker")
+        );
+        assert_eq!(service.name(), "api:worker");
+        assert_eq!(
+            service.log_file(Path::new("/repo")),
+            Path::new("/repo/.dev/run/api-worker.log")
+        );
+    }
+
+    #[test]
+    fn test_load_prunes_exited_services() {
+        let repo = std::env::temp_dir().join(format!("devkit-daemon-{}", std::process::id()));
+        let daemon = |pid| Daemon {
+            pid,
+            command: "npm run dev".to_string(),
+            log_file: PathBuf::from("api-dev.log"),
+            started_at: 0,
+        };
+        let mut state = Daemons::default();
+        state
+            .services
+            .insert("api".to_string(), daemon(std::process::id()));
+        state
+            .services
+            .insert("web".to_string(), daemon(999_999_999));
+        state.save(&repo).unwrap();
+
+        let state = Daemons::load(&repo).unwrap();
+        assert_eq!(state.services.keys().collect::<Vec<_>>(), ["api"]);
+
+        fs::remove_dir_all(&repo).ok();
+    }
+}
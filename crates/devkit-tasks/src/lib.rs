@@ -1,11 +1,18 @@
 //! Task discovery and execution engine for devkit
 
+pub mod affected;
+pub mod cache;
 pub mod cmd_builder;
+pub mod daemon;
+pub mod pipeline;
+pub mod procfile;
 pub mod runner;
 pub mod template;
 pub mod watch;
 
-pub use cmd_builder::CmdBuilder;
+pub use affected::{affected_packages, select_affected};
+pub use cmd_builder::{CmdBuilder, OutputLine, Stream};
+pub use pipeline::{run_pipeline, StepResult, StepStatus};
 pub use runner::{list_commands, print_results, run_cmd, CmdOptions, CmdResult};
 pub use template::{extract_vars, resolve_template};
 pub use watch::{watch_and_run, WatchConfig};
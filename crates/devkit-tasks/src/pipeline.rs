@@ -0,0 +1,154 @@
+//! Pipelines: named lists of steps run in order
+//!
+//! ```toml
+//! [pipeline.ci]
+//! description = "Full local CI"
+//! steps = [
+//!     { docker = "up -d postgres" },
+//!     { cmd = "lint" },
+//!     { cmd = "test", packages = ["api"] },
+//!     { name = "e2e", run = "./scripts/e2e.sh", continue_on_error = true },
+//! ]
+//! ```
+//!
+//! `cmd` steps run a package command like `devkit cmd`; `docker` steps run `docker
+//! compose` with the given arguments and `run` steps a shell command, both in the repo
+//! root with `DEVKIT_PIPELINE` set. A failing step stops the pipeline, and the steps
+//! after it are skipped, unless the step sets `continue_on_error`, the pipeline sets
+//! `fail_fast = false` or the run keeps going.
+
+use crate::runner::{run_cmd, CmdOptions};
+use anyhow::{anyhow, bail, Result};
+use devkit_core::config::{PipelineAction, PipelineStep};
+use devkit_core::hooks::shell_command;
+use devkit_core::oplog::LoggedCommand;
+use devkit_core::utils::docker_compose_program;
+use devkit_core::validation::compose_files;
+use devkit_core::AppContext;
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+/// How a step went
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StepStatus {
+    Passed,
+    Failed,
+    /// Not run because an earlier step failed
+    Skipped,
+}
+
+/// Outcome of one pipeline step
+#[derive(Debug)]
+pub struct StepResult {
+    pub label: String,
+    pub status: StepStatus,
+    pub duration: Duration,
+    /// Why the step failed
+    pub error: Option<String>,
+}
+
+/// Run the steps of `[pipeline.<name>]`; `keep_going` runs every step despite failures
+pub fn run_pipeline(ctx: &AppContext, name: &str, keep_going: bool) -> Result<Vec<StepResult>> {
+    let Some(pipeline) = ctx.config.global.pipeline.get(name) else {
+        let names = ctx.config.global.pipeline.names();
+        if names.is_empty() {
+            bail!(
+                "No pipeline named '{}'. Add one to .dev/config.toml:\n\n\
+                 [pipeline.{}]\n\
+                 steps = [{{ cmd = \"lint\" }}, {{ cmd = \"test\" }}]",
+                name,
+                name
+            );
+        }
+        bail!(
+            "No pipeline named '{}' (available: {})",
+            name,
+            names.join(", ")
+        );
+    };
+
+    let mut results = Vec::new();
+    let mut stopped = false;
+    for (index, step) in pipeline.steps.iter().enumerate() {
+        let label = step.label();
+        if stopped {
+            results.push(StepResult {
+                label,
+                status: StepStatus::Skipped,
+                duration: Duration::ZERO,
+                error: None,
+            });
+            continue;
+        }
+
+        ctx.print_header(&format!(
+            "[{}/{}] {}",
+            index + 1,
+            pipeline.steps.len(),
+            label
+        ));
+        let started = Instant::now();
+        let outcome = run_step(ctx, name, step);
+        let duration = started.elapsed();
+
+        let error = outcome.err().map(|e| format!("{:#}", e));
+        if let Some(error) = &error {
+            ctx.print_error(&format!("✗ {}: {}", label, error));
+            stopped = pipeline.fail_fast && !keep_going && !step.continue_on_error;
+        }
+        results.push(StepResult {
+            label,
+            status: match error {
+                Some(_) => StepStatus::Failed,
+                None => StepStatus::Passed,
+            },
+            duration,
+            error,
+        });
+    }
+
+    Ok(results)
+}
+
+fn run_step(ctx: &AppContext, pipeline: &str, step: &PipelineStep) -> Result<()> {
+    match &step.action {
+        PipelineAction::Cmd(cmd_name) => {
+            let opts = CmdOptions {
+                packages: step.packages.clone(),
+                ..Default::default()
+            };
+            let failed: Vec<_> = run_cmd(ctx, cmd_name, &opts)?
+                .into_iter()
+                .filter(|result| !result.success)
+                .map(|result| result.package)
+                .collect();
+            if !failed.is_empty() {
+                bail!("failed in {}", failed.join(", "));
+            }
+            Ok(())
+        }
+        PipelineAction::Docker(args) => {
+            let (program, mut compose_args) = docker_compose_program()?;
+            for file in compose_files(&ctx.repo, &ctx.config.global) {
+                compose_args.extend(["-f".to_string(), file]);
+            }
+            compose_args.extend(args.split_whitespace().map(String::from));
+            let mut cmd = Command::new(program);
+            cmd.args(compose_args);
+            run_in_repo(ctx, pipeline, cmd)
+        }
+        PipelineAction::Run(command) => run_in_repo(ctx, pipeline, shell_command(command)),
+    }
+}
+
+fn run_in_repo(ctx: &AppContext, pipeline: &str, mut cmd: Command) -> Result<()> {
+    let status = cmd
+        .current_dir(&ctx.repo)
+        .env("DEVKIT_PIPELINE", pipeline)
+        .logged_status()?;
+    match status.code() {
+        Some(0) => Ok(()),
+        Some(code) => Err(anyhow!("exited with code {}", code)),
+        None => Err(anyhow!("was terminated")),
+    }
+}
@@ -0,0 +1,404 @@
+//! Procfile-style process runner
+//!
+//! `devkit up` runs the processes under `[run]` in .dev/config.toml — or, without
+//! one, those in a `Procfile` at the repo root — side by side, prefixing each line of
+//! output with the process name. When one exits the rest are stopped, unless it's
+//! set to restart; Ctrl-C stops everything, including what the processes started.
+//!
+//! ```toml
+//! [run]
+//! api = "cargo run -p api"
+//! web = { command = "npm run dev", cwd = "packages/web", restart = true }
+//! ```
+
+use crate::runner::prefix_style;
+use anyhow::{anyhow, bail, Context, Result};
+use console::Style;
+use devkit_core::oplog::LoggedCommand;
+use devkit_core::process::terminate;
+use devkit_core::AppContext;
+use std::fs;
+use std::io::{BufRead, BufReader, Read};
+use std::path::PathBuf;
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Once;
+use std::thread;
+use std::time::{Duration, Instant};
+
+static STOP: AtomicBool = AtomicBool::new(false);
+static CTRL_C: Once = Once::new();
+
+/// How long to wait before starting a crashed process again
+const RESTART_DELAY: Duration = Duration::from_secs(1);
+
+/// How long processes get to exit after SIGTERM before they're killed
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(10);
+
+/// A process to run
+#[derive(Debug, Clone, PartialEq)]
+pub struct Process {
+    pub name: String,
+    pub command: String,
+    pub cwd: PathBuf,
+    pub restart: bool,
+}
+
+/// The processes under `[run]`, sorted by name, or else those in the Procfile
+pub fn processes(ctx: &AppContext) -> Result<Vec<Process>> {
+    let run = &ctx.config.global.run.processes;
+    if !run.is_empty() {
+        let mut processes: Vec<Process> = run
+            .iter()
+            .map(|(name, entry)| Process {
+                name: name.clone(),
+                command: entry.command().to_string(),
+                cwd: ctx.repo.join(entry.cwd().unwrap_or(".")),
+                restart: entry.restart(),
+            })
+            .collect();
+        processes.sort_by(|a, b| a.name.cmp(&b.name));
+        return Ok(processes);
+    }
+
+    let path = ctx.repo.join("Procfile");
+    if !path.exists() {
+        bail!("Nothing to run: add a [run] section to .dev/config.toml or a Procfile");
+    }
+    let contents =
+        fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+    Ok(parse_procfile(&contents)
+        .map_err(|e| anyhow!("{}: {}", path.display(), e))?
+        .into_iter()
+        .map(|(name, command)| Process {
+            name,
+            command,
+            cwd: ctx.repo.clone(),
+            restart: false,
+        })
+        .collect())
+}
+
+/// `name: command` lines, in order; blank lines and `#` comments are skipped
+fn parse_procfile(contents: &str) -> std::result::Result<Vec<(String, String)>, String> {
+    let mut processes: Vec<(String, String)> = Vec::new();
+    for (number, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let Some((name, command)) = line.split_once(':') else {
+            return Err(format!("line {}: expected `name: command`", number + 1));
+        };
+        let name = name.trim();
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            return Err(format!(
+                "line {}: invalid process name '{}'",
+                number + 1,
+                name
+            ));
+        }
+        if processes.iter().any(|(existing, _)| existing == name) {
+            return Err(format!("line {}: duplicate process '{}'", number + 1, name));
+        }
+        processes.push((name.to_string(), command.trim().to_string()));
+    }
+    Ok(processes)
+}
+
+/// A line of output from the process at an index
+type Line = (usize, String);
+
+/// Where a process stands
+enum State {
+    Running(Child),
+    /// Waiting to be started again
+    Restarting(Instant),
+    Exited,
+}
+
+struct Runner<'a> {
+    ctx: &'a AppContext,
+    processes: Vec<Process>,
+    prefixes: Vec<String>,
+    states: Vec<State>,
+    tx: Sender<Line>,
+}
+
+/// Run `processes` until one exits (and isn't restarted) or Ctrl-C
+///
+/// With `restart_all`, every process restarts when it exits, as if set to restart.
+/// Errors when a process that stops the others exited unsuccessfully.
+pub fn up(ctx: &AppContext, processes: Vec<Process>, restart_all: bool) -> Result<()> {
+    if processes.is_empty() {
+        bail!("Nothing to run");
+    }
+    stop_on_ctrl_c()?;
+
+    let width = processes.iter().map(|p| p.name.len()).max().unwrap_or(0);
+    let prefixes = processes
+        .iter()
+        .enumerate()
+        .map(|(index, process)| {
+            prefix_style(index)
+                .apply_to(format!("{:<width$} |", process.name))
+                .to_string()
+        })
+        .collect();
+    let processes: Vec<Process> = processes
+        .into_iter()
+        .map(|process| Process {
+            command: ctx.config.global.services.expand(&process.command),
+            restart: process.restart || restart_all,
+            ..process
+        })
+        .collect();
+
+    let (tx, rx) = mpsc::channel();
+    let mut runner = Runner {
+        ctx,
+        states: processes.iter().map(|_| State::Exited).collect(),
+        processes,
+        prefixes,
+        tx,
+    };
+    for index in 0..runner.processes.len() {
+        runner.start(index);
+    }
+
+    let result = runner.supervise(&rx);
+    runner.shutdown(&rx);
+    result
+}
+
+impl Runner<'_> {
+    fn start(&mut self, index: usize) {
+        let process = &self.processes[index];
+        let status = format!("$ {}", process.command);
+        self.print_status(index, &status, Style::new().dim());
+        self.states[index] = match spawn(process, index, &self.tx) {
+            Ok(child) => State::Running(child),
+            Err(e) => {
+                self.print_status(index, &format!("{:#}", e), Style::new().red());
+                State::Exited
+            }
+        };
+    }
+
+    /// Print output and restart processes until it's time to stop everything
+    fn supervise(&mut self, rx: &Receiver<Line>) -> Result<()> {
+        loop {
+            if let Ok(line) = rx.recv_timeout(Duration::from_millis(100)) {
+                self.print_line(line);
+                for line in rx.try_iter() {
+                    self.print_line(line);
+                }
+            }
+            if STOP.load(Ordering::SeqCst) {
+                println!();
+                self.ctx.print_info("Stopping...");
+                return Ok(());
+            }
+
+            for index in 0..self.states.len() {
+                match &mut self.states[index] {
+                    State::Running(child) => {
+                        let Some(status) = child.try_wait()? else {
+                            continue;
+                        };
+                        // Print what it wrote before exiting first
+                        thread::sleep(Duration::from_millis(50));
+                        for line in rx.try_iter() {
+                            self.print_line(line);
+                        }
+                        let message = exit_message(&status);
+                        if self.processes[index].restart {
+                            self.print_status(
+                                index,
+                                &format!("{}, restarting", message),
+                                Style::new().yellow(),
+                            );
+                            self.states[index] = State::Restarting(Instant::now() + RESTART_DELAY);
+                            continue;
+                        }
+
+                        let style = match status.success() {
+                            true => Style::new().dim(),
+                            false => Style::new().red(),
+                        };
+                        self.print_status(index, &message, style);
+                        self.states[index] = State::Exited;
+                        let name = &self.processes[index].name;
+                        self.ctx
+                            .print_info(&format!("{} exited, stopping the rest", name));
+                        if !status.success() {
+                            bail!("{} {}", name, message);
+                        }
+                        return Ok(());
+                    }
+                    State::Restarting(at) if Instant::now() >= *at => self.start(index),
+                    _ => {}
+                }
+            }
+
+            if self
+                .states
+                .iter()
+                .all(|state| matches!(state, State::Exited))
+            {
+                bail!("No process could be started");
+            }
+        }
+    }
+
+    /// Stop every running process and wait for it to exit, killing stragglers
+    fn shutdown(&mut self, rx: &Receiver<Line>) {
+        for state in &mut self.states {
+            if let State::Running(child) = state {
+                if terminate(child.id()).is_err() {
+                    let _ = child.kill();
+                }
+            }
+        }
+
+        let deadline = Instant::now() + SHUTDOWN_GRACE;
+        loop {
+            for line in rx.try_iter() {
+                self.print_line(line);
+            }
+            for index in 0..self.states.len() {
+                if let State::Running(child) = &mut self.states[index] {
+                    if let Ok(Some(_)) = child.try_wait() {
+                        self.print_status(index, "stopped", Style::new().dim());
+                        self.states[index] = State::Exited;
+                    }
+                }
+            }
+            if !self.states.iter().any(|s| matches!(s, State::Running(_))) {
+                return;
+            }
+            if Instant::now() >= deadline {
+                for state in &mut self.states {
+                    if let State::Running(child) = state {
+                        kill_group(child);
+                    }
+                }
+                return;
+            }
+            thread::sleep(Duration::from_millis(100));
+        }
+    }
+
+    fn print_line(&self, (index, text): Line) {
+        println!("{} {}", self.prefixes[index], text);
+    }
+
+    fn print_status(&self, index: usize, message: &str, style: Style) {
+        println!("{} {}", self.prefixes[index], style.apply_to(message));
+    }
+}
+
+/// Start a process in its own process group, streaming its output to `tx`
+fn spawn(process: &Process, index: usize, tx: &Sender<Line>) -> Result<Child> {
+    let mut cmd = if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.args(["/C", &process.command]);
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", &process.command]);
+        cmd
+    };
+    // Ctrl-C reaches only devkit, which then stops each process and its children
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut cmd, 0);
+    cmd.current_dir(&process.cwd)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+
+    let mut child = cmd
+        .logged_spawn()
+        .with_context(|| format!("Failed to start {}", process.name))?;
+    read_lines(child.stdout.take(), index, tx.clone());
+    read_lines(child.stderr.take(), index, tx.clone());
+    Ok(child)
+}
+
+fn read_lines(pipe: Option<impl Read + Send + 'static>, index: usize, tx: Sender<Line>) {
+    let Some(pipe) = pipe else {
+        return;
+    };
+    thread::spawn(move || {
+        let mut reader = BufReader::new(pipe);
+        let mut buf = Vec::new();
+        while matches!(reader.read_until(b'\n', &mut buf), Ok(n) if n > 0) {
+            let text = String::from_utf8_lossy(&buf);
+            let text = text.trim_end_matches(['\n', '\r']).to_string();
+            if tx.send((index, text)).is_err() {
+                return;
+            }
+            buf.clear();
+        }
+    });
+}
+
+fn exit_message(status: &ExitStatus) -> String {
+    match status.code() {
+        Some(code) => format!("exited with code {}", code),
+        None => "was killed by a signal".to_string(),
+    }
+}
+
+#[cfg(unix)]
+fn kill_group(child: &mut Child) {
+    unsafe {
+        libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL);
+    }
+    let _ = child.wait();
+}
+
+#[cfg(windows)]
+fn kill_group(child: &mut Child) {
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+/// Reset the stop flag, installing the Ctrl-C handler on first use
+fn stop_on_ctrl_c() -> Result<()> {
+    STOP.store(false, Ordering::SeqCst);
+    let mut result = Ok(());
+    CTRL_C.call_once(|| {
+        result = ctrlc::set_handler(|| STOP.store(true, Ordering::SeqCst))
+            .context("Failed to set the Ctrl-C handler");
+    });
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_procfile() {
+        let contents =
+            "# dev processes\nweb: npm run dev -- --port 3000\n\nworker:bundle exec sidekiq\n";
+        assert_eq!(
+            parse_procfile(contents).unwrap(),
+            [
+                ("web".to_string(), "npm run dev -- --port 3000".to_string()),
+                ("worker".to_string(), "bundle exec sidekiq".to_string()),
+            ]
+        );
+
+        assert_eq!(
+            parse_procfile("web npm start").unwrap_err(),
+            "line 1: expected `name: command`"
+        );
+        assert_eq!(
+            parse_procfile("web: a\nweb: b").unwrap_err(),
+            "line 2: duplicate process 'web'"
+        );
+    }
+}
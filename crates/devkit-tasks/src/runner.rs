@@ -13,26 +13,61 @@
 //! [cmd.fmt]
 //! default = "npx prettier --check src/**/*.{ts,tsx}"
 //! fix = "npx prettier --write src/**/*.{ts,tsx}"
+//!
+//! [cmd.migrate]
+//! default = "sqlx migrate run"
+//! wait = ["postgres"]
+//! ```
+//!
+//! `wait` targets (see [`devkit_core::wait`]) must be ready before the command starts.
+//! `env_file` (relative to the package) and `env` set variables for the command alone:
+//!
+//! ```toml
+//! [cmd.test]
+//! default = "cargo test"
+//! env_file = ".env.test"
+//! env = { RUST_LOG = "debug", DATABASE_URL = "{services.postgres}" }
 //! ```
+//!
+//! Commands run in dependency order. In parallel mode, a command starts as soon as the
+//! commands it depends on have succeeded, up to `jobs` at a time, and its output lines
+//! are prefixed with the package name. Commands depending on a failed one are skipped.
+//!
+//! Commands with `inputs` are skipped while their inputs, and those of the commands they
+//! depend on, are unchanged since their last successful run (see [`crate::cache`]),
+//! unless `force` is set.
+//!
+//! Each package command that runs is recorded in the command history as
+//! `<package>:<cmd>`, with its duration and exit code.
 
+use crate::cache;
+use crate::cmd_builder::CmdBuilder;
 use anyhow::{anyhow, Result};
+use console::{Color, Style};
+use devkit_core::history::{add_to_history, HistoryEntry, HistorySource};
+use devkit_core::hooks::run_hooks;
+use devkit_core::wait::{wait_for, WaitOptions};
 use devkit_core::{AppContext, Config};
 use std::collections::{HashMap, HashSet};
-use std::process::{Command, Stdio};
-use std::sync::{Arc, Mutex};
+use std::sync::mpsc;
 use std::thread;
+use std::time::Instant;
 
 /// Options for running commands
 #[derive(Debug, Default)]
 pub struct CmdOptions {
     /// Run commands in parallel where possible
     pub parallel: bool,
+    /// Most commands to run at once in parallel (defaults to the number of CPUs)
+    pub jobs: Option<usize>,
     /// Variant to use (e.g., "fix", "watch")
     pub variant: Option<String>,
     /// Only run for specific packages
     pub packages: Vec<String>,
     /// Capture output instead of streaming
     pub capture: bool,
+    /// Run commands even when their inputs are unchanged
+    pub force: bool,
 }
 
 /// Result of running a command
@@ -42,6 +77,8 @@ pub struct CmdResult {
     pub cmd_name: String,
     pub success: bool,
     pub output: Option<String>,
+    /// Skipped because its inputs were unchanged
+    pub cached: bool,
 }
 
 /// Run a command across all packages that define it
@@ -80,11 +117,19 @@ pub fn run_cmd(ctx: &AppContext, cmd_name: &str, opts: &CmdOptions) -> Result<Ve
     // Build dependency graph and execution order
     let order = resolve_execution_order(&ctx.config, cmd_name, &packages)?;
 
-    if opts.parallel {
-        run_parallel(ctx, cmd_name, &order, opts)
+    let hook_env = [("DEVKIT_CMD", cmd_name)];
+    run_hooks(ctx, "pre_cmd", &hook_env)?;
+
+    let results = if opts.parallel {
+        run_parallel(ctx, cmd_name, &order, opts)?
     } else {
-        run_sequential(ctx, cmd_name, &order, opts)
+        run_sequential(ctx, cmd_name, &order, opts)?
+    };
+
+    if results.iter().all(|r| r.success) {
+        run_hooks(ctx, "post_cmd", &hook_env)?;
     }
+    Ok(results)
 }
 
 /// Resolve execution order respecting dependencies
@@ -229,17 +274,47 @@ fn run_sequential(
     )],
     opts: &CmdOptions,
 ) -> Result<Vec<CmdResult>> {
+    let names: Vec<&str> = packages.iter().map(|(name, _, _)| *name).collect();
+    let deps: Vec<&[String]> = packages.iter().map(|(_, _, entry)| entry.deps()).collect();
+    let deps = dependency_indices(&names, &deps);
     let mut results = Vec::new();
+    let mut hashes: Vec<Option<String>> = vec![None; packages.len()];
+
+    for (index, (pkg_name, pkg_config, cmd_entry)) in packages.iter().enumerate() {
+        let cmd_str = ctx
+            .config
+            .global
+            .services
+            .expand(get_cmd_for_variant(cmd_entry, opts.variant.as_deref()));
+
+        let hash = inputs_hash(ctx, pkg_name, pkg_config, cmd_entry, &cmd_str);
+        hashes[index] = with_dependency_hashes(hash, &deps[index], &hashes);
+        let hash = hashes[index].clone();
+        if let Some(result) = cached_result(ctx, pkg_name, cmd_name, hash.as_deref(), opts) {
+            results.push(result);
+            continue;
+        }
 
-    for (pkg_name, pkg_config, cmd_entry) in packages {
-        let cmd_str = get_cmd_for_variant(cmd_entry, opts.variant.as_deref());
+        let env = cmd_env(ctx, pkg_config, cmd_entry)?;
+        wait_for(ctx, cmd_entry.wait(), &WaitOptions::default())?;
 
         if !ctx.quiet {
             println!("[{}] Running {} on {}...", cmd_name, cmd_str, pkg_name);
         }
 
-        let result = run_single_cmd(pkg_name, cmd_name, &pkg_config.path, cmd_str, opts.capture)?;
+        let result = run_single_cmd(
+            pkg_name,
+            cmd_name,
+            opts.variant.as_deref(),
+            &pkg_config.path,
+            &cmd_str,
+            &env,
+            opts.capture,
+        )?;
         let success = result.success;
+        if success {
+            record_inputs(ctx, pkg_name, cmd_name, hash.as_deref());
+        }
         results.push(result);
 
         if !success && !opts.capture {
@@ -251,7 +326,8 @@ fn run_sequential(
     Ok(results)
 }
 
-/// Run commands in parallel where dependencies allow
+/// Run commands in parallel as their dependencies finish, at most `opts.jobs` at a
+/// time. Output is streamed with a `[package]` prefix unless it's captured.
 fn run_parallel(
     ctx: &AppContext,
     cmd_name: &str,
@@ -262,97 +338,377 @@ fn run_parallel(
     )],
     opts: &CmdOptions,
 ) -> Result<Vec<CmdResult>> {
-    // For now, simple parallel execution (ignoring dep ordering for parallel)
-    // TODO: Implement proper parallel execution with dependency graph
-    let results = Arc::new(Mutex::new(Vec::new()));
-    let mut handles = Vec::new();
+    let names: Vec<&str> = packages.iter().map(|(name, _, _)| *name).collect();
+    let deps: Vec<&[String]> = packages.iter().map(|(_, _, entry)| entry.deps()).collect();
+    let deps = dependency_indices(&names, &deps);
+    let mut schedule = Schedule::new(deps.clone());
+    let jobs = opts.jobs.unwrap_or_else(default_jobs).max(1);
+    let mut results: Vec<Option<CmdResult>> = packages.iter().map(|_| None).collect();
+    let mut hashes: Vec<Option<String>> = vec![None; packages.len()];
+
+    thread::scope(|scope| -> Result<()> {
+        let (tx, rx) = mpsc::channel();
+        let mut running = 0;
+
+        loop {
+            for (index, dep) in schedule.skip_failed() {
+                results[index] = Some(CmdResult {
+                    package: names[index].to_string(),
+                    cmd_name: cmd_name.to_string(),
+                    success: false,
+                    output: Some(format!("skipped: dependency {} failed", names[dep])),
+                    cached: false,
+                });
+            }
 
-    for (pkg_name, pkg_config, cmd_entry) in packages {
-        let cmd_str = get_cmd_for_variant(cmd_entry, opts.variant.as_deref());
+            while running < jobs {
+                let Some(index) = schedule.next() else {
+                    break;
+                };
+                let (pkg_name, pkg_config, cmd_entry) = packages[index];
+                let cmd_str = ctx
+                    .config
+                    .global
+                    .services
+                    .expand(get_cmd_for_variant(cmd_entry, opts.variant.as_deref()));
+
+                let hash = inputs_hash(ctx, pkg_name, pkg_config, cmd_entry, &cmd_str);
+                hashes[index] = with_dependency_hashes(hash, &deps[index], &hashes);
+                let hash = hashes[index].as_deref();
+                if let Some(result) = cached_result(ctx, pkg_name, cmd_name, hash, opts) {
+                    schedule.finish(index, true);
+                    results[index] = Some(result);
+                    continue;
+                }
 
-        if !ctx.quiet {
-            println!("[{}] Starting {} on {}...", cmd_name, cmd_str, pkg_name);
-        }
+                if !ctx.quiet {
+                    println!("[{}] Starting {} on {}...", cmd_name, cmd_str, pkg_name);
+                }
 
-        let pkg_name = pkg_name.to_string();
-        let cmd_name = cmd_name.to_string();
-        let path = pkg_config.path.clone();
-        let cmd_str = cmd_str.to_string();
-        let results = Arc::clone(&results);
-
-        let handle = thread::spawn(move || {
-            let result = run_single_cmd(&pkg_name, &cmd_name, &path, &cmd_str, true)
-                .unwrap_or_else(|e| CmdResult {
-                    package: pkg_name.clone(),
-                    cmd_name: cmd_name.clone(),
-                    success: false,
-                    output: Some(e.to_string()),
+                let prefix = (!opts.capture).then(|| {
+                    prefix_style(index)
+                        .apply_to(format!("[{}]", pkg_name))
+                        .to_string()
                 });
-            results.lock().unwrap().push(result);
-        });
+                let tx = tx.clone();
+                scope.spawn(move || {
+                    let result = cmd_env(ctx, pkg_config, cmd_entry)
+                        .and_then(|env| {
+                            wait_for(ctx, cmd_entry.wait(), &WaitOptions::default())?;
+                            run_prefixed_cmd(
+                                pkg_name,
+                                cmd_name,
+                                opts.variant.as_deref(),
+                                &pkg_config.path,
+                                &cmd_str,
+                                &env,
+                                prefix.as_deref(),
+                            )
+                        })
+                        .unwrap_or_else(|e| CmdResult {
+                            package: pkg_name.to_string(),
+                            cmd_name: cmd_name.to_string(),
+                            success: false,
+                            output: Some(e.to_string()),
+                            cached: false,
+                        });
+                    let _ = tx.send((index, result));
+                });
+                running += 1;
+            }
+
+            if running == 0 {
+                return Ok(());
+            }
+            let (index, result) = rx.recv().map_err(|_| anyhow!("Thread panicked"))?;
+            running -= 1;
+            if result.success {
+                record_inputs(ctx, names[index], cmd_name, hashes[index].as_deref());
+            }
+            schedule.finish(index, result.success);
+            results[index] = Some(result);
+        }
+    })?;
+
+    Ok(results.into_iter().flatten().collect())
+}
+
+/// The command's `env_file` and `env` variables, with `{services.<name>}` expanded
+fn cmd_env(
+    ctx: &AppContext,
+    pkg_config: &devkit_core::config::PackageConfig,
+    cmd_entry: &devkit_core::config::CmdEntry,
+) -> Result<Vec<(String, String)>> {
+    let services = &ctx.config.global.services;
+    Ok(cmd_entry
+        .env_vars(&pkg_config.path)?
+        .into_iter()
+        .map(|(key, value)| (key, services.expand(&value)))
+        .collect())
+}
 
-        handles.push(handle);
+/// Hash of the command's inputs and variables, `None` when it has no inputs or they
+/// can't be read
+fn inputs_hash(
+    ctx: &AppContext,
+    pkg_name: &str,
+    pkg_config: &devkit_core::config::PackageConfig,
+    cmd_entry: &devkit_core::config::CmdEntry,
+    cmd_str: &str,
+) -> Option<String> {
+    if cmd_entry.inputs().is_empty() {
+        return None;
     }
+    cmd_env(ctx, pkg_config, cmd_entry)
+        .and_then(|env| cache::inputs_hash(&pkg_config.path, cmd_str, &env, cmd_entry.inputs()))
+        .map_err(|e| ctx.print_warning(&format!("Not caching {}: {:#}", pkg_name, e)))
+        .ok()
+}
 
-    // Wait for all to complete
-    for handle in handles {
-        handle.join().map_err(|_| anyhow!("Thread panicked"))?;
+/// A command's inputs hash folded with those of its dependencies (at `deps` in
+/// `hashes`); `None` when a dependency isn't cached, since it runs every time
+fn with_dependency_hashes(
+    hash: Option<String>,
+    deps: &[usize],
+    hashes: &[Option<String>],
+) -> Option<String> {
+    let hash = hash?;
+    if deps.is_empty() {
+        return Some(hash);
     }
+    let dependencies: Option<Vec<&str>> = deps.iter().map(|dep| hashes[*dep].as_deref()).collect();
+    Some(cache::with_dependencies(&hash, &dependencies?))
+}
 
-    let results = Arc::try_unwrap(results)
-        .map_err(|_| anyhow!("Failed to unwrap results"))?
-        .into_inner()
-        .map_err(|_| anyhow!("Failed to get results"))?;
+/// A result for a command whose inputs are unchanged since it last succeeded
+fn cached_result(
+    ctx: &AppContext,
+    pkg_name: &str,
+    cmd_name: &str,
+    hash: Option<&str>,
+    opts: &CmdOptions,
+) -> Option<CmdResult> {
+    let key = format!("{}:{}", pkg_name, cmd_name);
+    if opts.force || !cache::is_fresh(&ctx.repo, &key, hash?) {
+        return None;
+    }
+    if !ctx.quiet {
+        println!("[{}] {} is cached (inputs unchanged)", cmd_name, pkg_name);
+    }
+    Some(CmdResult {
+        package: pkg_name.to_string(),
+        cmd_name: cmd_name.to_string(),
+        success: true,
+        output: None,
+        cached: true,
+    })
+}
 
-    Ok(results)
+/// Save the inputs hash of a command that succeeded; failing to is ignored
+fn record_inputs(ctx: &AppContext, pkg_name: &str, cmd_name: &str, hash: Option<&str>) {
+    if let Some(hash) = hash {
+        let _ = cache::record(&ctx.repo, &format!("{}:{}", pkg_name, cmd_name), hash);
+    }
 }
 
-/// Run a single command
-fn run_single_cmd(
+/// Jobs to run at once when `--jobs` isn't given
+fn default_jobs() -> usize {
+    thread::available_parallelism().map_or(4, |n| n.get())
+}
+
+/// A color per package, so interleaved output can be told apart
+pub(crate) fn prefix_style(index: usize) -> Style {
+    const COLORS: [Color; 6] = [
+        Color::Cyan,
+        Color::Magenta,
+        Color::Yellow,
+        Color::Blue,
+        Color::Green,
+        Color::Red,
+    ];
+    Style::new().fg(COLORS[index % COLORS.len()]).bold()
+}
+
+/// For each package in `names`, the positions of the packages it depends on. Deps are
+/// "package" or "package:cmd"; packages that aren't being run are ignored.
+fn dependency_indices(names: &[&str], deps: &[&[String]]) -> Vec<Vec<usize>> {
+    deps.iter()
+        .enumerate()
+        .map(|(index, deps)| {
+            deps.iter()
+                .filter_map(|dep| {
+                    let dep_pkg = dep.split(':').next().unwrap_or(dep);
+                    names.iter().position(|name| *name == dep_pkg)
+                })
+                .filter(|dep| *dep != index)
+                .collect()
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum JobState {
+    Pending,
+    Running,
+    Done { success: bool },
+}
+
+/// Which commands can start, given which have finished
+struct Schedule {
+    deps: Vec<Vec<usize>>,
+    states: Vec<JobState>,
+}
+
+impl Schedule {
+    fn new(deps: Vec<Vec<usize>>) -> Self {
+        let states = vec![JobState::Pending; deps.len()];
+        Self { deps, states }
+    }
+
+    /// Start the first pending command whose dependencies all succeeded
+    fn next(&mut self) -> Option<usize> {
+        let index = (0..self.states.len()).find(|&index| {
+            self.states[index] == JobState::Pending
+                && self.deps[index]
+                    .iter()
+                    .all(|&dep| self.states[dep] == JobState::Done { success: true })
+        })?;
+        self.states[index] = JobState::Running;
+        Some(index)
+    }
+
+    fn finish(&mut self, index: usize, success: bool) {
+        self.states[index] = JobState::Done { success };
+    }
+
+    /// Give up on pending commands with a failed dependency, returning each with the
+    /// dependency that failed
+    fn skip_failed(&mut self) -> Vec<(usize, usize)> {
+        let mut skipped = Vec::new();
+        loop {
+            let failed = (0..self.states.len()).find_map(|index| {
+                let dep = self.deps[index]
+                    .iter()
+                    .find(|&&dep| self.states[dep] == JobState::Done { success: false })?;
+                (self.states[index] == JobState::Pending).then_some((index, *dep))
+            });
+            match failed {
+                Some((index, dep)) => {
+                    self.states[index] = JobState::Done { success: false };
+                    skipped.push((index, dep));
+                }
+                None => return skipped,
+            }
+        }
+    }
+}
+
+/// Run a single command, capturing its output and printing each line after `prefix`
+fn run_prefixed_cmd(
     pkg_name: &str,
     cmd_name: &str,
+    variant: Option<&str>,
     cwd: &std::path::Path,
     cmd_str: &str,
-    capture: bool,
+    env: &[(String, String)],
+    prefix: Option<&str>,
 ) -> Result<CmdResult> {
-    // Parse command string into program and args
     let parts: Vec<&str> = cmd_str.split_whitespace().collect();
     if parts.is_empty() {
         return Err(anyhow!("Empty command for {} in {}", cmd_name, pkg_name));
     }
 
-    let program = parts[0];
-    let args = &parts[1..];
+    let started = Instant::now();
+    let mut output = String::new();
+    let result = CmdBuilder::new(parts[0])
+        .args(parts[1..].iter().copied())
+        .cwd(cwd)
+        .envs(env.iter().cloned())
+        .run_streaming(|line| {
+            if let Some(prefix) = prefix {
+                println!("{} {}", prefix, line.text);
+            }
+            output.push_str(&line.text);
+            output.push('\n');
+        })?;
+    record_run(pkg_name, cmd_name, variant, started, result.code);
 
-    let mut cmd = Command::new(program);
-    cmd.args(args).current_dir(cwd);
+    Ok(CmdResult {
+        package: pkg_name.to_string(),
+        cmd_name: cmd_name.to_string(),
+        success: result.code == 0,
+        output: Some(output),
+        cached: false,
+    })
+}
 
-    if capture {
-        cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
+/// Run a single command
+fn run_single_cmd(
+    pkg_name: &str,
+    cmd_name: &str,
+    variant: Option<&str>,
+    cwd: &std::path::Path,
+    cmd_str: &str,
+    env: &[(String, String)],
+    capture: bool,
+) -> Result<CmdResult> {
+    // Parse command string into program and args
+    let parts: Vec<&str> = cmd_str.split_whitespace().collect();
+    if parts.is_empty() {
+        return Err(anyhow!("Empty command for {} in {}", cmd_name, pkg_name));
     }
 
-    let output = cmd.output()?;
-    let success = output.status.success();
+    let cmd = CmdBuilder::new(parts[0])
+        .args(parts[1..].iter().copied())
+        .cwd(cwd)
+        .envs(env.iter().cloned());
 
-    let output_str = if capture {
+    let started = Instant::now();
+    let (code, output_str) = if capture {
+        let output = cmd.run_streaming(|_| {})?;
         let mut s = String::from_utf8_lossy(&output.stdout).to_string();
         s.push_str(&String::from_utf8_lossy(&output.stderr));
-        Some(s)
+        (output.code, Some(s))
     } else {
-        None
+        (cmd.inherit_io().run()?, None)
     };
+    record_run(pkg_name, cmd_name, variant, started, code);
 
     Ok(CmdResult {
         package: pkg_name.to_string(),
         cmd_name: cmd_name.to_string(),
-        success,
+        success: code == 0,
         output: output_str,
+        cached: false,
     })
 }
 
+/// Record a package command run in the history; failing to write it is ignored
+fn record_run(
+    pkg_name: &str,
+    cmd_name: &str,
+    variant: Option<&str>,
+    started: Instant,
+    exit_code: i32,
+) {
+    let command = match variant {
+        Some(variant) => format!("{}:{} ({})", pkg_name, cmd_name, variant),
+        None => format!("{}:{}", pkg_name, cmd_name),
+    };
+    let entry = HistoryEntry::finished(
+        command,
+        HistorySource::Task,
+        Some(pkg_name.to_string()),
+        started.elapsed(),
+        exit_code,
+    );
+    let _ = add_to_history(entry);
+}
+
 /// Print results summary
 pub fn print_results(ctx: &AppContext, results: &[CmdResult]) {
-    let succeeded: Vec<_> = results.iter().filter(|r| r.success).collect();
+    let succeeded: Vec<_> = results.iter().filter(|r| r.success && !r.cached).collect();
+    let cached: Vec<_> = results.iter().filter(|r| r.cached).collect();
     let failed: Vec<_> = results.iter().filter(|r| !r.success).collect();
 
     println!();
@@ -384,6 +740,18 @@ pub fn print_results(ctx: &AppContext, results: &[CmdResult]) {
                 .join(", ")
         ));
     }
+
+    if !cached.is_empty() && !ctx.quiet {
+        ctx.print_info(&format!(
+            "{} package(s) cached (inputs unchanged): {}",
+            cached.len(),
+            cached
+                .iter()
+                .map(|r| r.package.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
 }
 
 /// List all available commands across packages
@@ -401,3 +769,100 @@ pub fn list_commands(config: &Config) -> HashMap<String, Vec<String>> {
 
     commands
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dependency_indices() {
+        let deps = [
+            vec![],
+            vec!["common:build".to_string()],
+            vec!["common".to_string(), "unknown".to_string()],
+        ];
+        let deps: Vec<&[String]> = deps.iter().map(Vec::as_slice).collect();
+        assert_eq!(
+            dependency_indices(&["common", "api", "web"], &deps),
+            [vec![], vec![0], vec![0]]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_dependency_invalidates_cache() {
+        let repo = std::env::temp_dir().join(format!("devkit-runner-{}", std::process::id()));
+        let package = |name: &str, deps: &str| {
+            let path = repo.join("packages").join(name);
+            std::fs::create_dir_all(path.join("src")).unwrap();
+            std::fs::write(path.join("src/main.ts"), "export {}").unwrap();
+            let cmd: devkit_core::config::CmdEntry = toml::from_str(&format!(
+                "default = \"touch ../../ran-{}\"\ninputs = [\"src/**\"]\ndeps = [{}]\n",
+                name, deps
+            ))
+            .unwrap();
+            let config = devkit_core::config::PackageConfig {
+                path,
+                cmd: HashMap::from([("build".to_string(), cmd)]),
+                ..Default::default()
+            };
+            (name.to_string(), config)
+        };
+        let config = Config {
+            packages: HashMap::from([package("common", ""), package("api", "\"common\"")]),
+            ..Default::default()
+        };
+        let ctx = AppContext {
+            repo: repo.clone(),
+            quiet: true,
+            config,
+            features: Default::default(),
+        };
+        let runs = || {
+            ["api", "common"]
+                .into_iter()
+                .filter(|name| std::fs::remove_file(repo.join(format!("ran-{}", name))).is_ok())
+                .collect::<Vec<_>>()
+        };
+
+        for parallel in [false, true] {
+            let opts = CmdOptions {
+                parallel,
+                capture: true,
+                ..Default::default()
+            };
+            std::fs::remove_dir_all(repo.join(".dev")).ok();
+            run_cmd(&ctx, "build", &opts).unwrap();
+            assert_eq!(runs(), ["api", "common"]);
+            let results = run_cmd(&ctx, "build", &opts).unwrap();
+            assert!(results.iter().all(|r| r.cached));
+            assert!(runs().is_empty());
+
+            // api's inputs are unchanged, but it depends on common, which reran
+            let source = repo.join("packages/common/src/main.ts");
+            std::fs::write(&source, format!("export const parallel = {}", parallel)).unwrap();
+            run_cmd(&ctx, "build", &opts).unwrap();
+            assert_eq!(runs(), ["api", "common"]);
+        }
+
+        std::fs::remove_dir_all(&repo).ok();
+    }
+
+    #[test]
+    fn test_schedule() {
+        // api and web depend on common, e2e depends on both
+        let mut schedule = Schedule::new(vec![vec![], vec![0], vec![0], vec![1, 2]]);
+        assert_eq!(schedule.next(), Some(0));
+        assert_eq!(schedule.next(), None);
+
+        schedule.finish(0, true);
+        assert_eq!(schedule.next(), Some(1));
+        assert_eq!(schedule.next(), Some(2));
+        assert_eq!(schedule.next(), None);
+
+        schedule.finish(1, false);
+        assert_eq!(schedule.skip_failed(), [(3, 1)]);
+        schedule.finish(2, true);
+        assert_eq!(schedule.next(), None);
+    }
+}
@@ -66,6 +66,14 @@ pub fn extract_vars(template: &str) -> Vec<String> {
 pub fn prompt_for_vars(vars: &[String]) -> Result<HashMap<String, String>> {
     use dialoguer::Input;
 
+    if !vars.is_empty() && !devkit_core::utils::is_terminal() {
+        return Err(devkit_core::DevkitError::not_interactive(
+            format!("Prompting for {}", vars.join(", ")),
+            "Set the variables in the environment instead",
+        )
+        .into());
+    }
+
     let mut result = HashMap::new();
 
     for var in vars {
@@ -76,7 +76,7 @@ fn run_command(ctx: &AppContext, cmd: Commands) -> Result<()> {
 
             if ctx.features.docker {
                 ctx.print_info("Docker detected - starting containers");
-                devkit_ext_docker::compose_up(ctx, &[], false)?;
+                devkit_ext_docker::compose_up(ctx, &[], &[], false)?;
             } else {
                 ctx.print_warning("Docker not detected - skipping container startup");
             }
@@ -93,7 +93,7 @@ fn run_command(ctx: &AppContext, cmd: Commands) -> Result<()> {
         }
 
         Commands::Docker { action } => match action {
-            DockerAction::Up => to_anyhow(devkit_ext_docker::compose_up(ctx, &[], false)),
+            DockerAction::Up => to_anyhow(devkit_ext_docker::compose_up(ctx, &[], &[], false)),
             DockerAction::Down => to_anyhow(devkit_ext_docker::compose_down(ctx)),
             DockerAction::Logs => {
                 let containers = to_anyhow(devkit_ext_docker::list_running_containers(ctx))?;
@@ -221,7 +221,7 @@ fn docker_menu(ctx: &AppContext) -> Result<()> {
         .interact()?;
 
     match choice {
-        0 => devkit_ext_docker::compose_up(ctx, &[], false)?,
+        0 => devkit_ext_docker::compose_up(ctx, &[], &[], false)?,
         1 => devkit_ext_docker::compose_down(ctx)?,
         2 => {
             let containers = devkit_ext_docker::list_running_containers(ctx)?;
@@ -0,0 +1,70 @@
+//! `devkit aws` subcommand
+
+use anyhow::Result;
+use clap::{Args, Command, FromArgMatches, Subcommand};
+use devkit_core::AppContext;
+
+use crate::{lambda_logs, stacks, status, sync, use_session};
+
+#[derive(Args)]
+pub struct AwsArgs {
+    #[command(subcommand)]
+    action: Option<AwsAction>,
+}
+
+#[derive(Subcommand)]
+enum AwsAction {
+    /// Show the selected profile, region and identity (default)
+    Status,
+    /// Choose the profile and region used in this checkout
+    Use {
+        #[arg(long)]
+        profile: Option<String>,
+        #[arg(long)]
+        region: Option<String>,
+    },
+    /// Tail a Lambda function's logs
+    Logs {
+        /// Function name or log group
+        function: String,
+        /// How far back to start, e.g. 10m, 2h
+        #[arg(long, default_value = "10m")]
+        since: String,
+        /// Keep streaming new log lines
+        #[arg(long, short)]
+        follow: bool,
+    },
+    /// Sync build artifacts to the buckets in [aws.sync]
+    Sync {
+        /// Sync target (all when omitted)
+        target: Option<String>,
+        /// Show what would change without uploading
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Show CloudFormation/CDK stack status
+    Stacks {
+        /// Only this stack, with its outputs
+        name: Option<String>,
+    },
+}
+
+pub fn command() -> Command {
+    AwsArgs::augment_args(Command::new("aws").about("AWS operations (requires the aws CLI)"))
+}
+
+pub fn handle(ctx: &AppContext, matches: &clap::ArgMatches) -> Result<()> {
+    let args = AwsArgs::from_arg_matches(matches)?;
+
+    match args.action {
+        Some(AwsAction::Status) | None => status(ctx),
+        Some(AwsAction::Use { profile, region }) => use_session(ctx, profile, region),
+        Some(AwsAction::Logs {
+            function,
+            since,
+            follow,
+        }) => lambda_logs(ctx, &function, &since, follow),
+        Some(AwsAction::Sync { target, dry_run }) => sync(ctx, target.as_deref(), dry_run),
+        Some(AwsAction::Stacks { name }) => stacks(ctx, name.as_deref()),
+    }
+}
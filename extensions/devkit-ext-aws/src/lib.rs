@@ -0,0 +1,409 @@
+//! AWS operations extension for devkit
+//!
+//! Lambda log tailing, S3 sync of build artifacts and CloudFormation/CDK stack status,
+//! using the profile and region picked with `devkit aws use` (see [`session`]).
+//! Sync targets and the stacks to watch are declared in `.dev/config.toml`:
+//! ```toml
+//! [aws]
+//! region = "eu-west-1"
+//! stacks = ["ApiStack", "WebStack"]
+//!
+//! [aws.sync.web]
+//! source = "packages/web/dist"
+//! bucket = "my-site-assets"
+//! delete = true
+//! ```
+
+mod cli;
+pub mod session;
+
+use anyhow::{anyhow, bail, Context, Result};
+use devkit_core::config::S3SyncTarget;
+use devkit_core::oplog::LoggedCommand;
+use devkit_core::{utils::cmd_exists, AppContext, Extension, MenuItem};
+use dialoguer::{Input, Select};
+use serde_json::Value;
+use session::{profiles, Session};
+
+pub struct AwsExtension;
+
+impl Extension for AwsExtension {
+    fn name(&self) -> &str {
+        "aws"
+    }
+
+    fn is_available(&self, _ctx: &AppContext) -> bool {
+        cmd_exists("aws")
+    }
+
+    fn unavailable_reason(&self, _ctx: &AppContext) -> Option<String> {
+        Some("aws CLI not installed".to_string())
+    }
+
+    fn menu_items(&self, ctx: &AppContext) -> Vec<MenuItem> {
+        use devkit_core::DevkitError;
+        let mut items = vec![
+            MenuItem {
+                label: "Status".to_string(),
+                group: Some("☁️  AWS".to_string()),
+                handler: Box::new(|ctx| status(ctx).map_err(DevkitError::from)),
+            },
+            MenuItem {
+                label: "Switch profile/region".to_string(),
+                group: Some("☁️  AWS".to_string()),
+                handler: Box::new(|ctx| use_session(ctx, None, None).map_err(DevkitError::from)),
+            },
+            MenuItem {
+                label: "Stacks".to_string(),
+                group: Some("☁️  AWS".to_string()),
+                handler: Box::new(|ctx| stacks(ctx, None).map_err(DevkitError::from)),
+            },
+        ];
+        if !ctx.config.global.aws.sync.is_empty() {
+            items.push(MenuItem {
+                label: "Sync build artifacts to S3".to_string(),
+                group: Some("☁️  AWS".to_string()),
+                handler: Box::new(|ctx| sync(ctx, None, false).map_err(DevkitError::from)),
+            });
+        }
+        items
+    }
+
+    fn subcommands(&self) -> Vec<clap::Command> {
+        vec![cli::command()]
+    }
+
+    fn handle_cli(
+        &self,
+        ctx: &AppContext,
+        name: &str,
+        matches: &clap::ArgMatches,
+    ) -> Option<devkit_core::Result<()>> {
+        (name == "aws").then(|| cli::handle(ctx, matches).map_err(Into::into))
+    }
+}
+
+fn session(ctx: &AppContext) -> Result<Session> {
+    Session::resolve(&ctx.repo, &ctx.config.global.aws)
+}
+
+/// Run an `aws` query with `--output json` and parse the result
+fn aws_json(session: &Session, args: &[&str]) -> Result<Value> {
+    let output = session
+        .aws()
+        .args(args)
+        .args(["--output", "json"])
+        .logged_output()
+        .context("Failed to run aws")?;
+    if !output.status.success() {
+        bail!(
+            "aws {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    serde_json::from_slice(&output.stdout).context("Unexpected output from aws")
+}
+
+/// Show the selected profile and region and who they authenticate as
+pub fn status(ctx: &AppContext) -> Result<()> {
+    let session = session(ctx)?;
+    ctx.print_header("AWS");
+    println!();
+    println!("  {}", session.describe());
+
+    match aws_json(&session, &["sts", "get-caller-identity"]) {
+        Ok(identity) => {
+            println!("  Account: {}", identity["Account"].as_str().unwrap_or("?"));
+            println!("  Identity: {}", identity["Arn"].as_str().unwrap_or("?"));
+        }
+        Err(err) => ctx.print_warning(&format!("Not authenticated: {}", err)),
+    }
+    Ok(())
+}
+
+/// Save the profile and region for this checkout; asks when neither is given
+pub fn use_session(
+    ctx: &AppContext,
+    profile: Option<String>,
+    region: Option<String>,
+) -> Result<()> {
+    let mut session = Session::load(&ctx.repo)?;
+
+    if profile.is_none() && region.is_none() {
+        ctx.require_interactive("Choosing a profile", "pass --profile and/or --region")?;
+        let current = self::session(ctx)?;
+
+        let profiles = profiles()?;
+        if !profiles.is_empty() {
+            let default = current
+                .profile
+                .as_ref()
+                .and_then(|profile| profiles.iter().position(|p| p == profile))
+                .unwrap_or(0);
+            let selection = Select::with_theme(&*ctx.theme())
+                .with_prompt("Profile")
+                .items(&profiles)
+                .default(default)
+                .interact()?;
+            session.profile = Some(profiles[selection].clone());
+        }
+
+        let region: String = Input::with_theme(&*ctx.theme())
+            .with_prompt("Region")
+            .with_initial_text(current.region.unwrap_or_default())
+            .interact_text()?;
+        session.region = Some(region.trim().to_string()).filter(|r| !r.is_empty());
+    } else {
+        if profile.is_some() {
+            session.profile = profile;
+        }
+        if region.is_some() {
+            session.region = region;
+        }
+    }
+
+    session.save(&ctx.repo)?;
+    ctx.print_success(&format!("✓ Using {}", session.describe()));
+    Ok(())
+}
+
+/// CloudWatch log group for a Lambda function name (log group paths pass through)
+fn lambda_log_group(function: &str) -> String {
+    if function.starts_with('/') {
+        function.to_string()
+    } else {
+        format!("/aws/lambda/{}", function)
+    }
+}
+
+/// Tail a Lambda function's logs
+pub fn lambda_logs(ctx: &AppContext, function: &str, since: &str, follow: bool) -> Result<()> {
+    let session = session(ctx)?;
+    let log_group = lambda_log_group(function);
+    ctx.print_info(&format!("Tailing {} ({})", log_group, session.describe()));
+
+    let mut cmd = session.aws();
+    cmd.args([
+        "logs", "tail", &log_group, "--since", since, "--format", "short",
+    ]);
+    if follow {
+        cmd.arg("--follow");
+    }
+    let status = cmd.logged_status().context("Failed to run aws logs tail")?;
+
+    // 130 = interrupted with Ctrl+C
+    if !status.success() && status.code() != Some(130) {
+        bail!("aws logs tail failed (does {} exist?)", log_group);
+    }
+    Ok(())
+}
+
+/// `s3://bucket/prefix` for a sync target
+fn s3_url(target: &S3SyncTarget) -> String {
+    let bucket = target
+        .bucket
+        .trim_start_matches("s3://")
+        .trim_end_matches('/');
+    match target.prefix.as_deref().map(|p| p.trim_matches('/')) {
+        Some(prefix) if !prefix.is_empty() => format!("s3://{}/{}", bucket, prefix),
+        _ => format!("s3://{}", bucket),
+    }
+}
+
+/// Sync one `[aws.sync]` target, or all of them, to S3
+pub fn sync(ctx: &AppContext, name: Option<&str>, dry_run: bool) -> Result<()> {
+    let targets = &ctx.config.global.aws.sync;
+    if targets.is_empty() {
+        bail!("No sync targets. Add one under [aws.sync.<name>] in .dev/config.toml");
+    }
+
+    let mut names: Vec<&String> = match name {
+        Some(name) => vec![targets
+            .get_key_value(name)
+            .map(|(name, _)| name)
+            .ok_or_else(|| anyhow!("No sync target named '{}'", name))?],
+        None => targets.keys().collect(),
+    };
+    names.sort();
+
+    let session = session(ctx)?;
+    for name in names {
+        let target = &targets[name];
+        let source = ctx.repo.join(&target.source);
+        if !source.is_dir() {
+            bail!(
+                "[aws.sync.{}] source {} doesn't exist (build it first?)",
+                name,
+                target.source
+            );
+        }
+
+        let destination = s3_url(target);
+        ctx.print_info(&format!("Syncing {} -> {}", target.source, destination));
+        let mut cmd = session.aws();
+        cmd.args(["s3", "sync"]).arg(&source).arg(&destination);
+        if target.delete {
+            cmd.arg("--delete");
+        }
+        if dry_run {
+            cmd.arg("--dryrun");
+        }
+        let status = cmd.logged_status().context("Failed to run aws s3 sync")?;
+        if !status.success() {
+            bail!("Sync of {} failed", name);
+        }
+    }
+
+    ctx.print_success(if dry_run {
+        "✓ Dry run complete"
+    } else {
+        "✓ Sync complete"
+    });
+    Ok(())
+}
+
+/// A CloudFormation (or CDK) stack
+#[derive(Debug, PartialEq)]
+pub struct Stack {
+    pub name: String,
+    pub status: String,
+    /// Last update, or creation if never updated
+    pub updated: String,
+    pub outputs: Vec<(String, String)>,
+}
+
+impl Stack {
+    /// Failed or rolled back
+    pub fn is_failed(&self) -> bool {
+        self.status.contains("FAILED") || self.status.contains("ROLLBACK")
+    }
+}
+
+/// `aws cloudformation describe-stacks` output
+fn parse_stacks(doc: &Value) -> Vec<Stack> {
+    doc["Stacks"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|stack| Stack {
+            name: stack["StackName"].as_str().unwrap_or_default().to_string(),
+            status: stack["StackStatus"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+            updated: stack["LastUpdatedTime"]
+                .as_str()
+                .or(stack["CreationTime"].as_str())
+                .unwrap_or_default()
+                .to_string(),
+            outputs: stack["Outputs"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .map(|output| {
+                    (
+                        output["OutputKey"].as_str().unwrap_or_default().to_string(),
+                        output["OutputValue"]
+                            .as_str()
+                            .unwrap_or_default()
+                            .to_string(),
+                    )
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+/// Show stack status; with a name, that stack's outputs too
+pub fn stacks(ctx: &AppContext, name: Option<&str>) -> Result<()> {
+    let session = session(ctx)?;
+    ctx.print_header("CloudFormation Stacks");
+    println!("  {}", session.describe());
+    println!();
+
+    let mut args = vec!["cloudformation", "describe-stacks"];
+    if let Some(name) = name {
+        args.extend(["--stack-name", name]);
+    }
+    let mut stacks = parse_stacks(&aws_json(&session, &args)?);
+
+    let watched = &ctx.config.global.aws.stacks;
+    if name.is_none() && !watched.is_empty() {
+        stacks.retain(|stack| watched.contains(&stack.name));
+    }
+    stacks.sort_by(|a, b| a.name.cmp(&b.name));
+
+    if stacks.is_empty() {
+        ctx.print_info("No stacks found");
+        return Ok(());
+    }
+    for stack in &stacks {
+        let line = format!(
+            "  {:<32} {:<28} {}",
+            stack.name, stack.status, stack.updated
+        );
+        if stack.is_failed() {
+            ctx.print_error(&line);
+        } else {
+            println!("{}", line);
+        }
+        if name.is_some() {
+            for (key, value) in &stack.outputs {
+                println!("    {} = {}", key, value);
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_stacks() {
+        let doc = serde_json::json!({"Stacks": [
+            {
+                "StackName": "ApiStack",
+                "StackStatus": "UPDATE_COMPLETE",
+                "CreationTime": "2024-01-01T00:00:00Z",
+                "LastUpdatedTime": "2024-03-01T00:00:00Z",
+                "Outputs": [{"OutputKey": "Url", "OutputValue": "https://api.example.com"}]
+            },
+            {
+                "StackName": "WebStack",
+                "StackStatus": "UPDATE_ROLLBACK_COMPLETE",
+                "CreationTime": "2024-02-01T00:00:00Z"
+            }
+        ]});
+
+        let stacks = parse_stacks(&doc);
+        assert_eq!(stacks[0].updated, "2024-03-01T00:00:00Z");
+        assert_eq!(
+            stacks[0].outputs,
+            [("Url".to_string(), "https://api.example.com".to_string())]
+        );
+        assert!(!stacks[0].is_failed());
+        assert_eq!(stacks[1].updated, "2024-02-01T00:00:00Z");
+        assert!(stacks[1].is_failed());
+    }
+
+    #[test]
+    fn test_s3_url_and_log_group() {
+        let target = |bucket: &str, prefix: Option<&str>| S3SyncTarget {
+            source: "dist".to_string(),
+            bucket: bucket.to_string(),
+            prefix: prefix.map(str::to_string),
+            delete: false,
+        };
+        assert_eq!(s3_url(&target("assets", None)), "s3://assets");
+        assert_eq!(
+            s3_url(&target("s3://assets/", Some("/web/"))),
+            "s3://assets/web"
+        );
+
+        assert_eq!(lambda_log_group("resize"), "/aws/lambda/resize");
+        assert_eq!(lambda_log_group("/custom/group"), "/custom/group");
+    }
+}
@@ -0,0 +1,131 @@
+//! Profile and region selection
+//!
+//! `devkit aws use` saves a profile and region in `.dev/aws.json` so they stick for
+//! this checkout. Anything not saved falls back to `[aws]` in config.toml, then to the
+//! AWS CLI's own defaults (`AWS_PROFILE`, `~/.aws/config`).
+
+use anyhow::{Context, Result};
+use devkit_core::config::AwsConfig;
+use devkit_core::oplog::LoggedCommand;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const SESSION_FILE: &str = ".dev/aws.json";
+
+#[derive(Debug, Serialize, Deserialize, Default, Clone, PartialEq)]
+pub struct Session {
+    pub profile: Option<String>,
+    pub region: Option<String>,
+}
+
+impl Session {
+    /// The saved selection for this checkout
+    pub fn load(repo: &Path) -> Result<Self> {
+        let path = session_path(repo);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let contents = fs::read_to_string(&path)?;
+        serde_json::from_str(&contents)
+            .with_context(|| format!("Invalid AWS session file: {}", path.display()))
+    }
+
+    pub fn save(&self, repo: &Path) -> Result<()> {
+        let path = session_path(repo);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// The saved selection with `[aws]` filling the gaps
+    pub fn resolve(repo: &Path, config: &AwsConfig) -> Result<Self> {
+        Ok(Self::load(repo)?.or(config))
+    }
+
+    fn or(self, config: &AwsConfig) -> Self {
+        Self {
+            profile: self.profile.or_else(|| config.profile.clone()),
+            region: self.region.or_else(|| config.region.clone()),
+        }
+    }
+
+    /// An `aws` command using this profile and region
+    pub fn aws(&self) -> Command {
+        let mut cmd = Command::new("aws");
+        if let Some(profile) = &self.profile {
+            cmd.env("AWS_PROFILE", profile);
+        }
+        if let Some(region) = &self.region {
+            cmd.env("AWS_REGION", region)
+                .env("AWS_DEFAULT_REGION", region);
+        }
+        cmd
+    }
+
+    /// "profile dev, region eu-west-1", for display
+    pub fn describe(&self) -> String {
+        format!(
+            "profile {}, region {}",
+            self.profile.as_deref().unwrap_or("(default)"),
+            self.region.as_deref().unwrap_or("(default)")
+        )
+    }
+}
+
+/// Profiles in `~/.aws/config` and `~/.aws/credentials`
+pub fn profiles() -> Result<Vec<String>> {
+    let output = Command::new("aws")
+        .args(["configure", "list-profiles"])
+        .logged_output()
+        .context("Failed to run aws configure list-profiles")?;
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+fn session_path(repo: &Path) -> PathBuf {
+    repo.join(SESSION_FILE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_saved_selection_overrides_config() {
+        let config = AwsConfig {
+            profile: Some("shared".to_string()),
+            region: Some("us-east-1".to_string()),
+            ..Default::default()
+        };
+        let saved = Session {
+            profile: Some("mine".to_string()),
+            region: None,
+        };
+
+        let session = saved.or(&config);
+        assert_eq!(session.profile.as_deref(), Some("mine"));
+        assert_eq!(session.region.as_deref(), Some("us-east-1"));
+        assert_eq!(session.describe(), "profile mine, region us-east-1");
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let repo = std::env::temp_dir().join(format!("devkit-aws-{}", std::process::id()));
+        let session = Session {
+            profile: Some("dev".to_string()),
+            region: Some("eu-west-1".to_string()),
+        };
+        session.save(&repo).unwrap();
+        assert_eq!(Session::load(&repo).unwrap(), session);
+        fs::remove_dir_all(repo).ok();
+    }
+}
@@ -0,0 +1,112 @@
+//! Benchmark comparison between runs
+
+use console::style;
+
+use crate::results::{format_duration, BenchResults};
+
+/// Change of a single benchmark between two runs
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchDelta {
+    pub name: String,
+    pub base_ns: Option<f64>,
+    pub current_ns: Option<f64>,
+}
+
+impl BenchDelta {
+    /// Percentage change in mean time (positive = slower)
+    pub fn change_pct(&self) -> Option<f64> {
+        match (self.base_ns, self.current_ns) {
+            (Some(base), Some(current)) if base > 0.0 => Some((current - base) / base * 100.0),
+            _ => None,
+        }
+    }
+}
+
+/// Pair up benchmarks from two runs, including ones present in only one of them
+pub fn compare_results(base: &BenchResults, current: &BenchResults) -> Vec<BenchDelta> {
+    let mut names: Vec<&String> = base.keys().chain(current.keys()).collect();
+    names.sort();
+    names.dedup();
+
+    names
+        .into_iter()
+        .map(|name| BenchDelta {
+            name: name.clone(),
+            base_ns: base.get(name).map(|r| r.mean_ns),
+            current_ns: current.get(name).map(|r| r.mean_ns),
+        })
+        .collect()
+}
+
+/// Print a comparison table
+pub fn print_deltas(deltas: &[BenchDelta]) {
+    let width = deltas
+        .iter()
+        .map(|d| d.name.len())
+        .max()
+        .unwrap_or(0)
+        .max(9);
+
+    println!(
+        "  {:<width$}  {:>12}  {:>12}  {:>9}",
+        "Benchmark",
+        "Base",
+        "Current",
+        "Change",
+        width = width
+    );
+    for delta in deltas {
+        let fmt = |ns: Option<f64>| ns.map(format_duration).unwrap_or_else(|| "-".to_string());
+        let change = match delta.change_pct() {
+            Some(pct) => {
+                let text = format!("{:+.1}%", pct);
+                // Changes within ±2% are usually noise
+                if pct > 2.0 {
+                    style(text).red()
+                } else if pct < -2.0 {
+                    style(text).green()
+                } else {
+                    style(text).dim()
+                }
+            }
+            None if delta.base_ns.is_none() => style("new".to_string()).cyan(),
+            None => style("removed".to_string()).dim(),
+        };
+
+        println!(
+            "  {:<width$}  {:>12}  {:>12}  {:>9}",
+            delta.name,
+            fmt(delta.base_ns),
+            fmt(delta.current_ns),
+            change,
+            width = width
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::results::BenchResult;
+
+    fn results(entries: &[(&str, f64)]) -> BenchResults {
+        entries
+            .iter()
+            .map(|(name, ns)| (name.to_string(), BenchResult { mean_ns: *ns }))
+            .collect()
+    }
+
+    #[test]
+    fn test_compare_results() {
+        let base = results(&[("parse", 100.0), ("old", 50.0)]);
+        let current = results(&[("parse", 110.0), ("new", 20.0)]);
+
+        let deltas = compare_results(&base, &current);
+        let names: Vec<_> = deltas.iter().map(|d| d.name.as_str()).collect();
+        assert_eq!(names, vec!["new", "old", "parse"]);
+
+        let parse = deltas.iter().find(|d| d.name == "parse").unwrap();
+        assert!((parse.change_pct().unwrap() - 10.0).abs() < 1e-9);
+        assert_eq!(deltas[0].change_pct(), None);
+    }
+}
@@ -0,0 +1,123 @@
+//! Benchmark result history
+//!
+//! Each run is stored as `.dev/bench/<date>-<time>-<sha>.json` so results can be
+//! compared across commits and branches.
+
+use anyhow::{anyhow, Context, Result};
+use chrono::Local;
+use devkit_tasks::CmdBuilder;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::results::BenchResults;
+
+const HISTORY_DIR: &str = ".dev/bench";
+
+/// A recorded benchmark run
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BenchRun {
+    /// Commit the benchmarks ran against
+    pub sha: String,
+    /// Branch name at the time of the run
+    pub branch: Option<String>,
+    /// RFC 3339 timestamp
+    pub date: String,
+    pub results: BenchResults,
+}
+
+impl BenchRun {
+    /// A run for the current HEAD
+    pub fn new(repo: &Path, results: BenchResults) -> Result<Self> {
+        Ok(Self {
+            sha: resolve_ref(repo, "HEAD")?,
+            branch: current_branch(repo),
+            date: Local::now().to_rfc3339(),
+            results,
+        })
+    }
+
+    pub fn short_sha(&self) -> &str {
+        &self.sha[..self.sha.len().min(8)]
+    }
+}
+
+/// Save a run to the history directory, returning its path
+pub fn save_run(repo: &Path, run: &BenchRun) -> Result<PathBuf> {
+    let dir = repo.join(HISTORY_DIR);
+    fs::create_dir_all(&dir)?;
+
+    let path = dir.join(format!(
+        "{}-{}.json",
+        Local::now().format("%Y-%m-%d-%H%M%S"),
+        run.short_sha()
+    ));
+    fs::write(&path, serde_json::to_string_pretty(run)?)?;
+    Ok(path)
+}
+
+/// Load all recorded runs, oldest first
+pub fn load_runs(repo: &Path) -> Result<Vec<BenchRun>> {
+    let dir = repo.join(HISTORY_DIR);
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut paths: Vec<PathBuf> = fs::read_dir(&dir)?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    // File names start with the run date, so name order is chronological
+    paths.sort();
+
+    paths
+        .iter()
+        .map(|path| {
+            let contents = fs::read_to_string(path)?;
+            serde_json::from_str(&contents)
+                .with_context(|| format!("Invalid benchmark result: {}", path.display()))
+        })
+        .collect()
+}
+
+/// Most recent run recorded for a git ref (branch, tag or SHA)
+pub fn latest_run_for_ref(repo: &Path, reference: &str) -> Result<BenchRun> {
+    let sha = resolve_ref(repo, reference)?;
+    load_runs(repo)?
+        .into_iter()
+        .rev()
+        .find(|run| run.sha == sha)
+        .ok_or_else(|| {
+            anyhow!(
+                "No benchmark results recorded for {} ({})\nCheck it out and run 'devkit bench' first",
+                reference,
+                &sha[..sha.len().min(8)]
+            )
+        })
+}
+
+fn resolve_ref(repo: &Path, reference: &str) -> Result<String> {
+    let output = CmdBuilder::new("git")
+        .args([
+            "rev-parse",
+            "--verify",
+            &format!("{}^{{commit}}", reference),
+        ])
+        .cwd(repo)
+        .capture_stdout()
+        .run_capture()
+        .with_context(|| format!("Unknown git ref: {}", reference))?;
+    Ok(output.stdout_string().trim().to_string())
+}
+
+fn current_branch(repo: &Path) -> Option<String> {
+    CmdBuilder::new("git")
+        .args(["rev-parse", "--abbrev-ref", "HEAD"])
+        .cwd(repo)
+        .capture_stdout()
+        .run_capture()
+        .ok()
+        .map(|out| out.stdout_string().trim().to_string())
+        .filter(|branch| branch != "HEAD")
+}
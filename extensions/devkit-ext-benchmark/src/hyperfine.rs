@@ -0,0 +1,143 @@
+//! Command benchmarks via hyperfine
+//!
+//! Benchmarks arbitrary commands (CLIs, scripts) that aren't cargo/npm bench targets.
+//! Each run exports hyperfine's JSON and Markdown reports to `.dev/bench/hyperfine/`.
+
+use anyhow::{anyhow, Context, Result};
+use devkit_core::config::BenchCommand;
+use devkit_core::utils::shell_quote;
+use devkit_core::AppContext;
+use devkit_tasks::CmdBuilder;
+use serde::Deserialize;
+use std::fs;
+
+use crate::results::{BenchResult, BenchResults};
+
+const EXPORT_DIR: &str = ".dev/bench/hyperfine";
+
+/// Prefix for command benchmark names in recorded results
+pub const COMMAND_PREFIX: &str = "cmd/";
+
+#[derive(Debug, Deserialize)]
+struct HyperfineExport {
+    results: Vec<HyperfineResult>,
+}
+
+#[derive(Debug, Deserialize)]
+struct HyperfineResult {
+    /// Mean wall time in seconds
+    mean: f64,
+}
+
+fn ensure_hyperfine() -> Result<()> {
+    if devkit_core::cmd_exists("hyperfine") {
+        return Ok(());
+    }
+    Err(anyhow!(
+        "hyperfine not found. Install with: cargo install hyperfine (or brew install hyperfine)"
+    ))
+}
+
+/// Run a command under hyperfine, returning its mean time per run
+pub fn run_hyperfine(ctx: &AppContext, name: &str, bench: &BenchCommand) -> Result<BenchResult> {
+    ensure_hyperfine()?;
+
+    let export_dir = ctx.repo.join(EXPORT_DIR);
+    fs::create_dir_all(&export_dir)?;
+    let json_path = export_dir.join(format!("{}.json", name));
+    let markdown_path = export_dir.join(format!("{}.md", name));
+
+    let mut cmd = CmdBuilder::new("hyperfine")
+        .args(["--warmup", &bench.warmup.to_string()])
+        .args(["--command-name", name]);
+    if let Some(runs) = bench.runs {
+        cmd = cmd.args(["--runs", &runs.to_string()]);
+    }
+    if let Some(prepare) = &bench.prepare {
+        cmd = cmd.args(["--prepare", prepare]);
+    }
+    cmd = cmd
+        .arg("--export-json")
+        .arg(json_path.to_string_lossy())
+        .arg("--export-markdown")
+        .arg(markdown_path.to_string_lossy())
+        .arg(&bench.command);
+
+    let code = cmd.cwd(&ctx.repo).inherit_io().run()?;
+    if code != 0 {
+        return Err(anyhow!(
+            "hyperfine exited with code {} for '{}'",
+            code,
+            name
+        ));
+    }
+
+    let export: HyperfineExport = serde_json::from_str(&fs::read_to_string(&json_path)?)
+        .with_context(|| format!("Invalid hyperfine export: {}", json_path.display()))?;
+    let mean = export
+        .results
+        .first()
+        .map(|r| r.mean)
+        .ok_or_else(|| anyhow!("hyperfine reported no results for '{}'", name))?;
+
+    ctx.print_info(&format!("Reports: {}", markdown_path.display()));
+    Ok(BenchResult {
+        mean_ns: mean * 1e9,
+    })
+}
+
+/// Run the named `[bench.*]` entries (all of them when `names` is empty)
+pub fn run_command_benchmarks(ctx: &AppContext, names: &[String]) -> Result<BenchResults> {
+    let config = &ctx.config.global.bench;
+    let names: Vec<String> = if names.is_empty() {
+        config
+            .command_names()
+            .into_iter()
+            .map(String::from)
+            .collect()
+    } else {
+        names.to_vec()
+    };
+
+    let mut results = BenchResults::new();
+    for name in &names {
+        let bench = config.commands.get(name).ok_or_else(|| {
+            anyhow!(
+                "Benchmark '{}' not found in [bench]\nAvailable: {}",
+                name,
+                config.command_names().join(", ")
+            )
+        })?;
+        ctx.print_info(&format!("Benchmarking {}: {}", name, bench.command));
+        let result = run_hyperfine(ctx, name, bench)?;
+        results.insert(format!("{}{}", COMMAND_PREFIX, name), result);
+    }
+    Ok(results)
+}
+
+/// Benchmark an ad-hoc command given on the command line
+///
+/// hyperfine runs it through a shell, so each argument is quoted to keep it intact.
+pub fn run_adhoc_benchmark(
+    ctx: &AppContext,
+    name: &str,
+    command: &[String],
+    warmup: u32,
+    runs: Option<u32>,
+) -> Result<BenchResults> {
+    let bench = BenchCommand {
+        command: command
+            .iter()
+            .map(|arg| shell_quote(arg))
+            .collect::<Vec<_>>()
+            .join(" "),
+        warmup,
+        runs,
+        prepare: None,
+    };
+    let result = run_hyperfine(ctx, name, &bench)?;
+    Ok(BenchResults::from([(
+        format!("{}{}", COMMAND_PREFIX, name),
+        result,
+    )]))
+}
@@ -0,0 +1,359 @@
+//! Benchmarking extension for devkit
+//!
+//! Runs each package's `[cmd.bench]` from dev.toml (falling back to workspace-level
+//! `cargo bench` (criterion) and `npm run bench` (benchmark.js-style output)) and
+//! records the results under `.dev/bench/` keyed by commit and date.
+//! `devkit bench compare <ref>` shows per-benchmark deltas against the results recorded
+//! for another branch or commit.
+//!
+//! `devkit bench --check` fails when any benchmark is slower than the committed baseline
+//! (`devkit bench --save-baseline`) by more than `[bench] threshold` percent.
+//!
+//! Arbitrary commands can be benchmarked with hyperfine, either ad hoc
+//! (`devkit bench cmd -- <command>`) or from config:
+//! ```toml
+//! [bench.startup]
+//! command = "./target/release/mycli --help"
+//! warmup = 3
+//! ```
+
+mod compare;
+mod history;
+mod hyperfine;
+mod regression;
+mod results;
+
+pub use compare::{compare_results, print_deltas, BenchDelta};
+pub use history::{latest_run_for_ref, load_runs, save_run, BenchRun};
+pub use hyperfine::{run_adhoc_benchmark, run_command_benchmarks, run_hyperfine};
+pub use regression::{check_regressions, find_missing, find_regressions, save_baseline};
+pub use results::{
+    collect_criterion, format_duration, parse_ops_per_sec, BenchResult, BenchResults,
+};
+
+use anyhow::{anyhow, Result};
+use devkit_core::{AppContext, Extension, MenuItem};
+use devkit_tasks::{print_results, run_cmd, CmdBuilder, CmdOptions};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+pub struct BenchmarkExtension;
+
+/// Options for running benchmarks
+#[derive(Debug, Default)]
+pub struct BenchOptions {
+    /// Only run `[cmd.bench]` for these packages
+    pub packages: Vec<String>,
+    /// `[cmd.bench]` variant to run (e.g., "quick")
+    pub variant: Option<String>,
+}
+
+impl Extension for BenchmarkExtension {
+    fn name(&self) -> &str {
+        "benchmark"
+    }
+
+    fn is_available(&self, ctx: &AppContext) -> bool {
+        has_package_benches(ctx)
+            || has_cargo_benches(ctx)
+            || has_npm_bench(ctx)
+            || has_command_benches(ctx)
+    }
+
+    fn unavailable_reason(&self, _ctx: &AppContext) -> Option<String> {
+        Some("no benchmarks found ([cmd.bench], cargo benches, npm bench or [bench.*])".to_string())
+    }
+
+    fn menu_items(&self, ctx: &AppContext) -> Vec<MenuItem> {
+        let base = ctx.config.global.git.default_pr_base.clone();
+        vec![
+            MenuItem {
+                label: "⏱  Run benchmarks".to_string(),
+                group: Some("⏱  Benchmarks".to_string()),
+                handler: Box::new(|ctx| {
+                    run_benchmarks(ctx, &BenchOptions::default())
+                        .map(|_| ())
+                        .map_err(Into::into)
+                }),
+            },
+            MenuItem {
+                label: format!("📈 Compare benchmarks with {}", base),
+                group: Some("⏱  Benchmarks".to_string()),
+                handler: Box::new(move |ctx| compare_benchmarks(ctx, &base).map_err(Into::into)),
+            },
+        ]
+    }
+}
+
+fn has_package_benches(ctx: &AppContext) -> bool {
+    !ctx.config.packages_with_cmd("bench").is_empty()
+}
+
+fn has_cargo_benches(ctx: &AppContext) -> bool {
+    ctx.features.cargo && cargo_benches_in(&ctx.repo)
+}
+
+/// Whether the crate at `dir`, or any workspace member, has a `benches/` directory
+/// or `[[bench]]` targets
+fn cargo_benches_in(dir: &Path) -> bool {
+    let Some(manifest) = read_manifest(dir) else {
+        return false;
+    };
+    if crate_has_benches(dir, &manifest) {
+        return true;
+    }
+
+    manifest
+        .get("workspace")
+        .and_then(|workspace| workspace.get("members"))
+        .and_then(|members| members.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|member| member.as_str())
+        .filter_map(|member| glob::glob(&dir.join(member).to_string_lossy()).ok())
+        .flatten()
+        .flatten()
+        .any(|member| read_manifest(&member).is_some_and(|m| crate_has_benches(&member, &m)))
+}
+
+fn crate_has_benches(dir: &Path, manifest: &toml::Table) -> bool {
+    dir.join("benches").is_dir() || manifest.get("bench").is_some_and(|b| b.is_array())
+}
+
+fn read_manifest(dir: &Path) -> Option<toml::Table> {
+    std::fs::read_to_string(dir.join("Cargo.toml"))
+        .ok()?
+        .parse()
+        .ok()
+}
+
+fn has_command_benches(ctx: &AppContext) -> bool {
+    !ctx.config.global.bench.commands.is_empty()
+}
+
+fn has_npm_bench(ctx: &AppContext) -> bool {
+    std::fs::read_to_string(ctx.repo.join("package.json"))
+        .ok()
+        .and_then(|s| serde_json::from_str::<serde_json::Value>(&s).ok())
+        .is_some_and(|pkg| pkg["scripts"]["bench"].is_string())
+}
+
+/// Cargo target directory, honoring `CARGO_TARGET_DIR`
+fn cargo_target_dir(ctx: &AppContext) -> PathBuf {
+    std::env::var_os("CARGO_TARGET_DIR")
+        .map(PathBuf::from)
+        .map(|dir| {
+            if dir.is_absolute() {
+                dir
+            } else {
+                ctx.repo.join(dir)
+            }
+        })
+        .unwrap_or_else(|| ctx.repo.join("target"))
+}
+
+/// Run all benchmarks and record the results for the current commit
+pub fn run_benchmarks(ctx: &AppContext, opts: &BenchOptions) -> Result<BenchRun> {
+    ctx.print_header("Running benchmarks");
+
+    let started = SystemTime::now();
+    let mut results = BenchResults::new();
+    let mut ran_any = false;
+
+    if has_package_benches(ctx) {
+        ran_any = true;
+        results.extend(run_package_benches(ctx, opts, started)?);
+    } else if !opts.packages.is_empty() || opts.variant.is_some() {
+        return Err(anyhow!(
+            "No packages define [cmd.bench] in dev.toml, so --package/--variant don't apply"
+        ));
+    } else if has_cargo_benches(ctx) {
+        ran_any = true;
+        ctx.print_info("cargo bench");
+        let code = CmdBuilder::new("cargo")
+            .arg("bench")
+            .cwd(&ctx.repo)
+            .inherit_io()
+            .run()?;
+        if code != 0 {
+            return Err(anyhow!("cargo bench exited with code {}", code));
+        }
+        results.extend(collect_criterion(&cargo_target_dir(ctx), started));
+    }
+
+    if has_npm_bench(ctx) && !has_package_benches(ctx) {
+        ran_any = true;
+        ctx.print_info("npm run bench");
+        let output = CmdBuilder::new("npm")
+            .args(["run", "bench"])
+            .cwd(&ctx.repo)
+            .capture_stdout()
+            .run_capture()?;
+        let stdout = output.stdout_string();
+        print!("{}", stdout);
+        results.extend(parse_ops_per_sec(&stdout));
+    }
+
+    if has_command_benches(ctx) {
+        ran_any = true;
+        results.extend(run_command_benchmarks(ctx, &[])?);
+    }
+
+    if !ran_any {
+        return Err(anyhow!(
+            "No benchmarks found (expected a Cargo workspace, a package.json 'bench' script or [bench] commands)"
+        ));
+    }
+
+    record_results(ctx, results)
+}
+
+/// Run `[cmd.bench]` in each selected package and collect its results
+///
+/// Output is captured so benchmark.js-style lines can be parsed; those results are
+/// prefixed with the package name. Criterion results are read from the workspace
+/// target dir and each package's own target dir.
+fn run_package_benches(
+    ctx: &AppContext,
+    opts: &BenchOptions,
+    started: SystemTime,
+) -> Result<BenchResults> {
+    let cmd_opts = CmdOptions {
+        parallel: false,
+        jobs: None,
+        variant: opts.variant.clone(),
+        packages: opts.packages.clone(),
+        capture: true,
+        // A cached run has nothing to measure
+        force: true,
+    };
+    let cmd_results = run_cmd(ctx, "bench", &cmd_opts)?;
+
+    let mut results = BenchResults::new();
+    for result in &cmd_results {
+        let output = result.output.as_deref().unwrap_or_default();
+        print!("{}", output);
+        for (name, bench) in parse_ops_per_sec(output) {
+            results.insert(format!("{}/{}", result.package, name), bench);
+        }
+    }
+
+    if cmd_results.iter().any(|r| !r.success) {
+        print_results(ctx, &cmd_results);
+        return Err(anyhow!("Benchmarks failed"));
+    }
+
+    results.extend(collect_criterion(&cargo_target_dir(ctx), started));
+    for (name, _, _) in ctx.config.packages_with_cmd("bench") {
+        if let Some(pkg) = ctx.config.get_package(name) {
+            results.extend(collect_criterion(&pkg.path.join("target"), started));
+        }
+    }
+    Ok(results)
+}
+
+/// Benchmark commands with hyperfine and record the results
+///
+/// Runs `command` ad hoc when given, otherwise the `[bench.*]` entries in `names`
+/// (or all of them).
+pub fn bench_commands(
+    ctx: &AppContext,
+    names: &[String],
+    command: &[String],
+    warmup: u32,
+    runs: Option<u32>,
+) -> Result<BenchRun> {
+    ctx.print_header("Benchmarking commands");
+
+    let results = if command.is_empty() {
+        if !has_command_benches(ctx) && names.is_empty() {
+            return Err(anyhow!(
+                "No [bench] commands configured. Pass one with: devkit bench cmd -- <command>"
+            ));
+        }
+        run_command_benchmarks(ctx, names)?
+    } else {
+        let name = names.first().map(String::as_str).unwrap_or("adhoc");
+        run_adhoc_benchmark(ctx, name, command, warmup, runs)?
+    };
+
+    record_results(ctx, results)
+}
+
+/// Save results for the current commit to the benchmark history
+fn record_results(ctx: &AppContext, results: BenchResults) -> Result<BenchRun> {
+    let run = BenchRun::new(&ctx.repo, results)?;
+    println!();
+    if run.results.is_empty() {
+        ctx.print_warning(
+            "No benchmark results recognized (criterion estimates or 'x ops/sec' output)",
+        );
+        return Ok(run);
+    }
+
+    let path = save_run(&ctx.repo, &run)?;
+    ctx.print_success(&format!(
+        "✓ Recorded {} benchmark results for {}",
+        run.results.len(),
+        run.short_sha()
+    ));
+    ctx.print_info(&format!("Saved to {}", path.display()));
+    Ok(run)
+}
+
+/// Compare the latest results for HEAD against those recorded for `reference`
+pub fn compare_benchmarks(ctx: &AppContext, reference: &str) -> Result<()> {
+    let base = latest_run_for_ref(&ctx.repo, reference)?;
+    let current = latest_run_for_ref(&ctx.repo, "HEAD")?;
+
+    ctx.print_header(&format!(
+        "Benchmarks: {} ({}) → HEAD ({})",
+        reference,
+        base.short_sha(),
+        current.short_sha()
+    ));
+    print_deltas(&compare_results(&base.results, &current.results));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_cargo_benches_in() {
+        let dir = std::env::temp_dir().join(format!("devkit-bench-{}", std::process::id()));
+        fs::create_dir_all(dir.join("crates/core")).unwrap();
+        fs::write(
+            dir.join("Cargo.toml"),
+            "[workspace]\nmembers = [\"crates/*\"]\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("crates/core/Cargo.toml"),
+            "[package]\nname = \"core\"\n",
+        )
+        .unwrap();
+        assert!(!cargo_benches_in(&dir));
+
+        // A [[bench]] target in a member
+        fs::write(
+            dir.join("crates/core/Cargo.toml"),
+            "[package]\nname = \"core\"\n\n[[bench]]\nname = \"parse\"\nharness = false\n",
+        )
+        .unwrap();
+        assert!(cargo_benches_in(&dir));
+
+        // A benches/ directory at the root
+        fs::write(
+            dir.join("crates/core/Cargo.toml"),
+            "[package]\nname = \"core\"\n",
+        )
+        .unwrap();
+        fs::create_dir_all(dir.join("benches")).unwrap();
+        assert!(cargo_benches_in(&dir));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
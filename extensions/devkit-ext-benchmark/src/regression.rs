@@ -0,0 +1,186 @@
+//! Performance regression gating
+//!
+//! `devkit bench --check` compares fresh results with a baseline and fails when any
+//! benchmark slowed down by more than the configured threshold, or when the run
+//! produced no results or lost benchmarks the baseline has. The baseline is a
+//! committed results file (`[bench] baseline`), so CI can gate without local history.
+
+use anyhow::{anyhow, Context, Result};
+use console::style;
+use devkit_core::AppContext;
+use std::fs;
+
+use crate::compare::{compare_results, print_deltas, BenchDelta};
+use crate::history::{latest_run_for_ref, BenchRun};
+
+/// Benchmarks that slowed down by more than `threshold` percent
+pub fn find_regressions(deltas: &[BenchDelta], threshold: f64) -> Vec<&BenchDelta> {
+    deltas
+        .iter()
+        .filter(|d| d.change_pct().is_some_and(|pct| pct > threshold))
+        .collect()
+}
+
+/// Benchmarks in the baseline that the current run didn't produce
+pub fn find_missing(deltas: &[BenchDelta]) -> Vec<&BenchDelta> {
+    deltas
+        .iter()
+        .filter(|d| d.base_ns.is_some() && d.current_ns.is_none())
+        .collect()
+}
+
+/// Write a run as the baseline file
+pub fn save_baseline(ctx: &AppContext, run: &BenchRun) -> Result<()> {
+    let path = ctx.repo.join(&ctx.config.global.bench.baseline);
+    fs::write(&path, serde_json::to_string_pretty(run)?)?;
+    ctx.print_success(&format!(
+        "✓ Saved baseline ({} benchmarks) to {}",
+        run.results.len(),
+        path.display()
+    ));
+    Ok(())
+}
+
+fn load_baseline(ctx: &AppContext) -> Result<BenchRun> {
+    let path = ctx.repo.join(&ctx.config.global.bench.baseline);
+    if !path.exists() {
+        return Err(anyhow!(
+            "No benchmark baseline at {}\nCreate one with: devkit bench --save-baseline",
+            path.display()
+        ));
+    }
+    let contents = fs::read_to_string(&path)?;
+    serde_json::from_str(&contents)
+        .with_context(|| format!("Invalid benchmark baseline: {}", path.display()))
+}
+
+/// Fail if `current` regressed against the baseline file, or the results for `against`
+pub fn check_regressions(
+    ctx: &AppContext,
+    current: &BenchRun,
+    against: Option<&str>,
+    threshold: Option<f64>,
+) -> Result<()> {
+    let threshold = threshold.unwrap_or(ctx.config.global.bench.threshold);
+    let baseline = match against {
+        Some(reference) => latest_run_for_ref(&ctx.repo, reference)?,
+        None => load_baseline(ctx)?,
+    };
+
+    if current.results.is_empty() {
+        return Err(anyhow!(
+            "No benchmark results to check against {}",
+            against.unwrap_or("the baseline")
+        ));
+    }
+
+    ctx.print_header(&format!(
+        "Regression check against {} (threshold {}%)",
+        against.unwrap_or("baseline"),
+        threshold
+    ));
+    let deltas = compare_results(&baseline.results, &current.results);
+    print_deltas(&deltas);
+    println!();
+
+    let regressions = find_regressions(&deltas, threshold);
+    let missing = find_missing(&deltas);
+    if regressions.is_empty() && missing.is_empty() {
+        ctx.print_success("✓ No performance regressions");
+        return Ok(());
+    }
+
+    for delta in &regressions {
+        println!(
+            "  {} {} ({:+.1}%)",
+            style("✗").red(),
+            delta.name,
+            delta.change_pct().unwrap_or_default()
+        );
+    }
+    for delta in &missing {
+        println!(
+            "  {} {} (missing from this run)",
+            style("✗").red(),
+            delta.name
+        );
+    }
+    match (regressions.len(), missing.len()) {
+        (regressed, 0) => Err(anyhow!(
+            "{} benchmark(s) regressed by more than {}%",
+            regressed,
+            threshold
+        )),
+        (0, missing) => Err(anyhow!("{} benchmark(s) missing from this run", missing)),
+        (regressed, missing) => Err(anyhow!(
+            "{} benchmark(s) regressed by more than {}% and {} are missing from this run",
+            regressed,
+            threshold,
+            missing
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::results::BenchResult;
+
+    fn delta(name: &str, base: f64, current: f64) -> BenchDelta {
+        BenchDelta {
+            name: name.to_string(),
+            base_ns: Some(base),
+            current_ns: Some(current),
+        }
+    }
+
+    #[test]
+    fn test_find_regressions() {
+        let deltas = vec![
+            delta("faster", 100.0, 80.0),
+            delta("noise", 100.0, 104.0),
+            delta("slower", 100.0, 120.0),
+            BenchDelta {
+                name: "new".to_string(),
+                base_ns: None,
+                current_ns: Some(500.0),
+            },
+        ];
+
+        let regressed: Vec<_> = find_regressions(&deltas, 5.0)
+            .into_iter()
+            .map(|d| d.name.as_str())
+            .collect();
+        assert_eq!(regressed, vec!["slower"]);
+    }
+
+    #[test]
+    fn test_check_regressions_fails_on_missing_results() {
+        let repo = std::env::temp_dir().join(format!("devkit-bench-check-{}", std::process::id()));
+        fs::create_dir_all(&repo).unwrap();
+        let ctx = AppContext {
+            repo: repo.clone(),
+            quiet: true,
+            config: Default::default(),
+            features: Default::default(),
+        };
+        let run = |names: &[&str]| BenchRun {
+            sha: "abc123".to_string(),
+            branch: None,
+            date: "2024-05-01T10:00:00Z".to_string(),
+            results: names
+                .iter()
+                .map(|name| (name.to_string(), BenchResult { mean_ns: 100.0 }))
+                .collect(),
+        };
+        save_baseline(&ctx, &run(&["parse", "render"])).unwrap();
+
+        assert!(check_regressions(&ctx, &run(&["parse", "render"]), None, None).is_ok());
+        let error = check_regressions(&ctx, &run(&[]), None, None).unwrap_err();
+        assert!(error.to_string().starts_with("No benchmark results"));
+        let error = check_regressions(&ctx, &run(&["parse"]), None, None).unwrap_err();
+        assert_eq!(error.to_string(), "1 benchmark(s) missing from this run");
+
+        fs::remove_dir_all(&repo).ok();
+    }
+}
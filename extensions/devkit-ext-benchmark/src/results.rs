@@ -0,0 +1,136 @@
+//! Benchmark result collection
+//!
+//! Reads criterion's `estimates.json` files for Rust benchmarks and parses
+//! benchmark.js-style `ops/sec` lines from Node benchmark output.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::time::SystemTime;
+use walkdir::WalkDir;
+
+/// A single benchmark measurement
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct BenchResult {
+    /// Mean time per iteration in nanoseconds
+    pub mean_ns: f64,
+}
+
+/// Benchmark results keyed by benchmark name
+pub type BenchResults = BTreeMap<String, BenchResult>;
+
+#[derive(Debug, Deserialize)]
+struct CriterionEstimates {
+    mean: CriterionEstimate,
+}
+
+#[derive(Debug, Deserialize)]
+struct CriterionEstimate {
+    point_estimate: f64,
+}
+
+/// Collect criterion results written after `since`
+///
+/// Criterion keeps results for every benchmark it has ever run, so only estimates
+/// refreshed by the current run are collected.
+pub fn collect_criterion(target_dir: &Path, since: SystemTime) -> BenchResults {
+    let criterion_dir = target_dir.join("criterion");
+    let mut results = BenchResults::new();
+
+    for entry in WalkDir::new(&criterion_dir)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_name() == "estimates.json")
+    {
+        let path = entry.path();
+        let Some(bench_dir) = path.parent().filter(|p| p.ends_with("new")) else {
+            continue;
+        };
+        let fresh = fs::metadata(path)
+            .and_then(|m| m.modified())
+            .map(|modified| modified >= since)
+            .unwrap_or(false);
+        if !fresh {
+            continue;
+        }
+
+        let Some(name) = bench_dir
+            .parent()
+            .and_then(|dir| dir.strip_prefix(&criterion_dir).ok())
+        else {
+            continue;
+        };
+
+        let estimates = fs::read_to_string(path)
+            .ok()
+            .and_then(|s| serde_json::from_str::<CriterionEstimates>(&s).ok());
+        if let Some(estimates) = estimates {
+            results.insert(
+                name.to_string_lossy().replace('\\', "/"),
+                BenchResult {
+                    mean_ns: estimates.mean.point_estimate,
+                },
+            );
+        }
+    }
+
+    results
+}
+
+/// Parse benchmark.js-style output (`name x 1,234 ops/sec ±0.5% (90 runs sampled)`)
+pub fn parse_ops_per_sec(output: &str) -> BenchResults {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (name, rest) = line.trim().rsplit_once(" x ")?;
+            let ops = rest.split_whitespace().next()?.replace(',', "");
+            let ops: f64 = ops.parse().ok()?;
+            if !rest.contains("ops/sec") || ops <= 0.0 {
+                return None;
+            }
+            Some((name.trim().to_string(), BenchResult { mean_ns: 1e9 / ops }))
+        })
+        .collect()
+}
+
+/// Format nanoseconds with a readable unit
+pub fn format_duration(ns: f64) -> String {
+    if ns >= 1e9 {
+        format!("{:.2} s", ns / 1e9)
+    } else if ns >= 1e6 {
+        format!("{:.2} ms", ns / 1e6)
+    } else if ns >= 1e3 {
+        format!("{:.2} µs", ns / 1e3)
+    } else {
+        format!("{:.2} ns", ns)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ops_per_sec() {
+        let output = "\
+> bench
+> node bench.js
+
+RegExp#test x 4,000,000 ops/sec ±0.50% (92 runs sampled)
+String#indexOf x 1,000,000,000 ops/sec ±1.2% (95 runs sampled)
+Fastest is String#indexOf
+";
+        let results = parse_ops_per_sec(output);
+        assert_eq!(results.len(), 2);
+        assert_eq!(results["RegExp#test"].mean_ns, 250.0);
+        assert_eq!(results["String#indexOf"].mean_ns, 1.0);
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(12.0), "12.00 ns");
+        assert_eq!(format_duration(1_500.0), "1.50 µs");
+        assert_eq!(format_duration(2_500_000.0), "2.50 ms");
+    }
+}
@@ -0,0 +1,67 @@
+//! `devkit cache` subcommand
+
+use anyhow::Result;
+use clap::{ArgGroup, Args, Command, FromArgMatches, Subcommand};
+use devkit_core::AppContext;
+
+use crate::{clean_all, clean_cache, parse_size, prune, show_stats, PrunePolicy};
+use std::time::Duration;
+
+#[derive(Args)]
+pub struct CacheArgs {
+    #[command(subcommand)]
+    action: Option<CacheAction>,
+}
+
+#[derive(Subcommand)]
+enum CacheAction {
+    /// Show cache sizes (default)
+    Stats,
+    /// Remove build caches
+    Clean {
+        /// Only remove the cache whose name contains this (e.g. "cargo", "node")
+        name: Option<String>,
+    },
+    /// Remove old cache entries, or the oldest until each cache fits a size budget
+    #[command(group(ArgGroup::new("policy").required(true).multiple(true)))]
+    Prune {
+        /// Only prune the cache whose name contains this
+        name: Option<String>,
+        /// Remove entries not modified in this many days
+        #[arg(long, group = "policy")]
+        max_age_days: Option<u64>,
+        /// Trim each cache to this size (e.g. 500M, 10G), oldest entries first
+        #[arg(long, group = "policy", value_parser = parse_size)]
+        max_size: Option<u64>,
+        /// Show what would be removed without removing it
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+pub fn command() -> Command {
+    CacheArgs::augment_args(Command::new("cache").about("Build cache statistics and cleanup"))
+}
+
+pub fn handle(ctx: &AppContext, matches: &clap::ArgMatches) -> Result<()> {
+    let args = CacheArgs::from_arg_matches(matches)?;
+
+    match args.action {
+        Some(CacheAction::Stats) | None => show_stats(ctx),
+        Some(CacheAction::Clean { name: Some(name) }) => clean_cache(ctx, &name),
+        Some(CacheAction::Clean { name: None }) => clean_all(ctx),
+        Some(CacheAction::Prune {
+            name,
+            max_age_days,
+            max_size,
+            dry_run,
+        }) => {
+            let policy = PrunePolicy {
+                max_age: max_age_days.map(|days| Duration::from_secs(days * 24 * 60 * 60)),
+                max_size,
+                dry_run,
+            };
+            prune(ctx, name.as_deref(), &policy)
+        }
+    }
+}
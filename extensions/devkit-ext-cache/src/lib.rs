@@ -1,7 +1,11 @@
 //! Build cache management extension
 //!
 //! Provides commands to clean, analyze, and manage build caches
-//! across different build systems (cargo, npm, gradle, maven, etc.)
+//! across different build systems (cargo, npm, gradle, maven, etc.).
+//! `devkit cache prune` removes old entries or trims caches to a size budget.
+
+mod cli;
+mod prune;
 
 use anyhow::Result;
 use devkit_core::{AppContext, Extension, MenuItem};
@@ -10,6 +14,8 @@ use std::fs;
 use std::path::{Path, PathBuf};
 use walkdir::WalkDir;
 
+pub use prune::{parse_size, prune, PrunePolicy};
+
 pub struct CacheExtension;
 
 impl Extension for CacheExtension {
@@ -36,6 +42,19 @@ impl Extension for CacheExtension {
             },
         ]
     }
+
+    fn subcommands(&self) -> Vec<clap::Command> {
+        vec![cli::command()]
+    }
+
+    fn handle_cli(
+        &self,
+        ctx: &AppContext,
+        name: &str,
+        matches: &clap::ArgMatches,
+    ) -> Option<devkit_core::Result<()>> {
+        (name == "cache").then(|| cli::handle(ctx, matches).map_err(Into::into))
+    }
 }
 
 #[derive(Debug)]
@@ -232,9 +251,3 @@ pub fn clean_cache(ctx: &AppContext, cache_name: &str) -> Result<()> {
 
     Ok(())
 }
-
-/// Prune old cache entries (not implemented yet)
-pub fn prune(_ctx: &AppContext, _max_age_days: u32) -> Result<()> {
-    // TODO: Implement age-based pruning
-    Ok(())
-}
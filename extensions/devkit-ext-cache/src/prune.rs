@@ -0,0 +1,236 @@
+//! Age- and size-based cache pruning
+//!
+//! Each top-level entry of a cache (a `node_modules` package, `target/debug`, a Go
+//! build bucket) is aged by the newest file in it. Entries older than the age limit
+//! are removed, then the oldest remaining ones until the cache fits the size budget.
+
+use anyhow::{anyhow, Result};
+use devkit_core::AppContext;
+use humansize::{format_size, BINARY};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use walkdir::WalkDir;
+
+use crate::detect_caches;
+
+/// What to remove; entries matching either limit go
+#[derive(Debug, Clone, Default)]
+pub struct PrunePolicy {
+    /// Remove entries not modified for this long
+    pub max_age: Option<Duration>,
+    /// Trim each cache to this many bytes, oldest entries first
+    pub max_size: Option<u64>,
+    /// Only report what would be removed
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Entry {
+    path: PathBuf,
+    size: u64,
+    /// Newest modification time of anything in the entry
+    modified: SystemTime,
+}
+
+/// Prune the caches (those whose name contains `name`, when given)
+pub fn prune(ctx: &AppContext, name: Option<&str>, policy: &PrunePolicy) -> Result<()> {
+    let caches: Vec<_> = detect_caches(ctx)
+        .into_iter()
+        .filter(|cache| {
+            name.is_none_or(|name| cache.name.to_lowercase().contains(&name.to_lowercase()))
+        })
+        .collect();
+    if caches.is_empty() {
+        return match name {
+            Some(name) => Err(anyhow!("Cache '{}' not found", name)),
+            None => {
+                ctx.print_info("No build caches found");
+                Ok(())
+            }
+        };
+    }
+
+    ctx.print_header(if policy.dry_run {
+        "Pruning Build Caches (dry run)"
+    } else {
+        "Pruning Build Caches"
+    });
+    println!();
+
+    let now = SystemTime::now();
+    let mut total = 0;
+    for cache in &caches {
+        let entries = entries(&cache.path);
+        let count = entries.len();
+        let doomed = select(entries, policy, now);
+        let size: u64 = doomed.iter().map(|entry| entry.size).sum();
+
+        if !policy.dry_run {
+            for entry in &doomed {
+                // Symlinks (e.g. workspace packages in node_modules) are removed, not followed
+                let is_dir = fs::symlink_metadata(&entry.path).is_ok_and(|meta| meta.is_dir());
+                let removed = if is_dir {
+                    fs::remove_dir_all(&entry.path)
+                } else {
+                    fs::remove_file(&entry.path)
+                };
+                if let Err(e) = removed {
+                    ctx.print_warning(&format!("Failed to remove {}: {}", entry.path.display(), e));
+                }
+            }
+        }
+
+        println!(
+            "  {} - {} of {} entries, {}",
+            cache.name,
+            doomed.len(),
+            count,
+            format_size(size, BINARY)
+        );
+        total += size;
+    }
+
+    println!();
+    if policy.dry_run {
+        ctx.print_info(&format!("Would free {}", format_size(total, BINARY)));
+    } else {
+        ctx.print_success(&format!("✓ Freed {}", format_size(total, BINARY)));
+    }
+    Ok(())
+}
+
+/// Top-level entries of a cache with their sizes and ages
+fn entries(cache: &Path) -> Vec<Entry> {
+    let Ok(children) = fs::read_dir(cache) else {
+        return Vec::new();
+    };
+    children
+        .flatten()
+        .map(|child| {
+            let mut size = 0;
+            let mut modified = SystemTime::UNIX_EPOCH;
+            for meta in WalkDir::new(child.path())
+                .into_iter()
+                .filter_map(|e| e.ok())
+                .filter_map(|e| e.metadata().ok())
+            {
+                if meta.is_file() {
+                    size += meta.len();
+                }
+                if let Ok(time) = meta.modified() {
+                    modified = modified.max(time);
+                }
+            }
+            Entry {
+                path: child.path(),
+                size,
+                modified,
+            }
+        })
+        .collect()
+}
+
+/// Entries to remove under the policy, oldest first
+fn select(mut entries: Vec<Entry>, policy: &PrunePolicy, now: SystemTime) -> Vec<Entry> {
+    entries.sort_by_key(|entry| entry.modified);
+
+    let too_old = |entry: &Entry| {
+        policy.max_age.is_some_and(|max_age| {
+            now.duration_since(entry.modified)
+                .is_ok_and(|age| age > max_age)
+        })
+    };
+    let mut kept: u64 = entries.iter().map(|entry| entry.size).sum();
+
+    entries
+        .into_iter()
+        .filter(|entry| {
+            let over_budget = policy.max_size.is_some_and(|max_size| kept > max_size);
+            let remove = too_old(entry) || over_budget;
+            if remove {
+                kept -= entry.size;
+            }
+            remove
+        })
+        .collect()
+}
+
+/// A size like `500M`, `10G`, `1.5GiB` or a number of bytes
+pub fn parse_size(size: &str) -> std::result::Result<u64, String> {
+    let size = size.trim();
+    let split = size
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(size.len());
+    let (number, unit) = size.split_at(split);
+    let number: f64 = number
+        .parse()
+        .map_err(|_| format!("invalid size '{}' (e.g. 500M, 10G)", size))?;
+    let multiplier: u64 = match unit
+        .trim()
+        .to_lowercase()
+        .trim_end_matches("ib")
+        .trim_end_matches('b')
+    {
+        "" => 1,
+        "k" => 1 << 10,
+        "m" => 1 << 20,
+        "g" => 1 << 30,
+        "t" => 1 << 40,
+        _ => return Err(format!("unknown unit in '{}' (use K, M, G or T)", size)),
+    };
+    Ok((number * multiplier as f64) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+    #[test]
+    fn test_select() {
+        let now = SystemTime::now();
+        let entry = |name: &str, size, age_days| Entry {
+            path: PathBuf::from(name),
+            size,
+            modified: now - DAY * age_days,
+        };
+        let entries = vec![
+            entry("new", 100, 1),
+            entry("old", 50, 40),
+            entry("mid", 200, 10),
+        ];
+        let names = |policy: &PrunePolicy| -> Vec<String> {
+            select(entries.clone(), policy, now)
+                .iter()
+                .map(|entry| entry.path.display().to_string())
+                .collect()
+        };
+
+        let by_age = PrunePolicy {
+            max_age: Some(DAY * 30),
+            ..Default::default()
+        };
+        assert_eq!(names(&by_age), ["old"]);
+
+        // 350 bytes in all: dropping "old" and "mid" gets under 150
+        let by_size = PrunePolicy {
+            max_size: Some(150),
+            ..Default::default()
+        };
+        assert_eq!(names(&by_size), ["old", "mid"]);
+
+        assert!(names(&PrunePolicy::default()).is_empty());
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("1024"), Ok(1024));
+        assert_eq!(parse_size("500M"), Ok(500 << 20));
+        assert_eq!(parse_size("1.5GiB"), Ok(3 << 29));
+        assert_eq!(parse_size("10 gb"), Ok(10 << 30));
+        assert!(parse_size("10X").is_err());
+        assert!(parse_size("big").is_err());
+    }
+}
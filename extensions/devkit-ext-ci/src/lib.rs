@@ -7,7 +7,7 @@ use devkit_core::{AppContext, Extension, MenuItem};
 mod status;
 mod workflows;
 
-pub use status::{ci_runs, ci_status};
+pub use status::{ci_runs, ci_status, last_run, CiRun};
 pub use workflows::{ci_cancel, ci_logs, ci_rerun, ci_trigger, ci_watch};
 
 pub struct CiExtension;
@@ -21,6 +21,14 @@ impl Extension for CiExtension {
         ctx.features.git && devkit_core::cmd_exists("gh")
     }
 
+    fn unavailable_reason(&self, ctx: &AppContext) -> Option<String> {
+        Some(if ctx.features.git {
+            "gh not installed".to_string()
+        } else {
+            "not a git repository".to_string()
+        })
+    }
+
     fn menu_items(&self, _ctx: &AppContext) -> Vec<MenuItem> {
         vec![
             MenuItem {
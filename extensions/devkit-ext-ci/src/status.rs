@@ -48,6 +48,53 @@ pub fn ci_status(ctx: &AppContext, protected_branches: Option<Vec<String>>) -> R
     Ok(())
 }
 
+/// A workflow run as `gh run list --json` reports it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CiRun {
+    pub workflow: String,
+    /// queued, in_progress or completed
+    pub status: String,
+    /// success, failure, cancelled, ... (empty until completed)
+    pub conclusion: String,
+    pub url: String,
+}
+
+/// The latest workflow run on `branch`, if there is one
+pub fn last_run(ctx: &AppContext, branch: &str) -> Result<Option<CiRun>> {
+    let output = CmdBuilder::new("gh")
+        .args([
+            "run",
+            "list",
+            "--branch",
+            branch,
+            "--limit",
+            "1",
+            "--json",
+            "workflowName,status,conclusion,url",
+        ])
+        .cwd(&ctx.repo)
+        .capture_stdout()
+        .run_capture()?;
+    parse_runs(&output.stdout_string()).map(|runs| runs.into_iter().next())
+}
+
+/// From `gh run list --json workflowName,status,conclusion,url`
+fn parse_runs(json: &str) -> Result<Vec<CiRun>> {
+    let runs: Vec<serde_json::Value> = serde_json::from_str(json)?;
+    Ok(runs
+        .iter()
+        .map(|run| {
+            let field = |key: &str| run[key].as_str().unwrap_or_default().to_string();
+            CiRun {
+                workflow: field("workflowName"),
+                status: field("status"),
+                conclusion: field("conclusion"),
+                url: field("url"),
+            }
+        })
+        .collect())
+}
+
 /// List recent workflow runs
 pub fn ci_runs(ctx: &AppContext, limit: u32, workflow: Option<&str>) -> Result<()> {
     ctx.print_header("Recent Workflow Runs");
@@ -72,3 +119,26 @@ pub fn ci_runs(ctx: &AppContext, limit: u32, workflow: Option<&str>) -> Result<(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_runs() {
+        let runs = parse_runs(
+            r#"[{"conclusion":"failure","status":"completed","url":"https://github.com/acme/app/actions/runs/42","workflowName":"CI"}]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            runs,
+            [CiRun {
+                workflow: "CI".to_string(),
+                status: "completed".to_string(),
+                conclusion: "failure".to_string(),
+                url: "https://github.com/acme/app/actions/runs/42".to_string(),
+            }]
+        );
+        assert!(parse_runs("[]").unwrap().is_empty());
+    }
+}
@@ -85,6 +85,7 @@ fn execute_command(ctx: &AppContext, cmd_name: &str, variant: Option<&str>) -> d
     let opts = devkit_tasks::CmdOptions {
         packages: vec![],
         parallel: false,
+        jobs: None,
         variant: variant.map(String::from),
         capture: false,
     };
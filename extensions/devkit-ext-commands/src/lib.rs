@@ -91,8 +91,10 @@ fn execute_command(
     let opts = CmdOptions {
         packages: vec![],
         parallel: false,
+        jobs: None,
         variant: variant.map(String::from),
         capture: false,
+        force: false,
     };
 
     run_cmd(ctx, cmd_name, &opts).map_err(|e| devkit_core::DevkitError::Other(e))?;
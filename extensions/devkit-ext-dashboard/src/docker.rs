@@ -0,0 +1,221 @@
+//! Service data from Docker Compose
+//!
+//! Status comes from `docker compose ps --format json` (one object per line, or an
+//! array from older Compose versions) and CPU/memory from `docker stats --no-stream`.
+
+use anyhow::{anyhow, Context, Result};
+use devkit_core::oplog::LoggedCommand;
+use devkit_core::utils::docker_compose_program;
+use devkit_core::validation::{compose_file, compose_files};
+use devkit_core::AppContext;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+
+/// The repo's compose project, from the same files `devkit docker up` uses
+#[derive(Debug, Clone)]
+pub struct ComposeProject {
+    repo: PathBuf,
+    /// Passed with `-f`; empty leaves compose to find its files
+    files: Vec<String>,
+}
+
+impl ComposeProject {
+    pub fn new(ctx: &AppContext) -> Self {
+        Self {
+            repo: ctx.repo.clone(),
+            files: compose_files(&ctx.repo, &ctx.config.global),
+        }
+    }
+
+    /// `docker compose -f <file>...` run from the repo root
+    fn command(&self) -> Result<Command> {
+        if self.files.is_empty() && compose_file(&self.repo).is_none() {
+            return Err(anyhow!("No docker-compose.yml found"));
+        }
+        let (program, args) = docker_compose_program()?;
+        let mut cmd = Command::new(program);
+        cmd.args(args).current_dir(&self.repo);
+        for file in &self.files {
+            cmd.args(["-f", file]);
+        }
+        Ok(cmd)
+    }
+}
+
+/// A compose service's container
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServiceStatus {
+    pub name: String,
+    pub status: ServiceState,
+    /// As `docker compose ps` shows it, e.g. "Up 5 minutes (healthy)"
+    pub detail: String,
+    /// e.g. "0.52%"
+    pub cpu: Option<String>,
+    /// e.g. "25.3MiB / 7.6GiB"
+    pub memory: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServiceState {
+    Running,
+    Starting,
+    Stopped,
+    Error,
+}
+
+impl ServiceState {
+    /// From the `State`, `Health` and `ExitCode` of a `docker compose ps` entry
+    fn from_ps(state: &str, health: &str, exit_code: i64) -> Self {
+        match (state, health) {
+            ("running", "unhealthy") => Self::Error,
+            ("running", "starting") | ("created" | "restarting", _) => Self::Starting,
+            ("running", _) => Self::Running,
+            ("exited", _) if exit_code == 0 => Self::Stopped,
+            ("paused", _) => Self::Stopped,
+            _ => Self::Error,
+        }
+    }
+}
+
+/// The repo's compose services with their status and resource use
+pub fn services(project: &ComposeProject) -> Result<Vec<ServiceStatus>> {
+    let output = project
+        .command()?
+        .args(["ps", "--all", "--format", "json"])
+        .logged_output()
+        .context("Failed to run docker compose ps")?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "docker compose ps failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    let mut services = parse_ps(&String::from_utf8_lossy(&output.stdout))?;
+
+    let running: Vec<&str> = services
+        .iter()
+        .filter(|(_, service)| service.status != ServiceState::Stopped)
+        .map(|(container, _)| container.as_str())
+        .collect();
+    if !running.is_empty() {
+        let output = Command::new("docker")
+            .args(["stats", "--no-stream", "--format", "{{json .}}"])
+            .args(&running)
+            .logged_output()
+            .context("Failed to run docker stats")?;
+        let stats = parse_stats(&String::from_utf8_lossy(&output.stdout));
+        for (container, service) in &mut services {
+            if let Some((cpu, memory)) = stats.get(container.as_str()) {
+                service.cpu = Some(cpu.clone());
+                service.memory = Some(memory.clone());
+            }
+        }
+    }
+
+    Ok(services.into_iter().map(|(_, service)| service).collect())
+}
+
+/// Follow the logs of every service, starting with the last `tail` lines
+pub fn follow_logs(project: &ComposeProject, tail: usize) -> Result<Child> {
+    project
+        .command()?
+        .args(["logs", "-f", "--no-color", "--tail"])
+        .arg(tail.to_string())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .logged_spawn()
+        .context("Failed to run docker compose logs")
+}
+
+/// Services from `docker compose ps --format json`, with their container names,
+/// sorted by service
+fn parse_ps(output: &str) -> Result<Vec<(String, ServiceStatus)>> {
+    let output = output.trim();
+    let entries: Vec<Value> = if output.starts_with('[') {
+        serde_json::from_str(output)?
+    } else {
+        output
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(serde_json::from_str)
+            .collect::<serde_json::Result<_>>()?
+    };
+
+    let mut services: Vec<(String, ServiceStatus)> = entries
+        .iter()
+        .map(|entry| {
+            let field = |key: &str| entry[key].as_str().unwrap_or_default().to_string();
+            let status = ServiceState::from_ps(
+                &field("State"),
+                &field("Health"),
+                entry["ExitCode"].as_i64().unwrap_or(0),
+            );
+            let service = ServiceStatus {
+                name: field("Service"),
+                status,
+                detail: field("Status"),
+                cpu: None,
+                memory: None,
+            };
+            (field("Name"), service)
+        })
+        .collect();
+    services.sort_by(|(_, a), (_, b)| a.name.cmp(&b.name));
+    Ok(services)
+}
+
+/// CPU and memory per container name from `docker stats --format '{{json .}}'`
+fn parse_stats(output: &str) -> HashMap<String, (String, String)> {
+    output
+        .lines()
+        .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+        .filter_map(|stats| {
+            Some((
+                stats["Name"].as_str()?.to_string(),
+                (
+                    stats["CPUPerc"].as_str()?.to_string(),
+                    stats["MemUsage"].as_str()?.to_string(),
+                ),
+            ))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ps() {
+        let lines = r#"{"Name":"app-web-1","Service":"web","State":"running","Health":"","ExitCode":0,"Status":"Up 2 minutes"}
+{"Name":"app-postgres-1","Service":"postgres","State":"running","Health":"healthy","ExitCode":0,"Status":"Up 2 minutes (healthy)"}
+{"Name":"app-worker-1","Service":"worker","State":"exited","Health":"","ExitCode":1,"Status":"Exited (1) 10 seconds ago"}"#;
+        let services = parse_ps(lines).unwrap();
+        let names: Vec<&str> = services.iter().map(|(_, s)| s.name.as_str()).collect();
+        assert_eq!(names, ["postgres", "web", "worker"]);
+        assert_eq!(services[0].0, "app-postgres-1");
+        assert_eq!(services[0].1.status, ServiceState::Running);
+        assert_eq!(services[0].1.detail, "Up 2 minutes (healthy)");
+        assert_eq!(services[2].1.status, ServiceState::Error);
+
+        // Older Compose versions print an array
+        let array =
+            r#"[{"Name":"app-redis-1","Service":"redis","State":"running","Health":"starting"}]"#;
+        let services = parse_ps(array).unwrap();
+        assert_eq!(services[0].1.status, ServiceState::Starting);
+        assert!(parse_ps("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_parse_stats() {
+        let stats = parse_stats(
+            r#"{"Container":"3f2a","Name":"app-web-1","CPUPerc":"0.52%","MemUsage":"25.3MiB / 7.6GiB","MemPerc":"0.32%"}"#,
+        );
+        assert_eq!(
+            stats["app-web-1"],
+            ("0.52%".to_string(), "25.3MiB / 7.6GiB".to_string())
+        );
+    }
+}
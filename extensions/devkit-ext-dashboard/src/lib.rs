@@ -1,6 +1,10 @@
 //! Visual TUI dashboard extension
 //!
-//! Provides a terminal UI with service status, logs, and metrics
+//! Provides a terminal UI with service status, logs, and metrics. Compose services
+//! and their CPU/memory use are refreshed on a background thread, and their logs
+//! are followed into the logs panel (see [`docker`]).
+
+pub mod docker;
 
 use anyhow::Result;
 use crossterm::{
@@ -9,15 +13,28 @@ use crossterm::{
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use devkit_core::{AppContext, Extension, MenuItem};
+use docker::ComposeProject;
+pub use docker::{ServiceState, ServiceStatus};
 use ratatui::{
     backend::CrosstermBackend,
     layout::{Constraint, Direction, Layout},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, List, ListItem, Paragraph},
-    Terminal,
+    widgets::{Block, Borders, Cell, Paragraph, Row, Table},
+    Frame, Terminal,
 };
-use std::io;
+use std::io::{self, BufRead, BufReader};
+use std::process::Child;
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How often services are queried
+const REFRESH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Log lines kept for the logs panel
+const MAX_LOG_LINES: usize = 500;
 
 pub struct DashboardExtension;
 
@@ -33,7 +50,7 @@ impl Extension for DashboardExtension {
     fn menu_items(&self, _ctx: &AppContext) -> Vec<MenuItem> {
         vec![MenuItem {
             label: "📊 Open visual dashboard".to_string(),
-                group: None,
+            group: None,
             handler: Box::new(|ctx| run_dashboard(ctx).map_err(Into::into)),
         }]
     }
@@ -65,84 +82,158 @@ pub fn run_dashboard(ctx: &AppContext) -> Result<()> {
 
 fn run_app<B: ratatui::backend::Backend>(
     terminal: &mut Terminal<B>,
-    _ctx: &AppContext,
+    ctx: &AppContext,
 ) -> Result<()> {
-    loop {
-        terminal.draw(|f| {
-            let chunks = Layout::default()
-                .direction(Direction::Vertical)
-                .margin(1)
-                .constraints([
-                    Constraint::Length(3), // Header
-                    Constraint::Min(10),   // Main content
-                    Constraint::Length(3), // Footer
-                ])
-                .split(f.area());
-
-            // Header
-            let header = Paragraph::new("devkit Dashboard")
-                .style(
-                    Style::default()
-                        .fg(Color::Cyan)
-                        .add_modifier(Modifier::BOLD),
-                )
-                .block(Block::default().borders(Borders::ALL).title("Header"));
-            f.render_widget(header, chunks[0]);
-
-            // Main content - split into left and right
-            let main_chunks = Layout::default()
-                .direction(Direction::Horizontal)
-                .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
-                .split(chunks[1]);
-
-            // Services panel (left)
-            let services = vec![
-                ListItem::new(Line::from(vec![
-                    Span::styled("✓ ", Style::default().fg(Color::Green)),
-                    Span::raw("Docker"),
-                ])),
-                ListItem::new(Line::from(vec![
-                    Span::styled("✓ ", Style::default().fg(Color::Green)),
-                    Span::raw("Postgres"),
-                ])),
-                ListItem::new(Line::from(vec![
-                    Span::styled("✗ ", Style::default().fg(Color::Red)),
-                    Span::raw("Redis"),
-                ])),
-            ];
-            let services_list =
-                List::new(services).block(Block::default().borders(Borders::ALL).title("Services"));
-            f.render_widget(services_list, main_chunks[0]);
-
-            // Logs panel (right)
-            let logs = Paragraph::new("Logs would appear here...\nPress 'q' to quit")
-                .style(Style::default().fg(Color::White))
-                .block(Block::default().borders(Borders::ALL).title("Logs"));
-            f.render_widget(logs, main_chunks[1]);
-
-            // Footer
-            let footer = Paragraph::new("q: Quit | r: Refresh | c: Clear")
-                .style(Style::default().fg(Color::Yellow))
-                .block(Block::default().borders(Borders::ALL).title("Help"));
-            f.render_widget(footer, chunks[2]);
-        })?;
+    let state = Arc::new(Mutex::new(DashboardState::new()));
+    let project = ComposeProject::new(ctx);
+    let refresh = spawn_refresher(project.clone(), Arc::clone(&state));
+    let mut logs = spawn_log_reader(&project, Arc::clone(&state));
+
+    let result = (|| loop {
+        terminal.draw(|f| draw(f, &ctx.config.global.project.name, &state.lock().unwrap()))?;
 
         // Handle input
-        if event::poll(std::time::Duration::from_millis(100))? {
+        if event::poll(Duration::from_millis(100))? {
             if let Event::Key(key) = event::read()? {
                 match key.code {
                     KeyCode::Char('q') => return Ok(()),
                     KeyCode::Char('r') => {
-                        // Refresh logic
-                    }
-                    KeyCode::Char('c') => {
-                        // Clear logs
+                        let _ = refresh.send(());
                     }
+                    KeyCode::Char('c') => state.lock().unwrap().clear_logs(),
                     _ => {}
                 }
             }
         }
+    })();
+
+    if let Some(child) = &mut logs {
+        let _ = child.kill();
+        let _ = child.wait();
     }
+    result
+}
+
+/// Query services every [`REFRESH_INTERVAL`], or right away when signalled. Stops
+/// once the returned sender is dropped.
+fn spawn_refresher(project: ComposeProject, state: Arc<Mutex<DashboardState>>) -> Sender<()> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || loop {
+        // Query without holding the lock so drawing isn't held up
+        let services = docker::services(&project);
+        state.lock().unwrap().apply(services);
+        if let Err(RecvTimeoutError::Disconnected) = rx.recv_timeout(REFRESH_INTERVAL) {
+            return;
+        }
+    });
+    tx
+}
+
+/// Follow the compose logs into the logs panel; the child is killed on exit
+fn spawn_log_reader(project: &ComposeProject, state: Arc<Mutex<DashboardState>>) -> Option<Child> {
+    let mut child = docker::follow_logs(project, MAX_LOG_LINES).ok()?;
+    let stdout = child.stdout.take()?;
+    thread::spawn(move || {
+        for line in BufReader::new(stdout).lines().map_while(io::Result::ok) {
+            state.lock().unwrap().push_log(line);
+        }
+    });
+    Some(child)
+}
+
+fn draw(f: &mut Frame, project: &str, state: &DashboardState) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([
+            Constraint::Length(3), // Header
+            Constraint::Min(10),   // Main content
+            Constraint::Length(3), // Footer
+        ])
+        .split(f.area());
+
+    // Header
+    let mut title = vec![Span::styled(
+        format!("devkit Dashboard - {}", project),
+        Style::default()
+            .fg(Color::Cyan)
+            .add_modifier(Modifier::BOLD),
+    )];
+    match (&state.error, state.updated) {
+        (Some(error), _) => title.push(Span::styled(
+            format!("  {}", error),
+            Style::default().fg(Color::Red),
+        )),
+        (None, Some(updated)) => title.push(Span::styled(
+            format!("  updated {}s ago", updated.elapsed().as_secs()),
+            Style::default().fg(Color::DarkGray),
+        )),
+        (None, None) => title.push(Span::styled(
+            "  loading...",
+            Style::default().fg(Color::DarkGray),
+        )),
+    }
+    let header = Paragraph::new(Line::from(title))
+        .block(Block::default().borders(Borders::ALL).title("Header"));
+    f.render_widget(header, chunks[0]);
+
+    // Main content - split into left and right
+    let main_chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(chunks[1]);
+
+    // Services panel (left)
+    let rows = state.services.iter().map(|service| {
+        let (symbol, color) = match service.status {
+            ServiceState::Running => ("✓", Color::Green),
+            ServiceState::Starting => ("…", Color::Yellow),
+            ServiceState::Stopped => ("○", Color::DarkGray),
+            ServiceState::Error => ("✗", Color::Red),
+        };
+        Row::new(vec![
+            Cell::from(Span::styled(symbol, Style::default().fg(color))),
+            Cell::from(service.name.as_str()),
+            Cell::from(service.detail.as_str()),
+            Cell::from(service.cpu.as_deref().unwrap_or("-")),
+            Cell::from(service.memory.as_deref().unwrap_or("-")),
+        ])
+    });
+    let services_table = Table::new(
+        rows,
+        [
+            Constraint::Length(1),
+            Constraint::Percentage(25),
+            Constraint::Percentage(35),
+            Constraint::Length(8),
+            Constraint::Min(10),
+        ],
+    )
+    .header(
+        Row::new(["", "Service", "Status", "CPU", "Memory"])
+            .style(Style::default().add_modifier(Modifier::BOLD)),
+    )
+    .block(Block::default().borders(Borders::ALL).title("Services"));
+    f.render_widget(services_table, main_chunks[0]);
+
+    // Logs panel (right), showing the lines that fit
+    let visible = main_chunks[1].height.saturating_sub(2) as usize;
+    let logs: Vec<Line> = state
+        .logs
+        .iter()
+        .skip(state.logs.len().saturating_sub(visible))
+        .map(|line| Line::raw(line.as_str()))
+        .collect();
+    let logs = Paragraph::new(logs)
+        .style(Style::default().fg(Color::White))
+        .block(Block::default().borders(Borders::ALL).title("Logs"));
+    f.render_widget(logs, main_chunks[1]);
+
+    // Footer
+    let footer = Paragraph::new("q: Quit | r: Refresh | c: Clear")
+        .style(Style::default().fg(Color::Yellow))
+        .block(Block::default().borders(Borders::ALL).title("Help"));
+    f.render_widget(footer, chunks[2]);
 }
 
 /// Dashboard state
@@ -150,18 +241,10 @@ pub struct DashboardState {
     pub services: Vec<ServiceStatus>,
     pub logs: Vec<String>,
     pub selected_panel: usize,
-}
-
-pub struct ServiceStatus {
-    pub name: String,
-    pub status: ServiceState,
-    pub uptime: Option<u64>,
-}
-
-pub enum ServiceState {
-    Running,
-    Stopped,
-    Error,
+    /// Why the last refresh failed
+    pub error: Option<String>,
+    /// When services were last queried successfully
+    pub updated: Option<Instant>,
 }
 
 impl DashboardState {
@@ -170,11 +253,36 @@ impl DashboardState {
             services: Vec::new(),
             logs: Vec::new(),
             selected_panel: 0,
+            error: None,
+            updated: None,
+        }
+    }
+
+    /// Query service status and resource use
+    pub fn refresh(&mut self, ctx: &AppContext) {
+        self.apply(docker::services(&ComposeProject::new(ctx)));
+    }
+
+    fn apply(&mut self, services: Result<Vec<ServiceStatus>>) {
+        match services {
+            Ok(services) => {
+                self.services = services;
+                self.error = None;
+                self.updated = Some(Instant::now());
+            }
+            Err(e) => self.error = Some(e.to_string()),
+        }
+    }
+
+    /// Add a log line, dropping the oldest past [`MAX_LOG_LINES`]
+    pub fn push_log(&mut self, line: String) {
+        self.logs.push(line);
+        if self.logs.len() > MAX_LOG_LINES {
+            self.logs.drain(..self.logs.len() - MAX_LOG_LINES);
         }
     }
 
-    pub fn refresh(&mut self) {
-        // Refresh service status
-        // Query Docker, databases, etc.
+    pub fn clear_logs(&mut self) {
+        self.logs.clear();
     }
 }
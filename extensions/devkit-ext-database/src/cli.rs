@@ -0,0 +1,48 @@
+//! `devkit database` subcommand
+
+use anyhow::Result;
+use clap::{Args, Command, FromArgMatches, Subcommand};
+use devkit_core::AppContext;
+
+use crate::{migrate, reset, seed, shell};
+
+#[derive(Args)]
+pub struct DatabaseArgs {
+    #[command(subcommand)]
+    action: DbAction,
+}
+
+#[derive(Subcommand)]
+enum DbAction {
+    /// Run pending migrations
+    Migrate(PackageArg),
+    /// Drop and recreate the database
+    Reset(PackageArg),
+    /// Load seed data
+    Seed(PackageArg),
+    /// Open a database shell
+    Shell(PackageArg),
+}
+
+#[derive(Args)]
+struct PackageArg {
+    /// Package whose database to use (default: all for migrate and reset, else the
+    /// first by name)
+    #[arg(short, long)]
+    package: Option<String>,
+}
+
+pub fn command() -> Command {
+    DatabaseArgs::augment_args(Command::new("database").about("Database operations (if enabled)"))
+}
+
+pub fn handle(ctx: &AppContext, matches: &clap::ArgMatches) -> Result<()> {
+    let args = DatabaseArgs::from_arg_matches(matches)?;
+
+    match args.action {
+        DbAction::Migrate(args) => migrate(ctx, args.package.as_deref()),
+        DbAction::Reset(args) => reset(ctx, args.package.as_deref()),
+        DbAction::Seed(args) => seed(ctx, args.package.as_deref()),
+        DbAction::Shell(args) => shell(ctx, args.package.as_deref()),
+    }
+}
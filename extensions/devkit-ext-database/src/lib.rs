@@ -1,8 +1,17 @@
 //! Database operations
+//!
+//! Postgres, MySQL and SQLite; see [`engine`] for how the engine is picked and
+//! [`migration`] for the migration tools.
+
+mod cli;
+pub mod engine;
+pub mod migration;
 
 use anyhow::{anyhow, Result};
-use devkit_core::{utils::cmd_exists, AppContext, Extension, MenuItem};
-use std::process::Command;
+use devkit_core::extension::unavailable_error;
+use devkit_core::oplog::LoggedCommand;
+use devkit_core::{AppContext, Extension, MenuItem};
+use std::fs::File;
 
 pub struct DatabaseExtension;
 
@@ -15,107 +24,131 @@ impl Extension for DatabaseExtension {
         ctx.features.database
     }
 
+    fn unavailable_reason(&self, _ctx: &AppContext) -> Option<String> {
+        Some("no package declares [database] in dev.toml".to_string())
+    }
+
     fn menu_items(&self, _ctx: &AppContext) -> Vec<MenuItem> {
         use devkit_core::DevkitError;
         vec![
             MenuItem {
                 label: "Migrate".to_string(),
                 group: Some("🗄  Database".to_string()),
-                handler: Box::new(|ctx| migrate(ctx).map_err(DevkitError::from)),
+                handler: Box::new(|ctx| migrate(ctx, None).map_err(DevkitError::from)),
             },
             MenuItem {
                 label: "Reset".to_string(),
                 group: Some("🗄  Database".to_string()),
-                handler: Box::new(|ctx| reset(ctx).map_err(DevkitError::from)),
+                handler: Box::new(|ctx| reset(ctx, None).map_err(DevkitError::from)),
             },
             MenuItem {
                 label: "Seed".to_string(),
                 group: Some("🗄  Database".to_string()),
-                handler: Box::new(|ctx| seed(ctx).map_err(DevkitError::from)),
+                handler: Box::new(|ctx| seed(ctx, None).map_err(DevkitError::from)),
             },
             MenuItem {
                 label: "Shell".to_string(),
                 group: Some("🗄  Database".to_string()),
-                handler: Box::new(|ctx| shell(ctx).map_err(DevkitError::from)),
+                handler: Box::new(|ctx| shell(ctx, None).map_err(DevkitError::from)),
             },
         ]
     }
-}
 
-pub fn migrate(ctx: &AppContext) -> Result<()> {
-    if !cmd_exists("sqlx") {
-        return Err(anyhow!(
-            "sqlx-cli not installed. Run: cargo install sqlx-cli"
-        ));
+    fn subcommands(&self) -> Vec<clap::Command> {
+        vec![cli::command()]
     }
 
-    ctx.print_info("Running migrations...");
-
-    let status = Command::new("sqlx")
-        .args(["migrate", "run"])
-        .current_dir(&ctx.repo)
-        .status()?;
+    fn handle_cli(
+        &self,
+        ctx: &AppContext,
+        name: &str,
+        matches: &clap::ArgMatches,
+    ) -> Option<devkit_core::Result<()>> {
+        (name == "database").then(|| {
+            if !self.is_available(ctx) {
+                return Err(unavailable_error(self, ctx));
+            }
+            cli::handle(ctx, matches).map_err(Into::into)
+        })
+    }
+}
 
-    if !status.success() {
-        return Err(anyhow!("Migration failed"));
+/// Run pending migrations for `package`, or every package with `[database]`
+pub fn migrate(ctx: &AppContext, package: Option<&str>) -> Result<()> {
+    for target in migration::targets(ctx, package)? {
+        ctx.print_info(&format!(
+            "Running migrations for {} ({})...",
+            target.package,
+            target.tool.name()
+        ));
+        target.run(&target.tool.migrate(target.db))?;
     }
 
     ctx.print_success("✓ Migrations complete");
     Ok(())
 }
 
-pub fn reset(ctx: &AppContext) -> Result<()> {
-    ctx.print_warning("This will drop and recreate the database!");
+/// Drop and recreate the database of `package`, or of every package with `[database]`
+pub fn reset(ctx: &AppContext, package: Option<&str>) -> Result<()> {
+    let targets = migration::targets(ctx, package)?;
+    ctx.print_warning(match targets.len() {
+        1 => "This will drop and recreate the database of:",
+        _ => "This will drop and recreate the databases of every package with [database]:",
+    });
+    let width = targets.iter().map(|t| t.package.len()).max().unwrap_or(0);
+    for target in &targets {
+        let (_, url) = engine::connection(ctx, target.db);
+        println!(
+            "  {:<width$}  {}  {}",
+            target.package,
+            target.tool.name(),
+            engine::display_url(&url),
+            width = width
+        );
+    }
 
-    if !ctx.confirm("Are you sure?", false)? {
+    let question = match targets.len() {
+        1 => "Reset this database?".to_string(),
+        count => format!("Reset all {} databases?", count),
+    };
+    if !ctx.confirm(&question, false)? {
         ctx.print_info("Cancelled");
         return Ok(());
     }
 
-    ctx.print_info("Resetting database...");
-
-    // Drop
-    let status = Command::new("sqlx")
-        .args(["database", "drop", "-y"])
-        .current_dir(&ctx.repo)
-        .status()?;
-
-    if !status.success() {
-        return Err(anyhow!("Drop failed"));
-    }
-
-    // Create
-    let status = Command::new("sqlx")
-        .args(["database", "create"])
-        .current_dir(&ctx.repo)
-        .status()?;
-
-    if !status.success() {
-        return Err(anyhow!("Create failed"));
+    for target in targets {
+        ctx.print_info(&format!(
+            "Resetting database for {} ({})...",
+            target.package,
+            target.tool.name()
+        ));
+        for args in target.tool.reset(target.db) {
+            target.run(&args)?;
+        }
     }
 
-    // Migrate
-    migrate(ctx)?;
-
     ctx.print_success("✓ Database reset");
     Ok(())
 }
 
-pub fn seed(ctx: &AppContext) -> Result<()> {
-    ctx.print_info("Seeding database...");
+/// Load the seed file of `package`, or of the first package with `[database]`: its
+/// `seeds`, else `seeds/dev.sql` in the repo
+pub fn seed(ctx: &AppContext, package: Option<&str>) -> Result<()> {
+    let target = single_target(ctx, package)?;
+    ctx.print_info(&format!("Seeding database for {}...", target.package));
 
-    // Look for seed file
-    let seed_file = ctx.repo.join("seeds/dev.sql");
+    let seed_file = match &target.db.seeds {
+        Some(seeds) => target.dir.join(seeds),
+        None => ctx.repo.join("seeds/dev.sql"),
+    };
     if !seed_file.exists() {
         return Err(anyhow!("Seed file not found: {}", seed_file.display()));
     }
 
-    let status = Command::new("psql")
-        .arg(std::env::var("DATABASE_URL")?)
-        .arg("-f")
-        .arg(&seed_file)
-        .current_dir(&ctx.repo)
-        .status()?;
+    let (engine, url) = engine::connection(ctx, target.db);
+    let status = engine::client(&target.dir, engine, &url)?
+        .stdin(File::open(&seed_file)?)
+        .logged_status()?;
 
     if !status.success() {
         return Err(anyhow!("Seed failed"));
@@ -125,20 +158,45 @@ pub fn seed(ctx: &AppContext) -> Result<()> {
     Ok(())
 }
 
-pub fn shell(ctx: &AppContext) -> Result<()> {
-    ctx.print_info("Opening database shell...");
-
-    let database_url =
-        std::env::var("DATABASE_URL").unwrap_or_else(|_| "postgresql://localhost/dev".to_string());
+/// Open a shell on the database of `package`, or of the first package with `[database]`
+pub fn shell(ctx: &AppContext, package: Option<&str>) -> Result<()> {
+    let target = single_target(ctx, package)?;
+    ctx.print_info(&format!("Opening database shell for {}...", target.package));
 
-    let status = Command::new("psql")
-        .arg(database_url)
-        .current_dir(&ctx.repo)
-        .status()?;
+    let (engine, url) = engine::connection(ctx, target.db);
+    let status = engine::client(&target.dir, engine, &url)?.logged_status()?;
 
     if !status.success() {
-        return Err(anyhow!("psql failed"));
+        return Err(anyhow!("{} failed", engine.client()));
     }
 
     Ok(())
 }
+
+fn single_target<'a>(ctx: &'a AppContext, package: Option<&str>) -> Result<migration::Target<'a>> {
+    migration::targets(ctx, package)?
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow!("No package declares [database] in dev.toml"))
+}
+
+/// Applied and pending migrations
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct MigrationStatus {
+    pub applied: usize,
+    /// Descriptions of migrations not yet run, oldest first
+    pub pending: Vec<String>,
+}
+
+/// Which migrations have run, across every package with `[database]`
+pub fn migration_status(ctx: &AppContext) -> Result<MigrationStatus> {
+    let mut status = MigrationStatus::default();
+    for target in migration::targets(ctx, None)? {
+        let package = target
+            .status()
+            .map_err(|e| anyhow!("{}: {}", target.package, e))?;
+        status.applied += package.applied;
+        status.pending.extend(package.pending);
+    }
+    Ok(status)
+}
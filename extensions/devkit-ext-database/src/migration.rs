@@ -0,0 +1,384 @@
+//! Migration tools
+//!
+//! Each package with `[database]` migrates with the tool its files point to:
+//! `diesel.toml` for Diesel, `prisma/schema.prisma` for Prisma, `alembic.ini` for
+//! Alembic and a knexfile for Knex. Anything else uses sqlx-cli with the package's
+//! `migrations` directory. Tools run in the package directory with `DATABASE_URL` set to
+//! the package's `url`, so a polyglot workspace migrates every package, each against
+//! its own database, with one `devkit database migrate`.
+
+use anyhow::{anyhow, Result};
+use devkit_core::config::DatabaseConfig;
+use devkit_core::oplog::LoggedCommand;
+use devkit_core::utils::cmd_exists;
+use devkit_core::AppContext;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::MigrationStatus;
+
+/// A migration tool; commands are arguments to [`MigrationTool::program`]
+pub trait MigrationTool {
+    fn name(&self) -> &'static str;
+
+    /// Executable run for every command
+    fn program(&self) -> &'static str;
+
+    /// How to get `program` when it's missing
+    fn install_hint(&self) -> &'static str;
+
+    /// Apply pending migrations
+    fn migrate(&self, db: &DatabaseConfig) -> Vec<String>;
+
+    /// Commands that leave the database empty with every migration applied, run in
+    /// order
+    fn reset(&self, db: &DatabaseConfig) -> Vec<Vec<String>>;
+
+    /// Command listing migrations, for tools whose output [`MigrationTool::parse_status`]
+    /// understands
+    fn status(&self, _db: &DatabaseConfig) -> Option<Vec<String>> {
+        None
+    }
+
+    fn parse_status(&self, _output: &str) -> MigrationStatus {
+        MigrationStatus::default()
+    }
+}
+
+struct Sqlx;
+
+impl MigrationTool for Sqlx {
+    fn name(&self) -> &'static str {
+        "sqlx"
+    }
+
+    fn program(&self) -> &'static str {
+        "sqlx"
+    }
+
+    fn install_hint(&self) -> &'static str {
+        "cargo install sqlx-cli"
+    }
+
+    fn migrate(&self, db: &DatabaseConfig) -> Vec<String> {
+        args(&["migrate", "run", "--source", &db.migrations])
+    }
+
+    fn reset(&self, db: &DatabaseConfig) -> Vec<Vec<String>> {
+        vec![args(&[
+            "database",
+            "reset",
+            "-y",
+            "--source",
+            &db.migrations,
+        ])]
+    }
+
+    fn status(&self, db: &DatabaseConfig) -> Option<Vec<String>> {
+        Some(args(&["migrate", "info", "--source", &db.migrations]))
+    }
+
+    /// Lines look like `20240101120000/installed create users`
+    fn parse_status(&self, output: &str) -> MigrationStatus {
+        let mut status = MigrationStatus::default();
+        for line in output.lines() {
+            let Some((version, description)) = line.trim().split_once(' ') else {
+                continue;
+            };
+            match version.split_once('/') {
+                Some((_, "installed")) => status.applied += 1,
+                Some((_, "pending")) => status.pending.push(description.trim().to_string()),
+                _ => {}
+            }
+        }
+        status
+    }
+}
+
+struct Diesel;
+
+impl MigrationTool for Diesel {
+    fn name(&self) -> &'static str {
+        "diesel"
+    }
+
+    fn program(&self) -> &'static str {
+        "diesel"
+    }
+
+    fn install_hint(&self) -> &'static str {
+        "cargo install diesel_cli"
+    }
+
+    fn migrate(&self, _db: &DatabaseConfig) -> Vec<String> {
+        args(&["migration", "run"])
+    }
+
+    fn reset(&self, _db: &DatabaseConfig) -> Vec<Vec<String>> {
+        vec![args(&["database", "reset"])]
+    }
+
+    fn status(&self, _db: &DatabaseConfig) -> Option<Vec<String>> {
+        Some(args(&["migration", "list"]))
+    }
+
+    /// Lines look like `  [X] 2024-01-01-120000_create_users`
+    fn parse_status(&self, output: &str) -> MigrationStatus {
+        let mut status = MigrationStatus::default();
+        for line in output.lines() {
+            if line.trim_start().starts_with("[X]") {
+                status.applied += 1;
+            } else if let Some(name) = line.trim_start().strip_prefix("[ ]") {
+                status.pending.push(name.trim().to_string());
+            }
+        }
+        status
+    }
+}
+
+struct Prisma;
+
+impl MigrationTool for Prisma {
+    fn name(&self) -> &'static str {
+        "prisma"
+    }
+
+    fn program(&self) -> &'static str {
+        "npx"
+    }
+
+    fn install_hint(&self) -> &'static str {
+        "https://nodejs.org (then npm install --save-dev prisma)"
+    }
+
+    fn migrate(&self, _db: &DatabaseConfig) -> Vec<String> {
+        args(&["prisma", "migrate", "deploy"])
+    }
+
+    fn reset(&self, _db: &DatabaseConfig) -> Vec<Vec<String>> {
+        vec![args(&["prisma", "migrate", "reset", "--force"])]
+    }
+}
+
+struct Knex;
+
+impl MigrationTool for Knex {
+    fn name(&self) -> &'static str {
+        "knex"
+    }
+
+    fn program(&self) -> &'static str {
+        "npx"
+    }
+
+    fn install_hint(&self) -> &'static str {
+        "https://nodejs.org (then npm install --save-dev knex)"
+    }
+
+    fn migrate(&self, _db: &DatabaseConfig) -> Vec<String> {
+        args(&["knex", "migrate:latest"])
+    }
+
+    // Knex can't drop the database, so roll everything back instead
+    fn reset(&self, db: &DatabaseConfig) -> Vec<Vec<String>> {
+        vec![
+            args(&["knex", "migrate:rollback", "--all"]),
+            self.migrate(db),
+        ]
+    }
+}
+
+struct Alembic;
+
+impl MigrationTool for Alembic {
+    fn name(&self) -> &'static str {
+        "alembic"
+    }
+
+    fn program(&self) -> &'static str {
+        "alembic"
+    }
+
+    fn install_hint(&self) -> &'static str {
+        "pip install alembic"
+    }
+
+    fn migrate(&self, _db: &DatabaseConfig) -> Vec<String> {
+        args(&["upgrade", "head"])
+    }
+
+    fn reset(&self, db: &DatabaseConfig) -> Vec<Vec<String>> {
+        vec![args(&["downgrade", "base"]), self.migrate(db)]
+    }
+}
+
+const KNEXFILES: &[&str] = &["knexfile.js", "knexfile.ts", "knexfile.cjs", "knexfile.mjs"];
+
+/// The tool the files in a package directory point to
+pub fn detect(dir: &Path) -> Box<dyn MigrationTool> {
+    if dir.join("diesel.toml").exists() {
+        Box::new(Diesel)
+    } else if dir.join("prisma/schema.prisma").exists() {
+        Box::new(Prisma)
+    } else if dir.join("alembic.ini").exists() {
+        Box::new(Alembic)
+    } else if KNEXFILES.iter().any(|file| dir.join(file).exists()) {
+        Box::new(Knex)
+    } else {
+        Box::new(Sqlx)
+    }
+}
+
+/// A package with `[database]` and its migration tool
+pub struct Target<'a> {
+    pub package: &'a str,
+    pub dir: PathBuf,
+    pub db: &'a DatabaseConfig,
+    pub tool: Box<dyn MigrationTool>,
+    /// The package's own connection string, passed to tools as `DATABASE_URL`
+    pub url: Option<String>,
+}
+
+/// `package`, or every package with `[database]` sorted by name
+pub fn targets<'a>(ctx: &'a AppContext, package: Option<&str>) -> Result<Vec<Target<'a>>> {
+    let mut targets: Vec<_> = ctx
+        .config
+        .database_packages()
+        .into_iter()
+        .filter_map(|(name, db)| {
+            let dir = ctx.config.packages.get(name)?.path.clone();
+            Some(Target {
+                package: name,
+                tool: detect(&dir),
+                dir,
+                db,
+                url: db
+                    .url
+                    .as_ref()
+                    .map(|url| ctx.config.global.services.expand(url)),
+            })
+        })
+        .collect();
+    targets.sort_by_key(|target| target.package);
+
+    if let Some(package) = package {
+        let names: Vec<_> = targets.iter().map(|target| target.package).collect();
+        let Some(index) = names.iter().position(|name| *name == package) else {
+            return Err(anyhow!(
+                "No package named '{}' has [database] (available: {})",
+                package,
+                names.join(", ")
+            ));
+        };
+        targets = vec![targets.swap_remove(index)];
+    }
+    Ok(targets)
+}
+
+impl Target<'_> {
+    /// Run the tool with `args` in the package directory
+    pub fn run(&self, args: &[String]) -> Result<()> {
+        let status = self.command(args)?.logged_status()?;
+        if !status.success() {
+            return Err(anyhow!(
+                "{} {} failed in {}",
+                self.tool.name(),
+                args.join(" "),
+                self.package
+            ));
+        }
+        Ok(())
+    }
+
+    /// Applied and pending migrations
+    pub fn status(&self) -> Result<MigrationStatus> {
+        let Some(args) = self.tool.status(self.db) else {
+            return Err(anyhow!(
+                "{} doesn't report migration status",
+                self.tool.name()
+            ));
+        };
+        let output = self.command(&args)?.logged_output()?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(anyhow!(
+                "{}",
+                stderr.lines().next().unwrap_or("listing migrations failed")
+            ));
+        }
+        Ok(self
+            .tool
+            .parse_status(&String::from_utf8_lossy(&output.stdout)))
+    }
+
+    fn command(&self, args: &[String]) -> Result<Command> {
+        let program = self.tool.program();
+        if !cmd_exists(program) {
+            return Err(anyhow!(
+                "{} not found (needed for {} in {}). Install: {}",
+                program,
+                self.tool.name(),
+                self.package,
+                self.tool.install_hint()
+            ));
+        }
+        let mut cmd = Command::new(program);
+        cmd.args(args).current_dir(&self.dir);
+        if let Some(url) = &self.url {
+            cmd.env("DATABASE_URL", url);
+        }
+        Ok(cmd)
+    }
+}
+
+fn args(args: &[&str]) -> Vec<String> {
+    args.iter().map(|arg| arg.to_string()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_detect() {
+        let dir = std::env::temp_dir().join(format!("devkit-migration-{}", std::process::id()));
+        fs::create_dir_all(dir.join("prisma")).unwrap();
+        assert_eq!(detect(&dir).name(), "sqlx");
+        fs::write(dir.join("knexfile.ts"), "").unwrap();
+        assert_eq!(detect(&dir).name(), "knex");
+        fs::write(dir.join("alembic.ini"), "").unwrap();
+        assert_eq!(detect(&dir).name(), "alembic");
+        fs::write(dir.join("prisma/schema.prisma"), "").unwrap();
+        assert_eq!(detect(&dir).name(), "prisma");
+        fs::write(dir.join("diesel.toml"), "").unwrap();
+        assert_eq!(detect(&dir).name(), "diesel");
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_parse_status() {
+        let output = "20240101120000/installed create users\n\
+                      20240102090000/installed add email index\n\
+                      20240110170000/pending create posts\n";
+        assert_eq!(
+            Sqlx.parse_status(output),
+            MigrationStatus {
+                applied: 2,
+                pending: vec!["create posts".to_string()],
+            }
+        );
+        assert_eq!(Sqlx.parse_status(""), MigrationStatus::default());
+
+        let output = "Migrations:\n  \
+                      [X] 00000000000000_diesel_initial_setup\n  \
+                      [ ] 2024-01-10-170000_create_posts\n";
+        assert_eq!(
+            Diesel.parse_status(output),
+            MigrationStatus {
+                applied: 1,
+                pending: vec!["2024-01-10-170000_create_posts".to_string()],
+            }
+        );
+    }
+}
@@ -2,7 +2,7 @@
 
 use devkit_core::{AppContext, Extension, MenuItem, Result};
 
-use crate::{check_and_install, print_summary};
+use crate::{check_and_install, check_toolchain, print_summary};
 
 pub struct DepsExtension;
 
@@ -16,6 +16,10 @@ impl Extension for DepsExtension {
         !crate::discover_packages(ctx).is_empty()
     }
 
+    fn unavailable_reason(&self, _ctx: &AppContext) -> Option<String> {
+        Some("no packages with dependencies found".to_string())
+    }
+
     fn menu_items(&self, _ctx: &AppContext) -> Vec<MenuItem> {
         use devkit_core::DevkitError;
         vec![MenuItem {
@@ -45,11 +49,11 @@ impl Extension for DepsExtension {
         }
     }
 
-    /// Prerun hook - disabled to avoid prompting on every run
-    /// Users can run `devkit deps` or `./dev deps` manually to install dependencies
-    fn prerun(&self, _ctx: &AppContext) -> Result<()> {
-        // Disabled: was prompting every time devkit runs
-        // Users should explicitly run `devkit deps` when needed
+    /// Prerun hook - warns about runtimes pinned with mise/asdf that aren't installed
+    /// or activated. Dependencies aren't installed here to avoid prompting on every
+    /// run; users run `devkit deps` or `./dev deps` when needed
+    fn prerun(&self, ctx: &AppContext) -> Result<()> {
+        check_toolchain(ctx);
         Ok(())
     }
 }
@@ -1,6 +1,7 @@
 //! Dependency installation logic
 
 use anyhow::{Context, Result};
+use devkit_core::oplog::LoggedCommand;
 use std::process::Command;
 
 use crate::detection::PackageInfo;
@@ -32,7 +33,7 @@ pub fn install_package(package: &PackageInfo, quiet: bool) -> Result<()> {
     }
 
     let status = cmd
-        .status()
+        .logged_status()
         .with_context(|| format!("Failed to run {}", package.package_manager.name()))?;
 
     if !status.success() {
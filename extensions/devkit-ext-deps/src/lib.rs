@@ -3,6 +3,8 @@
 //! Automatically detects what dependencies each package needs and installs them.
 
 use anyhow::Result;
+use devkit_core::hooks::with_hooks;
+use devkit_core::toolchain::Toolchain;
 use devkit_core::AppContext;
 
 mod detection;
@@ -69,7 +71,54 @@ pub fn check_and_install(ctx: &AppContext) -> Result<()> {
         return Ok(());
     }
 
-    install_all(&packages, ctx.quiet)
+    // Hooks only run when something is actually installed
+    if !packages.iter().any(|p| p.needs_install) {
+        return install_all(&packages, ctx.quiet);
+    }
+
+    with_hooks(ctx, "install", &[], || install_all(&packages, ctx.quiet))
+}
+
+/// Warn when runtimes pinned in `.mise.toml`/`.tool-versions` aren't installed, or
+/// the manager isn't activated so commands would run other versions
+pub fn check_toolchain(ctx: &AppContext) {
+    let toolchain = match Toolchain::detect(&ctx.repo) {
+        Ok(Some(toolchain)) => toolchain,
+        Ok(None) => return,
+        Err(e) => {
+            ctx.print_warning(&format!("{:#}", e));
+            return;
+        }
+    };
+    let file = toolchain.file_name();
+    let manager = toolchain.manager.binary();
+
+    if !toolchain.manager.is_installed() {
+        ctx.print_warning(&format!(
+            "{} pins runtime versions but {} isn't installed",
+            file, manager
+        ));
+        return;
+    }
+
+    let missing: Vec<String> = toolchain
+        .status()
+        .iter()
+        .filter(|status| !status.installed)
+        .map(|status| format!("{} {}", status.tool.name, status.tool.version))
+        .collect();
+    if !missing.is_empty() {
+        ctx.print_warning(&format!(
+            "{} from {} not installed. Run: devkit toolchain install",
+            missing.join(", "),
+            file
+        ));
+    } else if !toolchain.is_activated() {
+        ctx.print_warning(&format!(
+            "{} isn't activated in this shell, so commands may not use the versions in {}",
+            manager, file
+        ));
+    }
 }
 
 /// Print a summary of discovered packages
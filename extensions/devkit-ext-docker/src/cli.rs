@@ -0,0 +1,99 @@
+//! `devkit docker` subcommand
+
+use anyhow::{bail, Result};
+use clap::{Args, Command, FromArgMatches, Subcommand};
+use devkit_core::utils::closest_match;
+use devkit_core::AppContext;
+
+use crate::{
+    compose_down, compose_restart, compose_up, forward, logs, print_status, profiles, shell,
+};
+
+#[derive(Args)]
+pub struct DockerArgs {
+    #[command(subcommand)]
+    action: DockerAction,
+}
+
+#[derive(Subcommand)]
+enum DockerAction {
+    /// Start the compose services
+    Up {
+        /// Also start the services of a compose profile (repeatable)
+        #[arg(long)]
+        profile: Vec<String>,
+    },
+    /// Stop the compose services
+    Down,
+    /// Show each service's state, health, published ports and restarts
+    Status,
+    /// Restart the compose services
+    Restart,
+    /// Follow a service's logs
+    Logs { service: Option<String> },
+    /// Open a shell in a service's container
+    Shell { service: Option<String> },
+    /// Forward a local port to a service port, published or not
+    Forward {
+        service: String,
+        local_port: u16,
+        /// Port on the service (defaults to the local port)
+        remote_port: Option<u16>,
+        /// Keep forwarding in the background (see `devkit forward`)
+        #[arg(long, short)]
+        background: bool,
+    },
+}
+
+pub fn command() -> Command {
+    DockerArgs::augment_args(Command::new("docker").about("Docker operations (if enabled)"))
+}
+
+pub fn handle(ctx: &AppContext, matches: &clap::ArgMatches) -> Result<()> {
+    let args = DockerArgs::from_arg_matches(matches)?;
+
+    match args.action {
+        DockerAction::Up { profile } => {
+            check_profiles(ctx, &profile)?;
+            compose_up(ctx, &[], &profile, false)
+        }
+        DockerAction::Down => compose_down(ctx),
+        DockerAction::Status => print_status(ctx),
+        DockerAction::Restart => compose_restart(ctx, &[]),
+        DockerAction::Logs { service } => logs(ctx, service.as_deref()),
+        DockerAction::Shell { service } => shell(ctx, service.as_deref()),
+        DockerAction::Forward {
+            service,
+            local_port,
+            remote_port,
+            background,
+        } => forward(
+            ctx,
+            &service,
+            local_port,
+            remote_port.unwrap_or(local_port),
+            background,
+        ),
+    }
+}
+
+/// Fail on a profile no compose file defines, which compose would silently ignore
+fn check_profiles(ctx: &AppContext, requested: &[String]) -> Result<()> {
+    let known = profiles(ctx);
+    let Some(unknown) = requested.iter().find(|profile| !known.contains(profile)) else {
+        return Ok(());
+    };
+    match closest_match(unknown, known.iter().map(String::as_str)) {
+        Some(suggestion) => bail!(
+            "Unknown compose profile '{}' (did you mean '{}'?)",
+            unknown,
+            suggestion
+        ),
+        None if known.is_empty() => bail!("The compose file defines no profiles"),
+        None => bail!(
+            "Unknown compose profile '{}' (profiles: {})",
+            unknown,
+            known.join(", ")
+        ),
+    }
+}
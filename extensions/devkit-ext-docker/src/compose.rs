@@ -1,12 +1,217 @@
 //! Docker Compose operations
+//!
+//! With `[repos]` in `.dev/config.toml`, each sibling repo with a compose file is its
+//! own stack, run from its directory, and its services are named `<repo>/<service>`.
+//! This repo's stack uses `[docker] compose_files` and the active environment's
+//! override when there are any (see [`compose_files`]).
+//!
+//! Services assigned compose `profiles` only start when one of their profiles is
+//! enabled (`devkit docker up --profile tools`); `down` and `restart` enable them all,
+//! so containers started with a profile aren't skipped.
 
 use anyhow::{anyhow, Result};
+use devkit_core::oplog::LoggedCommand;
 use devkit_core::{
+    hooks::run_hooks,
     utils::{docker_compose_program, ensure_docker},
+    validation::{compose_file, compose_files, compose_profiles},
     AppContext,
 };
 use devkit_tasks::CmdBuilder;
 use std::cell::RefCell;
+use std::path::PathBuf;
+
+// =============================================================================
+// Stacks
+// =============================================================================
+
+/// A compose project: this repo's, or a `[repos]` sibling's
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stack {
+    /// `[repos]` name; `None` for this repo
+    pub repo: Option<String>,
+    pub dir: PathBuf,
+    /// Compose files, relative to `dir`; empty when compose finds its own
+    pub files: Vec<String>,
+}
+
+impl Stack {
+    /// Service name as shown to the user
+    pub fn service_name(&self, service: &str) -> String {
+        match &self.repo {
+            Some(repo) => format!("{}/{}", repo, service),
+            None => service.to_string(),
+        }
+    }
+
+    /// `docker compose` arguments with a `-f` per compose file
+    pub fn compose_args(&self, base_args: &[String]) -> Vec<String> {
+        let mut args = base_args.to_vec();
+        for file in &self.files {
+            args.extend(["-f".to_string(), file.clone()]);
+        }
+        args
+    }
+}
+
+/// This repo's stack, then the stacks of `[repos]` siblings with a compose file. This
+/// repo's is left out when it has no compose file but a sibling does.
+pub fn stacks(ctx: &AppContext) -> Vec<Stack> {
+    let siblings: Vec<Stack> = ctx
+        .config
+        .repos()
+        .into_iter()
+        .filter(|(_, dir)| compose_file(dir).is_some())
+        .map(|(repo, dir)| Stack {
+            repo: Some(repo.to_string()),
+            dir,
+            files: Vec::new(),
+        })
+        .collect();
+
+    let mut stacks = Vec::new();
+    if compose_file(&ctx.repo).is_some() || siblings.is_empty() {
+        stacks.push(Stack {
+            repo: None,
+            dir: ctx.repo.clone(),
+            files: compose_files(&ctx.repo, &ctx.config.global),
+        });
+    }
+    stacks.extend(siblings);
+    stacks
+}
+
+/// Group services by the stack they belong to; no services means all of every stack
+fn by_stack(stacks: &[Stack], services: &[String]) -> Vec<(Stack, Vec<String>)> {
+    if services.is_empty() {
+        return stacks
+            .iter()
+            .map(|stack| (stack.clone(), Vec::new()))
+            .collect();
+    }
+
+    let mut groups: Vec<(Stack, Vec<String>)> = Vec::new();
+    for service in services {
+        let (stack, name) = stacks
+            .iter()
+            .find_map(|stack| {
+                let name = service
+                    .strip_prefix(stack.repo.as_deref()?)?
+                    .strip_prefix('/')?;
+                Some((stack, name))
+            })
+            .unwrap_or((&stacks[0], service.as_str()));
+
+        match groups.iter_mut().find(|(group, _)| group == stack) {
+            Some((_, names)) => names.push(name.to_string()),
+            None => groups.push((stack.clone(), vec![name.to_string()])),
+        }
+    }
+    groups
+}
+
+/// The stack a service belongs to, with the name its compose file uses
+pub fn stack_for(ctx: &AppContext, service: &str) -> (Stack, String) {
+    let mut groups = by_stack(&stacks(ctx), &[service.to_string()]);
+    let (stack, mut names) = groups.remove(0);
+    (stack, names.remove(0))
+}
+
+/// Profiles defined by any stack's compose file, sorted
+pub fn profiles(ctx: &AppContext) -> Vec<String> {
+    let mut profiles: Vec<String> = stacks(ctx)
+        .iter()
+        .flat_map(|stack| compose_profiles(&stack.dir, &stack.files))
+        .collect();
+    profiles.sort();
+    profiles.dedup();
+    profiles
+}
+
+/// `--profile` arguments enabling `profiles`
+fn profile_args(profiles: &[String]) -> Vec<String> {
+    profiles
+        .iter()
+        .flat_map(|profile| ["--profile".to_string(), profile.clone()])
+        .collect()
+}
+
+/// Run `docker compose [--profile ...] <command> [services]` in each stack the services
+/// belong to
+fn compose_in_stacks(
+    ctx: &AppContext,
+    profiles: &[String],
+    command: &[&str],
+    services: &[String],
+    echo: bool,
+) -> Result<()> {
+    let (prog, base_args) = docker_compose_program()?;
+
+    for (stack, names) in by_stack(&stacks(ctx), services) {
+        let mut args = stack.compose_args(&base_args);
+        args.extend(profile_args(profiles));
+        args.extend(command.iter().map(|arg| arg.to_string()));
+        args.extend(names);
+
+        if echo && !ctx.quiet {
+            match &stack.repo {
+                Some(repo) => println!("[docker:{}] {} {}", repo, prog, args.join(" ")),
+                None => println!("[docker] {} {}", prog, args.join(" ")),
+            }
+        }
+
+        let code = CmdBuilder::new(&prog)
+            .args(&args)
+            .cwd(&stack.dir)
+            .inherit_io()
+            .run()?;
+
+        if code != 0 {
+            return Err(anyhow!(
+                "docker compose {} exited with code {code}",
+                command[0]
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Follow `docker compose logs` for the services (all when empty); stacks are
+/// followed side by side, each prefixing lines with its service names
+pub fn follow_stack_logs(ctx: &AppContext, services: &[String], tail: &str) -> Result<()> {
+    let (prog, base_args) = docker_compose_program()?;
+    let groups = by_stack(&stacks(ctx), services);
+
+    let mut children = Vec::new();
+    for (stack, names) in &groups {
+        let mut args = stack.compose_args(&base_args);
+        args.extend(["logs", "-f", "--tail", tail].map(String::from));
+        args.extend(names.iter().cloned());
+
+        if groups.len() == 1 {
+            let code = CmdBuilder::new(&prog)
+                .args(&args)
+                .cwd(&stack.dir)
+                .inherit_io()
+                .run()?;
+            if code != 0 {
+                return Err(anyhow!("docker compose logs exited with code {}", code));
+            }
+            return Ok(());
+        }
+        children.push(
+            std::process::Command::new(&prog)
+                .args(&args)
+                .current_dir(&stack.dir)
+                .spawn()?,
+        );
+    }
+
+    for mut child in children {
+        child.wait()?;
+    }
+    Ok(())
+}
 
 // =============================================================================
 // Service Cache
@@ -22,7 +227,7 @@ pub fn list_services(ctx: &AppContext) -> Result<Vec<String>> {
         if let Some(ref services) = *cache.borrow() {
             return Ok(services.clone());
         }
-        let services = list_services_uncached(ctx)?;
+        let services = list_services_uncached(ctx, &[])?;
         *cache.borrow_mut() = Some(services.clone());
         Ok(services)
     })
@@ -35,20 +240,24 @@ fn invalidate_cache() {
     });
 }
 
-/// List all services defined in docker-compose.yml (uncached)
-fn list_services_uncached(ctx: &AppContext) -> Result<Vec<String>> {
+/// List the services `up` starts with `profiles` enabled (uncached)
+pub fn list_services_uncached(ctx: &AppContext, profiles: &[String]) -> Result<Vec<String>> {
     let (prog, base_args) = docker_compose_program()?;
 
-    let mut args = base_args;
-    args.extend(["config", "--services"].map(String::from));
+    let mut svcs = Vec::new();
+    for stack in stacks(ctx) {
+        let mut args = stack.compose_args(&base_args);
+        args.extend(profile_args(profiles));
+        args.extend(["config", "--services"].map(String::from));
 
-    let out = CmdBuilder::new(&prog)
-        .args(&args)
-        .cwd(&ctx.repo)
-        .capture_stdout()
-        .run_capture()?;
+        let out = CmdBuilder::new(&prog)
+            .args(&args)
+            .cwd(&stack.dir)
+            .capture_stdout()
+            .run_capture()?;
 
-    let mut svcs = out.stdout_lines();
+        svcs.extend(out.stdout_lines().iter().map(|svc| stack.service_name(svc)));
+    }
     svcs.sort();
     Ok(svcs)
 }
@@ -67,35 +276,37 @@ pub struct Container {
 pub fn list_running_containers(ctx: &AppContext) -> Result<Vec<Container>> {
     let (prog, base_args) = docker_compose_program()?;
 
-    let mut args = base_args.clone();
-    args.extend(["ps", "--services", "--filter", "status=running"].map(String::from));
-
-    let out = CmdBuilder::new(&prog)
-        .args(&args)
-        .cwd(&ctx.repo)
-        .capture_stdout()
-        .run_capture()?;
-
-    let services = out.stdout_lines();
     let mut containers: Vec<Container> = Vec::new();
 
-    for svc in services {
-        let mut args2 = base_args.clone();
-        args2.extend(["ps", "-q"].map(String::from));
-        args2.push(svc.clone());
+    for stack in stacks(ctx) {
+        let mut args = stack.compose_args(&base_args);
+        args.extend(["ps", "--services", "--filter", "status=running"].map(String::from));
 
-        let out2 = CmdBuilder::new(&prog)
-            .args(&args2)
-            .cwd(&ctx.repo)
+        let out = CmdBuilder::new(&prog)
+            .args(&args)
+            .cwd(&stack.dir)
             .capture_stdout()
             .run_capture()?;
 
-        for id in out2.stdout_lines() {
-            let short = id.chars().take(12).collect::<String>();
-            containers.push(Container {
-                label: format!("{svc} ({short})"),
-                id,
-            });
+        for svc in out.stdout_lines() {
+            let mut args2 = stack.compose_args(&base_args);
+            args2.extend(["ps", "-q"].map(String::from));
+            args2.push(svc.clone());
+
+            let out2 = CmdBuilder::new(&prog)
+                .args(&args2)
+                .cwd(&stack.dir)
+                .capture_stdout()
+                .run_capture()?;
+
+            let svc = stack.service_name(&svc);
+            for id in out2.stdout_lines() {
+                let short = id.chars().take(12).collect::<String>();
+                containers.push(Container {
+                    label: format!("{svc} ({short})"),
+                    id,
+                });
+            }
         }
     }
 
@@ -107,62 +318,43 @@ pub fn list_running_containers(ctx: &AppContext) -> Result<Vec<Container>> {
 // Compose Operations
 // =============================================================================
 
-/// Start docker containers (docker compose up -d)
-pub fn compose_up(ctx: &AppContext, services: &[String], build: bool) -> Result<()> {
+/// Start docker containers (docker compose up -d), with services of `profiles` too
+pub fn compose_up(
+    ctx: &AppContext,
+    services: &[String],
+    profiles: &[String],
+    build: bool,
+) -> Result<()> {
     ensure_docker()?;
+    run_hooks(ctx, "pre_docker_up", &[])?;
 
-    let (prog, base_args) = docker_compose_program()?;
-    let mut args = base_args;
-    args.push("up".to_string());
-    args.push("-d".to_string());
-
+    let mut command = vec!["up", "-d"];
     if build {
-        args.push("--build".to_string());
+        command.push("--build");
     }
-    args.extend(services.iter().cloned());
 
     ctx.print_header("Starting docker containers");
-    if !ctx.quiet {
-        println!("[docker] {} {}", prog, args.join(" "));
-    }
-
-    let code = CmdBuilder::new(&prog)
-        .args(&args)
-        .cwd(&ctx.repo)
-        .inherit_io()
-        .run()?;
-
-    if code != 0 {
-        return Err(anyhow!("docker compose up exited with code {code}"));
-    }
+    compose_in_stacks(ctx, profiles, &command, services, true)?;
 
     invalidate_cache();
+
     ctx.print_success("Docker containers started!");
+    run_hooks(ctx, "post_docker_up", &[])?;
     Ok(())
 }
 
 /// Stop docker containers (docker compose down)
 pub fn compose_down(ctx: &AppContext) -> Result<()> {
     ensure_docker()?;
+    run_hooks(ctx, "pre_docker_down", &[])?;
 
     ctx.print_header("Stopping docker containers");
-
-    let (prog, base_args) = docker_compose_program()?;
-    let mut args = base_args;
-    args.push("down".to_string());
-
-    let code = CmdBuilder::new(&prog)
-        .args(&args)
-        .cwd(&ctx.repo)
-        .inherit_io()
-        .run()?;
-
-    if code != 0 {
-        return Err(anyhow!("docker compose down exited with code {code}"));
-    }
+    compose_in_stacks(ctx, &profiles(ctx), &["down"], &[], false)?;
 
     invalidate_cache();
+
     ctx.print_success("Docker containers stopped!");
+    run_hooks(ctx, "post_docker_down", &[])?;
     Ok(())
 }
 
@@ -170,25 +362,8 @@ pub fn compose_down(ctx: &AppContext) -> Result<()> {
 pub fn compose_restart(ctx: &AppContext, services: &[String]) -> Result<()> {
     ensure_docker()?;
 
-    let (prog, base_args) = docker_compose_program()?;
-    let mut args = base_args;
-    args.push("restart".to_string());
-    args.extend(services.iter().cloned());
-
     ctx.print_header("Restarting docker containers");
-    if !ctx.quiet {
-        println!("[docker] {} {}", prog, args.join(" "));
-    }
-
-    let code = CmdBuilder::new(&prog)
-        .args(&args)
-        .cwd(&ctx.repo)
-        .inherit_io()
-        .run()?;
-
-    if code != 0 {
-        return Err(anyhow!("docker compose restart exited with code {code}"));
-    }
+    compose_in_stacks(ctx, &profiles(ctx), &["restart"], services, true)?;
 
     ctx.print_success("Docker containers restarted!");
     Ok(())
@@ -203,34 +378,19 @@ pub fn compose_build(
 ) -> Result<()> {
     ensure_docker()?;
 
-    let (prog, base_args) = docker_compose_program()?;
-    let mut args = base_args;
-    args.push("build".to_string());
-
+    let mut command = vec!["build"];
     if pull {
-        args.push("--pull".to_string());
+        command.push("--pull");
     }
     if no_cache {
-        args.push("--no-cache".to_string());
+        command.push("--no-cache");
     }
-    args.extend(services.iter().cloned());
 
     ctx.print_header("Building docker images");
-    if !ctx.quiet {
-        println!("[docker] {} {}", prog, args.join(" "));
-    }
-
-    let code = CmdBuilder::new(&prog)
-        .args(&args)
-        .cwd(&ctx.repo)
-        .inherit_io()
-        .run()?;
-
-    if code != 0 {
-        return Err(anyhow!("docker compose build exited with code {code}"));
-    }
+    compose_in_stacks(ctx, &[], &command, services, true)?;
 
     invalidate_cache();
+
     ctx.print_success("Docker images built!");
     Ok(())
 }
@@ -239,8 +399,6 @@ pub fn compose_build(
 pub fn nuke_rebuild(ctx: &AppContext, services: &[String]) -> Result<()> {
     ensure_docker()?;
 
-    let (prog, base_args) = docker_compose_program()?;
-
     ctx.print_header("Nuke and rebuild docker images");
     ctx.print_warning("This will stop containers, remove images, and rebuild from scratch");
 
@@ -254,15 +412,7 @@ pub fn nuke_rebuild(ctx: &AppContext, services: &[String]) -> Result<()> {
     if !ctx.quiet {
         println!("[docker] Stopping and removing containers...");
     }
-    let mut args = base_args.clone();
-    args.extend(["rm", "-sf"].map(String::from));
-    args.extend(services.iter().cloned());
-
-    CmdBuilder::new(&prog)
-        .args(&args)
-        .cwd(&ctx.repo)
-        .inherit_io()
-        .run()?;
+    compose_in_stacks(ctx, &[], &["rm", "-sf"], services, false)?;
 
     // Step 2: Remove images
     if !images.is_empty() {
@@ -291,21 +441,84 @@ pub fn nuke_rebuild(ctx: &AppContext, services: &[String]) -> Result<()> {
 /// Get image names for compose services
 fn get_service_images(ctx: &AppContext, services: &[String]) -> Result<Vec<String>> {
     let (prog, base_args) = docker_compose_program()?;
-    let mut args = base_args;
-    args.extend(["images", "-q"].map(String::from));
-    args.extend(services.iter().cloned());
-
-    let output = std::process::Command::new(&prog)
-        .args(&args)
-        .current_dir(&ctx.repo)
-        .output()?;
-
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let images: Vec<String> = stdout
-        .lines()
-        .filter(|l| !l.is_empty())
-        .map(|s| s.to_string())
-        .collect();
+    let mut images = Vec::new();
+
+    for (stack, names) in by_stack(&stacks(ctx), services) {
+        let mut args = stack.compose_args(&base_args);
+        args.extend(["images", "-q"].map(String::from));
+        args.extend(names);
+
+        let output = std::process::Command::new(&prog)
+            .args(&args)
+            .current_dir(&stack.dir)
+            .logged_output()?;
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        images.extend(
+            stdout
+                .lines()
+                .filter(|l| !l.is_empty())
+                .map(|s| s.to_string()),
+        );
+    }
 
     Ok(images)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_by_stack() {
+        let stack = |repo: Option<&str>, dir: &str| Stack {
+            repo: repo.map(String::from),
+            dir: PathBuf::from(dir),
+            files: Vec::new(),
+        };
+        let stacks = [
+            stack(None, "/work/app"),
+            stack(Some("api"), "/work/api"),
+            stack(Some("auth"), "/work/auth"),
+        ];
+
+        assert_eq!(by_stack(&stacks, &[]).len(), 3);
+
+        let services = ["postgres", "api/server", "api/redis", "apix/worker"].map(String::from);
+        assert_eq!(
+            by_stack(&stacks, &services),
+            [
+                (
+                    stacks[0].clone(),
+                    vec!["postgres".to_string(), "apix/worker".to_string()]
+                ),
+                (
+                    stacks[1].clone(),
+                    vec!["server".to_string(), "redis".to_string()]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_compose_args() {
+        let stack = Stack {
+            repo: None,
+            dir: PathBuf::from("/work/app"),
+            files: vec![
+                "docker-compose.yml".to_string(),
+                "docker-compose.dev.yml".to_string(),
+            ],
+        };
+        assert_eq!(
+            stack.compose_args(&["compose".to_string()]),
+            [
+                "compose",
+                "-f",
+                "docker-compose.yml",
+                "-f",
+                "docker-compose.dev.yml"
+            ]
+        );
+    }
+}
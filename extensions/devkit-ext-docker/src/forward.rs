@@ -0,0 +1,96 @@
+//! Port forwards to compose services
+//!
+//! Reaches a port the service doesn't publish: a socat container joins the service's
+//! network and relays `localhost:<local>` to `<service>:<remote>`. In the background,
+//! the relay is managed with `devkit forward`.
+
+use anyhow::{anyhow, Context, Result};
+use devkit_core::forwards;
+use devkit_core::oplog::LoggedCommand;
+use devkit_core::utils::{docker_compose_program, ensure_docker};
+use devkit_core::AppContext;
+use devkit_tasks::CmdBuilder;
+use std::process::Command;
+
+use crate::compose::stack_for;
+
+const RELAY_IMAGE: &str = "alpine/socat";
+
+/// Forward `localhost:<local>` to the service's `remote` port
+pub fn forward(
+    ctx: &AppContext,
+    service: &str,
+    local: u16,
+    remote: u16,
+    background: bool,
+) -> Result<()> {
+    ensure_docker()?;
+    let (stack, name) = stack_for(ctx, service);
+
+    let (prog, base_args) = docker_compose_program()?;
+    let mut args = stack.compose_args(&base_args);
+    args.extend(["ps", "-q", &name].map(String::from));
+    let container = CmdBuilder::new(&prog)
+        .args(&args)
+        .cwd(&stack.dir)
+        .capture_stdout()
+        .run_capture()?
+        .stdout_lines()
+        .into_iter()
+        .next()
+        .ok_or_else(|| {
+            anyhow!(
+                "{} isn't running (start it with `devkit docker up`)",
+                service
+            )
+        })?;
+
+    let networks = CmdBuilder::new("docker")
+        .args([
+            "inspect",
+            "-f",
+            "{{range $name, $_ := .NetworkSettings.Networks}}{{$name}} {{end}}",
+            &container,
+        ])
+        .capture_stdout()
+        .run_capture()?
+        .stdout_string();
+    let network = networks
+        .split_whitespace()
+        .next()
+        .ok_or_else(|| anyhow!("{} isn't attached to a network", service))?;
+
+    ctx.print_info(&format!(
+        "Forwarding localhost:{} -> {}:{}",
+        local, service, remote
+    ));
+    let mut cmd = Command::new("docker");
+    cmd.args(["run", "--rm", "--network", network])
+        .args(["-p", &format!("127.0.0.1:{}:{}", local, remote)])
+        .arg(RELAY_IMAGE)
+        .arg(format!("TCP-LISTEN:{},fork,reuseaddr", remote))
+        .arg(format!("TCP:{}:{}", name, remote));
+
+    if background {
+        let forward = forwards::start(
+            &ctx.repo,
+            "docker",
+            &format!("{}:{}", service, remote),
+            &[local],
+            cmd,
+        )?;
+        ctx.print_success(&format!(
+            "✓ Forwarding in the background (pid {}); stop with `devkit forward stop {}`",
+            forward.pid, local
+        ));
+        return Ok(());
+    }
+
+    let status = cmd
+        .logged_status()
+        .context("Failed to start the relay container")?;
+    if !status.success() && status.code() != Some(130) {
+        return Err(anyhow!("Port forwarding failed"));
+    }
+    Ok(())
+}
@@ -1,16 +1,22 @@
 //! Docker compose operations
 
+mod cli;
 mod compose;
+mod forward;
 mod logs;
 mod shell;
+mod status;
 
 pub use compose::*;
+pub use forward::*;
 pub use logs::*;
 pub use shell::*;
+pub use status::*;
 
 use anyhow::{anyhow, Result};
+use devkit_core::extension::unavailable_error;
 use devkit_core::{AppContext, Extension, MenuItem};
-use dialoguer::{theme::ColorfulTheme, MultiSelect, Select};
+use dialoguer::{MultiSelect, Select};
 
 pub struct DockerExtension;
 
@@ -23,6 +29,14 @@ impl Extension for DockerExtension {
         ctx.features.docker
     }
 
+    fn unavailable_reason(&self, _ctx: &AppContext) -> Option<String> {
+        Some(if devkit_core::docker_available() {
+            "no docker-compose.yml or compose.yml found".to_string()
+        } else {
+            "docker not installed".to_string()
+        })
+    }
+
     fn menu_items(&self, _ctx: &AppContext) -> Vec<MenuItem> {
         vec![
             MenuItem {
@@ -30,6 +44,11 @@ impl Extension for DockerExtension {
                 group: Some("🐳 Docker".to_string()),
                 handler: Box::new(|ctx| Ok(docker_up_interactive(ctx)?)),
             },
+            MenuItem {
+                label: "Status".to_string(),
+                group: Some("🐳 Docker".to_string()),
+                handler: Box::new(|ctx| Ok(print_status(ctx)?)),
+            },
             MenuItem {
                 label: "Down".to_string(),
                 group: Some("🐳 Docker".to_string()),
@@ -57,6 +76,24 @@ impl Extension for DockerExtension {
             },
         ]
     }
+
+    fn subcommands(&self) -> Vec<clap::Command> {
+        vec![cli::command()]
+    }
+
+    fn handle_cli(
+        &self,
+        ctx: &AppContext,
+        name: &str,
+        matches: &clap::ArgMatches,
+    ) -> Option<devkit_core::Result<()>> {
+        (name == "docker").then(|| {
+            if !self.is_available(ctx) {
+                return Err(unavailable_error(self, ctx));
+            }
+            cli::handle(ctx, matches).map_err(Into::into)
+        })
+    }
 }
 
 // =============================================================================
@@ -76,13 +113,19 @@ fn select_containers_multi(
         return Err(anyhow!("No running containers found"));
     }
 
+    // Without a terminal, [All] is the only sensible choice
+    if include_all && !ctx.is_interactive() {
+        return Ok(running.iter().map(|c| c.id.clone()).collect());
+    }
+    ctx.require_interactive("Selecting containers", "Pass the service names instead")?;
+
     let mut items = Vec::new();
     if include_all {
         items.push("[All]".to_string());
     }
     items.extend(running.iter().map(|c| c.label.clone()));
 
-    let selection = MultiSelect::with_theme(&ColorfulTheme::default())
+    let selection = MultiSelect::with_theme(&*ctx.theme())
         .with_prompt(prompt)
         .items(&items)
         .interact()?;
@@ -112,9 +155,17 @@ fn select_container_single(ctx: &AppContext, prompt: &str) -> Result<String> {
         return Err(anyhow!("No running containers found"));
     }
 
+    if running.len() == 1 {
+        return Ok(running[0].id.clone());
+    }
+    ctx.require_interactive(
+        "Selecting a container",
+        "Pass the service name instead, e.g. `devkit docker shell <service>`",
+    )?;
+
     let items: Vec<String> = running.iter().map(|c| c.label.clone()).collect();
 
-    let selection = Select::with_theme(&ColorfulTheme::default())
+    let selection = Select::with_theme(&*ctx.theme())
         .with_prompt(prompt)
         .items(&items)
         .default(0)
@@ -123,21 +174,44 @@ fn select_container_single(ctx: &AppContext, prompt: &str) -> Result<String> {
     Ok(running[selection].id.clone())
 }
 
-/// Select services (not containers) for operations that work with service names
-fn select_services_multi(ctx: &AppContext, prompt: &str, include_all: bool) -> Result<Vec<String>> {
-    let services = list_services(ctx)?;
+/// Select compose profiles to enable; none (or no terminal) means the default services
+fn select_profiles(ctx: &AppContext) -> Result<Vec<String>> {
+    let profiles = profiles(ctx);
+    if profiles.is_empty() || !ctx.is_interactive() {
+        return Ok(Vec::new());
+    }
+
+    let selection = MultiSelect::with_theme(&*ctx.theme())
+        .with_prompt("Select profiles to enable (none for the default services)")
+        .items(&profiles)
+        .interact()?;
+    Ok(selection.iter().map(|&i| profiles[i].clone()).collect())
+}
 
+/// Select services (not containers) for operations that work with service names
+fn select_services_multi(
+    ctx: &AppContext,
+    services: Vec<String>,
+    prompt: &str,
+    include_all: bool,
+) -> Result<Vec<String>> {
     if services.is_empty() {
         return Err(anyhow!("No services found in docker-compose.yml"));
     }
 
+    // Empty means all services
+    if include_all && !ctx.is_interactive() {
+        return Ok(Vec::new());
+    }
+    ctx.require_interactive("Selecting services", "Pass the service names instead")?;
+
     let mut items = Vec::new();
     if include_all {
         items.push("[All]".to_string());
     }
     items.extend(services.clone());
 
-    let selection = MultiSelect::with_theme(&ColorfulTheme::default())
+    let selection = MultiSelect::with_theme(&*ctx.theme())
         .with_prompt(prompt)
         .items(&items)
         .interact()?;
@@ -165,19 +239,22 @@ fn select_services_multi(ctx: &AppContext, prompt: &str, include_all: bool) -> R
 
 /// Interactive handler for docker up
 fn docker_up_interactive(ctx: &AppContext) -> Result<()> {
+    let profiles = select_profiles(ctx)?;
     let services = select_services_multi(
         ctx,
+        list_services_uncached(ctx, &profiles)?,
         "Select services to start (space to select, enter to confirm)",
         true,
     )?;
 
-    compose_up(ctx, &services, false)
+    compose_up(ctx, &services, &profiles, false)
 }
 
 /// Interactive handler for docker restart
 fn docker_restart_interactive(ctx: &AppContext) -> Result<()> {
     let services = select_services_multi(
         ctx,
+        list_services(ctx)?,
         "Select services to restart (space to select, enter to confirm)",
         true,
     )?;
@@ -189,6 +266,7 @@ fn docker_restart_interactive(ctx: &AppContext) -> Result<()> {
 fn docker_build_interactive(ctx: &AppContext) -> Result<()> {
     let services = select_services_multi(
         ctx,
+        list_services(ctx)?,
         "Select services to build (space to select, enter to confirm)",
         true,
     )?;
@@ -198,9 +276,6 @@ fn docker_build_interactive(ctx: &AppContext) -> Result<()> {
 
 /// Interactive handler for docker logs with live following (supports multiple containers)
 fn docker_logs_interactive(ctx: &AppContext) -> Result<()> {
-    use devkit_core::utils::docker_compose_program;
-    use devkit_tasks::CmdBuilder;
-
     // Get all running services
     let services = list_services(ctx)?;
 
@@ -208,14 +283,18 @@ fn docker_logs_interactive(ctx: &AppContext) -> Result<()> {
         return Err(anyhow!("No services found in docker-compose.yml"));
     }
 
-    // Multi-select services to follow
+    // Multi-select services to follow (all of them without a terminal)
     let mut items = vec!["[All]".to_string()];
     items.extend(services.clone());
 
-    let selection = MultiSelect::with_theme(&ColorfulTheme::default())
-        .with_prompt("Select containers to follow logs (space to select, enter to confirm)")
-        .items(&items)
-        .interact()?;
+    let selection = if ctx.is_interactive() {
+        MultiSelect::with_theme(&*ctx.theme())
+            .with_prompt("Select containers to follow logs (space to select, enter to confirm)")
+            .items(&items)
+            .interact()?
+    } else {
+        vec![0]
+    };
 
     if selection.is_empty() {
         return Err(anyhow!("No containers selected"));
@@ -230,28 +309,14 @@ fn docker_logs_interactive(ctx: &AppContext) -> Result<()> {
         selection.iter().map(|&i| services[i - 1].clone()).collect()
     };
 
-    // Follow logs using docker compose logs
-    let (prog, mut args) = docker_compose_program()?;
-    args.extend(["logs", "-f", "--tail", "200"].map(String::from));
-    args.extend(selected_services.clone());
-
     ctx.print_header(&format!(
         "Following logs for: {}",
         selected_services.join(", ")
     ));
     println!();
 
-    let code = CmdBuilder::new(&prog)
-        .args(&args)
-        .cwd(&ctx.repo)
-        .inherit_io()
-        .run()?;
-
-    if code != 0 {
-        return Err(anyhow!("docker compose logs exited with code {}", code));
-    }
-
-    Ok(())
+    // Follow logs using docker compose logs
+    follow_stack_logs(ctx, &selected_services, "200")
 }
 
 /// Interactive handler for docker shell
@@ -267,32 +332,11 @@ fn docker_shell_interactive(ctx: &AppContext) -> Result<()> {
 
 /// Follow logs for a service (CLI compatibility wrapper)
 pub fn logs(ctx: &AppContext, service: Option<&str>) -> Result<()> {
-    use devkit_core::utils::docker_compose_program;
-    use devkit_tasks::CmdBuilder;
-
-    let (prog, mut args) = docker_compose_program()?;
-    args.push("logs".to_string());
-    args.push("-f".to_string());
-    args.push("--tail".to_string());
-    args.push("100".to_string());
-
-    if let Some(svc) = service {
-        args.push(svc.to_string());
-    }
+    let services: Vec<String> = service.map(String::from).into_iter().collect();
 
     ctx.print_info("Following logs...");
 
-    let code = CmdBuilder::new(&prog)
-        .args(&args)
-        .cwd(&ctx.repo)
-        .inherit_io()
-        .run()?;
-
-    if code != 0 {
-        return Err(anyhow!("docker compose logs exited with code {}", code));
-    }
-
-    Ok(())
+    follow_stack_logs(ctx, &services, "100")
 }
 
 /// Open shell in a service (CLI compatibility wrapper)
@@ -300,40 +344,49 @@ pub fn shell(ctx: &AppContext, service: Option<&str>) -> Result<()> {
     use devkit_core::utils::docker_compose_program;
     use devkit_tasks::CmdBuilder;
 
-    let service = match service {
-        Some(s) => s.to_string(),
+    let (stack, service) = match service {
+        Some(s) => stack_for(ctx, s),
         None => {
             // Get first running service
-            let (prog, mut args) = docker_compose_program()?;
-            args.push("ps".to_string());
-            args.push("--services".to_string());
-            args.push("--filter".to_string());
-            args.push("status=running".to_string());
-
-            let out = CmdBuilder::new(&prog)
-                .args(&args)
-                .cwd(&ctx.repo)
-                .capture_stdout()
-                .run_capture()?;
-
-            let services = out.stdout_lines();
-            services
-                .first()
-                .ok_or_else(|| anyhow!("No running containers found"))?
-                .to_string()
+            let (prog, base_args) = docker_compose_program()?;
+
+            let mut running = None;
+            for stack in stacks(ctx) {
+                let mut args = stack.compose_args(&base_args);
+                args.push("ps".to_string());
+                args.push("--services".to_string());
+                args.push("--filter".to_string());
+                args.push("status=running".to_string());
+
+                let out = CmdBuilder::new(&prog)
+                    .args(&args)
+                    .cwd(&stack.dir)
+                    .capture_stdout()
+                    .run_capture()?;
+
+                if let Some(service) = out.stdout_lines().into_iter().next() {
+                    running = Some((stack, service));
+                    break;
+                }
+            }
+            running.ok_or_else(|| anyhow!("No running containers found"))?
         }
     };
 
-    let (prog, mut args) = docker_compose_program()?;
+    let (prog, base_args) = docker_compose_program()?;
+    let mut args = stack.compose_args(&base_args);
     args.push("exec".to_string());
     args.push(service.clone());
     args.push("sh".to_string());
 
-    ctx.print_info(&format!("Opening shell in {}...", service));
+    ctx.print_info(&format!(
+        "Opening shell in {}...",
+        stack.service_name(&service)
+    ));
 
     let code = CmdBuilder::new(&prog)
         .args(&args)
-        .cwd(&ctx.repo)
+        .cwd(&stack.dir)
         .inherit_io()
         .run()?;
 
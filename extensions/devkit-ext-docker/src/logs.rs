@@ -2,6 +2,7 @@
 
 use anyhow::{anyhow, Result};
 use console::style;
+use devkit_core::oplog::LoggedCommand;
 use devkit_core::{utils::cmd_exists, AppContext};
 use devkit_tasks::CmdBuilder;
 
@@ -9,7 +10,7 @@ use devkit_tasks::CmdBuilder;
 fn is_container_running(container: &str) -> bool {
     let output = std::process::Command::new("docker")
         .args(["inspect", "-f", "{{.State.Running}}", container])
-        .output();
+        .logged_output();
 
     match output {
         Ok(out) => String::from_utf8_lossy(&out.stdout).trim() == "true",
@@ -0,0 +1,290 @@
+//! Compose service status
+//!
+//! `devkit docker status` shows each service's state, health check, published ports
+//! and restart count in a table. The containers come from
+//! `docker compose ps --all --format json` (one object per line, or an array from
+//! older Compose versions), restart counts from `docker inspect`.
+
+use anyhow::{anyhow, Context, Result};
+use console::{style, Style};
+use devkit_core::oplog::LoggedCommand;
+use devkit_core::utils::{docker_compose_program, ensure_docker};
+use devkit_core::AppContext;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::process::Command;
+
+use crate::compose::{list_services, stacks};
+
+/// A compose service's container, or a service with none yet
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServiceStatus {
+    /// As shown to the user, `<repo>/<service>` for `[repos]` stacks
+    pub service: String,
+    /// running, exited, restarting, ...; `None` when no container was created
+    pub state: Option<String>,
+    /// healthy, unhealthy or starting; `None` without a health check
+    pub health: Option<String>,
+    pub exit_code: i64,
+    /// Published ports, e.g. `8080->80`
+    pub ports: Vec<String>,
+    pub restarts: u64,
+    container_id: String,
+}
+
+impl ServiceStatus {
+    fn state_label(&self) -> String {
+        match (self.state.as_deref(), self.exit_code) {
+            (None, _) => "not created".to_string(),
+            (Some("exited"), code) if code != 0 => format!("exited ({})", code),
+            (Some(state), _) => state.to_string(),
+        }
+    }
+
+    fn state_style(&self) -> Style {
+        match self.state.as_deref() {
+            Some("running") => Style::new().green(),
+            Some("restarting" | "created" | "paused") => Style::new().yellow(),
+            Some("exited") if self.exit_code == 0 => Style::new().dim(),
+            None => Style::new().dim(),
+            Some(_) => Style::new().red(),
+        }
+    }
+
+    fn health_style(&self) -> Style {
+        match self.health.as_deref() {
+            Some("healthy") => Style::new().green(),
+            Some("unhealthy") => Style::new().red(),
+            Some(_) => Style::new().yellow(),
+            None => Style::new().dim(),
+        }
+    }
+}
+
+/// Every service of every stack, sorted, with its container's status
+pub fn service_statuses(ctx: &AppContext) -> Result<Vec<ServiceStatus>> {
+    ensure_docker()?;
+    let (prog, base_args) = docker_compose_program()?;
+
+    let mut statuses = Vec::new();
+    for stack in stacks(ctx) {
+        let mut args = stack.compose_args(&base_args);
+        args.extend(["ps", "--all", "--format", "json"].map(String::from));
+        let output = Command::new(&prog)
+            .args(&args)
+            .current_dir(&stack.dir)
+            .logged_output()
+            .context("Failed to run docker compose ps")?;
+        if !output.status.success() {
+            return Err(anyhow!(
+                "docker compose ps failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        for mut status in parse_ps(&String::from_utf8_lossy(&output.stdout))? {
+            status.service = stack.service_name(&status.service);
+            statuses.push(status);
+        }
+    }
+
+    let ids: Vec<&str> = statuses
+        .iter()
+        .map(|status| status.container_id.as_str())
+        .filter(|id| !id.is_empty())
+        .collect();
+    if !ids.is_empty() {
+        let output = Command::new("docker")
+            .args(["inspect", "--format", "{{.Id}} {{.RestartCount}}"])
+            .args(&ids)
+            .logged_output()
+            .context("Failed to run docker inspect")?;
+        let restarts = parse_restarts(&String::from_utf8_lossy(&output.stdout));
+        for status in &mut statuses {
+            if let Some((_, count)) = restarts.iter().find(|(id, _)| {
+                !status.container_id.is_empty() && id.starts_with(&status.container_id)
+            }) {
+                status.restarts = *count;
+            }
+        }
+    }
+
+    // Services without a container yet
+    for service in list_services(ctx)? {
+        if !statuses.iter().any(|status| status.service == service) {
+            statuses.push(ServiceStatus {
+                service,
+                state: None,
+                health: None,
+                exit_code: 0,
+                ports: Vec::new(),
+                restarts: 0,
+                container_id: String::new(),
+            });
+        }
+    }
+
+    statuses.sort_by(|a, b| a.service.cmp(&b.service));
+    Ok(statuses)
+}
+
+/// Print the services as a table
+pub fn print_status(ctx: &AppContext) -> Result<()> {
+    let statuses = service_statuses(ctx)?;
+
+    ctx.print_header("Docker services");
+    println!();
+    if statuses.is_empty() {
+        ctx.print_info("No services in the compose file");
+        return Ok(());
+    }
+
+    let rows: Vec<[String; 5]> = statuses
+        .iter()
+        .map(|status| {
+            [
+                status.service.clone(),
+                status.state_label(),
+                status.health.clone().unwrap_or_else(|| "-".to_string()),
+                match status.ports.is_empty() {
+                    true => "-".to_string(),
+                    false => status.ports.join(", "),
+                },
+                status.restarts.to_string(),
+            ]
+        })
+        .collect();
+    let headers = ["SERVICE", "STATE", "HEALTH", "PORTS", "RESTARTS"];
+    let widths: Vec<usize> = (0..headers.len())
+        .map(|column| {
+            rows.iter()
+                .map(|row| row[column].chars().count())
+                .chain([headers[column].len()])
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    let cell = |text: &str, column: usize| format!("{:<width$}", text, width = widths[column]);
+
+    println!(
+        "  {}",
+        style(
+            headers
+                .iter()
+                .enumerate()
+                .map(|(column, header)| cell(header, column))
+                .collect::<Vec<_>>()
+                .join("  ")
+        )
+        .bold()
+    );
+    for (status, row) in statuses.iter().zip(&rows) {
+        let restarts = match status.restarts {
+            0 => Style::new().dim(),
+            _ => Style::new().yellow(),
+        };
+        println!(
+            "  {}  {}  {}  {}  {}",
+            style(cell(&row[0], 0)).bold(),
+            status.state_style().apply_to(cell(&row[1], 1)),
+            status.health_style().apply_to(cell(&row[2], 2)),
+            cell(&row[3], 3),
+            restarts.apply_to(&row[4])
+        );
+    }
+    Ok(())
+}
+
+/// Containers from `docker compose ps --format json`
+fn parse_ps(output: &str) -> Result<Vec<ServiceStatus>> {
+    let output = output.trim();
+    let entries: Vec<Value> = if output.starts_with('[') {
+        serde_json::from_str(output)?
+    } else {
+        output
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(serde_json::from_str)
+            .collect::<serde_json::Result<_>>()?
+    };
+
+    Ok(entries
+        .iter()
+        .map(|entry| {
+            let field = |key: &str| {
+                entry[key]
+                    .as_str()
+                    .filter(|value| !value.is_empty())
+                    .map(String::from)
+            };
+            ServiceStatus {
+                service: field("Service").unwrap_or_default(),
+                state: field("State"),
+                health: field("Health"),
+                exit_code: entry["ExitCode"].as_i64().unwrap_or(0),
+                ports: published_ports(entry),
+                restarts: 0,
+                container_id: field("ID").unwrap_or_default(),
+            }
+        })
+        .collect())
+}
+
+/// `8080->80` per published port, `/udp` marked; IPv4 and IPv6 bindings count once
+fn published_ports(entry: &Value) -> Vec<String> {
+    let mut ports: Vec<String> = Vec::new();
+    for publisher in entry["Publishers"].as_array().into_iter().flatten() {
+        let published = publisher["PublishedPort"].as_u64().unwrap_or(0);
+        if published == 0 {
+            continue;
+        }
+        let mut port = format!("{}->{}", published, publisher["TargetPort"]);
+        if let Some(protocol) = publisher["Protocol"].as_str().filter(|p| *p != "tcp") {
+            port.push_str(&format!("/{}", protocol));
+        }
+        if !ports.contains(&port) {
+            ports.push(port);
+        }
+    }
+    ports
+}
+
+/// Restart count per full container id, from `docker inspect` lines of "<id> <count>"
+fn parse_restarts(output: &str) -> HashMap<String, u64> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (id, count) = line.split_once(' ')?;
+            Some((id.to_string(), count.trim().parse().ok()?))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ps() {
+        let lines = r#"{"ID":"3f2a9c1d","Service":"postgres","State":"running","Health":"healthy","ExitCode":0,"Publishers":[{"URL":"0.0.0.0","TargetPort":5432,"PublishedPort":5432,"Protocol":"tcp"},{"URL":"::","TargetPort":5432,"PublishedPort":5432,"Protocol":"tcp"},{"URL":"","TargetPort":8125,"PublishedPort":0,"Protocol":"udp"}]}
+{"ID":"b7e01f2a","Service":"worker","State":"exited","Health":"","ExitCode":137,"Publishers":null}"#;
+        let statuses = parse_ps(lines).unwrap();
+        assert_eq!(statuses[0].ports, ["5432->5432"]);
+        assert_eq!(statuses[0].health.as_deref(), Some("healthy"));
+        assert_eq!(statuses[0].state_label(), "running");
+        assert_eq!(statuses[1].health, None);
+        assert_eq!(statuses[1].state_label(), "exited (137)");
+
+        // Older Compose versions print an array
+        let array = r#"[{"ID":"c0ffee","Service":"redis","State":"restarting","Publishers":[{"TargetPort":53,"PublishedPort":5353,"Protocol":"udp"}]}]"#;
+        let statuses = parse_ps(array).unwrap();
+        assert_eq!(statuses[0].ports, ["5353->53/udp"]);
+        assert!(parse_ps("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_parse_restarts() {
+        let restarts = parse_restarts("3f2a9c1d00aa 0\nb7e01f2a00bb 4\n");
+        assert_eq!(restarts["b7e01f2a00bb"], 4);
+        assert_eq!(restarts.len(), 2);
+    }
+}
@@ -0,0 +1,57 @@
+//! `devkit docs` subcommand
+
+use anyhow::Result;
+use clap::{Args, Command, FromArgMatches, Subcommand};
+use devkit_core::AppContext;
+
+use crate::{build, find_site, list, open, serve};
+
+#[derive(Args)]
+pub struct DocsArgs {
+    #[command(subcommand)]
+    action: Option<DocsAction>,
+}
+
+#[derive(Subcommand)]
+enum DocsAction {
+    /// List docs sites (default)
+    List,
+    /// Serve a site with live reload
+    Serve {
+        /// Site kind (mdbook, docusaurus, mkdocs, rustdoc) or directory
+        site: Option<String>,
+        /// Open the browser
+        #[arg(long)]
+        open: bool,
+    },
+    /// Build a site
+    Build {
+        /// Site kind (mdbook, docusaurus, mkdocs, rustdoc) or directory
+        site: Option<String>,
+    },
+    /// Open the built site (`cargo doc --open` for rustdoc)
+    Open {
+        /// Site kind (mdbook, docusaurus, mkdocs, rustdoc) or directory
+        site: Option<String>,
+    },
+}
+
+pub fn command() -> Command {
+    DocsArgs::augment_args(
+        Command::new("docs")
+            .about("Serve, build and open documentation (mdBook, Docusaurus, MkDocs, rustdoc)"),
+    )
+}
+
+pub fn handle(ctx: &AppContext, matches: &clap::ArgMatches) -> Result<()> {
+    let args = DocsArgs::from_arg_matches(matches)?;
+
+    match args.action {
+        Some(DocsAction::List) | None => list(ctx),
+        Some(DocsAction::Serve { site, open }) => {
+            serve(ctx, &find_site(ctx, site.as_deref())?, open)
+        }
+        Some(DocsAction::Build { site }) => build(ctx, &find_site(ctx, site.as_deref())?),
+        Some(DocsAction::Open { site }) => open(ctx, &find_site(ctx, site.as_deref())?),
+    }
+}
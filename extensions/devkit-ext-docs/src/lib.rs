@@ -0,0 +1,394 @@
+//! Documentation extension for devkit
+//!
+//! Finds mdBook (`book.toml`), Docusaurus (`docusaurus.config.*`) and MkDocs
+//! (`mkdocs.yml`) sites at the repo root, in `docs/`, `website/` or `book/`, and in
+//! packages, plus rustdoc for Rust workspaces. Each site can be served with live
+//! reload, built, or opened in the browser.
+
+mod cli;
+
+use anyhow::{anyhow, bail, Context, Result};
+use devkit_core::frameworks::node_runner;
+use devkit_core::oplog::LoggedCommand;
+use devkit_core::utils::{cmd_exists, open_in_browser};
+use devkit_core::{AppContext, Config, Extension, MenuItem};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Directories besides the repo root and packages where doc sites usually live
+const DOC_DIRS: &[&str] = &["docs", "website", "book"];
+
+pub struct DocsExtension;
+
+impl Extension for DocsExtension {
+    fn name(&self) -> &str {
+        "docs"
+    }
+
+    fn is_available(&self, ctx: &AppContext) -> bool {
+        !sites(&ctx.config).is_empty()
+    }
+
+    fn unavailable_reason(&self, _ctx: &AppContext) -> Option<String> {
+        Some("no mdBook, Docusaurus, MkDocs or Rust project found".to_string())
+    }
+
+    fn menu_items(&self, ctx: &AppContext) -> Vec<MenuItem> {
+        use devkit_core::DevkitError;
+        let mut items = Vec::new();
+        for site in sites(&ctx.config) {
+            let label = site.label(&ctx.repo);
+            if site.kind != DocKind::Rustdoc {
+                let serving = site.clone();
+                items.push(MenuItem {
+                    label: format!("Serve {}", label),
+                    group: Some("📚 Docs".to_string()),
+                    handler: Box::new(move |ctx| {
+                        serve(ctx, &serving, true).map_err(DevkitError::from)
+                    }),
+                });
+            }
+            let building = site.clone();
+            items.push(MenuItem {
+                label: format!("Build {}", label),
+                group: Some("📚 Docs".to_string()),
+                handler: Box::new(move |ctx| build(ctx, &building).map_err(DevkitError::from)),
+            });
+            items.push(MenuItem {
+                label: format!("Open {}", label),
+                group: Some("📚 Docs".to_string()),
+                handler: Box::new(move |ctx| open(ctx, &site).map_err(DevkitError::from)),
+            });
+        }
+        items
+    }
+
+    fn subcommands(&self) -> Vec<clap::Command> {
+        vec![cli::command()]
+    }
+
+    fn handle_cli(
+        &self,
+        ctx: &AppContext,
+        name: &str,
+        matches: &clap::ArgMatches,
+    ) -> Option<devkit_core::Result<()>> {
+        (name == "docs").then(|| cli::handle(ctx, matches).map_err(Into::into))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DocKind {
+    MdBook,
+    Docusaurus,
+    MkDocs,
+    Rustdoc,
+}
+
+impl DocKind {
+    /// Identifier accepted on the command line
+    pub fn id(self) -> &'static str {
+        match self {
+            DocKind::MdBook => "mdbook",
+            DocKind::Docusaurus => "docusaurus",
+            DocKind::MkDocs => "mkdocs",
+            DocKind::Rustdoc => "rustdoc",
+        }
+    }
+
+    fn detect(dir: &Path) -> Option<DocKind> {
+        if dir.join("book.toml").exists() {
+            Some(DocKind::MdBook)
+        } else if ["js", "ts", "mjs", "cjs"]
+            .iter()
+            .any(|ext| dir.join(format!("docusaurus.config.{}", ext)).exists())
+        {
+            Some(DocKind::Docusaurus)
+        } else if dir.join("mkdocs.yml").exists() || dir.join("mkdocs.yaml").exists() {
+            Some(DocKind::MkDocs)
+        } else {
+            None
+        }
+    }
+
+    /// Tool that has to be installed, if any (Docusaurus runs from node_modules)
+    fn tool(self) -> Option<(&'static str, &'static str)> {
+        match self {
+            DocKind::MdBook => Some(("mdbook", "cargo install mdbook")),
+            DocKind::MkDocs => Some(("mkdocs", "pip install mkdocs")),
+            DocKind::Rustdoc => Some(("cargo", "https://rustup.rs")),
+            DocKind::Docusaurus => None,
+        }
+    }
+
+    /// Build output directory, relative to the site
+    fn output_dir(self) -> &'static str {
+        match self {
+            DocKind::MdBook => "book",
+            DocKind::Docusaurus => "build",
+            DocKind::MkDocs => "site",
+            DocKind::Rustdoc => "target/doc",
+        }
+    }
+}
+
+impl fmt::Display for DocKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            DocKind::MdBook => "mdBook",
+            DocKind::Docusaurus => "Docusaurus",
+            DocKind::MkDocs => "MkDocs",
+            DocKind::Rustdoc => "rustdoc",
+        })
+    }
+}
+
+/// A documentation site
+#[derive(Debug, Clone, PartialEq)]
+pub struct DocSite {
+    pub kind: DocKind,
+    pub dir: PathBuf,
+}
+
+impl DocSite {
+    /// "mdBook (docs)", or just "mdBook" at the repo root
+    pub fn label(&self, repo: &Path) -> String {
+        match self.dir.strip_prefix(repo) {
+            Ok(rel) if !rel.as_os_str().is_empty() => format!("{} ({})", self.kind, rel.display()),
+            _ => self.kind.to_string(),
+        }
+    }
+
+    /// Whether `query` names this site: its kind or its directory
+    fn matches(&self, repo: &Path, query: &str) -> bool {
+        self.kind.id() == query
+            || self
+                .dir
+                .strip_prefix(repo)
+                .is_ok_and(|rel| rel == Path::new(query.trim_end_matches('/')))
+    }
+
+    fn command(&self, program: &str) -> Command {
+        let mut cmd = Command::new(program);
+        cmd.current_dir(&self.dir);
+        cmd
+    }
+
+    /// The site's Node runner (`npx`, `pnpm exec`, `yarn`) followed by `docusaurus`
+    fn docusaurus(&self) -> Command {
+        let runner = node_runner(&self.dir);
+        let mut words = runner.split_whitespace();
+        let mut cmd = self.command(words.next().unwrap_or("npx"));
+        cmd.args(words).arg("docusaurus");
+        cmd
+    }
+}
+
+/// Doc sites in the repo; rustdoc last
+pub fn sites(config: &Config) -> Vec<DocSite> {
+    let repo = config.repo_root.as_path();
+    let mut dirs: Vec<PathBuf> = std::iter::once(repo.to_path_buf())
+        .chain(DOC_DIRS.iter().map(|dir| repo.join(dir)))
+        .chain(config.packages.values().map(|pkg| pkg.path.clone()))
+        .collect();
+    dirs.sort();
+    dirs.dedup();
+
+    let mut sites: Vec<DocSite> = dirs
+        .into_iter()
+        .filter_map(|dir| DocKind::detect(&dir).map(|kind| DocSite { kind, dir }))
+        .collect();
+    if repo.join("Cargo.toml").exists() {
+        sites.push(DocSite {
+            kind: DocKind::Rustdoc,
+            dir: repo.to_path_buf(),
+        });
+    }
+    sites
+}
+
+/// Find a site by kind or directory; without a query, the only site (rustdoc aside)
+pub fn find_site(ctx: &AppContext, query: Option<&str>) -> Result<DocSite> {
+    let sites = sites(&ctx.config);
+    if let Some(query) = query {
+        return sites
+            .into_iter()
+            .find(|site| site.matches(&ctx.repo, query))
+            .ok_or_else(|| anyhow!("No docs site '{}' (see devkit docs list)", query));
+    }
+
+    let mut candidates: Vec<&DocSite> = sites
+        .iter()
+        .filter(|site| site.kind != DocKind::Rustdoc)
+        .collect();
+    if candidates.is_empty() {
+        candidates = sites.iter().collect();
+    }
+    match candidates[..] {
+        [site] => Ok(site.clone()),
+        [] => bail!("No mdBook, Docusaurus, MkDocs or Rust project found"),
+        _ => bail!(
+            "Several docs sites found; pick one: {}",
+            candidates
+                .iter()
+                .map(|site| site.label(&ctx.repo))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    }
+}
+
+fn ensure_tool(kind: DocKind) -> Result<()> {
+    match kind.tool() {
+        Some((tool, hint)) if !cmd_exists(tool) => {
+            bail!("{} not installed. Install it with: {}", tool, hint)
+        }
+        _ => Ok(()),
+    }
+}
+
+fn run(mut cmd: Command, what: &str) -> Result<()> {
+    let status = cmd
+        .logged_status()
+        .with_context(|| format!("Failed to {}", what))?;
+    // 130 = stopped with Ctrl+C
+    if !status.success() && status.code() != Some(130) {
+        bail!("Failed to {} ({})", what, status);
+    }
+    Ok(())
+}
+
+/// List doc sites
+pub fn list(ctx: &AppContext) -> Result<()> {
+    ctx.print_header("Docs");
+    println!();
+    let sites = sites(&ctx.config);
+    if sites.is_empty() {
+        ctx.print_info("No mdBook, Docusaurus, MkDocs or Rust project found");
+    }
+    for site in sites {
+        println!("  {:<12} {}", site.kind.id(), site.label(&ctx.repo));
+    }
+    Ok(())
+}
+
+/// Serve a site with live reload until interrupted
+pub fn serve(ctx: &AppContext, site: &DocSite, open_browser: bool) -> Result<()> {
+    ensure_tool(site.kind)?;
+    ctx.print_info(&format!(
+        "Serving {} (Ctrl+C to stop)",
+        site.label(&ctx.repo)
+    ));
+
+    let cmd = match site.kind {
+        DocKind::MdBook => {
+            let mut cmd = site.command("mdbook");
+            cmd.arg("serve");
+            if open_browser {
+                cmd.arg("--open");
+            }
+            cmd
+        }
+        DocKind::Docusaurus => {
+            let mut cmd = site.docusaurus();
+            cmd.arg("start");
+            if !open_browser {
+                cmd.arg("--no-open");
+            }
+            cmd
+        }
+        DocKind::MkDocs => {
+            let mut cmd = site.command("mkdocs");
+            cmd.arg("serve");
+            if open_browser {
+                cmd.arg("--open");
+            }
+            cmd
+        }
+        // rustdoc has no server; the built files are opened directly
+        DocKind::Rustdoc => return open(ctx, site),
+    };
+    run(cmd, &format!("serve {}", site.kind))
+}
+
+/// Build a site
+pub fn build(ctx: &AppContext, site: &DocSite) -> Result<()> {
+    ensure_tool(site.kind)?;
+    ctx.print_info(&format!("Building {}...", site.label(&ctx.repo)));
+
+    let cmd = match site.kind {
+        DocKind::MdBook => {
+            let mut cmd = site.command("mdbook");
+            cmd.arg("build");
+            cmd
+        }
+        DocKind::Docusaurus => {
+            let mut cmd = site.docusaurus();
+            cmd.arg("build");
+            cmd
+        }
+        DocKind::MkDocs => {
+            let mut cmd = site.command("mkdocs");
+            cmd.arg("build");
+            cmd
+        }
+        DocKind::Rustdoc => {
+            let mut cmd = site.command("cargo");
+            cmd.args(["doc", "--no-deps", "--workspace"]);
+            cmd
+        }
+    };
+    run(cmd, &format!("build {}", site.kind))?;
+
+    ctx.print_success(&format!(
+        "✓ Built {}",
+        site.dir.join(site.kind.output_dir()).display()
+    ));
+    Ok(())
+}
+
+/// Open a site's built output in the browser, building it first if needed
+pub fn open(ctx: &AppContext, site: &DocSite) -> Result<()> {
+    if site.kind == DocKind::Rustdoc {
+        ensure_tool(site.kind)?;
+        let mut cmd = site.command("cargo");
+        cmd.args(["doc", "--no-deps", "--workspace", "--open"]);
+        return run(cmd, "run cargo doc");
+    }
+
+    let index = site.dir.join(site.kind.output_dir()).join("index.html");
+    if !index.exists() {
+        build(ctx, site)?;
+    }
+    open_in_browser(&index.to_string_lossy())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_sites() {
+        let repo = std::env::temp_dir().join(format!("devkit-docs-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&repo);
+        fs::create_dir_all(repo.join("docs")).unwrap();
+        fs::create_dir_all(repo.join("website")).unwrap();
+        fs::write(repo.join("Cargo.toml"), "[workspace]\n").unwrap();
+        fs::write(repo.join("docs/book.toml"), "").unwrap();
+        fs::write(repo.join("website/docusaurus.config.ts"), "").unwrap();
+
+        let config = Config::load(&repo).unwrap();
+        let sites = sites(&config);
+        let labels: Vec<String> = sites.iter().map(|site| site.label(&repo)).collect();
+        assert_eq!(labels, ["mdBook (docs)", "Docusaurus (website)", "rustdoc"]);
+
+        assert!(sites[0].matches(&repo, "docs/"));
+        assert!(sites[1].matches(&repo, "docusaurus"));
+        assert!(!sites[1].matches(&repo, "docs"));
+
+        fs::remove_dir_all(repo).ok();
+    }
+}
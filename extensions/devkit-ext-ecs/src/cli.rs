@@ -0,0 +1,125 @@
+//! `devkit ecs` subcommand
+
+use anyhow::Result;
+use clap::{Args, Command, FromArgMatches, Subcommand};
+use devkit_core::AppContext;
+
+use crate::cloudwatch::TailOptions;
+use crate::selection::Target;
+use crate::{deploy, ecs_logs_interactive, exec, list, logs, select};
+
+#[derive(Args)]
+pub struct EcsArgs {
+    #[command(subcommand)]
+    action: Option<EcsAction>,
+}
+
+#[derive(Subcommand)]
+enum EcsAction {
+    /// List tasks in the selected cluster and service (default)
+    List(TargetArgs),
+    /// Open a shell in a task's container (needs the Session Manager plugin)
+    Exec(TargetArgs),
+    /// Follow the CloudWatch logs of a task's container
+    Logs(TargetArgs),
+    /// Pick the cluster and service used from now on
+    Select(TargetArgs),
+    /// Register a new task definition revision and roll it out to a service
+    Deploy(DeployArgs),
+    /// Pick a CloudWatch log group and stream and show its events
+    Cloudwatch(CloudwatchArgs),
+}
+
+/// Anything left out is picked (or the saved pick is used)
+#[derive(Args, Default)]
+struct TargetArgs {
+    #[arg(long)]
+    cluster: Option<String>,
+    #[arg(long)]
+    service: Option<String>,
+    /// Task ID
+    #[arg(long)]
+    task: Option<String>,
+    #[arg(long)]
+    container: Option<String>,
+}
+
+#[derive(Args)]
+struct DeployArgs {
+    /// Service to deploy (default: the selected one)
+    service: Option<String>,
+    #[arg(long)]
+    cluster: Option<String>,
+    /// Image tag substituted for {tag} in the template (default: short SHA of HEAD)
+    #[arg(long)]
+    tag: Option<String>,
+    /// Don't wait for the service to reach a steady state
+    #[arg(long)]
+    no_wait: bool,
+}
+
+/// Anything left out is picked
+#[derive(Args)]
+struct CloudwatchArgs {
+    /// Log group name
+    #[arg(long)]
+    group: Option<String>,
+    /// Log stream name
+    #[arg(long)]
+    stream: Option<String>,
+    /// How far back to start, e.g. 30m, 2h or 2024-05-01T10:00:00 (default: 10m)
+    #[arg(long)]
+    since: Option<String>,
+    /// CloudWatch filter pattern, e.g. ERROR or "{ $.level = \"error\" }"
+    #[arg(long)]
+    filter: Option<String>,
+    /// Keep printing new events
+    #[arg(short, long)]
+    follow: bool,
+}
+
+impl From<TargetArgs> for Target {
+    fn from(args: TargetArgs) -> Self {
+        Target {
+            cluster: args.cluster,
+            service: args.service,
+            task: args.task,
+            container: args.container,
+        }
+    }
+}
+
+pub fn command() -> Command {
+    EcsArgs::augment_args(
+        Command::new("ecs")
+            .about("AWS ECS tasks: list, exec, logs and deploy (requires the aws CLI)"),
+    )
+}
+
+pub fn handle(ctx: &AppContext, matches: &clap::ArgMatches) -> Result<()> {
+    let args = EcsArgs::from_arg_matches(matches)?;
+
+    match args.action {
+        Some(EcsAction::List(target)) => list(ctx, &target.into()),
+        None => list(ctx, &Target::default()),
+        Some(EcsAction::Exec(target)) => exec(ctx, &target.into()),
+        Some(EcsAction::Logs(target)) => logs(ctx, &target.into()),
+        Some(EcsAction::Select(target)) => select(ctx, &target.into()),
+        Some(EcsAction::Deploy(args)) => {
+            let target = Target {
+                cluster: args.cluster,
+                service: args.service,
+                ..Default::default()
+            };
+            deploy(ctx, &target, args.tag.as_deref(), !args.no_wait)
+        }
+        Some(EcsAction::Cloudwatch(args)) => {
+            let options = TailOptions {
+                since: args.since,
+                filter: args.filter,
+                follow: args.follow,
+            };
+            ecs_logs_interactive(ctx, args.group.as_deref(), args.stream.as_deref(), &options)
+        }
+    }
+}
@@ -0,0 +1,175 @@
+//! CloudWatch log groups and streams
+//!
+//! `devkit ecs cloudwatch` lists log groups and their most recently written streams
+//! with the AWS CLI, so logs can be read without knowing the group name up front.
+
+use anyhow::{anyhow, Context, Result};
+use devkit_core::oplog::LoggedCommand;
+use devkit_core::AppContext;
+use serde_json::Value;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::resources::{aws, aws_json};
+
+/// How many streams are offered, most recently written first
+const MAX_STREAMS: &str = "50";
+
+/// A log stream and when it was last written to
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stream {
+    pub name: String,
+    /// Milliseconds since the epoch
+    pub last_event: Option<u64>,
+}
+
+impl Stream {
+    /// "ecs/api/f00d  (3m ago)", for pickers
+    pub fn label(&self, now: u64) -> String {
+        match self.last_event {
+            Some(at) => format!("{}  ({} ago)", self.name, ago(now.saturating_sub(at))),
+            None => self.name.clone(),
+        }
+    }
+}
+
+/// What `aws logs tail` shows
+#[derive(Debug, Default)]
+pub struct TailOptions {
+    /// How far back to start, e.g. `10m`, `2h` or an ISO timestamp (aws default: 10m)
+    pub since: Option<String>,
+    /// CloudWatch filter pattern
+    pub filter: Option<String>,
+    pub follow: bool,
+}
+
+/// Log group names, sorted
+pub fn log_groups(ctx: &AppContext) -> Result<Vec<String>> {
+    let doc = aws_json(ctx, &["logs", "describe-log-groups"])?;
+    Ok(parse_groups(&doc))
+}
+
+fn parse_groups(doc: &Value) -> Vec<String> {
+    let mut groups: Vec<String> = doc["logGroups"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|group| group["logGroupName"].as_str().map(String::from))
+        .collect();
+    groups.sort();
+    groups
+}
+
+/// The group's most recently written streams
+pub fn log_streams(ctx: &AppContext, group: &str) -> Result<Vec<Stream>> {
+    let doc = aws_json(
+        ctx,
+        &[
+            "logs",
+            "describe-log-streams",
+            "--log-group-name",
+            group,
+            "--order-by",
+            "LastEventTime",
+            "--descending",
+            "--max-items",
+            MAX_STREAMS,
+        ],
+    )?;
+    Ok(parse_streams(&doc))
+}
+
+fn parse_streams(doc: &Value) -> Vec<Stream> {
+    doc["logStreams"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|stream| {
+            Some(Stream {
+                name: stream["logStreamName"].as_str()?.to_string(),
+                last_event: stream["lastEventTimestamp"].as_u64(),
+            })
+        })
+        .collect()
+}
+
+/// Print (or follow) a group's events, only `stream`'s when given
+pub fn tail(
+    ctx: &AppContext,
+    group: &str,
+    stream: Option<&str>,
+    options: &TailOptions,
+) -> Result<()> {
+    let mut cmd = aws(ctx);
+    cmd.args(["logs", "tail", group]);
+    if let Some(stream) = stream {
+        cmd.args(["--log-stream-names", stream]);
+    }
+    if let Some(since) = &options.since {
+        cmd.args(["--since", since]);
+    }
+    if let Some(filter) = &options.filter {
+        cmd.args(["--filter-pattern", filter]);
+    }
+    if options.follow {
+        cmd.arg("--follow");
+    }
+
+    let status = cmd.logged_status().context("Failed to run aws logs tail")?;
+    if !status.success() && status.code() != Some(130) {
+        return Err(anyhow!("Failed to view logs"));
+    }
+    Ok(())
+}
+
+/// Milliseconds since the epoch
+pub fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+/// "45s", "3m", "2h" or "5d"
+fn ago(millis: u64) -> String {
+    let secs = millis / 1000;
+    match secs {
+        0..=59 => format!("{}s", secs),
+        60..=3599 => format!("{}m", secs / 60),
+        3600..=86399 => format!("{}h", secs / 3600),
+        _ => format!("{}d", secs / 86400),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_streams() {
+        let doc = json!({
+            "logStreams": [
+                { "logStreamName": "ecs/api/f00d", "lastEventTimestamp": 1_700_000_000_000u64 },
+                { "logStreamName": "ecs/api/beef" }
+            ]
+        });
+        let streams = parse_streams(&doc);
+        assert_eq!(
+            streams[0].label(1_700_000_180_000),
+            "ecs/api/f00d  (3m ago)"
+        );
+        assert_eq!(streams[1].label(1_700_000_180_000), "ecs/api/beef");
+
+        let doc = json!({
+            "logGroups": [{ "logGroupName": "/ecs/web" }, { "logGroupName": "/ecs/api" }]
+        });
+        assert_eq!(parse_groups(&doc), ["/ecs/api", "/ecs/web"]);
+    }
+
+    #[test]
+    fn test_ago() {
+        assert_eq!(ago(45_000), "45s");
+        assert_eq!(ago(7_200_000), "2h");
+        assert_eq!(ago(3 * 86_400_000), "3d");
+    }
+}
@@ -0,0 +1,265 @@
+//! Service deploys
+//!
+//! `devkit ecs deploy <service>` renders the service's task definition template,
+//! registers it as a new revision, points the service at it and waits for the rollout
+//! to settle. Templates are `aws ecs register-task-definition --cli-input-json` input,
+//! with `{tag}` standing for the image tag (`--tag`, or the short SHA of HEAD):
+//!
+//! ```toml
+//! [ecs.task_definitions]
+//! api = "deploy/api-task.json"    # default: ecs/<service>.json
+//! ```
+
+use anyhow::{bail, Context, Result};
+use console::style;
+use devkit_core::discovery_cache::cache_dir;
+use devkit_core::oplog::LoggedCommand;
+use devkit_core::AppContext;
+use serde_json::Value;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::resources::{aws, aws_json};
+
+/// How long a rollout may take before `deploy` gives up waiting
+const ROLLOUT_TIMEOUT: Duration = Duration::from_secs(15 * 60);
+
+/// The service's template
+pub fn template_path(ctx: &AppContext, service: &str) -> PathBuf {
+    match ctx.config.global.ecs.task_definitions.get(service) {
+        Some(path) => ctx.repo.join(path),
+        None => ctx.repo.join("ecs").join(format!("{}.json", service)),
+    }
+}
+
+/// The template with `{tag}` filled in
+fn render(template: &str, tag: &str) -> String {
+    template.replace("{tag}", tag)
+}
+
+/// Short SHA of HEAD, the default image tag
+pub fn head_tag(ctx: &AppContext) -> Result<String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .current_dir(&ctx.repo)
+        .logged_output()
+        .context("Failed to run git rev-parse")?;
+    if !output.status.success() {
+        bail!("Can't tell the image tag from git (pass --tag)");
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Register the rendered template as a new revision; returns its ARN
+pub fn register(ctx: &AppContext, service: &str, tag: &str) -> Result<String> {
+    let template = template_path(ctx, service);
+    let contents = fs::read_to_string(&template).with_context(|| {
+        format!(
+            "No task definition template for {} at {} (or set one under [ecs.task_definitions])",
+            service,
+            template.display()
+        )
+    })?;
+    let rendered = render(&contents, tag);
+    serde_json::from_str::<Value>(&rendered)
+        .with_context(|| format!("Invalid JSON in {}", template.display()))?;
+
+    let dir = cache_dir(&ctx.repo)?.join("ecs");
+    fs::create_dir_all(&dir)?;
+    let path = dir.join(format!("{}.json", service));
+    fs::write(&path, rendered).with_context(|| format!("Failed to write {}", path.display()))?;
+
+    let input = format!("file://{}", path.display());
+    let doc = aws_json(
+        ctx,
+        &[
+            "ecs",
+            "register-task-definition",
+            "--cli-input-json",
+            &input,
+        ],
+    )?;
+    doc["taskDefinition"]["taskDefinitionArn"]
+        .as_str()
+        .map(String::from)
+        .context("aws ecs register-task-definition returned no ARN")
+}
+
+/// Point the service at a task definition revision
+pub fn update_service(
+    ctx: &AppContext,
+    cluster: &str,
+    service: &str,
+    task_definition: &str,
+) -> Result<()> {
+    let output = aws(ctx)
+        .args(["ecs", "update-service", "--cluster", cluster])
+        .args(["--service", service, "--task-definition", task_definition])
+        .args(["--output", "json"])
+        .logged_output()
+        .context("Failed to run aws ecs update-service")?;
+    if !output.status.success() {
+        bail!(
+            "aws ecs update-service failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+/// Where the service's primary deployment stands
+#[derive(Debug, PartialEq)]
+struct Rollout {
+    /// IN_PROGRESS, COMPLETED or FAILED; missing for services without rollout state
+    state: Option<String>,
+    reason: Option<String>,
+    running: u64,
+    pending: u64,
+    desired: u64,
+    /// Deployments still active, the primary one included
+    deployments: usize,
+}
+
+impl Rollout {
+    fn is_steady(&self) -> bool {
+        match self.state.as_deref() {
+            Some(state) => state == "COMPLETED",
+            None => self.deployments == 1 && self.running == self.desired,
+        }
+    }
+
+    /// "IN_PROGRESS  2/3 running, 1 pending", for progress output
+    fn label(&self) -> String {
+        let mut label = format!(
+            "{}  {}/{} running",
+            self.state.as_deref().unwrap_or("DEPLOYING"),
+            self.running,
+            self.desired
+        );
+        if self.pending > 0 {
+            label.push_str(&format!(", {} pending", self.pending));
+        }
+        if self.deployments > 1 {
+            label.push_str(&format!(
+                ", {} old deployment(s) draining",
+                self.deployments - 1
+            ));
+        }
+        label
+    }
+}
+
+/// The primary deployment in `aws ecs describe-services` output
+fn parse_rollout(doc: &Value) -> Option<Rollout> {
+    let deployments = doc["services"][0]["deployments"].as_array()?;
+    let primary = deployments
+        .iter()
+        .find(|deployment| deployment["status"] == "PRIMARY")?;
+    let count = |key: &str| primary[key].as_u64().unwrap_or(0);
+    Some(Rollout {
+        state: primary["rolloutState"].as_str().map(String::from),
+        reason: primary["rolloutStateReason"].as_str().map(String::from),
+        running: count("runningCount"),
+        pending: count("pendingCount"),
+        desired: count("desiredCount"),
+        deployments: deployments.len(),
+    })
+}
+
+/// Wait for the service to reach a steady state, printing progress as it changes
+pub fn wait(ctx: &AppContext, cluster: &str, service: &str) -> Result<()> {
+    let started = Instant::now();
+    let mut last = String::new();
+    while started.elapsed() < ROLLOUT_TIMEOUT {
+        let doc = aws_json(
+            ctx,
+            &[
+                "ecs",
+                "describe-services",
+                "--cluster",
+                cluster,
+                "--services",
+                service,
+            ],
+        )?;
+        let rollout = parse_rollout(&doc)
+            .with_context(|| format!("No deployment found for {} in {}", service, cluster))?;
+
+        let label = rollout.label();
+        if label != last {
+            println!(
+                "  {}  {}",
+                style(format!("{:>4}s", started.elapsed().as_secs())).dim(),
+                label
+            );
+            last = label;
+        }
+        if rollout.state.as_deref() == Some("FAILED") {
+            bail!(
+                "Deployment of {} failed: {}",
+                service,
+                rollout.reason.as_deref().unwrap_or("no reason given")
+            );
+        }
+        if rollout.is_steady() {
+            return Ok(());
+        }
+        thread::sleep(Duration::from_secs(5));
+    }
+    bail!(
+        "{} didn't reach a steady state within {}m (check `devkit ecs list` and the ECS console)",
+        service,
+        ROLLOUT_TIMEOUT.as_secs() / 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_render() {
+        assert_eq!(
+            render(r#"{"image": "repo/api:{tag}"}"#, "3f2a9c1"),
+            r#"{"image": "repo/api:3f2a9c1"}"#
+        );
+    }
+
+    #[test]
+    fn test_parse_rollout() {
+        let doc = json!({
+            "services": [{
+                "deployments": [
+                    {
+                        "status": "PRIMARY",
+                        "rolloutState": "IN_PROGRESS",
+                        "runningCount": 1,
+                        "pendingCount": 1,
+                        "desiredCount": 2
+                    },
+                    { "status": "ACTIVE", "runningCount": 2, "desiredCount": 2 }
+                ]
+            }]
+        });
+        let rollout = parse_rollout(&doc).unwrap();
+        assert!(!rollout.is_steady());
+        assert_eq!(
+            rollout.label(),
+            "IN_PROGRESS  1/2 running, 1 pending, 1 old deployment(s) draining"
+        );
+
+        let doc = json!({
+            "services": [{
+                "deployments": [
+                    { "status": "PRIMARY", "runningCount": 2, "desiredCount": 2 }
+                ]
+            }]
+        });
+        assert!(parse_rollout(&doc).unwrap().is_steady());
+        assert_eq!(parse_rollout(&json!({ "services": [] })), None);
+    }
+}
@@ -1,11 +1,25 @@
 //! ECS extension for devkit
 //!
-//! Provides AWS ECS container operations (exec, logs, status).
+//! Provides AWS ECS container operations (exec, logs, status). Clusters, services,
+//! tasks and containers are picked interactively when not given, and the picks are
+//! remembered per checkout (see [`selection`]). `devkit ecs deploy` rolls out a new
+//! task definition revision (see [`deploy`]), and `devkit ecs cloudwatch` browses any
+//! log group (see [`cloudwatch`]).
 
-use anyhow::{anyhow, Result};
+mod cli;
+pub mod cloudwatch;
+pub mod deploy;
+pub mod resources;
+pub mod selection;
+
+use anyhow::{anyhow, bail, Context, Result};
+use cloudwatch::TailOptions;
 use console::style;
+use devkit_core::oplog::LoggedCommand;
 use devkit_core::{AppContext, Extension, MenuItem};
-use devkit_tasks::CmdBuilder;
+use dialoguer::{FuzzySelect, Select};
+use resources::{arn_name, aws};
+use selection::Target;
 
 pub struct EcsExtension;
 
@@ -18,12 +32,249 @@ impl Extension for EcsExtension {
         devkit_core::cmd_exists("aws")
     }
 
+    fn unavailable_reason(&self, _ctx: &AppContext) -> Option<String> {
+        Some("aws CLI not installed".to_string())
+    }
+
     fn menu_items(&self, _ctx: &AppContext) -> Vec<MenuItem> {
-        // Note: ECS operations require cluster/task parameters
-        // These are better used programmatically or via CLI args
-        // For now, return empty menu items
-        vec![]
+        vec![
+            MenuItem {
+                label: "List tasks".to_string(),
+                group: Some("🚢 ECS".to_string()),
+                handler: Box::new(|ctx| Ok(list(ctx, &Target::default())?)),
+            },
+            MenuItem {
+                label: "Exec into a task".to_string(),
+                group: Some("🚢 ECS".to_string()),
+                handler: Box::new(|ctx| Ok(exec(ctx, &Target::default())?)),
+            },
+            MenuItem {
+                label: "Follow task logs".to_string(),
+                group: Some("🚢 ECS".to_string()),
+                handler: Box::new(|ctx| Ok(logs(ctx, &Target::default())?)),
+            },
+            MenuItem {
+                label: "Browse CloudWatch logs".to_string(),
+                group: Some("🚢 ECS".to_string()),
+                handler: Box::new(|ctx| {
+                    Ok(ecs_logs_interactive(
+                        ctx,
+                        None,
+                        None,
+                        &TailOptions::default(),
+                    )?)
+                }),
+            },
+            MenuItem {
+                label: "Switch cluster/service".to_string(),
+                group: Some("🚢 ECS".to_string()),
+                handler: Box::new(|ctx| Ok(select(ctx, &Target::default())?)),
+            },
+        ]
+    }
+
+    fn subcommands(&self) -> Vec<clap::Command> {
+        vec![cli::command()]
+    }
+
+    fn handle_cli(
+        &self,
+        ctx: &AppContext,
+        name: &str,
+        matches: &clap::ArgMatches,
+    ) -> Option<devkit_core::Result<()>> {
+        (name == "ecs").then(|| cli::handle(ctx, matches).map_err(Into::into))
+    }
+}
+
+fn ensure_aws() -> Result<()> {
+    if !devkit_core::cmd_exists("aws") {
+        return Err(anyhow!(
+            "AWS CLI not found. Install from: https://aws.amazon.com/cli/"
+        ));
     }
+    Ok(())
+}
+
+/// List the tasks of the selected cluster and service
+pub fn list(ctx: &AppContext, target: &Target) -> Result<()> {
+    ensure_aws()?;
+    let scope = selection::scope(ctx, target)?;
+    let tasks = resources::tasks(ctx, &scope.cluster, scope.service.as_deref())?;
+
+    ctx.print_header(&format!(
+        "Tasks in {}{}",
+        scope.cluster,
+        scope
+            .service
+            .as_ref()
+            .map(|service| format!(" / {}", service))
+            .unwrap_or_default()
+    ));
+    println!();
+    if tasks.is_empty() {
+        ctx.print_info("No tasks running");
+    }
+    for task in tasks {
+        println!(
+            "  {}  {}  {}",
+            style(&task.label()).cyan(),
+            arn_name(&task.task_definition),
+            task.containers.join(", ")
+        );
+    }
+    Ok(())
+}
+
+/// Open a shell in a task picked from the selected cluster and service
+pub fn exec(ctx: &AppContext, target: &Target) -> Result<()> {
+    ensure_aws()?;
+    let picked = selection::task(ctx, target)?;
+    ecs_exec(
+        ctx,
+        &picked.cluster,
+        &picked.task.id,
+        Some(&picked.container),
+    )
+}
+
+/// Follow the CloudWatch logs of a task's container
+pub fn logs(ctx: &AppContext, target: &Target) -> Result<()> {
+    ensure_aws()?;
+    let picked = selection::task(ctx, target)?;
+    let config = match resources::log_config(ctx, &picked.task.task_definition, &picked.container) {
+        Ok(config) => config,
+        // Fall back to the configured group, filtered to the task
+        Err(e) => match &ctx.config.global.ecs.log_group {
+            Some(group) => return ecs_logs(ctx, group, &picked.task.id),
+            None => return Err(e),
+        },
+    };
+
+    let Some(stream) = config.stream(&picked.container, &picked.task.id) else {
+        return ecs_logs(ctx, &config.group, &picked.task.id);
+    };
+
+    ctx.print_header(&format!(
+        "Viewing logs for {} in task {}",
+        picked.container, picked.task.id
+    ));
+    let status = aws(ctx)
+        .args(["logs", "tail", &config.group, "--follow"])
+        .args(["--log-stream-names", &stream])
+        .logged_status()
+        .context("Failed to run aws logs tail")?;
+
+    if !status.success() && status.code() != Some(130) {
+        return Err(anyhow!("Failed to view logs"));
+    }
+    Ok(())
+}
+
+/// Register a new task definition revision for the service, update the service and,
+/// with `wait`, follow the rollout until it settles
+pub fn deploy(ctx: &AppContext, target: &Target, tag: Option<&str>, wait: bool) -> Result<()> {
+    ensure_aws()?;
+    let scope = selection::scope(ctx, target)?;
+    let Some(service) = scope.service else {
+        return Err(anyhow!("No services in cluster {}", scope.cluster));
+    };
+    let tag = match tag {
+        Some(tag) => tag.to_string(),
+        None => deploy::head_tag(ctx)?,
+    };
+
+    ctx.print_header(&format!(
+        "Deploying {} to {} ({})",
+        service, scope.cluster, tag
+    ));
+    let task_definition = deploy::register(ctx, &service, &tag)?;
+    let revision = arn_name(&task_definition);
+    ctx.print_info(&format!("Registered {}", revision));
+    deploy::update_service(ctx, &scope.cluster, &service, &task_definition)?;
+
+    if !wait {
+        ctx.print_success(&format!("✓ {} is rolling out {}", service, revision));
+        return Ok(());
+    }
+    deploy::wait(ctx, &scope.cluster, &service)?;
+    ctx.print_success(&format!("✓ {} is running {}", service, revision));
+    Ok(())
+}
+
+/// Show a log group's events, picking the group and stream when not given
+pub fn ecs_logs_interactive(
+    ctx: &AppContext,
+    group: Option<&str>,
+    stream: Option<&str>,
+    options: &TailOptions,
+) -> Result<()> {
+    ensure_aws()?;
+    let group = match group {
+        Some(group) => group.to_string(),
+        None => {
+            ctx.require_interactive("Choosing a log group", "pass --group")?;
+            let groups = cloudwatch::log_groups(ctx)?;
+            if groups.is_empty() {
+                bail!("No CloudWatch log groups found");
+            }
+            let configured = ctx.config.global.ecs.log_group.as_deref();
+            let default = groups
+                .iter()
+                .position(|group| Some(group.as_str()) == configured)
+                .unwrap_or(0);
+            let selection = FuzzySelect::with_theme(&*ctx.theme())
+                .with_prompt("Log group")
+                .items(&groups)
+                .default(default)
+                .interact()?;
+            groups[selection].clone()
+        }
+    };
+
+    let stream = match stream {
+        Some(stream) => Some(stream.to_string()),
+        None => {
+            ctx.require_interactive("Choosing a log stream", "pass --stream")?;
+            let streams = cloudwatch::log_streams(ctx, &group)?;
+            let now = cloudwatch::now_millis();
+            let mut labels = vec!["All streams".to_string()];
+            labels.extend(streams.iter().map(|stream| stream.label(now)));
+            let selection = Select::with_theme(&*ctx.theme())
+                .with_prompt("Log stream (most recent first)")
+                .items(&labels)
+                .default(0)
+                .interact()?;
+            selection
+                .checked_sub(1)
+                .map(|index| streams[index].name.clone())
+        }
+    };
+
+    ctx.print_header(&format!(
+        "Logs in {}{}",
+        group,
+        stream
+            .as_ref()
+            .map(|stream| format!(" / {}", stream))
+            .unwrap_or_default()
+    ));
+    cloudwatch::tail(ctx, &group, stream.as_deref(), options)
+}
+
+/// Pick the cluster and service again
+pub fn select(ctx: &AppContext, target: &Target) -> Result<()> {
+    ensure_aws()?;
+    let scope = selection::reselect(ctx, target)?;
+    ctx.print_success(&format!(
+        "✓ Using cluster {}{}",
+        scope.cluster,
+        scope
+            .service
+            .map(|service| format!(", service {}", service))
+            .unwrap_or_default()
+    ));
+    Ok(())
 }
 
 /// Execute a command in an ECS container
@@ -33,16 +284,12 @@ pub fn ecs_exec(
     task: &str,
     container: Option<&str>,
 ) -> Result<()> {
-    if !devkit_core::cmd_exists("aws") {
-        return Err(anyhow!(
-            "AWS CLI not found. Install from: https://aws.amazon.com/cli/"
-        ));
-    }
+    ensure_aws()?;
 
     // Check for Session Manager plugin
     let session_manager_check = std::process::Command::new("session-manager-plugin")
         .arg("--version")
-        .output();
+        .logged_output();
 
     if session_manager_check.is_err() {
         return Err(anyhow!(
@@ -71,11 +318,12 @@ pub fn ecs_exec(
         args.push(c);
     }
 
-    let code = CmdBuilder::new("aws")
+    let code = aws(ctx)
         .args(args)
-        .cwd(&ctx.repo)
-        .inherit_io()
-        .run()?;
+        .current_dir(&ctx.repo)
+        .logged_status()?
+        .code()
+        .unwrap_or(-1);
 
     // 130 = SIGINT (Ctrl+D), 254 = normal exit from exec session
     if code != 0 && code != 130 && code != 254 {
@@ -87,11 +335,7 @@ pub fn ecs_exec(
 
 /// List tasks in an ECS cluster
 pub fn ecs_list_tasks(ctx: &AppContext, cluster: &str, service: Option<&str>) -> Result<()> {
-    if !devkit_core::cmd_exists("aws") {
-        return Err(anyhow!(
-            "AWS CLI not found. Install from: https://aws.amazon.com/cli/"
-        ));
-    }
+    ensure_aws()?;
 
     ctx.print_header(&format!("Listing tasks in {}", cluster));
 
@@ -107,9 +351,12 @@ pub fn ecs_list_tasks(ctx: &AppContext, cluster: &str, service: Option<&str>) ->
         args.push(svc.to_string());
     }
 
-    let code = CmdBuilder::new("aws").args(&args).cwd(&ctx.repo).run()?;
+    let status = aws(ctx)
+        .args(&args)
+        .current_dir(&ctx.repo)
+        .logged_status()?;
 
-    if code != 0 {
+    if !status.success() {
         return Err(anyhow!("Failed to list ECS tasks"));
     }
 
@@ -118,16 +365,12 @@ pub fn ecs_list_tasks(ctx: &AppContext, cluster: &str, service: Option<&str>) ->
 
 /// View logs for an ECS task
 pub fn ecs_logs(ctx: &AppContext, log_group: &str, task_id: &str) -> Result<()> {
-    if !devkit_core::cmd_exists("aws") {
-        return Err(anyhow!(
-            "AWS CLI not found. Install from: https://aws.amazon.com/cli/"
-        ));
-    }
+    ensure_aws()?;
 
     ctx.print_header(&format!("Viewing logs for task {}", task_id));
 
     // Stream logs from CloudWatch
-    let code = CmdBuilder::new("aws")
+    let status = aws(ctx)
         .args([
             "logs",
             "tail",
@@ -136,11 +379,10 @@ pub fn ecs_logs(ctx: &AppContext, log_group: &str, task_id: &str) -> Result<()>
             "--filter-pattern",
             task_id,
         ])
-        .cwd(&ctx.repo)
-        .inherit_io()
-        .run()?;
+        .current_dir(&ctx.repo)
+        .logged_status()?;
 
-    if code != 0 && code != 130 {
+    if !status.success() && status.code() != Some(130) {
         return Err(anyhow!("Failed to view logs"));
     }
 
@@ -0,0 +1,242 @@
+//! Clusters, services and tasks, queried with the AWS CLI
+//!
+//! Queries use the `[aws]` profile and region from `.dev/config.toml` when set.
+
+use anyhow::{bail, Context, Result};
+use devkit_core::oplog::LoggedCommand;
+use devkit_core::AppContext;
+use serde_json::Value;
+use std::process::Command;
+
+/// A running (or recently stopped) task
+#[derive(Debug, Clone, PartialEq)]
+pub struct Task {
+    /// Task ID (last part of the ARN)
+    pub id: String,
+    pub status: String,
+    pub started: Option<String>,
+    pub task_definition: String,
+    pub containers: Vec<String>,
+}
+
+impl Task {
+    /// "abc123  RUNNING  since 2024-05-01T10:00:00", for pickers
+    pub fn label(&self) -> String {
+        match &self.started {
+            Some(started) => format!("{}  {}  since {}", self.id, self.status, started),
+            None => format!("{}  {}", self.id, self.status),
+        }
+    }
+}
+
+/// Where a container's awslogs driver sends its output
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogConfig {
+    pub group: String,
+    pub stream_prefix: Option<String>,
+}
+
+impl LogConfig {
+    /// Stream of one task's container: `<prefix>/<container>/<task id>`
+    pub fn stream(&self, container: &str, task_id: &str) -> Option<String> {
+        self.stream_prefix
+            .as_ref()
+            .map(|prefix| format!("{}/{}/{}", prefix, container, task_id))
+    }
+}
+
+/// An `aws` command with the `[aws]` profile and region
+pub fn aws(ctx: &AppContext) -> Command {
+    let config = &ctx.config.global.aws;
+    let mut cmd = Command::new("aws");
+    if let Some(profile) = &config.profile {
+        cmd.env("AWS_PROFILE", profile);
+    }
+    if let Some(region) = &config.region {
+        cmd.env("AWS_REGION", region)
+            .env("AWS_DEFAULT_REGION", region);
+    }
+    cmd
+}
+
+/// Run an `aws` query with `--output json` and parse the result
+pub(crate) fn aws_json(ctx: &AppContext, args: &[&str]) -> Result<Value> {
+    let output = aws(ctx)
+        .args(args)
+        .args(["--output", "json"])
+        .logged_output()
+        .context("Failed to run aws")?;
+    if !output.status.success() {
+        bail!(
+            "aws {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    serde_json::from_slice(&output.stdout).context("Unexpected output from aws")
+}
+
+/// Name or ID at the end of an ARN (`arn:aws:ecs:...:task/cluster/abc123` -> `abc123`)
+pub fn arn_name(arn: &str) -> &str {
+    arn.rsplit('/').next().unwrap_or(arn)
+}
+
+/// Names at the end of the ARNs in `doc[key]`, sorted
+fn arn_names(doc: &Value, key: &str) -> Vec<String> {
+    let mut names: Vec<String> = doc[key]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .map(|arn| arn_name(arn).to_string())
+        .collect();
+    names.sort();
+    names
+}
+
+pub fn clusters(ctx: &AppContext) -> Result<Vec<String>> {
+    let doc = aws_json(ctx, &["ecs", "list-clusters"])?;
+    Ok(arn_names(&doc, "clusterArns"))
+}
+
+pub fn services(ctx: &AppContext, cluster: &str) -> Result<Vec<String>> {
+    let doc = aws_json(ctx, &["ecs", "list-services", "--cluster", cluster])?;
+    Ok(arn_names(&doc, "serviceArns"))
+}
+
+/// Tasks in the cluster, only the service's when one is given
+pub fn tasks(ctx: &AppContext, cluster: &str, service: Option<&str>) -> Result<Vec<Task>> {
+    let mut args = vec!["ecs", "list-tasks", "--cluster", cluster];
+    if let Some(service) = service {
+        args.extend(["--service-name", service]);
+    }
+    let arns = arn_names(&aws_json(ctx, &args)?, "taskArns");
+    if arns.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut args = vec!["ecs", "describe-tasks", "--cluster", cluster, "--tasks"];
+    args.extend(arns.iter().map(String::as_str));
+    Ok(parse_tasks(&aws_json(ctx, &args)?))
+}
+
+/// `aws ecs describe-tasks` output, sorted by ID
+fn parse_tasks(doc: &Value) -> Vec<Task> {
+    let mut tasks: Vec<Task> = doc["tasks"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|task| Task {
+            id: arn_name(task["taskArn"].as_str().unwrap_or_default()).to_string(),
+            status: task["lastStatus"].as_str().unwrap_or("UNKNOWN").to_string(),
+            started: task["startedAt"].as_str().map(String::from),
+            task_definition: task["taskDefinitionArn"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+            containers: task["containers"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|container| container["name"].as_str().map(String::from))
+                .collect(),
+        })
+        .collect();
+    tasks.sort_by(|a, b| a.id.cmp(&b.id));
+    tasks
+}
+
+/// The container's awslogs configuration from its task definition
+pub fn log_config(ctx: &AppContext, task_definition: &str, container: &str) -> Result<LogConfig> {
+    let doc = aws_json(
+        ctx,
+        &[
+            "ecs",
+            "describe-task-definition",
+            "--task-definition",
+            task_definition,
+        ],
+    )?;
+    parse_log_config(&doc, container).with_context(|| {
+        format!(
+            "Container {} doesn't log to CloudWatch (no awslogs driver in {})",
+            container,
+            arn_name(task_definition)
+        )
+    })
+}
+
+/// `aws ecs describe-task-definition` output
+fn parse_log_config(doc: &Value, container: &str) -> Option<LogConfig> {
+    let options = doc["taskDefinition"]["containerDefinitions"]
+        .as_array()?
+        .iter()
+        .find(|definition| definition["name"] == container)?
+        .get("logConfiguration")
+        .filter(|config| config["logDriver"] == "awslogs")?
+        .get("options")?;
+    Some(LogConfig {
+        group: options["awslogs-group"].as_str()?.to_string(),
+        stream_prefix: options["awslogs-stream-prefix"].as_str().map(String::from),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_tasks() {
+        let doc = json!({
+            "tasks": [
+                {
+                    "taskArn": "arn:aws:ecs:eu-west-1:123:task/prod/f00d",
+                    "lastStatus": "RUNNING",
+                    "startedAt": "2024-05-01T10:00:00+00:00",
+                    "taskDefinitionArn": "arn:aws:ecs:eu-west-1:123:task-definition/api:7",
+                    "containers": [{ "name": "api" }, { "name": "envoy" }]
+                },
+                {
+                    "taskArn": "arn:aws:ecs:eu-west-1:123:task/prod/beef",
+                    "lastStatus": "PROVISIONING",
+                    "taskDefinitionArn": "arn:aws:ecs:eu-west-1:123:task-definition/api:7",
+                    "containers": []
+                }
+            ]
+        });
+        let tasks = parse_tasks(&doc);
+        assert_eq!(tasks.len(), 2);
+        assert_eq!(tasks[0].label(), "beef  PROVISIONING");
+        assert_eq!(tasks[1].id, "f00d");
+        assert_eq!(tasks[1].containers, ["api", "envoy"]);
+    }
+
+    #[test]
+    fn test_parse_log_config() {
+        let doc = json!({
+            "taskDefinition": {
+                "containerDefinitions": [
+                    { "name": "envoy" },
+                    {
+                        "name": "api",
+                        "logConfiguration": {
+                            "logDriver": "awslogs",
+                            "options": {
+                                "awslogs-group": "/ecs/api",
+                                "awslogs-stream-prefix": "ecs"
+                            }
+                        }
+                    }
+                ]
+            }
+        });
+        let config = parse_log_config(&doc, "api").unwrap();
+        assert_eq!(config.group, "/ecs/api");
+        assert_eq!(
+            config.stream("api", "f00d").as_deref(),
+            Some("ecs/api/f00d")
+        );
+        assert_eq!(parse_log_config(&doc, "envoy"), None);
+    }
+}
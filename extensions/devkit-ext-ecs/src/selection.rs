@@ -0,0 +1,215 @@
+//! Cluster, service, task and container selection
+//!
+//! Anything not passed on the command line is picked from what the AWS CLI lists.
+//! Picked clusters, services and containers are saved in `.dev/ecs.json` and reused
+//! while they still exist, with `[ecs] cluster` and `service` as the fallback;
+//! `devkit ecs select` picks them again. Tasks come and go, so they're always picked
+//! fresh.
+
+use anyhow::{bail, Context, Result};
+use devkit_core::AppContext;
+use dialoguer::Select;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+use crate::resources::{self, Task};
+
+const SELECTION_FILE: &str = ".dev/ecs.json";
+
+/// Picks saved for this checkout
+#[derive(Debug, Serialize, Deserialize, Default, Clone, PartialEq)]
+pub struct Selection {
+    pub cluster: Option<String>,
+    pub service: Option<String>,
+    pub container: Option<String>,
+}
+
+impl Selection {
+    pub fn load(repo: &Path) -> Result<Self> {
+        let path = repo.join(SELECTION_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let contents = fs::read_to_string(&path)?;
+        serde_json::from_str(&contents)
+            .with_context(|| format!("Invalid ECS selection file: {}", path.display()))
+    }
+
+    pub fn save(&self, repo: &Path) -> Result<()> {
+        let path = repo.join(SELECTION_FILE);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// What was given on the command line
+#[derive(Debug, Default, Clone)]
+pub struct Target {
+    pub cluster: Option<String>,
+    pub service: Option<String>,
+    pub task: Option<String>,
+    pub container: Option<String>,
+}
+
+/// A cluster and, when it has any, one of its services
+#[derive(Debug)]
+pub struct Scope {
+    pub cluster: String,
+    pub service: Option<String>,
+}
+
+/// A task and the container to use in it
+#[derive(Debug)]
+pub struct Picked {
+    pub cluster: String,
+    pub task: Task,
+    pub container: String,
+}
+
+/// The given item, the first of `preferred` (the saved pick, then the configured
+/// default) that still exists, the only one, or one the user picks (`flag` is
+/// suggested when there's no terminal to ask on)
+fn pick(
+    ctx: &AppContext,
+    what: &str,
+    flag: &str,
+    items: &[String],
+    given: Option<&str>,
+    preferred: &[Option<&str>],
+) -> Result<String> {
+    if let Some(given) = given {
+        return Ok(given.to_string());
+    }
+    if let Some(preferred) = preferred
+        .iter()
+        .flatten()
+        .find(|preferred| items.iter().any(|item| item == *preferred))
+    {
+        return Ok(preferred.to_string());
+    }
+    match items {
+        [] => bail!("No {}s found", what),
+        [only] => return Ok(only.clone()),
+        _ => {}
+    }
+
+    ctx.require_interactive(&format!("Choosing a {}", what), &format!("pass {}", flag))?;
+    let selection = Select::with_theme(&*ctx.theme())
+        .with_prompt(format!("ECS {}", what))
+        .items(items)
+        .default(0)
+        .interact()?;
+    Ok(items[selection].clone())
+}
+
+/// Resolve the cluster and service, saving them for next time
+pub fn scope(ctx: &AppContext, target: &Target) -> Result<Scope> {
+    let mut saved = Selection::load(&ctx.repo)?;
+    let config = &ctx.config.global.ecs;
+
+    let clusters = match &target.cluster {
+        Some(_) => Vec::new(),
+        None => resources::clusters(ctx)?,
+    };
+    let cluster = pick(
+        ctx,
+        "cluster",
+        "--cluster",
+        &clusters,
+        target.cluster.as_deref(),
+        &[saved.cluster.as_deref(), config.cluster.as_deref()],
+    )?;
+    if saved.cluster.as_deref() != Some(cluster.as_str()) {
+        saved = Selection {
+            cluster: Some(cluster.clone()),
+            ..Default::default()
+        };
+    }
+
+    // Standalone tasks run outside any service
+    let services = match &target.service {
+        Some(_) => Vec::new(),
+        None => resources::services(ctx, &cluster)?,
+    };
+    let service = if services.is_empty() && target.service.is_none() {
+        None
+    } else {
+        Some(pick(
+            ctx,
+            "service",
+            "--service",
+            &services,
+            target.service.as_deref(),
+            &[saved.service.as_deref(), config.service.as_deref()],
+        )?)
+    };
+    saved.service = service.clone();
+
+    saved.save(&ctx.repo)?;
+    Ok(Scope { cluster, service })
+}
+
+/// Resolve a task and container to exec into or follow logs of
+pub fn task(ctx: &AppContext, target: &Target) -> Result<Picked> {
+    let scope = scope(ctx, target)?;
+    let tasks = resources::tasks(ctx, &scope.cluster, scope.service.as_deref())?;
+    if tasks.is_empty() {
+        bail!(
+            "No tasks running in {}{}",
+            scope.cluster,
+            scope
+                .service
+                .as_ref()
+                .map(|service| format!("/{}", service))
+                .unwrap_or_default()
+        );
+    }
+
+    let task = match &target.task {
+        Some(id) => tasks
+            .iter()
+            .find(|task| &task.id == id)
+            .cloned()
+            .with_context(|| format!("No task {} in {}", id, scope.cluster))?,
+        None if tasks.len() == 1 => tasks[0].clone(),
+        None => {
+            ctx.require_interactive("Choosing a task", "pass --task")?;
+            let labels: Vec<String> = tasks.iter().map(Task::label).collect();
+            let selection = Select::with_theme(&*ctx.theme())
+                .with_prompt("ECS task")
+                .items(&labels)
+                .default(0)
+                .interact()?;
+            tasks[selection].clone()
+        }
+    };
+
+    let mut saved = Selection::load(&ctx.repo)?;
+    let container = pick(
+        ctx,
+        "container",
+        "--container",
+        &task.containers,
+        target.container.as_deref(),
+        &[saved.container.as_deref()],
+    )?;
+    saved.container = Some(container.clone());
+    saved.save(&ctx.repo)?;
+
+    Ok(Picked {
+        cluster: scope.cluster,
+        task,
+        container,
+    })
+}
+
+/// Forget the saved picks and choose the cluster and service again
+pub fn reselect(ctx: &AppContext, target: &Target) -> Result<Scope> {
+    Selection::default().save(&ctx.repo)?;
+    scope(ctx, target)
+}
@@ -23,6 +23,10 @@ impl Extension for GitExtension {
         ctx.features.git
     }
 
+    fn unavailable_reason(&self, _ctx: &AppContext) -> Option<String> {
+        Some("not a git repository".to_string())
+    }
+
     fn menu_items(&self, _ctx: &AppContext) -> Vec<MenuItem> {
         vec![
             MenuItem {
@@ -2,6 +2,7 @@
 
 use anyhow::{anyhow, Result};
 use console::style;
+use devkit_core::hooks::run_hooks;
 use devkit_core::AppContext;
 use devkit_tasks::CmdBuilder;
 
@@ -74,6 +75,9 @@ pub fn create_release(ctx: &AppContext, opts: &ReleaseOptions) -> Result<()> {
         run_preflight_checks(ctx)?;
     }
 
+    let hook_env = [("DEVKIT_VERSION", tag.as_str())];
+    run_hooks(ctx, "pre_release", &hook_env)?;
+
     // Create annotated tag
     let tag_message = opts
         .message
@@ -95,6 +99,7 @@ pub fn create_release(ctx: &AppContext, opts: &ReleaseOptions) -> Result<()> {
         .run()?;
 
     ctx.print_success(&format!("Released {}!", tag));
+    run_hooks(ctx, "post_release", &hook_env)?;
 
     Ok(())
 }
@@ -0,0 +1,141 @@
+//! `devkit k8s` subcommand
+
+use anyhow::Result;
+use clap::{Args, Command, FromArgMatches, Subcommand};
+use devkit_core::AppContext;
+
+use crate::{
+    apply_manifests, cluster_status, diff_manifests, exec, helm_install, helm_rollback,
+    helm_upgrade, list_pods, list_services, logs, port_forward, scale, use_context, use_namespace,
+};
+
+#[derive(Args)]
+pub struct K8sArgs {
+    #[command(subcommand)]
+    action: Option<K8sAction>,
+}
+
+#[derive(Subcommand)]
+enum K8sAction {
+    /// Show cluster status (default)
+    Status,
+    /// List pods
+    Pods,
+    /// List services
+    Services,
+    /// Forward a local port to a pod
+    Forward {
+        local_port: u16,
+        /// Port on the pod (defaults to the local port)
+        remote_port: Option<u16>,
+        /// Pod to forward to (picked from a list when omitted)
+        #[arg(long, short)]
+        pod: Option<String>,
+        /// Keep forwarding in the background (see `devkit forward`)
+        #[arg(long, short)]
+        background: bool,
+    },
+    /// Show pod logs
+    Logs {
+        /// Pod (picked from a list when omitted)
+        pod: Option<String>,
+        /// Container, for pods with several
+        #[arg(long, short)]
+        container: Option<String>,
+        /// Stream new log lines
+        #[arg(long, short)]
+        follow: bool,
+    },
+    /// Run a command in a pod, or open a shell
+    Exec {
+        /// Pod (picked from a list when omitted)
+        pod: Option<String>,
+        /// Container, for pods with several
+        #[arg(long, short)]
+        container: Option<String>,
+        /// Command to run (after `--`; default: a shell)
+        #[arg(last = true)]
+        command: Vec<String>,
+    },
+    /// Scale a deployment
+    Scale { deployment: String, replicas: u32 },
+    /// Show what applying [k8s] manifests would change (server-side)
+    Diff,
+    /// Diff [k8s] manifests, then apply them after confirmation
+    Apply {
+        /// Skip the confirmation prompt
+        #[arg(long, short)]
+        yes: bool,
+    },
+    /// Install, upgrade or roll back a Helm release
+    Helm {
+        #[command(subcommand)]
+        action: HelmAction,
+    },
+    /// Switch the kubectl context for this checkout (pick from a list without a name)
+    Context { name: Option<String> },
+    /// Switch the namespace for this checkout (pick from a list without a name)
+    Namespace { name: Option<String> },
+}
+
+#[derive(Subcommand)]
+enum HelmAction {
+    /// Install a chart's release (pick the chart when omitted)
+    Install { release: Option<String> },
+    /// Upgrade a chart's release, installing it if needed
+    Upgrade { release: Option<String> },
+    /// Roll a release back (to the previous revision by default)
+    Rollback {
+        release: Option<String>,
+        revision: Option<u32>,
+    },
+}
+
+pub fn command() -> Command {
+    K8sArgs::augment_args(Command::new("k8s").about("Kubernetes operations (requires kubectl)"))
+}
+
+pub fn handle(ctx: &AppContext, matches: &clap::ArgMatches) -> Result<()> {
+    let args = K8sArgs::from_arg_matches(matches)?;
+
+    match args.action {
+        Some(K8sAction::Status) | None => cluster_status(ctx),
+        Some(K8sAction::Pods) => list_pods(ctx),
+        Some(K8sAction::Services) => list_services(ctx),
+        Some(K8sAction::Forward {
+            local_port,
+            remote_port,
+            pod,
+            background,
+        }) => port_forward(
+            ctx,
+            pod,
+            local_port,
+            remote_port.unwrap_or(local_port),
+            background,
+        ),
+        Some(K8sAction::Logs {
+            pod,
+            container,
+            follow,
+        }) => logs(ctx, pod, container, follow),
+        Some(K8sAction::Exec {
+            pod,
+            container,
+            command,
+        }) => exec(ctx, pod, container, &command),
+        Some(K8sAction::Scale {
+            deployment,
+            replicas,
+        }) => scale(ctx, &deployment, replicas),
+        Some(K8sAction::Diff) => diff_manifests(ctx).map(|_| ()),
+        Some(K8sAction::Apply { yes }) => apply_manifests(ctx, yes),
+        Some(K8sAction::Helm { action }) => match action {
+            HelmAction::Install { release } => helm_install(ctx, release),
+            HelmAction::Upgrade { release } => helm_upgrade(ctx, release),
+            HelmAction::Rollback { release, revision } => helm_rollback(ctx, release, revision),
+        },
+        Some(K8sAction::Context { name }) => use_context(ctx, name),
+        Some(K8sAction::Namespace { name }) => use_namespace(ctx, name),
+    }
+}
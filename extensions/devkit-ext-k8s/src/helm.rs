@@ -0,0 +1,151 @@
+//! Helm charts
+//!
+//! Charts are found by their `Chart.yaml` anywhere in the repo, skipping dependency
+//! and build directories and the subcharts inside a chart, and are released under
+//! their directory's name. Values files come from the active environment's
+//! `helm_values` (relative to the chart), or `values-<env>.yaml` when the chart has
+//! one:
+//!
+//! ```toml
+//! [environments.staging]
+//! helm_values = ["values-staging.yaml", "values-eu.yaml"]
+//! ```
+
+use anyhow::{bail, Context, Result};
+use devkit_core::oplog::LoggedCommand;
+use devkit_core::AppContext;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::session::Session;
+
+/// Directories never searched for charts
+const SKIP_DIRS: &[&str] = &["node_modules", "target", "vendor", "dist", "build"];
+
+/// How deep below the repo root charts are looked for
+const MAX_DEPTH: usize = 5;
+
+/// A chart in the repo
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chart {
+    /// Release name: the chart directory's name
+    pub release: String,
+    pub dir: PathBuf,
+}
+
+/// Charts in the repo, sorted by release name
+pub fn charts(repo: &Path) -> Vec<Chart> {
+    let mut charts = Vec::new();
+    find_charts(repo, 0, &mut charts);
+    charts.sort_by(|a, b| a.release.cmp(&b.release));
+    charts
+}
+
+fn find_charts(dir: &Path, depth: usize, charts: &mut Vec<Chart>) {
+    if dir.join("Chart.yaml").exists() {
+        if let Some(name) = dir.file_name() {
+            charts.push(Chart {
+                release: name.to_string_lossy().to_string(),
+                dir: dir.to_path_buf(),
+            });
+        }
+        return;
+    }
+    if depth == MAX_DEPTH {
+        return;
+    }
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.starts_with('.') || SKIP_DIRS.contains(&name.as_str()) {
+            continue;
+        }
+        if entry.file_type().is_ok_and(|kind| kind.is_dir()) {
+            find_charts(&entry.path(), depth + 1, charts);
+        }
+    }
+}
+
+/// Values files for the active environment
+pub fn values_files(ctx: &AppContext, chart: &Path) -> Vec<PathBuf> {
+    let environments = &ctx.config.global.environments;
+    match environments.current_settings() {
+        Some(settings) if !settings.helm_values.is_empty() => settings
+            .helm_values
+            .iter()
+            .map(|file| chart.join(file))
+            .collect(),
+        _ => {
+            let file = chart.join(format!("values-{}.yaml", environments.current()));
+            if file.exists() {
+                vec![file]
+            } else {
+                Vec::new()
+            }
+        }
+    }
+}
+
+/// `-f <file>` for each values file
+pub fn values_args(ctx: &AppContext, chart: &Path) -> Vec<String> {
+    values_files(ctx, chart)
+        .into_iter()
+        .flat_map(|file| ["-f".to_string(), file.to_string_lossy().to_string()])
+        .collect()
+}
+
+/// Releases installed in the session's namespace
+pub fn releases(session: &Session) -> Result<Vec<String>> {
+    let output = session
+        .helm()
+        .args(["list", "-q"])
+        .logged_output()
+        .context("Failed to run helm list")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!("{}", stderr.lines().next().unwrap_or("helm list failed"));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_charts() {
+        let repo = std::env::temp_dir().join(format!("devkit-helm-{}", std::process::id()));
+        for dir in [
+            "deploy/charts/api",
+            "deploy/charts/api/charts/redis",
+            "web/node_modules/some-chart",
+            "infra/worker",
+        ] {
+            fs::create_dir_all(repo.join(dir)).unwrap();
+            fs::write(repo.join(dir).join("Chart.yaml"), "").unwrap();
+        }
+
+        assert_eq!(
+            charts(&repo),
+            [
+                Chart {
+                    release: "api".to_string(),
+                    dir: repo.join("deploy/charts/api"),
+                },
+                Chart {
+                    release: "worker".to_string(),
+                    dir: repo.join("infra/worker"),
+                },
+            ]
+        );
+
+        fs::remove_dir_all(&repo).ok();
+    }
+}
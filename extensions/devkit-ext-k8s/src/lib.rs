@@ -1,7 +1,21 @@
 //! Kubernetes operations extension
+//!
+//! kubectl runs against the context and namespace picked with `devkit k8s context` and
+//! `devkit k8s namespace`, falling back to the active environment's `k8s_context`; see
+//! [`session`].
 
-use anyhow::{Context, Result};
+mod cli;
+pub mod helm;
+pub mod manifests;
+pub mod pods;
+pub mod session;
+
+use anyhow::{bail, Context, Result};
+use devkit_core::forwards;
+use devkit_core::oplog::LoggedCommand;
 use devkit_core::{AppContext, Extension, MenuItem};
+use dialoguer::Select;
+use session::Session;
 use std::process::Command;
 
 pub struct K8sExtension;
@@ -15,8 +29,12 @@ impl Extension for K8sExtension {
         cmd_exists("kubectl")
     }
 
-    fn menu_items(&self, _ctx: &AppContext) -> Vec<MenuItem> {
-        vec![
+    fn unavailable_reason(&self, _ctx: &AppContext) -> Option<String> {
+        Some("kubectl not installed".to_string())
+    }
+
+    fn menu_items(&self, ctx: &AppContext) -> Vec<MenuItem> {
+        let mut items = vec![
             MenuItem {
                 label: "☸️  Show cluster status".to_string(),
                 group: None,
@@ -32,26 +50,81 @@ impl Extension for K8sExtension {
                 group: None,
                 handler: Box::new(|ctx| list_services(ctx).map_err(Into::into)),
             },
-        ]
+            MenuItem {
+                label: "🐚 Shell into pod".to_string(),
+                group: None,
+                handler: Box::new(|ctx| exec(ctx, None, None, &[]).map_err(Into::into)),
+            },
+            MenuItem {
+                label: "📜 Pod logs".to_string(),
+                group: None,
+                handler: Box::new(|ctx| logs(ctx, None, None, false).map_err(Into::into)),
+            },
+            MenuItem {
+                label: "🔀 Switch context".to_string(),
+                group: None,
+                handler: Box::new(|ctx| use_context(ctx, None).map_err(Into::into)),
+            },
+            MenuItem {
+                label: "🔀 Switch namespace".to_string(),
+                group: None,
+                handler: Box::new(|ctx| use_namespace(ctx, None).map_err(Into::into)),
+            },
+        ];
+
+        if cmd_exists("helm") && !helm::charts(&ctx.repo).is_empty() {
+            items.push(MenuItem {
+                label: "⎈  Helm upgrade".to_string(),
+                group: None,
+                handler: Box::new(|ctx| helm_upgrade(ctx, None).map_err(Into::into)),
+            });
+            items.push(MenuItem {
+                label: "⎈  Helm rollback".to_string(),
+                group: None,
+                handler: Box::new(|ctx| helm_rollback(ctx, None, None).map_err(Into::into)),
+            });
+        }
+        items
+    }
+
+    fn subcommands(&self) -> Vec<clap::Command> {
+        vec![cli::command()]
     }
+
+    fn handle_cli(
+        &self,
+        ctx: &AppContext,
+        name: &str,
+        matches: &clap::ArgMatches,
+    ) -> Option<devkit_core::Result<()>> {
+        (name == "k8s").then(|| cli::handle(ctx, matches).map_err(Into::into))
+    }
+}
+
+/// Print a header naming the context and namespace kubectl will use; returns them
+fn header(ctx: &AppContext, title: &str) -> Result<Session> {
+    let session = Session::resolve(ctx)?;
+    ctx.print_header(&format!("{} ({})", title, session.describe()));
+    Ok(session)
 }
 
 fn cmd_exists(cmd: &str) -> bool {
     Command::new("which")
         .arg(cmd)
-        .output()
+        .logged_output()
         .map(|output| output.status.success())
         .unwrap_or(false)
 }
 
 /// Show cluster status
 pub fn cluster_status(ctx: &AppContext) -> Result<()> {
-    ctx.print_header("Kubernetes Cluster Status");
+    let session = header(ctx, "Kubernetes Cluster Status")?;
     println!();
 
-    let output = Command::new("kubectl")
+    let output = session
+        .kubectl()
         .args(["cluster-info"])
-        .output()
+        .logged_output()
         .context("Failed to run kubectl")?;
 
     if output.status.success() {
@@ -69,12 +142,13 @@ pub fn cluster_status(ctx: &AppContext) -> Result<()> {
 
 /// List pods
 pub fn list_pods(ctx: &AppContext) -> Result<()> {
-    ctx.print_header("Pods");
+    let session = header(ctx, "Pods")?;
     println!();
 
-    let output = Command::new("kubectl")
+    let output = session
+        .kubectl()
         .args(["get", "pods", "-o", "wide"])
-        .output()
+        .logged_output()
         .context("Failed to run kubectl")?;
 
     println!("{}", String::from_utf8_lossy(&output.stdout));
@@ -84,12 +158,13 @@ pub fn list_pods(ctx: &AppContext) -> Result<()> {
 
 /// List services
 pub fn list_services(ctx: &AppContext) -> Result<()> {
-    ctx.print_header("Services");
+    let session = header(ctx, "Services")?;
     println!();
 
-    let output = Command::new("kubectl")
+    let output = session
+        .kubectl()
         .args(["get", "services"])
-        .output()
+        .logged_output()
         .context("Failed to run kubectl")?;
 
     println!("{}", String::from_utf8_lossy(&output.stdout));
@@ -97,22 +172,49 @@ pub fn list_services(ctx: &AppContext) -> Result<()> {
     Ok(())
 }
 
-/// Port forward to a pod
-pub fn port_forward(ctx: &AppContext, pod: &str, local_port: u16, remote_port: u16) -> Result<()> {
+/// Port forward to `pod`, or one the user picks; in the background, the forward is
+/// managed with `devkit forward`
+pub fn port_forward(
+    ctx: &AppContext,
+    pod: Option<String>,
+    local_port: u16,
+    remote_port: u16,
+    background: bool,
+) -> Result<()> {
+    let session = Session::resolve(ctx)?;
+    let target = pods::pick(ctx, &session, pod, None, false)?;
     ctx.print_info(&format!(
-        "Forwarding localhost:{} -> {}:{}",
-        local_port, pod, remote_port
+        "Forwarding localhost:{} -> {}:{} ({})",
+        local_port,
+        target.pod,
+        remote_port,
+        session.describe()
     ));
 
-    let status = Command::new("kubectl")
-        .args([
-            "port-forward",
-            pod,
-            &format!("{}:{}", local_port, remote_port),
-        ])
-        .status()
-        .context("Failed to run kubectl port-forward")?;
+    let mut cmd = session.kubectl();
+    cmd.args([
+        "port-forward",
+        &target.pod,
+        &format!("{}:{}", local_port, remote_port),
+    ]);
+    if background {
+        let forward = forwards::start(
+            &ctx.repo,
+            "k8s",
+            &format!("{}:{}", target.pod, remote_port),
+            &[local_port],
+            cmd,
+        )?;
+        ctx.print_success(&format!(
+            "✓ Forwarding in the background (pid {}); stop with `devkit forward stop {}`",
+            forward.pid, local_port
+        ));
+        return Ok(());
+    }
 
+    let status = cmd
+        .logged_status()
+        .context("Failed to run kubectl port-forward")?;
     if !status.success() {
         return Err(anyhow::anyhow!("Port forwarding failed"));
     }
@@ -120,18 +222,31 @@ pub fn port_forward(ctx: &AppContext, pod: &str, local_port: u16, remote_port: u
     Ok(())
 }
 
-/// Get logs from a pod
-pub fn logs(ctx: &AppContext, pod: &str, follow: bool) -> Result<()> {
-    ctx.print_info(&format!("Fetching logs from {}", pod));
+/// Get logs from `pod`, or one the user picks
+pub fn logs(
+    ctx: &AppContext,
+    pod: Option<String>,
+    container: Option<String>,
+    follow: bool,
+) -> Result<()> {
+    let session = Session::resolve(ctx)?;
+    let target = pods::pick(ctx, &session, pod, container, true)?;
+    ctx.print_info(&format!(
+        "Fetching logs from {} ({})",
+        target.pod,
+        session.describe()
+    ));
 
-    let mut args = vec!["logs", pod];
+    let mut args = vec!["logs", target.pod.as_str()];
+    args.extend(target.container_args());
     if follow {
         args.push("-f");
     }
 
-    let status = Command::new("kubectl")
+    let status = session
+        .kubectl()
         .args(&args)
-        .status()
+        .logged_status()
         .context("Failed to get logs")?;
 
     if !status.success() {
@@ -141,11 +256,57 @@ pub fn logs(ctx: &AppContext, pod: &str, follow: bool) -> Result<()> {
     Ok(())
 }
 
+/// Run `command` in `pod`, or one the user picks; a shell when `command` is empty
+pub fn exec(
+    ctx: &AppContext,
+    pod: Option<String>,
+    container: Option<String>,
+    command: &[String],
+) -> Result<()> {
+    let session = Session::resolve(ctx)?;
+    let target = pods::pick(ctx, &session, pod, container, true)?;
+    ctx.print_info(&format!(
+        "Connecting to {} ({})",
+        target.pod,
+        session.describe()
+    ));
+
+    let mut cmd = session.kubectl();
+    cmd.args(["exec", "-it", &target.pod])
+        .args(target.container_args())
+        .arg("--");
+    if command.is_empty() {
+        // Not every image has bash
+        cmd.args([
+            "sh",
+            "-c",
+            "command -v bash >/dev/null && exec bash || exec sh",
+        ]);
+    } else {
+        cmd.args(command);
+    }
+
+    let status = cmd.logged_status().context("Failed to run kubectl exec")?;
+    // 130: the shell's last command was interrupted, which is how shells usually end
+    if !status.success() && status.code() != Some(130) {
+        bail!("kubectl exec exited with {}", status);
+    }
+
+    Ok(())
+}
+
 /// Scale a deployment
 pub fn scale(ctx: &AppContext, deployment: &str, replicas: u32) -> Result<()> {
-    ctx.print_info(&format!("Scaling {} to {} replicas", deployment, replicas));
+    let session = Session::resolve(ctx)?;
+    ctx.print_info(&format!(
+        "Scaling {} to {} replicas ({})",
+        deployment,
+        replicas,
+        session.describe()
+    ));
 
-    let output = Command::new("kubectl")
+    let output = session
+        .kubectl()
         .args([
             "scale",
             "deployment",
@@ -153,7 +314,7 @@ pub fn scale(ctx: &AppContext, deployment: &str, replicas: u32) -> Result<()> {
             "--replicas",
             &replicas.to_string(),
         ])
-        .output()
+        .logged_output()
         .context("Failed to scale deployment")?;
 
     if output.status.success() {
@@ -167,3 +328,229 @@ pub fn scale(ctx: &AppContext, deployment: &str, replicas: u32) -> Result<()> {
 
     Ok(())
 }
+
+/// Show what applying the manifests would change; returns whether anything would
+pub fn diff_manifests(ctx: &AppContext) -> Result<bool> {
+    let session = header(ctx, "Manifest diff")?;
+    let sources = manifests::sources(ctx)?;
+    manifests::require_tools(&sources)?;
+
+    let mut changed = false;
+    for source in &sources {
+        ctx.print_info(&format!("── {}", source.describe(&ctx.repo)));
+        changed |= manifests::diff(ctx, &session, source)?;
+    }
+    if !changed {
+        ctx.print_success("✓ No changes");
+    }
+    Ok(changed)
+}
+
+/// Diff the manifests, then apply the ones with changes once confirmed (or `yes`)
+pub fn apply_manifests(ctx: &AppContext, yes: bool) -> Result<()> {
+    let session = header(ctx, "Apply manifests")?;
+    let sources = manifests::sources(ctx)?;
+    manifests::require_tools(&sources)?;
+
+    let mut changed = Vec::new();
+    for source in &sources {
+        ctx.print_info(&format!("── {}", source.describe(&ctx.repo)));
+        if manifests::diff(ctx, &session, source)? {
+            changed.push(source);
+        }
+    }
+    if changed.is_empty() {
+        ctx.print_success("✓ No changes to apply");
+        return Ok(());
+    }
+
+    if !yes {
+        ctx.require_interactive("Confirming the apply", "pass --yes")?;
+        let prompt = format!("Apply these changes ({})?", session.describe());
+        if !ctx.confirm(&prompt, false)? {
+            ctx.print_info("Cancelled");
+            return Ok(());
+        }
+    }
+
+    for source in changed {
+        ctx.print_info(&format!("Applying {}", source.describe(&ctx.repo)));
+        manifests::apply(ctx, &session, source)?;
+    }
+    ctx.print_success("✓ Manifests applied");
+    Ok(())
+}
+
+/// Install a chart's release for the first time
+pub fn helm_install(ctx: &AppContext, release: Option<String>) -> Result<()> {
+    helm_release(ctx, release, &["install"], "installed")
+}
+
+/// Upgrade a chart's release, installing it if needed
+pub fn helm_upgrade(ctx: &AppContext, release: Option<String>) -> Result<()> {
+    helm_release(ctx, release, &["upgrade", "--install"], "upgraded")
+}
+
+/// Run `helm <args> <release> <chart>` with the environment's values files for
+/// `release`, or a chart the user picks
+fn helm_release(
+    ctx: &AppContext,
+    release: Option<String>,
+    args: &[&str],
+    done: &str,
+) -> Result<()> {
+    require_helm()?;
+    let charts = helm::charts(&ctx.repo);
+    let names: Vec<String> = charts.iter().map(|chart| chart.release.clone()).collect();
+    let release = pick(ctx, "release", &names, release, None)?;
+    let Some(chart) = charts.iter().find(|chart| chart.release == release) else {
+        bail!("No chart for release '{}'", release);
+    };
+
+    let session = Session::resolve(ctx)?;
+    let values = helm::values_files(ctx, &chart.dir);
+    ctx.print_info(&format!(
+        "helm {} {} ({}; values: {})",
+        args[0],
+        release,
+        session.describe(),
+        if values.is_empty() {
+            "chart defaults".to_string()
+        } else {
+            values
+                .iter()
+                .map(|file| {
+                    file.strip_prefix(&chart.dir)
+                        .unwrap_or(file)
+                        .display()
+                        .to_string()
+                })
+                .collect::<Vec<_>>()
+                .join(", ")
+        }
+    ));
+
+    let status = session
+        .helm()
+        .args(args)
+        .arg(&release)
+        .arg(&chart.dir)
+        .args(helm::values_args(ctx, &chart.dir))
+        .logged_status()
+        .context("Failed to run helm")?;
+    if !status.success() {
+        bail!("helm {} failed ({})", args[0], status);
+    }
+
+    ctx.print_success(&format!("✓ {} {}", release, done));
+    Ok(())
+}
+
+/// Roll `release`, or one the user picks, back to `revision` (default: the previous one)
+pub fn helm_rollback(
+    ctx: &AppContext,
+    release: Option<String>,
+    revision: Option<u32>,
+) -> Result<()> {
+    require_helm()?;
+    let session = Session::resolve(ctx)?;
+    let releases = helm::releases(&session)?;
+    let release = pick(ctx, "release", &releases, release, None)?;
+
+    ctx.print_info(&format!(
+        "Rolling back {} ({})",
+        release,
+        session.describe()
+    ));
+    let mut cmd = session.helm();
+    cmd.args(["rollback", &release]);
+    if let Some(revision) = revision {
+        cmd.arg(revision.to_string());
+    }
+    let status = cmd.logged_status().context("Failed to run helm")?;
+    if !status.success() {
+        bail!("helm rollback failed ({})", status);
+    }
+
+    ctx.print_success(&format!("✓ {} rolled back", release));
+    Ok(())
+}
+
+fn require_helm() -> Result<()> {
+    if !cmd_exists("helm") {
+        bail!("helm not found. Install: https://helm.sh/docs/intro/install/");
+    }
+    Ok(())
+}
+
+/// Switch the context kubectl uses for this checkout: `name`, or one the user picks.
+/// The namespace goes back to the context's own.
+pub fn use_context(ctx: &AppContext, name: Option<String>) -> Result<()> {
+    let contexts = session::contexts()?;
+    let current = Session::resolve(ctx)?;
+    let context = pick(ctx, "context", &contexts, name, current.context.as_deref())?;
+
+    let session = Session {
+        context: Some(context),
+        namespace: None,
+    };
+    session.save(&ctx.repo)?;
+    ctx.print_success(&format!("✓ Using {}", session.describe()));
+    Ok(())
+}
+
+/// Switch the namespace kubectl uses for this checkout: `name`, or one the user picks
+pub fn use_namespace(ctx: &AppContext, name: Option<String>) -> Result<()> {
+    let mut session = Session::resolve(ctx)?;
+    let namespaces = session::namespaces(&session)?;
+    let namespace = pick(
+        ctx,
+        "namespace",
+        &namespaces,
+        name,
+        session.namespace.as_deref(),
+    )?;
+
+    session.namespace = Some(namespace);
+    session.save(&ctx.repo)?;
+    ctx.print_success(&format!("✓ Using {}", session.describe()));
+    Ok(())
+}
+
+/// `given` if it's one of `items`, otherwise one the user picks with `current` selected
+fn pick(
+    ctx: &AppContext,
+    what: &str,
+    items: &[String],
+    given: Option<String>,
+    current: Option<&str>,
+) -> Result<String> {
+    if let Some(given) = given {
+        if !items.contains(&given) {
+            bail!(
+                "No {} named '{}' (available: {})",
+                what,
+                given,
+                items.join(", ")
+            );
+        }
+        return Ok(given);
+    }
+    if items.is_empty() {
+        bail!("No {}s found", what);
+    }
+
+    ctx.require_interactive(
+        &format!("Choosing a {}", what),
+        &format!("pass the {} name", what),
+    )?;
+    let default = current
+        .and_then(|current| items.iter().position(|item| item == current))
+        .unwrap_or(0);
+    let selection = Select::with_theme(&*ctx.theme())
+        .with_prompt(format!("Kubernetes {}", what))
+        .items(items)
+        .default(default)
+        .interact()?;
+    Ok(items[selection].clone())
+}
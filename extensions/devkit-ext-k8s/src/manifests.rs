@@ -0,0 +1,240 @@
+//! Manifest diff and apply
+//!
+//! `[k8s] manifests` lists files and directories relative to the repo root (default:
+//! `k8s/` and `kubernetes/` when they exist):
+//!
+//! ```toml
+//! [k8s]
+//! manifests = ["deploy/base", "deploy/charts/api", "deploy/extra.yaml"]
+//! ```
+//!
+//! A directory with a kustomization file goes through `kubectl -k`, one with a
+//! `Chart.yaml` through helm (see [`crate::helm`] for release names and values files),
+//! and anything else through `kubectl -f`. Diffs are server-side, so they show what the
+//! API server would actually change.
+
+use anyhow::{anyhow, bail, Context, Result};
+use devkit_core::discovery_cache::cache_dir;
+use devkit_core::oplog::LoggedCommand;
+use devkit_core::AppContext;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::cmd_exists;
+use crate::helm::values_args;
+use crate::session::Session;
+
+/// Directories used when `[k8s] manifests` is empty
+const DEFAULT_DIRS: &[&str] = &["k8s", "kubernetes"];
+
+const KUSTOMIZATIONS: &[&str] = &["kustomization.yaml", "kustomization.yml", "Kustomization"];
+
+/// Something to diff and apply
+#[derive(Debug, PartialEq)]
+pub enum Source {
+    Kustomize(PathBuf),
+    Helm {
+        chart: PathBuf,
+        release: String,
+    },
+    /// A manifest file or a directory of them
+    Files(PathBuf),
+}
+
+impl Source {
+    fn detect(path: PathBuf) -> Self {
+        if path.is_dir() {
+            if KUSTOMIZATIONS.iter().any(|file| path.join(file).exists()) {
+                return Self::Kustomize(path);
+            }
+            if path.join("Chart.yaml").exists() {
+                let release = path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_else(|| "release".to_string());
+                return Self::Helm {
+                    chart: path,
+                    release,
+                };
+            }
+        }
+        Self::Files(path)
+    }
+
+    /// "deploy/base (kustomize)", for headers
+    pub fn describe(&self, repo: &Path) -> String {
+        let (path, kind) = match self {
+            Self::Kustomize(path) => (path, "kustomize"),
+            Self::Helm { chart, .. } => (chart, "helm"),
+            Self::Files(path) => (path, "manifests"),
+        };
+        format!(
+            "{} ({})",
+            path.strip_prefix(repo).unwrap_or(path).display(),
+            kind
+        )
+    }
+}
+
+/// The sources `[k8s] manifests` lists, or the default directories that exist
+pub fn sources(ctx: &AppContext) -> Result<Vec<Source>> {
+    let configured = &ctx.config.global.k8s.manifests;
+    let paths: Vec<PathBuf> = if configured.is_empty() {
+        DEFAULT_DIRS
+            .iter()
+            .map(|dir| ctx.repo.join(dir))
+            .filter(|dir| dir.is_dir())
+            .collect()
+    } else {
+        configured.iter().map(|path| ctx.repo.join(path)).collect()
+    };
+
+    if paths.is_empty() {
+        bail!(
+            "No manifests found. List them in .dev/config.toml:\n\n\
+             [k8s]\n\
+             manifests = [\"deploy/\"]"
+        );
+    }
+    if let Some(missing) = paths.iter().find(|path| !path.exists()) {
+        bail!("Manifest path not found: {}", missing.display());
+    }
+    Ok(paths.into_iter().map(Source::detect).collect())
+}
+
+/// Show what applying `source` would change; returns whether anything would
+pub fn diff(ctx: &AppContext, session: &Session, source: &Source) -> Result<bool> {
+    let mut cmd = session.kubectl();
+    cmd.args(["diff", "--server-side"]);
+    match source {
+        Source::Kustomize(dir) => {
+            cmd.arg("-k").arg(dir);
+        }
+        Source::Helm { chart, release } => {
+            cmd.arg("-f")
+                .arg(render_chart(ctx, session, chart, release)?);
+        }
+        Source::Files(path) => {
+            cmd.arg("-f").arg(path).arg("-R");
+        }
+    }
+
+    // kubectl diff exits 1 when there are differences
+    let status = cmd.logged_status().context("Failed to run kubectl diff")?;
+    match status.code() {
+        Some(0) => Ok(false),
+        Some(1) => Ok(true),
+        _ => Err(anyhow!("kubectl diff failed ({})", status)),
+    }
+}
+
+/// Apply `source` to the cluster
+pub fn apply(ctx: &AppContext, session: &Session, source: &Source) -> Result<()> {
+    let mut cmd = match source {
+        Source::Kustomize(dir) => {
+            let mut cmd = session.kubectl();
+            cmd.args(["apply", "--server-side", "-k"]).arg(dir);
+            cmd
+        }
+        Source::Helm { chart, release } => {
+            let mut cmd = session.helm();
+            cmd.args(["upgrade", "--install", release])
+                .arg(chart)
+                .args(values_args(ctx, chart));
+            cmd
+        }
+        Source::Files(path) => {
+            let mut cmd = session.kubectl();
+            cmd.args(["apply", "--server-side", "-f"])
+                .arg(path)
+                .arg("-R");
+            cmd
+        }
+    };
+
+    let status = cmd.logged_status().context("Failed to apply manifests")?;
+    if !status.success() {
+        bail!("Apply failed ({})", status);
+    }
+    Ok(())
+}
+
+/// `helm template` output for a chart, written to `.dev/cache/k8s/<release>.yaml`
+fn render_chart(
+    ctx: &AppContext,
+    session: &Session,
+    chart: &Path,
+    release: &str,
+) -> Result<PathBuf> {
+    let output = session
+        .helm()
+        .args(["template", release])
+        .arg(chart)
+        .args(values_args(ctx, chart))
+        .logged_output()
+        .context("Failed to run helm template")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!(
+            "{}",
+            stderr.lines().next().unwrap_or("helm template failed")
+        );
+    }
+
+    let dir = cache_dir(&ctx.repo)?.join("k8s");
+    fs::create_dir_all(&dir)?;
+    let path = dir.join(format!("{}.yaml", release));
+    fs::write(&path, &output.stdout)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(path)
+}
+
+/// Fail early when a chart needs helm and it isn't installed
+pub fn require_tools(sources: &[Source]) -> Result<()> {
+    let has_chart = sources
+        .iter()
+        .any(|source| matches!(source, Source::Helm { .. }));
+    if has_chart && !cmd_exists("helm") {
+        bail!("helm not found (needed for charts). Install: https://helm.sh/docs/intro/install/");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect() {
+        let repo = std::env::temp_dir().join(format!("devkit-k8s-{}", std::process::id()));
+        fs::create_dir_all(repo.join("deploy/base")).unwrap();
+        fs::create_dir_all(repo.join("deploy/charts/api")).unwrap();
+        fs::write(repo.join("deploy/base/kustomization.yaml"), "").unwrap();
+        fs::write(repo.join("deploy/charts/api/Chart.yaml"), "").unwrap();
+        fs::write(repo.join("deploy/extra.yaml"), "").unwrap();
+
+        assert_eq!(
+            Source::detect(repo.join("deploy/base")),
+            Source::Kustomize(repo.join("deploy/base"))
+        );
+        let chart = Source::detect(repo.join("deploy/charts/api"));
+        assert_eq!(
+            chart,
+            Source::Helm {
+                chart: repo.join("deploy/charts/api"),
+                release: "api".to_string(),
+            }
+        );
+        assert_eq!(chart.describe(&repo), "deploy/charts/api (helm)");
+        assert_eq!(
+            Source::detect(repo.join("deploy/extra.yaml")).describe(&repo),
+            "deploy/extra.yaml (manifests)"
+        );
+        assert_eq!(
+            Source::detect(repo.join("deploy")),
+            Source::Files(repo.join("deploy"))
+        );
+
+        fs::remove_dir_all(&repo).ok();
+    }
+}
@@ -0,0 +1,167 @@
+//! Pod and container selection
+//!
+//! `logs`, `exec` and `forward` take a pod name; without one, the user picks from
+//! `kubectl get pods` in the session's namespace. A picked pod with several containers
+//! asks which one too, unless `--container` names it.
+
+use anyhow::{bail, Context, Result};
+use devkit_core::oplog::LoggedCommand;
+use devkit_core::AppContext;
+use dialoguer::Select;
+use serde_json::Value;
+
+use crate::session::Session;
+
+/// A pod from `kubectl get pods -o json`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pod {
+    pub name: String,
+    /// Pending, Running, Succeeded, Failed or Unknown
+    pub phase: String,
+    pub containers: Vec<String>,
+}
+
+impl Pod {
+    /// "api-7d9f4 (Running, 2 containers)", for pickers
+    fn label(&self) -> String {
+        match self.containers.len() {
+            1 => format!("{} ({})", self.name, self.phase),
+            count => format!("{} ({}, {} containers)", self.name, self.phase, count),
+        }
+    }
+}
+
+/// A pod and, when there was a choice to make, the container to use
+#[derive(Debug)]
+pub struct Target {
+    pub pod: String,
+    pub container: Option<String>,
+}
+
+impl Target {
+    /// `-c <container>` for kubectl, when a container was picked
+    pub fn container_args(&self) -> Vec<&str> {
+        match &self.container {
+            Some(container) => vec!["-c", container],
+            None => Vec::new(),
+        }
+    }
+}
+
+/// Pods in the session's namespace
+pub fn pods(session: &Session) -> Result<Vec<Pod>> {
+    let output = session
+        .kubectl()
+        .args(["get", "pods", "-o", "json"])
+        .logged_output()
+        .context("Failed to run kubectl get pods")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!(
+            "{}",
+            stderr.lines().next().unwrap_or("kubectl get pods failed")
+        );
+    }
+    let doc: Value =
+        serde_json::from_slice(&output.stdout).context("Invalid kubectl get pods output")?;
+    Ok(parse_pods(&doc))
+}
+
+fn parse_pods(doc: &Value) -> Vec<Pod> {
+    let Some(items) = doc["items"].as_array() else {
+        return Vec::new();
+    };
+    items
+        .iter()
+        .filter_map(|item| {
+            Some(Pod {
+                name: item["metadata"]["name"].as_str()?.to_string(),
+                phase: item["status"]["phase"]
+                    .as_str()
+                    .unwrap_or("Unknown")
+                    .to_string(),
+                containers: item["spec"]["containers"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|container| container["name"].as_str())
+                    .map(str::to_string)
+                    .collect(),
+            })
+        })
+        .collect()
+}
+
+/// The given pod (and container) as is, or ones the user picks; `choose_container`
+/// is false for commands that work on the whole pod
+pub fn pick(
+    ctx: &AppContext,
+    session: &Session,
+    pod: Option<String>,
+    container: Option<String>,
+    choose_container: bool,
+) -> Result<Target> {
+    if let Some(pod) = pod {
+        return Ok(Target { pod, container });
+    }
+
+    ctx.require_interactive("Choosing a pod", "pass the pod name")?;
+    let pods = pods(session)?;
+    if pods.is_empty() {
+        bail!("No pods found ({})", session.describe());
+    }
+    let labels: Vec<String> = pods.iter().map(Pod::label).collect();
+    let selection = Select::with_theme(&*ctx.theme())
+        .with_prompt("Pod")
+        .items(&labels)
+        .default(0)
+        .interact()?;
+    let pod = &pods[selection];
+
+    let container = match container {
+        Some(container) => Some(container),
+        None if choose_container && pod.containers.len() > 1 => {
+            let selection = Select::with_theme(&*ctx.theme())
+                .with_prompt("Container")
+                .items(&pod.containers)
+                .default(0)
+                .interact()?;
+            Some(pod.containers[selection].clone())
+        }
+        None => None,
+    };
+
+    Ok(Target {
+        pod: pod.name.clone(),
+        container,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_pods() {
+        let doc = serde_json::json!({
+            "items": [
+                {
+                    "metadata": {"name": "api-7d9f4"},
+                    "spec": {"containers": [{"name": "api"}, {"name": "envoy"}]},
+                    "status": {"phase": "Running"}
+                },
+                {
+                    "metadata": {"name": "migrate-x2k"},
+                    "spec": {"containers": [{"name": "migrate"}]},
+                    "status": {}
+                }
+            ]
+        });
+        let pods = parse_pods(&doc);
+        assert_eq!(pods.len(), 2);
+        assert_eq!(pods[0].containers, ["api", "envoy"]);
+        assert_eq!(pods[0].label(), "api-7d9f4 (Running, 2 containers)");
+        assert_eq!(pods[1].label(), "migrate-x2k (Unknown)");
+        assert!(parse_pods(&serde_json::json!({})).is_empty());
+    }
+}
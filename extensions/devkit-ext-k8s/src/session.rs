@@ -0,0 +1,199 @@
+//! Context and namespace selection
+//!
+//! `devkit k8s context` and `devkit k8s namespace` save a choice in `.dev/k8s.json` so
+//! it sticks for this checkout. Without a saved context, the active environment's
+//! `k8s_context` is used, then kubectl's current context; without a saved namespace,
+//! the context's own.
+
+use anyhow::{bail, Context, Result};
+use devkit_core::oplog::LoggedCommand;
+use devkit_core::AppContext;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const SESSION_FILE: &str = ".dev/k8s.json";
+
+#[derive(Debug, Serialize, Deserialize, Default, Clone, PartialEq)]
+pub struct Session {
+    pub context: Option<String>,
+    pub namespace: Option<String>,
+}
+
+impl Session {
+    /// The saved selection for this checkout
+    pub fn load(repo: &Path) -> Result<Self> {
+        let path = session_path(repo);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let contents = fs::read_to_string(&path)?;
+        serde_json::from_str(&contents)
+            .with_context(|| format!("Invalid k8s session file: {}", path.display()))
+    }
+
+    pub fn save(&self, repo: &Path) -> Result<()> {
+        let path = session_path(repo);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// The saved selection with the environment's `k8s_context` filling the gap
+    pub fn resolve(ctx: &AppContext) -> Result<Self> {
+        let env_context = ctx
+            .config
+            .global
+            .environments
+            .current_settings()
+            .and_then(|settings| settings.k8s_context.as_deref());
+        Ok(Self::load(&ctx.repo)?.or(env_context))
+    }
+
+    fn or(self, env_context: Option<&str>) -> Self {
+        Self {
+            context: self.context.or_else(|| env_context.map(str::to_string)),
+            namespace: self.namespace,
+        }
+    }
+
+    /// A `kubectl` command using this context and namespace
+    pub fn kubectl(&self) -> Command {
+        let mut cmd = Command::new("kubectl");
+        if let Some(context) = &self.context {
+            cmd.args(["--context", context]);
+        }
+        if let Some(namespace) = &self.namespace {
+            cmd.args(["--namespace", namespace]);
+        }
+        cmd
+    }
+
+    /// A `helm` command using this context and namespace
+    pub fn helm(&self) -> Command {
+        let mut cmd = Command::new("helm");
+        if let Some(context) = &self.context {
+            cmd.args(["--kube-context", context]);
+        }
+        if let Some(namespace) = &self.namespace {
+            cmd.args(["--namespace", namespace]);
+        }
+        cmd
+    }
+
+    /// "context staging, namespace api", for display; asks kubectl for its current
+    /// context when none is set
+    pub fn describe(&self) -> String {
+        let context = self
+            .context
+            .clone()
+            .or_else(current_context)
+            .unwrap_or_else(|| "(none)".to_string());
+        format!(
+            "context {}, namespace {}",
+            context,
+            self.namespace.as_deref().unwrap_or("(default)")
+        )
+    }
+}
+
+/// kubectl's current context
+fn current_context() -> Option<String> {
+    let output = Command::new("kubectl")
+        .args(["config", "current-context"])
+        .logged_output()
+        .ok()?;
+    let context = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !context.is_empty()).then_some(context)
+}
+
+/// Contexts in the kubeconfig
+pub fn contexts() -> Result<Vec<String>> {
+    let output = Command::new("kubectl")
+        .args(["config", "get-contexts", "-o", "name"])
+        .logged_output()
+        .context("Failed to run kubectl config get-contexts")?;
+    Ok(names(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Namespaces in the cluster of the session's context
+pub fn namespaces(session: &Session) -> Result<Vec<String>> {
+    let output = Session {
+        namespace: None,
+        ..session.clone()
+    }
+    .kubectl()
+    .args(["get", "namespaces", "-o", "name"])
+    .logged_output()
+    .context("Failed to run kubectl get namespaces")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!(
+            "{}",
+            stderr
+                .lines()
+                .next()
+                .unwrap_or("kubectl get namespaces failed")
+        );
+    }
+    Ok(names(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// One name per line, without the `namespace/` prefix of `-o name`
+fn names(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| line.strip_prefix("namespace/").unwrap_or(line))
+        .map(str::to_string)
+        .collect()
+}
+
+fn session_path(repo: &Path) -> PathBuf {
+    repo.join(SESSION_FILE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_saved_context_overrides_environment() {
+        let saved = Session {
+            context: None,
+            namespace: Some("api".to_string()),
+        };
+        let session = saved.clone().or(Some("staging-cluster"));
+        assert_eq!(session.context.as_deref(), Some("staging-cluster"));
+        assert_eq!(session.describe(), "context staging-cluster, namespace api");
+
+        let saved = Session {
+            context: Some("kind-dev".to_string()),
+            ..saved
+        };
+        assert_eq!(
+            saved.or(Some("staging-cluster")).context.as_deref(),
+            Some("kind-dev")
+        );
+    }
+
+    #[test]
+    fn test_names() {
+        assert_eq!(
+            names("namespace/default\nnamespace/kube-system\n\n"),
+            ["default", "kube-system"]
+        );
+        assert_eq!(
+            names("kind-dev\narn:aws:eks:eu-west-1:123456789012:cluster/staging\n"),
+            [
+                "kind-dev",
+                "arn:aws:eks:eu-west-1:123456789012:cluster/staging"
+            ]
+        );
+    }
+}
@@ -0,0 +1,53 @@
+//! `devkit localstack` subcommand
+
+use anyhow::Result;
+use clap::{Args, Command, FromArgMatches, Subcommand};
+use devkit_core::AppContext;
+
+use crate::{down, print_env, seed, status, up, write_env};
+
+#[derive(Args)]
+pub struct LocalstackArgs {
+    #[command(subcommand)]
+    action: Option<LocalstackAction>,
+}
+
+#[derive(Subcommand)]
+enum LocalstackAction {
+    /// Show which AWS services are running (default)
+    Status,
+    /// Start LocalStack, create resources and write AWS_* vars to .env.local
+    Up,
+    /// Stop LocalStack
+    Down,
+    /// Create resources declared under [localstack] that don't exist yet
+    Seed,
+    /// Print AWS_* exports for the shell: eval "$(devkit localstack env)"
+    Env {
+        /// Write them to .env.local instead
+        #[arg(long)]
+        write: bool,
+    },
+}
+
+pub fn command() -> Command {
+    LocalstackArgs::augment_args(
+        Command::new("localstack").about("Local AWS services via LocalStack"),
+    )
+}
+
+pub fn handle(ctx: &AppContext, matches: &clap::ArgMatches) -> Result<()> {
+    let args = LocalstackArgs::from_arg_matches(matches)?;
+
+    match args.action {
+        Some(LocalstackAction::Status) | None => status(ctx),
+        Some(LocalstackAction::Up) => up(ctx),
+        Some(LocalstackAction::Down) => down(ctx),
+        Some(LocalstackAction::Seed) => seed(ctx),
+        Some(LocalstackAction::Env { write: true }) => write_env(ctx),
+        Some(LocalstackAction::Env { write: false }) => {
+            print_env(ctx);
+            Ok(())
+        }
+    }
+}
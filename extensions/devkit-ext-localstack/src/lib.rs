@@ -0,0 +1,382 @@
+//! LocalStack extension for devkit
+//!
+//! Runs AWS services locally through a LocalStack service in the compose file, so
+//! AWS-dependent apps work offline. Resources to create are declared in
+//! `.dev/config.toml`:
+//! ```toml
+//! [localstack]
+//! s3 = ["uploads"]
+//! sqs = ["jobs", "events.fifo"]
+//!
+//! [localstack.dynamodb]
+//! sessions = "id"    # table = partition key
+//! ```
+//!
+//! `devkit localstack up` starts the service, waits for it to be healthy, creates any
+//! missing resources with `awslocal` inside the container and writes `AWS_*` vars to
+//! `.env.local`. Services running in compose reach LocalStack by service name, so set
+//! `endpoint = "http://localstack:4566"` when they read `.env.local` through `env_file`.
+
+mod cli;
+
+use anyhow::{anyhow, bail, Context, Result};
+use devkit_core::config::LocalstackConfig;
+use devkit_core::oplog::LoggedCommand;
+use devkit_core::utils::{docker_available, docker_compose_program, ensure_docker};
+use devkit_core::validation::compose_services;
+use devkit_core::{utils::upsert_env_vars, AppContext, Extension, MenuItem};
+use std::process::{Command, Output};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const ENV_FILE: &str = ".env.local";
+
+/// How long `up` waits for LocalStack to report healthy
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(90);
+
+pub struct LocalstackExtension;
+
+impl Extension for LocalstackExtension {
+    fn name(&self) -> &str {
+        "localstack"
+    }
+
+    fn is_available(&self, ctx: &AppContext) -> bool {
+        docker_available() && has_service(ctx)
+    }
+
+    fn unavailable_reason(&self, ctx: &AppContext) -> Option<String> {
+        if !docker_available() {
+            Some("docker not installed".to_string())
+        } else {
+            Some(format!(
+                "no '{}' service in the compose file",
+                ctx.config.global.localstack.service
+            ))
+        }
+    }
+
+    fn menu_items(&self, _ctx: &AppContext) -> Vec<MenuItem> {
+        use devkit_core::DevkitError;
+        vec![
+            MenuItem {
+                label: "Start LocalStack".to_string(),
+                group: Some("☁️  LocalStack".to_string()),
+                handler: Box::new(|ctx| up(ctx).map_err(DevkitError::from)),
+            },
+            MenuItem {
+                label: "Stop LocalStack".to_string(),
+                group: Some("☁️  LocalStack".to_string()),
+                handler: Box::new(|ctx| down(ctx).map_err(DevkitError::from)),
+            },
+            MenuItem {
+                label: "Seed resources".to_string(),
+                group: Some("☁️  LocalStack".to_string()),
+                handler: Box::new(|ctx| seed(ctx).map_err(DevkitError::from)),
+            },
+            MenuItem {
+                label: "Status".to_string(),
+                group: Some("☁️  LocalStack".to_string()),
+                handler: Box::new(|ctx| status(ctx).map_err(DevkitError::from)),
+            },
+        ]
+    }
+
+    fn subcommands(&self) -> Vec<clap::Command> {
+        vec![cli::command()]
+    }
+
+    fn handle_cli(
+        &self,
+        ctx: &AppContext,
+        name: &str,
+        matches: &clap::ArgMatches,
+    ) -> Option<devkit_core::Result<()>> {
+        (name == "localstack").then(|| cli::handle(ctx, matches).map_err(Into::into))
+    }
+}
+
+fn has_service(ctx: &AppContext) -> bool {
+    let service = &ctx.config.global.localstack.service;
+    compose_services(&ctx.repo).is_some_and(|(_, services)| services.contains_key(service))
+}
+
+/// `docker compose <args>` in the repo root
+fn compose(ctx: &AppContext, args: &[&str]) -> Result<Command> {
+    ensure_docker()?;
+    let (prog, base_args) = docker_compose_program()?;
+    let mut cmd = Command::new(prog);
+    cmd.args(base_args).args(args).current_dir(&ctx.repo);
+    Ok(cmd)
+}
+
+/// Run `awslocal` inside the LocalStack container
+fn awslocal(ctx: &AppContext, args: &[String]) -> Result<Output> {
+    let service = &ctx.config.global.localstack.service;
+    compose(ctx, &["exec", "-T", service, "awslocal"])?
+        .args(args)
+        .logged_output()
+        .context("Failed to run awslocal")
+}
+
+/// Start LocalStack, wait until it's healthy, create resources and write env vars
+pub fn up(ctx: &AppContext) -> Result<()> {
+    let config = &ctx.config.global.localstack;
+    ctx.print_info(&format!("Starting {}...", config.service));
+
+    let status = compose(ctx, &["up", "-d", &config.service])?
+        .logged_status()
+        .context("Failed to run docker compose up")?;
+    if !status.success() {
+        bail!("docker compose up {} failed", config.service);
+    }
+
+    wait_healthy(config)?;
+    ctx.print_success(&format!("✓ LocalStack is up at {}", health_url(config)));
+
+    seed(ctx)?;
+    write_env(ctx)
+}
+
+/// Stop the LocalStack service (other compose services keep running)
+pub fn down(ctx: &AppContext) -> Result<()> {
+    let service = &ctx.config.global.localstack.service;
+    let status = compose(ctx, &["stop", service])?
+        .logged_status()
+        .context("Failed to run docker compose stop")?;
+    if !status.success() {
+        bail!("docker compose stop {} failed", service);
+    }
+    ctx.print_success("✓ LocalStack stopped");
+    Ok(())
+}
+
+fn health_url(config: &LocalstackConfig) -> String {
+    format!("http://localhost:{}/_localstack/health", config.port)
+}
+
+fn wait_healthy(config: &LocalstackConfig) -> Result<()> {
+    let started = Instant::now();
+    while started.elapsed() < STARTUP_TIMEOUT {
+        if health(config).is_ok() {
+            return Ok(());
+        }
+        thread::sleep(Duration::from_secs(1));
+    }
+    bail!(
+        "LocalStack didn't become healthy within {}s (check `devkit docker logs {}`)",
+        STARTUP_TIMEOUT.as_secs(),
+        config.service
+    )
+}
+
+/// LocalStack's health report: each AWS service and its state
+fn health(config: &LocalstackConfig) -> Result<Vec<(String, String)>> {
+    let body: serde_json::Value = ureq::get(&health_url(config))
+        .timeout(Duration::from_secs(2))
+        .call()?
+        .into_json()?;
+    let services = body["services"]
+        .as_object()
+        .ok_or_else(|| anyhow!("Unexpected health response from LocalStack"))?;
+    Ok(services
+        .iter()
+        .map(|(name, state)| {
+            (
+                name.clone(),
+                state.as_str().unwrap_or("unknown").to_string(),
+            )
+        })
+        .collect())
+}
+
+/// Print which AWS services LocalStack is running
+pub fn status(ctx: &AppContext) -> Result<()> {
+    let config = &ctx.config.global.localstack;
+    ctx.print_header("LocalStack");
+    println!();
+
+    let Ok(services) = health(config) else {
+        ctx.print_warning(&format!(
+            "LocalStack isn't reachable on port {}. Start it with: devkit localstack up",
+            config.port
+        ));
+        return Ok(());
+    };
+    for (name, state) in services
+        .iter()
+        .filter(|(_, state)| state == "running" || state == "available")
+    {
+        println!("  {:<20} {}", name, state);
+    }
+    println!();
+    println!("  Endpoint: {}", config.endpoint_url());
+    Ok(())
+}
+
+/// A resource declared in `[localstack]`
+#[derive(Debug, PartialEq)]
+struct Resource {
+    /// "S3 bucket uploads", for messages
+    label: String,
+    /// awslocal arguments that succeed when the resource exists
+    check: Vec<String>,
+    /// awslocal arguments that create it
+    create: Vec<String>,
+}
+
+fn resources(config: &LocalstackConfig) -> Vec<Resource> {
+    let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+    let mut resources = Vec::new();
+
+    for bucket in &config.s3 {
+        resources.push(Resource {
+            label: format!("S3 bucket {}", bucket),
+            check: args(&["s3api", "head-bucket", "--bucket", bucket]),
+            create: args(&["s3api", "create-bucket", "--bucket", bucket]),
+        });
+    }
+
+    for queue in &config.sqs {
+        let mut create = args(&["sqs", "create-queue", "--queue-name", queue]);
+        if queue.ends_with(".fifo") {
+            create.extend(args(&["--attributes", "FifoQueue=true"]));
+        }
+        resources.push(Resource {
+            label: format!("SQS queue {}", queue),
+            check: args(&["sqs", "get-queue-url", "--queue-name", queue]),
+            create,
+        });
+    }
+
+    let mut tables: Vec<_> = config.dynamodb.iter().collect();
+    tables.sort();
+    for (table, key) in tables {
+        resources.push(Resource {
+            label: format!("DynamoDB table {}", table),
+            check: args(&["dynamodb", "describe-table", "--table-name", table]),
+            create: args(&[
+                "dynamodb",
+                "create-table",
+                "--table-name",
+                table,
+                "--attribute-definitions",
+                &format!("AttributeName={},AttributeType=S", key),
+                "--key-schema",
+                &format!("AttributeName={},KeyType=HASH", key),
+                "--billing-mode",
+                "PAY_PER_REQUEST",
+            ]),
+        });
+    }
+
+    resources
+}
+
+/// Create the declared resources that don't exist yet
+pub fn seed(ctx: &AppContext) -> Result<()> {
+    let resources = resources(&ctx.config.global.localstack);
+    if resources.is_empty() {
+        ctx.print_info("No resources declared under [localstack]");
+        return Ok(());
+    }
+
+    let mut created = 0;
+    for resource in &resources {
+        if awslocal(ctx, &resource.check)?.status.success() {
+            continue;
+        }
+        let output = awslocal(ctx, &resource.create)?;
+        if !output.status.success() {
+            bail!(
+                "Failed to create {}: {}",
+                resource.label,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        ctx.print_info(&format!("Created {}", resource.label));
+        created += 1;
+    }
+
+    ctx.print_success(&format!(
+        "✓ {} resources ready ({} created)",
+        resources.len(),
+        created
+    ));
+    Ok(())
+}
+
+/// `AWS_*` vars pointing SDKs and the AWS CLI at LocalStack
+pub fn env_vars(config: &LocalstackConfig) -> Vec<(&'static str, String)> {
+    vec![
+        ("AWS_ENDPOINT_URL", config.endpoint_url()),
+        ("AWS_ACCESS_KEY_ID", "test".to_string()),
+        ("AWS_SECRET_ACCESS_KEY", "test".to_string()),
+        ("AWS_REGION", config.region.clone()),
+        ("AWS_DEFAULT_REGION", config.region.clone()),
+    ]
+}
+
+/// Write the `AWS_*` vars to `.env.local`
+pub fn write_env(ctx: &AppContext) -> Result<()> {
+    let vars = env_vars(&ctx.config.global.localstack);
+    let pairs: Vec<(&str, &str)> = vars.iter().map(|(k, v)| (*k, v.as_str())).collect();
+    upsert_env_vars(&ctx.repo.join(ENV_FILE), &pairs)
+        .with_context(|| format!("Failed to write {}", ENV_FILE))?;
+    ctx.print_info(&format!("Set AWS_* vars in {}", ENV_FILE));
+    Ok(())
+}
+
+/// Print `export` lines for `eval "$(devkit localstack env)"`
+pub fn print_env(ctx: &AppContext) {
+    for (key, value) in env_vars(&ctx.config.global.localstack) {
+        println!("export {}={}", key, value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resources() {
+        let config = LocalstackConfig {
+            s3: vec!["uploads".to_string()],
+            sqs: vec!["jobs".to_string(), "events.fifo".to_string()],
+            dynamodb: [("sessions".to_string(), "id".to_string())].into(),
+            ..Default::default()
+        };
+
+        let resources = resources(&config);
+        let labels: Vec<&str> = resources.iter().map(|r| r.label.as_str()).collect();
+        assert_eq!(
+            labels,
+            [
+                "S3 bucket uploads",
+                "SQS queue jobs",
+                "SQS queue events.fifo",
+                "DynamoDB table sessions",
+            ]
+        );
+        assert!(!resources[1].create.contains(&"FifoQueue=true".to_string()));
+        assert!(resources[2].create.contains(&"FifoQueue=true".to_string()));
+        assert!(resources[3]
+            .create
+            .contains(&"AttributeName=id,KeyType=HASH".to_string()));
+    }
+
+    #[test]
+    fn test_env_vars() {
+        let mut config = LocalstackConfig::default();
+        assert_eq!(env_vars(&config)[0].1, "http://localhost:4566");
+
+        config.endpoint = Some("http://localstack:4566".to_string());
+        config.region = "eu-west-1".to_string();
+        let vars = env_vars(&config);
+        assert_eq!(
+            vars[0],
+            ("AWS_ENDPOINT_URL", "http://localstack:4566".to_string())
+        );
+        assert_eq!(vars[3], ("AWS_REGION", "eu-west-1".to_string()));
+    }
+}
@@ -0,0 +1,78 @@
+//! `devkit mobile` subcommand
+
+use anyhow::Result;
+use clap::{Args, Command, FromArgMatches, Subcommand};
+use devkit_core::AppContext;
+use std::path::PathBuf;
+
+use crate::{boot, devices, find_device, install, launch, list, logs, prepare};
+
+#[derive(Args)]
+pub struct MobileArgs {
+    #[command(subcommand)]
+    action: Option<MobileAction>,
+}
+
+#[derive(Subcommand)]
+enum MobileAction {
+    /// List simulators and emulators (default)
+    Devices,
+    /// Boot a simulator or emulator and wait until it's ready
+    Boot {
+        /// Device name, simulator UDID or AVD name
+        device: String,
+    },
+    /// Install an .app bundle or .apk
+    Install {
+        app: PathBuf,
+        /// Target device (defaults to the only booted one)
+        #[arg(long, short)]
+        device: Option<String>,
+    },
+    /// Launch an app by bundle identifier or Android package name
+    Launch {
+        app_id: String,
+        /// Target device (defaults to the only booted one)
+        #[arg(long, short)]
+        device: Option<String>,
+    },
+    /// Run the pre_run_scripts of mobile packages
+    Prepare {
+        /// Only this package
+        package: Option<String>,
+    },
+    /// Stream device logs
+    Logs {
+        /// Target device (defaults to the only booted one)
+        #[arg(long, short)]
+        device: Option<String>,
+        /// Only logs from this app (bundle identifier or package name)
+        #[arg(long)]
+        app: Option<String>,
+    },
+}
+
+pub fn command() -> Command {
+    MobileArgs::augment_args(Command::new("mobile").about("iOS simulators and Android emulators"))
+}
+
+pub fn handle(ctx: &AppContext, matches: &clap::ArgMatches) -> Result<()> {
+    let args = MobileArgs::from_arg_matches(matches)?;
+
+    match args.action {
+        Some(MobileAction::Devices) | None => list(ctx),
+        Some(MobileAction::Boot { device }) => boot(ctx, &find_device(&devices()?, Some(&device))?),
+        Some(MobileAction::Install { app, device }) => {
+            install(ctx, &find_device(&devices()?, device.as_deref())?, &app)
+        }
+        Some(MobileAction::Launch { app_id, device }) => {
+            launch(ctx, &find_device(&devices()?, device.as_deref())?, &app_id)
+        }
+        Some(MobileAction::Prepare { package }) => prepare(ctx, package.as_deref()),
+        Some(MobileAction::Logs { device, app }) => logs(
+            ctx,
+            &find_device(&devices()?, device.as_deref())?,
+            app.as_deref(),
+        ),
+    }
+}
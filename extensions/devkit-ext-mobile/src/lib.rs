@@ -0,0 +1,656 @@
+//! iOS simulator and Android emulator extension
+//!
+//! Available when a package declares `[mobile]` in its dev.toml and Xcode's `xcrun`
+//! or the Android SDK's `emulator`/`adb` is installed. Simulators are addressed by
+//! name or UDID, emulators by AVD name or adb serial.
+
+mod cli;
+
+use anyhow::{anyhow, bail, Context, Result};
+use devkit_core::oplog::LoggedCommand;
+use devkit_core::{utils::cmd_exists, AppContext, Extension, MenuItem};
+use dialoguer::Select;
+use serde_json::Value;
+use std::fmt;
+use std::path::Path;
+use std::process::Command;
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub struct MobileExtension;
+
+impl Extension for MobileExtension {
+    fn name(&self) -> &str {
+        "mobile"
+    }
+
+    fn is_available(&self, ctx: &AppContext) -> bool {
+        ctx.features.mobile && (ios_available() || android_available())
+    }
+
+    fn unavailable_reason(&self, ctx: &AppContext) -> Option<String> {
+        if !ctx.features.mobile {
+            Some("no package declares [mobile] in dev.toml".to_string())
+        } else {
+            Some("neither xcrun nor the Android emulator is installed".to_string())
+        }
+    }
+
+    fn menu_items(&self, _ctx: &AppContext) -> Vec<MenuItem> {
+        use devkit_core::DevkitError;
+        vec![
+            MenuItem {
+                label: "List devices".to_string(),
+                group: Some("📱 Mobile".to_string()),
+                handler: Box::new(|ctx| list(ctx).map_err(DevkitError::from)),
+            },
+            MenuItem {
+                label: "Boot device".to_string(),
+                group: Some("📱 Mobile".to_string()),
+                handler: Box::new(|ctx| {
+                    select_device(ctx, "Boot which device?", false)
+                        .and_then(|device| boot(ctx, &device))
+                        .map_err(DevkitError::from)
+                }),
+            },
+            MenuItem {
+                label: "Run pre-run scripts".to_string(),
+                group: Some("📱 Mobile".to_string()),
+                handler: Box::new(|ctx| prepare(ctx, None).map_err(DevkitError::from)),
+            },
+            MenuItem {
+                label: "Stream logs".to_string(),
+                group: Some("📱 Mobile".to_string()),
+                handler: Box::new(|ctx| {
+                    select_device(ctx, "Logs from which device?", true)
+                        .and_then(|device| logs(ctx, &device, None))
+                        .map_err(DevkitError::from)
+                }),
+            },
+        ]
+    }
+
+    fn subcommands(&self) -> Vec<clap::Command> {
+        vec![cli::command()]
+    }
+
+    fn handle_cli(
+        &self,
+        ctx: &AppContext,
+        name: &str,
+        matches: &clap::ArgMatches,
+    ) -> Option<devkit_core::Result<()>> {
+        (name == "mobile").then(|| cli::handle(ctx, matches).map_err(Into::into))
+    }
+}
+
+fn ios_available() -> bool {
+    cfg!(target_os = "macos") && cmd_exists("xcrun")
+}
+
+fn android_available() -> bool {
+    cmd_exists("emulator") || cmd_exists("adb")
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Platform {
+    Ios,
+    Android,
+}
+
+impl fmt::Display for Platform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Platform::Ios => write!(f, "iOS"),
+            Platform::Android => write!(f, "Android"),
+        }
+    }
+}
+
+/// A simulator, emulator or connected Android device
+#[derive(Debug, Clone, PartialEq)]
+pub struct Device {
+    pub platform: Platform,
+    pub name: String,
+    /// Simulator UDID or AVD name; what `boot` takes
+    pub id: String,
+    /// iOS runtime, e.g. "iOS 17.2"
+    pub runtime: Option<String>,
+    /// Set while the device is running: the UDID for simulators, the adb serial for
+    /// Android
+    pub serial: Option<String>,
+}
+
+impl Device {
+    pub fn is_booted(&self) -> bool {
+        self.serial.is_some()
+    }
+
+    /// Whether `query` names this device (name, UDID, AVD name or serial)
+    fn matches(&self, query: &str) -> bool {
+        self.name.eq_ignore_ascii_case(query)
+            || self.id == query
+            || self.serial.as_deref() == Some(query)
+    }
+}
+
+impl fmt::Display for Device {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.runtime {
+            Some(runtime) => write!(f, "{} ({})", self.name, runtime),
+            None => write!(f, "{} ({})", self.name, self.platform),
+        }
+    }
+}
+
+/// Every simulator and emulator on this machine, booted ones first
+pub fn devices() -> Result<Vec<Device>> {
+    let mut devices = Vec::new();
+    if ios_available() {
+        devices.extend(ios_devices()?);
+    }
+    if android_available() {
+        devices.extend(android_devices()?);
+    }
+    devices.sort_by_key(|device| !device.is_booted());
+    Ok(devices)
+}
+
+fn ios_devices() -> Result<Vec<Device>> {
+    let output = Command::new("xcrun")
+        .args(["simctl", "list", "devices", "available", "--json"])
+        .logged_output()
+        .context("Failed to run xcrun simctl")?;
+    if !output.status.success() {
+        bail!(
+            "xcrun simctl failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    parse_simctl(&String::from_utf8_lossy(&output.stdout))
+}
+
+fn android_devices() -> Result<Vec<Device>> {
+    let avds = if cmd_exists("emulator") {
+        let output = Command::new("emulator")
+            .arg("-list-avds")
+            .logged_output()
+            .context("Failed to run emulator")?;
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(str::trim)
+            // The emulator prints INFO/WARNING lines alongside AVD names
+            .filter(|line| !line.is_empty() && !line.contains(' '))
+            .map(str::to_string)
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    let serials = if cmd_exists("adb") {
+        let output = Command::new("adb")
+            .arg("devices")
+            .logged_output()
+            .context("Failed to run adb")?;
+        parse_adb_devices(&String::from_utf8_lossy(&output.stdout))
+    } else {
+        Vec::new()
+    };
+
+    // Running emulators report which AVD they were started from
+    let running: Vec<(String, Option<String>)> = serials
+        .into_iter()
+        .map(|serial| {
+            let avd = serial
+                .starts_with("emulator-")
+                .then(|| running_avd(&serial))
+                .flatten();
+            (serial, avd)
+        })
+        .collect();
+
+    let mut devices: Vec<Device> = avds
+        .into_iter()
+        .map(|avd| Device {
+            platform: Platform::Android,
+            name: avd.clone(),
+            serial: running
+                .iter()
+                .find(|(_, running)| running.as_deref() == Some(avd.as_str()))
+                .map(|(serial, _)| serial.clone()),
+            id: avd,
+            runtime: None,
+        })
+        .collect();
+
+    // Physical devices and emulators started from elsewhere
+    for (serial, _) in running {
+        if !devices.iter().any(|d| d.serial.as_ref() == Some(&serial)) {
+            devices.push(Device {
+                platform: Platform::Android,
+                name: serial.clone(),
+                id: serial.clone(),
+                runtime: None,
+                serial: Some(serial),
+            });
+        }
+    }
+    Ok(devices)
+}
+
+/// AVD name of a running emulator
+fn running_avd(serial: &str) -> Option<String> {
+    let output = Command::new("adb")
+        .args(["-s", serial, "emu", "avd", "name"])
+        .logged_output()
+        .ok()?;
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .next()
+        .map(|line| line.trim().to_string())
+        .filter(|name| !name.is_empty())
+}
+
+/// `xcrun simctl list devices --json` output
+fn parse_simctl(json: &str) -> Result<Vec<Device>> {
+    let doc: Value = serde_json::from_str(json).context("Unexpected simctl output")?;
+    let runtimes = doc["devices"]
+        .as_object()
+        .ok_or_else(|| anyhow!("Unexpected simctl output: no devices"))?;
+
+    let mut devices = Vec::new();
+    for (runtime, entries) in runtimes {
+        for entry in entries.as_array().into_iter().flatten() {
+            if entry["isAvailable"] == false {
+                continue;
+            }
+            let (Some(udid), Some(name)) = (entry["udid"].as_str(), entry["name"].as_str()) else {
+                continue;
+            };
+            devices.push(Device {
+                platform: Platform::Ios,
+                name: name.to_string(),
+                id: udid.to_string(),
+                runtime: Some(runtime_name(runtime)),
+                serial: (entry["state"] == "Booted").then(|| udid.to_string()),
+            });
+        }
+    }
+    Ok(devices)
+}
+
+/// `com.apple.CoreSimulator.SimRuntime.iOS-17-2` -> `iOS 17.2`
+fn runtime_name(identifier: &str) -> String {
+    let runtime = identifier.rsplit('.').next().unwrap_or(identifier);
+    match runtime.split_once('-') {
+        Some((os, version)) => format!("{} {}", os, version.replace('-', ".")),
+        None => runtime.to_string(),
+    }
+}
+
+/// Serials of devices `adb devices` lists as ready
+fn parse_adb_devices(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .skip_while(|line| !line.starts_with("List of devices"))
+        .skip(1)
+        .filter_map(
+            |line| match line.split_whitespace().collect::<Vec<_>>()[..] {
+                [serial, "device", ..] => Some(serial.to_string()),
+                _ => None,
+            },
+        )
+        .collect()
+}
+
+/// Find a device by name, UDID, AVD name or serial; without a query, the only booted
+/// device
+pub fn find_device(devices: &[Device], query: Option<&str>) -> Result<Device> {
+    if let Some(query) = query {
+        return devices
+            .iter()
+            .find(|device| device.matches(query))
+            .cloned()
+            .ok_or_else(|| anyhow!("No simulator or emulator named '{}'", query));
+    }
+
+    let booted: Vec<&Device> = devices.iter().filter(|d| d.is_booted()).collect();
+    match booted[..] {
+        [device] => Ok(device.clone()),
+        [] => bail!("No device is booted. Boot one with: devkit mobile boot <device>"),
+        _ => bail!(
+            "Several devices are booted ({}); pick one with --device",
+            booted
+                .iter()
+                .map(|d| d.name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    }
+}
+
+fn select_device(ctx: &AppContext, prompt: &str, booted: bool) -> Result<Device> {
+    let devices: Vec<Device> = devices()?
+        .into_iter()
+        .filter(|device| device.is_booted() == booted)
+        .collect();
+    if devices.is_empty() {
+        bail!(if booted {
+            "No device is booted"
+        } else {
+            "No shut-down simulators or emulators found"
+        });
+    }
+
+    let items: Vec<String> = devices.iter().map(ToString::to_string).collect();
+    let selection = Select::with_theme(&*ctx.theme())
+        .with_prompt(prompt)
+        .items(&items)
+        .default(0)
+        .interact()?;
+    Ok(devices[selection].clone())
+}
+
+/// Print every simulator and emulator
+pub fn list(ctx: &AppContext) -> Result<()> {
+    ctx.print_header("Mobile Devices");
+    println!();
+
+    let devices = devices()?;
+    if devices.is_empty() {
+        ctx.print_info("No simulators or emulators found");
+        return Ok(());
+    }
+    for device in &devices {
+        let state = if device.is_booted() {
+            "booted"
+        } else {
+            "shut down"
+        };
+        println!("  {:<40} {:<10} {}", device.to_string(), state, device.id);
+    }
+    Ok(())
+}
+
+/// Boot a device and wait until it's ready, up to the packages' `startup_timeout_secs`
+pub fn boot(ctx: &AppContext, device: &Device) -> Result<()> {
+    if device.is_booted() {
+        ctx.print_info(&format!("{} is already booted", device));
+        return Ok(());
+    }
+
+    ctx.print_info(&format!("Booting {}...", device));
+    match device.platform {
+        Platform::Ios => {
+            let status = Command::new("xcrun")
+                .args(["simctl", "boot", &device.id])
+                .logged_status()
+                .context("Failed to run xcrun simctl boot")?;
+            if !status.success() {
+                bail!("Failed to boot {}", device);
+            }
+            // Show the window; the simulator runs headless otherwise
+            let _ = Command::new("open")
+                .args(["-a", "Simulator"])
+                .logged_status();
+        }
+        Platform::Android => {
+            if !cmd_exists("emulator") {
+                bail!("The Android emulator isn't on PATH (add $ANDROID_HOME/emulator)");
+            }
+            Command::new("emulator")
+                .args(["-avd", &device.id])
+                .logged_spawn()
+                .context("Failed to start the emulator")?;
+        }
+    }
+
+    let timeout = Duration::from_secs(startup_timeout(ctx).into());
+    let started = Instant::now();
+    while started.elapsed() < timeout {
+        if is_ready(device)? {
+            ctx.print_success(&format!("✓ {} is ready", device));
+            return Ok(());
+        }
+        thread::sleep(Duration::from_secs(2));
+    }
+    bail!(
+        "{} didn't finish booting within {}s (raise startup_timeout_secs under [mobile])",
+        device,
+        timeout.as_secs()
+    )
+}
+
+/// Longest `startup_timeout_secs` among mobile packages
+fn startup_timeout(ctx: &AppContext) -> u32 {
+    ctx.config
+        .mobile_packages()
+        .iter()
+        .map(|(_, _, mobile)| mobile.startup_timeout_secs)
+        .max()
+        .unwrap_or(300)
+}
+
+fn is_ready(device: &Device) -> Result<bool> {
+    let Some(current) = devices()?.into_iter().find(|d| d.id == device.id) else {
+        return Ok(false);
+    };
+    let Some(serial) = current.serial else {
+        return Ok(false);
+    };
+    if device.platform == Platform::Ios {
+        return Ok(true);
+    }
+    let output = Command::new("adb")
+        .args(["-s", &serial, "shell", "getprop", "sys.boot_completed"])
+        .logged_output()
+        .context("Failed to run adb")?;
+    Ok(String::from_utf8_lossy(&output.stdout).trim() == "1")
+}
+
+/// Install an app bundle (`.app`) on a simulator or an `.apk` on an Android device
+pub fn install(ctx: &AppContext, device: &Device, app: &Path) -> Result<()> {
+    let serial = booted_serial(device)?;
+    let platform = match app.extension().and_then(|ext| ext.to_str()) {
+        Some("app") => Platform::Ios,
+        Some("apk") => Platform::Android,
+        _ => bail!("Expected an .app bundle or an .apk, got {}", app.display()),
+    };
+    if platform != device.platform {
+        bail!(
+            "{} is a {} app but {} isn't",
+            app.display(),
+            platform,
+            device
+        );
+    }
+
+    ctx.print_info(&format!("Installing {} on {}...", app.display(), device));
+    let status = match platform {
+        Platform::Ios => Command::new("xcrun")
+            .args(["simctl", "install", serial])
+            .arg(app)
+            .logged_status(),
+        Platform::Android => Command::new("adb")
+            .args(["-s", serial, "install", "-r"])
+            .arg(app)
+            .logged_status(),
+    }
+    .context("Failed to install the app")?;
+    if !status.success() {
+        bail!("Install failed");
+    }
+
+    ctx.print_success("✓ Installed");
+    Ok(())
+}
+
+/// Launch an app by bundle identifier (iOS) or package name (Android)
+pub fn launch(ctx: &AppContext, device: &Device, app_id: &str) -> Result<()> {
+    let serial = booted_serial(device)?;
+
+    ctx.print_info(&format!("Launching {} on {}...", app_id, device));
+    let status = match device.platform {
+        Platform::Ios => Command::new("xcrun")
+            .args(["simctl", "launch", serial, app_id])
+            .logged_status(),
+        Platform::Android => Command::new("adb")
+            .args(["-s", serial, "shell", "monkey", "-p", app_id])
+            .args(["-c", "android.intent.category.LAUNCHER", "1"])
+            .logged_status(),
+    }
+    .context("Failed to launch the app")?;
+    if !status.success() {
+        bail!("Launching {} failed", app_id);
+    }
+
+    ctx.print_success(&format!("✓ Launched {}", app_id));
+    Ok(())
+}
+
+/// Stream device logs until interrupted, optionally only from one app
+pub fn logs(ctx: &AppContext, device: &Device, app: Option<&str>) -> Result<()> {
+    let serial = booted_serial(device)?;
+    ctx.print_info(&format!("Streaming logs from {} (Ctrl+C to stop)", device));
+
+    let mut cmd = match device.platform {
+        Platform::Ios => {
+            let mut cmd = Command::new("xcrun");
+            cmd.args([
+                "simctl", "spawn", serial, "log", "stream", "--style", "compact",
+            ]);
+            if let Some(app) = app {
+                // Process names are the last component of the bundle identifier
+                let process = app.rsplit('.').next().unwrap_or(app);
+                cmd.args(["--predicate", &format!("process == \"{}\"", process)]);
+            }
+            cmd
+        }
+        Platform::Android => {
+            let mut cmd = Command::new("adb");
+            cmd.args(["-s", serial, "logcat"]);
+            if let Some(app) = app {
+                let output = Command::new("adb")
+                    .args(["-s", serial, "shell", "pidof", app])
+                    .logged_output()
+                    .context("Failed to run adb")?;
+                let pid = String::from_utf8_lossy(&output.stdout).trim().to_string();
+                if pid.is_empty() {
+                    bail!("{} isn't running on {}", app, device);
+                }
+                cmd.arg(format!("--pid={}", pid));
+            }
+            cmd
+        }
+    };
+
+    cmd.logged_status().context("Failed to stream logs")?;
+    Ok(())
+}
+
+fn booted_serial(device: &Device) -> Result<&str> {
+    device.serial.as_deref().ok_or_else(|| {
+        anyhow!(
+            "{} isn't booted. Boot it with: devkit mobile boot \"{}\"",
+            device,
+            device.name
+        )
+    })
+}
+
+/// Run the `pre_run_scripts` of one mobile package, or of all of them
+pub fn prepare(ctx: &AppContext, package: Option<&str>) -> Result<()> {
+    let packages = ctx.config.mobile_packages();
+    if let Some(name) = package {
+        if !packages.iter().any(|(pkg, _, _)| *pkg == name) {
+            bail!("Package '{}' has no [mobile] section", name);
+        }
+    }
+
+    for (name, pkg, mobile) in packages {
+        if package.is_some_and(|package| package != name) {
+            continue;
+        }
+        for script in &mobile.pre_run_scripts {
+            ctx.print_info(&format!("[{}] {}", name, script));
+            let status = shell_command(script)
+                .current_dir(&pkg.path)
+                .logged_status()
+                .with_context(|| format!("Failed to run {}", script))?;
+            if !status.success() {
+                bail!("[{}] {} failed with {}", name, script, status);
+            }
+        }
+    }
+
+    ctx.print_success("✓ Pre-run scripts complete");
+    Ok(())
+}
+
+#[cfg(windows)]
+fn shell_command(command: &str) -> Command {
+    let mut cmd = Command::new("cmd");
+    cmd.args(["/C", command]);
+    cmd
+}
+
+#[cfg(not(windows))]
+fn shell_command(command: &str) -> Command {
+    let mut cmd = Command::new("sh");
+    cmd.args(["-c", command]);
+    cmd
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_simctl() {
+        let json = r#"{"devices": {
+            "com.apple.CoreSimulator.SimRuntime.iOS-17-2": [
+                {"udid": "A1", "name": "iPhone 15", "state": "Booted", "isAvailable": true},
+                {"udid": "B2", "name": "iPad Air", "state": "Shutdown", "isAvailable": true},
+                {"udid": "C3", "name": "iPhone 8", "state": "Shutdown", "isAvailable": false}
+            ]
+        }}"#;
+        let devices = parse_simctl(json).unwrap();
+        assert_eq!(devices.len(), 2);
+        assert_eq!(devices[0].to_string(), "iPhone 15 (iOS 17.2)");
+        assert_eq!(devices[0].serial.as_deref(), Some("A1"));
+        assert!(!devices[1].is_booted());
+    }
+
+    #[test]
+    fn test_parse_adb_devices() {
+        let output = "* daemon started successfully\nList of devices attached\nemulator-5554\tdevice\nR58M\tunauthorized\n0A1B\tdevice usb:1-1\n\n";
+        assert_eq!(parse_adb_devices(output), ["emulator-5554", "0A1B"]);
+    }
+
+    #[test]
+    fn test_find_device() {
+        let device = |name: &str, serial: Option<&str>| Device {
+            platform: Platform::Android,
+            name: name.to_string(),
+            id: name.to_string(),
+            runtime: None,
+            serial: serial.map(str::to_string),
+        };
+        let devices = [
+            device("Pixel_7", Some("emulator-5554")),
+            device("Pixel_Tablet", None),
+        ];
+
+        assert_eq!(find_device(&devices, None).unwrap().name, "Pixel_7");
+        assert_eq!(
+            find_device(&devices, Some("pixel_tablet")).unwrap().name,
+            "Pixel_Tablet"
+        );
+        assert_eq!(
+            find_device(&devices, Some("emulator-5554")).unwrap().name,
+            "Pixel_7"
+        );
+        assert!(find_device(&devices, Some("Nexus")).is_err());
+        assert!(find_device(&devices[1..], None).is_err());
+    }
+}
@@ -0,0 +1,334 @@
+//! Starter alert rules and a local Alertmanager webhook receiver
+
+use anyhow::{anyhow, Context, Result};
+use console::style;
+use devkit_core::oplog::LoggedCommand;
+use devkit_core::{utils::desktop_notify, AppContext};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{IpAddr, Ipv4Addr, TcpListener, TcpStream};
+use std::process::Command;
+use std::thread;
+
+/// Default port for the local alert receiver
+pub const RECEIVER_PORT: u16 = 9095;
+
+/// Largest webhook body accepted; Alertmanager batches are far smaller
+const MAX_BODY: usize = 1024 * 1024;
+
+/// Generate alert rules for each service in `[services]`
+///
+/// Container rules need cAdvisor metrics; port rules need blackbox exporter probes.
+pub fn alert_rules(
+    services: &HashMap<String, u16>,
+    container_metrics: bool,
+    port_probes: bool,
+) -> String {
+    let mut names: Vec<_> = services.keys().collect();
+    names.sort();
+
+    let mut rules = String::new();
+    for name in names {
+        if container_metrics {
+            rules.push_str(&format!(
+                r#"      - alert: ServiceDown
+        expr: absent(container_last_seen{{container_label_com_docker_compose_service="{name}"}})
+        for: 1m
+        labels:
+          severity: critical
+          service: {name}
+        annotations:
+          summary: "{name} container is not running"
+      - alert: HighRestartCount
+        expr: changes(container_start_time_seconds{{container_label_com_docker_compose_service="{name}"}}[15m]) > 3
+        labels:
+          severity: warning
+          service: {name}
+        annotations:
+          summary: "{name} restarted more than 3 times in 15 minutes"
+"#
+            ));
+        }
+        if port_probes {
+            rules.push_str(&format!(
+                r#"      - alert: PortNotListening
+        expr: probe_success{{job="service-ports", service="{name}"}} == 0
+        for: 1m
+        labels:
+          severity: warning
+          service: {name}
+        annotations:
+          summary: "{name} is not listening on port {port}"
+"#,
+                port = services[name]
+            ));
+        }
+    }
+
+    if rules.is_empty() {
+        return "# Generated by devkit from [services] in .dev/config.toml\ngroups: []\n"
+            .to_string();
+    }
+
+    format!(
+        "# Generated by devkit from [services] in .dev/config.toml\n\
+         groups:\n  - name: devkit-services\n    rules:\n{}",
+        rules
+    )
+}
+
+/// Alertmanager config routing every alert to the local receiver
+pub fn alertmanager_config() -> String {
+    format!(
+        r#"route:
+  receiver: devkit
+  group_wait: 10s
+  group_interval: 1m
+  repeat_interval: 1h
+
+receivers:
+  - name: devkit
+    webhook_configs:
+      - url: http://host.docker.internal:{}/alerts
+        send_resolved: true
+"#,
+        RECEIVER_PORT
+    )
+}
+
+#[derive(Debug, Deserialize)]
+struct WebhookPayload {
+    alerts: Vec<Alert>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Alert {
+    status: String,
+    #[serde(default)]
+    labels: HashMap<String, String>,
+    #[serde(default)]
+    annotations: HashMap<String, String>,
+}
+
+/// Receive Alertmanager webhooks, printing each alert and raising a desktop notification
+///
+/// Listens on localhost, and on Linux on the Docker bridge too, since that's where
+/// `host.docker.internal` points from the Alertmanager container.
+pub fn listen_for_alerts(ctx: &AppContext, port: u16) -> Result<()> {
+    let mut listeners = vec![TcpListener::bind((Ipv4Addr::LOCALHOST, port))
+        .with_context(|| format!("Failed to listen on port {}", port))?];
+    if let Some(bridge) = docker_bridge_address() {
+        match TcpListener::bind((bridge, port)) {
+            Ok(listener) => listeners.push(listener),
+            Err(e) => ctx.print_warning(&format!(
+                "Failed to listen on the Docker bridge ({}): {}",
+                bridge, e
+            )),
+        }
+    }
+
+    ctx.print_header("Listening for monitoring alerts");
+    ctx.print_info(&format!(
+        "Alertmanager webhook receiver on http://localhost:{}/alerts (Ctrl+C to stop)",
+        port
+    ));
+    println!();
+
+    thread::scope(|scope| {
+        for listener in &listeners {
+            scope.spawn(move || receive_alerts(ctx, listener));
+        }
+    });
+    Ok(())
+}
+
+fn receive_alerts(ctx: &AppContext, listener: &TcpListener) {
+    for stream in listener.incoming() {
+        let mut stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                ctx.print_warning(&format!("Connection failed: {}", e));
+                continue;
+            }
+        };
+
+        let result = read_body(&mut stream).and_then(|body| {
+            serde_json::from_slice::<WebhookPayload>(&body).context("Invalid webhook payload")
+        });
+
+        match result {
+            Ok(payload) => {
+                respond(&mut stream, "200 OK");
+                for alert in &payload.alerts {
+                    report_alert(alert);
+                }
+            }
+            Err(e) => {
+                match e.downcast_ref::<BodyTooLarge>() {
+                    Some(_) => respond(&mut stream, "413 Payload Too Large"),
+                    None => respond(&mut stream, "400 Bad Request"),
+                }
+                ctx.print_warning(&format!("Ignoring request: {:#}", e));
+            }
+        }
+    }
+}
+
+/// The default Docker bridge's gateway on Linux, where `host-gateway` resolves to;
+/// Docker Desktop forwards `host.docker.internal` to localhost instead
+fn docker_bridge_address() -> Option<IpAddr> {
+    if !cfg!(target_os = "linux") {
+        return None;
+    }
+    let output = Command::new("docker")
+        .args([
+            "network",
+            "inspect",
+            "bridge",
+            "--format",
+            "{{range .IPAM.Config}}{{.Gateway}}{{end}}",
+        ])
+        .logged_output()
+        .ok()?;
+    String::from_utf8_lossy(&output.stdout).trim().parse().ok()
+}
+
+fn report_alert(alert: &Alert) {
+    let name = alert
+        .labels
+        .get("alertname")
+        .map(String::as_str)
+        .unwrap_or("Alert");
+    let summary = alert
+        .annotations
+        .get("summary")
+        .cloned()
+        .unwrap_or_else(|| name.to_string());
+
+    let status = if alert.status == "resolved" {
+        style("resolved").green()
+    } else {
+        style("firing").red().bold()
+    };
+    println!("[{}] {} - {}", status, name, summary);
+
+    // Notifications are best effort - the terminal output above is the source of truth
+    let _ = desktop_notify(&format!("{} ({})", name, alert.status), &summary);
+}
+
+/// A request whose Content-Length is over [`MAX_BODY`]
+#[derive(Debug)]
+struct BodyTooLarge(usize);
+
+impl std::fmt::Display for BodyTooLarge {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Body of {} bytes is over the {} byte limit",
+            self.0, MAX_BODY
+        )
+    }
+}
+
+impl std::error::Error for BodyTooLarge {}
+
+/// Read an HTTP request and return its body
+fn read_body(stream: impl Read) -> Result<Vec<u8>> {
+    let mut reader = BufReader::new(stream);
+    let mut content_length = 0;
+
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            return Err(anyhow!("Connection closed before headers were complete"));
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((key, value)) = line.split_once(':') {
+            if key.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().context("Invalid Content-Length")?;
+            }
+        }
+    }
+
+    if content_length > MAX_BODY {
+        return Err(BodyTooLarge(content_length).into());
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+    Ok(body)
+}
+
+fn respond(stream: &mut TcpStream, status: &str) {
+    let _ = write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+        status
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_alert_rules() {
+        let services = HashMap::from([("postgres".to_string(), 5432), ("api".to_string(), 8080)]);
+
+        let rules = alert_rules(&services, true, true);
+        assert!(rules.contains("groups:\n  - name: devkit-services\n    rules:\n"));
+        assert!(rules.contains(
+            r#"expr: absent(container_last_seen{container_label_com_docker_compose_service="api"})"#
+        ));
+        assert!(
+            rules.contains(r#"expr: probe_success{job="service-ports", service="postgres"} == 0"#)
+        );
+        assert!(rules.contains("postgres is not listening on port 5432"));
+        // Sorted by service name
+        assert!(rules.find("service: api").unwrap() < rules.find("service: postgres").unwrap());
+
+        let rules = alert_rules(&services, false, true);
+        assert!(!rules.contains("ServiceDown"));
+        assert_eq!(rules.matches("alert: PortNotListening").count(), 2);
+
+        // Without cAdvisor or probes there's nothing to alert on
+        assert!(alert_rules(&services, false, false).ends_with("\ngroups: []\n"));
+        assert!(alert_rules(&HashMap::new(), true, true).ends_with("\ngroups: []\n"));
+    }
+
+    #[test]
+    fn test_alertmanager_config() {
+        let config = alertmanager_config();
+        assert!(config.contains("receiver: devkit"));
+        assert!(config.contains(&format!(
+            "url: http://host.docker.internal:{}/alerts",
+            RECEIVER_PORT
+        )));
+    }
+
+    #[test]
+    fn test_read_body() {
+        let body = r#"{"alerts":[{"status":"firing","labels":{"alertname":"ServiceDown"}}]}"#;
+        let request = format!(
+            "POST /alerts HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        let read = read_body(request.as_bytes()).unwrap();
+        let payload: WebhookPayload = serde_json::from_slice(&read).unwrap();
+        assert_eq!(payload.alerts[0].labels["alertname"], "ServiceDown");
+
+        let too_large = format!(
+            "POST /alerts HTTP/1.1\r\ncontent-length: {}\r\n\r\n",
+            MAX_BODY + 1
+        );
+        let error = read_body(too_large.as_bytes()).unwrap_err();
+        assert!(error.downcast_ref::<BodyTooLarge>().is_some());
+        assert!(
+            read_body("POST /alerts HTTP/1.1\r\nContent-Length: x\r\n\r\n".as_bytes()).is_err()
+        );
+    }
+}
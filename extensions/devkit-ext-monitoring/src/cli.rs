@@ -0,0 +1,50 @@
+//! `devkit monitoring` subcommand
+
+use anyhow::Result;
+use clap::{Args, Command, FromArgMatches, Subcommand};
+use devkit_core::AppContext;
+
+use crate::{listen_for_alerts, monitoring_status, start_monitoring, stop_monitoring};
+
+#[derive(Args)]
+pub struct MonitoringArgs {
+    #[command(subcommand)]
+    action: MonitoringAction,
+}
+
+#[derive(Subcommand)]
+enum MonitoringAction {
+    /// Start the monitoring stack
+    Start {
+        /// Only start these components (e.g., grafana,prometheus)
+        #[arg(long, value_delimiter = ',')]
+        only: Vec<String>,
+    },
+    /// Stop the monitoring stack
+    Stop,
+    /// Show which components are running
+    Status,
+    /// Receive Alertmanager alerts as desktop notifications
+    Alerts {
+        /// Port for the webhook receiver
+        #[arg(long, default_value_t = crate::RECEIVER_PORT)]
+        port: u16,
+    },
+}
+
+pub fn command() -> Command {
+    MonitoringArgs::augment_args(
+        Command::new("monitoring").about("Monitoring stack operations (if enabled)"),
+    )
+}
+
+pub fn handle(ctx: &AppContext, matches: &clap::ArgMatches) -> Result<()> {
+    let args = MonitoringArgs::from_arg_matches(matches)?;
+
+    match args.action {
+        MonitoringAction::Start { only } => start_monitoring(ctx, &only),
+        MonitoringAction::Stop => stop_monitoring(ctx),
+        MonitoringAction::Status => monitoring_status(ctx),
+        MonitoringAction::Alerts { port } => listen_for_alerts(ctx, port),
+    }
+}
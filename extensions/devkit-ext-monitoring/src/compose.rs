@@ -0,0 +1,86 @@
+//! Docker compose invocation for the monitoring project
+
+use anyhow::{anyhow, Result};
+use devkit_core::{
+    utils::{docker_compose_program, ensure_docker},
+    AppContext,
+};
+use devkit_tasks::CmdBuilder;
+
+use crate::stack::compose_project_name;
+
+/// Compose project name for the monitoring stack
+///
+/// Kept separate from the application's compose project so `down` never
+/// touches application containers (and vice versa).
+pub fn monitoring_project_name(ctx: &AppContext) -> String {
+    ctx.config
+        .global
+        .monitoring
+        .project_name
+        .clone()
+        .unwrap_or_else(|| format!("{}-monitoring", compose_project_name(&ctx.repo)))
+}
+
+/// Get docker compose program and base args targeting the monitoring project
+pub fn monitoring_compose_program(ctx: &AppContext) -> Result<(String, Vec<String>)> {
+    ensure_docker()?;
+
+    let monitoring = &ctx.config.global.monitoring;
+    let (prog, mut args) = docker_compose_program()?;
+
+    args.push("-p".to_string());
+    args.push(monitoring_project_name(ctx));
+    args.push("-f".to_string());
+    args.push(monitoring.compose_file.clone());
+
+    for file in &monitoring.override_files {
+        if !ctx.repo.join(file).exists() {
+            return Err(anyhow!("Monitoring compose override not found: {}", file));
+        }
+        args.push("-f".to_string());
+        args.push(file.clone());
+    }
+
+    Ok((prog, args))
+}
+
+/// Run a compose subcommand against the monitoring project, streaming output
+pub fn run_compose(ctx: &AppContext, subcommand: &[&str]) -> Result<()> {
+    let (prog, mut args) = monitoring_compose_program(ctx)?;
+    args.extend(subcommand.iter().map(|s| s.to_string()));
+
+    if !ctx.quiet {
+        println!("[monitoring] {} {}", prog, args.join(" "));
+    }
+
+    let code = CmdBuilder::new(&prog)
+        .args(&args)
+        .cwd(&ctx.repo)
+        .inherit_io()
+        .run()?;
+
+    if code != 0 {
+        return Err(anyhow!(
+            "docker compose {} exited with code {}",
+            subcommand.first().unwrap_or(&""),
+            code
+        ));
+    }
+
+    Ok(())
+}
+
+/// List running services of the monitoring project
+pub fn running_services(ctx: &AppContext) -> Result<Vec<String>> {
+    let (prog, mut args) = monitoring_compose_program(ctx)?;
+    args.extend(["ps", "--services", "--filter", "status=running"].map(String::from));
+
+    let out = CmdBuilder::new(&prog)
+        .args(&args)
+        .cwd(&ctx.repo)
+        .capture_stdout()
+        .run_capture()?;
+
+    Ok(out.stdout_lines())
+}
@@ -1,10 +1,40 @@
 //! Monitoring stack extension
 //!
-//! Provides local Prometheus, Grafana, Loki, and Tempo stack
-
-use anyhow::{Context, Result};
+//! Provides local Prometheus, Grafana, Loki, and Tempo stack.
+//! Container logs from the project's compose services are shipped to Loki via Promtail.
+//!
+//! Components are selected via `.dev/config.toml`:
+//! ```toml
+//! [monitoring]
+//! components = ["prometheus", "grafana", "loki"]
+//! ```
+//!
+//! Container and host metrics exporters (`cadvisor`, `node-exporter`) are opt-in
+//! and get matching Prometheus scrape jobs when enabled.
+//!
+//! Enabling `alertmanager` adds starter alert rules for every `[services]` entry
+//! (service down, high restart count, port not listening). Alerts are delivered to
+//! a local webhook receiver (`devkit monitoring alerts`) as desktop notifications.
+
+mod alerts;
+mod cli;
+mod compose;
+mod stack;
+mod status;
+
+pub use alerts::{listen_for_alerts, RECEIVER_PORT};
+pub use compose::{monitoring_compose_program, monitoring_project_name};
+pub use stack::{select_components, Component, COMPONENTS};
+pub use status::*;
+
+use anyhow::{bail, Result};
+use compose::run_compose;
+use devkit_core::extension::unavailable_error;
 use devkit_core::{AppContext, Extension, MenuItem};
-use std::process::Command;
+use stack::{
+    compose_outdated, configured_components, create_missing_config_files,
+    create_monitoring_compose, missing_scrape_jobs,
+};
 
 pub struct MonitoringExtension;
 
@@ -17,62 +47,141 @@ impl Extension for MonitoringExtension {
         ctx.features.docker
     }
 
+    fn unavailable_reason(&self, _ctx: &AppContext) -> Option<String> {
+        Some("requires docker and a compose file".to_string())
+    }
+
     fn menu_items(&self, _ctx: &AppContext) -> Vec<MenuItem> {
         vec![
             MenuItem {
                 label: "📊 Start monitoring stack".to_string(),
                 group: None,
-                handler: Box::new(|ctx| start_monitoring(ctx).map_err(Into::into)),
+                handler: Box::new(|ctx| start_monitoring(ctx, &[]).map_err(Into::into)),
             },
             MenuItem {
                 label: "⏹  Stop monitoring stack".to_string(),
                 group: None,
                 handler: Box::new(|ctx| stop_monitoring(ctx).map_err(Into::into)),
             },
+            MenuItem {
+                label: "🩺 Monitoring stack status".to_string(),
+                group: None,
+                handler: Box::new(|ctx| monitoring_status(ctx).map_err(Into::into)),
+            },
+            MenuItem {
+                label: "🔔 Listen for monitoring alerts".to_string(),
+                group: None,
+                handler: Box::new(|ctx| listen_for_alerts(ctx, RECEIVER_PORT).map_err(Into::into)),
+            },
         ]
     }
+
+    fn subcommands(&self) -> Vec<clap::Command> {
+        vec![cli::command()]
+    }
+
+    fn handle_cli(
+        &self,
+        ctx: &AppContext,
+        name: &str,
+        matches: &clap::ArgMatches,
+    ) -> Option<devkit_core::Result<()>> {
+        (name == "monitoring").then(|| {
+            if !self.is_available(ctx) {
+                return Err(unavailable_error(self, ctx));
+            }
+            cli::handle(ctx, matches).map_err(Into::into)
+        })
+    }
 }
 
 /// Start monitoring stack
-pub fn start_monitoring(ctx: &AppContext) -> Result<()> {
+///
+/// Starts every configured component, or only the components in `only` when given;
+/// those must be configured too, since the compose file defines only configured ones.
+pub fn start_monitoring(ctx: &AppContext, only: &[String]) -> Result<()> {
     ctx.print_header("Starting Monitoring Stack");
     println!();
 
+    let configured = configured_components(ctx)?;
+    let targets = if only.is_empty() {
+        configured.clone()
+    } else {
+        select_components(only)?
+    };
+    let unconfigured = targets
+        .iter()
+        .find(|target| !configured.iter().any(|c| c.name == target.name));
+    if let Some(component) = unconfigured {
+        bail!(
+            "'{}' is not in [monitoring] components in .dev/config.toml",
+            component.name
+        );
+    }
+
     ctx.print_info("Monitoring stack includes:");
-    println!("  • Prometheus (metrics)    - http://localhost:9090");
-    println!("  • Grafana (dashboards)    - http://localhost:3000");
-    println!("  • Loki (logs)            - http://localhost:3100");
-    println!("  • Tempo (traces)         - http://localhost:3200");
+    for component in &targets {
+        println!(
+            "  • {:<24} - {}",
+            format!("{} ({})", component.label, component.description),
+            component.url()
+        );
+    }
     println!();
 
-    // Check if docker-compose file exists
-    let compose_file = ctx.repo.join("docker-compose.monitoring.yml");
+    // Generate the compose file if it doesn't exist yet or the components changed
+    let compose_file = &ctx.config.global.monitoring.compose_file;
+    let compose_path = ctx.repo.join(compose_file);
+
+    if !compose_path.exists() {
+        ctx.print_warning(&format!("{} not found", compose_file));
+        ctx.print_info("Creating monitoring stack configuration...");
+        create_monitoring_compose(&compose_path, &configured)?;
+    } else if compose_outdated(&compose_path, &configured) {
+        ctx.print_info(&format!(
+            "Updating {} for the configured components",
+            compose_file
+        ));
+        create_monitoring_compose(&compose_path, &configured)?;
+    }
 
-    if !compose_file.exists() {
-        ctx.print_warning("docker-compose.monitoring.yml not found");
-        ctx.print_info("Creating default monitoring stack configuration...");
-        create_monitoring_compose(&ctx.repo)?;
+    create_missing_config_files(&ctx.repo, &configured, &ctx.config.global.services.ports)?;
+
+    if configured.iter().any(|c| c.name == "prometheus") {
+        for name in missing_scrape_jobs(&ctx.repo, &configured) {
+            ctx.print_warning(&format!(
+                "prometheus.yml has no scrape job for '{}' - delete it to regenerate",
+                name
+            ));
+        }
     }
 
     ctx.print_info("Starting containers...");
 
-    let output = Command::new("docker-compose")
-        .args(["-f", "docker-compose.monitoring.yml", "up", "-d"])
-        .current_dir(&ctx.repo)
-        .output()
-        .context("Failed to start monitoring stack")?;
+    let mut subcommand = vec!["up", "-d"];
+    if !only.is_empty() {
+        subcommand.extend(targets.iter().map(|c| c.name));
+    }
+    run_compose(ctx, &subcommand)?;
 
-    if output.status.success() {
-        ctx.print_success("✓ Monitoring stack started");
+    ctx.print_success("✓ Monitoring stack started");
+    println!();
+    println!("Access dashboards at:");
+    for component in &targets {
+        match component.name {
+            "grafana" => println!("  Grafana:    {} (admin/admin)", component.url()),
+            "prometheus" => println!("  Prometheus: {}", component.url()),
+            _ => {}
+        }
+    }
+    if targets.iter().any(|c| c.name == "alertmanager") {
         println!();
-        println!("Access dashboards at:");
-        println!("  Grafana:    http://localhost:3000 (admin/admin)");
-        println!("  Prometheus: http://localhost:9090");
-    } else {
-        ctx.print_error(&format!(
-            "Error: {}",
-            String::from_utf8_lossy(&output.stderr)
-        ));
+        println!("Run 'devkit monitoring alerts' to receive alerts as desktop notifications.");
+    }
+    if targets.iter().any(|c| c.name == "loki") {
+        println!();
+        println!("Container logs from this project are shipped to Loki.");
+        println!("View them in Grafana under Explore → Loki ({{service=\"<name>\"}}).");
     }
 
     Ok(())
@@ -82,77 +191,8 @@ pub fn start_monitoring(ctx: &AppContext) -> Result<()> {
 pub fn stop_monitoring(ctx: &AppContext) -> Result<()> {
     ctx.print_info("Stopping monitoring stack...");
 
-    let output = Command::new("docker-compose")
-        .args(["-f", "docker-compose.monitoring.yml", "down"])
-        .current_dir(&ctx.repo)
-        .output()
-        .context("Failed to stop monitoring stack")?;
-
-    if output.status.success() {
-        ctx.print_success("✓ Monitoring stack stopped");
-    } else {
-        ctx.print_error(&format!(
-            "Error: {}",
-            String::from_utf8_lossy(&output.stderr)
-        ));
-    }
-
-    Ok(())
-}
-
-fn create_monitoring_compose(repo: &std::path::Path) -> Result<()> {
-    let compose_content = r#"version: '3.8'
-
-services:
-  prometheus:
-    image: prom/prometheus:latest
-    ports:
-      - "9090:9090"
-    volumes:
-      - ./prometheus.yml:/etc/prometheus/prometheus.yml
-    command:
-      - '--config.file=/etc/prometheus/prometheus.yml'
-
-  grafana:
-    image: grafana/grafana:latest
-    ports:
-      - "3000:3000"
-    environment:
-      - GF_SECURITY_ADMIN_PASSWORD=admin
-    volumes:
-      - grafana-data:/var/lib/grafana
-
-  loki:
-    image: grafana/loki:latest
-    ports:
-      - "3100:3100"
-    command: -config.file=/etc/loki/local-config.yaml
-
-  tempo:
-    image: grafana/tempo:latest
-    ports:
-      - "3200:3200"
-    command: [ "-config.file=/etc/tempo.yaml" ]
-
-volumes:
-  grafana-data:
-"#;
-
-    let compose_file = repo.join("docker-compose.monitoring.yml");
-    std::fs::write(compose_file, compose_content)?;
-
-    // Create basic prometheus config
-    let prometheus_config = r#"global:
-  scrape_interval: 15s
-
-scrape_configs:
-  - job_name: 'prometheus'
-    static_configs:
-      - targets: ['localhost:9090']
-"#;
-
-    let prometheus_file = repo.join("prometheus.yml");
-    std::fs::write(prometheus_file, prometheus_config)?;
+    run_compose(ctx, &["down"])?;
 
+    ctx.print_success("✓ Monitoring stack stopped");
     Ok(())
 }
@@ -0,0 +1,557 @@
+//! Monitoring stack components and generated configuration files
+
+use anyhow::{anyhow, Context, Result};
+use devkit_core::AppContext;
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::alerts::{alert_rules, alertmanager_config};
+
+/// A component of the monitoring stack
+pub struct Component {
+    /// Compose service name
+    pub name: &'static str,
+    /// Display label
+    pub label: &'static str,
+    /// What the component provides
+    pub description: &'static str,
+    /// Host port the component listens on
+    pub port: u16,
+    /// HTTP path that returns 2xx once the component is ready
+    pub health_path: &'static str,
+    /// Components that are always started alongside this one
+    pub companions: &'static [&'static str],
+    /// In-network address Prometheus scrapes for this component's metrics
+    pub scrape_target: Option<&'static str>,
+    /// Compose service definition
+    pub service: &'static str,
+}
+
+impl Component {
+    pub fn url(&self) -> String {
+        format!("http://localhost:{}", self.port)
+    }
+
+    pub fn health_url(&self) -> String {
+        format!("{}{}", self.url(), self.health_path)
+    }
+}
+
+/// All components the monitoring stack can include
+pub const COMPONENTS: &[Component] = &[
+    Component {
+        name: "prometheus",
+        label: "Prometheus",
+        description: "metrics",
+        port: 9090,
+        health_path: "/-/ready",
+        companions: &[],
+        scrape_target: Some("localhost:9090"),
+        service: r#"  prometheus:
+    image: prom/prometheus:latest
+    ports:
+      - "9090:9090"
+    volumes:
+      - ./prometheus.yml:/etc/prometheus/prometheus.yml
+      - ./alert-rules.yml:/etc/prometheus/alert-rules.yml
+    command:
+      - '--config.file=/etc/prometheus/prometheus.yml'
+"#,
+    },
+    Component {
+        name: "grafana",
+        label: "Grafana",
+        description: "dashboards",
+        port: 3000,
+        health_path: "/api/health",
+        companions: &[],
+        scrape_target: None,
+        service: r#"  grafana:
+    image: grafana/grafana:latest
+    ports:
+      - "3000:3000"
+    environment:
+      - GF_SECURITY_ADMIN_PASSWORD=admin
+    volumes:
+      - grafana-data:/var/lib/grafana
+      - ./grafana-datasources.yml:/etc/grafana/provisioning/datasources/datasources.yml
+"#,
+    },
+    Component {
+        name: "loki",
+        label: "Loki",
+        description: "logs",
+        port: 3100,
+        health_path: "/ready",
+        companions: &["promtail"],
+        scrape_target: None,
+        service: r#"  loki:
+    image: grafana/loki:latest
+    ports:
+      - "3100:3100"
+    command: -config.file=/etc/loki/local-config.yaml
+"#,
+    },
+    Component {
+        name: "promtail",
+        label: "Promtail",
+        description: "log shipping",
+        port: 9080,
+        health_path: "/ready",
+        companions: &[],
+        scrape_target: None,
+        service: r#"  promtail:
+    image: grafana/promtail:latest
+    ports:
+      - "9080:9080"
+    volumes:
+      - ./promtail.yml:/etc/promtail/config.yml
+      - /var/run/docker.sock:/var/run/docker.sock:ro
+    command: -config.file=/etc/promtail/config.yml
+    depends_on:
+      - loki
+"#,
+    },
+    Component {
+        name: "tempo",
+        label: "Tempo",
+        description: "traces",
+        port: 3200,
+        health_path: "/ready",
+        companions: &[],
+        scrape_target: None,
+        service: r#"  tempo:
+    image: grafana/tempo:latest
+    ports:
+      - "3200:3200"
+    command: [ "-config.file=/etc/tempo.yaml" ]
+"#,
+    },
+    Component {
+        name: "cadvisor",
+        label: "cAdvisor",
+        description: "container metrics",
+        port: 8088,
+        health_path: "/healthz",
+        companions: &[],
+        scrape_target: Some("cadvisor:8080"),
+        service: r#"  cadvisor:
+    image: gcr.io/cadvisor/cadvisor:latest
+    ports:
+      - "8088:8080"
+    volumes:
+      - /:/rootfs:ro
+      - /var/run:/var/run:ro
+      - /sys:/sys:ro
+      - /var/lib/docker/:/var/lib/docker:ro
+    privileged: true
+"#,
+    },
+    Component {
+        name: "node-exporter",
+        label: "Node exporter",
+        description: "host metrics",
+        port: 9100,
+        health_path: "/",
+        companions: &[],
+        scrape_target: Some("node-exporter:9100"),
+        service: r#"  node-exporter:
+    image: prom/node-exporter:latest
+    ports:
+      - "9100:9100"
+    volumes:
+      - /proc:/host/proc:ro
+      - /sys:/host/sys:ro
+    command:
+      - '--path.procfs=/host/proc'
+      - '--path.sysfs=/host/sys'
+"#,
+    },
+    Component {
+        name: "alertmanager",
+        label: "Alertmanager",
+        description: "alerting",
+        port: 9093,
+        health_path: "/-/ready",
+        companions: &["blackbox-exporter"],
+        scrape_target: Some("alertmanager:9093"),
+        service: r#"  alertmanager:
+    image: prom/alertmanager:latest
+    ports:
+      - "9093:9093"
+    volumes:
+      - ./alertmanager.yml:/etc/alertmanager/alertmanager.yml
+    command:
+      - '--config.file=/etc/alertmanager/alertmanager.yml'
+    extra_hosts:
+      - "host.docker.internal:host-gateway"
+"#,
+    },
+    Component {
+        name: "blackbox-exporter",
+        label: "Blackbox exporter",
+        description: "port probes",
+        port: 9115,
+        health_path: "/-/healthy",
+        companions: &[],
+        scrape_target: None,
+        service: r#"  blackbox-exporter:
+    image: prom/blackbox-exporter:latest
+    ports:
+      - "9115:9115"
+    extra_hosts:
+      - "host.docker.internal:host-gateway"
+"#,
+    },
+];
+
+/// Resolve component names (plus their companions) in stack order
+pub fn select_components(names: &[String]) -> Result<Vec<&'static Component>> {
+    let mut selected: Vec<&str> = Vec::new();
+
+    for name in names {
+        let component = COMPONENTS
+            .iter()
+            .find(|c| c.name == name.trim())
+            .ok_or_else(|| {
+                anyhow!(
+                    "Unknown monitoring component '{}'\nAvailable components: {}",
+                    name,
+                    COMPONENTS
+                        .iter()
+                        .map(|c| c.name)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )
+            })?;
+
+        selected.push(component.name);
+        selected.extend(component.companions.iter().copied());
+    }
+
+    Ok(COMPONENTS
+        .iter()
+        .filter(|c| selected.contains(&c.name))
+        .collect())
+}
+
+/// Components enabled by `[monitoring] components` in .dev/config.toml
+pub fn configured_components(ctx: &AppContext) -> Result<Vec<&'static Component>> {
+    select_components(&ctx.config.global.monitoring.components)
+}
+
+/// First line of a compose file devkit generated
+const GENERATED_HEADER: &str = "# Generated by devkit from [monitoring] components";
+
+/// Header line listing the components a generated compose file defines
+const COMPONENTS_HEADER: &str = "# Components: ";
+
+/// Whether the compose file is missing or was generated for other components
+///
+/// Files devkit didn't generate are left alone.
+pub fn compose_outdated(compose_file: &Path, components: &[&Component]) -> bool {
+    let Ok(content) = std::fs::read_to_string(compose_file) else {
+        return true;
+    };
+    let names = component_names(components);
+    content.starts_with(GENERATED_HEADER)
+        && !content
+            .lines()
+            .any(|line| line.strip_prefix(COMPONENTS_HEADER) == Some(names.as_str()))
+}
+
+fn component_names(components: &[&Component]) -> String {
+    components
+        .iter()
+        .map(|c| c.name)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Generate the monitoring compose file for the given components
+pub fn create_monitoring_compose(compose_file: &Path, components: &[&Component]) -> Result<()> {
+    let mut compose_content = format!(
+        "{} in .dev/config.toml\n\
+         # Regenerated when they change; delete this file to regenerate it now\n\
+         {}{}\n\
+         version: '3.8'\n\nservices:\n",
+        GENERATED_HEADER,
+        COMPONENTS_HEADER,
+        component_names(components)
+    );
+
+    for component in components {
+        compose_content.push_str(component.service);
+        compose_content.push('\n');
+    }
+
+    if components.iter().any(|c| c.name == "grafana") {
+        compose_content.push_str("volumes:\n  grafana-data:\n");
+    }
+
+    std::fs::write(compose_file, compose_content)
+        .with_context(|| format!("Failed to write {}", compose_file.display()))?;
+
+    Ok(())
+}
+
+/// Write the config files mounted by the monitoring stack, keeping any existing ones
+pub fn create_missing_config_files(
+    repo: &Path,
+    components: &[&Component],
+    services: &HashMap<String, u16>,
+) -> Result<()> {
+    let has = |name: &str| components.iter().any(|c| c.name == name);
+
+    if has("prometheus") {
+        // Scrape every enabled component that exposes metrics
+        let mut prometheus_config =
+            String::from("global:\n  scrape_interval: 15s\n\nrule_files:\n  - /etc/prometheus/alert-rules.yml\n\n");
+        if has("alertmanager") {
+            prometheus_config.push_str(
+                "alerting:\n  alertmanagers:\n    - static_configs:\n        - targets: ['alertmanager:9093']\n\n",
+            );
+        }
+        prometheus_config.push_str("scrape_configs:\n");
+        for component in components {
+            if let Some(target) = component.scrape_target {
+                prometheus_config.push_str(&format!(
+                    "  - job_name: '{}'\n    static_configs:\n      - targets: ['{}']\n",
+                    component.name, target
+                ));
+            }
+        }
+        if has("blackbox-exporter") && !services.is_empty() {
+            prometheus_config.push_str(&service_probe_job(services));
+        }
+        write_if_missing(&repo.join("prometheus.yml"), &prometheus_config)?;
+
+        let rules = alert_rules(services, has("cadvisor"), has("blackbox-exporter"));
+        write_if_missing(&repo.join("alert-rules.yml"), &rules)?;
+    }
+
+    if has("alertmanager") {
+        write_if_missing(&repo.join("alertmanager.yml"), &alertmanager_config())?;
+    }
+
+    if has("promtail") {
+        // Promtail discovers this project's compose containers and pushes their logs to Loki
+        let promtail_config = format!(
+            r#"server:
+  http_listen_port: 9080
+  grpc_listen_port: 0
+
+positions:
+  filename: /tmp/positions.yaml
+
+clients:
+  - url: http://loki:3100/loki/api/v1/push
+
+scrape_configs:
+  - job_name: docker
+    docker_sd_configs:
+      - host: unix:///var/run/docker.sock
+        refresh_interval: 5s
+        filters:
+          - name: label
+            values: ['com.docker.compose.project={}']
+    relabel_configs:
+      - source_labels: ['__meta_docker_container_name']
+        regex: '/(.*)'
+        target_label: 'container'
+      - source_labels: ['__meta_docker_container_label_com_docker_compose_service']
+        target_label: 'service'
+      - source_labels: ['__meta_docker_container_label_com_docker_compose_project']
+        target_label: 'project'
+"#,
+            compose_project_name(repo)
+        );
+        write_if_missing(&repo.join("promtail.yml"), &promtail_config)?;
+    }
+
+    if has("grafana") {
+        // Provision Grafana datasources so logs and metrics are queryable out of the box
+        let mut datasources_config = String::from("apiVersion: 1\n\ndatasources:\n");
+        if has("prometheus") {
+            datasources_config.push_str(
+                "  - name: Prometheus\n    type: prometheus\n    access: proxy\n    url: http://prometheus:9090\n    isDefault: true\n",
+            );
+        }
+        if has("loki") {
+            datasources_config.push_str(
+                "  - name: Loki\n    type: loki\n    access: proxy\n    url: http://loki:3100\n",
+            );
+        }
+        if has("tempo") {
+            datasources_config.push_str(
+                "  - name: Tempo\n    type: tempo\n    access: proxy\n    url: http://tempo:3200\n",
+            );
+        }
+        write_if_missing(&repo.join("grafana-datasources.yml"), &datasources_config)?;
+    }
+
+    Ok(())
+}
+
+/// Scrape job probing each `[services]` port on the host through the blackbox exporter
+fn service_probe_job(services: &HashMap<String, u16>) -> String {
+    let mut names: Vec<_> = services.keys().collect();
+    names.sort();
+
+    let mut job = String::from(
+        "  - job_name: 'service-ports'\n    metrics_path: /probe\n    params:\n      module: [tcp_connect]\n    static_configs:\n",
+    );
+    for name in names {
+        job.push_str(&format!(
+            "      - targets: ['host.docker.internal:{}']\n        labels:\n          service: '{}'\n",
+            services[name], name
+        ));
+    }
+    job.push_str(
+        "    relabel_configs:\n      - source_labels: [__address__]\n        target_label: __param_target\n      - target_label: __address__\n        replacement: blackbox-exporter:9115\n",
+    );
+    job
+}
+
+/// Enabled components whose scrape job is missing from an existing prometheus.yml
+pub fn missing_scrape_jobs(repo: &Path, components: &[&Component]) -> Vec<&'static str> {
+    let Ok(content) = std::fs::read_to_string(repo.join("prometheus.yml")) else {
+        return Vec::new();
+    };
+
+    components
+        .iter()
+        .filter(|c| c.scrape_target.is_some())
+        .filter(|c| !content.contains(&format!("job_name: '{}'", c.name)))
+        .map(|c| c.name)
+        .collect()
+}
+
+fn write_if_missing(path: &Path, content: &str) -> Result<()> {
+    if !path.exists() {
+        std::fs::write(path, content)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+    }
+    Ok(())
+}
+
+/// Compose project name for the repository (compose derives it from the directory name)
+pub fn compose_project_name(repo: &Path) -> String {
+    repo.file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or_default()
+        .to_lowercase()
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_')
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn test_select_components() {
+        // Companions are added and everything comes back in stack order
+        let selected = select_components(&names(&["alertmanager", "loki", "prometheus"])).unwrap();
+        let selected: Vec<_> = selected.iter().map(|c| c.name).collect();
+        assert_eq!(
+            selected,
+            [
+                "prometheus",
+                "loki",
+                "promtail",
+                "alertmanager",
+                "blackbox-exporter"
+            ]
+        );
+
+        // Naming a companion too doesn't duplicate it
+        let selected = select_components(&names(&["promtail", "loki"])).unwrap();
+        assert_eq!(selected.len(), 2);
+
+        let Err(error) = select_components(&names(&["prometheus", "jaeger"])) else {
+            panic!("expected an unknown component error");
+        };
+        assert!(error
+            .to_string()
+            .starts_with("Unknown monitoring component 'jaeger'"));
+    }
+
+    #[test]
+    fn test_create_monitoring_compose() {
+        let dir =
+            std::env::temp_dir().join(format!("devkit-monitoring-compose-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let compose_file = dir.join("docker-compose.monitoring.yml");
+
+        let components = select_components(&names(&["prometheus", "grafana"])).unwrap();
+        create_monitoring_compose(&compose_file, &components).unwrap();
+        let content = std::fs::read_to_string(&compose_file).unwrap();
+        assert!(content.starts_with(GENERATED_HEADER));
+        assert!(content.contains("# Components: prometheus, grafana\n"));
+        assert!(content.contains("  prometheus:\n    image: prom/prometheus:latest"));
+        assert!(content.contains("  grafana:\n    image: grafana/grafana:latest"));
+        assert!(content.ends_with("volumes:\n  grafana-data:\n"));
+
+        // The grafana volume is only declared alongside grafana
+        let components = select_components(&names(&["loki"])).unwrap();
+        create_monitoring_compose(&compose_file, &components).unwrap();
+        let content = std::fs::read_to_string(&compose_file).unwrap();
+        assert!(content.contains("  promtail:\n"));
+        assert!(!content.contains("grafana-data"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_missing_scrape_jobs() {
+        let dir =
+            std::env::temp_dir().join(format!("devkit-monitoring-scrape-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let services = HashMap::from([("api".to_string(), 8080)]);
+
+        let components = select_components(&names(&["prometheus", "alertmanager"])).unwrap();
+        assert!(missing_scrape_jobs(&dir, &components).is_empty());
+        create_missing_config_files(&dir, &components, &services).unwrap();
+        let prometheus = std::fs::read_to_string(dir.join("prometheus.yml")).unwrap();
+        assert!(prometheus.contains("targets: ['alertmanager:9093']"));
+        assert!(prometheus.contains("targets: ['host.docker.internal:8080']"));
+        assert!(missing_scrape_jobs(&dir, &components).is_empty());
+
+        // prometheus.yml is kept, so components enabled later lack a job
+        let components =
+            select_components(&names(&["prometheus", "alertmanager", "cadvisor"])).unwrap();
+        assert_eq!(missing_scrape_jobs(&dir, &components), ["cadvisor"]);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_compose_outdated() {
+        let dir = std::env::temp_dir().join(format!("devkit-monitoring-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let compose_file = dir.join("docker-compose.monitoring.yml");
+        let metrics = select_components(&names(&["prometheus", "grafana"])).unwrap();
+        let logs = select_components(&names(&["prometheus", "grafana", "loki"])).unwrap();
+
+        assert!(compose_outdated(&compose_file, &metrics));
+        create_monitoring_compose(&compose_file, &metrics).unwrap();
+        assert!(!compose_outdated(&compose_file, &metrics));
+        assert!(compose_outdated(&compose_file, &logs));
+
+        // Hand-written files are kept
+        std::fs::write(
+            &compose_file,
+            "services:\n  prometheus:\n    image: prom/prometheus\n",
+        )
+        .unwrap();
+        assert!(!compose_outdated(&compose_file, &logs));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
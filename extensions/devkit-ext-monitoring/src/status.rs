@@ -0,0 +1,70 @@
+//! Monitoring stack health reporting
+
+use anyhow::Result;
+use console::style;
+use devkit_core::AppContext;
+use std::time::Duration;
+
+use crate::compose::running_services;
+use crate::stack::{configured_components, Component};
+
+/// Show which monitoring components are running and ready
+pub fn monitoring_status(ctx: &AppContext) -> Result<()> {
+    ctx.print_header("Monitoring Stack Status");
+    println!();
+
+    let compose_file = &ctx.config.global.monitoring.compose_file;
+    if !ctx.repo.join(compose_file).exists() {
+        ctx.print_warning(&format!("{} not found", compose_file));
+        ctx.print_info("Start the monitoring stack to generate it");
+        return Ok(());
+    }
+
+    let components = configured_components(ctx)?;
+    let running = running_services(ctx)?;
+    let mut ready_count = 0;
+
+    for component in &components {
+        let is_running = running.iter().any(|s| s == component.name);
+        let is_ready = is_running && is_ready(component);
+
+        let (icon, state) = if is_ready {
+            ready_count += 1;
+            (style("✓").green(), style("up").green())
+        } else if is_running {
+            (style("!").yellow(), style("not ready").yellow())
+        } else {
+            (style("✗").red(), style("down").red())
+        };
+
+        println!(
+            "  {} {:<12} {:<10} {}",
+            icon,
+            component.label,
+            state,
+            component.url()
+        );
+    }
+
+    println!();
+    if ready_count == components.len() {
+        ctx.print_success("✓ All monitoring components are up");
+    } else {
+        ctx.print_warning(&format!(
+            "{}/{} monitoring components are up",
+            ready_count,
+            components.len()
+        ));
+    }
+
+    Ok(())
+}
+
+/// Check a component's readiness endpoint
+fn is_ready(component: &Component) -> bool {
+    let agent = ureq::AgentBuilder::new()
+        .timeout(Duration::from_secs(2))
+        .build();
+
+    agent.get(&component.health_url()).call().is_ok()
+}
@@ -0,0 +1,55 @@
+//! `devkit nix` subcommand
+
+use anyhow::Result;
+use clap::{Args, Command, FromArgMatches, Subcommand};
+use devkit_core::AppContext;
+
+use crate::{run, shell, status};
+
+#[derive(Args)]
+pub struct NixArgs {
+    #[command(subcommand)]
+    action: Option<NixAction>,
+}
+
+#[derive(Subcommand)]
+enum NixAction {
+    /// Check that the current shell matches the flake's dev shell (default)
+    Status {
+        /// devShell to check (default: default)
+        #[arg(long)]
+        shell: Option<String>,
+    },
+    /// Enter the flake's dev shell
+    Shell {
+        /// devShell to enter (default: default)
+        #[arg(long)]
+        shell: Option<String>,
+    },
+    /// Run a devkit command inside the dev shell, e.g. `devkit nix run cmd build`
+    Run {
+        /// devShell to run in (default: default)
+        #[arg(long)]
+        shell: Option<String>,
+        /// devkit arguments (none opens the menu)
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+}
+
+pub fn command() -> Command {
+    NixArgs::augment_args(
+        Command::new("nix").about("Enter the flake's dev shell and run devkit commands inside it"),
+    )
+}
+
+pub fn handle(ctx: &AppContext, matches: &clap::ArgMatches) -> Result<()> {
+    let args = NixArgs::from_arg_matches(matches)?;
+
+    match args.action {
+        Some(NixAction::Status { shell }) => status(ctx, shell.as_deref()),
+        None => status(ctx, None),
+        Some(NixAction::Shell { shell: name }) => shell(ctx, name.as_deref()),
+        Some(NixAction::Run { shell, args }) => run(ctx, shell.as_deref(), &args),
+    }
+}
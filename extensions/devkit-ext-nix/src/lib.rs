@@ -0,0 +1,193 @@
+//! Nix flake extension for devkit
+//!
+//! For repos with a `flake.nix`: enter a dev shell, run devkit commands inside
+//! `nix develop -c`, and check that the current shell has the dev shell's packages on
+//! PATH. Flakes are enabled per invocation, so nix doesn't need them turned on in
+//! nix.conf.
+
+mod cli;
+
+use anyhow::{bail, Context, Result};
+use devkit_core::oplog::LoggedCommand;
+use devkit_core::utils::cmd_exists;
+use devkit_core::{AppContext, Extension, MenuItem};
+use serde_json::Value;
+use std::env;
+use std::path::Path;
+use std::process::Command;
+
+pub struct NixExtension;
+
+impl Extension for NixExtension {
+    fn name(&self) -> &str {
+        "nix"
+    }
+
+    fn is_available(&self, ctx: &AppContext) -> bool {
+        ctx.repo.join("flake.nix").exists()
+    }
+
+    fn unavailable_reason(&self, _ctx: &AppContext) -> Option<String> {
+        Some("no flake.nix found".to_string())
+    }
+
+    fn menu_items(&self, _ctx: &AppContext) -> Vec<MenuItem> {
+        use devkit_core::DevkitError;
+        vec![
+            MenuItem {
+                label: "Enter dev shell".to_string(),
+                group: Some("❄️ Nix".to_string()),
+                handler: Box::new(|ctx| shell(ctx, None).map_err(DevkitError::from)),
+            },
+            MenuItem {
+                label: "Check dev shell".to_string(),
+                group: Some("❄️ Nix".to_string()),
+                handler: Box::new(|ctx| status(ctx, None).map_err(DevkitError::from)),
+            },
+        ]
+    }
+
+    fn subcommands(&self) -> Vec<clap::Command> {
+        vec![cli::command()]
+    }
+
+    fn handle_cli(
+        &self,
+        ctx: &AppContext,
+        name: &str,
+        matches: &clap::ArgMatches,
+    ) -> Option<devkit_core::Result<()>> {
+        (name == "nix").then(|| cli::handle(ctx, matches).map_err(Into::into))
+    }
+}
+
+/// `.#<shell>`, defaulting to the flake's default dev shell
+fn installable(shell: Option<&str>) -> String {
+    format!(".#{}", shell.unwrap_or("default"))
+}
+
+/// `nix` in the repo with flakes enabled
+fn nix(repo: &Path) -> Result<Command> {
+    if !cmd_exists("nix") {
+        bail!("nix not installed. Install it from https://nixos.org/download");
+    }
+    let mut cmd = Command::new("nix");
+    cmd.args(["--extra-experimental-features", "nix-command flakes"])
+        .current_dir(repo);
+    Ok(cmd)
+}
+
+/// Open an interactive dev shell
+pub fn shell(ctx: &AppContext, name: Option<&str>) -> Result<()> {
+    if env::var_os("IN_NIX_SHELL").is_some() {
+        ctx.print_warning("Already inside a Nix shell");
+    }
+    ctx.print_info(&format!("Entering {} (exit to return)", installable(name)));
+
+    let status = nix(&ctx.repo)?
+        .args(["develop", &installable(name)])
+        .logged_status()
+        .context("Failed to run nix develop")?;
+    // The shell's exit code is whatever its last command returned
+    if !status.success() && status.code().is_none() {
+        bail!("nix develop failed ({})", status);
+    }
+    Ok(())
+}
+
+/// Run devkit with `args` inside the dev shell; with no args, the menu
+pub fn run(ctx: &AppContext, name: Option<&str>, args: &[String]) -> Result<()> {
+    let devkit = env::current_exe().context("Failed to locate the devkit binary")?;
+    let status = nix(&ctx.repo)?
+        .args(["develop", &installable(name), "-c"])
+        .arg(devkit)
+        .args(args)
+        .logged_status()
+        .context("Failed to run nix develop")?;
+    if !status.success() {
+        bail!("Command failed in {} ({})", installable(name), status);
+    }
+    Ok(())
+}
+
+/// Check that the current shell has the dev shell's packages on PATH
+pub fn status(ctx: &AppContext, name: Option<&str>) -> Result<()> {
+    ctx.print_header("Nix dev shell");
+    println!();
+
+    match env::var("IN_NIX_SHELL") {
+        Ok(kind) => ctx.print_success(&format!("✓ Inside a Nix shell ({})", kind)),
+        Err(_) => ctx.print_warning("✗ Not inside a Nix shell"),
+    }
+
+    ctx.print_info(&format!("Evaluating {}...", installable(name)));
+    let output = nix(&ctx.repo)?
+        .args(["print-dev-env", "--json", &installable(name)])
+        .logged_output()
+        .context("Failed to run nix print-dev-env")?;
+    if !output.status.success() {
+        bail!(
+            "nix print-dev-env failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    let dev_path = dev_env_path(&String::from_utf8_lossy(&output.stdout))?;
+
+    let current = env::var("PATH").unwrap_or_default();
+    let missing = missing_store_paths(&dev_path, &current);
+    if missing.is_empty() {
+        ctx.print_success(&format!("✓ Shell matches {}", installable(name)));
+        return Ok(());
+    }
+
+    for path in &missing {
+        ctx.print_warning(&format!("✗ {} isn't on PATH", path));
+    }
+    bail!(
+        "This shell doesn't match {}. Run: devkit nix shell",
+        installable(name)
+    )
+}
+
+/// PATH from `nix print-dev-env --json`
+fn dev_env_path(json: &str) -> Result<String> {
+    let env: Value = serde_json::from_str(json).context("Invalid nix print-dev-env output")?;
+    Ok(env["variables"]["PATH"]["value"]
+        .as_str()
+        .unwrap_or_default()
+        .to_string())
+}
+
+/// Nix store entries of the dev shell's PATH that aren't on the current PATH
+fn missing_store_paths<'a>(dev_path: &'a str, current: &str) -> Vec<&'a str> {
+    let current: Vec<&str> = current.split(':').collect();
+    dev_path
+        .split(':')
+        .filter(|dir| dir.starts_with("/nix/store/") && !current.contains(dir))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_store_paths() {
+        let json = r#"{"variables": {
+            "PATH": {"type": "exported", "value": "/nix/store/abc-nodejs-20/bin:/nix/store/def-cargo-1.77/bin"},
+            "name": {"type": "var", "value": "nix-shell"}
+        }}"#;
+        let dev_path = dev_env_path(json).unwrap();
+
+        assert!(missing_store_paths(
+            &dev_path,
+            "/nix/store/def-cargo-1.77/bin:/nix/store/abc-nodejs-20/bin:/usr/bin"
+        )
+        .is_empty());
+        assert_eq!(
+            missing_store_paths(&dev_path, "/nix/store/abc-nodejs-20/bin:/usr/bin"),
+            ["/nix/store/def-cargo-1.77/bin"]
+        );
+        assert_eq!(installable(None), ".#default");
+    }
+}
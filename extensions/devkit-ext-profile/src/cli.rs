@@ -0,0 +1,62 @@
+//! `devkit profile` subcommand
+
+use anyhow::Result;
+use clap::{Args, Command, FromArgMatches, Subcommand};
+use devkit_core::AppContext;
+
+use crate::{list_reports, list_targets, open_report, profile, Profiler};
+
+#[derive(Args)]
+pub struct ProfileArgs {
+    #[command(subcommand)]
+    action: Option<ProfileAction>,
+}
+
+#[derive(Subcommand)]
+enum ProfileAction {
+    /// List binaries and services that can be profiled, and installed profilers (default)
+    Targets,
+    /// Run a target under a profiler
+    Run {
+        /// Binary or service name (asks when omitted)
+        target: Option<String>,
+        /// Profiler to use (defaults to the first installed one for the target)
+        #[arg(long = "with", value_enum)]
+        profiler: Option<Profiler>,
+        /// Don't open the report afterwards
+        #[arg(long)]
+        no_open: bool,
+        /// Arguments passed to the program
+        #[arg(last = true)]
+        args: Vec<String>,
+    },
+    /// List collected reports, newest first
+    Reports,
+    /// Open a report
+    Open {
+        /// Report directory name (asks when omitted)
+        report: Option<String>,
+    },
+}
+
+pub fn command() -> Command {
+    ProfileArgs::augment_args(
+        Command::new("profile").about("Profile Rust binaries and Node services"),
+    )
+}
+
+pub fn handle(ctx: &AppContext, matches: &clap::ArgMatches) -> Result<()> {
+    let args = ProfileArgs::from_arg_matches(matches)?;
+
+    match args.action {
+        Some(ProfileAction::Targets) | None => list_targets(ctx),
+        Some(ProfileAction::Run {
+            target,
+            profiler,
+            no_open,
+            args,
+        }) => profile(ctx, target.as_deref(), profiler, &args, !no_open),
+        Some(ProfileAction::Reports) => list_reports(ctx),
+        Some(ProfileAction::Open { report }) => open_report(ctx, report.as_deref()),
+    }
+}
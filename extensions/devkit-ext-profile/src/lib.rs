@@ -0,0 +1,499 @@
+//! Profiling extension for devkit
+//!
+//! Runs a Rust binary under samply or cargo-flamegraph, or a Node service under
+//! clinic.js or 0x, and keeps the report in `.dev/profiles/<target>-<timestamp>/`.
+//! Targets are discovered from the packages (see [`targets`]); the first installed
+//! profiler for the target's language is used unless one is picked with `--with`.
+//!
+//! Rust binaries are built in release mode with debug info so stacks have symbols.
+
+mod cli;
+pub mod targets;
+
+use anyhow::{anyhow, bail, Context, Result};
+use devkit_core::oplog::LoggedCommand;
+use devkit_core::utils::{cmd_exists, open_in_browser};
+use devkit_core::{AppContext, Extension, MenuItem};
+use dialoguer::Select;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use targets::{discover, Target, TargetKind};
+
+const PROFILES_DIR: &str = ".dev/profiles";
+
+pub struct ProfileExtension;
+
+impl Extension for ProfileExtension {
+    fn name(&self) -> &str {
+        "profile"
+    }
+
+    fn is_available(&self, ctx: &AppContext) -> bool {
+        !discover(&ctx.config).is_empty()
+    }
+
+    fn unavailable_reason(&self, _ctx: &AppContext) -> Option<String> {
+        Some("no Rust binaries or Node services found".to_string())
+    }
+
+    fn menu_items(&self, _ctx: &AppContext) -> Vec<MenuItem> {
+        use devkit_core::DevkitError;
+        vec![
+            MenuItem {
+                label: "Profile a binary or service".to_string(),
+                group: Some("🔥 Profile".to_string()),
+                handler: Box::new(|ctx| {
+                    profile(ctx, None, None, &[], true).map_err(DevkitError::from)
+                }),
+            },
+            MenuItem {
+                label: "Open a report".to_string(),
+                group: Some("🔥 Profile".to_string()),
+                handler: Box::new(|ctx| open_report(ctx, None).map_err(DevkitError::from)),
+            },
+        ]
+    }
+
+    fn subcommands(&self) -> Vec<clap::Command> {
+        vec![cli::command()]
+    }
+
+    fn handle_cli(
+        &self,
+        ctx: &AppContext,
+        name: &str,
+        matches: &clap::ArgMatches,
+    ) -> Option<devkit_core::Result<()>> {
+        (name == "profile").then(|| cli::handle(ctx, matches).map_err(Into::into))
+    }
+}
+
+/// A supported profiler
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum Profiler {
+    Samply,
+    Flamegraph,
+    Clinic,
+    #[value(name = "0x")]
+    ZeroX,
+}
+
+impl Profiler {
+    /// In order of preference
+    const ALL: [Profiler; 4] = [
+        Profiler::Samply,
+        Profiler::Flamegraph,
+        Profiler::Clinic,
+        Profiler::ZeroX,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Profiler::Samply => "samply",
+            Profiler::Flamegraph => "cargo-flamegraph",
+            Profiler::Clinic => "clinic",
+            Profiler::ZeroX => "0x",
+        }
+    }
+
+    fn language(self) -> &'static str {
+        match self {
+            Profiler::Samply | Profiler::Flamegraph => "rust",
+            Profiler::Clinic | Profiler::ZeroX => "node",
+        }
+    }
+
+    fn install_hint(self) -> &'static str {
+        match self {
+            Profiler::Samply => "cargo install samply",
+            Profiler::Flamegraph => "cargo install flamegraph",
+            Profiler::Clinic => "npm install -g clinic",
+            Profiler::ZeroX => "npm install -g 0x",
+        }
+    }
+
+    pub fn is_installed(self) -> bool {
+        cmd_exists(self.name())
+    }
+
+    /// The first installed profiler for a language
+    fn for_language(language: &str) -> Result<Profiler> {
+        let candidates: Vec<Profiler> = Self::ALL
+            .into_iter()
+            .filter(|profiler| profiler.language() == language)
+            .collect();
+        candidates
+            .iter()
+            .copied()
+            .find(|profiler| profiler.is_installed())
+            .ok_or_else(|| {
+                anyhow!(
+                    "No {} profiler installed. Install one of: {}",
+                    language,
+                    candidates
+                        .iter()
+                        .map(|p| p.install_hint())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )
+            })
+    }
+}
+
+/// List discovered targets and installed profilers
+pub fn list_targets(ctx: &AppContext) -> Result<()> {
+    ctx.print_header("Profiling Targets");
+    println!();
+
+    let targets = discover(&ctx.config);
+    if targets.is_empty() {
+        ctx.print_info("No Rust binaries or Node services found");
+    }
+    for target in &targets {
+        let dir = target
+            .dir
+            .strip_prefix(&ctx.repo)
+            .unwrap_or(&target.dir)
+            .display()
+            .to_string();
+        println!(
+            "  {:<24} {:<6} {}",
+            target.name,
+            target.language(),
+            if dir.is_empty() { "." } else { &dir }
+        );
+    }
+
+    println!();
+    for profiler in Profiler::ALL {
+        if profiler.is_installed() {
+            println!("  ✓ {}", profiler.name());
+        } else {
+            println!("  ✗ {} ({})", profiler.name(), profiler.install_hint());
+        }
+    }
+    Ok(())
+}
+
+fn pick_target(ctx: &AppContext, name: Option<&str>) -> Result<Target> {
+    let targets = discover(&ctx.config);
+    if let Some(name) = name {
+        return targets
+            .into_iter()
+            .find(|target| target.name == name)
+            .ok_or_else(|| {
+                anyhow!(
+                    "No binary or service named '{}' (see devkit profile targets)",
+                    name
+                )
+            });
+    }
+    if targets.is_empty() {
+        bail!("No Rust binaries or Node services found");
+    }
+
+    ctx.require_interactive("Choosing a target", "pass the target name")?;
+    let items: Vec<String> = targets
+        .iter()
+        .map(|t| format!("{} ({})", t.name, t.language()))
+        .collect();
+    let selection = Select::with_theme(&*ctx.theme())
+        .with_prompt("Profile which target?")
+        .items(&items)
+        .default(0)
+        .interact()?;
+    Ok(targets[selection].clone())
+}
+
+/// Run a target under a profiler and keep the report; `open` opens it afterwards
+pub fn profile(
+    ctx: &AppContext,
+    target: Option<&str>,
+    profiler: Option<Profiler>,
+    args: &[String],
+    open: bool,
+) -> Result<()> {
+    let target = pick_target(ctx, target)?;
+    let profiler = match profiler {
+        Some(profiler) if profiler.language() != target.language() => bail!(
+            "{} profiles {} targets but {} is {}",
+            profiler.name(),
+            profiler.language(),
+            target.name,
+            target.language()
+        ),
+        Some(profiler) if !profiler.is_installed() => bail!(
+            "{} isn't installed. Install it with: {}",
+            profiler.name(),
+            profiler.install_hint()
+        ),
+        Some(profiler) => profiler,
+        None => Profiler::for_language(target.language())?,
+    };
+
+    // Scoped npm names (`@acme/api`) become `acme-api`
+    let report = ctx.repo.join(PROFILES_DIR).join(format!(
+        "{}-{}",
+        target.name.trim_start_matches('@').replace('/', "-"),
+        chrono::Local::now().format("%Y%m%d-%H%M%S")
+    ));
+    fs::create_dir_all(&report)?;
+
+    ctx.print_info(&format!(
+        "Profiling {} with {} (stop it with Ctrl+C to write the report)",
+        target.name,
+        profiler.name()
+    ));
+    let mut cmd = profiler_command(&target, profiler, &report, args, open)?;
+    // The profiled program gets Ctrl+C; the profiler then writes its report
+    let status = cmd
+        .logged_status()
+        .context("Failed to start the profiler")?;
+    if !status.success() && status.code() != Some(130) {
+        ctx.print_warning(&format!("{} exited with {}", profiler.name(), status));
+    }
+
+    let Some(file) = report_file(&report) else {
+        fs::remove_dir_all(&report).ok();
+        bail!("{} didn't write a report", profiler.name());
+    };
+    ctx.print_success(&format!(
+        "✓ Report saved to {}",
+        file.strip_prefix(&ctx.repo).unwrap_or(&file).display()
+    ));
+
+    // samply, clinic and 0x open their own viewers
+    if open && profiler == Profiler::Flamegraph {
+        open_in_browser(&file.to_string_lossy())?;
+    }
+    Ok(())
+}
+
+fn profiler_command(
+    target: &Target,
+    profiler: Profiler,
+    report: &Path,
+    args: &[String],
+    open: bool,
+) -> Result<Command> {
+    let mut cmd = match (&target.kind, profiler) {
+        (TargetKind::Rust { bin }, Profiler::Samply) => {
+            let binary = build_release(&target.dir, bin)?;
+            let mut cmd = Command::new("samply");
+            cmd.arg("record")
+                .arg("-o")
+                .arg(report.join("profile.json.gz"));
+            if !open {
+                cmd.arg("--save-only");
+            }
+            cmd.arg(binary);
+            cmd
+        }
+        (TargetKind::Rust { bin }, Profiler::Flamegraph) => {
+            let mut cmd = Command::new("cargo");
+            cmd.args(["flamegraph", "--bin", bin, "-o"])
+                .arg(report.join("flamegraph.svg"))
+                .arg("--")
+                .env("CARGO_PROFILE_RELEASE_DEBUG", "true");
+            cmd
+        }
+        (TargetKind::Node { entry }, Profiler::Clinic) => {
+            let mut cmd = Command::new("clinic");
+            cmd.args(["flame", "--dest"])
+                .arg(report)
+                .arg(format!("--open={}", open))
+                .args(["--", "node", entry]);
+            cmd
+        }
+        (TargetKind::Node { entry }, Profiler::ZeroX) => {
+            let mut cmd = Command::new("0x");
+            cmd.arg("--output-dir").arg(report);
+            if open {
+                cmd.arg("--open");
+            }
+            cmd.arg(entry);
+            cmd
+        }
+        _ => bail!("{} can't profile {}", profiler.name(), target.name),
+    };
+    cmd.args(args).current_dir(&target.dir);
+    Ok(cmd)
+}
+
+/// Build a binary in release mode with debug info; returns its path
+fn build_release(dir: &Path, bin: &str) -> Result<PathBuf> {
+    let status = Command::new("cargo")
+        .args(["build", "--release", "--bin", bin])
+        .current_dir(dir)
+        .env("CARGO_PROFILE_RELEASE_DEBUG", "true")
+        .logged_status()
+        .context("Failed to run cargo build")?;
+    if !status.success() {
+        bail!("cargo build --release --bin {} failed", bin);
+    }
+
+    let output = Command::new("cargo")
+        .args(["metadata", "--format-version", "1", "--no-deps"])
+        .current_dir(dir)
+        .logged_output()
+        .context("Failed to run cargo metadata")?;
+    let metadata: serde_json::Value =
+        serde_json::from_slice(&output.stdout).context("Unexpected cargo metadata output")?;
+    let target_dir = metadata["target_directory"]
+        .as_str()
+        .ok_or_else(|| anyhow!("cargo metadata didn't report a target directory"))?;
+    Ok(Path::new(target_dir).join("release").join(format!(
+        "{}{}",
+        bin,
+        std::env::consts::EXE_SUFFIX
+    )))
+}
+
+/// The file to open in a report directory: an HTML or SVG report, else the first file
+fn report_file(report: &Path) -> Option<PathBuf> {
+    let mut files: Vec<PathBuf> = walk(report);
+    files.sort();
+    files
+        .iter()
+        .find(|file| {
+            file.extension()
+                .is_some_and(|ext| ext == "html" || ext == "svg")
+        })
+        .or(files.first())
+        .cloned()
+}
+
+fn walk(dir: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir).into_iter().flatten().flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            files.extend(walk(&path));
+        } else {
+            files.push(path);
+        }
+    }
+    files
+}
+
+/// Collected reports, newest first
+pub fn reports(repo: &Path) -> Vec<PathBuf> {
+    let mut reports: Vec<PathBuf> = fs::read_dir(repo.join(PROFILES_DIR))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .collect();
+    reports.sort_by_key(|path| std::cmp::Reverse(recorded_at(path)));
+    reports
+}
+
+/// The `%Y%m%d-%H%M%S` timestamp a report directory name ends in
+fn recorded_at(report: &Path) -> String {
+    let name = report.file_name().unwrap_or_default().to_string_lossy();
+    let chars: Vec<char> = name.chars().collect();
+    chars[chars.len().saturating_sub(15)..].iter().collect()
+}
+
+/// List collected reports
+pub fn list_reports(ctx: &AppContext) -> Result<()> {
+    ctx.print_header("Profiles");
+    println!();
+    let reports = reports(&ctx.repo);
+    if reports.is_empty() {
+        ctx.print_info("No reports yet. Record one with: devkit profile run");
+    }
+    for report in reports {
+        if let Some(file) = report_file(&report) {
+            println!(
+                "  {}",
+                file.strip_prefix(&ctx.repo).unwrap_or(&file).display()
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Open a report (by directory name), or pick one
+pub fn open_report(ctx: &AppContext, name: Option<&str>) -> Result<()> {
+    let reports = reports(&ctx.repo);
+    let report = match name {
+        Some(name) => reports
+            .into_iter()
+            .find(|report| report.file_name().is_some_and(|n| n == name))
+            .ok_or_else(|| anyhow!("No report named '{}'", name))?,
+        None => {
+            if reports.is_empty() {
+                bail!("No reports yet. Record one with: devkit profile run");
+            }
+            ctx.require_interactive("Choosing a report", "pass the report name")?;
+            let items: Vec<String> = reports
+                .iter()
+                .map(|r| {
+                    r.file_name()
+                        .unwrap_or_default()
+                        .to_string_lossy()
+                        .to_string()
+                })
+                .collect();
+            let selection = Select::with_theme(&*ctx.theme())
+                .with_prompt("Open which report?")
+                .items(&items)
+                .default(0)
+                .interact()?;
+            reports[selection].clone()
+        }
+    };
+
+    let file = report_file(&report).ok_or_else(|| anyhow!("{} is empty", report.display()))?;
+    if file.to_string_lossy().ends_with(".json.gz") && cmd_exists("samply") {
+        Command::new("samply")
+            .arg("load")
+            .arg(&file)
+            .logged_status()
+            .context("Failed to run samply load")?;
+        return Ok(());
+    }
+    open_in_browser(&file.to_string_lossy())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reports_newest_first_and_report_file() {
+        let repo =
+            std::env::temp_dir().join(format!("devkit-profile-reports-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&repo);
+        let profiles = repo.join(PROFILES_DIR);
+        fs::create_dir_all(profiles.join("api-20240101-120000")).unwrap();
+        fs::create_dir_all(profiles.join("web-20240301-090000/.clinic")).unwrap();
+        fs::write(profiles.join("api-20240101-120000/flamegraph.svg"), "").unwrap();
+        fs::write(
+            profiles.join("web-20240301-090000/.clinic/1.clinic-flame"),
+            "",
+        )
+        .unwrap();
+        fs::write(
+            profiles.join("web-20240301-090000/.clinic/1.clinic-flame.html"),
+            "",
+        )
+        .unwrap();
+
+        let reports = reports(&repo);
+        let names: Vec<String> = reports
+            .iter()
+            .map(|r| r.file_name().unwrap().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(names, ["web-20240301-090000", "api-20240101-120000"]);
+        assert!(report_file(&reports[0])
+            .unwrap()
+            .ends_with("1.clinic-flame.html"));
+
+        fs::remove_dir_all(repo).ok();
+    }
+}
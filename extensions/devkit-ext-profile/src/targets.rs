@@ -0,0 +1,188 @@
+//! Profiling target discovery
+//!
+//! Rust binaries come from each package's Cargo.toml (`[[bin]]` entries, `src/main.rs`
+//! and `src/bin/`). Node services come from package.json: the `main` entry, or the file
+//! a `node <file>` start script runs.
+
+use devkit_core::Config;
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TargetKind {
+    /// A Cargo binary target
+    Rust { bin: String },
+    /// A Node entry point, relative to the package
+    Node { entry: String },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Target {
+    pub name: String,
+    /// Package directory the profiler runs in
+    pub dir: PathBuf,
+    pub kind: TargetKind,
+}
+
+impl Target {
+    pub fn language(&self) -> &'static str {
+        match self.kind {
+            TargetKind::Rust { .. } => "rust",
+            TargetKind::Node { .. } => "node",
+        }
+    }
+}
+
+/// Every Rust binary and Node service in the repo, sorted by name
+pub fn discover(config: &Config) -> Vec<Target> {
+    let mut dirs: Vec<&Path> = config.packages.values().map(|p| p.path.as_path()).collect();
+    dirs.push(config.repo_root.as_path());
+    dirs.sort();
+    dirs.dedup();
+
+    let mut targets = Vec::new();
+    for dir in dirs {
+        targets.extend(rust_targets(dir));
+        targets.extend(node_target(dir));
+    }
+    targets.sort_by(|a, b| a.name.cmp(&b.name));
+    targets.dedup_by(|a, b| a.name == b.name && a.kind == b.kind);
+    targets
+}
+
+fn rust_targets(dir: &Path) -> Vec<Target> {
+    let Ok(contents) = fs::read_to_string(dir.join("Cargo.toml")) else {
+        return Vec::new();
+    };
+    let Ok(manifest) = contents.parse::<toml::Table>() else {
+        return Vec::new();
+    };
+    let Some(package) = manifest
+        .get("package")
+        .and_then(|p| p.get("name"))
+        .and_then(|n| n.as_str())
+    else {
+        // A virtual workspace manifest has no binaries of its own
+        return Vec::new();
+    };
+
+    let mut bins: Vec<String> = manifest
+        .get("bin")
+        .and_then(|bins| bins.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|bin| bin.get("name")?.as_str().map(str::to_string))
+        .collect();
+    if dir.join("src/main.rs").exists() {
+        bins.push(package.to_string());
+    }
+    if let Ok(entries) = fs::read_dir(dir.join("src/bin")) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let name = if path.extension().is_some_and(|ext| ext == "rs") {
+                path.file_stem()
+            } else if path.join("main.rs").exists() {
+                path.file_name()
+            } else {
+                None
+            };
+            if let Some(name) = name {
+                bins.push(name.to_string_lossy().to_string());
+            }
+        }
+    }
+    bins.sort();
+    bins.dedup();
+
+    bins.into_iter()
+        .map(|bin| Target {
+            name: bin.clone(),
+            dir: dir.to_path_buf(),
+            kind: TargetKind::Rust { bin },
+        })
+        .collect()
+}
+
+fn node_target(dir: &Path) -> Option<Target> {
+    let contents = fs::read_to_string(dir.join("package.json")).ok()?;
+    let package: Value = serde_json::from_str(&contents).ok()?;
+
+    let entry = package["main"].as_str().map(str::to_string).or_else(|| {
+        let start = package["scripts"]["start"].as_str()?;
+        let mut words = start.split_whitespace();
+        (words.next()? == "node")
+            .then(|| words.find(|word| !word.starts_with('-')))
+            .flatten()
+            .map(str::to_string)
+    })?;
+    if !dir.join(&entry).exists() {
+        return None;
+    }
+
+    let name = package["name"]
+        .as_str()
+        .map(str::to_string)
+        .or_else(|| Some(dir.file_name()?.to_string_lossy().to_string()))?;
+    Some(Target {
+        name,
+        dir: dir.to_path_buf(),
+        kind: TargetKind::Node { entry },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("devkit-profile-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_rust_targets() {
+        let dir = temp_dir("rust");
+        fs::create_dir_all(dir.join("src/bin/worker")).unwrap();
+        fs::write(
+            dir.join("Cargo.toml"),
+            "[package]\nname = \"api\"\n\n[[bin]]\nname = \"migrate\"\npath = \"tools/migrate.rs\"\n",
+        )
+        .unwrap();
+        fs::write(dir.join("src/main.rs"), "").unwrap();
+        fs::write(dir.join("src/bin/seed.rs"), "").unwrap();
+        fs::write(dir.join("src/bin/worker/main.rs"), "").unwrap();
+
+        let names: Vec<String> = rust_targets(&dir).into_iter().map(|t| t.name).collect();
+        assert_eq!(names, ["api", "migrate", "seed", "worker"]);
+
+        fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn test_node_target_from_start_script() {
+        let dir = temp_dir("node");
+        fs::write(
+            dir.join("package.json"),
+            r#"{"name": "web", "scripts": {"start": "node --enable-source-maps dist/server.js"}}"#,
+        )
+        .unwrap();
+        assert!(node_target(&dir).is_none());
+
+        fs::create_dir_all(dir.join("dist")).unwrap();
+        fs::write(dir.join("dist/server.js"), "").unwrap();
+        let target = node_target(&dir).unwrap();
+        assert_eq!(target.name, "web");
+        assert_eq!(
+            target.kind,
+            TargetKind::Node {
+                entry: "dist/server.js".to_string()
+            }
+        );
+
+        fs::remove_dir_all(dir).ok();
+    }
+}
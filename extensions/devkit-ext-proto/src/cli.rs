@@ -0,0 +1,57 @@
+//! `devkit proto` subcommand
+
+use anyhow::Result;
+use clap::{Args, Command, FromArgMatches, Subcommand};
+use devkit_core::AppContext;
+
+use crate::{breaking, generate, lint, list};
+
+#[derive(Args)]
+pub struct ProtoArgs {
+    #[command(subcommand)]
+    action: Option<ProtoAction>,
+}
+
+#[derive(Subcommand)]
+enum ProtoAction {
+    /// List protobuf modules (default)
+    List,
+    /// Generate code (buf generate, or protoc with the [proto] plugins)
+    Generate {
+        /// Module (package name; default: all)
+        module: Option<String>,
+    },
+    /// Lint .proto files
+    Lint {
+        /// Module (package name; default: all)
+        module: Option<String>,
+    },
+    /// Check for breaking changes with buf
+    Breaking {
+        /// Module (package name; default: all)
+        module: Option<String>,
+        /// buf input to compare against (default: the [git] default_pr_base branch)
+        #[arg(long)]
+        against: Option<String>,
+    },
+}
+
+pub fn command() -> Command {
+    ProtoArgs::augment_args(
+        Command::new("proto")
+            .about("Generate, lint and check protobuf definitions (buf or protoc)"),
+    )
+}
+
+pub fn handle(ctx: &AppContext, matches: &clap::ArgMatches) -> Result<()> {
+    let args = ProtoArgs::from_arg_matches(matches)?;
+
+    match args.action {
+        Some(ProtoAction::List) | None => list(ctx),
+        Some(ProtoAction::Generate { module }) => generate(ctx, module.as_deref()),
+        Some(ProtoAction::Lint { module }) => lint(ctx, module.as_deref()),
+        Some(ProtoAction::Breaking { module, against }) => {
+            breaking(ctx, module.as_deref(), against.as_deref())
+        }
+    }
+}
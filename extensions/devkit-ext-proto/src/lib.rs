@@ -0,0 +1,337 @@
+//! Protobuf/gRPC extension for devkit
+//!
+//! Generates code from .proto files, lints them, and checks for breaking changes. buf
+//! is used when a buf.yaml or buf.gen.yaml covers the module, protoc otherwise (with
+//! the plugins listed in `[proto]`). The prerun hook regenerates a module's code when
+//! any of its .proto files changed since the last generation.
+
+mod cli;
+mod modules;
+
+pub use modules::{discover, ProtoModule};
+
+use anyhow::{anyhow, bail, Context, Result};
+use devkit_core::oplog::LoggedCommand;
+use devkit_core::utils::cmd_exists;
+use devkit_core::{AppContext, Extension, MenuItem};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const STAMPS_FILE: &str = ".dev/proto.json";
+
+pub struct ProtoExtension;
+
+impl Extension for ProtoExtension {
+    fn name(&self) -> &str {
+        "proto"
+    }
+
+    fn is_available(&self, ctx: &AppContext) -> bool {
+        !discover(&ctx.config).is_empty()
+    }
+
+    fn unavailable_reason(&self, _ctx: &AppContext) -> Option<String> {
+        Some("no [proto] packages, buf.yaml or proto/ directory found".to_string())
+    }
+
+    fn menu_items(&self, _ctx: &AppContext) -> Vec<MenuItem> {
+        use devkit_core::DevkitError;
+        vec![
+            MenuItem {
+                label: "Generate code".to_string(),
+                group: Some("📡 Protobuf".to_string()),
+                handler: Box::new(|ctx| generate(ctx, None).map_err(DevkitError::from)),
+            },
+            MenuItem {
+                label: "Lint".to_string(),
+                group: Some("📡 Protobuf".to_string()),
+                handler: Box::new(|ctx| lint(ctx, None).map_err(DevkitError::from)),
+            },
+            MenuItem {
+                label: "Check breaking changes".to_string(),
+                group: Some("📡 Protobuf".to_string()),
+                handler: Box::new(|ctx| breaking(ctx, None, None).map_err(DevkitError::from)),
+            },
+        ]
+    }
+
+    fn subcommands(&self) -> Vec<clap::Command> {
+        vec![cli::command()]
+    }
+
+    fn handle_cli(
+        &self,
+        ctx: &AppContext,
+        name: &str,
+        matches: &clap::ArgMatches,
+    ) -> Option<devkit_core::Result<()>> {
+        (name == "proto").then(|| cli::handle(ctx, matches).map_err(Into::into))
+    }
+
+    /// Regenerate code for `[proto]` packages whose .proto files changed. Failures are
+    /// warnings so a broken .proto file doesn't block unrelated commands.
+    fn prerun(&self, ctx: &AppContext) -> devkit_core::Result<()> {
+        // A repo-root module without `[proto]` hasn't opted in to generation on every run
+        if ctx.config.proto_packages().is_empty() {
+            return Ok(());
+        }
+        let Ok(mut stamps) = Stamps::load(&ctx.repo) else {
+            return Ok(());
+        };
+        for module in discover(&ctx.config) {
+            let Some(newest) = module.newest_change() else {
+                continue;
+            };
+            if stamps.generated.get(&module.name) == Some(&newest) {
+                continue;
+            }
+            ctx.print_info(&format!(
+                "Regenerating protobuf code for {}...",
+                module.name
+            ));
+            match generate_module(ctx, &module) {
+                Ok(()) => {
+                    stamps.generated.insert(module.name.clone(), newest);
+                }
+                Err(e) => ctx.print_warning(&format!("{}: {:#}", module.name, e)),
+            }
+        }
+        if let Err(e) = stamps.save(&ctx.repo) {
+            ctx.print_warning(&format!("{:#}", e));
+        }
+        Ok(())
+    }
+}
+
+/// Newest .proto change each module's code was generated from, so the prerun hook
+/// only regenerates when something changed
+#[derive(Debug, Serialize, Deserialize, Default)]
+struct Stamps {
+    generated: HashMap<String, u64>,
+}
+
+impl Stamps {
+    fn load(repo: &Path) -> Result<Self> {
+        let path = repo.join(STAMPS_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = fs::read_to_string(&path)?;
+        serde_json::from_str(&contents)
+            .with_context(|| format!("Invalid proto stamps file: {}", path.display()))
+    }
+
+    fn save(&self, repo: &Path) -> Result<()> {
+        let path = repo.join(STAMPS_FILE);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// The named module, or every module
+fn select(ctx: &AppContext, name: Option<&str>) -> Result<Vec<ProtoModule>> {
+    let modules = discover(&ctx.config);
+    let Some(name) = name else {
+        if modules.is_empty() {
+            bail!("No [proto] packages, buf.yaml or proto/ directory found");
+        }
+        return Ok(modules);
+    };
+    let module = modules
+        .into_iter()
+        .find(|module| module.name == name)
+        .ok_or_else(|| anyhow!("No protobuf module '{}' (see devkit proto list)", name))?;
+    Ok(vec![module])
+}
+
+fn ensure_tool(tool: &str, hint: &str) -> Result<()> {
+    if !cmd_exists(tool) {
+        bail!("{} not installed. Install it with: {}", tool, hint);
+    }
+    Ok(())
+}
+
+/// `buf <subcommand>` in the buf workspace, limited to the module's .proto directory
+/// when the buf config lives at the repo root
+fn buf(module: &ProtoModule, buf_dir: &Path, subcommand: &str) -> Result<Command> {
+    ensure_tool("buf", "https://buf.build/docs/installation")?;
+    let mut cmd = Command::new("buf");
+    cmd.arg(subcommand).current_dir(buf_dir);
+    if buf_dir != module.dir {
+        if let Ok(rel) = module.proto_dir().strip_prefix(buf_dir) {
+            cmd.arg("--path").arg(rel);
+        }
+    }
+    Ok(cmd)
+}
+
+/// `protoc -I <proto dir>` in the module directory
+fn protoc(module: &ProtoModule) -> Result<Command> {
+    ensure_tool("protoc", "https://grpc.io/docs/protoc-installation")?;
+    let mut cmd = Command::new("protoc");
+    cmd.arg("-I")
+        .arg(&module.config.dir)
+        .current_dir(&module.dir);
+    Ok(cmd)
+}
+
+/// Paths of the module's .proto files as protoc sees them from the module directory
+fn protoc_files(module: &ProtoModule) -> Vec<PathBuf> {
+    let dir = Path::new(&module.config.dir);
+    module
+        .files()
+        .into_iter()
+        .map(|file| dir.join(file))
+        .collect()
+}
+
+fn run(mut cmd: Command, what: &str) -> Result<()> {
+    let status = cmd
+        .logged_status()
+        .with_context(|| format!("Failed to {}", what))?;
+    if !status.success() {
+        bail!("Failed to {} ({})", what, status);
+    }
+    Ok(())
+}
+
+fn generate_module(ctx: &AppContext, module: &ProtoModule) -> Result<()> {
+    if let Some(buf_dir) = module.buf_dir(&ctx.repo) {
+        return run(buf(module, &buf_dir, "generate")?, "run buf generate");
+    }
+
+    if module.config.plugins.is_empty() {
+        bail!(
+            "No protoc plugins configured. Set plugins in [proto] of {}/dev.toml, or add a buf.gen.yaml",
+            module.dir.display()
+        );
+    }
+    let mut cmd = protoc(module)?;
+    fs::create_dir_all(module.dir.join(&module.config.out))?;
+    for plugin in &module.config.plugins {
+        cmd.arg(format!("--{}_out={}", plugin, module.config.out));
+    }
+    cmd.args(protoc_files(module));
+    run(cmd, "run protoc")
+}
+
+/// List protobuf modules and the tool each one uses
+pub fn list(ctx: &AppContext) -> Result<()> {
+    ctx.print_header("Protobuf");
+    println!();
+    let modules = discover(&ctx.config);
+    if modules.is_empty() {
+        ctx.print_info("No [proto] packages, buf.yaml or proto/ directory found");
+    }
+    for module in modules {
+        let tool = if module.buf_dir(&ctx.repo).is_some() {
+            "buf"
+        } else {
+            "protoc"
+        };
+        println!(
+            "  {:<16} {:<7} {} files in {}",
+            module.name,
+            tool,
+            module.files().len(),
+            module.proto_dir().display()
+        );
+    }
+    Ok(())
+}
+
+/// Generate code for one module or all of them
+pub fn generate(ctx: &AppContext, name: Option<&str>) -> Result<()> {
+    let mut stamps = Stamps::load(&ctx.repo)?;
+    for module in select(ctx, name)? {
+        ctx.print_info(&format!("Generating {}...", module.name));
+        generate_module(ctx, &module)?;
+        if let Some(newest) = module.newest_change() {
+            stamps.generated.insert(module.name.clone(), newest);
+        }
+    }
+    stamps.save(&ctx.repo)?;
+    ctx.print_success("✓ Code generated");
+    Ok(())
+}
+
+/// Lint with buf, or check that the files compile with protoc
+pub fn lint(ctx: &AppContext, name: Option<&str>) -> Result<()> {
+    for module in select(ctx, name)? {
+        ctx.print_info(&format!("Linting {}...", module.name));
+        if let Some(buf_dir) = module.buf_dir(&ctx.repo) {
+            run(buf(&module, &buf_dir, "lint")?, "run buf lint")?;
+            continue;
+        }
+
+        ctx.print_warning("No buf.yaml; protoc only checks that the files compile");
+        let mut cmd = protoc(&module)?;
+        cmd.arg("--descriptor_set_out=/dev/null")
+            .args(protoc_files(&module));
+        run(cmd, "run protoc")?;
+    }
+    ctx.print_success("✓ No lint errors");
+    Ok(())
+}
+
+/// Check for breaking changes against a git ref (default: `[git] default_pr_base`) or
+/// any other buf input
+pub fn breaking(ctx: &AppContext, name: Option<&str>, against: Option<&str>) -> Result<()> {
+    for module in select(ctx, name)? {
+        let buf_dir = module.buf_dir(&ctx.repo).ok_or_else(|| {
+            anyhow!(
+                "Breaking-change checks need buf; add a buf.yaml for {}",
+                module.name
+            )
+        })?;
+        let against = match against {
+            Some(against) => against.to_string(),
+            None => against_branch(&ctx.repo, &buf_dir, &ctx.config.global.git.default_pr_base),
+        };
+
+        ctx.print_info(&format!("Checking {} against {}...", module.name, against));
+        let mut cmd = buf(&module, &buf_dir, "breaking")?;
+        cmd.args(["--against", &against]);
+        run(cmd, "run buf breaking")?;
+    }
+    ctx.print_success("✓ No breaking changes");
+    Ok(())
+}
+
+/// buf input for the same workspace on another branch
+fn against_branch(repo: &Path, buf_dir: &Path, branch: &str) -> String {
+    let git = repo.join(".git");
+    match buf_dir.strip_prefix(repo) {
+        Ok(subdir) if !subdir.as_os_str().is_empty() => format!(
+            "{}#branch={},subdir={}",
+            git.display(),
+            branch,
+            subdir.display()
+        ),
+        _ => format!("{}#branch={}", git.display(), branch),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_against_branch() {
+        let repo = Path::new("/work/app");
+        assert_eq!(
+            against_branch(repo, repo, "main"),
+            "/work/app/.git#branch=main"
+        );
+        assert_eq!(
+            against_branch(repo, &repo.join("packages/api"), "develop"),
+            "/work/app/.git#branch=develop,subdir=packages/api"
+        );
+    }
+}
@@ -0,0 +1,140 @@
+//! Protobuf module discovery
+//!
+//! Each package declaring `[proto]` in dev.toml is a module. Without any, the repo root
+//! is one when it has a buf.yaml or .proto files under `proto/`.
+
+use devkit_core::config::ProtoConfig;
+use devkit_core::Config;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// Name of the module at the repo root
+pub const ROOT: &str = "root";
+
+/// Directories never searched for .proto files
+const SKIP_DIRS: &[&str] = &["node_modules", "target", ".git", "vendor"];
+
+#[derive(Debug, Clone)]
+pub struct ProtoModule {
+    pub name: String,
+    /// Package directory (or repo root)
+    pub dir: PathBuf,
+    pub config: ProtoConfig,
+}
+
+impl ProtoModule {
+    pub fn proto_dir(&self) -> PathBuf {
+        self.dir.join(&self.config.dir)
+    }
+
+    /// .proto files, relative to [`proto_dir`](Self::proto_dir)
+    pub fn files(&self) -> Vec<PathBuf> {
+        let root = self.proto_dir();
+        let mut files = Vec::new();
+        collect_protos(&root, &mut files);
+        files.sort();
+        files
+            .into_iter()
+            .filter_map(|file| file.strip_prefix(&root).ok().map(Path::to_path_buf))
+            .collect()
+    }
+
+    /// Most recent modification of any .proto file, in seconds since the epoch
+    pub fn newest_change(&self) -> Option<u64> {
+        let root = self.proto_dir();
+        self.files()
+            .iter()
+            .filter_map(|file| fs::metadata(root.join(file)).ok()?.modified().ok())
+            .filter_map(|modified| modified.duration_since(UNIX_EPOCH).ok())
+            .map(|age| age.as_secs())
+            .max()
+    }
+
+    /// Directory with the buf config that covers this module: the module's own, else
+    /// the repo root's
+    pub fn buf_dir(&self, repo: &Path) -> Option<PathBuf> {
+        [self.dir.as_path(), repo]
+            .into_iter()
+            .find(|dir| dir.join("buf.yaml").exists() || dir.join("buf.gen.yaml").exists())
+            .map(Path::to_path_buf)
+    }
+}
+
+/// Protobuf modules, sorted by name
+pub fn discover(config: &Config) -> Vec<ProtoModule> {
+    let mut modules: Vec<ProtoModule> = config
+        .proto_packages()
+        .into_iter()
+        .map(|(name, pkg, proto)| ProtoModule {
+            name: name.to_string(),
+            dir: pkg.path.clone(),
+            config: proto.clone(),
+        })
+        .collect();
+    modules.sort_by(|a, b| a.name.cmp(&b.name));
+
+    if modules.is_empty() {
+        let root = ProtoModule {
+            name: ROOT.to_string(),
+            dir: config.repo_root.clone(),
+            config: ProtoConfig::default(),
+        };
+        if root.dir.join("buf.yaml").exists() || !root.files().is_empty() {
+            modules.push(root);
+        }
+    }
+    modules
+}
+
+fn collect_protos(dir: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            let name = entry.file_name();
+            if !SKIP_DIRS.iter().any(|skip| name == *skip) {
+                collect_protos(&path, files);
+            }
+        } else if path.extension().is_some_and(|ext| ext == "proto") {
+            files.push(path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_discover_root_module() {
+        let repo = std::env::temp_dir().join(format!("devkit-proto-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&repo);
+        fs::create_dir_all(repo.join("proto/api/v1")).unwrap();
+        fs::create_dir_all(repo.join("proto/node_modules/dep")).unwrap();
+        fs::write(repo.join("proto/api/v1/users.proto"), "").unwrap();
+        fs::write(repo.join("proto/common.proto"), "").unwrap();
+        fs::write(repo.join("proto/node_modules/dep/dep.proto"), "").unwrap();
+
+        let config = Config::load(&repo).unwrap();
+        let modules = discover(&config);
+        assert_eq!(modules.len(), 1);
+        assert_eq!(modules[0].name, ROOT);
+        assert_eq!(
+            modules[0].files(),
+            [
+                PathBuf::from("api/v1/users.proto"),
+                PathBuf::from("common.proto")
+            ]
+        );
+        assert!(modules[0].newest_change().is_some());
+        assert!(modules[0].buf_dir(&repo).is_none());
+
+        fs::write(repo.join("buf.yaml"), "version: v2\n").unwrap();
+        assert_eq!(modules[0].buf_dir(&repo), Some(repo.clone()));
+
+        fs::remove_dir_all(repo).ok();
+    }
+}
@@ -0,0 +1,63 @@
+//! `devkit pulumi` subcommand
+
+use anyhow::Result;
+use clap::{Args, Command, FromArgMatches, Subcommand};
+use devkit_core::AppContext;
+
+use crate::{pulumi_destroy, pulumi_preview, pulumi_stack_select, pulumi_up};
+
+#[derive(Args)]
+pub struct PulumiArgs {
+    #[command(subcommand)]
+    action: Option<PulumiAction>,
+}
+
+#[derive(Subcommand)]
+enum PulumiAction {
+    /// Summarize the changes an update would make (default)
+    Preview {
+        #[arg(long, short)]
+        stack: Option<String>,
+        /// List every change with its property diff
+        #[arg(long)]
+        details: bool,
+    },
+    /// Deploy the stack
+    Up {
+        #[arg(long, short)]
+        stack: Option<String>,
+        /// Skip the confirmation prompt
+        #[arg(long, short)]
+        yes: bool,
+    },
+    /// Tear down the stack's resources
+    Destroy {
+        #[arg(long, short)]
+        stack: Option<String>,
+        /// Skip the confirmation prompt
+        #[arg(long, short)]
+        yes: bool,
+    },
+    /// Select the current stack
+    Stack { name: String },
+}
+
+pub fn command() -> Command {
+    PulumiArgs::augment_args(
+        Command::new("pulumi").about("Pulumi previews and deployments (requires the pulumi CLI)"),
+    )
+}
+
+pub fn handle(ctx: &AppContext, matches: &clap::ArgMatches) -> Result<()> {
+    let args = PulumiArgs::from_arg_matches(matches)?;
+
+    match args.action {
+        Some(PulumiAction::Preview { stack, details }) => {
+            pulumi_preview(ctx, stack.as_deref(), details)
+        }
+        None => pulumi_preview(ctx, None, false),
+        Some(PulumiAction::Up { stack, yes }) => pulumi_up(ctx, stack.as_deref(), yes),
+        Some(PulumiAction::Destroy { stack, yes }) => pulumi_destroy(ctx, stack.as_deref(), yes),
+        Some(PulumiAction::Stack { name }) => pulumi_stack_select(ctx, &name),
+    }
+}
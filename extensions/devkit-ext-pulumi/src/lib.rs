@@ -1,10 +1,20 @@
 //! Pulumi extension for devkit
 //!
-//! Provides Pulumi infrastructure deployment operations.
+//! Provides Pulumi infrastructure deployment operations. Previews are summarized
+//! from `pulumi preview --json` (see [`preview`]). Without `--stack`, commands use
+//! the active environment's `pulumi_stack`, or the environment's name when there's a
+//! `Pulumi.<env>.yaml`.
 
-use anyhow::{anyhow, Result};
+mod cli;
+pub mod preview;
+
+use anyhow::{anyhow, bail, Context, Result};
+use console::style;
+use devkit_core::oplog::LoggedCommand;
 use devkit_core::{AppContext, Extension, MenuItem};
 use devkit_tasks::CmdBuilder;
+use dialoguer::Select;
+use preview::{parse_preview, symbol, Change, Preview};
 
 pub struct PulumiExtension;
 
@@ -17,12 +27,20 @@ impl Extension for PulumiExtension {
         ctx.features.pulumi
     }
 
+    fn unavailable_reason(&self, _ctx: &AppContext) -> Option<String> {
+        Some(if devkit_core::cmd_exists("pulumi") {
+            "no Pulumi.yaml found".to_string()
+        } else {
+            "pulumi not installed".to_string()
+        })
+    }
+
     fn menu_items(&self, _ctx: &AppContext) -> Vec<MenuItem> {
         vec![
             MenuItem {
                 label: "☁️  Pulumi - Preview".to_string(),
                 group: None,
-                handler: Box::new(|ctx| pulumi_preview(ctx, None).map_err(Into::into)),
+                handler: Box::new(|ctx| pulumi_preview(ctx, None, false).map_err(Into::into)),
             },
             MenuItem {
                 label: "☁️  Pulumi - Deploy (Up)".to_string(),
@@ -31,6 +49,38 @@ impl Extension for PulumiExtension {
             },
         ]
     }
+
+    fn subcommands(&self) -> Vec<clap::Command> {
+        vec![cli::command()]
+    }
+
+    fn handle_cli(
+        &self,
+        ctx: &AppContext,
+        name: &str,
+        matches: &clap::ArgMatches,
+    ) -> Option<devkit_core::Result<()>> {
+        (name == "pulumi").then(|| cli::handle(ctx, matches).map_err(Into::into))
+    }
+}
+
+/// The given stack, else the active environment's
+fn stack_for(ctx: &AppContext, stack: Option<&str>) -> Option<String> {
+    if let Some(stack) = stack {
+        return Some(stack.to_string());
+    }
+    let environments = &ctx.config.global.environments;
+    if let Some(stack) = environments
+        .current_settings()
+        .and_then(|settings| settings.pulumi_stack.clone())
+    {
+        return Some(stack);
+    }
+    let env = environments.current();
+    ctx.repo
+        .join(format!("Pulumi.{}.yaml", env))
+        .exists()
+        .then(|| env.to_string())
 }
 
 /// Pulumi up (deploy infrastructure)
@@ -45,9 +95,9 @@ pub fn pulumi_up(ctx: &AppContext, stack: Option<&str>, yes: bool) -> Result<()>
 
     let mut args = vec!["up".to_string()];
 
-    if let Some(s) = stack {
+    if let Some(s) = stack_for(ctx, stack) {
         args.push("--stack".to_string());
-        args.push(s.to_string());
+        args.push(s);
     }
 
     if yes {
@@ -68,8 +118,10 @@ pub fn pulumi_up(ctx: &AppContext, stack: Option<&str>, yes: bool) -> Result<()>
     Ok(())
 }
 
-/// Pulumi preview (preview changes)
-pub fn pulumi_preview(ctx: &AppContext, stack: Option<&str>) -> Result<()> {
+/// Pulumi preview, summarized by operation and resource type. `details` lists every
+/// change with its property diff; otherwise changes can be picked to inspect when
+/// there's a terminal.
+pub fn pulumi_preview(ctx: &AppContext, stack: Option<&str>, details: bool) -> Result<()> {
     if !devkit_core::cmd_exists("pulumi") {
         return Err(anyhow!(
             "Pulumi CLI not found. Install from: https://www.pulumi.com/docs/get-started/install/"
@@ -78,22 +130,104 @@ pub fn pulumi_preview(ctx: &AppContext, stack: Option<&str>) -> Result<()> {
 
     ctx.print_header("Previewing infrastructure changes");
 
-    let mut args = vec!["preview".to_string()];
+    let mut args = vec!["preview".to_string(), "--json".to_string()];
 
-    if let Some(s) = stack {
+    if let Some(s) = stack_for(ctx, stack) {
         args.push("--stack".to_string());
-        args.push(s.to_string());
+        args.push(s);
     }
 
-    let code = CmdBuilder::new("pulumi").args(&args).cwd(&ctx.repo).run()?;
+    let output = std::process::Command::new("pulumi")
+        .args(&args)
+        .current_dir(&ctx.repo)
+        .logged_output()
+        .context("Failed to run pulumi preview")?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let preview = match parse_preview(&stdout) {
+        Ok(preview) => preview,
+        Err(_) if !output.status.success() => bail!(
+            "Pulumi preview failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ),
+        Err(e) => return Err(e),
+    };
+    if !preview.errors.is_empty() || !output.status.success() {
+        for error in &preview.errors {
+            ctx.print_error(error);
+        }
+        bail!("Pulumi preview failed with code {:?}", output.status.code());
+    }
 
-    if code != 0 {
-        return Err(anyhow!("Pulumi preview failed with code {}", code));
+    print_summary(ctx, &preview);
+    if preview.changes.is_empty() {
+        return Ok(());
     }
 
+    if details {
+        for change in &preview.changes {
+            print_change(change);
+        }
+    } else if ctx.is_interactive() {
+        inspect_changes(ctx, &preview)?;
+    }
     Ok(())
 }
 
+fn print_summary(ctx: &AppContext, preview: &Preview) {
+    println!();
+    if preview.changes.is_empty() {
+        ctx.print_success(&format!(
+            "✓ No changes ({} resources unchanged)",
+            preview.unchanged
+        ));
+        return;
+    }
+
+    println!("  {}", style(preview.headline()).bold());
+    for (op, types) in preview.summary() {
+        println!();
+        println!(
+            "  {} {} ({})",
+            symbol(op),
+            op,
+            types.values().sum::<usize>()
+        );
+        for (kind, count) in types {
+            println!("      {:<48} {}", kind, count);
+        }
+    }
+    println!();
+}
+
+fn print_change(change: &Change) {
+    println!("  {}", style(change.label()).bold());
+    for (symbol, path) in &change.diffs {
+        println!("      {} {}", symbol, path);
+    }
+}
+
+/// Let the user pick changes to see the property diff of, until they're done
+fn inspect_changes(ctx: &AppContext, preview: &Preview) -> Result<()> {
+    let mut items = vec!["[Done]".to_string()];
+    items.extend(preview.changes.iter().map(Change::label));
+
+    let mut selected = 0;
+    loop {
+        selected = Select::with_theme(&*ctx.theme())
+            .with_prompt("Inspect a change")
+            .items(&items)
+            .default(selected)
+            .interact()?;
+        if selected == 0 {
+            return Ok(());
+        }
+        println!();
+        print_change(&preview.changes[selected - 1]);
+        println!();
+    }
+}
+
 /// Pulumi destroy (tear down infrastructure)
 pub fn pulumi_destroy(ctx: &AppContext, stack: Option<&str>, yes: bool) -> Result<()> {
     if !devkit_core::cmd_exists("pulumi") {
@@ -106,9 +240,9 @@ pub fn pulumi_destroy(ctx: &AppContext, stack: Option<&str>, yes: bool) -> Resul
 
     let mut args = vec!["destroy".to_string()];
 
-    if let Some(s) = stack {
+    if let Some(s) = stack_for(ctx, stack) {
         args.push("--stack".to_string());
-        args.push(s.to_string());
+        args.push(s);
     }
 
     if yes {
@@ -0,0 +1,299 @@
+//! `pulumi preview --json` parsing and summaries
+//!
+//! Changes are grouped by operation, then by resource type, with counts. Each
+//! change's property diff can be shown on its own.
+
+use anyhow::{bail, Context, Result};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Operations in the order they're shown, with their symbols
+const OPS: &[(&str, &str)] = &[
+    ("create", "+"),
+    ("update", "~"),
+    ("replace", "+-"),
+    ("delete", "-"),
+    ("import", "="),
+    ("read", ">"),
+];
+
+/// Steps that only make up a replacement, which is shown once as `replace`
+const REPLACEMENT_STEPS: &[&str] = &["create-replacement", "delete-replaced"];
+
+/// A resource that would change
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change {
+    pub op: String,
+    /// Resource type, e.g. `aws:s3/bucket:Bucket`
+    pub kind: String,
+    pub name: String,
+    /// Changed properties with `+`, `-` or `~` (`+-` when they force a replacement)
+    pub diffs: Vec<(String, String)>,
+}
+
+impl Change {
+    pub fn symbol(&self) -> &str {
+        symbol(&self.op)
+    }
+
+    /// "~ aws:lambda/function:Function  api-handler", for lists
+    pub fn label(&self) -> String {
+        format!("{} {}  {}", self.symbol(), self.kind, self.name)
+    }
+}
+
+/// A parsed preview
+#[derive(Debug, Default, PartialEq)]
+pub struct Preview {
+    pub changes: Vec<Change>,
+    pub unchanged: u64,
+    /// Error diagnostics (the preview failed when there are any)
+    pub errors: Vec<String>,
+}
+
+impl Preview {
+    /// Changes per operation (in display order), then per type with counts
+    pub fn summary(&self) -> Vec<(&str, BTreeMap<&str, usize>)> {
+        let mut by_op: BTreeMap<usize, (&str, BTreeMap<&str, usize>)> = BTreeMap::new();
+        for change in &self.changes {
+            let order = OPS
+                .iter()
+                .position(|(op, _)| *op == change.op)
+                .unwrap_or(OPS.len());
+            let (_, types) = by_op
+                .entry(order)
+                .or_insert_with(|| (change.op.as_str(), BTreeMap::new()));
+            *types.entry(change.kind.as_str()).or_default() += 1;
+        }
+        by_op.into_values().collect()
+    }
+
+    /// "2 to create, 1 to update, 12 unchanged"
+    pub fn headline(&self) -> String {
+        let mut parts: Vec<String> = self
+            .summary()
+            .iter()
+            .map(|(op, types)| format!("{} to {}", types.values().sum::<usize>(), op))
+            .collect();
+        parts.push(format!("{} unchanged", self.unchanged));
+        parts.join(", ")
+    }
+}
+
+pub fn symbol(op: &str) -> &str {
+    OPS.iter()
+        .find(|(name, _)| *name == op)
+        .map(|(_, symbol)| *symbol)
+        .unwrap_or("*")
+}
+
+/// Type and name from a URN (`urn:pulumi:<stack>::<project>::<parent$type>::<name>`)
+fn parse_urn(urn: &str) -> (String, String) {
+    let mut parts = urn.splitn(4, "::").skip(2);
+    let kind = parts.next().unwrap_or_default();
+    let kind = kind.rsplit('$').next().unwrap_or(kind);
+    (kind.to_string(), parts.next().unwrap_or(urn).to_string())
+}
+
+/// Symbol for a `detailedDiff` kind (`add`, `update-replace`, ...)
+fn diff_symbol(kind: &str) -> &'static str {
+    if kind.ends_with("-replace") {
+        "+-"
+    } else {
+        match kind {
+            "add" => "+",
+            "delete" => "-",
+            _ => "~",
+        }
+    }
+}
+
+/// Property diffs of a step: `detailedDiff` when present, else `diffReasons`
+fn parse_diffs(step: &Value) -> Vec<(String, String)> {
+    if let Some(detailed) = step["detailedDiff"].as_object() {
+        return detailed
+            .iter()
+            .map(|(path, diff)| {
+                let kind = diff["kind"].as_str().unwrap_or("update");
+                (diff_symbol(kind).to_string(), path.clone())
+            })
+            .collect();
+    }
+    let replaces: Vec<&str> = step["replaceReasons"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .collect();
+    step["diffReasons"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .map(|path| {
+            let symbol = if replaces.contains(&path) { "+-" } else { "~" };
+            (symbol.to_string(), path.to_string())
+        })
+        .collect()
+}
+
+/// `pulumi preview --json` output
+pub fn parse_preview(output: &str) -> Result<Preview> {
+    let doc: Value = serde_json::from_str(output).context("Unexpected output from pulumi")?;
+    if !doc.is_object() {
+        bail!("Unexpected output from pulumi");
+    }
+
+    let changes = doc["steps"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|step| {
+            let op = step["op"].as_str()?;
+            if op == "same" || REPLACEMENT_STEPS.contains(&op) {
+                return None;
+            }
+            let (kind, name) = parse_urn(step["urn"].as_str()?);
+            // Deletes carry no new state, so the type may only be in the old one
+            let kind = step["newState"]["type"]
+                .as_str()
+                .or(step["oldState"]["type"].as_str())
+                .map(String::from)
+                .unwrap_or(kind);
+            Some(Change {
+                op: op.to_string(),
+                kind,
+                name,
+                diffs: parse_diffs(step),
+            })
+        })
+        .collect();
+
+    let errors = doc["diagnostics"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|diagnostic| diagnostic["severity"] == "error")
+        .filter_map(|diagnostic| diagnostic["message"].as_str())
+        .map(|message| message.trim().to_string())
+        .collect();
+
+    Ok(Preview {
+        changes,
+        unchanged: doc["changeSummary"]["same"].as_u64().unwrap_or(0),
+        errors,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PREVIEW: &str = r#"{
+        "steps": [
+            {
+                "op": "same",
+                "urn": "urn:pulumi:dev::infra::pulumi:pulumi:Stack::infra-dev"
+            },
+            {
+                "op": "create",
+                "urn": "urn:pulumi:dev::infra::aws:s3/bucket:Bucket::assets",
+                "newState": { "type": "aws:s3/bucket:Bucket" }
+            },
+            {
+                "op": "create",
+                "urn": "urn:pulumi:dev::infra::aws:s3/bucket:Bucket::logs",
+                "newState": { "type": "aws:s3/bucket:Bucket" }
+            },
+            {
+                "op": "update",
+                "urn": "urn:pulumi:dev::infra::my:index:Api$aws:lambda/function:Function::api-handler",
+                "newState": { "type": "aws:lambda/function:Function" },
+                "detailedDiff": {
+                    "environment.variables.LOG_LEVEL": { "kind": "update" },
+                    "timeout": { "kind": "add" }
+                }
+            },
+            {
+                "op": "create-replacement",
+                "urn": "urn:pulumi:dev::infra::aws:rds/instance:Instance::db"
+            },
+            {
+                "op": "replace",
+                "urn": "urn:pulumi:dev::infra::aws:rds/instance:Instance::db",
+                "diffReasons": ["engineVersion", "tags"],
+                "replaceReasons": ["engineVersion"]
+            },
+            {
+                "op": "delete-replaced",
+                "urn": "urn:pulumi:dev::infra::aws:rds/instance:Instance::db"
+            },
+            {
+                "op": "delete",
+                "urn": "urn:pulumi:dev::infra::aws:iam/role:Role::old-role",
+                "oldState": { "type": "aws:iam/role:Role" }
+            }
+        ],
+        "changeSummary": { "create": 2, "update": 1, "replace": 1, "delete": 1, "same": 12 }
+    }"#;
+
+    #[test]
+    fn test_parse_preview() {
+        let preview = parse_preview(PREVIEW).unwrap();
+        assert_eq!(preview.changes.len(), 5);
+        assert_eq!(preview.unchanged, 12);
+
+        let update = &preview.changes[2];
+        assert_eq!(
+            update.label(),
+            "~ aws:lambda/function:Function  api-handler"
+        );
+        assert_eq!(
+            update.diffs,
+            [
+                (
+                    "~".to_string(),
+                    "environment.variables.LOG_LEVEL".to_string()
+                ),
+                ("+".to_string(), "timeout".to_string()),
+            ]
+        );
+        assert_eq!(
+            preview.changes[3].diffs,
+            [
+                ("+-".to_string(), "engineVersion".to_string()),
+                ("~".to_string(), "tags".to_string()),
+            ]
+        );
+        assert_eq!(preview.changes[4].kind, "aws:iam/role:Role");
+    }
+
+    #[test]
+    fn test_summary() {
+        let preview = parse_preview(PREVIEW).unwrap();
+        let summary = preview.summary();
+        let ops: Vec<&str> = summary.iter().map(|(op, _)| *op).collect();
+        assert_eq!(ops, ["create", "update", "replace", "delete"]);
+        assert_eq!(summary[0].1["aws:s3/bucket:Bucket"], 2);
+        assert_eq!(
+            preview.headline(),
+            "2 to create, 1 to update, 1 to replace, 1 to delete, 12 unchanged"
+        );
+    }
+
+    #[test]
+    fn test_parse_preview_errors() {
+        let preview = parse_preview(
+            r#"{"diagnostics": [
+                {"message": "warning: deprecated\n", "severity": "warning"},
+                {"message": "error: missing required configuration variable 'region'\n", "severity": "error"}
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            preview.errors,
+            ["error: missing required configuration variable 'region'"]
+        );
+        assert!(parse_preview("not json").is_err());
+    }
+}
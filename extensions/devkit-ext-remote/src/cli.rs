@@ -0,0 +1,91 @@
+//! `devkit remote` subcommand
+
+use anyhow::Result;
+use clap::{Args, Command, FromArgMatches, Subcommand};
+use devkit_core::AppContext;
+
+use crate::{
+    connect_remote, exec_remote, list_remotes, port_forward, sync_to_remote, watch_and_sync,
+    SyncOptions,
+};
+
+#[derive(Args)]
+pub struct RemoteArgs {
+    #[command(subcommand)]
+    action: Option<RemoteAction>,
+}
+
+#[derive(Subcommand)]
+enum RemoteAction {
+    /// List the remotes in .dev/config.toml (default)
+    List,
+    /// Open a shell in the synced directory, with port forwards
+    Connect {
+        /// Remote name (the only one, or picked, when left out)
+        name: Option<String>,
+    },
+    /// Sync the repo to a remote with rsync
+    Sync {
+        #[arg(short, long)]
+        remote: Option<String>,
+        /// Delete remote files that don't exist locally
+        #[arg(long)]
+        delete: bool,
+        /// Show what would be transferred
+        #[arg(long)]
+        dry_run: bool,
+        /// Keep syncing as files change
+        #[arg(short, long)]
+        watch: bool,
+    },
+    /// Run a command in the synced directory on a remote
+    Exec {
+        #[arg(short, long)]
+        remote: Option<String>,
+        /// Command and arguments
+        #[arg(required = true, trailing_var_arg = true, allow_hyphen_values = true)]
+        command: Vec<String>,
+    },
+    /// Forward the remote's port_forwards without opening a shell
+    Forward {
+        #[arg(short, long)]
+        remote: Option<String>,
+        /// Keep forwarding in the background (see `devkit forward`)
+        #[arg(short, long)]
+        background: bool,
+    },
+}
+
+pub fn command() -> Command {
+    RemoteArgs::augment_args(
+        Command::new("remote").about("Remote development over SSH: sync, exec, connect, forward"),
+    )
+}
+
+pub fn handle(ctx: &AppContext, matches: &clap::ArgMatches) -> Result<()> {
+    let args = RemoteArgs::from_arg_matches(matches)?;
+
+    match args.action {
+        Some(RemoteAction::List) | None => list_remotes(ctx),
+        Some(RemoteAction::Connect { name }) => connect_remote(ctx, name.as_deref()),
+        Some(RemoteAction::Sync {
+            remote,
+            delete,
+            dry_run,
+            watch,
+        }) => {
+            let options = SyncOptions { delete, dry_run };
+            if watch {
+                watch_and_sync(ctx, remote.as_deref(), &options)
+            } else {
+                sync_to_remote(ctx, remote.as_deref(), &options)
+            }
+        }
+        Some(RemoteAction::Exec { remote, command }) => {
+            exec_remote(ctx, remote.as_deref(), &command)
+        }
+        Some(RemoteAction::Forward { remote, background }) => {
+            port_forward(ctx, remote.as_deref(), background)
+        }
+    }
+}
@@ -1,9 +1,31 @@
 //! Remote development support extension
 //!
-//! Enables SSH-based remote development with file sync and command execution
+//! Enables SSH-based remote development with file sync and command execution.
+//! Remotes are defined in `.dev/config.toml`:
+//!
+//! ```toml
+//! [remote.staging]
+//! host = "staging.example.com"
+//! user = "deploy"
+//! path = "/app"
+//! sync_patterns = ["src/**", "Cargo.toml"]
+//! excludes = ["*.log"]
+//! port_forwards = ["8080", "15432:5432"]
+//! ```
+//!
+//! The repo is synced with rsync over ssh. `.git/` and `.gitignore`d files are never
+//! synced, nor are `excludes`; with `sync_patterns`, only the paths they match are.
+
+mod cli;
 
-use anyhow::{Context, Result};
+use anyhow::{anyhow, Context, Result};
+use devkit_core::config::RemoteEntry;
+use devkit_core::forwards;
+use devkit_core::oplog::LoggedCommand;
+use devkit_core::utils::shell_quote;
 use devkit_core::{AppContext, Extension, MenuItem};
+use devkit_tasks::{watch_and_run, WatchConfig};
+use dialoguer::Select;
 use std::process::Command;
 
 pub struct RemoteExtension;
@@ -17,133 +39,382 @@ impl Extension for RemoteExtension {
         cmd_exists("ssh") && cmd_exists("rsync")
     }
 
-    fn menu_items(&self, _ctx: &AppContext) -> Vec<MenuItem> {
+    fn unavailable_reason(&self, _ctx: &AppContext) -> Option<String> {
+        Some("ssh and rsync are required".to_string())
+    }
+
+    fn menu_items(&self, ctx: &AppContext) -> Vec<MenuItem> {
+        if ctx.config.global.remote.entries.is_empty() {
+            return Vec::new();
+        }
         vec![
             MenuItem {
                 label: "🌐 Connect to remote".to_string(),
                 group: None,
-                handler: Box::new(|ctx| connect_remote(ctx).map_err(Into::into)),
+                handler: Box::new(|ctx| connect_remote(ctx, None).map_err(Into::into)),
             },
             MenuItem {
                 label: "🔄 Sync files to remote".to_string(),
                 group: None,
-                handler: Box::new(|ctx| sync_to_remote(ctx).map_err(Into::into)),
+                handler: Box::new(|ctx| {
+                    sync_to_remote(ctx, None, &SyncOptions::default()).map_err(Into::into)
+                }),
             },
         ]
     }
+
+    fn subcommands(&self) -> Vec<clap::Command> {
+        vec![cli::command()]
+    }
+
+    fn handle_cli(
+        &self,
+        ctx: &AppContext,
+        name: &str,
+        matches: &clap::ArgMatches,
+    ) -> Option<devkit_core::Result<()>> {
+        (name == "remote").then(|| cli::handle(ctx, matches).map_err(Into::into))
+    }
 }
 
 fn cmd_exists(cmd: &str) -> bool {
     Command::new("which")
         .arg(cmd)
-        .output()
+        .logged_output()
         .map(|output| output.status.success())
         .unwrap_or(false)
 }
 
-/// Connect to remote environment
-pub fn connect_remote(ctx: &AppContext) -> Result<()> {
-    ctx.print_header("Remote Development");
-    println!();
+/// How `devkit remote sync` runs rsync
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SyncOptions {
+    /// Delete remote files that don't exist locally
+    pub delete: bool,
+    /// Only list what would be transferred
+    pub dry_run: bool,
+}
 
-    ctx.print_info("Remote development enables:");
-    println!("  • SSH connection management");
-    println!("  • File synchronization (rsync)");
-    println!("  • Remote command execution");
-    println!("  • Port forwarding");
-    println!("  • Context switching");
-    println!();
+/// The remote called `name`; without a name, the only remote or one picked
+/// interactively
+pub fn resolve_remote<'a>(
+    ctx: &'a AppContext,
+    name: Option<&str>,
+) -> Result<(String, &'a RemoteEntry)> {
+    let remotes = &ctx.config.global.remote;
+    let names = remotes.names();
 
-    ctx.print_info("Configuration in .dev/config.toml:");
-    println!();
-    println!("  [remote.staging]");
-    println!("  host = \"staging.example.com\"");
-    println!("  user = \"deploy\"");
-    println!("  path = \"/app\"");
-    println!("  sync_patterns = [\"src/**\", \"Cargo.toml\"]");
-    println!("  port_forwards = [\"8080:8080\"]");
+    let name = match name {
+        Some(name) => name.to_string(),
+        None if names.len() == 1 => names[0].to_string(),
+        None if names.is_empty() => {
+            return Err(anyhow!(
+                "No remotes configured. Add one to .dev/config.toml:\n\n\
+                 [remote.staging]\n\
+                 host = \"staging.example.com\"\n\
+                 path = \"/app\""
+            ))
+        }
+        None => {
+            ctx.require_interactive("Selecting a remote", "Pass the remote name")?;
+            let selection = Select::with_theme(&*ctx.theme())
+                .with_prompt("Which remote?")
+                .items(&names)
+                .default(0)
+                .interact()?;
+            names[selection].to_string()
+        }
+    };
+
+    let remote = remotes.get(&name).ok_or_else(|| {
+        anyhow!(
+            "Remote '{}' not found\nAvailable remotes: {}",
+            name,
+            if names.is_empty() {
+                "none".to_string()
+            } else {
+                names.join(", ")
+            }
+        )
+    })?;
+    Ok((name, remote))
+}
+
+/// List the configured remotes
+pub fn list_remotes(ctx: &AppContext) -> Result<()> {
+    let remotes = &ctx.config.global.remote;
+    if remotes.entries.is_empty() {
+        ctx.print_info("No remotes configured ([remote.<name>] in .dev/config.toml)");
+        return Ok(());
+    }
+
+    ctx.print_header("Remotes");
     println!();
+    for name in remotes.names() {
+        let Some(remote) = remotes.get(name) else {
+            continue;
+        };
+        print!("  {} - {}:{}", name, remote.destination(), remote.path);
+        if !remote.port_forwards.is_empty() {
+            print!(" (forwards {})", remote.port_forwards.join(", "));
+        }
+        println!();
+    }
+    Ok(())
+}
 
-    ctx.print_info("Commands:");
-    println!("  devkit remote connect staging");
-    println!("  devkit remote sync");
-    println!("  devkit remote exec \"cargo build\"");
-    println!("  devkit remote forward");
+/// Open a shell on the remote in the synced directory, with its ports forwarded
+pub fn connect_remote(ctx: &AppContext, name: Option<&str>) -> Result<()> {
+    let (name, remote) = resolve_remote(ctx, name)?;
+    let forwards = remote.forwards().map_err(|e| anyhow!(e))?;
+
+    ctx.print_header(&format!(
+        "Connecting to {} ({})",
+        name,
+        remote.destination()
+    ));
+    for (local, remote_port) in &forwards {
+        ctx.print_info(&format!(
+            "Forwarding localhost:{} -> {}",
+            local, remote_port
+        ));
+    }
+
+    let status = Command::new("ssh")
+        .arg("-t")
+        .args(forward_args(&forwards))
+        .arg(remote.destination())
+        .arg(format!(
+            "cd {} && exec $SHELL -l",
+            shell_quote(&remote.path)
+        ))
+        .logged_status()
+        .context("Failed to run ssh")?;
 
+    // 255 is ssh's own failure; anything else is the remote shell's exit code
+    if status.code() == Some(255) {
+        return Err(anyhow!("Couldn't connect to {}", remote.destination()));
+    }
     Ok(())
 }
 
-/// Sync files to remote
-pub fn sync_to_remote(ctx: &AppContext) -> Result<()> {
-    ctx.print_info("Syncing files to remote...");
+/// Sync the repo to the remote with rsync
+pub fn sync_to_remote(ctx: &AppContext, name: Option<&str>, options: &SyncOptions) -> Result<()> {
+    let (name, remote) = resolve_remote(ctx, name)?;
+    sync(ctx, &name, remote, options)
+}
 
-    // Example rsync command
-    ctx.print_info("Would run: rsync -avz --exclude target/ ./ user@host:/path");
-    ctx.print_success("✓ Files synced (demo mode)");
+fn sync(ctx: &AppContext, name: &str, remote: &RemoteEntry, options: &SyncOptions) -> Result<()> {
+    ctx.print_info(&format!(
+        "Syncing to {} ({}:{}){}...",
+        name,
+        remote.destination(),
+        remote.path,
+        if options.dry_run { " (dry run)" } else { "" }
+    ));
 
+    let mut args = vec!["-az".to_string()];
+    if !ctx.quiet || options.dry_run {
+        args.push("--itemize-changes".to_string());
+    }
+    if options.delete {
+        args.push("--delete".to_string());
+    }
+    if options.dry_run {
+        args.push("--dry-run".to_string());
+    }
+    args.extend(rsync_filters(remote));
+    args.push(format!("{}/", ctx.repo.display()));
+    args.push(format!(
+        "{}:{}/",
+        remote.destination(),
+        remote.path.trim_end_matches('/')
+    ));
+
+    let status = Command::new("rsync")
+        .args(&args)
+        .logged_status()
+        .context("Failed to run rsync")?;
+    if !status.success() {
+        return Err(anyhow!(
+            "rsync to {} exited with code {}",
+            name,
+            status.code().unwrap_or(-1)
+        ));
+    }
+
+    if !options.dry_run {
+        ctx.print_success(&format!("✓ Synced to {}", name));
+    }
     Ok(())
 }
 
-/// Execute command on remote
-pub fn exec_remote(ctx: &AppContext, remote: &str, command: &str) -> Result<()> {
-    ctx.print_info(&format!("Executing on {}: {}", remote, command));
+/// rsync filter rules for a remote. rsync uses the first rule that matches, so the
+/// excludes come first; `sync_patterns` are anchored at the repo root, and every
+/// directory is included so rsync descends to the files they match.
+pub fn rsync_filters(remote: &RemoteEntry) -> Vec<String> {
+    let mut filters = vec![
+        "--exclude=/.git/".to_string(),
+        "--filter=:- .gitignore".to_string(),
+    ];
+    filters.extend(
+        remote
+            .excludes
+            .iter()
+            .map(|pattern| format!("--exclude={}", pattern)),
+    );
 
-    // Parse remote config
-    // let config = load_remote_config(ctx, remote)?;
+    if !remote.sync_patterns.is_empty() {
+        filters.push("--include=*/".to_string());
+        for pattern in &remote.sync_patterns {
+            let pattern = format!(
+                "/{}",
+                pattern.trim_start_matches("./").trim_start_matches('/')
+            );
+            filters.push(format!("--include={}", pattern));
+            // A directory brings its contents
+            if !pattern.ends_with("**") {
+                filters.push(format!("--include={}/**", pattern.trim_end_matches('/')));
+            }
+        }
+        filters.push("--exclude=*".to_string());
+        filters.push("--prune-empty-dirs".to_string());
+    }
+    filters
+}
 
-    // Execute via SSH
-    let output = Command::new("ssh")
-        .args(&[remote, command])
-        .output()
+/// Run a command on the remote in the synced directory
+pub fn exec_remote(ctx: &AppContext, name: Option<&str>, command: &[String]) -> Result<()> {
+    let (name, remote) = resolve_remote(ctx, name)?;
+    let command = command.join(" ");
+    if !ctx.quiet {
+        println!("[remote:{}] {}", name, command);
+    }
+
+    let mut ssh = Command::new("ssh");
+    if ctx.is_interactive() {
+        ssh.arg("-t");
+    }
+    let status = ssh
+        .arg(remote.destination())
+        .arg(format!("cd {} && {}", shell_quote(&remote.path), command))
+        .logged_status()
         .context("Failed to execute remote command")?;
 
-    if output.status.success() {
-        println!("{}", String::from_utf8_lossy(&output.stdout));
-        ctx.print_success("✓ Command executed successfully");
-    } else {
-        ctx.print_error(&format!(
-            "Error: {}",
-            String::from_utf8_lossy(&output.stderr)
+    if !status.success() {
+        return Err(anyhow!(
+            "Remote command exited with code {}",
+            status.code().unwrap_or(-1)
         ));
     }
-
     Ok(())
 }
 
-/// Start port forwarding
-pub fn port_forward(
-    ctx: &AppContext,
-    remote: &str,
-    local_port: u16,
-    remote_port: u16,
-) -> Result<()> {
-    ctx.print_info(&format!(
-        "Forwarding localhost:{} -> {}:{}",
-        local_port, remote, remote_port
-    ));
+/// Forward the remote's `port_forwards` until interrupted, or in the background where
+/// `devkit forward` manages them
+pub fn port_forward(ctx: &AppContext, name: Option<&str>, background: bool) -> Result<()> {
+    let (name, remote) = resolve_remote(ctx, name)?;
+    let forwards = remote.forwards().map_err(|e| anyhow!(e))?;
+    if forwards.is_empty() {
+        return Err(anyhow!("[remote.{}] has no port_forwards", name));
+    }
 
-    let status = Command::new("ssh")
-        .args(&[
-            "-L",
-            &format!("{}:localhost:{}", local_port, remote_port),
-            "-N",
-            remote,
-        ])
-        .status()
+    for (local, remote_port) in &forwards {
+        ctx.print_info(&format!(
+            "Forwarding localhost:{} -> {}:{}",
+            local, name, remote_port
+        ));
+    }
+
+    let mut cmd = Command::new("ssh");
+    cmd.arg("-N").args(forward_args(&forwards));
+    if background {
+        // Nobody is there to type a password, and a taken port should fail the start
+        cmd.args(["-o", "BatchMode=yes", "-o", "ExitOnForwardFailure=yes"])
+            .arg(remote.destination());
+        let ports: Vec<u16> = forwards.iter().map(|(local, _)| *local).collect();
+        let forward = forwards::start(&ctx.repo, "remote", &name, &ports, cmd)?;
+        ctx.print_success(&format!(
+            "✓ Forwarding in the background (pid {}); stop with `devkit forward stop {}`",
+            forward.pid, ports[0]
+        ));
+        return Ok(());
+    }
+
+    let status = cmd
+        .arg(remote.destination())
+        .logged_status()
         .context("Failed to start port forwarding")?;
 
-    if !status.success() {
-        return Err(anyhow::anyhow!("Port forwarding failed"));
+    if !status.success() && status.code() != Some(130) {
+        return Err(anyhow!("Port forwarding failed"));
     }
 
     Ok(())
 }
 
-/// Watch and sync files on changes
-pub fn watch_and_sync(ctx: &AppContext, _remote: &str) -> Result<()> {
-    ctx.print_info("Starting file watcher for remote sync...");
-    ctx.print_info("This would watch files and rsync on changes");
-    ctx.print_info("Integration with devkit-tasks watch module");
+/// Sync, then sync again whenever files change
+pub fn watch_and_sync(ctx: &AppContext, name: Option<&str>, options: &SyncOptions) -> Result<()> {
+    let (name, remote) = resolve_remote(ctx, name)?;
+    let config = WatchConfig {
+        patterns: Vec::new(),
+        debounce_ms: 500,
+        clear_terminal: false,
+    };
+    watch_and_run(&ctx.repo, &config, || sync(ctx, &name, remote, options))
+}
 
-    Ok(())
+fn forward_args(forwards: &[(u16, u16)]) -> Vec<String> {
+    forwards
+        .iter()
+        .flat_map(|(local, remote)| ["-L".to_string(), format!("{}:localhost:{}", local, remote)])
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn remote(sync_patterns: &[&str], excludes: &[&str]) -> RemoteEntry {
+        RemoteEntry {
+            host: "staging.example.com".to_string(),
+            user: Some("deploy".to_string()),
+            path: "/app".to_string(),
+            sync_patterns: sync_patterns.iter().map(|p| p.to_string()).collect(),
+            excludes: excludes.iter().map(|p| p.to_string()).collect(),
+            port_forwards: vec!["8080".to_string(), "15432:5432".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_rsync_filters() {
+        assert_eq!(
+            rsync_filters(&remote(&[], &["*.log"])),
+            [
+                "--exclude=/.git/",
+                "--filter=:- .gitignore",
+                "--exclude=*.log"
+            ]
+        );
+        assert_eq!(
+            rsync_filters(&remote(&["src/**", "./Cargo.toml"], &[])),
+            [
+                "--exclude=/.git/",
+                "--filter=:- .gitignore",
+                "--include=*/",
+                "--include=/src/**",
+                "--include=/Cargo.toml",
+                "--include=/Cargo.toml/**",
+                "--exclude=*",
+                "--prune-empty-dirs",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_remote_entry() {
+        let remote = remote(&[], &[]);
+        assert_eq!(remote.destination(), "deploy@staging.example.com");
+        assert_eq!(remote.forwards(), Ok(vec![(8080, 8080), (15432, 5432)]));
+        assert_eq!(forward_args(&[(8080, 80)]), ["-L", "8080:localhost:80"]);
+    }
 }
@@ -0,0 +1,158 @@
+//! Functions available to scripts
+//!
+//! - `run(cmd)` - run a shell command in the repo root, throwing if it fails
+//! - `output(cmd)` - run a shell command and return its trimmed stdout
+//! - `config(path)` - value from `.dev/config.toml` by dotted path (`()` if unset)
+//! - `repo_root()`, `packages()` - repository path and package names
+//! - `args()` - extra arguments from `devkit run <script>:<function> ...`
+//! - `prompt(msg)`, `confirm(msg)` - ask the user (throw in quiet mode or without a terminal)
+//! - `info(msg)`, `success(msg)`, `warn(msg)` - styled output; `print` also works
+
+use console::style;
+use devkit_core::oplog::LoggedCommand;
+use devkit_core::AppContext;
+use dialoguer::{Confirm, Input};
+use rhai::{Array, Dynamic, Engine, EvalAltResult, Map};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+type ScriptResult<T> = Result<T, Box<EvalAltResult>>;
+
+/// Register the devkit API on an engine for one call
+pub(crate) fn register(engine: &mut Engine, ctx: &AppContext, args: &[String]) {
+    let repo = ctx.repo.clone();
+    let quiet = ctx.quiet;
+    let interactive = ctx.is_interactive();
+    let theme = ctx.config.global.output.prompt_theme();
+
+    let args: Array = args.iter().cloned().map(Dynamic::from).collect();
+    engine.register_fn("args", move || args.clone());
+
+    let root = repo.clone();
+    engine.register_fn("repo_root", move || root.display().to_string());
+
+    let mut names: Vec<String> = ctx.config.packages.keys().cloned().collect();
+    names.sort();
+    engine.register_fn("packages", move || -> Array {
+        names.iter().cloned().map(Dynamic::from).collect()
+    });
+
+    let config = load_config(&repo);
+    engine.register_fn("config", move |path: &str| -> Dynamic {
+        path.split('.')
+            .try_fold(&config, |value, key| value.get(key))
+            .map(to_dynamic)
+            .unwrap_or(Dynamic::UNIT)
+    });
+
+    let cwd = repo.clone();
+    engine.register_fn("run", move |command: &str| -> ScriptResult<()> {
+        if !quiet {
+            println!("{}", style(format!("$ {}", command)).dim());
+        }
+        let status = shell(command, &cwd)
+            .logged_status()
+            .map_err(|e| format!("Failed to run `{}`: {}", command, e))?;
+        if !status.success() {
+            return Err(format!(
+                "`{}` exited with code {}",
+                command,
+                status.code().unwrap_or(-1)
+            )
+            .into());
+        }
+        Ok(())
+    });
+
+    let cwd = repo;
+    engine.register_fn("output", move |command: &str| -> ScriptResult<String> {
+        let output = shell(command, &cwd)
+            .stderr(Stdio::inherit())
+            .logged_output()
+            .map_err(|e| format!("Failed to run `{}`: {}", command, e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "`{}` exited with code {}",
+                command,
+                output.status.code().unwrap_or(-1)
+            )
+            .into());
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    });
+
+    engine.register_fn("prompt", move |message: &str| -> ScriptResult<String> {
+        if !interactive {
+            return Err(format!("prompt(\"{}\") needs an interactive terminal", message).into());
+        }
+        Input::<String>::with_theme(&*theme.dialoguer())
+            .with_prompt(message)
+            .allow_empty(true)
+            .interact_text()
+            .map_err(|e| e.to_string().into())
+    });
+
+    engine.register_fn("confirm", move |message: &str| -> ScriptResult<bool> {
+        if !interactive {
+            return Err(format!("confirm(\"{}\") needs an interactive terminal", message).into());
+        }
+        Confirm::with_theme(&*theme.dialoguer())
+            .with_prompt(message)
+            .interact()
+            .map_err(|e| e.to_string().into())
+    });
+
+    engine.register_fn("info", move |message: &str| {
+        if !quiet {
+            println!("{}", style(message).cyan());
+        }
+    });
+    engine.register_fn("success", move |message: &str| {
+        if !quiet {
+            println!("{}", style(message).green());
+        }
+    });
+    engine.register_fn("warn", move |message: &str| {
+        if !quiet {
+            println!("{}", style(message).yellow());
+        }
+    });
+}
+
+/// Raw `.dev/config.toml`, so scripts can read any section (including `[ext.*]`)
+fn load_config(repo: &Path) -> toml::Value {
+    std::fs::read_to_string(repo.join(".dev/config.toml"))
+        .ok()
+        .and_then(|contents| toml::from_str(&contents).ok())
+        .unwrap_or_else(|| toml::Value::Table(Default::default()))
+}
+
+fn to_dynamic(value: &toml::Value) -> Dynamic {
+    match value {
+        toml::Value::String(s) => s.clone().into(),
+        toml::Value::Integer(i) => (*i).into(),
+        toml::Value::Float(f) => (*f).into(),
+        toml::Value::Boolean(b) => (*b).into(),
+        toml::Value::Datetime(d) => d.to_string().into(),
+        toml::Value::Array(items) => items.iter().map(to_dynamic).collect::<Array>().into(),
+        toml::Value::Table(table) => table
+            .iter()
+            .map(|(key, value)| (key.as_str().into(), to_dynamic(value)))
+            .collect::<Map>()
+            .into(),
+    }
+}
+
+#[cfg(windows)]
+fn shell(command: &str, cwd: &Path) -> Command {
+    let mut cmd = Command::new("cmd");
+    cmd.args(["/C", command]).current_dir(cwd);
+    cmd
+}
+
+#[cfg(not(windows))]
+fn shell(command: &str, cwd: &Path) -> Command {
+    let mut cmd = Command::new("sh");
+    cmd.args(["-c", command]).current_dir(cwd);
+    cmd
+}
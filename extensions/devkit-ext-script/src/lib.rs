@@ -0,0 +1,199 @@
+//! Rhai scripting extension
+//!
+//! Loads `.dev/scripts/*.rhai`. Every public function without parameters becomes a
+//! menu item (and a `devkit run <script>:<function>` action), labeled by the first
+//! line of its doc comment:
+//!
+//! ```rhai
+//! /// Regenerate API types
+//! fn codegen() {
+//!     let schema = config("ext.codegen.schema") ?? "schema.graphql";
+//!     run(`npx graphql-codegen --schema ${schema}`);
+//!     success("Types regenerated");
+//! }
+//!
+//! private fn helper() { ... }   // not exposed
+//! ```
+//!
+//! Scripts are for glue logic too complex for a single shell command but not worth a
+//! compiled extension. The functions they can call are listed in `api.rs`.
+
+use anyhow::{anyhow, Context as _, Result};
+use devkit_core::{AppContext, Extension, MenuItem};
+use rhai::{CallFnOptions, Dynamic, Engine, FnAccess, Scope, AST};
+use std::path::{Path, PathBuf};
+
+mod api;
+
+/// A script function exposed as an action
+#[derive(Debug, Clone)]
+pub struct ScriptFunction {
+    pub name: String,
+    /// First doc comment line, or the function name
+    pub label: String,
+}
+
+/// A compiled `.rhai` script
+#[derive(Clone)]
+pub struct Script {
+    name: String,
+    path: PathBuf,
+    ast: AST,
+    functions: Vec<ScriptFunction>,
+}
+
+impl Script {
+    /// Compile a script and collect its public functions
+    pub fn load(path: &Path) -> Result<Self> {
+        let name = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .ok_or_else(|| anyhow!("Invalid script name: {}", path.display()))?
+            .to_string();
+
+        let ast = Engine::new()
+            .compile_file(path.to_path_buf())
+            .map_err(|e| anyhow!("{}", e))
+            .with_context(|| format!("Failed to compile {}", path.display()))?;
+
+        let mut functions: Vec<ScriptFunction> = ast
+            .iter_functions()
+            .filter(|f| f.access == FnAccess::Public && f.params.is_empty())
+            .map(|f| ScriptFunction {
+                name: f.name.to_string(),
+                label: f
+                    .comments
+                    .iter()
+                    .flat_map(|comment| comment.lines())
+                    .map(|line| line.trim_start_matches('/').trim_start_matches('*').trim())
+                    .find(|line| !line.is_empty())
+                    .unwrap_or(f.name)
+                    .to_string(),
+            })
+            .collect();
+        functions.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(Self {
+            name,
+            path: path.to_path_buf(),
+            ast,
+            functions,
+        })
+    }
+
+    /// Script name (file stem)
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Functions exposed as actions, sorted by name
+    pub fn functions(&self) -> &[ScriptFunction] {
+        &self.functions
+    }
+
+    /// Call one of the script's functions with the devkit API available
+    ///
+    /// `args` are returned by the script's `args()`.
+    pub fn call(&self, ctx: &AppContext, function: &str, args: &[String]) -> Result<()> {
+        if !self.functions.iter().any(|f| f.name == function) {
+            return Err(anyhow!(
+                "Script '{}' has no function '{}'",
+                self.name,
+                function
+            ));
+        }
+
+        let mut engine = Engine::new();
+        api::register(&mut engine, ctx, args);
+
+        // Top-level statements run first, so scripts can set up constants
+        engine
+            .call_fn_with_options::<Dynamic>(
+                CallFnOptions::new(),
+                &mut Scope::new(),
+                &self.ast,
+                function,
+                (),
+            )
+            .map(|_| ())
+            .map_err(|e| anyhow!("{}:{}: {}", self.name, function, e))
+    }
+}
+
+/// Directory scripts are loaded from
+pub fn scripts_dir(repo_root: &Path) -> PathBuf {
+    repo_root.join(".dev/scripts")
+}
+
+fn script_paths(repo_root: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(scripts_dir(repo_root)) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "rhai"))
+        .collect();
+    paths.sort();
+    paths
+}
+
+/// Compile every script in `.dev/scripts`
+///
+/// Scripts that fail to compile are logged and skipped.
+pub fn load_scripts(repo_root: &Path) -> Vec<Script> {
+    script_paths(repo_root)
+        .into_iter()
+        .filter_map(|path| match Script::load(&path) {
+            Ok(script) => Some(script),
+            Err(e) => {
+                tracing::warn!("✗ Failed to load script: {:#}", e);
+                None
+            }
+        })
+        .collect()
+}
+
+/// Menu items for the functions in `.dev/scripts/*.rhai`
+pub struct ScriptExtension;
+
+impl Extension for ScriptExtension {
+    fn name(&self) -> &str {
+        "script"
+    }
+
+    fn is_available(&self, ctx: &AppContext) -> bool {
+        !script_paths(&ctx.repo).is_empty()
+    }
+
+    fn unavailable_reason(&self, _ctx: &AppContext) -> Option<String> {
+        Some("no .dev/scripts/*.rhai files".to_string())
+    }
+
+    fn menu_items(&self, ctx: &AppContext) -> Vec<MenuItem> {
+        load_scripts(&ctx.repo)
+            .into_iter()
+            .flat_map(|script| {
+                let group = format!("📜 {}", script.name());
+                script
+                    .functions()
+                    .to_vec()
+                    .into_iter()
+                    .map(move |function| {
+                        let script = script.clone();
+                        MenuItem {
+                            label: function.label,
+                            group: Some(group.clone()),
+                            handler: Box::new(move |ctx| {
+                                script.call(ctx, &function.name, &[]).map_err(Into::into)
+                            }),
+                        }
+                    })
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,66 @@
+use devkit_core::{AppContext, Config};
+use devkit_ext_script::Script;
+use std::path::{Path, PathBuf};
+
+fn fixture() -> Script {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/tools.rhai");
+    Script::load(&path).unwrap()
+}
+
+/// A scratch repo with `[ext.tools]` settings
+fn repo(name: &str) -> PathBuf {
+    let repo = std::env::temp_dir().join(format!("devkit-script-{}-{}", name, std::process::id()));
+    std::fs::create_dir_all(repo.join(".dev")).unwrap();
+    std::fs::write(
+        repo.join(".dev/config.toml"),
+        "[ext.tools]\nname = \"fixture\"\n",
+    )
+    .unwrap();
+    repo
+}
+
+fn context(repo: PathBuf) -> AppContext {
+    AppContext {
+        repo,
+        quiet: true,
+        config: Config::default(),
+        features: Default::default(),
+    }
+}
+
+#[test]
+fn test_load_script_functions() {
+    let script = fixture();
+    assert_eq!(script.name(), "tools");
+
+    let functions: Vec<(&str, &str)> = script
+        .functions()
+        .iter()
+        .map(|f| (f.name.as_str(), f.label.as_str()))
+        .collect();
+    assert_eq!(
+        functions,
+        [
+            ("check", "Check config lookups and command output"),
+            ("fail", "fail"),
+            ("greet", "Print a greeting"),
+        ]
+    );
+}
+
+#[test]
+fn test_call_script_function() {
+    let script = fixture();
+    let repo = repo("call");
+    let ctx = context(repo.clone());
+
+    assert!(script.call(&ctx, "greet", &[]).is_ok());
+    script.call(&ctx, "check", &[]).unwrap();
+
+    let err = script.call(&ctx, "fail", &[]).unwrap_err();
+    assert!(err.to_string().contains("exited with code 3"));
+    let err = script.call(&ctx, "helper", &[]).unwrap_err();
+    assert!(err.to_string().contains("has no function 'helper'"));
+
+    std::fs::remove_dir_all(repo).ok();
+}
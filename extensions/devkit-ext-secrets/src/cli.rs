@@ -0,0 +1,50 @@
+//! `devkit secrets` subcommand
+
+use anyhow::Result;
+use clap::{Args, Command, FromArgMatches, Subcommand};
+use devkit_core::AppContext;
+
+use crate::{get_secret, list_secrets, pull_secrets, push_secrets};
+
+#[derive(Args)]
+pub struct SecretsArgs {
+    #[command(subcommand)]
+    action: SecretsAction,
+}
+
+#[derive(Subcommand)]
+enum SecretsAction {
+    /// Pull secrets from the configured provider into .env
+    Pull,
+    /// List available secrets
+    List,
+    /// Print one secret's value
+    Get {
+        /// Secret name (or an op:// reference for 1Password)
+        key: String,
+    },
+    /// Create or update secrets at the environment's path
+    Push {
+        /// KEY=value pairs
+        #[arg(required = true)]
+        secrets: Vec<String>,
+    },
+}
+
+pub fn command() -> Command {
+    SecretsArgs::augment_args(
+        Command::new("secrets")
+            .about("Secrets management (AWS Secrets Manager, 1Password, Doppler, Vault)"),
+    )
+}
+
+pub fn handle(ctx: &AppContext, matches: &clap::ArgMatches) -> Result<()> {
+    let args = SecretsArgs::from_arg_matches(matches)?;
+
+    match args.action {
+        SecretsAction::Pull => pull_secrets(ctx),
+        SecretsAction::List => list_secrets(ctx),
+        SecretsAction::Get { key } => get_secret(ctx, &key),
+        SecretsAction::Push { secrets } => push_secrets(ctx, &secrets),
+    }
+}
@@ -1,13 +1,34 @@
 //! Secrets management extension
 //!
-//! Supports multiple providers: AWS Secrets Manager, 1Password, Doppler, environment files
-
-use anyhow::{Context, Result};
+//! Supports multiple providers: AWS Secrets Manager, 1Password, Doppler, HashiCorp Vault
+//!
+//! `[secrets] provider` picks the provider; without it the first installed CLI of
+//! 1Password, Doppler and AWS is used (Vault only when chosen). The secret path for the active environment
+//! comes from `[secrets.paths]`, the environment's `secrets` setting or
+//! `[secrets] path`. Secrets are saved to `.env.local` for the default environment
+//! and to `.env.<env>.local` for others.
+
+mod cli;
+pub mod provider;
+pub mod vault;
+
+use anyhow::{anyhow, Context, Result};
+use devkit_core::config::SecretsProviderKind;
+use devkit_core::oplog::LoggedCommand;
 use devkit_core::{AppContext, Extension, MenuItem};
-use std::collections::HashMap;
 use std::fs;
+use std::path::PathBuf;
 use std::process::Command;
 
+pub use provider::{provider, SecretsProvider};
+
+/// Detection order when `[secrets] provider` isn't set
+const DETECT_ORDER: [SecretsProviderKind; 3] = [
+    SecretsProviderKind::OnePassword,
+    SecretsProviderKind::Doppler,
+    SecretsProviderKind::Aws,
+];
+
 pub struct SecretsExtension;
 
 impl Extension for SecretsExtension {
@@ -15,9 +36,12 @@ impl Extension for SecretsExtension {
         "secrets"
     }
 
-    fn is_available(&self, _ctx: &AppContext) -> bool {
-        // Available if any secrets tool is installed
-        cmd_exists("aws") || cmd_exists("op") || cmd_exists("doppler")
+    fn is_available(&self, ctx: &AppContext) -> bool {
+        // Available if the configured (or any) secrets tool is installed
+        match ctx.config.global.secrets.provider {
+            Some(kind) => cmd_exists(kind.binary()),
+            None => DETECT_ORDER.iter().any(|kind| cmd_exists(kind.binary())),
+        }
     }
 
     fn menu_items(&self, _ctx: &AppContext) -> Vec<MenuItem> {
@@ -34,191 +58,141 @@ impl Extension for SecretsExtension {
             },
         ]
     }
+
+    fn subcommands(&self) -> Vec<clap::Command> {
+        vec![cli::command()]
+    }
+
+    fn handle_cli(
+        &self,
+        ctx: &AppContext,
+        name: &str,
+        matches: &clap::ArgMatches,
+    ) -> Option<devkit_core::Result<()>> {
+        (name == "secrets").then(|| cli::handle(ctx, matches).map_err(Into::into))
+    }
 }
 
 fn cmd_exists(cmd: &str) -> bool {
     Command::new("which")
         .arg(cmd)
-        .output()
+        .logged_output_redacted()
         .map(|output| output.status.success())
         .unwrap_or(false)
 }
 
-/// Pull secrets from configured provider
-pub fn pull_secrets(ctx: &AppContext) -> Result<()> {
-    ctx.print_header("Pulling Secrets");
-
-    // Check for 1Password CLI
-    if cmd_exists("op") {
-        return pull_from_1password(ctx);
+/// The configured provider, or the first one whose CLI is installed
+fn active_provider(ctx: &AppContext) -> Result<Option<Box<dyn SecretsProvider>>> {
+    match ctx.config.global.secrets.provider {
+        Some(kind) if !cmd_exists(kind.binary()) => Err(anyhow!(
+            "{} is the configured secrets provider but `{}` isn't installed",
+            provider(kind, ctx).name(),
+            kind.binary()
+        )),
+        Some(kind) => Ok(Some(provider(kind, ctx))),
+        None => Ok(DETECT_ORDER
+            .iter()
+            .find(|kind| cmd_exists(kind.binary()))
+            .map(|kind| provider(*kind, ctx))),
     }
-
-    // Check for Doppler
-    if cmd_exists("doppler") {
-        return pull_from_doppler(ctx);
-    }
-
-    // Check for AWS CLI
-    if cmd_exists("aws") {
-        return pull_from_aws(ctx);
-    }
-
-    ctx.print_warning("No secrets provider found");
-    ctx.print_info("Install: aws-cli, 1password-cli, or doppler");
-
-    Ok(())
 }
 
-fn pull_from_1password(ctx: &AppContext) -> Result<()> {
-    ctx.print_info("Pulling from 1Password...");
-
-    // Example: op run --env-file=.env -- env | grep -v "^#" > .env.local
-    let output = Command::new("op")
-        .args(["run", "--", "env"])
-        .output()
-        .context("Failed to run 1Password CLI")?;
-
-    if !output.status.success() {
-        return Err(anyhow::anyhow!(
-            "1Password CLI failed: {}",
-            String::from_utf8_lossy(&output.stderr)
-        ));
-    }
-
-    let env_path = ctx.repo.join(".env.local");
-    fs::write(&env_path, &output.stdout).context("Failed to write .env.local")?;
-
-    ctx.print_success(&format!("✓ Secrets saved to {}", env_path.display()));
-
-    Ok(())
+/// The active provider, or an error saying how to get one
+fn require_provider(ctx: &AppContext) -> Result<Box<dyn SecretsProvider>> {
+    active_provider(ctx)?
+        .context("No secrets provider found (install aws-cli, 1password-cli, or doppler)")
 }
 
-fn pull_from_doppler(ctx: &AppContext) -> Result<()> {
-    ctx.print_info("Pulling from Doppler...");
-
-    let output = Command::new("doppler")
-        .args(["secrets", "download", "--no-file", "--format", "env"])
-        .output()
-        .context("Failed to run Doppler CLI")?;
+/// The active environment's secret path
+fn secrets_path(ctx: &AppContext) -> Option<&str> {
+    let environments = &ctx.config.global.environments;
+    ctx.config
+        .global
+        .secrets
+        .path_for(environments.current(), environments)
+}
 
-    if !output.status.success() {
-        return Err(anyhow::anyhow!(
-            "Doppler CLI failed: {}",
-            String::from_utf8_lossy(&output.stderr)
-        ));
+/// Env file pulled secrets go to
+fn env_path(ctx: &AppContext) -> PathBuf {
+    let environments = &ctx.config.global.environments;
+    if environments.current() == environments.default {
+        ctx.repo.join(".env.local")
+    } else {
+        ctx.repo
+            .join(format!(".env.{}.local", environments.current()))
     }
+}
 
-    let env_path = ctx.repo.join(".env.local");
-    fs::write(&env_path, &output.stdout).context("Failed to write .env.local")?;
+fn save_env(ctx: &AppContext, contents: impl AsRef<[u8]>) -> Result<()> {
+    let env_path = env_path(ctx);
+    fs::write(&env_path, contents)
+        .with_context(|| format!("Failed to write {}", env_path.display()))?;
 
     ctx.print_success(&format!("✓ Secrets saved to {}", env_path.display()));
 
     Ok(())
 }
 
-fn pull_from_aws(ctx: &AppContext) -> Result<()> {
-    ctx.print_info("Pulling from AWS Secrets Manager...");
-    ctx.print_warning("AWS integration requires additional configuration");
-    ctx.print_info("Set AWS_SECRET_NAME environment variable");
-
-    let secret_name = std::env::var("AWS_SECRET_NAME").context("AWS_SECRET_NAME not set")?;
-
-    let output = Command::new("aws")
-        .args([
-            "secretsmanager",
-            "get-secret-value",
-            "--secret-id",
-            &secret_name,
-            "--query",
-            "SecretString",
-            "--output",
-            "text",
-        ])
-        .output()
-        .context("Failed to run AWS CLI")?;
-
-    if !output.status.success() {
-        return Err(anyhow::anyhow!(
-            "AWS CLI failed: {}",
-            String::from_utf8_lossy(&output.stderr)
-        ));
-    }
-
-    let secrets_json = String::from_utf8_lossy(&output.stdout);
-    let secrets: HashMap<String, String> =
-        serde_json::from_str(&secrets_json).context("Failed to parse secrets JSON")?;
-
-    let mut env_content = String::new();
-    for (key, value) in secrets {
-        env_content.push_str(&format!("{}={}\n", key, value));
-    }
-
-    let env_path = ctx.repo.join(".env.local");
-    fs::write(&env_path, env_content).context("Failed to write .env.local")?;
+/// Pull secrets from configured provider
+pub fn pull_secrets(ctx: &AppContext) -> Result<()> {
+    ctx.print_header("Pulling Secrets");
 
-    ctx.print_success(&format!("✓ Secrets saved to {}", env_path.display()));
+    let Some(provider) = active_provider(ctx)? else {
+        ctx.print_warning("No secrets provider found");
+        ctx.print_info("Install: aws-cli, 1password-cli, or doppler");
+        return Ok(());
+    };
 
-    Ok(())
+    ctx.print_info(&format!("Pulling from {}...", provider.name()));
+    let contents = provider.pull(secrets_path(ctx))?;
+    save_env(ctx, contents)
 }
 
 /// List available secrets
 pub fn list_secrets(ctx: &AppContext) -> Result<()> {
     ctx.print_header("Available Secrets");
 
-    if cmd_exists("op") {
-        list_1password_secrets(ctx)?;
-    } else if cmd_exists("doppler") {
-        list_doppler_secrets(ctx)?;
-    } else if cmd_exists("aws") {
-        list_aws_secrets(ctx)?;
-    } else {
+    let Some(provider) = active_provider(ctx)? else {
         ctx.print_warning("No secrets provider found");
+        return Ok(());
+    };
+
+    let path = secrets_path(ctx);
+    ctx.print_info(&match path {
+        Some(path) => format!("{} secrets in {}:", provider.name(), path),
+        None => format!("{}:", provider.name()),
+    });
+    for name in provider.list(path)? {
+        println!("  {}", name);
     }
 
     Ok(())
 }
 
-fn list_1password_secrets(ctx: &AppContext) -> Result<()> {
-    ctx.print_info("1Password vaults:");
-
-    let output = Command::new("op")
-        .args(["vault", "list", "--format", "json"])
-        .output()
-        .context("Failed to list 1Password vaults")?;
-
-    if output.status.success() {
-        println!("{}", String::from_utf8_lossy(&output.stdout));
-    }
-
-    Ok(())
-}
-
-fn list_doppler_secrets(ctx: &AppContext) -> Result<()> {
-    ctx.print_info("Doppler secrets:");
-
-    let output = Command::new("doppler")
-        .args(["secrets"])
-        .output()
-        .context("Failed to list Doppler secrets")?;
-
-    if output.status.success() {
-        println!("{}", String::from_utf8_lossy(&output.stdout));
-    }
-
+/// Print one secret's value, for use in scripts
+pub fn get_secret(ctx: &AppContext, key: &str) -> Result<()> {
+    let provider = require_provider(ctx)?;
+    println!("{}", provider.get(secrets_path(ctx), key)?);
     Ok(())
 }
 
-fn list_aws_secrets(ctx: &AppContext) -> Result<()> {
-    ctx.print_info("AWS secrets:");
-
-    let output = Command::new("aws")
-        .args(["secretsmanager", "list-secrets"])
-        .output()
-        .context("Failed to list AWS secrets")?;
-
-    if output.status.success() {
-        println!("{}", String::from_utf8_lossy(&output.stdout));
-    }
-
+/// Create or update secrets given as `KEY=value`
+pub fn push_secrets(ctx: &AppContext, pairs: &[String]) -> Result<()> {
+    let secrets = pairs
+        .iter()
+        .map(|pair| {
+            pair.split_once('=')
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .ok_or_else(|| anyhow!("Expected KEY=value, got '{}'", pair))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let provider = require_provider(ctx)?;
+    provider.push(secrets_path(ctx), &secrets)?;
+    ctx.print_success(&format!(
+        "✓ Pushed {} secret(s) to {}",
+        secrets.len(),
+        provider.name()
+    ));
     Ok(())
 }
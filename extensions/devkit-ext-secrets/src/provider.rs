@@ -0,0 +1,328 @@
+//! Secrets providers
+//!
+//! Each provider wraps its CLI. The secret path is provider-specific: a Doppler
+//! config, an AWS Secrets Manager secret id, or an env file of `op://` references
+//! for 1Password, or a KV v2 path for Vault. CLIs run from the repo root, where Doppler looks up its project
+//! setup and relative env files resolve.
+
+use anyhow::{anyhow, bail, Context, Result};
+use devkit_core::config::SecretsProviderKind;
+use devkit_core::oplog::LoggedCommand;
+use devkit_core::AppContext;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::vault::Vault;
+
+/// A place secrets are pulled from and pushed to
+pub trait SecretsProvider {
+    /// Display name, e.g. "Doppler"
+    fn name(&self) -> &'static str;
+
+    /// The secrets at `path` as `KEY=value` lines
+    fn pull(&self, path: Option<&str>) -> Result<String>;
+
+    /// Secret names at `path`, or what's available when there's no path
+    fn list(&self, path: Option<&str>) -> Result<Vec<String>>;
+
+    /// One secret's value
+    fn get(&self, path: Option<&str>, key: &str) -> Result<String>;
+
+    /// Create or update secrets at `path`
+    fn push(&self, path: Option<&str>, secrets: &[(String, String)]) -> Result<()>;
+}
+
+/// The provider for a `[secrets] provider` setting, run from the repo root
+pub fn provider(kind: SecretsProviderKind, ctx: &AppContext) -> Box<dyn SecretsProvider> {
+    let repo = ctx.repo.clone();
+    match kind {
+        SecretsProviderKind::Aws => Box::new(AwsSecretsManager { repo }),
+        SecretsProviderKind::OnePassword => Box::new(OnePassword { repo }),
+        SecretsProviderKind::Doppler => Box::new(Doppler { repo }),
+        SecretsProviderKind::Vault => {
+            Box::new(Vault::new(repo, ctx.config.global.secrets.vault.clone()))
+        }
+    }
+}
+
+/// AWS Secrets Manager; a secret is a JSON object of key/value pairs
+pub struct AwsSecretsManager {
+    repo: PathBuf,
+}
+
+impl AwsSecretsManager {
+    /// `AWS_SECRET_NAME` wins over the configured path
+    fn secret_id(path: Option<&str>) -> Result<String> {
+        std::env::var("AWS_SECRET_NAME")
+            .ok()
+            .or_else(|| path.map(String::from))
+            .context("AWS_SECRET_NAME not set and no secret id for the environment in [secrets]")
+    }
+
+    fn values(&self, secret_id: &str) -> Result<BTreeMap<String, String>> {
+        let json = run(
+            command("aws", &self.repo).args([
+                "secretsmanager",
+                "get-secret-value",
+                "--secret-id",
+                secret_id,
+                "--query",
+                "SecretString",
+                "--output",
+                "text",
+            ]),
+            "AWS CLI",
+        )?;
+        serde_json::from_str(&json).context("Failed to parse secrets JSON")
+    }
+}
+
+impl SecretsProvider for AwsSecretsManager {
+    fn name(&self) -> &'static str {
+        "AWS Secrets Manager"
+    }
+
+    fn pull(&self, path: Option<&str>) -> Result<String> {
+        Ok(env_lines(&self.values(&Self::secret_id(path)?)?))
+    }
+
+    fn list(&self, path: Option<&str>) -> Result<Vec<String>> {
+        if let Ok(secret_id) = Self::secret_id(path) {
+            return Ok(self.values(&secret_id)?.into_keys().collect());
+        }
+        let names = run(
+            command("aws", &self.repo).args([
+                "secretsmanager",
+                "list-secrets",
+                "--query",
+                "SecretList[].Name",
+                "--output",
+                "text",
+            ]),
+            "AWS CLI",
+        )?;
+        Ok(names.split_whitespace().map(String::from).collect())
+    }
+
+    fn get(&self, path: Option<&str>, key: &str) -> Result<String> {
+        let secret_id = Self::secret_id(path)?;
+        self.values(&secret_id)?
+            .remove(key)
+            .ok_or_else(|| anyhow!("Secret '{}' not found in {}", key, secret_id))
+    }
+
+    fn push(&self, path: Option<&str>, secrets: &[(String, String)]) -> Result<()> {
+        let secret_id = Self::secret_id(path)?;
+        let mut values = self.values(&secret_id)?;
+        values.extend(secrets.iter().cloned());
+        run(
+            command("aws", &self.repo)
+                .args(["secretsmanager", "put-secret-value", "--secret-id"])
+                .arg(&secret_id)
+                .arg("--secret-string")
+                .arg(serde_json::to_string(&values)?),
+            "AWS CLI",
+        )?;
+        Ok(())
+    }
+}
+
+/// Doppler; the path is the config (e.g. `dev`, `stg`)
+pub struct Doppler {
+    repo: PathBuf,
+}
+
+impl Doppler {
+    /// `doppler secrets <args>` for the config at `path`
+    fn secrets<'a>(&self, path: Option<&str>, args: impl IntoIterator<Item = &'a str>) -> Command {
+        let mut cmd = command("doppler", &self.repo);
+        cmd.arg("secrets").args(args);
+        if let Some(config) = path {
+            cmd.args(["--config", config]);
+        }
+        cmd
+    }
+}
+
+impl SecretsProvider for Doppler {
+    fn name(&self) -> &'static str {
+        "Doppler"
+    }
+
+    fn pull(&self, path: Option<&str>) -> Result<String> {
+        run(
+            &mut self.secrets(path, ["download", "--no-file", "--format", "env"]),
+            "Doppler CLI",
+        )
+    }
+
+    fn list(&self, path: Option<&str>) -> Result<Vec<String>> {
+        let json = run(
+            &mut self.secrets(path, ["download", "--no-file", "--format", "json"]),
+            "Doppler CLI",
+        )?;
+        let secrets: BTreeMap<String, Value> =
+            serde_json::from_str(&json).context("Failed to parse Doppler secrets")?;
+        Ok(secrets.into_keys().collect())
+    }
+
+    fn get(&self, path: Option<&str>, key: &str) -> Result<String> {
+        let value = run(
+            &mut self.secrets(path, ["get", key, "--plain"]),
+            "Doppler CLI",
+        )?;
+        Ok(value.trim_end_matches('\n').to_string())
+    }
+
+    fn push(&self, path: Option<&str>, secrets: &[(String, String)]) -> Result<()> {
+        let pairs: Vec<String> = secrets
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect();
+        let mut cmd = self.secrets(
+            path,
+            std::iter::once("set").chain(pairs.iter().map(String::as_str)),
+        );
+        run(&mut cmd, "Doppler CLI")?;
+        Ok(())
+    }
+}
+
+/// 1Password; the path is an env file whose values are `op://` references
+pub struct OnePassword {
+    repo: PathBuf,
+}
+
+impl OnePassword {
+    fn template(path: Option<&str>) -> Result<&str> {
+        path.context(
+            "1Password needs an env file of op:// references: set [secrets] path or [secrets.paths]",
+        )
+    }
+}
+
+impl SecretsProvider for OnePassword {
+    fn name(&self) -> &'static str {
+        "1Password"
+    }
+
+    fn pull(&self, path: Option<&str>) -> Result<String> {
+        run(
+            command("op", &self.repo).args(["inject", "--in-file", Self::template(path)?]),
+            "1Password CLI",
+        )
+    }
+
+    fn list(&self, path: Option<&str>) -> Result<Vec<String>> {
+        if let Some(template) = path {
+            let contents = fs::read_to_string(self.repo.join(template))
+                .with_context(|| format!("Failed to read {}", template))?;
+            return Ok(parse_env(&contents).into_keys().collect());
+        }
+        let json = run(
+            command("op", &self.repo).args(["vault", "list", "--format", "json"]),
+            "1Password CLI",
+        )?;
+        let vaults: Vec<Value> =
+            serde_json::from_str(&json).context("Failed to parse 1Password vaults")?;
+        Ok(vaults
+            .iter()
+            .filter_map(|vault| vault["name"].as_str().map(String::from))
+            .collect())
+    }
+
+    fn get(&self, path: Option<&str>, key: &str) -> Result<String> {
+        let reference = if key.starts_with("op://") {
+            key.to_string()
+        } else {
+            let template = Self::template(path)?;
+            let contents = fs::read_to_string(self.repo.join(template))
+                .with_context(|| format!("Failed to read {}", template))?;
+            parse_env(&contents)
+                .remove(key)
+                .ok_or_else(|| anyhow!("Secret '{}' not found in {}", key, template))?
+        };
+        let value = run(
+            command("op", &self.repo).args(["read", &reference]),
+            "1Password CLI",
+        )?;
+        Ok(value.trim_end_matches('\n').to_string())
+    }
+
+    fn push(&self, path: Option<&str>, _secrets: &[(String, String)]) -> Result<()> {
+        bail!(
+            "Pushing to 1Password isn't supported; edit the items in 1Password and reference them from {}",
+            path.unwrap_or("your env file")
+        )
+    }
+}
+
+pub(crate) fn command(program: &str, repo: &Path) -> Command {
+    let mut cmd = Command::new(program);
+    cmd.current_dir(repo);
+    cmd
+}
+
+/// Run a provider CLI, returning its stdout; output is redacted in the operation log
+pub(crate) fn run(cmd: &mut Command, cli: &str) -> Result<String> {
+    let output = cmd
+        .logged_output_redacted()
+        .with_context(|| format!("Failed to run {}", cli))?;
+    if !output.status.success() {
+        bail!(
+            "{} failed: {}",
+            cli,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// `KEY=value` lines, sorted by key
+pub(crate) fn env_lines(secrets: &BTreeMap<String, String>) -> String {
+    secrets
+        .iter()
+        .map(|(key, value)| format!("{}={}\n", key, value))
+        .collect()
+}
+
+/// Keys and raw values of an env file, skipping comments and `export`
+fn parse_env(contents: &str) -> BTreeMap<String, String> {
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| line.strip_prefix("export ").unwrap_or(line).split_once('='))
+        .map(|(key, value)| {
+            let value = value.trim().trim_matches('"').trim_matches('\'');
+            (key.trim().to_string(), value.to_string())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_env() {
+        let env = parse_env(
+            "# 1Password references\n\
+             DATABASE_URL=\"op://dev/postgres/url\"\n\
+             export API_KEY=op://dev/api/credential\n\
+             \n\
+             not a variable\n",
+        );
+        assert_eq!(env["DATABASE_URL"], "op://dev/postgres/url");
+        assert_eq!(env["API_KEY"], "op://dev/api/credential");
+        assert_eq!(env.len(), 2);
+
+        assert_eq!(
+            env_lines(&env),
+            "API_KEY=op://dev/api/credential\nDATABASE_URL=op://dev/postgres/url\n"
+        );
+    }
+}
@@ -0,0 +1,209 @@
+//! HashiCorp Vault provider
+//!
+//! Reads and writes KV v2 secrets with the `vault` CLI, against `[secrets.vault]
+//! address` or `VAULT_ADDR`. Token auth uses whatever the CLI finds (`VAULT_TOKEN`
+//! or the token `vault login` saved); AppRole logs in once per run and passes the
+//! token on in `VAULT_TOKEN`.
+
+use anyhow::{bail, Context, Result};
+use devkit_core::config::{VaultAuth, VaultConfig};
+use devkit_core::oplog::LoggedCommand;
+use serde_json::Value;
+use std::cell::OnceCell;
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Output, Stdio};
+
+use crate::provider::{command, env_lines, run, SecretsProvider};
+
+pub struct Vault {
+    repo: PathBuf,
+    config: VaultConfig,
+    token: OnceCell<String>,
+}
+
+impl Vault {
+    pub fn new(repo: PathBuf, config: VaultConfig) -> Self {
+        Self {
+            repo,
+            config,
+            token: OnceCell::new(),
+        }
+    }
+
+    /// `vault <args>`, logged in
+    fn vault<'a>(&self, args: impl IntoIterator<Item = &'a str>) -> Result<Command> {
+        let mut cmd = self.base_command();
+        if self.config.auth == VaultAuth::AppRole {
+            cmd.env("VAULT_TOKEN", self.approle_token()?);
+        }
+        cmd.args(args);
+        Ok(cmd)
+    }
+
+    fn base_command(&self) -> Command {
+        let mut cmd = command("vault", &self.repo);
+        if let Some(address) = &self.config.address {
+            cmd.env("VAULT_ADDR", address);
+        }
+        cmd
+    }
+
+    /// Log in with AppRole, passing the secret id on stdin so it stays out of `ps`
+    fn approle_token(&self) -> Result<&str> {
+        if let Some(token) = self.token.get() {
+            return Ok(token);
+        }
+        let role_id = self
+            .config
+            .role_id
+            .as_deref()
+            .context("[secrets.vault] auth = \"approle\" needs a role_id")?;
+        let secret_id = std::env::var(&self.config.secret_id_env).with_context(|| {
+            format!(
+                "{} isn't set (the AppRole secret id)",
+                self.config.secret_id_env
+            )
+        })?;
+
+        let output = with_stdin(
+            self.base_command()
+                .args(["write", "-field=token", "auth/approle/login"])
+                .arg(format!("role_id={}", role_id))
+                .arg("secret_id=-"),
+            secret_id.as_bytes(),
+        )?;
+        if !output.status.success() {
+            bail!(
+                "Vault AppRole login failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+
+        let token = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        Ok(self.token.get_or_init(|| token))
+    }
+
+    fn mount(&self) -> String {
+        format!("-mount={}", self.config.mount)
+    }
+
+    fn kv_path(path: Option<&str>) -> Result<&str> {
+        path.context("Vault needs a KV v2 path: set [secrets] path or [secrets.paths]")
+    }
+
+    fn read(&self, path: &str) -> Result<BTreeMap<String, String>> {
+        let json = run(
+            &mut self.vault(["kv", "get", "-format=json", &self.mount(), path])?,
+            "Vault CLI",
+        )?;
+        kv_data(&json)
+    }
+}
+
+impl SecretsProvider for Vault {
+    fn name(&self) -> &'static str {
+        "Vault"
+    }
+
+    fn pull(&self, path: Option<&str>) -> Result<String> {
+        Ok(env_lines(&self.read(Self::kv_path(path)?)?))
+    }
+
+    fn list(&self, path: Option<&str>) -> Result<Vec<String>> {
+        if let Some(path) = path {
+            return Ok(self.read(path)?.into_keys().collect());
+        }
+        let mount = format!("{}/", self.config.mount);
+        let json = run(
+            &mut self.vault(["kv", "list", "-format=json", &mount])?,
+            "Vault CLI",
+        )?;
+        serde_json::from_str(&json).context("Failed to parse Vault secrets")
+    }
+
+    fn get(&self, path: Option<&str>, key: &str) -> Result<String> {
+        let field = format!("-field={}", key);
+        let value = run(
+            &mut self.vault(["kv", "get", &self.mount(), &field, Self::kv_path(path)?])?,
+            "Vault CLI",
+        )?;
+        Ok(value.trim_end_matches('\n').to_string())
+    }
+
+    fn push(&self, path: Option<&str>, secrets: &[(String, String)]) -> Result<()> {
+        // patch keeps the secret's other keys (the secret must already exist); the
+        // values go in as JSON on stdin so they stay out of `ps`
+        let data: serde_json::Map<String, Value> = secrets
+            .iter()
+            .map(|(key, value)| (key.clone(), Value::String(value.clone())))
+            .collect();
+        let mount = self.mount();
+        let output = with_stdin(
+            &mut self.vault(["kv", "patch", &mount, Self::kv_path(path)?, "-"])?,
+            Value::Object(data).to_string().as_bytes(),
+        )?;
+        if !output.status.success() {
+            bail!(
+                "Vault CLI failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Run `cmd` with `input` on its stdin, capturing its output
+fn with_stdin(cmd: &mut Command, input: &[u8]) -> Result<Output> {
+    let mut child = cmd
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .logged_spawn()
+        .context("Failed to run Vault CLI")?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(input)?;
+    }
+    Ok(child.wait_with_output()?)
+}
+
+/// The key/value pairs of a `vault kv get -format=json` response
+fn kv_data(json: &str) -> Result<BTreeMap<String, String>> {
+    let response: Value = serde_json::from_str(json).context("Failed to parse Vault response")?;
+    let data = response["data"]["data"]
+        .as_object()
+        .context("Vault response has no data (is the mount KV v2?)")?;
+    Ok(data
+        .iter()
+        .map(|(key, value)| {
+            let value = match value {
+                Value::String(value) => value.clone(),
+                other => other.to_string(),
+            };
+            (key.clone(), value)
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kv_data() {
+        let json = r#"{
+            "request_id": "5c1d",
+            "data": {
+                "data": {"DATABASE_URL": "postgres://localhost/app", "PORT": 8080},
+                "metadata": {"version": 3}
+            }
+        }"#;
+        let data = kv_data(json).unwrap();
+        assert_eq!(data["DATABASE_URL"], "postgres://localhost/app");
+        assert_eq!(data["PORT"], "8080");
+        assert_eq!(data.len(), 2);
+
+        assert!(kv_data(r#"{"data": {"password": "x"}}"#).is_err());
+    }
+}
@@ -3,6 +3,7 @@
 //! Integrates with cargo audit, npm audit, and secret scanning tools
 
 use anyhow::{Context, Result};
+use devkit_core::oplog::LoggedCommand;
 use devkit_core::{AppContext, Extension, MenuItem};
 use std::process::Command;
 
@@ -20,7 +21,7 @@ impl Extension for SecurityExtension {
     fn menu_items(&self, ctx: &AppContext) -> Vec<MenuItem> {
         let mut items = vec![MenuItem {
             label: "🔒 Run security scan".to_string(),
-                group: None,
+            group: None,
             handler: Box::new(|ctx| security_scan(ctx).map_err(Into::into)),
         }];
 
@@ -108,7 +109,7 @@ pub fn cargo_audit(ctx: &AppContext) -> Result<()> {
     let output = Command::new("cargo")
         .arg("audit")
         .current_dir(&ctx.repo)
-        .output()
+        .logged_output()
         .context("Failed to run cargo audit")?;
 
     if !output.status.success() {
@@ -123,7 +124,7 @@ pub fn npm_audit(ctx: &AppContext) -> Result<()> {
     let output = Command::new("npm")
         .arg("audit")
         .current_dir(&ctx.repo)
-        .output()
+        .logged_output()
         .context("Failed to run npm audit")?;
 
     if !output.status.success() {
@@ -140,7 +141,7 @@ pub fn scan_secrets(ctx: &AppContext) -> Result<()> {
         let output = Command::new("gitleaks")
             .args(["detect", "--no-git", "-v"])
             .current_dir(&ctx.repo)
-            .output()
+            .logged_output()
             .context("Failed to run gitleaks")?;
 
         if !output.status.success() {
@@ -177,7 +178,7 @@ pub fn generate_sbom(ctx: &AppContext) -> Result<()> {
 fn cmd_exists(cmd: &str) -> bool {
     Command::new("which")
         .arg(cmd)
-        .output()
+        .logged_output()
         .map(|output| output.status.success())
         .unwrap_or(false)
 }
@@ -0,0 +1,26 @@
+//! `devkit serve` subcommand
+
+use anyhow::Result;
+use clap::{Args, Command, FromArgMatches};
+use devkit_core::AppContext;
+
+use crate::serve;
+
+#[derive(Args)]
+pub struct ServeArgs {
+    /// Port on 127.0.0.1 (0 picks a free one)
+    #[arg(long, default_value_t = 7420)]
+    port: u16,
+}
+
+pub fn command() -> Command {
+    ServeArgs::augment_args(
+        Command::new("serve")
+            .about("Serve a local HTTP/WebSocket API for editor plugins and dashboards"),
+    )
+}
+
+pub fn handle(ctx: &AppContext, matches: &clap::ArgMatches) -> Result<()> {
+    let args = ServeArgs::from_arg_matches(matches)?;
+    serve(ctx, args.port)
+}
@@ -0,0 +1,288 @@
+//! Local HTTP/WebSocket API for devkit
+//!
+//! `devkit serve` listens on 127.0.0.1 so editor plugins and dashboards can drive
+//! devkit:
+//!
+//! - `GET /api/status` - project, git, compose services and the runs started so far
+//! - `GET /api/commands` - `[cmd]` commands with the devkit arguments that run them
+//! - `POST /api/run` - run `devkit <args>` from a `{"args": ["cmd", "build"]}` body,
+//!   streaming newline-delimited JSON events
+//! - `GET /api/ws` - WebSocket; send `{"run": [...]}` and receive the same events
+//!
+//! Every request needs the token written to `.dev/server.json` (with the URL) on start,
+//! as `Authorization: Bearer <token>` or `?token=`, so pages open in a browser can't
+//! run commands.
+
+mod cli;
+mod runs;
+mod status;
+
+use anyhow::{anyhow, Result};
+use devkit_core::{AppContext, Extension, MenuItem};
+use runs::{write_ndjson, Event, Tasks};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::fs;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::thread;
+use tiny_http::{Header, Method, Request, Response, Server, StatusCode};
+use tungstenite::handshake::derive_accept_key;
+use tungstenite::protocol::Role;
+use tungstenite::{Message, WebSocket};
+
+const INFO_FILE: &str = ".dev/server.json";
+
+pub struct ServerExtension;
+
+impl Extension for ServerExtension {
+    fn name(&self) -> &str {
+        "server"
+    }
+
+    fn is_available(&self, _ctx: &AppContext) -> bool {
+        true
+    }
+
+    fn menu_items(&self, _ctx: &AppContext) -> Vec<MenuItem> {
+        Vec::new()
+    }
+
+    fn subcommands(&self) -> Vec<clap::Command> {
+        vec![cli::command()]
+    }
+
+    fn handle_cli(
+        &self,
+        ctx: &AppContext,
+        name: &str,
+        matches: &clap::ArgMatches,
+    ) -> Option<devkit_core::Result<()>> {
+        (name == "serve").then(|| cli::handle(ctx, matches).map_err(Into::into))
+    }
+}
+
+#[derive(Deserialize)]
+struct RunRequest {
+    args: Vec<String>,
+}
+
+/// Serve the API until interrupted
+pub fn serve(ctx: &AppContext, port: u16) -> Result<()> {
+    let server = Server::http(("127.0.0.1", port))
+        .map_err(|e| anyhow!("Failed to listen on 127.0.0.1:{}: {}", port, e))?;
+    let addr = server
+        .server_addr()
+        .to_ip()
+        .ok_or_else(|| anyhow!("Server isn't listening on TCP"))?;
+    let url = format!("http://{}", addr);
+    let token = new_token()?;
+
+    let info = ctx.repo.join(INFO_FILE);
+    if let Some(dir) = info.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let contents = json!({ "url": url, "token": token, "pid": std::process::id() });
+    write_private(&info, &serde_json::to_string_pretty(&contents)?)?;
+
+    ctx.print_success(&format!("✓ Serving the devkit API on {}", url));
+    ctx.print_info(&format!("Token: {} (also in {})", token, INFO_FILE));
+    ctx.print_info("Ctrl+C to stop");
+
+    let tasks = Tasks::default();
+    thread::scope(|scope| {
+        for request in server.incoming_requests() {
+            let (tasks, token) = (&tasks, token.as_str());
+            scope.spawn(move || handle(ctx, tasks, token, request));
+        }
+    });
+    Ok(())
+}
+
+/// 32 random bytes from the OS, hex encoded
+fn new_token() -> Result<String> {
+    let mut bytes = [0u8; 32];
+    getrandom::getrandom(&mut bytes).map_err(|e| anyhow!("Failed to generate a token: {}", e))?;
+    Ok(bytes.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// Write a file only the current user can read, since it holds the token
+fn write_private(path: &Path, contents: &str) -> Result<()> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options.open(path)?;
+    // `mode` only applies when the file is created
+    #[cfg(unix)]
+    file.set_permissions(std::os::unix::fs::PermissionsExt::from_mode(0o600))?;
+    file.write_all(contents.as_bytes())?;
+    Ok(())
+}
+
+fn handle(ctx: &AppContext, tasks: &Tasks, token: &str, mut request: Request) {
+    let url = request.url().to_string();
+    let (path, query) = url.split_once('?').unwrap_or((&url, ""));
+    if !authorized(&request, query, token) {
+        respond_json(request, 401, json!({ "error": "missing or invalid token" }));
+        return;
+    }
+
+    match (request.method(), path) {
+        (Method::Get, "/api/status") => {
+            let mut status = status::status(ctx);
+            status["tasks"] = tasks.to_json();
+            respond_json(request, 200, status);
+        }
+        (Method::Get, "/api/commands") => {
+            respond_json(request, 200, status::commands(&ctx.config));
+        }
+        (Method::Post, "/api/run") => {
+            let mut body = String::new();
+            let parsed = request
+                .as_reader()
+                .read_to_string(&mut body)
+                .map_err(anyhow::Error::from)
+                .and_then(|_| Ok(serde_json::from_str::<RunRequest>(&body)?));
+            match parsed {
+                Ok(run) if !run.args.is_empty() => run_http(ctx, tasks, request, run.args),
+                _ => respond_json(
+                    request,
+                    400,
+                    json!({ "error": "expected {\"args\": [...]} with at least one argument" }),
+                ),
+            }
+        }
+        (Method::Get, "/api/ws") => websocket(ctx, tasks, request),
+        _ => respond_json(request, 404, json!({ "error": "not found" })),
+    }
+}
+
+fn authorized(request: &Request, query: &str, token: &str) -> bool {
+    let bearer = request
+        .headers()
+        .iter()
+        .find(|header| header.field.equiv("Authorization"))
+        .and_then(|header| header.value.as_str().strip_prefix("Bearer "))
+        .is_some_and(|value| tokens_match(value, token));
+    bearer
+        || query
+            .split('&')
+            .filter_map(|pair| pair.strip_prefix("token="))
+            .any(|value| tokens_match(value, token))
+}
+
+/// Compare in constant time so response timing doesn't leak the token
+fn tokens_match(given: &str, token: &str) -> bool {
+    given.len() == token.len()
+        && given
+            .bytes()
+            .zip(token.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+fn respond_json(request: Request, status: u16, body: Value) {
+    let response = Response::from_string(body.to_string())
+        .with_status_code(StatusCode(status))
+        .with_header(header("Content-Type", "application/json"));
+    let _ = request.respond(response);
+}
+
+fn header(name: &str, value: &str) -> Header {
+    Header::from_bytes(name.as_bytes(), value.as_bytes()).expect("valid header")
+}
+
+/// Stream a run's events as the response body. The headers are written by hand
+/// because tiny_http buffers chunked bodies.
+fn run_http(ctx: &AppContext, tasks: &Tasks, request: Request, args: Vec<String>) {
+    let run = match tasks.start(&ctx.repo, args) {
+        Ok(run) => run,
+        Err(e) => return respond_json(request, 500, json!({ "error": format!("{:#}", e) })),
+    };
+    let mut out = request.into_writer();
+    let _ = out
+        .write_all(
+            b"HTTP/1.1 200 OK\r\nContent-Type: application/x-ndjson\r\nTransfer-Encoding: chunked\r\n\r\n",
+        )
+        .and_then(|_| write_ndjson(&mut out, run));
+}
+
+/// Accept a WebSocket and run each `{"run": [...]}` message, replying with its events
+fn websocket(ctx: &AppContext, tasks: &Tasks, request: Request) {
+    let Some(key) = request
+        .headers()
+        .iter()
+        .find(|header| header.field.equiv("Sec-WebSocket-Key"))
+        .map(|header| derive_accept_key(header.value.as_bytes()))
+    else {
+        return respond_json(
+            request,
+            400,
+            json!({ "error": "expected a WebSocket upgrade" }),
+        );
+    };
+
+    let response = Response::empty(StatusCode(101))
+        .with_header(header("Upgrade", "websocket"))
+        .with_header(header("Sec-WebSocket-Accept", &key));
+    let stream = request.upgrade("websocket", response);
+    let mut socket = WebSocket::from_raw_socket(stream, Role::Server, None);
+
+    while let Ok(message) = socket.read() {
+        let text = match message {
+            Message::Text(text) => text,
+            Message::Close(_) => break,
+            _ => continue,
+        };
+        let args = serde_json::from_str::<Value>(&text)
+            .ok()
+            .and_then(|message| serde_json::from_value::<Vec<String>>(message["run"].clone()).ok())
+            .filter(|args| !args.is_empty());
+        let Some(args) = args else {
+            let error = json!({ "type": "error", "message": "expected {\"run\": [...]}" });
+            if !send(&mut socket, &error) {
+                break;
+            }
+            continue;
+        };
+
+        let sent = match tasks.start(&ctx.repo, args) {
+            Ok(mut run) => run.all(|event: Event| send(&mut socket, &event)),
+            Err(e) => send(
+                &mut socket,
+                &json!({ "type": "error", "message": format!("{:#}", e) }),
+            ),
+        };
+        if !sent {
+            break;
+        }
+    }
+}
+
+/// Send a JSON message; false once the client is gone
+fn send<S: Read + Write>(socket: &mut WebSocket<S>, message: &impl serde::Serialize) -> bool {
+    let text = serde_json::to_string(message).unwrap_or_default();
+    socket.send(Message::Text(text)).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_token() {
+        let token = new_token().unwrap();
+        assert_eq!(token.len(), 64);
+        assert!(token.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_ne!(token, new_token().unwrap());
+    }
+
+    #[test]
+    fn test_tokens_match() {
+        assert!(tokens_match("abc123", "abc123"));
+        assert!(!tokens_match("abc124", "abc123"));
+        assert!(!tokens_match("abc12", "abc123"));
+        assert!(!tokens_match("", "abc123"));
+    }
+}
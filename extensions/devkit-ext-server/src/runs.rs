@@ -0,0 +1,181 @@
+//! Commands started through the API
+//!
+//! Each run is `devkit <args>` in a child process with stdout and stderr merged, read
+//! line by line as [`Event`]s. A run whose client goes away is killed.
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use serde_json::Value;
+use std::io::{self, BufRead, BufReader, PipeReader, Write};
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A run as listed in `/api/status`
+#[derive(Debug, Clone, Serialize)]
+pub struct Task {
+    pub id: u64,
+    pub args: Vec<String>,
+    /// Seconds since the epoch
+    pub started: u64,
+    pub running: bool,
+    /// `null` while running, or when the process was killed by a signal
+    pub exit_code: Option<i32>,
+}
+
+/// Streamed to clients, one JSON object per line (or WebSocket message)
+#[derive(Debug, Serialize, PartialEq)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Event {
+    Started { task: u64 },
+    Output { line: String },
+    Exit { task: u64, code: Option<i32> },
+}
+
+/// Runs started since the server came up
+#[derive(Default)]
+pub struct Tasks {
+    tasks: Mutex<Vec<Task>>,
+}
+
+impl Tasks {
+    pub fn to_json(&self) -> Value {
+        serde_json::to_value(&*self.tasks.lock().unwrap()).unwrap_or_default()
+    }
+
+    /// Start `devkit <args>` in the repo
+    pub fn start(&self, repo: &Path, args: Vec<String>) -> Result<Run<'_>> {
+        let devkit = std::env::current_exe().context("Failed to locate the devkit binary")?;
+        let (reader, writer) = io::pipe()?;
+        let child = Command::new(devkit)
+            .args(&args)
+            .current_dir(repo)
+            .stdin(Stdio::null())
+            .stdout(writer.try_clone()?)
+            .stderr(writer)
+            .spawn()
+            .context("Failed to start devkit")?;
+
+        let mut tasks = self.tasks.lock().unwrap();
+        let id = tasks.len() as u64 + 1;
+        tasks.push(Task {
+            id,
+            args,
+            started: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|age| age.as_secs())
+                .unwrap_or(0),
+            running: true,
+            exit_code: None,
+        });
+        Ok(Run {
+            tasks: self,
+            id,
+            child,
+            output: BufReader::new(reader),
+            started: false,
+            done: false,
+        })
+    }
+
+    fn finish(&self, id: u64, code: Option<i32>) {
+        if let Some(task) = self.tasks.lock().unwrap().iter_mut().find(|t| t.id == id) {
+            task.running = false;
+            task.exit_code = code;
+        }
+    }
+}
+
+/// A running command, yielding `Started`, each output line, then `Exit`
+pub struct Run<'a> {
+    tasks: &'a Tasks,
+    id: u64,
+    child: Child,
+    output: BufReader<PipeReader>,
+    started: bool,
+    done: bool,
+}
+
+impl Iterator for Run<'_> {
+    type Item = Event;
+
+    fn next(&mut self) -> Option<Event> {
+        if !self.started {
+            self.started = true;
+            return Some(Event::Started { task: self.id });
+        }
+        if self.done {
+            return None;
+        }
+
+        let mut line = Vec::new();
+        if matches!(self.output.read_until(b'\n', &mut line), Ok(n) if n > 0) {
+            let line = String::from_utf8_lossy(&line);
+            return Some(Event::Output {
+                line: line.trim_end_matches(['\n', '\r']).to_string(),
+            });
+        }
+
+        self.done = true;
+        let code = self.child.wait().ok().and_then(|status| status.code());
+        self.tasks.finish(self.id, code);
+        Some(Event::Exit {
+            task: self.id,
+            code,
+        })
+    }
+}
+
+impl Drop for Run<'_> {
+    fn drop(&mut self) {
+        if !self.done {
+            let _ = self.child.kill();
+            let _ = self.child.wait();
+            self.tasks.finish(self.id, None);
+        }
+    }
+}
+
+/// Write events as newline-delimited JSON in an HTTP/1.1 chunked body, one chunk per
+/// event so clients see output as soon as it's printed
+pub fn write_ndjson(out: &mut dyn Write, events: impl Iterator<Item = Event>) -> io::Result<()> {
+    for event in events {
+        let mut line = serde_json::to_vec(&event)?;
+        line.push(b'\n');
+        write!(out, "{:x}\r\n", line.len())?;
+        out.write_all(&line)?;
+        out.write_all(b"\r\n")?;
+        out.flush()?;
+    }
+    out.write_all(b"0\r\n\r\n")?;
+    out.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_ndjson() {
+        let events = vec![
+            Event::Started { task: 1 },
+            Event::Output {
+                line: "Compiling api".to_string(),
+            },
+            Event::Exit {
+                task: 1,
+                code: Some(0),
+            },
+        ];
+        let mut body = Vec::new();
+        write_ndjson(&mut body, events.into_iter()).unwrap();
+        assert_eq!(
+            String::from_utf8(body).unwrap(),
+            "1c\r\n{\"type\":\"started\",\"task\":1}\n\r\n\
+             29\r\n{\"type\":\"output\",\"line\":\"Compiling api\"}\n\r\n\
+             22\r\n{\"type\":\"exit\",\"task\":1,\"code\":0}\n\r\n\
+             0\r\n\r\n"
+        );
+    }
+}
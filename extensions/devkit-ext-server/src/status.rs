@@ -0,0 +1,65 @@
+//! JSON for `/api/status` and `/api/commands`
+
+use devkit_core::status::{git_status, running_services};
+use devkit_core::validation::compose_services;
+use devkit_core::{AppContext, Config};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+
+/// Project, git and compose service status
+pub fn status(ctx: &AppContext) -> Value {
+    json!({
+        "project": ctx.config.global.project.name,
+        "root": ctx.repo,
+        "git": git_status(&ctx.repo),
+        "services": services(ctx),
+    })
+}
+
+/// Compose services with their host ports and whether they're running (`null` when
+/// docker isn't available)
+fn services(ctx: &AppContext) -> Value {
+    let Some((_, services)) = compose_services(&ctx.repo) else {
+        return json!([]);
+    };
+    let running = running_services(&ctx.repo, &ctx.config.global);
+
+    let services: BTreeMap<String, Vec<u16>> = services.into_iter().collect();
+    services
+        .into_iter()
+        .map(|(name, ports)| {
+            let running = running.as_ref().map(|running| running.contains(&name));
+            json!({ "name": name, "ports": ports, "running": running })
+        })
+        .collect()
+}
+
+/// Every `[cmd]` command with the packages defining it, its variants, and the devkit
+/// arguments that run it across packages
+pub fn commands(config: &Config) -> Value {
+    let mut commands: BTreeMap<&str, (Vec<&str>, Vec<&str>)> = BTreeMap::new();
+    for (pkg_name, package) in &config.packages {
+        for (cmd_name, entry) in &package.cmd {
+            let (packages, variants) = commands.entry(cmd_name.as_str()).or_default();
+            packages.push(pkg_name.as_str());
+            if let devkit_core::config::CmdEntry::Full(cmd) = entry {
+                variants.extend(cmd.variants.keys().map(String::as_str));
+            }
+        }
+    }
+
+    commands
+        .into_iter()
+        .map(|(name, (mut packages, mut variants))| {
+            packages.sort();
+            variants.sort();
+            variants.dedup();
+            json!({
+                "name": name,
+                "packages": packages,
+                "variants": variants,
+                "args": ["cmd", name],
+            })
+        })
+        .collect()
+}
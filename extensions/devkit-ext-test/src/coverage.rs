@@ -0,0 +1,229 @@
+//! Cross-language coverage aggregation
+//!
+//! Runs the coverage tool for each package (cargo-llvm-cov or cargo-tarpaulin for
+//! Rust, vitest or jest for Node), has each write lcov, and merges everything into
+//! `.dev/coverage/lcov.info` with a combined line coverage percentage. `--html`
+//! renders the merged report with genhtml.
+
+use anyhow::{anyhow, Result};
+use console::style;
+use devkit_core::AppContext;
+use devkit_tasks::CmdBuilder;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::lcov::LcovReport;
+use crate::{detect_kind, node_test_runner, select_targets, NodeRunner, ProjectKind};
+
+const COVERAGE_DIR: &str = ".dev/coverage";
+
+/// Options for a coverage run
+#[derive(Debug, Default)]
+pub struct CoverageOptions {
+    /// Only collect coverage for these packages
+    pub packages: Vec<String>,
+    /// Render an HTML report with genhtml
+    pub html: bool,
+}
+
+/// Coverage for one package
+#[derive(Debug)]
+pub struct PackageCoverage {
+    pub package: String,
+    pub report: LcovReport,
+}
+
+/// Merged coverage for all packages
+#[derive(Debug)]
+pub struct CoverageSummary {
+    pub packages: Vec<PackageCoverage>,
+    pub merged: LcovReport,
+    /// Path of the merged lcov file
+    pub lcov_path: PathBuf,
+    /// Path of the HTML index, when rendered
+    pub html_index: Option<PathBuf>,
+}
+
+/// Collect coverage for each package and merge it into one report
+pub fn run_coverage(ctx: &AppContext, opts: &CoverageOptions) -> Result<CoverageSummary> {
+    ctx.print_header("Collecting coverage");
+
+    let coverage_dir = ctx.repo.join(COVERAGE_DIR);
+    let targets = select_targets(ctx, &opts.packages)?;
+
+    let mut packages = Vec::new();
+    let mut failed = Vec::new();
+    for (name, path) in &targets {
+        let out_dir = coverage_dir.join(name.replace('/', "_"));
+        // Stale lcov from a previous run must not be mistaken for fresh results
+        let _ = fs::remove_dir_all(&out_dir);
+        fs::create_dir_all(&out_dir)?;
+
+        match collect_package(ctx, name, path, &out_dir) {
+            Ok(Some(report)) => packages.push(PackageCoverage {
+                package: name.clone(),
+                report,
+            }),
+            Ok(None) => {}
+            Err(e) => {
+                ctx.print_warning(&format!("{}: {:#}", name, e));
+                failed.push(name.clone());
+            }
+        }
+    }
+
+    if packages.is_empty() && failed.is_empty() {
+        return Err(anyhow!(
+            "No coverage collected (expected Rust crates or Node packages using vitest/jest)"
+        ));
+    }
+
+    let mut merged = LcovReport::default();
+    for pkg in &packages {
+        merged.merge(&pkg.report);
+    }
+    let lcov_path = coverage_dir.join("lcov.info");
+    fs::write(&lcov_path, merged.to_lcov())?;
+
+    let html_index = if opts.html {
+        Some(render_html(&lcov_path, &coverage_dir.join("html"))?)
+    } else {
+        None
+    };
+
+    let summary = CoverageSummary {
+        packages,
+        merged,
+        lcov_path,
+        html_index,
+    };
+    print_summary(ctx, &summary);
+
+    if !failed.is_empty() {
+        return Err(anyhow!(
+            "Coverage failed for {} package(s): {}",
+            failed.len(),
+            failed.join(", ")
+        ));
+    }
+    Ok(summary)
+}
+
+/// Run the package's coverage tool, returning its lcov report (None when skipped)
+fn collect_package(
+    ctx: &AppContext,
+    name: &str,
+    path: &Path,
+    out_dir: &Path,
+) -> Result<Option<LcovReport>> {
+    let lcov_file = out_dir.join("lcov.info");
+    let out = out_dir.to_string_lossy().to_string();
+
+    let cmd = match detect_kind(path) {
+        Some(ProjectKind::Rust) => {
+            if devkit_core::cmd_exists("cargo-llvm-cov") {
+                CmdBuilder::new("cargo")
+                    .args(["llvm-cov", "--lcov", "--output-path"])
+                    .arg(lcov_file.to_string_lossy())
+            } else if devkit_core::cmd_exists("cargo-tarpaulin") {
+                CmdBuilder::new("cargo")
+                    .args(["tarpaulin", "--out", "Lcov", "--output-dir"])
+                    .arg(&out)
+            } else {
+                ctx.print_warning(&format!(
+                    "{}: skipped (install cargo-llvm-cov or cargo-tarpaulin)",
+                    name
+                ));
+                return Ok(None);
+            }
+        }
+        Some(ProjectKind::Node) => match node_test_runner(path) {
+            Some(NodeRunner::Vitest) => CmdBuilder::new("npx")
+                .args(["vitest", "run", "--coverage", "--coverage.reporter=lcov"])
+                .arg(format!("--coverage.reportsDirectory={}", out)),
+            Some(NodeRunner::Jest) => CmdBuilder::new("npx")
+                .args(["jest", "--coverage", "--coverageReporters=lcov"])
+                .arg(format!("--coverageDirectory={}", out)),
+            None => {
+                ctx.print_warning(&format!("{}: skipped (no vitest or jest dependency)", name));
+                return Ok(None);
+            }
+        },
+        None => return Ok(None),
+    };
+
+    ctx.print_info(&format!("Coverage: {}", name));
+    let code = cmd.cwd(path).inherit_io().run()?;
+    if code != 0 {
+        return Err(anyhow!("coverage run exited with code {}", code));
+    }
+
+    let contents = fs::read_to_string(&lcov_file)
+        .map_err(|_| anyhow!("no lcov report written to {}", lcov_file.display()))?;
+    Ok(Some(LcovReport::parse(&contents, path, &ctx.repo)))
+}
+
+fn render_html(lcov_path: &Path, html_dir: &Path) -> Result<PathBuf> {
+    if !devkit_core::cmd_exists("genhtml") {
+        return Err(anyhow!(
+            "genhtml not found. Install lcov (brew install lcov / apt install lcov) for HTML reports"
+        ));
+    }
+    let code = CmdBuilder::new("genhtml")
+        .arg(lcov_path.to_string_lossy())
+        .args(["--quiet", "--output-directory"])
+        .arg(html_dir.to_string_lossy())
+        .inherit_io()
+        .run()?;
+    if code != 0 {
+        return Err(anyhow!("genhtml exited with code {}", code));
+    }
+    Ok(html_dir.join("index.html"))
+}
+
+fn print_summary(ctx: &AppContext, summary: &CoverageSummary) {
+    println!();
+    let width = summary
+        .packages
+        .iter()
+        .map(|p| p.package.len())
+        .max()
+        .unwrap_or(0)
+        .max(5);
+
+    for pkg in &summary.packages {
+        let (hit, total) = pkg.report.line_counts();
+        println!(
+            "  {:<width$}  {}  ({}/{} lines)",
+            pkg.package,
+            format_pct(pkg.report.percentage()),
+            hit,
+            total,
+        );
+    }
+    let (hit, total) = summary.merged.line_counts();
+    println!(
+        "  {}  {}  ({}/{} lines)",
+        style(format!("{:<width$}", "total")).bold(),
+        format_pct(summary.merged.percentage()),
+        hit,
+        total,
+    );
+    println!();
+
+    ctx.print_success(&format!("✓ lcov: {}", summary.lcov_path.display()));
+    if let Some(index) = &summary.html_index {
+        ctx.print_success(&format!("✓ HTML: {}", index.display()));
+    }
+}
+
+fn format_pct(pct: f64) -> String {
+    let text = format!("{:>6.2}%", pct);
+    if pct >= 80.0 {
+        style(text).green().to_string()
+    } else if pct >= 50.0 {
+        style(text).yellow().to_string()
+    } else {
+        style(text).red().to_string()
+    }
+}
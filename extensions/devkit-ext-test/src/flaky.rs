@@ -0,0 +1,315 @@
+//! Flaky test detection
+//!
+//! `devkit test flaky --runs N [filter]` repeats the test suite, tracks how often each
+//! test passes, and reports the ones that both passed and failed along with their
+//! failure output. Each session is saved to `.dev/test-flaky/` so pass rates can be
+//! compared over time.
+
+use anyhow::{anyhow, Context, Result};
+use chrono::Local;
+use console::style;
+use devkit_core::AppContext;
+use devkit_tasks::CmdBuilder;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::execute_tests;
+use crate::junit::{parse_test_output, TestStatus};
+
+const HISTORY_DIR: &str = ".dev/test-flaky";
+
+/// Number of failure output lines shown per flaky test
+const FAILURE_PREVIEW_LINES: usize = 12;
+
+/// Options for flaky test detection
+#[derive(Debug)]
+pub struct FlakyOptions {
+    /// How many times to run the suite
+    pub runs: u32,
+    /// Only track tests whose name contains this
+    pub filter: Option<String>,
+    /// Only run `[cmd.test]` for these packages
+    pub packages: Vec<String>,
+}
+
+/// Pass/fail counts for one test across a session
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TestStats {
+    pub passed: u32,
+    pub failed: u32,
+    /// Output from the most recent failure
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_failure: Option<String>,
+}
+
+impl TestStats {
+    /// Fraction of runs that passed (0.0 - 1.0)
+    pub fn pass_rate(&self) -> f64 {
+        match self.passed + self.failed {
+            0 => 1.0,
+            total => self.passed as f64 / total as f64,
+        }
+    }
+
+    /// Both passed and failed at least once
+    pub fn is_flaky(&self) -> bool {
+        self.passed > 0 && self.failed > 0
+    }
+}
+
+/// A recorded flaky detection session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FlakyRun {
+    /// Commit the tests ran against
+    pub sha: Option<String>,
+    /// RFC 3339 timestamp
+    pub date: String,
+    pub runs: u32,
+    pub filter: Option<String>,
+    /// Stats keyed by `<binary or file> › <test name>`
+    pub tests: BTreeMap<String, TestStats>,
+}
+
+impl FlakyRun {
+    pub fn flaky_tests(&self) -> Vec<(&String, &TestStats)> {
+        self.tests.iter().filter(|(_, s)| s.is_flaky()).collect()
+    }
+}
+
+/// Record one run's test output into the session stats
+fn record_output(
+    tests: &mut BTreeMap<String, TestStats>,
+    output: &str,
+    package: &str,
+    filter: Option<&str>,
+) {
+    for case in parse_test_output(output, package) {
+        if filter.is_some_and(|f| !case.name.contains(f)) {
+            continue;
+        }
+        let stats = tests
+            .entry(format!("{} › {}", case.classname, case.name))
+            .or_default();
+        match case.status {
+            TestStatus::Passed => stats.passed += 1,
+            TestStatus::Failed => {
+                stats.failed += 1;
+                if case.output.is_some() {
+                    stats.last_failure = case.output;
+                }
+            }
+            TestStatus::Skipped => {}
+        }
+    }
+}
+
+/// Repeat the test suite and report tests that fail intermittently
+pub fn detect_flaky(ctx: &AppContext, opts: &FlakyOptions) -> Result<FlakyRun> {
+    if opts.runs < 2 {
+        return Err(anyhow!("--runs must be at least 2 to detect flaky tests"));
+    }
+    ctx.print_header(&format!("Running tests {} times", opts.runs));
+
+    let filter = opts.filter.as_deref();
+    let mut tests = BTreeMap::new();
+    for i in 1..=opts.runs {
+        let results = execute_tests(ctx, &opts.packages, true, filter, None)?;
+        let mut run_tests = BTreeMap::new();
+        for result in &results {
+            let output = result.output.as_deref().unwrap_or_default();
+            record_output(&mut run_tests, output, &result.package, filter);
+        }
+
+        let failed = run_tests.values().filter(|s| s.failed > 0).count();
+        println!(
+            "  Run {}/{}: {} passed, {} failed",
+            i,
+            opts.runs,
+            run_tests.len() - failed,
+            failed
+        );
+        for (name, stats) in run_tests {
+            let total: &mut TestStats = tests.entry(name).or_default();
+            total.passed += stats.passed;
+            total.failed += stats.failed;
+            if stats.last_failure.is_some() {
+                total.last_failure = stats.last_failure;
+            }
+        }
+    }
+
+    if tests.is_empty() {
+        return Err(anyhow!(
+            "No tests recognized in output (cargo test, nextest or jest)"
+        ));
+    }
+
+    let run = FlakyRun {
+        sha: head_sha(&ctx.repo),
+        date: Local::now().to_rfc3339(),
+        runs: opts.runs,
+        filter: opts.filter.clone(),
+        tests,
+    };
+    let history = load_flaky_history(&ctx.repo).unwrap_or_default();
+    let path = save_flaky_run(&ctx.repo, &run)?;
+
+    print_report(ctx, &run, &history);
+    ctx.print_info(&format!("Saved to {}", path.display()));
+
+    let flaky = run.flaky_tests().len();
+    if flaky > 0 {
+        return Err(anyhow!("{} flaky test(s) in {} runs", flaky, run.runs));
+    }
+    Ok(run)
+}
+
+fn print_report(ctx: &AppContext, run: &FlakyRun, history: &[FlakyRun]) {
+    println!();
+    let flaky = run.flaky_tests();
+    let always_failing: Vec<&String> = run
+        .tests
+        .iter()
+        .filter(|(_, s)| s.passed == 0 && s.failed > 0)
+        .map(|(name, _)| name)
+        .collect();
+
+    if flaky.is_empty() {
+        ctx.print_success(&format!(
+            "✓ No flaky tests in {} runs ({} tests)",
+            run.runs,
+            run.tests.len()
+        ));
+    }
+
+    for (name, stats) in &flaky {
+        println!(
+            "{} {}  {}/{} passed ({:.0}%){}",
+            style("⚠").yellow(),
+            style(name).bold(),
+            stats.passed,
+            stats.passed + stats.failed,
+            stats.pass_rate() * 100.0,
+            format_trend(name, history)
+        );
+        if let Some(failure) = &stats.last_failure {
+            for line in failure.lines().take(FAILURE_PREVIEW_LINES) {
+                println!("    {}", style(line).dim());
+            }
+        }
+        println!();
+    }
+
+    if !always_failing.is_empty() {
+        ctx.print_warning(&format!(
+            "{} test(s) failed in every run (not flaky, just broken):",
+            always_failing.len()
+        ));
+        for name in always_failing {
+            println!("  {} {}", style("✗").red(), name);
+        }
+    }
+}
+
+/// Pass rates from the last few sessions that ran this test, oldest first
+fn format_trend(name: &str, history: &[FlakyRun]) -> String {
+    let rates: Vec<String> = history
+        .iter()
+        .filter_map(|run| run.tests.get(name))
+        .map(|stats| format!("{:.0}%", stats.pass_rate() * 100.0))
+        .collect();
+    if rates.is_empty() {
+        return String::new();
+    }
+    let recent = &rates[rates.len().saturating_sub(5)..];
+    format!("  history: {}", recent.join(" → "))
+}
+
+fn head_sha(repo: &Path) -> Option<String> {
+    CmdBuilder::new("git")
+        .args(["rev-parse", "HEAD"])
+        .cwd(repo)
+        .capture_stdout()
+        .run_capture()
+        .ok()
+        .map(|out| out.stdout_string().trim().to_string())
+}
+
+fn save_flaky_run(repo: &Path, run: &FlakyRun) -> Result<PathBuf> {
+    let dir = repo.join(HISTORY_DIR);
+    fs::create_dir_all(&dir)?;
+
+    let sha = run.sha.as_deref().unwrap_or("nogit");
+    let path = dir.join(format!(
+        "{}-{}.json",
+        Local::now().format("%Y-%m-%d-%H%M%S"),
+        &sha[..sha.len().min(8)]
+    ));
+    fs::write(&path, serde_json::to_string_pretty(run)?)?;
+    Ok(path)
+}
+
+/// Load previous flaky detection sessions, oldest first
+pub fn load_flaky_history(repo: &Path) -> Result<Vec<FlakyRun>> {
+    let dir = repo.join(HISTORY_DIR);
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut paths: Vec<PathBuf> = fs::read_dir(&dir)?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    // File names start with the session date, so name order is chronological
+    paths.sort();
+
+    paths
+        .iter()
+        .map(|path| {
+            let contents = fs::read_to_string(path)?;
+            serde_json::from_str(&contents)
+                .with_context(|| format!("Invalid flaky test results: {}", path.display()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_output_tracks_pass_rates() {
+        let mut tests = BTreeMap::new();
+        let pass = "test tests::a ... ok\ntest tests::b ... ok\n";
+        let fail = "test tests::a ... ok\ntest tests::b ... FAILED\n\n---- tests::b stdout ----\nboom\n\nfailures:\n";
+        record_output(&mut tests, pass, "core", None);
+        record_output(&mut tests, fail, "core", None);
+        record_output(&mut tests, pass, "core", None);
+
+        let a = &tests["core › tests::a"];
+        let b = &tests["core › tests::b"];
+        assert!(!a.is_flaky());
+        assert!(b.is_flaky());
+        assert_eq!((b.passed, b.failed), (2, 1));
+        assert_eq!(b.last_failure.as_deref(), Some("boom"));
+        assert!((b.pass_rate() - 2.0 / 3.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_record_output_applies_filter() {
+        let mut tests = BTreeMap::new();
+        record_output(
+            &mut tests,
+            "test db::connects ... ok\ntest api::serves ... ok\n",
+            "core",
+            Some("db::"),
+        );
+        assert_eq!(
+            tests.keys().collect::<Vec<_>>(),
+            vec!["core › db::connects"]
+        );
+    }
+}
@@ -0,0 +1,571 @@
+//! JUnit XML reports from test runner output
+//!
+//! Parses the human-readable output of cargo test (libtest), cargo-nextest and jest,
+//! plus libtest JSON events (nextest `--message-format libtest-json`), into test cases and writes one JUnit XML file per package to `.dev/test-reports/`.
+//! Jest only lists individual tests with `--verbose` (or a single test file); otherwise
+//! each test file is reported as one test case.
+
+use anyhow::Result;
+use devkit_core::AppContext;
+use devkit_tasks::CmdResult;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+const REPORT_DIR: &str = ".dev/test-reports";
+
+/// Test report format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    Junit,
+}
+
+impl FromStr for ReportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "junit" => Ok(ReportFormat::Junit),
+            other => Err(format!(
+                "Unknown report format '{}' (expected junit)",
+                other
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TestStatus {
+    Passed,
+    Failed,
+    Skipped,
+}
+
+/// A single test parsed from runner output
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestCase {
+    /// Test binary (cargo) or test file (jest)
+    pub classname: String,
+    pub name: String,
+    pub status: TestStatus,
+    pub time_secs: Option<f64>,
+    /// Captured failure output
+    pub output: Option<String>,
+}
+
+/// Parse cargo test, nextest and jest output into test cases
+///
+/// `default_class` is used for tests that can't be attributed to a binary or file.
+pub fn parse_test_output(output: &str, default_class: &str) -> Vec<TestCase> {
+    let mut cases: Vec<TestCase> = Vec::new();
+    let mut class = default_class.to_string();
+    // Jest suites in order, with whether they passed and listed any tests
+    let mut suites: Vec<(String, bool)> = Vec::new();
+
+    for line in output.lines() {
+        let trimmed = line.trim();
+
+        if let Some(binary) = libtest_binary(trimmed) {
+            class = binary;
+        } else if let Some(case) = parse_libtest_line(trimmed, &class) {
+            cases.push(case);
+        } else if let Some(case) = parse_libtest_json(trimmed, &class) {
+            cases.retain(|c| c.classname != case.classname || c.name != case.name);
+            cases.push(case);
+        } else if let Some(case) = parse_nextest_line(trimmed) {
+            // Retried tests report every attempt; the last one wins
+            cases.retain(|c| c.classname != case.classname || c.name != case.name);
+            cases.push(case);
+        } else if let Some((suite, passed)) = parse_jest_suite(trimmed) {
+            class = suite.clone();
+            suites.push((suite, passed));
+        } else if let Some(case) = parse_jest_test(trimmed, &class) {
+            cases.push(case);
+        }
+    }
+
+    for (suite, passed) in suites {
+        if !cases.iter().any(|c| c.classname == suite) {
+            cases.push(TestCase {
+                name: suite.clone(),
+                classname: suite,
+                status: if passed {
+                    TestStatus::Passed
+                } else {
+                    TestStatus::Failed
+                },
+                time_secs: None,
+                output: None,
+            });
+        }
+    }
+
+    let blocks = failure_blocks(output);
+    for case in cases
+        .iter_mut()
+        .filter(|c| c.status == TestStatus::Failed && c.output.is_none())
+    {
+        case.output = blocks
+            .get(&case.name)
+            .or_else(|| blocks.get(&format!("{} {}", case.classname, case.name)))
+            .cloned();
+    }
+
+    cases
+}
+
+/// `Running unittests src/lib.rs (target/debug/deps/foo-1a2b3c)` -> `foo`
+fn libtest_binary(line: &str) -> Option<String> {
+    if let Some(name) = line.strip_prefix("Doc-tests ") {
+        return Some(format!("{} (doc)", name.trim()));
+    }
+    let rest = line.strip_prefix("Running ")?;
+    let path = rest
+        .rsplit_once('(')
+        .map(|(_, p)| p.trim_end_matches(')'))
+        .unwrap_or(rest);
+    let file = path.rsplit(['/', '\\']).next()?;
+    let file = file.trim_end_matches(".exe");
+    let name = match file.rsplit_once('-') {
+        Some((name, hash)) if hash.chars().all(|c| c.is_ascii_hexdigit()) => name,
+        _ => file,
+    };
+    Some(name.to_string())
+}
+
+/// `test tests::adds ... ok`
+fn parse_libtest_line(line: &str, class: &str) -> Option<TestCase> {
+    let (name, result) = line.strip_prefix("test ")?.rsplit_once(" ... ")?;
+    let status = match result.trim() {
+        "ok" => TestStatus::Passed,
+        "FAILED" => TestStatus::Failed,
+        r if r.starts_with("ignored") => TestStatus::Skipped,
+        _ => return None,
+    };
+    Some(TestCase {
+        classname: class.to_string(),
+        name: name.trim().to_string(),
+        status,
+        time_secs: None,
+        output: None,
+    })
+}
+
+/// libtest JSON test events, as emitted by nextest's `--message-format libtest-json`
+///
+/// nextest names tests `<binary id>$<test name>`.
+fn parse_libtest_json(line: &str, class: &str) -> Option<TestCase> {
+    if !line.starts_with('{') {
+        return None;
+    }
+    let event: serde_json::Value = serde_json::from_str(line).ok()?;
+    if event["type"] != "test" {
+        return None;
+    }
+    let status = match event["event"].as_str()? {
+        "ok" => TestStatus::Passed,
+        "failed" | "timeout" => TestStatus::Failed,
+        "ignored" => TestStatus::Skipped,
+        _ => return None,
+    };
+    let full_name = event["name"].as_str()?;
+    let (classname, name) = full_name.split_once('$').unwrap_or((class, full_name));
+    Some(TestCase {
+        classname: classname.to_string(),
+        name: name.to_string(),
+        status,
+        time_secs: event["exec_time"].as_f64(),
+        output: event["stdout"]
+            .as_str()
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty()),
+    })
+}
+
+/// `PASS [   0.004s] (1/3) my-crate tests::adds` (also `TRY 2 FAIL [...] ...`)
+fn parse_nextest_line(line: &str) -> Option<TestCase> {
+    let mut line = line;
+    if let Some(rest) = line.strip_prefix("TRY ") {
+        line = rest.split_once(' ')?.1.trim_start();
+    }
+    let (status_word, rest) = line.split_once(' ')?;
+    let status = match status_word {
+        "PASS" => TestStatus::Passed,
+        "SKIP" => TestStatus::Skipped,
+        "FAIL" | "TIMEOUT" | "ABORT" | "SIGSEGV" | "SIGABRT" | "SIGKILL" => TestStatus::Failed,
+        _ => return None,
+    };
+
+    let rest = rest.trim_start().strip_prefix('[')?;
+    let (time, rest) = rest.split_once(']')?;
+    let time_secs = time.trim().trim_end_matches('s').parse().ok();
+
+    let mut rest = rest.trim_start();
+    if rest.starts_with('(') {
+        rest = rest.split_once(')')?.1.trim_start();
+    }
+    let (binary, name) = rest.split_once(' ')?;
+    Some(TestCase {
+        classname: binary.to_string(),
+        name: name.trim().to_string(),
+        status,
+        time_secs,
+        output: None,
+    })
+}
+
+/// `PASS src/math.test.ts` / `FAIL src/math.test.ts (5.2 s)`
+fn parse_jest_suite(line: &str) -> Option<(String, bool)> {
+    let (passed, rest) = if let Some(rest) = line.strip_prefix("PASS ") {
+        (true, rest)
+    } else if let Some(rest) = line.strip_prefix("FAIL ") {
+        (false, rest)
+    } else {
+        return None;
+    };
+    let path = rest.split_whitespace().next()?;
+    Some((path.to_string(), passed))
+}
+
+/// `✓ adds numbers (3 ms)` / `✕ subtracts (5 ms)` / `○ skipped todo`
+fn parse_jest_test(line: &str, class: &str) -> Option<TestCase> {
+    let mut chars = line.chars();
+    let status = match chars.next()? {
+        '✓' | '√' => TestStatus::Passed,
+        '✕' | '×' => TestStatus::Failed,
+        '○' => TestStatus::Skipped,
+        _ => return None,
+    };
+    let rest = chars.as_str().trim();
+    let rest = rest.strip_prefix("skipped ").unwrap_or(rest);
+
+    let (name, time_secs) = match rest.rsplit_once(" (") {
+        Some((name, time)) if time.ends_with(" ms)") => (
+            name,
+            time.trim_end_matches(" ms)")
+                .parse::<f64>()
+                .ok()
+                .map(|ms| ms / 1000.0),
+        ),
+        _ => (rest, None),
+    };
+    if name.is_empty() {
+        return None;
+    }
+    Some(TestCase {
+        classname: class.to_string(),
+        name: name.to_string(),
+        status,
+        time_secs,
+        output: None,
+    })
+}
+
+/// Failure output blocks keyed by test name
+///
+/// libtest: `---- name stdout ----`, nextest: `--- STDOUT: binary name ---`,
+/// jest: `● Suite › name`.
+fn failure_blocks(output: &str) -> HashMap<String, String> {
+    let mut blocks: HashMap<String, String> = HashMap::new();
+    let mut current: Option<String> = None;
+
+    for line in output.lines() {
+        let trimmed = line.trim();
+
+        let header = if let Some(rest) = trimmed.strip_prefix("---- ") {
+            rest.strip_suffix(" ----")
+                .and_then(|r| r.rsplit_once(' '))
+                .map(|(name, _)| name.to_string())
+        } else if let Some(rest) = trimmed.strip_prefix("--- ") {
+            rest.strip_suffix(" ---")
+                .and_then(|r| r.split_once(':'))
+                .map(|(_, name)| name.trim().to_string())
+        } else {
+            trimmed
+                .strip_prefix("● ")
+                .map(|rest| rest.rsplit(" › ").next().unwrap_or(rest).trim().to_string())
+        };
+
+        if let Some(name) = header {
+            current = Some(name);
+            continue;
+        }
+
+        let block_ends = trimmed == "failures:"
+            || (!trimmed.is_empty() && trimmed.chars().all(|c| c == '-'))
+            || trimmed.starts_with("test result:")
+            || trimmed.starts_with("Test Suites:")
+            || parse_jest_suite(trimmed).is_some()
+            || parse_nextest_line(trimmed).is_some();
+        if block_ends {
+            current = None;
+            continue;
+        }
+
+        if let Some(name) = &current {
+            let block = blocks.entry(name.clone()).or_default();
+            block.push_str(line);
+            block.push('\n');
+        }
+    }
+
+    for block in blocks.values_mut() {
+        *block = block.trim().to_string();
+    }
+    blocks
+}
+
+/// Render test cases as a JUnit XML document with one testsuite per class
+pub fn to_junit_xml(package: &str, cases: &[TestCase]) -> String {
+    let count = |status| cases.iter().filter(|c| c.status == status).count();
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = writeln!(
+        xml,
+        "<testsuites name=\"{}\" tests=\"{}\" failures=\"{}\" skipped=\"{}\">",
+        escape_xml(package),
+        cases.len(),
+        count(TestStatus::Failed),
+        count(TestStatus::Skipped)
+    );
+
+    let mut classes: Vec<&str> = Vec::new();
+    for case in cases {
+        if !classes.contains(&case.classname.as_str()) {
+            classes.push(&case.classname);
+        }
+    }
+
+    for class in classes {
+        let suite: Vec<&TestCase> = cases.iter().filter(|c| c.classname == class).collect();
+        let failures = suite
+            .iter()
+            .filter(|c| c.status == TestStatus::Failed)
+            .count();
+        let skipped = suite
+            .iter()
+            .filter(|c| c.status == TestStatus::Skipped)
+            .count();
+        let time = suite
+            .iter()
+            .filter_map(|c| c.time_secs)
+            .fold(0.0, |total, t| total + t);
+        let _ = writeln!(
+            xml,
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" skipped=\"{}\" time=\"{:.3}\">",
+            escape_xml(class),
+            suite.len(),
+            failures,
+            skipped,
+            time
+        );
+
+        for case in suite {
+            let _ = write!(
+                xml,
+                "    <testcase classname=\"{}\" name=\"{}\" time=\"{:.3}\"",
+                escape_xml(&case.classname),
+                escape_xml(&case.name),
+                case.time_secs.unwrap_or_default()
+            );
+            match case.status {
+                TestStatus::Passed => xml.push_str("/>\n"),
+                TestStatus::Skipped => xml.push_str(">\n      <skipped/>\n    </testcase>\n"),
+                TestStatus::Failed => {
+                    let _ = writeln!(
+                        xml,
+                        ">\n      <failure message=\"failed\">{}</failure>\n    </testcase>",
+                        escape_xml(case.output.as_deref().unwrap_or_default())
+                    );
+                }
+            }
+        }
+        xml.push_str("  </testsuite>\n");
+    }
+
+    xml.push_str("</testsuites>\n");
+    xml
+}
+
+fn escape_xml(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            // Control characters (e.g. ANSI escapes) aren't allowed in XML 1.0
+            '\n' | '\r' | '\t' => out.push(c),
+            c if c.is_control() => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Write a JUnit XML report for each package's captured test output
+pub fn write_junit_reports(ctx: &AppContext, results: &[CmdResult]) -> Result<Vec<PathBuf>> {
+    let dir = ctx.repo.join(REPORT_DIR);
+    fs::create_dir_all(&dir)?;
+
+    let mut paths = Vec::new();
+    for result in results {
+        let cases = parse_test_output(
+            result.output.as_deref().unwrap_or_default(),
+            &result.package,
+        );
+        if cases.is_empty() {
+            ctx.print_warning(&format!(
+                "{}: no tests recognized in output (cargo test, nextest or jest)",
+                result.package
+            ));
+        }
+        let path = dir.join(format!("{}.xml", result.package.replace('/', "_")));
+        fs::write(&path, to_junit_xml(&result.package, &cases))?;
+        paths.push(path);
+    }
+
+    ctx.print_success(&format!(
+        "✓ Wrote {} JUnit report(s) to {}",
+        paths.len(),
+        dir.display()
+    ));
+    Ok(paths)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cargo_test() {
+        let output = "\
+     Running unittests src/lib.rs (target/debug/deps/mylib-0123abcd)
+
+running 3 tests
+test tests::adds ... ok
+test tests::slow ... ignored
+test tests::breaks ... FAILED
+
+failures:
+
+---- tests::breaks stdout ----
+thread 'tests::breaks' panicked at src/lib.rs:10:5:
+assertion failed
+
+failures:
+    tests::breaks
+
+test result: FAILED. 1 passed; 1 failed; 1 ignored
+";
+        let cases = parse_test_output(output, "pkg");
+        assert_eq!(cases.len(), 3);
+        assert!(cases.iter().all(|c| c.classname == "mylib"));
+        assert_eq!(cases[1].status, TestStatus::Skipped);
+        assert_eq!(cases[2].status, TestStatus::Failed);
+        assert_eq!(
+            cases[2].output.as_deref(),
+            Some("thread 'tests::breaks' panicked at src/lib.rs:10:5:\nassertion failed")
+        );
+    }
+
+    #[test]
+    fn test_parse_nextest() {
+        let output = "\
+    Starting 3 tests across 1 binary
+        PASS [   0.004s] (1/3) mylib tests::adds
+   TRY 1 FAIL [   0.010s] (2/3) mylib tests::flaky
+   TRY 2 PASS [   0.009s] (2/3) mylib tests::flaky
+        FAIL [   0.020s] (3/3) mylib tests::breaks
+--- STDOUT:              mylib tests::breaks ---
+boom
+------------
+     Summary [   0.030s] 3 tests run: 2 passed, 1 failed
+";
+        let cases = parse_test_output(output, "pkg");
+        assert_eq!(cases.len(), 3);
+        let flaky = cases.iter().find(|c| c.name == "tests::flaky").unwrap();
+        assert_eq!(flaky.status, TestStatus::Passed);
+        let breaks = cases.iter().find(|c| c.name == "tests::breaks").unwrap();
+        assert_eq!(breaks.time_secs, Some(0.02));
+        assert_eq!(breaks.output.as_deref(), Some("boom"));
+    }
+
+    #[test]
+    fn test_parse_libtest_json() {
+        let output = r#"{"type":"suite","event":"started","test_count":2}
+{"type":"test","event":"started","name":"mylib$tests::adds"}
+{"type":"test","event":"ok","name":"mylib$tests::adds","exec_time":0.004}
+{"type":"test","event":"failed","name":"mylib::it$breaks","exec_time":0.02,"stdout":"boom\n"}
+{"type":"suite","event":"failed","passed":1,"failed":1}
+"#;
+        let cases = parse_test_output(output, "pkg");
+        assert_eq!(cases.len(), 2);
+        assert_eq!(cases[0].classname, "mylib");
+        assert_eq!(cases[0].time_secs, Some(0.004));
+        assert_eq!(cases[1].classname, "mylib::it");
+        assert_eq!(cases[1].status, TestStatus::Failed);
+        assert_eq!(cases[1].output.as_deref(), Some("boom"));
+    }
+
+    #[test]
+    fn test_parse_jest() {
+        let output = "\
+PASS src/math.test.ts
+  math
+    ✓ adds numbers (3 ms)
+    ○ skipped todo
+FAIL src/str.test.ts
+  ✕ trims (5 ms)
+
+  ● trims
+
+    expect(received).toBe(expected)
+
+PASS src/other.test.ts
+Test Suites: 1 failed, 2 passed, 3 total
+";
+        let cases = parse_test_output(output, "web");
+        assert_eq!(cases.len(), 4);
+        assert_eq!(cases[0].classname, "src/math.test.ts");
+        assert_eq!(cases[0].time_secs, Some(0.003));
+        assert_eq!(cases[1].name, "todo");
+        assert_eq!(cases[2].status, TestStatus::Failed);
+        assert_eq!(
+            cases[2].output.as_deref(),
+            Some("expect(received).toBe(expected)")
+        );
+        // Suites without listed tests become a single case
+        assert_eq!(cases[3].name, "src/other.test.ts");
+    }
+
+    #[test]
+    fn test_to_junit_xml() {
+        let cases = vec![
+            TestCase {
+                classname: "mylib".to_string(),
+                name: "adds".to_string(),
+                status: TestStatus::Passed,
+                time_secs: Some(0.5),
+                output: None,
+            },
+            TestCase {
+                classname: "mylib".to_string(),
+                name: "breaks".to_string(),
+                status: TestStatus::Failed,
+                time_secs: None,
+                output: Some("left < right & \"x\"".to_string()),
+            },
+        ];
+        let xml = to_junit_xml("core", &cases);
+        assert!(xml.contains("<testsuites name=\"core\" tests=\"2\" failures=\"1\" skipped=\"0\">"));
+        assert!(xml.contains(
+            "<testsuite name=\"mylib\" tests=\"2\" failures=\"1\" skipped=\"0\" time=\"0.500\">"
+        ));
+        assert!(xml
+            .contains("<failure message=\"failed\">left &lt; right &amp; &quot;x&quot;</failure>"));
+    }
+}
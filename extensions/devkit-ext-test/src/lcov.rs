@@ -0,0 +1,153 @@
+//! Minimal lcov parsing and merging
+//!
+//! Only line coverage (`DA`) is tracked; that's what the combined percentage is based on.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::Path;
+
+/// Line hit counts per source file
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct LcovReport {
+    /// Source file -> (line number -> hit count)
+    pub files: BTreeMap<String, BTreeMap<u32, u64>>,
+}
+
+impl LcovReport {
+    /// Parse lcov data, resolving relative `SF:` paths against `base` and making
+    /// paths under `repo` relative to it
+    pub fn parse(contents: &str, base: &Path, repo: &Path) -> Self {
+        let mut report = LcovReport::default();
+        let mut current: Option<String> = None;
+
+        for line in contents.lines() {
+            let line = line.trim();
+            if let Some(path) = line.strip_prefix("SF:") {
+                current = Some(normalize_path(path, base, repo));
+            } else if let Some(data) = line.strip_prefix("DA:") {
+                let (Some(file), Some((line_no, hits))) = (&current, parse_da(data)) else {
+                    continue;
+                };
+                *report
+                    .files
+                    .entry(file.clone())
+                    .or_default()
+                    .entry(line_no)
+                    .or_default() += hits;
+            } else if line == "end_of_record" {
+                current = None;
+            }
+        }
+
+        report
+    }
+
+    /// Merge another report into this one, summing hits for lines seen in both
+    pub fn merge(&mut self, other: &LcovReport) {
+        for (file, lines) in &other.files {
+            let entry = self.files.entry(file.clone()).or_default();
+            for (line, hits) in lines {
+                *entry.entry(*line).or_default() += hits;
+            }
+        }
+    }
+
+    /// (covered lines, total lines)
+    pub fn line_counts(&self) -> (usize, usize) {
+        self.files.values().fold((0, 0), |(hit, total), lines| {
+            (
+                hit + lines.values().filter(|h| **h > 0).count(),
+                total + lines.len(),
+            )
+        })
+    }
+
+    /// Line coverage percentage (100% when there are no instrumented lines)
+    pub fn percentage(&self) -> f64 {
+        match self.line_counts() {
+            (_, 0) => 100.0,
+            (hit, total) => hit as f64 / total as f64 * 100.0,
+        }
+    }
+
+    /// Render as lcov tracefile data
+    pub fn to_lcov(&self) -> String {
+        let mut out = String::new();
+        for (file, lines) in &self.files {
+            let _ = writeln!(out, "SF:{}", file);
+            for (line, hits) in lines {
+                let _ = writeln!(out, "DA:{},{}", line, hits);
+            }
+            let _ = writeln!(out, "LF:{}", lines.len());
+            let _ = writeln!(out, "LH:{}", lines.values().filter(|h| **h > 0).count());
+            out.push_str("end_of_record\n");
+        }
+        out
+    }
+}
+
+fn parse_da(data: &str) -> Option<(u32, u64)> {
+    let mut parts = data.split(',');
+    let line = parts.next()?.parse().ok()?;
+    // Some tools emit fractional or huge counts; only "hit or not" matters downstream
+    let hits = parts.next()?.parse::<f64>().ok()? as u64;
+    Some((line, hits))
+}
+
+fn normalize_path(path: &str, base: &Path, repo: &Path) -> String {
+    let path = Path::new(path);
+    let absolute = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        base.join(path)
+    };
+    absolute
+        .strip_prefix(repo)
+        .unwrap_or(&absolute)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_merge() {
+        let repo = Path::new("/repo");
+        let rust = LcovReport::parse(
+            "SF:/repo/crates/a/src/lib.rs\nDA:1,3\nDA:2,0\nLF:2\nLH:1\nend_of_record\n",
+            repo,
+            repo,
+        );
+        let node = LcovReport::parse(
+            "TN:\nSF:src/index.ts\nDA:1,1\nDA:2,1\nend_of_record\n",
+            &repo.join("packages/web"),
+            repo,
+        );
+        assert_eq!(rust.line_counts(), (1, 2));
+
+        let mut merged = LcovReport::default();
+        merged.merge(&rust);
+        merged.merge(&node);
+        assert_eq!(
+            merged.files.keys().collect::<Vec<_>>(),
+            vec!["crates/a/src/lib.rs", "packages/web/src/index.ts"]
+        );
+        assert_eq!(merged.line_counts(), (3, 4));
+        assert_eq!(merged.percentage(), 75.0);
+    }
+
+    #[test]
+    fn test_merge_sums_hits_for_same_file() {
+        let repo = Path::new("/repo");
+        let mut a = LcovReport::parse("SF:src/x.rs\nDA:1,0\nDA:2,1\nend_of_record\n", repo, repo);
+        let b = LcovReport::parse("SF:src/x.rs\nDA:1,2\nend_of_record\n", repo, repo);
+        a.merge(&b);
+        assert_eq!(a.line_counts(), (2, 2));
+        assert_eq!(
+            a.to_lcov(),
+            "SF:src/x.rs\nDA:1,2\nDA:2,1\nLF:2\nLH:2\nend_of_record\n"
+        );
+    }
+}
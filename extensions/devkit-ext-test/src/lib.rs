@@ -0,0 +1,364 @@
+//! Test orchestration extension for devkit
+//!
+//! `devkit test` runs each package's `[cmd.test]` from dev.toml, falling back to
+//! `cargo test` / `npm test` at the repository root when no package defines one.
+//! Rust tests prefer cargo-nextest when available (see [`nextest`]).
+//!
+//! `devkit test coverage` collects coverage across Rust and Node packages and merges
+//! it into a single lcov report (see [`coverage`]).
+//!
+//! `devkit test --report junit` captures test output and writes JUnit XML per package
+//! to `.dev/test-reports/` for CI systems and test dashboards.
+//!
+//! `devkit test flaky --runs N` repeats the suite and reports tests that fail
+//! intermittently (see [`flaky`]).
+//!
+//! `devkit test --shard i/n` runs one deterministic slice of the suite so CI can
+//! spread it across machines (see [`shard`]).
+//!
+//! `devkit test --affected` only tests packages changed since the PR base and the
+//! packages that depend on them.
+
+mod coverage;
+mod flaky;
+mod junit;
+mod lcov;
+mod nextest;
+mod shard;
+
+pub use coverage::{run_coverage, CoverageOptions, CoverageSummary, PackageCoverage};
+pub use flaky::{detect_flaky, load_flaky_history, FlakyOptions, FlakyRun, TestStats};
+pub use junit::{
+    parse_test_output, to_junit_xml, write_junit_reports, ReportFormat, TestCase, TestStatus,
+};
+pub use lcov::LcovReport;
+pub use shard::Shard;
+
+use anyhow::{anyhow, Result};
+use devkit_core::hooks::run_hooks;
+use devkit_core::oplog::LoggedCommand;
+use devkit_core::{AppContext, Extension, MenuItem};
+use devkit_tasks::{print_results, run_cmd, select_affected, CmdBuilder, CmdOptions, CmdResult};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+pub struct TestExtension;
+
+/// Options for running tests
+#[derive(Debug, Default)]
+pub struct TestOptions {
+    /// Only run `[cmd.test]` for these packages
+    pub packages: Vec<String>,
+    /// Capture output and write a test report in this format
+    pub report: Option<ReportFormat>,
+    /// Only run this shard of the suite
+    pub shard: Option<Shard>,
+    /// Only test packages changed since `base`, and their dependents
+    pub affected: bool,
+    /// Git ref for `affected` (defaults to `[git] default_pr_base`)
+    pub base: Option<String>,
+}
+
+impl Extension for TestExtension {
+    fn name(&self) -> &str {
+        "test"
+    }
+
+    fn is_available(&self, ctx: &AppContext) -> bool {
+        !ctx.config.packages_with_cmd("test").is_empty() || detect_kind(&ctx.repo).is_some()
+    }
+
+    fn unavailable_reason(&self, _ctx: &AppContext) -> Option<String> {
+        Some("no tests found ([cmd.test], Cargo.toml or package.json)".to_string())
+    }
+
+    fn menu_items(&self, _ctx: &AppContext) -> Vec<MenuItem> {
+        vec![
+            MenuItem {
+                label: "🧪 Run tests".to_string(),
+                group: Some("🧪 Testing".to_string()),
+                handler: Box::new(|ctx| {
+                    run_tests(ctx, &TestOptions::default()).map_err(Into::into)
+                }),
+            },
+            MenuItem {
+                label: "📊 Coverage report".to_string(),
+                group: Some("🧪 Testing".to_string()),
+                handler: Box::new(|ctx| {
+                    run_coverage(ctx, &CoverageOptions::default())
+                        .map(|_| ())
+                        .map_err(Into::into)
+                }),
+            },
+        ]
+    }
+}
+
+/// Kind of project found in a directory
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProjectKind {
+    Rust,
+    Node,
+}
+
+/// Node test runner a package depends on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeRunner {
+    Vitest,
+    Jest,
+}
+
+/// Detect whether a directory holds a Rust crate or a Node package
+pub fn detect_kind(dir: &Path) -> Option<ProjectKind> {
+    if dir.join("Cargo.toml").exists() {
+        Some(ProjectKind::Rust)
+    } else if dir.join("package.json").exists() {
+        Some(ProjectKind::Node)
+    } else {
+        None
+    }
+}
+
+fn read_package_json(dir: &Path) -> Option<serde_json::Value> {
+    fs::read_to_string(dir.join("package.json"))
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+}
+
+/// Detect vitest or jest from a package's dependencies
+pub fn node_test_runner(dir: &Path) -> Option<NodeRunner> {
+    let pkg = read_package_json(dir)?;
+    let depends_on = |name: &str| {
+        ["dependencies", "devDependencies"]
+            .iter()
+            .any(|section| pkg[section].get(name).is_some())
+    };
+    if depends_on("vitest") {
+        Some(NodeRunner::Vitest)
+    } else if depends_on("jest") {
+        Some(NodeRunner::Jest)
+    } else {
+        None
+    }
+}
+
+/// Name used for the repository root when no packages are configured
+pub(crate) fn root_package_name(ctx: &AppContext) -> String {
+    ctx.repo
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "root".to_string())
+}
+
+/// Packages to operate on as (name, directory)
+///
+/// Without configured packages the repository root is treated as a single package.
+pub(crate) fn select_targets(ctx: &AppContext, only: &[String]) -> Result<Vec<(String, PathBuf)>> {
+    if ctx.config.packages.is_empty() {
+        if !only.is_empty() {
+            return Err(anyhow!(
+                "No packages configured, so --package doesn't apply"
+            ));
+        }
+        return Ok(vec![(root_package_name(ctx), ctx.repo.clone())]);
+    }
+
+    for name in only {
+        if ctx.config.get_package(name).is_none() {
+            return Err(anyhow!("Package '{}' not found", name));
+        }
+    }
+
+    let mut targets: Vec<(String, PathBuf)> = ctx
+        .config
+        .packages
+        .iter()
+        .filter(|(name, _)| only.is_empty() || only.contains(name))
+        .map(|(name, pkg)| (name.clone(), pkg.path.clone()))
+        .collect();
+    targets.sort();
+    Ok(targets)
+}
+
+/// Run tests for every package (or the repository root)
+pub fn run_tests(ctx: &AppContext, opts: &TestOptions) -> Result<()> {
+    let packages = if opts.affected {
+        let mut affected = select_affected(ctx, opts.base.as_deref(), &opts.packages)?;
+        if !ctx.config.packages_with_cmd("test").is_empty() {
+            affected.retain(|name| ctx.config.get_cmd(name, "test").is_some());
+        }
+        if affected.is_empty() {
+            ctx.print_success("✓ No packages affected");
+            return Ok(());
+        }
+        affected
+    } else {
+        opts.packages.clone()
+    };
+
+    run_hooks(ctx, "pre_test", &[])?;
+
+    let capture = opts.report.is_some();
+    let results = execute_tests(ctx, &packages, capture, None, opts.shard)?;
+
+    if capture {
+        for result in &results {
+            print!("{}", result.output.as_deref().unwrap_or_default());
+        }
+    }
+    if let Some(ReportFormat::Junit) = opts.report {
+        write_junit_reports(ctx, &results)?;
+    }
+
+    print_results(ctx, &results);
+    if results.iter().any(|r| !r.success) {
+        return Err(anyhow!("Tests failed"));
+    }
+    run_hooks(ctx, "post_test", &[])?;
+    Ok(())
+}
+
+/// Run each package's `[cmd.test]`, or the root test command when none define one
+///
+/// `filter` only narrows the root command (package commands are run as configured).
+/// With a `shard`, packages are split between shards when they define `[cmd.test]`;
+/// otherwise the root suite's test binaries or files are.
+pub(crate) fn execute_tests(
+    ctx: &AppContext,
+    packages: &[String],
+    capture: bool,
+    filter: Option<&str>,
+    shard: Option<Shard>,
+) -> Result<Vec<CmdResult>> {
+    let with_tests = ctx.config.packages_with_cmd("test");
+    if !with_tests.is_empty() {
+        let mut packages = packages.to_vec();
+        if let Some(shard) = shard {
+            let mut names: Vec<String> = with_tests
+                .iter()
+                .map(|(name, _, _)| name.to_string())
+                .filter(|name| packages.is_empty() || packages.contains(name))
+                .collect();
+            names.sort();
+            packages = shard.select(&names);
+            ctx.print_info(&format!(
+                "Shard {}: {} of {} packages",
+                shard,
+                packages.len(),
+                names.len()
+            ));
+            if packages.is_empty() {
+                return Ok(Vec::new());
+            }
+        }
+        let cmd_opts = CmdOptions {
+            packages,
+            capture,
+            ..Default::default()
+        };
+        run_cmd(ctx, "test", &cmd_opts)
+    } else if let Some(shard) = shard {
+        if !packages.is_empty() {
+            return Err(anyhow!(
+                "No packages define [cmd.test] in dev.toml, so --shard can't be combined with package selection"
+            ));
+        }
+        Ok(vec![shard::run_root_shard(ctx, shard, capture, filter)?])
+    } else {
+        Ok(vec![run_root_tests(ctx, packages, capture, filter)?])
+    }
+}
+
+/// Run `cargo test` (or cargo-nextest) / `npm test` at the repository root
+///
+/// Selected packages are passed to cargo as `-p <name>`.
+fn run_root_tests(
+    ctx: &AppContext,
+    packages: &[String],
+    capture: bool,
+    filter: Option<&str>,
+) -> Result<CmdResult> {
+    let mut env = Vec::new();
+    let (program, args): (&str, Vec<String>) = match detect_kind(&ctx.repo) {
+        Some(ProjectKind::Rust) if nextest::use_nextest(ctx) => {
+            let mut args = nextest::nextest_args(ctx, packages, capture);
+            if capture {
+                env.push(nextest::LIBTEST_JSON_ENV);
+            }
+            // nextest takes filters directly rather than after `--`
+            args.extend(filter.map(String::from));
+            ("cargo", args)
+        }
+        Some(ProjectKind::Rust) => {
+            let mut args = vec!["test".to_string()];
+            if packages.is_empty() {
+                args.push("--workspace".into());
+            }
+            for package in packages {
+                args.extend(["-p".into(), package.clone()]);
+            }
+            if let Some(filter) = filter {
+                args.extend(["--".into(), filter.into()]);
+            }
+            ("cargo", args)
+        }
+        Some(ProjectKind::Node) if packages.is_empty() => {
+            let mut args = vec!["test".to_string()];
+            if let Some(filter) = filter {
+                args.extend(["--".into(), "-t".into(), filter.into()]);
+            }
+            ("npm", args)
+        }
+        Some(ProjectKind::Node) => {
+            return Err(anyhow!(
+                "No packages define [cmd.test] in dev.toml, so package selection doesn't apply"
+            ))
+        }
+        None => {
+            return Err(anyhow!(
+                "No tests found (expected [cmd.test], a Cargo.toml or a package.json)"
+            ))
+        }
+    };
+    if !capture {
+        ctx.print_header("Running tests");
+    }
+    let (success, output) = run_test_command(ctx, program, &args, &env, capture)?;
+    Ok(CmdResult {
+        package: root_package_name(ctx),
+        cmd_name: "test".to_string(),
+        success,
+        output,
+        cached: false,
+    })
+}
+
+/// Run a test command in the repository root, capturing stdout and stderr if asked
+pub(crate) fn run_test_command(
+    ctx: &AppContext,
+    program: &str,
+    args: &[String],
+    env: &[(&str, &str)],
+    capture: bool,
+) -> Result<(bool, Option<String>)> {
+    if capture {
+        let output = Command::new(program)
+            .args(args)
+            .envs(env.iter().copied())
+            .current_dir(&ctx.repo)
+            .logged_output()?;
+        let mut text = String::from_utf8_lossy(&output.stdout).to_string();
+        text.push_str(&String::from_utf8_lossy(&output.stderr));
+        return Ok((output.status.success(), Some(text)));
+    }
+
+    let mut cmd = CmdBuilder::new(program)
+        .args(args.iter().cloned())
+        .cwd(&ctx.repo)
+        .inherit_io();
+    for (key, value) in env {
+        cmd = cmd.env(*key, *value);
+    }
+    Ok((cmd.run()? == 0, None))
+}
@@ -0,0 +1,62 @@
+//! cargo-nextest integration
+//!
+//! Rust tests run under cargo-nextest when it's installed (or `[test] runner = "nextest"`),
+//! falling back to `cargo test`. `[test] profile` and `[test] retries` are passed through.
+//! When output is captured for reports, nextest emits libtest JSON so results feed
+//! the same parser as cargo test and jest output.
+//!
+//! ```toml
+//! [test]
+//! runner = "nextest"   # or "cargo", default "auto"
+//! profile = "ci"
+//! retries = 2
+//! ```
+//!
+//! nextest doesn't run doc tests; run `cargo test --doc` separately if you rely on them.
+
+use devkit_core::config::TestRunner;
+use devkit_core::AppContext;
+
+/// Environment variable enabling nextest's (experimental) libtest JSON output
+pub(crate) const LIBTEST_JSON_ENV: (&str, &str) = ("NEXTEST_EXPERIMENTAL_LIBTEST_JSON", "1");
+
+/// Whether Rust tests should run under cargo-nextest
+pub(crate) fn use_nextest(ctx: &AppContext) -> bool {
+    let installed = devkit_core::cmd_exists("cargo-nextest");
+    match ctx.config.global.test.runner {
+        TestRunner::Cargo => false,
+        TestRunner::Auto => installed,
+        TestRunner::Nextest => {
+            if !installed {
+                ctx.print_warning(
+                    "cargo-nextest not found, falling back to cargo test (install with: cargo install cargo-nextest)",
+                );
+            }
+            installed
+        }
+    }
+}
+
+/// `cargo nextest run` arguments for the workspace or the given packages
+///
+/// `json` switches to libtest JSON output for parsing.
+pub(crate) fn nextest_args(ctx: &AppContext, packages: &[String], json: bool) -> Vec<String> {
+    let config = &ctx.config.global.test;
+    let mut args: Vec<String> = vec!["nextest".into(), "run".into()];
+    if packages.is_empty() {
+        args.push("--workspace".into());
+    }
+    for package in packages {
+        args.extend(["-p".into(), package.clone()]);
+    }
+    if let Some(profile) = &config.profile {
+        args.extend(["--profile".into(), profile.clone()]);
+    }
+    if let Some(retries) = config.retries {
+        args.extend(["--retries".into(), retries.to_string()]);
+    }
+    if json {
+        args.extend(["--message-format".into(), "libtest-json".into()]);
+    }
+    args
+}
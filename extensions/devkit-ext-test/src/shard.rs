@@ -0,0 +1,287 @@
+//! Test sharding for parallel CI
+//!
+//! `devkit test --shard i/n` deterministically splits the suite so n machines each run
+//! a disjoint part of it. Items are sorted by a stable key and dealt round-robin:
+//! - packages, when packages define `[cmd.test]`
+//! - cargo test binaries (from `cargo test --no-run`), run directly; under
+//!   cargo-nextest, its `--partition count:i/n` instead
+//! - jest test files (from `jest --listTests`); vitest uses its own `--shard`
+
+use anyhow::{anyhow, Result};
+use devkit_core::oplog::LoggedCommand;
+use devkit_core::AppContext;
+use devkit_tasks::{CmdBuilder, CmdResult};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::str::FromStr;
+
+use crate::nextest;
+use crate::{
+    detect_kind, node_test_runner, root_package_name, run_test_command, NodeRunner, ProjectKind,
+};
+
+/// One shard of `total` (index is 1-based)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Shard {
+    pub index: usize,
+    pub total: usize,
+}
+
+impl Shard {
+    /// Items belonging to this shard, from a list already in stable order
+    pub fn select<T: Clone>(&self, items: &[T]) -> Vec<T> {
+        items
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| i % self.total == self.index - 1)
+            .map(|(_, item)| item.clone())
+            .collect()
+    }
+}
+
+impl FromStr for Shard {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse = || -> Option<Shard> {
+            let (index, total) = s.split_once('/')?;
+            Some(Shard {
+                index: index.trim().parse().ok()?,
+                total: total.trim().parse().ok()?,
+            })
+        };
+        match parse() {
+            Some(shard) if shard.index >= 1 && shard.index <= shard.total => Ok(shard),
+            _ => Err(format!(
+                "Invalid shard '{}' (expected i/n with 1 <= i <= n, e.g. 2/4)",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Shard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.index, self.total)
+    }
+}
+
+/// A compiled cargo test binary
+#[derive(Debug, Clone)]
+struct TestBinary {
+    /// Stable sort key: `<package dir>/<target kind>/<target name>`
+    key: String,
+    executable: PathBuf,
+    manifest_dir: PathBuf,
+}
+
+/// Build test binaries and list them in stable order
+fn cargo_test_binaries(repo: &Path) -> Result<Vec<TestBinary>> {
+    let output = CmdBuilder::new("cargo")
+        .args(["test", "--workspace", "--no-run", "--message-format=json"])
+        .cwd(repo)
+        .capture_stdout()
+        .run_capture()?;
+
+    let mut binaries: Vec<TestBinary> = output
+        .stdout_lines()
+        .iter()
+        .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+        .filter(|msg| msg["reason"] == "compiler-artifact" && msg["profile"]["test"] == true)
+        .filter_map(|msg| {
+            let executable = PathBuf::from(msg["executable"].as_str()?);
+            let manifest_dir = Path::new(msg["manifest_path"].as_str()?)
+                .parent()?
+                .to_path_buf();
+            let kind = msg["target"]["kind"][0].as_str().unwrap_or("lib");
+            let name = msg["target"]["name"].as_str()?;
+            let package_dir = manifest_dir.strip_prefix(repo).unwrap_or(&manifest_dir);
+            Some(TestBinary {
+                key: package_dir
+                    .join(kind)
+                    .join(name)
+                    .to_string_lossy()
+                    .replace('\\', "/"),
+                executable,
+                manifest_dir,
+            })
+        })
+        .collect();
+    binaries.sort_by(|a, b| a.key.cmp(&b.key));
+    binaries.dedup_by(|a, b| a.key == b.key);
+    Ok(binaries)
+}
+
+/// List jest test files in stable (repo-relative) order
+fn jest_test_files(dir: &Path) -> Result<Vec<String>> {
+    let output = CmdBuilder::new("npx")
+        .args(["jest", "--listTests"])
+        .cwd(dir)
+        .capture_stdout()
+        .run_capture()?;
+    let mut files: Vec<String> = output
+        .stdout_lines()
+        .into_iter()
+        .map(|file| {
+            Path::new(&file)
+                .strip_prefix(dir)
+                .map(|p| p.to_string_lossy().replace('\\', "/"))
+                .unwrap_or(file)
+        })
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+/// Run this shard's part of the root test suite
+pub(crate) fn run_root_shard(
+    ctx: &AppContext,
+    shard: Shard,
+    capture: bool,
+    filter: Option<&str>,
+) -> Result<CmdResult> {
+    let (success, output) = match detect_kind(&ctx.repo) {
+        Some(ProjectKind::Rust) => run_cargo_shard(ctx, shard, capture, filter)?,
+        Some(ProjectKind::Node) => run_node_shard(ctx, shard, capture, filter)?,
+        None => {
+            return Err(anyhow!(
+                "No tests found (expected [cmd.test], a Cargo.toml or a package.json)"
+            ))
+        }
+    };
+    Ok(CmdResult {
+        package: root_package_name(ctx),
+        cmd_name: "test".to_string(),
+        success,
+        output,
+        cached: false,
+    })
+}
+
+fn run_cargo_shard(
+    ctx: &AppContext,
+    shard: Shard,
+    capture: bool,
+    filter: Option<&str>,
+) -> Result<(bool, Option<String>)> {
+    if nextest::use_nextest(ctx) {
+        ctx.print_info(&format!(
+            "Shard {}: nextest partition count:{}",
+            shard, shard
+        ));
+        let mut args = nextest::nextest_args(ctx, &[], capture);
+        args.extend(["--partition".into(), format!("count:{}", shard)]);
+        args.extend(filter.map(String::from));
+        let env = if capture {
+            vec![nextest::LIBTEST_JSON_ENV]
+        } else {
+            Vec::new()
+        };
+        return run_test_command(ctx, "cargo", &args, &env, capture);
+    }
+
+    let binaries = cargo_test_binaries(&ctx.repo)?;
+    let selected = shard.select(&binaries);
+    ctx.print_info(&format!(
+        "Shard {}: {} of {} test binaries (doc tests are not sharded)",
+        shard,
+        selected.len(),
+        binaries.len()
+    ));
+
+    let mut success = true;
+    let mut combined = String::new();
+    for binary in &selected {
+        // Matches cargo test's header so reports attribute tests to the binary
+        let header = format!(
+            "     Running {} ({})",
+            binary.key,
+            binary.executable.display()
+        );
+        let mut cmd = Command::new(&binary.executable);
+        cmd.args(filter)
+            .current_dir(&binary.manifest_dir)
+            .env("CARGO_MANIFEST_DIR", &binary.manifest_dir);
+
+        if capture {
+            let output = cmd.logged_output()?;
+            combined.push_str(&header);
+            combined.push('\n');
+            combined.push_str(&String::from_utf8_lossy(&output.stdout));
+            combined.push_str(&String::from_utf8_lossy(&output.stderr));
+            success &= output.status.success();
+        } else {
+            println!("{}", header);
+            success &= cmd.logged_status()?.success();
+        }
+    }
+
+    Ok((success, capture.then_some(combined)))
+}
+
+fn run_node_shard(
+    ctx: &AppContext,
+    shard: Shard,
+    capture: bool,
+    filter: Option<&str>,
+) -> Result<(bool, Option<String>)> {
+    let mut args: Vec<String> = match node_test_runner(&ctx.repo) {
+        Some(NodeRunner::Vitest) => {
+            vec!["vitest".into(), "run".into(), format!("--shard={}", shard)]
+        }
+        Some(NodeRunner::Jest) => {
+            let files = jest_test_files(&ctx.repo)?;
+            let selected = shard.select(&files);
+            ctx.print_info(&format!(
+                "Shard {}: {} of {} test files",
+                shard,
+                selected.len(),
+                files.len()
+            ));
+            if selected.is_empty() {
+                return Ok((true, capture.then(String::new)));
+            }
+            let mut args = vec!["jest".to_string(), "--runTestsByPath".to_string()];
+            args.extend(selected);
+            args
+        }
+        None => {
+            return Err(anyhow!(
+                "Sharding Node tests requires jest or vitest in package.json"
+            ))
+        }
+    };
+    if let Some(filter) = filter {
+        args.extend(["-t".to_string(), filter.to_string()]);
+    }
+
+    run_test_command(ctx, "npx", &args, &[], capture)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_shard() {
+        assert_eq!("2/4".parse(), Ok(Shard { index: 2, total: 4 }));
+        assert!("0/4".parse::<Shard>().is_err());
+        assert!("5/4".parse::<Shard>().is_err());
+        assert!("2".parse::<Shard>().is_err());
+    }
+
+    #[test]
+    fn test_shards_cover_every_item_once() {
+        let items: Vec<u32> = (0..10).collect();
+        let mut seen: Vec<u32> = (1..=3)
+            .flat_map(|index| Shard { index, total: 3 }.select(&items))
+            .collect();
+        seen.sort();
+        assert_eq!(seen, items);
+        assert_eq!(
+            Shard { index: 1, total: 3 }.select(&items),
+            vec![0, 3, 6, 9]
+        );
+    }
+}
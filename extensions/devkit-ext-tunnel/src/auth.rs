@@ -0,0 +1,166 @@
+//! Tunnel provider authentication
+//!
+//! Checks provider credentials before a tunnel starts so missing auth surfaces as a
+//! setup prompt rather than a provider-specific failure halfway through startup.
+//! Tokens are read from the environment first, then from `.env.local` (where
+//! `devkit secrets` writes pulled secrets, and `devkit tunnel auth ngrok` saves the
+//! authtoken). They reach the provider through its environment, never its arguments.
+
+use anyhow::{anyhow, Result};
+use devkit_core::config::{TunnelEntry, TunnelProvider};
+use devkit_core::utils::upsert_env_vars;
+use devkit_core::AppContext;
+use devkit_tasks::CmdBuilder;
+use dialoguer::Password;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Environment variable the provider agent reads its token from
+pub fn token_env_var(provider: TunnelProvider) -> &'static str {
+    match provider {
+        TunnelProvider::Ngrok => "NGROK_AUTHTOKEN",
+        TunnelProvider::Cloudflared => "TUNNEL_TOKEN",
+    }
+}
+
+/// Look up a provider token from the environment or `.env.local`
+pub fn provider_token(repo: &Path, provider: TunnelProvider) -> Option<String> {
+    let key = token_env_var(provider);
+    std::env::var(key)
+        .ok()
+        .filter(|v| !v.is_empty())
+        .or_else(|| read_env_file(&repo.join(".env.local"), key))
+}
+
+fn read_env_file(path: &Path, key: &str) -> Option<String> {
+    let contents = fs::read_to_string(path).ok()?;
+    contents.lines().find_map(|line| {
+        let line = line.trim().trim_start_matches("export ");
+        let (k, v) = line.split_once('=')?;
+        let v = v.trim().trim_matches('"').trim_matches('\'');
+        (k.trim() == key && !v.is_empty()).then(|| v.to_string())
+    })
+}
+
+/// ngrok agent config locations (v3 per-platform location, then the legacy v2 path)
+fn ngrok_config_paths() -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+    if let Some(dir) = dirs::config_dir() {
+        paths.push(dir.join("ngrok/ngrok.yml"));
+    }
+    if let Some(dir) = dirs::data_local_dir() {
+        paths.push(dir.join("ngrok/ngrok.yml"));
+    }
+    if let Some(home) = dirs::home_dir() {
+        paths.push(home.join(".ngrok2/ngrok.yml"));
+    }
+    paths
+}
+
+fn ngrok_config_has_token() -> bool {
+    ngrok_config_paths().iter().any(|path| {
+        fs::read_to_string(path)
+            .map(|contents| contents.lines().any(|l| l.trim().starts_with("authtoken:")))
+            .unwrap_or(false)
+    })
+}
+
+/// Whether `cloudflared tunnel login` has been run
+pub fn cloudflared_logged_in() -> bool {
+    dirs::home_dir()
+        .map(|home| home.join(".cloudflared/cert.pem").exists())
+        .unwrap_or(false)
+}
+
+/// Ensure the tunnel's provider is authenticated, returning a token to pass via env
+///
+/// Offers to run the provider's auth flow when credentials are missing.
+pub fn ensure_auth(ctx: &AppContext, tunnel: &TunnelEntry) -> Result<Option<String>> {
+    let token = provider_token(&ctx.repo, tunnel.provider);
+
+    match tunnel.provider {
+        TunnelProvider::Ngrok => {
+            if token.is_some() || ngrok_config_has_token() {
+                return Ok(token);
+            }
+            ctx.print_warning("ngrok has no authtoken configured");
+            if ctx.is_interactive() && ctx.confirm("Set up ngrok authentication now?", true)? {
+                setup_auth(ctx, TunnelProvider::Ngrok)?;
+            }
+            if let Some(token) = provider_token(&ctx.repo, tunnel.provider) {
+                return Ok(Some(token));
+            }
+            Err(anyhow!(
+                "ngrok authtoken missing\n\
+                 Run 'devkit tunnel auth ngrok', set {}, or add it to .env.local",
+                token_env_var(tunnel.provider)
+            ))
+        }
+        // Quick tunnels are anonymous; no login is required
+        TunnelProvider::Cloudflared if tunnel.tunnel_name.is_none() => Ok(None),
+        TunnelProvider::Cloudflared => {
+            let has_credentials = tunnel
+                .credentials_file
+                .as_ref()
+                .is_some_and(|path| Path::new(path).exists());
+            if token.is_some() || has_credentials || cloudflared_logged_in() {
+                return Ok(token);
+            }
+            ctx.print_warning("cloudflared is not logged in");
+            if ctx.is_interactive() && ctx.confirm("Log in to Cloudflare now?", true)? {
+                setup_auth(ctx, TunnelProvider::Cloudflared)?;
+            }
+            if cloudflared_logged_in() {
+                return Ok(None);
+            }
+            Err(anyhow!(
+                "cloudflared credentials missing for named tunnel\n\
+                 Run 'devkit tunnel auth cloudflared', set {}, or set credentials_file",
+                token_env_var(tunnel.provider)
+            ))
+        }
+    }
+}
+
+/// Run the provider's interactive authentication flow
+pub fn setup_auth(ctx: &AppContext, provider: TunnelProvider) -> Result<()> {
+    crate::ensure_provider(provider)?;
+    ctx.require_interactive(
+        "Tunnel authentication",
+        &format!(
+            "Set {} instead, or run `devkit tunnel auth` in a terminal",
+            token_env_var(provider)
+        ),
+    )?;
+
+    match provider {
+        TunnelProvider::Ngrok => {
+            ctx.print_info(
+                "Get your authtoken from https://dashboard.ngrok.com/get-started/your-authtoken",
+            );
+            let token = Password::with_theme(&*ctx.theme())
+                .with_prompt("ngrok authtoken")
+                .interact()?;
+            // `ngrok config add-authtoken` would put the token in the process list
+            let key = token_env_var(provider);
+            upsert_env_vars(&ctx.repo.join(".env.local"), &[(key, token.trim())])?;
+            ctx.print_info(&format!("Saved {} to .env.local", key));
+        }
+        TunnelProvider::Cloudflared => {
+            if cloudflared_logged_in() {
+                ctx.print_info("cloudflared is already logged in");
+                return Ok(());
+            }
+            let code = CmdBuilder::new("cloudflared")
+                .args(["tunnel", "login"])
+                .inherit_io()
+                .run()?;
+            if code != 0 {
+                return Err(anyhow!("cloudflared tunnel login failed"));
+            }
+        }
+    }
+
+    ctx.print_success(&format!("✓ {} authenticated", provider.binary()));
+    Ok(())
+}
@@ -0,0 +1,82 @@
+//! `devkit tunnel` subcommand
+
+use anyhow::Result;
+use clap::{Args, Command, FromArgMatches, Subcommand};
+use devkit_core::config::TunnelProvider;
+use devkit_core::AppContext;
+
+use crate::{
+    list_tunnels, setup_auth, start_tunnel, start_tunnel_background, stop_tunnel, tunnel_status,
+    webhook_inspector,
+};
+
+#[derive(Args)]
+pub struct TunnelArgs {
+    #[command(subcommand)]
+    action: Option<TunnelAction>,
+}
+
+#[derive(Subcommand)]
+enum TunnelAction {
+    /// Start a tunnel
+    Start {
+        name: String,
+        /// Run in the background
+        #[arg(long, short = 'd')]
+        detach: bool,
+    },
+    /// Stop a background tunnel
+    Stop { name: String },
+    /// Show background tunnels
+    Status,
+    /// List configured tunnels
+    List,
+    /// Authenticate a tunnel provider
+    Auth {
+        #[arg(default_value = "ngrok")]
+        provider: TunnelProvider,
+    },
+    /// Tunnel to a request inspector that logs incoming webhooks
+    Webhook {
+        /// Local port to replay requests against
+        port: u16,
+        /// Tunnel provider (ngrok or cloudflared)
+        #[arg(long, default_value = "ngrok")]
+        provider: TunnelProvider,
+        /// Forward each request to localhost:<port> and return its response
+        #[arg(long)]
+        replay: bool,
+    },
+    #[command(external_subcommand)]
+    Named(Vec<String>),
+}
+
+pub fn command() -> Command {
+    TunnelArgs::augment_args(
+        Command::new("tunnel")
+            .about("Tunnel operations (if enabled)")
+            .long_about(
+                "Tunnel operations (if enabled)\n\n\
+                 `devkit tunnel <name>` starts a tunnel from [tunnel.<name>] in the foreground.",
+            ),
+    )
+}
+
+pub fn handle(ctx: &AppContext, matches: &clap::ArgMatches) -> Result<()> {
+    let args = TunnelArgs::from_arg_matches(matches)?;
+
+    match args.action {
+        Some(TunnelAction::Start { name, detach }) if detach => start_tunnel_background(ctx, &name),
+        Some(TunnelAction::Start { name, .. }) => start_tunnel(ctx, &name),
+        Some(TunnelAction::Stop { name }) => stop_tunnel(ctx, &name),
+        Some(TunnelAction::Status) => tunnel_status(ctx),
+        Some(TunnelAction::Auth { provider }) => setup_auth(ctx, provider),
+        Some(TunnelAction::Webhook {
+            port,
+            provider,
+            replay,
+        }) => webhook_inspector(ctx, port, provider, replay),
+        Some(TunnelAction::Named(args)) => start_tunnel(ctx, &args[0]),
+        Some(TunnelAction::List) | None => list_tunnels(ctx),
+    }
+}
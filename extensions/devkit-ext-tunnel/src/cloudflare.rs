@@ -0,0 +1,75 @@
+//! cloudflared named tunnels
+//!
+//! Named tunnels run under a Cloudflare account and serve a stable hostname in the
+//! team's zone (e.g. `dev-{user}.example.com`) instead of a random quick tunnel URL:
+//! ```toml
+//! [tunnel.api]
+//! port = 8080
+//! provider = "cloudflared"
+//! tunnel_name = "dev-alice"
+//! hostname = "dev-{user}.example.com"
+//! ```
+//! Create the tunnel once with `cloudflared tunnel create <name>`.
+
+use anyhow::Result;
+use devkit_core::config::TunnelEntry;
+use devkit_core::AppContext;
+use devkit_tasks::CmdBuilder;
+
+use crate::auth::cloudflared_logged_in;
+
+/// The tunnel's hostname with `{user}` expanded
+pub fn public_hostname(tunnel: &TunnelEntry) -> Option<String> {
+    let user = std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_default();
+    tunnel
+        .hostname
+        .as_ref()
+        .map(|h| h.replace("{user}", &user.to_lowercase()))
+}
+
+/// Public URL of a named tunnel, known up front from its hostname
+pub fn named_tunnel_url(tunnel: &TunnelEntry) -> Option<String> {
+    public_hostname(tunnel).map(|host| format!("https://{}", host))
+}
+
+/// `cloudflared tunnel run` for a named tunnel, serving the local port
+pub fn named_tunnel_command(tunnel: &TunnelEntry, tunnel_name: &str) -> CmdBuilder {
+    let mut cmd = CmdBuilder::new("cloudflared")
+        .args(["tunnel", "run", "--url"])
+        .arg(format!("http://localhost:{}", tunnel.port));
+    if let Some(credentials) = &tunnel.credentials_file {
+        cmd = cmd.arg("--credentials-file").arg(credentials);
+    }
+    cmd.arg(tunnel_name)
+}
+
+/// Point the tunnel's hostname at it via a DNS record
+///
+/// Requires `cloudflared tunnel login`. An existing record for the hostname is left in place.
+pub fn route_hostname(ctx: &AppContext, tunnel: &TunnelEntry) -> Result<()> {
+    let (Some(tunnel_name), Some(hostname)) = (&tunnel.tunnel_name, public_hostname(tunnel)) else {
+        return Ok(());
+    };
+
+    if !cloudflared_logged_in() {
+        ctx.print_warning(&format!(
+            "Not logged in to cloudflared - make sure {} routes to tunnel '{}'",
+            hostname, tunnel_name
+        ));
+        return Ok(());
+    }
+
+    let result = CmdBuilder::new("cloudflared")
+        .args(["tunnel", "route", "dns", tunnel_name, &hostname])
+        .cwd(&ctx.repo)
+        .run_capture();
+
+    match result {
+        Ok(_) => ctx.print_info(&format!("Routed {} to tunnel '{}'", hostname, tunnel_name)),
+        Err(e) if e.to_string().contains("already exists") => {}
+        Err(e) => ctx.print_warning(&format!("Failed to route {}: {:#}", hostname, e)),
+    }
+    Ok(())
+}
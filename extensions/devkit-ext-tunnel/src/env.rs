@@ -0,0 +1,55 @@
+//! Tunnel URL injection into the service environment
+//!
+//! Writes a tunnel's public URL into `.env.local` under the names listed in its
+//! `env` setting, then recreates the compose services in `restart` so they pick it up.
+//! Services read the file through `env_file: .env.local` in the compose file.
+
+use anyhow::{anyhow, Result};
+use devkit_core::config::TunnelEntry;
+use devkit_core::utils::{docker_compose_program, ensure_docker, upsert_env_vars};
+use devkit_core::validation::compose_files;
+use devkit_core::AppContext;
+use devkit_tasks::CmdBuilder;
+
+const ENV_FILE: &str = ".env.local";
+
+/// Write the public URL to the tunnel's env vars and recreate dependent services
+pub fn inject_tunnel_url(ctx: &AppContext, tunnel: &TunnelEntry, url: &str) -> Result<()> {
+    if tunnel.env.is_empty() {
+        return Ok(());
+    }
+
+    let vars: Vec<(&str, &str)> = tunnel.env.iter().map(|k| (k.as_str(), url)).collect();
+    upsert_env_vars(&ctx.repo.join(ENV_FILE), &vars)?;
+    ctx.print_info(&format!("Set {} in {}", tunnel.env.join(", "), ENV_FILE));
+
+    if !tunnel.restart.is_empty() {
+        recreate_services(ctx, &tunnel.restart)?;
+    }
+    Ok(())
+}
+
+/// Recreate services so they load the updated env file (`restart` keeps the old env)
+fn recreate_services(ctx: &AppContext, services: &[String]) -> Result<()> {
+    ensure_docker()?;
+
+    let (prog, mut args) = docker_compose_program()?;
+    // The same files `devkit docker up` started the services from
+    for file in compose_files(&ctx.repo, &ctx.config.global) {
+        args.extend(["-f".to_string(), file]);
+    }
+    args.extend(["up", "-d", "--no-deps"].map(String::from));
+    args.extend(services.iter().cloned());
+
+    ctx.print_info(&format!("Recreating {}...", services.join(", ")));
+    let code = CmdBuilder::new(&prog)
+        .args(&args)
+        .cwd(&ctx.repo)
+        .inherit_io()
+        .run()?;
+
+    if code != 0 {
+        return Err(anyhow!("docker compose up exited with code {code}"));
+    }
+    Ok(())
+}
@@ -0,0 +1,325 @@
+//! Tunneling extension for devkit
+//!
+//! Exposes local ports publicly via ngrok or cloudflared. Tunnels are defined in
+//! `.dev/config.toml`:
+//! ```toml
+//! [tunnel.api]
+//! port = 8080
+//! provider = "ngrok"
+//! subdomain = "myapp"
+//! ```
+//!
+//! Tunnels started from the menu run in the background; use `devkit tunnel status`
+//! and `devkit tunnel stop <name>` to manage them. The assigned public URL is read
+//! from ngrok's local API or cloudflared's output and copied to the clipboard.
+//! Any `[services]` entry can also be tunneled by name without a `[tunnel]` section.
+//!
+//! cloudflared named tunnels with a stable `hostname` are supported via `tunnel_name`.
+//!
+//! List env var names in `env` to have the URL written to `.env.local`, and compose
+//! services in `restart` to have them recreated with it:
+//! ```toml
+//! [tunnel.api]
+//! port = 8080
+//! env = ["PUBLIC_URL", "WEBHOOK_BASE_URL"]
+//! restart = ["api"]
+//! ```
+//!
+//! Provider credentials are checked before starting; `devkit tunnel auth <provider>`
+//! saves an ngrok authtoken to `.env.local` or runs cloudflared's login flow.
+//!
+//! `devkit tunnel webhook <port>` tunnels to a request inspector that logs incoming
+//! requests and can replay them against the local service.
+
+mod auth;
+mod cli;
+mod cloudflare;
+mod env;
+mod manager;
+mod url;
+mod webhook;
+
+pub use auth::{ensure_auth, provider_token, setup_auth, token_env_var};
+pub use cloudflare::{named_tunnel_url, public_hostname};
+pub use env::inject_tunnel_url;
+pub use manager::{
+    start_tunnel_background, stop_all_tunnels, stop_tunnel, tunnel_status, RunningTunnel,
+    TunnelState,
+};
+pub use url::{find_public_url, wait_for_public_url};
+pub use webhook::{webhook_inspector, InspectedRequest};
+
+use anyhow::{anyhow, Result};
+use console::style;
+use devkit_core::config::{TunnelEntry, TunnelProvider};
+use devkit_core::{AppContext, Extension, MenuItem};
+use devkit_tasks::CmdBuilder;
+use dialoguer::Select;
+
+pub struct TunnelExtension;
+
+impl Extension for TunnelExtension {
+    fn name(&self) -> &str {
+        "tunnel"
+    }
+
+    fn is_available(&self, ctx: &AppContext) -> bool {
+        !ctx.config.global.tunnel.is_empty() || !ctx.config.global.services.ports.is_empty()
+    }
+
+    fn unavailable_reason(&self, _ctx: &AppContext) -> Option<String> {
+        Some("no [tunnel] or [services] configured".to_string())
+    }
+
+    fn menu_items(&self, ctx: &AppContext) -> Vec<MenuItem> {
+        let mut items: Vec<MenuItem> = ctx
+            .config
+            .global
+            .tunnel
+            .names()
+            .into_iter()
+            .filter_map(|name| {
+                let tunnel = ctx.config.global.tunnel.get(name)?;
+                let name = name.to_string();
+                Some(MenuItem {
+                    label: format!(
+                        "{} (port {} via {})",
+                        name,
+                        tunnel.port,
+                        tunnel.provider.binary()
+                    ),
+                    group: Some("🌍 Tunnels".to_string()),
+                    handler: Box::new(move |ctx| {
+                        start_tunnel_background(ctx, &name).map_err(Into::into)
+                    }),
+                })
+            })
+            .collect();
+
+        if !ctx.config.global.services.ports.is_empty() {
+            items.push(MenuItem {
+                label: "Tunnel a service...".to_string(),
+                group: Some("🌍 Tunnels".to_string()),
+                handler: Box::new(|ctx| {
+                    let name = select_service(ctx)?;
+                    start_tunnel_background(ctx, &name).map_err(Into::into)
+                }),
+            });
+        }
+        items.push(MenuItem {
+            label: "Tunnel status".to_string(),
+            group: Some("🌍 Tunnels".to_string()),
+            handler: Box::new(|ctx| tunnel_status(ctx).map_err(Into::into)),
+        });
+        items.push(MenuItem {
+            label: "Stop all tunnels".to_string(),
+            group: Some("🌍 Tunnels".to_string()),
+            handler: Box::new(|ctx| stop_all_tunnels(ctx).map_err(Into::into)),
+        });
+        items
+    }
+
+    fn subcommands(&self) -> Vec<clap::Command> {
+        vec![cli::command()]
+    }
+
+    fn handle_cli(
+        &self,
+        ctx: &AppContext,
+        name: &str,
+        matches: &clap::ArgMatches,
+    ) -> Option<devkit_core::Result<()>> {
+        (name == "tunnel").then(|| cli::handle(ctx, matches).map_err(Into::into))
+    }
+}
+
+/// Look up a tunnel by name
+///
+/// Falls back to a default tunnel for a `[services]` entry of the same name.
+pub fn find_tunnel(ctx: &AppContext, name: &str) -> Result<TunnelEntry> {
+    let tunnels = &ctx.config.global.tunnel;
+    if let Some(tunnel) = tunnels.get(name) {
+        return Ok(tunnel.clone());
+    }
+    if let Some(port) = ctx.config.global.services.ports.get(name) {
+        return Ok(TunnelEntry::for_port(*port));
+    }
+
+    let mut available = tunnels.names();
+    available.extend(
+        service_targets(ctx)
+            .into_iter()
+            .map(|(name, _)| name)
+            .filter(|name| tunnels.get(name).is_none()),
+    );
+    Err(anyhow!(
+        "Tunnel '{}' not found\nAvailable tunnels: {}",
+        name,
+        if available.is_empty() {
+            "none".to_string()
+        } else {
+            available.join(", ")
+        }
+    ))
+}
+
+/// `[services]` entries usable as tunnel targets, sorted by name
+fn service_targets(ctx: &AppContext) -> Vec<(&str, u16)> {
+    let mut services: Vec<_> = ctx
+        .config
+        .global
+        .services
+        .ports
+        .iter()
+        .map(|(name, port)| (name.as_str(), *port))
+        .collect();
+    services.sort();
+    services
+}
+
+/// Pick a `[services]` entry to tunnel
+fn select_service(ctx: &AppContext) -> Result<String> {
+    let services = service_targets(ctx);
+    if services.is_empty() {
+        return Err(anyhow!("No services defined in [services]"));
+    }
+    if services.len() == 1 {
+        return Ok(services[0].0.to_string());
+    }
+    ctx.require_interactive("Selecting a service", "Pass the service name to tunnel")?;
+
+    let items: Vec<String> = services
+        .iter()
+        .map(|(name, port)| format!("{} ({})", name, port))
+        .collect();
+
+    let selection = Select::with_theme(&*ctx.theme())
+        .with_prompt("Tunnel which service?")
+        .items(&items)
+        .default(0)
+        .interact()?;
+
+    Ok(services[selection].0.to_string())
+}
+
+/// Build the provider command for a tunnel, passing `token` through the provider's env var
+pub fn tunnel_command(tunnel: &TunnelEntry, token: Option<&str>) -> CmdBuilder {
+    let cmd = match tunnel.provider {
+        TunnelProvider::Ngrok => {
+            let mut cmd = CmdBuilder::new("ngrok")
+                .arg("http")
+                .arg(tunnel.port.to_string());
+            if let Some(subdomain) = &tunnel.subdomain {
+                cmd = cmd.arg(format!("--subdomain={}", subdomain));
+            }
+            cmd
+        }
+        TunnelProvider::Cloudflared => match &tunnel.tunnel_name {
+            Some(tunnel_name) => cloudflare::named_tunnel_command(tunnel, tunnel_name),
+            None => CmdBuilder::new("cloudflared")
+                .arg("tunnel")
+                .arg("--url")
+                .arg(format!("http://localhost:{}", tunnel.port)),
+        },
+    };
+
+    match token {
+        Some(token) => cmd.env(token_env_var(tunnel.provider), token),
+        None => cmd,
+    }
+}
+
+/// Ensure the provider CLI for a tunnel is installed
+pub(crate) fn ensure_provider(provider: TunnelProvider) -> Result<()> {
+    if devkit_core::cmd_exists(provider.binary()) {
+        return Ok(());
+    }
+
+    let hint = match provider {
+        TunnelProvider::Ngrok => "Install from: https://ngrok.com/download",
+        TunnelProvider::Cloudflared => {
+            "Install from: https://developers.cloudflare.com/cloudflare-one/connections/connect-networks/downloads/"
+        }
+    };
+    Err(anyhow!("{} not found. {}", provider.binary(), hint))
+}
+
+/// Start a configured tunnel in the foreground
+pub fn start_tunnel(ctx: &AppContext, name: &str) -> Result<()> {
+    let tunnel = find_tunnel(ctx, name)?;
+    ensure_provider(tunnel.provider)?;
+    let token = ensure_auth(ctx, &tunnel)?;
+
+    ctx.print_header(&format!("Starting tunnel: {}", name));
+    println!(
+        "Exposing {} via {}",
+        style(format!("localhost:{}", tunnel.port)).cyan(),
+        tunnel.provider.binary()
+    );
+
+    if tunnel.subdomain.is_some() && tunnel.provider == TunnelProvider::Cloudflared {
+        ctx.print_warning("cloudflared doesn't support subdomains - use tunnel_name and hostname");
+    }
+
+    cloudflare::route_hostname(ctx, &tunnel)?;
+    if let Some(url) = named_tunnel_url(&tunnel) {
+        println!("Public URL: {}", style(url).cyan().bold());
+    }
+
+    let code = tunnel_command(&tunnel, token.as_deref())
+        .cwd(&ctx.repo)
+        .inherit_io()
+        .run()?;
+
+    // Exit code 130 = Ctrl+C
+    if code != 0 && code != 130 {
+        return Err(anyhow!(
+            "{} exited with code {}",
+            tunnel.provider.binary(),
+            code
+        ));
+    }
+
+    Ok(())
+}
+
+/// List configured tunnels
+pub fn list_tunnels(ctx: &AppContext) -> Result<()> {
+    ctx.print_header("Configured tunnels");
+
+    let tunnels = &ctx.config.global.tunnel;
+    let services: Vec<_> = service_targets(ctx)
+        .into_iter()
+        .filter(|(name, _)| tunnels.get(name).is_none())
+        .collect();
+
+    if tunnels.is_empty() && services.is_empty() {
+        ctx.print_info("No tunnels configured. Add one to .dev/config.toml:");
+        println!();
+        println!("  [tunnel.api]");
+        println!("  port = 8080");
+        println!("  provider = \"ngrok\"");
+        return Ok(());
+    }
+
+    for name in tunnels.names() {
+        if let Some(tunnel) = tunnels.get(name) {
+            println!(
+                "  {:<16} localhost:{:<6} {}",
+                style(name).bold(),
+                tunnel.port,
+                tunnel.provider.binary()
+            );
+        }
+    }
+
+    if !services.is_empty() {
+        println!();
+        ctx.print_info("Services (tunnel with 'devkit tunnel <service>'):");
+        for (name, port) in services {
+            println!("  {:<16} localhost:{}", style(name).bold(), port);
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,225 @@
+//! Background tunnel manager
+//!
+//! Running tunnels are recorded in `.dev/run/tunnels.json` so later invocations can
+//! report on and stop them, each logging to `.dev/run/tunnel-<name>.log`. Entries whose
+//! process has exited are pruned on every load.
+
+use anyhow::{anyhow, Context, Result};
+use console::style;
+use devkit_core::config::TunnelEntry;
+use devkit_core::process::{current_timestamp, process_alive, terminate, RUN_DIR};
+use devkit_core::AppContext;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::auth::ensure_auth;
+use crate::env::inject_tunnel_url;
+use crate::url::{find_public_url, wait_for_public_url, URL_TIMEOUT};
+use crate::{cloudflare, ensure_provider, find_tunnel, named_tunnel_url, tunnel_command};
+
+const STATE_FILE: &str = "tunnels.json";
+
+/// A tunnel running in the background
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RunningTunnel {
+    pub pid: u32,
+    pub port: u16,
+    pub provider: String,
+    pub url: Option<String>,
+    pub log_file: PathBuf,
+    pub started_at: u64,
+}
+
+/// Running tunnels keyed by tunnel name
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct TunnelState {
+    pub tunnels: BTreeMap<String, RunningTunnel>,
+}
+
+impl TunnelState {
+    /// Load state, dropping entries whose process is no longer alive
+    pub fn load(repo: &Path) -> Result<Self> {
+        let path = state_path(repo);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let contents = fs::read_to_string(&path)?;
+        let mut state: TunnelState = serde_json::from_str(&contents)
+            .with_context(|| format!("Invalid tunnel state file: {}", path.display()))?;
+
+        let before = state.tunnels.len();
+        state.tunnels.retain(|_, t| process_alive(t.pid));
+        if state.tunnels.len() != before {
+            state.save(repo)?;
+        }
+
+        Ok(state)
+    }
+
+    pub fn save(&self, repo: &Path) -> Result<()> {
+        fs::create_dir_all(repo.join(RUN_DIR))?;
+        fs::write(state_path(repo), serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+fn state_path(repo: &Path) -> PathBuf {
+    repo.join(RUN_DIR).join(STATE_FILE)
+}
+
+/// Start a configured tunnel as a background process
+pub fn start_tunnel_background(ctx: &AppContext, name: &str) -> Result<()> {
+    let tunnel = find_tunnel(ctx, name)?;
+    ensure_provider(tunnel.provider)?;
+
+    let state = TunnelState::load(&ctx.repo)?;
+    if let Some(running) = state.tunnels.get(name) {
+        ctx.print_warning(&format!(
+            "Tunnel '{}' is already running (pid {})",
+            name, running.pid
+        ));
+        return Ok(());
+    }
+
+    launch_tunnel(ctx, name, &tunnel).map(|_| ())
+}
+
+/// Spawn a tunnel in the background, wait for its public URL and record it in the state file
+pub(crate) fn launch_tunnel(
+    ctx: &AppContext,
+    name: &str,
+    tunnel: &TunnelEntry,
+) -> Result<RunningTunnel> {
+    let token = ensure_auth(ctx, tunnel)?;
+    let mut state = TunnelState::load(&ctx.repo)?;
+
+    fs::create_dir_all(ctx.repo.join(RUN_DIR))?;
+    let log_file = ctx.repo.join(RUN_DIR).join(format!("tunnel-{}.log", name));
+
+    // Before spawning, so a failure can't leave a tunnel running outside the state file
+    cloudflare::route_hostname(ctx, tunnel)?;
+
+    let pid = tunnel_command(tunnel, token.as_deref())
+        .cwd(&ctx.repo)
+        .spawn_background(&log_file)?;
+
+    let mut running = RunningTunnel {
+        pid,
+        port: tunnel.port,
+        provider: tunnel.provider.binary().to_string(),
+        url: None,
+        log_file: log_file.clone(),
+        started_at: current_timestamp(),
+    };
+
+    ctx.print_success(&format!(
+        "✓ Tunnel '{}' started in background (pid {})",
+        name, pid
+    ));
+    running.url = match named_tunnel_url(tunnel) {
+        Some(url) => Some(url),
+        None => {
+            ctx.print_info("Waiting for public URL...");
+            wait_for_public_url(&running, URL_TIMEOUT)
+        }
+    };
+
+    if running.url.is_none() && !process_alive(pid) {
+        return Err(anyhow!(
+            "Tunnel '{}' exited during startup. See {}",
+            name,
+            log_file.display()
+        ));
+    }
+
+    state.tunnels.insert(name.to_string(), running.clone());
+    state.save(&ctx.repo)?;
+
+    match &running.url {
+        Some(url) => {
+            println!();
+            println!(
+                "  {} {}",
+                style("Public URL:").bold(),
+                style(url).cyan().bold().underlined()
+            );
+            println!();
+            if devkit_core::utils::copy_to_clipboard(url).is_ok() {
+                ctx.print_info("Copied to clipboard");
+            }
+            inject_tunnel_url(ctx, tunnel, url)?;
+        }
+        None => {
+            ctx.print_warning("Public URL not available yet - check 'devkit tunnel status'");
+        }
+    }
+    ctx.print_info(&format!("Logs: {}", log_file.display()));
+    Ok(running)
+}
+
+/// Stop a background tunnel
+pub fn stop_tunnel(ctx: &AppContext, name: &str) -> Result<()> {
+    let mut state = TunnelState::load(&ctx.repo)?;
+    let running = state
+        .tunnels
+        .remove(name)
+        .ok_or_else(|| anyhow!("Tunnel '{}' is not running", name))?;
+
+    terminate(running.pid)?;
+    state.save(&ctx.repo)?;
+
+    ctx.print_success(&format!("✓ Tunnel '{}' stopped", name));
+    Ok(())
+}
+
+/// Stop every background tunnel
+pub fn stop_all_tunnels(ctx: &AppContext) -> Result<()> {
+    let state = TunnelState::load(&ctx.repo)?;
+    if state.tunnels.is_empty() {
+        ctx.print_info("No tunnels running");
+        return Ok(());
+    }
+
+    for name in state.tunnels.keys() {
+        stop_tunnel(ctx, name)?;
+    }
+    Ok(())
+}
+
+/// Show background tunnels
+pub fn tunnel_status(ctx: &AppContext) -> Result<()> {
+    ctx.print_header("Tunnel status");
+
+    let mut state = TunnelState::load(&ctx.repo)?;
+    if state.tunnels.is_empty() {
+        ctx.print_info("No tunnels running");
+        return Ok(());
+    }
+
+    // Fill in URLs for tunnels that hadn't reported one when they were started
+    let mut resolved = false;
+    for tunnel in state.tunnels.values_mut().filter(|t| t.url.is_none()) {
+        tunnel.url = find_public_url(tunnel);
+        resolved |= tunnel.url.is_some();
+    }
+    if resolved {
+        state.save(&ctx.repo)?;
+    }
+
+    for (name, tunnel) in &state.tunnels {
+        println!(
+            "  {} {:<16} localhost:{:<6} {:<12} pid {:<8} {}",
+            style("●").green(),
+            style(name).bold(),
+            tunnel.port,
+            tunnel.provider,
+            tunnel.pid,
+            style(tunnel.url.as_deref().unwrap_or("-")).cyan()
+        );
+    }
+
+    Ok(())
+}
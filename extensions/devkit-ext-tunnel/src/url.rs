@@ -0,0 +1,135 @@
+//! Public URL discovery for running tunnels
+//!
+//! ngrok exposes its tunnels on a local API; cloudflared prints the assigned
+//! `*.trycloudflare.com` URL to its log output.
+
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::manager::RunningTunnel;
+
+/// ngrok's local agent API (4040 is the default; later agents bind 4041, 4042, ...)
+const NGROK_API_PORTS: std::ops::Range<u16> = 4040..4045;
+
+/// How long to wait for a freshly started tunnel to report its URL
+pub const URL_TIMEOUT: Duration = Duration::from_secs(15);
+
+#[derive(Debug, Deserialize)]
+struct NgrokTunnels {
+    tunnels: Vec<NgrokTunnel>,
+}
+
+#[derive(Debug, Deserialize)]
+struct NgrokTunnel {
+    public_url: String,
+    config: NgrokTunnelConfig,
+}
+
+#[derive(Debug, Deserialize)]
+struct NgrokTunnelConfig {
+    addr: String,
+}
+
+/// Look up the public URL of a running tunnel without waiting
+pub fn find_public_url(tunnel: &RunningTunnel) -> Option<String> {
+    match tunnel.provider.as_str() {
+        "ngrok" => ngrok_url(tunnel.port),
+        "cloudflared" => cloudflared_url(&tunnel.log_file),
+        _ => None,
+    }
+}
+
+/// Poll until the tunnel reports its public URL or `timeout` elapses
+pub fn wait_for_public_url(tunnel: &RunningTunnel, timeout: Duration) -> Option<String> {
+    let start = Instant::now();
+    while start.elapsed() < timeout {
+        if let Some(url) = find_public_url(tunnel) {
+            return Some(url);
+        }
+        thread::sleep(Duration::from_millis(500));
+    }
+    None
+}
+
+/// Query the ngrok agent API for the https tunnel forwarding to `port`
+fn ngrok_url(port: u16) -> Option<String> {
+    let agent = ureq::AgentBuilder::new()
+        .timeout(Duration::from_secs(1))
+        .build();
+
+    NGROK_API_PORTS.into_iter().find_map(|api_port| {
+        let response: NgrokTunnels = agent
+            .get(&format!("http://127.0.0.1:{}/api/tunnels", api_port))
+            .call()
+            .ok()?
+            .into_json()
+            .ok()?;
+
+        let mut matching: Vec<_> = response
+            .tunnels
+            .into_iter()
+            .filter(|t| addr_port(&t.config.addr) == Some(port))
+            .collect();
+        // Prefer https when ngrok opened both http and https endpoints
+        matching.sort_by_key(|t| !t.public_url.starts_with("https://"));
+        matching.into_iter().next().map(|t| t.public_url)
+    })
+}
+
+/// Extract the port from an ngrok `addr` (`http://localhost:8080`, `localhost:8080` or `8080`)
+fn addr_port(addr: &str) -> Option<u16> {
+    addr.rsplit(':').next()?.trim_end_matches('/').parse().ok()
+}
+
+/// Find the quick tunnel URL cloudflared logged at startup
+fn cloudflared_url(log_file: &Path) -> Option<String> {
+    let log = fs::read_to_string(log_file).ok()?;
+    log.split_whitespace()
+        .map(|word| word.trim_matches(|c: char| c == '|' || c == '"'))
+        .find(|word| word.starts_with("https://") && word.contains(".trycloudflare.com"))
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_addr_port() {
+        assert_eq!(addr_port("http://localhost:8080"), Some(8080));
+        assert_eq!(addr_port("http://localhost:8080/"), Some(8080));
+        assert_eq!(addr_port("localhost:8080"), Some(8080));
+        assert_eq!(addr_port("8080"), Some(8080));
+        assert_eq!(addr_port("http://localhost"), None);
+    }
+
+    #[test]
+    fn test_cloudflared_url() {
+        let log_file =
+            std::env::temp_dir().join(format!("devkit-cloudflared-{}.log", std::process::id()));
+        fs::write(
+            &log_file,
+            "2024-05-01T10:00:00Z INF Requesting new quick Tunnel on trycloudflare.com...\n\
+             2024-05-01T10:00:01Z INF |  Your quick Tunnel has been created! Visit it at:  |\n\
+             2024-05-01T10:00:01Z INF |  https://calm-river-1234.trycloudflare.com         |\n",
+        )
+        .unwrap();
+        assert_eq!(
+            cloudflared_url(&log_file).as_deref(),
+            Some("https://calm-river-1234.trycloudflare.com")
+        );
+
+        fs::write(
+            &log_file,
+            "2024-05-01T10:00:00Z ERR failed to request quick Tunnel\n",
+        )
+        .unwrap();
+        assert_eq!(cloudflared_url(&log_file), None);
+
+        fs::remove_file(&log_file).unwrap();
+        assert_eq!(cloudflared_url(&log_file), None);
+    }
+}
@@ -0,0 +1,322 @@
+//! Webhook testing helper
+//!
+//! Opens a tunnel to a local request inspector that prints every incoming request
+//! and optionally replays it against the local service. The tunnel is stopped when
+//! the inspector exits.
+
+use anyhow::{anyhow, bail, Context, Result};
+use console::style;
+use devkit_core::config::{TunnelEntry, TunnelProvider};
+use devkit_core::AppContext;
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Once;
+use std::thread;
+use std::time::Duration;
+
+use crate::ensure_provider;
+use crate::manager::{launch_tunnel, stop_tunnel, TunnelState};
+
+static STOP: AtomicBool = AtomicBool::new(false);
+static CTRL_C: Once = Once::new();
+
+/// Name the webhook tunnel is recorded under in the tunnel state file
+const WEBHOOK_TUNNEL: &str = "webhook";
+
+/// Headers that are connection-specific and must not be forwarded on replay
+const HOP_HEADERS: &[&str] = &["host", "content-length", "connection", "transfer-encoding"];
+
+/// Longest body printed to the terminal
+const MAX_PRINTED_BODY: usize = 4096;
+
+/// Largest body accepted; bigger requests get a 413
+const MAX_BODY: usize = 10 * 1024 * 1024;
+
+/// A request whose Content-Length is over [`MAX_BODY`]
+#[derive(Debug)]
+struct BodyTooLarge(usize);
+
+impl fmt::Display for BodyTooLarge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Body of {} bytes is over the {} byte limit",
+            self.0, MAX_BODY
+        )
+    }
+}
+
+impl std::error::Error for BodyTooLarge {}
+
+/// An HTTP request received by the inspector
+#[derive(Debug)]
+pub struct InspectedRequest {
+    pub method: String,
+    pub path: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+/// Tunnel to a request inspector, logging requests and optionally replaying them to `port`
+pub fn webhook_inspector(
+    ctx: &AppContext,
+    port: u16,
+    provider: TunnelProvider,
+    replay: bool,
+) -> Result<()> {
+    ensure_provider(provider)?;
+    if let Some(running) = TunnelState::load(&ctx.repo)?.tunnels.get(WEBHOOK_TUNNEL) {
+        bail!(
+            "A webhook tunnel is already running (pid {}). Stop it with 'devkit tunnel stop {}'",
+            running.pid,
+            WEBHOOK_TUNNEL
+        );
+    }
+    stop_on_ctrl_c()?;
+
+    let listener = TcpListener::bind("127.0.0.1:0").context("Failed to start request inspector")?;
+    listener.set_nonblocking(true)?;
+    let inspector_port = listener.local_addr()?.port();
+
+    ctx.print_header("Webhook inspector");
+    let tunnel = TunnelEntry {
+        provider,
+        ..TunnelEntry::for_port(inspector_port)
+    };
+    launch_tunnel(ctx, WEBHOOK_TUNNEL, &tunnel)?;
+
+    if replay {
+        ctx.print_info(&format!("Replaying requests to http://localhost:{}", port));
+    }
+    ctx.print_info("Waiting for requests (Ctrl+C to stop)...");
+    println!();
+
+    let result = inspect(ctx, &listener, port, replay);
+    println!();
+    stop_tunnel(ctx, WEBHOOK_TUNNEL)?;
+    result
+}
+
+/// Handle requests until Ctrl-C
+fn inspect(ctx: &AppContext, listener: &TcpListener, port: u16, replay: bool) -> Result<()> {
+    while !STOP.load(Ordering::SeqCst) {
+        let mut stream = match listener.accept() {
+            Ok((stream, _)) => stream,
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                thread::sleep(Duration::from_millis(100));
+                continue;
+            }
+            Err(e) => {
+                ctx.print_warning(&format!("Connection failed: {}", e));
+                continue;
+            }
+        };
+        stream.set_nonblocking(false)?;
+
+        let request = match read_request(&mut stream) {
+            Ok(request) => request,
+            Err(e) => {
+                match e.downcast_ref::<BodyTooLarge>() {
+                    Some(_) => respond(&mut stream, 413, "Payload Too Large", b""),
+                    None => respond(&mut stream, 400, "Bad Request", b""),
+                }
+                ctx.print_warning(&format!("Ignoring request: {:#}", e));
+                continue;
+            }
+        };
+
+        print_request(&request);
+
+        if replay {
+            match replay_request(&request, port) {
+                Ok((status, body)) => {
+                    println!("  {} {}", style("→ replayed:").dim(), style_status(status));
+                    respond(&mut stream, status, "", &body);
+                }
+                Err(e) => {
+                    ctx.print_warning(&format!("Replay failed: {:#}", e));
+                    respond(&mut stream, 502, "Bad Gateway", b"");
+                }
+            }
+        } else {
+            respond(&mut stream, 200, "OK", b"");
+        }
+        println!();
+    }
+
+    Ok(())
+}
+
+/// Parse an HTTP/1.1 request from the stream
+fn read_request(stream: impl Read) -> Result<InspectedRequest> {
+    let mut reader = BufReader::new(stream);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts
+        .next()
+        .ok_or_else(|| anyhow!("Empty request"))?
+        .to_string();
+    let path = parts.next().unwrap_or("/").to_string();
+
+    let mut headers = Vec::new();
+    let mut content_length = 0;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            return Err(anyhow!("Connection closed before headers were complete"));
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((key, value)) = line.split_once(':') {
+            let (key, value) = (key.trim().to_string(), value.trim().to_string());
+            if key.eq_ignore_ascii_case("content-length") {
+                content_length = value.parse().context("Invalid Content-Length")?;
+            }
+            headers.push((key, value));
+        }
+    }
+
+    if content_length > MAX_BODY {
+        return Err(BodyTooLarge(content_length).into());
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+
+    Ok(InspectedRequest {
+        method,
+        path,
+        headers,
+        body,
+    })
+}
+
+fn print_request(request: &InspectedRequest) {
+    println!(
+        "{} {}",
+        style(&request.method).cyan().bold(),
+        style(&request.path).bold()
+    );
+    for (key, value) in &request.headers {
+        println!("  {}: {}", style(key).dim(), value);
+    }
+
+    if !request.body.is_empty() {
+        println!();
+        let body = String::from_utf8_lossy(&request.body);
+        // Pretty-print JSON payloads, which is what most webhooks send
+        let body = serde_json::from_str::<serde_json::Value>(&body)
+            .and_then(|json| serde_json::to_string_pretty(&json))
+            .unwrap_or_else(|_| body.into_owned());
+        if body.len() > MAX_PRINTED_BODY {
+            println!(
+                "{}",
+                body.chars().take(MAX_PRINTED_BODY).collect::<String>()
+            );
+            println!(
+                "{}",
+                style(format!("... ({} bytes total)", request.body.len())).dim()
+            );
+        } else {
+            println!("{}", body);
+        }
+    }
+}
+
+/// Send the request to the local service, returning its status and body
+fn replay_request(request: &InspectedRequest, port: u16) -> Result<(u16, Vec<u8>)> {
+    let agent = ureq::AgentBuilder::new()
+        .timeout(Duration::from_secs(30))
+        .build();
+
+    let mut upstream = agent.request(
+        &request.method,
+        &format!("http://localhost:{}{}", port, request.path),
+    );
+    for (key, value) in &request.headers {
+        if !HOP_HEADERS.contains(&key.to_ascii_lowercase().as_str()) {
+            upstream = upstream.set(key, value);
+        }
+    }
+
+    let response = match upstream.send_bytes(&request.body) {
+        Ok(response) => response,
+        // Error statuses are still valid responses to pass back to the sender
+        Err(ureq::Error::Status(_, response)) => response,
+        Err(e) => return Err(e.into()),
+    };
+
+    let status = response.status();
+    let mut body = Vec::new();
+    response.into_reader().read_to_end(&mut body)?;
+    Ok((status, body))
+}
+
+fn style_status(status: u16) -> console::StyledObject<u16> {
+    match status {
+        200..=299 => style(status).green(),
+        400..=499 => style(status).yellow(),
+        _ => style(status).red(),
+    }
+}
+
+fn respond(stream: &mut TcpStream, status: u16, reason: &str, body: &[u8]) {
+    let _ = write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        status,
+        reason,
+        body.len()
+    );
+    let _ = stream.write_all(body);
+}
+
+/// Reset the stop flag, installing the Ctrl-C handler on first use
+fn stop_on_ctrl_c() -> Result<()> {
+    STOP.store(false, Ordering::SeqCst);
+    let mut result = Ok(());
+    CTRL_C.call_once(|| {
+        result = ctrlc::set_handler(|| STOP.store(true, Ordering::SeqCst))
+            .context("Failed to set the Ctrl-C handler");
+    });
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_request() {
+        let raw = "POST /hooks/github?x=1 HTTP/1.1\r\nHost: abc.ngrok.io\r\ncontent-length: 11\r\nX-GitHub-Event: push\r\n\r\n{\"ok\":true}";
+        let request = read_request(raw.as_bytes()).unwrap();
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.path, "/hooks/github?x=1");
+        assert_eq!(request.headers[2], ("X-GitHub-Event".into(), "push".into()));
+        assert_eq!(request.body, br#"{"ok":true}"#);
+
+        let request = read_request("GET / HTTP/1.1\r\n\r\n".as_bytes()).unwrap();
+        assert!(request.body.is_empty());
+    }
+
+    #[test]
+    fn test_read_request_rejects_bad_requests() {
+        let too_large = format!(
+            "POST / HTTP/1.1\r\nContent-Length: {}\r\n\r\n",
+            MAX_BODY + 1
+        );
+        let error = read_request(too_large.as_bytes()).unwrap_err();
+        assert!(error.downcast_ref::<BodyTooLarge>().is_some());
+
+        let invalid = "POST / HTTP/1.1\r\nContent-Length: -1\r\n\r\n";
+        assert!(read_request(invalid.as_bytes()).is_err());
+        // Body shorter than its Content-Length
+        assert!(read_request("POST / HTTP/1.1\r\nContent-Length: 5\r\n\r\nab".as_bytes()).is_err());
+        assert!(read_request("".as_bytes()).is_err());
+    }
+}
@@ -0,0 +1,211 @@
+//! WebAssembly plugin host
+//!
+//! Loads `*.wasm` components from the extension directories (`.dev/extensions/` and
+//! the user-global one) and exposes them as devkit extensions. Plugins implement the
+//! `devkit:plugin` world in `wit/plugin.wit`: `name`, `menu-items` and `execute`.
+//!
+//! Plugins run sandboxed: the only import is `print`, so they can't touch the
+//! filesystem, network or environment. Project state arrives as the same JSON
+//! context document external extensions get with `protocol = "json"`, including
+//! the plugin's `[ext.<name>]` settings as `ext_config`. Each call
+//! runs in a fresh store with a memory cap and a fuel budget.
+
+use anyhow::{anyhow, Context as _, Result};
+use devkit_core::extension_loader::extension_dirs;
+use devkit_core::external_extension::context_json;
+use devkit_core::{AppContext, Extension, MenuItem};
+use std::path::{Path, PathBuf};
+use wasmtime::component::{Component, Linker};
+use wasmtime::{Config, Engine, Store, StoreLimits, StoreLimitsBuilder};
+
+mod bindings {
+    wasmtime::component::bindgen!({
+        path: "wit",
+        world: "plugin",
+    });
+}
+
+pub use bindings::MenuItem as PluginMenuItem;
+use bindings::{Plugin, PluginImports};
+
+/// Maximum linear memory per plugin call
+const MAX_MEMORY_BYTES: usize = 256 * 1024 * 1024;
+
+/// Instructions (roughly) a plugin call may execute before it's stopped
+const FUEL_PER_CALL: u64 = 10_000_000_000;
+
+struct HostState {
+    limits: StoreLimits,
+}
+
+impl PluginImports for HostState {
+    fn print(&mut self, line: String) {
+        println!("{}", line);
+    }
+}
+
+/// A loaded WebAssembly plugin
+#[derive(Clone)]
+pub struct WasmPlugin {
+    name: String,
+    path: PathBuf,
+    engine: Engine,
+    component: Component,
+}
+
+impl WasmPlugin {
+    /// Compile a plugin component and read its name
+    pub fn load(path: &Path) -> Result<Self> {
+        let mut config = Config::new();
+        config.wasm_component_model(true).consume_fuel(true);
+        let engine = Engine::new(&config)?;
+        let component = Component::from_file(&engine, path)
+            .with_context(|| format!("Failed to compile {}", path.display()))?;
+
+        let mut plugin = Self {
+            name: String::new(),
+            path: path.to_path_buf(),
+            engine,
+            component,
+        };
+        plugin.name = plugin.call(|bindings, store| bindings.call_name(store))?;
+        Ok(plugin)
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Path of the `.wasm` file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Menu items the plugin offers for this project
+    pub fn items(&self, ctx: &AppContext) -> Result<Vec<PluginMenuItem>> {
+        let context = context_json(ctx, &self.name, None, &[]).to_string();
+        self.call(|bindings, store| bindings.call_menu_items(store, &context))
+    }
+
+    /// Run one of the plugin's menu items; `args` are passed in the JSON context
+    pub fn execute(&self, ctx: &AppContext, id: &str, args: &[String]) -> Result<()> {
+        let mut context = context_json(ctx, &self.name, None, args);
+        context["action"] = id.into();
+        let context = context.to_string();
+
+        self.call(|bindings, store| bindings.call_execute(store, &context, id))?
+            .map_err(|e| anyhow!("{} {}: {}", self.name, id, e))
+    }
+
+    /// Instantiate the component in a fresh store and call into it
+    fn call<T>(
+        &self,
+        f: impl FnOnce(&Plugin, &mut Store<HostState>) -> wasmtime::Result<T>,
+    ) -> Result<T> {
+        let mut store = Store::new(
+            &self.engine,
+            HostState {
+                limits: StoreLimitsBuilder::new()
+                    .memory_size(MAX_MEMORY_BYTES)
+                    .build(),
+            },
+        );
+        store.limiter(|state| &mut state.limits);
+        store.set_fuel(FUEL_PER_CALL)?;
+
+        let mut linker = Linker::new(&self.engine);
+        Plugin::add_to_linker(&mut linker, |state: &mut HostState| state)?;
+        let bindings = Plugin::instantiate(&mut store, &self.component, &linker)?;
+
+        f(&bindings, &mut store).with_context(|| format!("Plugin {} trapped", self.path.display()))
+    }
+}
+
+/// Load every `*.wasm` plugin in the extension directories
+///
+/// Plugins that fail to compile or instantiate are logged and skipped. A project
+/// plugin replaces a user-global one with the same name.
+pub fn load_wasm_plugins(repo_root: &Path) -> Vec<WasmPlugin> {
+    let mut plugins: Vec<WasmPlugin> = Vec::new();
+
+    for dir in extension_dirs(repo_root) {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        let mut paths: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "wasm"))
+            .collect();
+        paths.sort();
+
+        for path in paths {
+            match WasmPlugin::load(&path) {
+                Ok(plugin) => {
+                    tracing::info!(
+                        "✓ Loaded wasm plugin: {} from {}",
+                        plugin.name(),
+                        path.display()
+                    );
+                    plugins.retain(|p| p.name() != plugin.name());
+                    plugins.push(plugin);
+                }
+                Err(e) => {
+                    tracing::warn!("✗ Failed to load wasm plugin {}: {:#}", path.display(), e);
+                }
+            }
+        }
+    }
+
+    plugins.sort_by(|a, b| a.name.cmp(&b.name));
+    plugins
+}
+
+/// Registers a wasm plugin's menu items
+pub struct WasmExtension(pub WasmPlugin);
+
+impl Extension for WasmExtension {
+    fn name(&self) -> &str {
+        self.0.name()
+    }
+
+    fn is_available(&self, _ctx: &AppContext) -> bool {
+        true
+    }
+
+    fn menu_items(&self, ctx: &AppContext) -> Vec<MenuItem> {
+        let items = match self.0.items(ctx) {
+            Ok(items) => items,
+            Err(e) => {
+                tracing::warn!(
+                    "✗ Wasm plugin {}: menu-items failed: {:#}",
+                    self.0.name(),
+                    e
+                );
+                return Vec::new();
+            }
+        };
+
+        items
+            .into_iter()
+            .map(|item| {
+                let plugin = self.0.clone();
+                MenuItem {
+                    label: item.label,
+                    group: item.group,
+                    handler: Box::new(move |ctx| {
+                        plugin.execute(ctx, &item.id, &[]).map_err(Into::into)
+                    }),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Load all wasm plugins as extensions
+pub fn wasm_extensions(repo_root: &Path) -> Vec<Box<dyn Extension>> {
+    load_wasm_plugins(repo_root)
+        .into_iter()
+        .map(|plugin| Box::new(WasmExtension(plugin)) as Box<dyn Extension>)
+        .collect()
+}
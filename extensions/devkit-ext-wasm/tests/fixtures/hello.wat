@@ -0,0 +1,80 @@
+;; Minimal devkit:plugin component, hand-written so tests don't need a wasm toolchain.
+;; Offers one menu item ("greet") that prints a line; any other id returns an error.
+(component
+  (import "print" (func $print (param "line" string)))
+
+  ;; Memory lives in its own instance so `print` can be lowered before the main module
+  (core module $mem
+    (memory (export "memory") 1))
+  (core instance $mem (instantiate $mem))
+  (alias core export $mem "memory" (core memory $memory))
+
+  (core func $print_lowered (canon lower (func $print) (memory $memory)))
+  (core instance $host (export "print" (func $print_lowered)))
+
+  (core module $main
+    (import "host" "print" (func $print (param i32 i32)))
+    (import "env" "memory" (memory 1))
+    (global $heap (mut i32) (i32.const 4096))
+
+    ;; Strings
+    (data (i32.const 16) "hello")                 ;; name
+    (data (i32.const 32) "greet")                 ;; item id
+    (data (i32.const 48) "Say hello")             ;; item label
+    (data (i32.const 64) "Plugins")               ;; item group
+    (data (i32.const 80) "hello from wasm")       ;; printed line
+    (data (i32.const 112) "unknown item")         ;; error message
+
+    ;; name() -> (ptr, len)
+    (data (i32.const 200) "\10\00\00\00\05\00\00\00")
+    ;; menu-items() -> list (ptr 300, len 1)
+    (data (i32.const 208) "\2c\01\00\00\01\00\00\00")
+    ;; menu-item record: id, label, group = some("Plugins")
+    (data (i32.const 300)
+      "\20\00\00\00\05\00\00\00"
+      "\30\00\00\00\09\00\00\00"
+      "\01\00\00\00\40\00\00\00\07\00\00\00")
+    ;; execute() -> ok
+    (data (i32.const 400) "\00\00\00\00\00\00\00\00\00\00\00\00")
+    ;; execute() -> err("unknown item")
+    (data (i32.const 416) "\01\00\00\00\70\00\00\00\0c\00\00\00")
+
+    (func (export "realloc") (param i32 i32 i32 i32) (result i32)
+      (local $ptr i32)
+      (local.set $ptr (global.get $heap))
+      (global.set $heap (i32.add (global.get $heap) (local.get 3)))
+      (local.get $ptr))
+
+    (func (export "name") (result i32)
+      (i32.const 200))
+
+    (func (export "menu-items") (param i32 i32) (result i32)
+      (i32.const 208))
+
+    ;; execute(context, id): "greet" is the only id that's exactly 5 bytes long
+    (func (export "execute") (param i32 i32 i32 i32) (result i32)
+      (if (result i32) (i32.eq (local.get 3) (i32.const 5))
+        (then
+          (call $print (i32.const 80) (i32.const 15))
+          (i32.const 400))
+        (else
+          (i32.const 416)))))
+
+  (core instance $main (instantiate $main
+    (with "host" (instance $host))
+    (with "env" (instance $mem))))
+
+  (type $menu-item (record
+    (field "id" string)
+    (field "label" string)
+    (field "group" (option string))))
+  (export $menu-item-export "menu-item" (type $menu-item))
+
+  (func (export "name") (result string)
+    (canon lift (core func $main "name") (memory $memory)))
+  (func (export "menu-items") (param "context" string) (result (list $menu-item-export))
+    (canon lift (core func $main "menu-items") (memory $memory)
+      (realloc (core func $main "realloc"))))
+  (func (export "execute") (param "context" string) (param "id" string) (result (result (error string)))
+    (canon lift (core func $main "execute") (memory $memory)
+      (realloc (core func $main "realloc")))))
@@ -0,0 +1,39 @@
+use devkit_core::{AppContext, Config};
+use devkit_ext_wasm::WasmPlugin;
+use std::path::Path;
+
+fn fixture() -> WasmPlugin {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/hello.wat");
+    WasmPlugin::load(&path).unwrap()
+}
+
+fn context() -> AppContext {
+    AppContext {
+        repo: std::env::temp_dir(),
+        quiet: true,
+        config: Config::default(),
+        features: Default::default(),
+    }
+}
+
+#[test]
+fn test_load_plugin_and_list_items() {
+    let plugin = fixture();
+    assert_eq!(plugin.name(), "hello");
+
+    let items = plugin.items(&context()).unwrap();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].id, "greet");
+    assert_eq!(items[0].label, "Say hello");
+    assert_eq!(items[0].group.as_deref(), Some("Plugins"));
+}
+
+#[test]
+fn test_execute_plugin_item() {
+    let plugin = fixture();
+    let ctx = context();
+
+    assert!(plugin.execute(&ctx, "greet", &[]).is_ok());
+    let err = plugin.execute(&ctx, "missing", &[]).unwrap_err();
+    assert!(err.to_string().contains("unknown item"));
+}
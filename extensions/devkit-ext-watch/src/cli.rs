@@ -0,0 +1,24 @@
+//! `devkit watch` subcommand
+
+use anyhow::Result;
+use clap::{Args, Command, FromArgMatches};
+use devkit_core::AppContext;
+
+use crate::watch_multiple;
+
+#[derive(Args)]
+pub struct WatchArgs {
+    /// Watchers from [watch.<name>] to run (all when left out)
+    names: Vec<String>,
+}
+
+pub fn command() -> Command {
+    WatchArgs::augment_args(
+        Command::new("watch").about("Run the [watch.<name>] watchers side by side until Ctrl-C"),
+    )
+}
+
+pub fn handle(ctx: &AppContext, matches: &clap::ArgMatches) -> Result<()> {
+    let args = WatchArgs::from_arg_matches(matches)?;
+    watch_multiple(ctx, &args.names)
+}
@@ -2,9 +2,16 @@
 //!
 //! Provides sophisticated file watching with browser reload, notifications, and parallel watchers
 
-use anyhow::Result;
+mod cli;
+pub mod reload;
+pub mod watcher;
+
+use anyhow::{anyhow, Result};
+use devkit_core::config::WatcherEntry;
 use devkit_core::{AppContext, Extension, MenuItem};
 
+pub use watcher::Watcher;
+
 pub struct WatchExtension;
 
 impl Extension for WatchExtension {
@@ -18,14 +25,27 @@ impl Extension for WatchExtension {
 
     fn menu_items(&self, _ctx: &AppContext) -> Vec<MenuItem> {
         vec![MenuItem {
-            label: "👁  Start file watcher".to_string(),
-                group: None,
-            handler: Box::new(|ctx| start_watcher(ctx).map_err(Into::into)),
+            label: "👁  Start file watchers".to_string(),
+            group: None,
+            handler: Box::new(|ctx| watch_multiple(ctx, &[]).map_err(Into::into)),
         }]
     }
+
+    fn subcommands(&self) -> Vec<clap::Command> {
+        vec![cli::command()]
+    }
+
+    fn handle_cli(
+        &self,
+        ctx: &AppContext,
+        name: &str,
+        matches: &clap::ArgMatches,
+    ) -> Option<devkit_core::Result<()>> {
+        (name == "watch").then(|| cli::handle(ctx, matches).map_err(Into::into))
+    }
 }
 
-/// Start watching files
+/// Explain the `[watch.<name>]` config when none is set up
 pub fn start_watcher(ctx: &AppContext) -> Result<()> {
     ctx.print_header("File Watcher");
     ctx.print_info("Advanced file watching with browser reload");
@@ -37,7 +57,7 @@ pub fn start_watcher(ctx: &AppContext) -> Result<()> {
     println!("  • Conditional rebuilds");
     println!("  • Parallel watchers");
     println!();
-    ctx.print_info("Configuration in dev.toml:");
+    ctx.print_info("Configuration in .dev/config.toml:");
     println!();
     println!("  [watch.backend]");
     println!("  patterns = [\"src/**/*.rs\"]");
@@ -52,14 +72,46 @@ pub fn start_watcher(ctx: &AppContext) -> Result<()> {
     Ok(())
 }
 
-/// Watch multiple file patterns
-pub fn watch_multiple(_ctx: &AppContext, _patterns: Vec<String>) -> Result<()> {
-    // TODO: Implement multi-pattern watching
-    Ok(())
+/// Run the named watchers from `[watch.<name>]`, or all of them, until Ctrl-C
+pub fn watch_multiple(ctx: &AppContext, names: &[String]) -> Result<()> {
+    let config = &ctx.config.global.watch;
+    if config.entries.is_empty() {
+        return start_watcher(ctx);
+    }
+
+    let names: Vec<&str> = if names.is_empty() {
+        config.names()
+    } else {
+        names.iter().map(String::as_str).collect()
+    };
+    let watchers = names
+        .iter()
+        .enumerate()
+        .map(|(index, name)| {
+            let entry = config.get(name).ok_or_else(|| {
+                anyhow!(
+                    "Watcher '{}' not found (available: {})",
+                    name,
+                    config.names().join(", ")
+                )
+            })?;
+            Watcher::new(name, entry.clone(), index)
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    ctx.print_header("File Watchers");
+    watcher::run(ctx, watchers)
 }
 
-/// Watch with browser reload
-pub fn watch_with_reload(_ctx: &AppContext, _pattern: &str, _command: &str) -> Result<()> {
-    // TODO: Implement browser reload integration
-    Ok(())
+/// Run `command` when files matching `pattern` change, reloading the browser after
+/// each successful run
+pub fn watch_with_reload(ctx: &AppContext, pattern: &str, command: &str) -> Result<()> {
+    let entry = WatcherEntry {
+        patterns: vec![pattern.to_string()],
+        command: command.to_string(),
+        debounce: 300,
+        notify: false,
+        reload_browser: true,
+    };
+    watcher::run(ctx, vec![Watcher::new("watch", entry, 0)?])
 }
@@ -0,0 +1,137 @@
+//! Browser reload over Server-Sent Events
+//!
+//! Pages include `<script src="http://localhost:35729/reload.js"></script>`, which
+//! listens on `/events` and reloads the page when a `reload_browser` watcher's
+//! command succeeds.
+
+use anyhow::{Context, Result};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// The LiveReload port, so existing habits and firewall rules carry over
+pub const PORT: u16 = 35729;
+
+/// Connected browser tabs, notified on [`ReloadServer::reload`]
+#[derive(Clone)]
+pub struct ReloadServer {
+    port: u16,
+    clients: Arc<Mutex<Vec<TcpStream>>>,
+}
+
+impl ReloadServer {
+    /// Serve `/reload.js` and `/events` on localhost in the background
+    pub fn start(port: u16) -> Result<Self> {
+        let listener = TcpListener::bind(("127.0.0.1", port))
+            .with_context(|| format!("Failed to listen on port {} for browser reload", port))?;
+        let server = Self {
+            port: listener.local_addr()?.port(),
+            clients: Arc::default(),
+        };
+
+        let clients = Arc::clone(&server.clients);
+        let port = server.port;
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                if let Some(client) = respond(stream, port) {
+                    clients.lock().unwrap().push(client);
+                }
+            }
+        });
+        Ok(server)
+    }
+
+    /// `<script>` tag for pages to include
+    pub fn script_tag(&self) -> String {
+        format!(
+            "<script src=\"http://localhost:{}/reload.js\"></script>",
+            self.port
+        )
+    }
+
+    /// Reload every connected tab, returning how many there were
+    pub fn reload(&self) -> usize {
+        let mut clients = self.clients.lock().unwrap();
+        clients.retain_mut(|client| {
+            client
+                .write_all(b"data: reload\n\n")
+                .and_then(|()| client.flush())
+                .is_ok()
+        });
+        clients.len()
+    }
+}
+
+/// Answer a request; an `/events` stream is returned to be kept open
+fn respond(mut stream: TcpStream, port: u16) -> Option<TcpStream> {
+    stream.set_read_timeout(Some(Duration::from_secs(2))).ok()?;
+    let mut request = String::new();
+    BufReader::new(&stream).read_line(&mut request).ok()?;
+    let path = request.split_whitespace().nth(1).unwrap_or_default();
+
+    let cors = "Access-Control-Allow-Origin: *\r\n";
+    match path {
+        "/events" => {
+            let headers = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\n{}\r\n",
+                cors
+            );
+            stream.write_all(headers.as_bytes()).ok()?;
+            Some(stream)
+        }
+        "/reload.js" => {
+            let script = format!(
+                "new EventSource('http://localhost:{}/events').onmessage = () => location.reload();\n",
+                port
+            );
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/javascript\r\nContent-Length: {}\r\n{}Connection: close\r\n\r\n{}",
+                script.len(),
+                cors,
+                script
+            );
+            let _ = stream.write_all(response.as_bytes());
+            None
+        }
+        _ => {
+            let _ = stream.write_all(
+                b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+            );
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    #[test]
+    fn test_reload_server() {
+        let server = ReloadServer::start(0).unwrap();
+        let get = |path: &str| {
+            let mut stream = TcpStream::connect(("127.0.0.1", server.port)).unwrap();
+            write!(stream, "GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path).unwrap();
+            stream
+        };
+
+        let mut script = String::new();
+        get("/reload.js").read_to_string(&mut script).unwrap();
+        assert!(script.contains(&format!("localhost:{}/events", server.port)));
+
+        let events = get("/events");
+        let mut events = BufReader::new(events);
+        let mut line = String::new();
+        while line != "\r\n" {
+            line.clear();
+            events.read_line(&mut line).unwrap();
+        }
+        assert_eq!(server.reload(), 1);
+        line.clear();
+        events.read_line(&mut line).unwrap();
+        assert_eq!(line, "data: reload\n");
+    }
+}
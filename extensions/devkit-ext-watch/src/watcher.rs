@@ -0,0 +1,302 @@
+//! Running `[watch.<name>]` watchers side by side
+//!
+//! One recursive notify watcher on the repo root feeds every configured watcher.
+//! Each changed path (relative to the root) goes to the watchers whose patterns
+//! match it; each watcher runs on its own thread and reruns its command once
+//! changes have been quiet for its debounce period.
+
+use anyhow::{anyhow, Context, Result};
+use console::{Color, Style};
+use devkit_core::config::WatcherEntry;
+use devkit_core::utils::desktop_notify;
+use devkit_core::AppContext;
+use devkit_tasks::CmdBuilder;
+use glob::{MatchOptions, Pattern};
+use notify::{Config, EventKind, RecommendedWatcher, RecursiveMode, Watcher as _};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::Once;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::reload::{self, ReloadServer};
+
+/// Set by Ctrl-C; watchers finish their current command and stop
+static STOP: AtomicBool = AtomicBool::new(false);
+static CTRL_C: Once = Once::new();
+
+/// A named watcher with its patterns compiled
+pub struct Watcher {
+    pub name: String,
+    pub entry: WatcherEntry,
+    patterns: Vec<Pattern>,
+    prefix: String,
+}
+
+impl Watcher {
+    /// `index` picks the prefix color
+    pub fn new(name: &str, entry: WatcherEntry, index: usize) -> Result<Self> {
+        let patterns = entry
+            .patterns
+            .iter()
+            .map(|pattern| {
+                Pattern::new(pattern)
+                    .with_context(|| format!("Invalid pattern '{}' in [watch.{}]", pattern, name))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            name: name.to_string(),
+            prefix: prefix_style(index)
+                .apply_to(format!("[{}]", name))
+                .to_string(),
+            entry,
+            patterns,
+        })
+    }
+
+    /// Whether a path relative to the repo root is one of this watcher's
+    fn matches(&self, path: &Path) -> bool {
+        let options = MatchOptions {
+            require_literal_separator: true,
+            ..Default::default()
+        };
+        self.patterns
+            .iter()
+            .any(|pattern| pattern.matches_path_with(path, options))
+    }
+}
+
+/// Run the watchers until Ctrl-C
+pub fn run(ctx: &AppContext, watchers: Vec<Watcher>) -> Result<()> {
+    stop_on_ctrl_c()?;
+
+    let reload = if watchers.iter().any(|w| w.entry.reload_browser) {
+        let server = ReloadServer::start(reload::PORT)?;
+        ctx.print_info(&format!(
+            "Browser reload: add {} to your page",
+            server.script_tag()
+        ));
+        Some(server)
+    } else {
+        None
+    };
+
+    let (tx, changes) = mpsc::channel();
+    let mut fs_watcher = RecommendedWatcher::new(
+        move |res: notify::Result<notify::Event>| {
+            if let Ok(event) = res {
+                if matches!(
+                    event.kind,
+                    EventKind::Modify(_) | EventKind::Create(_) | EventKind::Remove(_)
+                ) {
+                    for path in event.paths {
+                        let _ = tx.send(path);
+                    }
+                }
+            }
+        },
+        Config::default(),
+    )?;
+    fs_watcher
+        .watch(&ctx.repo, RecursiveMode::Recursive)
+        .with_context(|| format!("Failed to watch {}", ctx.repo.display()))?;
+
+    // Events may carry the canonical path (e.g. /private/var on macOS)
+    let roots = [
+        ctx.repo.clone(),
+        ctx.repo.canonicalize().unwrap_or_else(|_| ctx.repo.clone()),
+    ];
+
+    println!("👀 Watching for changes... (press Ctrl+C to stop)");
+    for watcher in &watchers {
+        println!(
+            "  {} {} → {}",
+            watcher.prefix,
+            watcher.entry.patterns.join(", "),
+            watcher.entry.command
+        );
+    }
+    println!();
+
+    thread::scope(|scope| {
+        let mut senders: Vec<Sender<PathBuf>> = Vec::new();
+        for watcher in &watchers {
+            let (tx, rx) = mpsc::channel();
+            senders.push(tx);
+            let reload = reload.as_ref();
+            scope.spawn(move || run_watcher(ctx, watcher, rx, reload));
+        }
+
+        while !STOP.load(Ordering::SeqCst) {
+            let path = match changes.recv_timeout(Duration::from_millis(100)) {
+                Ok(path) => path,
+                Err(RecvTimeoutError::Timeout) => continue,
+                Err(RecvTimeoutError::Disconnected) => break,
+            };
+            let Some(relative) = roots.iter().find_map(|root| path.strip_prefix(root).ok()) else {
+                continue;
+            };
+            for (watcher, tx) in watchers.iter().zip(&senders) {
+                if watcher.matches(relative) {
+                    let _ = tx.send(relative.to_path_buf());
+                }
+            }
+        }
+        // Dropping the senders lets each watcher thread finish
+        drop(senders);
+    });
+
+    println!();
+    ctx.print_info("Stopped watching");
+    Ok(())
+}
+
+/// Run the watcher's command once, then after each quiet spell of changes
+fn run_watcher(
+    ctx: &AppContext,
+    watcher: &Watcher,
+    changes: Receiver<PathBuf>,
+    reload: Option<&ReloadServer>,
+) {
+    let debounce = Duration::from_millis(watcher.entry.debounce);
+    run_command(ctx, watcher, reload);
+
+    while let Ok(first) = changes.recv() {
+        let mut changed = vec![first];
+        loop {
+            match changes.recv_timeout(debounce) {
+                Ok(path) if !changed.contains(&path) => changed.push(path),
+                Ok(_) => {}
+                Err(RecvTimeoutError::Timeout) => break,
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+        }
+        if STOP.load(Ordering::SeqCst) {
+            return;
+        }
+
+        let more = match changed.len() {
+            1 => String::new(),
+            n => format!(" (+{} more)", n - 1),
+        };
+        println!(
+            "{} {} changed{}",
+            watcher.prefix,
+            changed[0].display(),
+            more
+        );
+        run_command(ctx, watcher, reload);
+    }
+}
+
+/// Run the command with prefixed output, then report, notify and reload
+fn run_command(ctx: &AppContext, watcher: &Watcher, reload: Option<&ReloadServer>) {
+    let entry = &watcher.entry;
+    println!("{} $ {}", watcher.prefix, entry.command);
+
+    let started = Instant::now();
+    let result = shell(&entry.command)
+        .cwd(&ctx.repo)
+        .run_streaming(|line| println!("{} {}", watcher.prefix, line.text))
+        .and_then(|output| match output.code {
+            0 => Ok(()),
+            code => Err(anyhow!("exited with code {}", code)),
+        });
+    let elapsed = started.elapsed().as_secs_f64();
+
+    let summary = match &result {
+        Ok(()) => {
+            println!(
+                "{} {}",
+                watcher.prefix,
+                Style::new()
+                    .green()
+                    .apply_to(format!("✓ done in {:.1}s", elapsed))
+            );
+            format!("{} succeeded", entry.command)
+        }
+        Err(e) => {
+            println!(
+                "{} {}",
+                watcher.prefix,
+                Style::new().red().apply_to(format!("✗ {}", e))
+            );
+            format!("{} failed: {}", entry.command, e)
+        }
+    };
+
+    if entry.notify {
+        let title = format!("{}: {}", ctx.config.global.project.name, watcher.name);
+        if let Err(e) = desktop_notify(&title, &summary) {
+            ctx.print_warning(&format!("Failed to send notification: {}", e));
+        }
+    }
+    if let (Ok(()), Some(server), true) = (&result, reload, entry.reload_browser) {
+        let tabs = server.reload();
+        if tabs > 0 {
+            println!("{} ↻ reloaded {} browser tab(s)", watcher.prefix, tabs);
+        }
+    }
+}
+
+fn shell(command: &str) -> CmdBuilder {
+    if cfg!(windows) {
+        CmdBuilder::new("cmd").args(["/C", command])
+    } else {
+        CmdBuilder::new("sh").args(["-c", command])
+    }
+}
+
+/// Reset the stop flag, installing the Ctrl-C handler on first use
+fn stop_on_ctrl_c() -> Result<()> {
+    STOP.store(false, Ordering::SeqCst);
+    let mut result = Ok(());
+    CTRL_C.call_once(|| {
+        result = ctrlc::set_handler(|| STOP.store(true, Ordering::SeqCst))
+            .context("Failed to set the Ctrl-C handler");
+    });
+    result
+}
+
+/// A color per watcher, so interleaved output can be told apart
+fn prefix_style(index: usize) -> Style {
+    const COLORS: [Color; 6] = [
+        Color::Cyan,
+        Color::Magenta,
+        Color::Yellow,
+        Color::Blue,
+        Color::Green,
+        Color::Red,
+    ];
+    Style::new().fg(COLORS[index % COLORS.len()]).bold()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches() {
+        let entry = WatcherEntry {
+            patterns: vec!["src/**/*.rs".to_string(), "*.toml".to_string()],
+            command: "cargo build".to_string(),
+            debounce: 300,
+            notify: false,
+            reload_browser: false,
+        };
+        let watcher = Watcher::new("backend", entry.clone(), 0).unwrap();
+        assert!(watcher.matches(Path::new("src/main.rs")));
+        assert!(watcher.matches(Path::new("src/cli/args.rs")));
+        assert!(watcher.matches(Path::new("Cargo.toml")));
+        // `*` doesn't cross directories
+        assert!(!watcher.matches(Path::new("crates/core/Cargo.toml")));
+        assert!(!watcher.matches(Path::new("ui/src/main.rs")));
+
+        let bad = WatcherEntry {
+            patterns: vec!["src/[".to_string()],
+            ..entry
+        };
+        assert!(Watcher::new("bad", bad, 0).is_err());
+    }
+}
@@ -13,6 +13,8 @@ DEVKIT_VERSION="${DEVKIT_VERSION:-latest}"
 REPO_URL="https://github.com/crcn/devkit"
 RAW_URL="https://raw.githubusercontent.com/crcn/devkit/main"
 INSTALL_DIR="${INSTALL_DIR:-$HOME/.local/bin}"
+# minisign public key; when set, SHA256SUMS must carry a valid signature (SHA256SUMS.minisig)
+DEVKIT_MINISIGN_PUBKEY="${DEVKIT_MINISIGN_PUBKEY:-}"
 
 # Colors
 RED='\033[0;31m'
@@ -101,17 +103,81 @@ detect_platform() {
     esac
 }
 
-get_download_url() {
+get_asset_url() {
     local version="$1"
-    local platform="$2"
+    local asset="$2"
 
     if [ "$version" = "latest" ]; then
-        echo "https://github.com/crcn/devkit/releases/latest/download/devkit-${platform}"
+        echo "https://github.com/crcn/devkit/releases/latest/download/${asset}"
+    else
+        echo "https://github.com/crcn/devkit/releases/download/${version}/${asset}"
+    fi
+}
+
+get_download_url() {
+    get_asset_url "$1" "devkit-$2"
+}
+
+sha256_of() {
+    if check_command sha256sum; then
+        sha256sum "$1" | cut -d' ' -f1
+    elif check_command shasum; then
+        shasum -a 256 "$1" | cut -d' ' -f1
     else
-        echo "https://github.com/crcn/devkit/releases/download/${version}/devkit-${platform}"
+        return 1
     fi
 }
 
+# Check a downloaded asset against the release's SHA256SUMS (and its signature when
+# DEVKIT_MINISIGN_PUBKEY is set). Fails if the download can't be verified.
+verify_download() {
+    local file="$1"
+    local asset="$2"
+    local dir
+    dir=$(dirname "$file")
+
+    if ! curl -fsSL "$(get_asset_url "$DEVKIT_VERSION" SHA256SUMS)" -o "$dir/SHA256SUMS"; then
+        log_error "Could not download SHA256SUMS for this release"
+        return 1
+    fi
+
+    if [ -n "$DEVKIT_MINISIGN_PUBKEY" ]; then
+        if ! check_command minisign; then
+            log_error "DEVKIT_MINISIGN_PUBKEY is set but minisign is not installed"
+            return 1
+        fi
+        if ! curl -fsSL "$(get_asset_url "$DEVKIT_VERSION" SHA256SUMS.minisig)" -o "$dir/SHA256SUMS.minisig"; then
+            log_error "Could not download SHA256SUMS.minisig for this release"
+            return 1
+        fi
+        if ! minisign -Vq -P "$DEVKIT_MINISIGN_PUBKEY" -m "$dir/SHA256SUMS" -x "$dir/SHA256SUMS.minisig"; then
+            log_error "SHA256SUMS signature is invalid"
+            return 1
+        fi
+        log_success "Verified SHA256SUMS signature"
+    fi
+
+    local expected
+    expected=$(awk -v asset="$asset" '$2 == asset || $2 == "*" asset { print $1 }' "$dir/SHA256SUMS")
+    if [ -z "$expected" ]; then
+        log_error "$asset is not listed in SHA256SUMS"
+        return 1
+    fi
+
+    local actual
+    if ! actual=$(sha256_of "$file"); then
+        log_error "sha256sum or shasum is required to verify the download"
+        return 1
+    fi
+    if [ "$actual" != "$expected" ]; then
+        log_error "Checksum mismatch for $asset"
+        log_error "  expected $expected"
+        log_error "  got      $actual"
+        return 1
+    fi
+    log_success "Verified checksum of $asset"
+}
+
 # =============================================================================
 # Dependency Checks
 # =============================================================================
@@ -229,17 +295,26 @@ install_devkit_binary() {
     # Create install directory
     mkdir -p "$INSTALL_DIR"
 
-    # Download binary
+    # Download and verify before replacing an existing binary
     local binary_path="$INSTALL_DIR/devkit"
-    if curl -fsSL "$url" -o "$binary_path"; then
-        chmod +x "$binary_path"
-        log_success "Installed devkit binary to $binary_path"
-    else
+    local download_dir
+    download_dir=$(mktemp -d)
+    trap 'rm -rf "$download_dir"' EXIT
+
+    if ! curl -fsSL "$url" -o "$download_dir/devkit"; then
         log_error "Failed to download devkit binary"
         log_error "You can build from source instead:"
         log_error "  git clone $REPO_URL && cd devkit && cargo build --release -p devkit-cli"
         exit 1
     fi
+    if ! verify_download "$download_dir/devkit" "devkit-${platform}"; then
+        log_error "Refusing to install an unverified binary"
+        exit 1
+    fi
+
+    chmod +x "$download_dir/devkit"
+    mv "$download_dir/devkit" "$binary_path"
+    log_success "Installed devkit binary to $binary_path"
 
     # Check if install directory is in PATH
     if [[ ":$PATH:" != *":$INSTALL_DIR:"* ]]; then
@@ -175,9 +175,11 @@ fn cmd_run(
 
     let opts = CmdOptions {
         parallel,
+        jobs: None,
         variant: None,
         packages,
         capture: false,
+        force: false,
     };
 
     let results = run_cmd(ctx, &cmd_name, &opts)?;